@@ -0,0 +1,305 @@
+//! Live Activity Module
+//!
+//! Reads current sessions, long-running transactions, and lock waits from a
+//! target database so operators can judge whether it is safe to migrate.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use tracing::debug;
+
+/// A single backend session from pg_stat_activity
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub pid: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_addr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_event_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_event: Option<String>,
+    /// Query text; literals are masked unless the caller is an admin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_started_at: Option<DateTime<Utc>>,
+    /// Seconds since the current transaction began
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_secs: Option<f64>,
+}
+
+/// A session waiting on a lock held by another session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockingLock {
+    pub blocked_pid: i32,
+    pub blocking_pids: Vec<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_secs: Option<f64>,
+}
+
+/// Activity overview for a connection
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityReport {
+    pub captured_at: DateTime<Utc>,
+    pub sessions: Vec<Session>,
+    pub long_running_transactions: Vec<Session>,
+    pub blocking_locks: Vec<BlockingLock>,
+    /// Threshold used to classify long-running transactions
+    pub long_running_threshold_secs: u64,
+    /// Whether query literals were masked
+    pub sanitized: bool,
+}
+
+/// Reads live activity from PostgreSQL
+pub struct ActivityInspector;
+
+impl ActivityInspector {
+    /// Capture sessions, long transactions, and lock waits for the current database
+    pub async fn inspect(
+        pool: &Pool,
+        long_running_threshold_secs: u64,
+        include_literals: bool,
+    ) -> Result<ActivityReport, AppError> {
+        let client = pool.get().await?;
+
+        let sessions = Self::get_sessions(&client, include_literals).await?;
+        let blocking_locks = Self::get_blocking_locks(&client).await?;
+
+        let long_running_transactions = sessions
+            .iter()
+            .filter(|s| {
+                s.transaction_secs
+                    .is_some_and(|secs| secs >= long_running_threshold_secs as f64)
+            })
+            .cloned()
+            .collect();
+
+        debug!(
+            "Captured activity: {} sessions, {} blocked",
+            sessions.len(),
+            blocking_locks.len()
+        );
+
+        Ok(ActivityReport {
+            captured_at: Utc::now(),
+            sessions,
+            long_running_transactions,
+            blocking_locks,
+            long_running_threshold_secs,
+            sanitized: !include_literals,
+        })
+    }
+
+    async fn get_sessions(
+        client: &deadpool_postgres::Client,
+        include_literals: bool,
+    ) -> Result<Vec<Session>, AppError> {
+        let query = r#"
+            SELECT
+                a.pid,
+                a.usename::text as username,
+                a.application_name,
+                a.client_addr::text as client_addr,
+                a.state,
+                a.wait_event_type,
+                a.wait_event,
+                a.query,
+                a.xact_start,
+                a.query_start,
+                EXTRACT(EPOCH FROM (now() - a.xact_start))::float8 as transaction_secs
+            FROM pg_stat_activity a
+            WHERE a.datname = current_database()
+              AND a.pid <> pg_backend_pid()
+              AND a.backend_type = 'client backend'
+            ORDER BY a.xact_start NULLS LAST
+        "#;
+
+        let rows = client.query(query, &[]).await?;
+
+        let sessions = rows.iter().map(|row| {
+            let query: Option<String> = row.get("query");
+            Session {
+                pid: row.get("pid"),
+                username: row.get("username"),
+                application_name: row.get("application_name"),
+                client_addr: row.get("client_addr"),
+                state: row.get("state"),
+                wait_event_type: row.get("wait_event_type"),
+                wait_event: row.get("wait_event"),
+                query: query.map(|q| if include_literals { q } else { sanitize_query(&q) }),
+                transaction_started_at: row.get("xact_start"),
+                query_started_at: row.get("query_start"),
+                transaction_secs: row.get("transaction_secs"),
+            }
+        }).collect();
+
+        Ok(sessions)
+    }
+
+    async fn get_blocking_locks(client: &deadpool_postgres::Client) -> Result<Vec<BlockingLock>, AppError> {
+        let query = r#"
+            SELECT
+                a.pid as blocked_pid,
+                pg_blocking_pids(a.pid) as blocking_pids,
+                l.locktype as lock_type,
+                l.relation::regclass::text as relation,
+                l.mode,
+                EXTRACT(EPOCH FROM (now() - a.query_start))::float8 as wait_secs
+            FROM pg_stat_activity a
+            JOIN pg_locks l ON l.pid = a.pid AND NOT l.granted
+            WHERE a.datname = current_database()
+              AND cardinality(pg_blocking_pids(a.pid)) > 0
+            ORDER BY wait_secs DESC NULLS LAST
+        "#;
+
+        let rows = client.query(query, &[]).await?;
+
+        let locks = rows.iter().map(|row| {
+            BlockingLock {
+                blocked_pid: row.get("blocked_pid"),
+                blocking_pids: row.try_get("blocking_pids").unwrap_or_default(),
+                lock_type: row.get("lock_type"),
+                relation: row.get("relation"),
+                mode: row.get("mode"),
+                wait_secs: row.get("wait_secs"),
+            }
+        }).collect();
+
+        Ok(locks)
+    }
+}
+
+/// Mask string and numeric literals in a SQL statement.
+///
+/// Identifiers, keywords, and positional parameters ($1) are kept so the
+/// shape of the query is still visible. Dollar-quoted strings ($$...$$,
+/// $tag$...$tag$) and escape strings (E'...') are masked like plain ones.
+pub fn sanitize_query(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut rest = sql;
+    // Previous emitted char, used to tell numbers apart from identifiers/params
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = rest.chars().next() {
+        let in_word = prev.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '$');
+        if let Some(len) = string_literal_len(rest, in_word) {
+            out.push_str("'?'");
+            rest = &rest[len..];
+            prev = Some('\'');
+        } else if c.is_ascii_digit() && !in_word {
+            let len = rest.find(|n: char| !n.is_ascii_digit() && n != '.').unwrap_or(rest.len());
+            out.push('?');
+            rest = &rest[len..];
+            prev = Some('?');
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+            prev = Some(c);
+        }
+    }
+
+    out
+}
+
+/// Byte length of the string literal `sql` starts with, if it starts with
+/// one; an unterminated literal runs to the end
+pub(crate) fn string_literal_len(sql: &str, in_word: bool) -> Option<usize> {
+    if let Some(body) = sql.strip_prefix('\'') {
+        return Some(1 + quoted_len(body, false));
+    }
+    if in_word {
+        return None;
+    }
+    if let Some(body) = sql.strip_prefix("E'").or_else(|| sql.strip_prefix("e'")) {
+        return Some(2 + quoted_len(body, true));
+    }
+    // $$ or $tag$, where a tag is an identifier; $1 is a parameter
+    let tag_len = sql.strip_prefix('$')?.find('$')?;
+    let tag = &sql[1..1 + tag_len];
+    let is_tag = tag.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !tag.starts_with(|c: char| c.is_ascii_digit());
+    if !is_tag {
+        return None;
+    }
+    let delimiter = &sql[..tag_len + 2];
+    let body = &sql[delimiter.len()..];
+    Some(match body.find(delimiter) {
+        Some(end) => delimiter.len() + end + delimiter.len(),
+        None => sql.len(),
+    })
+}
+
+/// Length of a single-quoted literal's body and closing quote, treating ''
+/// as an escaped quote and, in an escape string, a backslash as escaping
+/// the char after it
+fn quoted_len(body: &str, backslash_escapes: bool) -> usize {
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if backslash_escapes => {
+                chars.next();
+            }
+            '\'' if chars.peek().is_some_and(|(_, n)| *n == '\'') => {
+                chars.next();
+            }
+            '\'' => return i + 1,
+            _ => {}
+        }
+    }
+    body.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_masks_literals() {
+        let sql = "SELECT * FROM users WHERE email = 'a@b.com' AND age > 42.5 LIMIT 10";
+        assert_eq!(
+            sanitize_query(sql),
+            "SELECT * FROM users WHERE email = '?' AND age > ? LIMIT ?"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_keeps_identifiers_and_params() {
+        let sql = "UPDATE t1 SET note = 'it''s' WHERE id = $1";
+        assert_eq!(sanitize_query(sql), "UPDATE t1 SET note = '?' WHERE id = $1");
+    }
+
+    #[test]
+    fn test_sanitize_masks_dollar_quoted_and_escape_strings() {
+        assert_eq!(
+            sanitize_query("SELECT $$secret 'x'$$, $body$it's $$ 42$body$ FROM t WHERE id = $1"),
+            "SELECT '?', '?' FROM t WHERE id = $1"
+        );
+        // A backslash-escaped quote doesn't end the literal
+        assert_eq!(
+            sanitize_query("UPDATE t SET note = E'it\\'s a secret' WHERE id = 7"),
+            "UPDATE t SET note = '?' WHERE id = ?"
+        );
+        assert_eq!(sanitize_query("SELECT price$1 FROM t WHERE code = e'\\\\'"), "SELECT price$1 FROM t WHERE code = '?'");
+        // Unterminated literals are masked to the end
+        assert_eq!(sanitize_query("SELECT $x$never closed"), "SELECT '?'");
+        assert_eq!(sanitize_query("SELECT 'never closed"), "SELECT '?'");
+    }
+}
@@ -0,0 +1,117 @@
+//! Admin impersonation sessions
+//!
+//! An admin can obtain a short-lived, read-only token for another user to
+//! debug what that user sees. Sessions are tracked here so they can be listed
+//! and ended early; the token itself stops working at `expires_at`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Longest an impersonation session may last
+pub const MAX_IMPERSONATION_MINUTES: i64 = 30;
+
+/// A single impersonation session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub admin_id: String,
+    pub admin_email: String,
+    pub target_user_id: String,
+    pub target_email: String,
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl ImpersonationSession {
+    pub fn is_active(&self) -> bool {
+        self.ended_at.is_none() && Utc::now() < self.expires_at
+    }
+}
+
+/// In-memory registry of impersonation sessions
+#[derive(Clone, Default)]
+pub struct ImpersonationRegistry {
+    sessions: Arc<RwLock<HashMap<Uuid, ImpersonationSession>>>,
+}
+
+impl ImpersonationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a session; `minutes` is clamped to 1..=MAX_IMPERSONATION_MINUTES
+    pub async fn start(
+        &self,
+        admin_id: &str,
+        admin_email: &str,
+        target_user_id: &str,
+        target_email: &str,
+        reason: &str,
+        minutes: i64,
+    ) -> ImpersonationSession {
+        let now = Utc::now();
+        let session = ImpersonationSession {
+            id: Uuid::new_v4(),
+            admin_id: admin_id.to_string(),
+            admin_email: admin_email.to_string(),
+            target_user_id: target_user_id.to_string(),
+            target_email: target_email.to_string(),
+            reason: reason.to_string(),
+            started_at: now,
+            expires_at: now + Duration::minutes(minutes.clamp(1, MAX_IMPERSONATION_MINUTES)),
+            ended_at: None,
+        };
+
+        let mut sessions = self.sessions.write().await;
+        // Expired sessions are only kept until the next one starts
+        sessions.retain(|_, s| s.is_active());
+        sessions.insert(session.id, session.clone());
+        session
+    }
+
+    /// Close a session early; returns None if it was unknown or already over
+    pub async fn end(&self, id: Uuid) -> Option<ImpersonationSession> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.get_mut(&id).filter(|s| s.is_active())?;
+        session.ended_at = Some(Utc::now());
+        Some(session.clone())
+    }
+
+    pub async fn is_active(&self, id: Uuid) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(&id).is_some_and(|s| s.is_active())
+    }
+
+    pub async fn list_active(&self) -> Vec<ImpersonationSession> {
+        let sessions = self.sessions.read().await;
+        let mut active: Vec<_> = sessions.values().filter(|s| s.is_active()).cloned().collect();
+        active.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_is_time_boxed_and_endable() {
+        let registry = ImpersonationRegistry::new();
+        let session = registry.start("1", "admin@x.io", "2", "dev@x.io", "debug", 600).await;
+
+        assert_eq!(session.expires_at - session.started_at, Duration::minutes(MAX_IMPERSONATION_MINUTES));
+        assert!(registry.is_active(session.id).await);
+
+        assert!(registry.end(session.id).await.is_some());
+        assert!(!registry.is_active(session.id).await);
+        assert!(registry.end(session.id).await.is_none());
+    }
+}
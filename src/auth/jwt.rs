@@ -3,24 +3,43 @@
 //! Handles creation, validation, and refresh of JWT tokens.
 
 use crate::auth::Role;
+use crate::config::AuthConfig;
 use crate::error::AppError;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use uuid::Uuid;
 
-/// JWT secret key (should be from environment in production)
-static JWT_SECRET: Lazy<String> = Lazy::new(|| {
-    std::env::var("JWT_SECRET").unwrap_or_else(|_| {
-        "schemaflow-dev-secret-key-change-in-production".to_string()
-    })
-});
+/// Development signing key used when `auth.jwt_secret` is not configured
+pub const DEV_JWT_SECRET: &str = "schemaflow-dev-secret-key-change-in-production";
+
+/// Signing key and token lifetimes
+struct TokenSettings {
+    secret: String,
+    access_token_minutes: i64,
+    refresh_token_days: i64,
+}
 
-/// Access token expiration (15 minutes)
-const ACCESS_TOKEN_EXPIRATION_MINUTES: i64 = 15;
+static TOKEN_SETTINGS: OnceLock<TokenSettings> = OnceLock::new();
+
+/// Sign and expire tokens per the `[auth]` settings; must run before the
+/// first token is issued, later calls are ignored
+pub fn configure(config: &AuthConfig) {
+    let _ = TOKEN_SETTINGS.set(TokenSettings {
+        secret: config.jwt_secret.clone().unwrap_or_else(|| DEV_JWT_SECRET.to_string()),
+        access_token_minutes: config.access_token_minutes,
+        refresh_token_days: config.refresh_token_days,
+    });
+}
 
-/// Refresh token expiration (7 days)
-const REFRESH_TOKEN_EXPIRATION_DAYS: i64 = 7;
+fn settings() -> &'static TokenSettings {
+    TOKEN_SETTINGS.get_or_init(|| TokenSettings {
+        secret: std::env::var("JWT_SECRET").unwrap_or_else(|_| DEV_JWT_SECRET.to_string()),
+        access_token_minutes: AuthConfig::default().access_token_minutes,
+        refresh_token_days: AuthConfig::default().refresh_token_days,
+    })
+}
 
 /// JWT claims
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,6 +56,18 @@ pub struct Claims {
     pub iat: i64,
     /// Token type (access or refresh)
     pub token_type: TokenType,
+    /// Set when an admin is acting as this user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<Impersonator>,
+}
+
+/// The admin behind an impersonation token
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Impersonator {
+    pub sub: String,
+    pub email: String,
+    pub session_id: Uuid,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -66,15 +97,16 @@ pub fn create_tokens(user_id: impl Into<String>, email: &str, role: Role) -> Res
         sub: user_id_str.clone(),
         email: email.to_string(),
         role,
-        exp: (now + Duration::minutes(ACCESS_TOKEN_EXPIRATION_MINUTES)).timestamp(),
+        exp: (now + Duration::minutes(settings().access_token_minutes)).timestamp(),
         iat: now.timestamp(),
         token_type: TokenType::Access,
+        impersonator: None,
     };
     
     let access_token = encode(
         &Header::default(),
         &access_claims,
-        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        &EncodingKey::from_secret(settings().secret.as_bytes()),
     ).map_err(|e| AppError::Internal(format!("Failed to create access token: {}", e)))?;
     
     // Create refresh token
@@ -82,30 +114,58 @@ pub fn create_tokens(user_id: impl Into<String>, email: &str, role: Role) -> Res
         sub: user_id_str,
         email: email.to_string(),
         role,
-        exp: (now + Duration::days(REFRESH_TOKEN_EXPIRATION_DAYS)).timestamp(),
+        exp: (now + Duration::days(settings().refresh_token_days)).timestamp(),
         iat: now.timestamp(),
         token_type: TokenType::Refresh,
+        impersonator: None,
     };
     
     let refresh_token = encode(
         &Header::default(),
         &refresh_claims,
-        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        &EncodingKey::from_secret(settings().secret.as_bytes()),
     ).map_err(|e| AppError::Internal(format!("Failed to create refresh token: {}", e)))?;
     
     Ok(TokenPair {
         access_token,
         refresh_token,
         token_type: "Bearer".to_string(),
-        expires_in: ACCESS_TOKEN_EXPIRATION_MINUTES * 60,
+        expires_in: settings().access_token_minutes * 60,
     })
 }
 
+/// Create an access token that lets an admin act as another user.
+///
+/// No refresh token is issued, so the session ends at `expires_at`.
+pub fn create_impersonation_token(
+    user_id: &str,
+    email: &str,
+    impersonator: Impersonator,
+    expires_at: chrono::DateTime<Utc>,
+) -> Result<String, AppError> {
+    let claims = Claims {
+        sub: user_id.to_string(),
+        email: email.to_string(),
+        // Impersonation is for looking, never for approving or executing
+        role: Role::Viewer,
+        exp: expires_at.timestamp(),
+        iat: Utc::now().timestamp(),
+        token_type: TokenType::Access,
+        impersonator: Some(impersonator),
+    };
+    
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(settings().secret.as_bytes()),
+    ).map_err(|e| AppError::Internal(format!("Failed to create impersonation token: {}", e)))
+}
+
 /// Decode and validate a JWT token
 pub fn decode_token(token: &str) -> Result<Claims, AppError> {
     let token_data = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
+        &DecodingKey::from_secret(settings().secret.as_bytes()),
         &Validation::default(),
     ).map_err(|e| match e.kind() {
         jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
@@ -120,6 +180,56 @@ pub fn decode_token(token: &str) -> Result<Claims, AppError> {
     Ok(token_data.claims)
 }
 
+/// Claims of a proposal share link token
+///
+/// Deliberately shares no fields with `Claims`, so neither kind of token
+/// decodes as the other.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShareClaims {
+    /// Share link ID
+    pub sid: Uuid,
+    /// Proposal the link opens
+    pub pid: Uuid,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+/// Sign a share link token that expires with the link
+pub fn create_share_token(
+    link_id: Uuid,
+    proposal_id: Uuid,
+    expires_at: chrono::DateTime<Utc>,
+) -> Result<String, AppError> {
+    let claims = ShareClaims {
+        sid: link_id,
+        pid: proposal_id,
+        exp: expires_at.timestamp(),
+        iat: Utc::now().timestamp(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(settings().secret.as_bytes()),
+    ).map_err(|e| AppError::Internal(format!("Failed to create share token: {}", e)))
+}
+
+/// Verify a share link token's signature and expiry
+pub fn decode_share_token(token: &str) -> Result<ShareClaims, AppError> {
+    decode::<ShareClaims>(
+        token,
+        &DecodingKey::from_secret(settings().secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+    .map_err(|e| match e.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+            AppError::Unauthorized("Share link expired".to_string())
+        }
+        _ => AppError::Unauthorized("Invalid share link".to_string()),
+    })
+}
+
 /// Refresh tokens using a valid refresh token
 pub fn refresh_tokens(refresh_token: &str) -> Result<TokenPair, AppError> {
     let claims = decode_token(refresh_token)?;
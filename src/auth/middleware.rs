@@ -4,36 +4,82 @@
 
 use crate::auth::{Claims, Role, decode_token};
 use crate::error::AppError;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::state::SharedState;
 use axum::{
-    extract::Request,
+    extract::{Request, State},
     middleware::Next,
     response::Response,
 };
-use axum::http::header::AUTHORIZATION;
+use axum::http::header::{AUTHORIZATION, UPGRADE};
 
 /// Extract claims from request
 pub async fn auth_middleware(
+    State(state): State<SharedState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
     let auth_header = request
         .headers()
         .get(AUTHORIZATION)
-        .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
+        .and_then(|h| h.to_str().ok());
     
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?;
+    let token = match auth_header {
+        Some(header) => header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?,
+        // Browsers cannot set headers on a WebSocket handshake
+        None => websocket_query_token(&request)
+            .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?,
+    };
     
     let claims = decode_token(token)?;
     
+    if let Some(impersonator) = &claims.impersonator {
+        if !state.impersonations.is_active(impersonator.session_id).await {
+            return Err(AppError::Unauthorized("Impersonation session has ended".to_string()));
+        }
+        if !request.method().is_safe() {
+            return Err(AppError::Forbidden("Impersonation sessions are read-only".to_string()));
+        }
+        
+        let entry = AuditEntry::new(
+            AuditAction::ImpersonatedRequest,
+            &impersonator.email,
+            "user",
+            &claims.sub,
+        )
+        .with_details(&format!(
+            "{} as {}: {} {} (session {})",
+            impersonator.email,
+            claims.email,
+            request.method(),
+            request.uri().path(),
+            impersonator.session_id
+        ));
+        state.metadata.add_audit_entry(entry).await?;
+    }
+    
     // Insert claims into request extensions for handlers to use
     request.extensions_mut().insert(claims);
     
     Ok(next.run(request).await)
 }
 
+/// `access_token` query parameter of a WebSocket upgrade request
+fn websocket_query_token(request: &Request) -> Option<&str> {
+    let upgrade = request.headers().get(UPGRADE).and_then(|h| h.to_str().ok())?;
+    if !upgrade.eq_ignore_ascii_case("websocket") {
+        return None;
+    }
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("access_token="))
+        .filter(|token| !token.is_empty())
+}
+
 /// Require specific role
 pub fn require_role(claims: &Claims, required: Role) -> Result<(), AppError> {
     let has_permission = match required {
@@ -2,11 +2,16 @@
 //!
 //! Provides JWT-based authentication and role-based access control.
 
+pub mod impersonation;
 mod jwt;
 pub mod middleware;
 mod password;
 
-pub use jwt::{Claims, TokenPair, create_tokens, decode_token, refresh_tokens};
+pub use impersonation::{ImpersonationRegistry, ImpersonationSession};
+pub use jwt::{
+    Claims, DEV_JWT_SECRET, Impersonator, TokenPair, configure as configure_tokens, create_tokens, create_impersonation_token, create_share_token,
+    decode_share_token, decode_token, refresh_tokens,
+};
 #[allow(unused_imports)]
 pub use middleware::auth_middleware;
 pub use password::hash_password;
@@ -1,9 +1,22 @@
 //! Application configuration module
 //!
-//! Handles loading and validating configuration from environment variables.
+//! Settings are layered: `config/default.toml`, then the file for the
+//! deployment profile (`APP_ENV`, e.g. `config/production.toml`), then
+//! `SCHEMAFLOW__<SECTION>__<KEY>` environment variables, then the flat
+//! variables (`PORT`, `DATABASE_URL`, ...) used by `.env` deployments.
+//! Every file is optional. Values that fail to parse or validate are
+//! reported with the key that holds them.
 
+use crate::pipeline::mirror::SemanticLayer;
+use crate::quota::ProjectQuota;
+use crate::snapshot::Severity;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -20,6 +33,9 @@ pub enum ConfigError {
 
     #[error("Failed to parse configuration: {0}")]
     ParseError(String),
+
+    #[error("Invalid value for `{key}`: {message}")]
+    InvalidKey { key: String, message: String },
 }
 
 /// Server configuration
@@ -76,66 +92,801 @@ impl Default for CorsConfig {
     }
 }
 
+/// Proposal lifecycle policy
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProposalPolicyConfig {
+    /// Days an approval stays valid before the proposal needs re-approval (None = never expires)
+    pub approval_validity_days: Option<i64>,
+    /// Days a draft may go untouched before it is closed automatically (None = never)
+    pub stale_draft_days: Option<i64>,
+    /// How often the background task enforces the policy
+    pub sweep_interval_secs: u64,
+    /// Re-run risk analysis for open proposals when their connection's schema changes
+    pub reanalyze_on_drift: bool,
+    /// Statements per transaction when executing migrations (None = one transaction)
+    pub execution_chunk_size: Option<usize>,
+    /// Refuse approval while a statement-level comment thread is unresolved
+    pub block_on_open_statement_threads: bool,
+    /// Refuse approval until every migration statement has been signed off
+    pub require_statement_approvals: bool,
+    /// Largest table, in MB, a column reorder may copy (None = no limit)
+    pub reorder_max_table_mb: Option<i64>,
+    /// Hours after execution during which a proposal can be rolled back
+    /// directly; afterwards it takes a revert proposal (None = never)
+    pub rollback_window_hours: Option<i64>,
+    /// Days after a break-glass change by which its retrospective review is due
+    pub break_glass_review_days: i64,
+}
+
+impl Default for ProposalPolicyConfig {
+    fn default() -> Self {
+        Self {
+            approval_validity_days: Some(14),
+            stale_draft_days: Some(90),
+            sweep_interval_secs: 3600,
+            reanalyze_on_drift: true,
+            execution_chunk_size: None,
+            block_on_open_statement_threads: true,
+            require_statement_approvals: false,
+            reorder_max_table_mb: Some(5 * 1024),
+            rollback_window_hours: Some(24),
+            break_glass_review_days: 3,
+        }
+    }
+}
+
+/// S3-compatible archive storage for snapshots and execution artifacts
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArchiveConfig {
+    /// Custom endpoint for MinIO and other S3-compatible stores (None = AWS)
+    pub endpoint: Option<String>,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Key prefix for all archived objects
+    pub prefix: String,
+    /// Use path-style URLs (required by most MinIO deployments)
+    pub path_style: bool,
+    /// Transition archived objects to a colder storage class after N days
+    pub transition_after_days: Option<u32>,
+    pub transition_storage_class: Option<String>,
+    /// Delete archived objects after N days (None = keep forever)
+    pub expire_after_days: Option<u32>,
+}
+
+/// TLS mode for the SMTP connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection (local relays only)
+    None,
+    /// Upgrade with STARTTLS, usually on port 587
+    StartTls,
+    /// Implicit TLS, usually on port 465
+    Tls,
+}
+
+impl SmtpTls {
+    fn default_port(self) -> u16 {
+        match self {
+            SmtpTls::None => 25,
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+        }
+    }
+}
+
+/// Outgoing email via any SMTP server
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from_address: String,
+    pub from_name: String,
+    /// Public URL of the web app, used for links in emails
+    pub app_base_url: Option<String>,
+}
+
+/// Where pre-execution backups are taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupProviderKind {
+    /// `pg_dump` uploaded to the archive bucket
+    PgDump,
+    /// Amazon RDS snapshot API
+    Rds,
+    /// Google Cloud SQL backup API
+    CloudSql,
+}
+
+/// Backup taken before destructive proposals execute
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupConfig {
+    pub provider: BackupProviderKind,
+    /// `pg_dump` binary, looked up on PATH by default
+    pub pg_dump_path: String,
+    /// Execution is refused if the backup has not finished by then
+    pub timeout_secs: u64,
+}
+
+/// Chat completions endpoint that rewrites proposal summaries for
+/// non-technical readers, for projects with the `llm_summaries` feature
+#[derive(Debug, Clone, Deserialize)]
+pub struct SummaryConfig {
+    /// OpenAI-compatible `/v1/chat/completions` URL
+    pub llm_endpoint: String,
+    pub llm_api_key: Option<String>,
+    pub llm_model: String,
+    /// The template summary stays in place if the model has not answered by then
+    pub timeout_secs: u64,
+}
+
+/// Format of audit log exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditExportFormat {
+    /// ArcSight Common Event Format, one event per line
+    Cef,
+    /// `schemaflow.audit-export/v1` JSON document
+    #[default]
+    Json,
+}
+
+impl AuditExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "cef" => Some(Self::Cef),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// File extension of written exports
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Cef => "cef",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Audit log exports for SIEMs
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditExportConfig {
+    /// Periodic exports are written here; on demand only if unset
+    pub directory: Option<PathBuf>,
+    pub interval_secs: u64,
+    pub format: AuditExportFormat,
+    /// Key for the HMAC signature of JSON exports; unsigned if unset
+    pub signing_key: Option<String>,
+}
+
+impl Default for AuditExportConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            interval_secs: 3600,
+            format: AuditExportFormat::default(),
+            signing_key: None,
+        }
+    }
+}
+
+/// How long audit entries and execution records are kept; `None` keeps
+/// them forever. Anything under a legal hold is kept regardless.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RetentionConfig {
+    /// Audit entries older than this are pruned to their chain hashes
+    pub audit_days: Option<u64>,
+    /// Journal records of finished executions older than this are deleted
+    pub execution_days: Option<u64>,
+}
+
+/// How often each layer of stored semantic maps is refreshed; `None` leaves
+/// a layer to on-demand refreshes
+#[derive(Debug, Clone, Deserialize)]
+pub struct SemanticMapConfig {
+    pub schema_interval_secs: Option<u64>,
+    pub statistics_interval_secs: Option<u64>,
+    pub dependencies_interval_secs: Option<u64>,
+    pub hot_spots_interval_secs: Option<u64>,
+}
+
+impl SemanticMapConfig {
+    pub fn interval(&self, layer: SemanticLayer) -> Option<Duration> {
+        let secs = match layer {
+            SemanticLayer::Schema => self.schema_interval_secs,
+            SemanticLayer::Statistics => self.statistics_interval_secs,
+            SemanticLayer::Dependencies => self.dependencies_interval_secs,
+            SemanticLayer::HotSpots => self.hot_spots_interval_secs,
+        };
+        secs.map(Duration::from_secs)
+    }
+}
+
+impl Default for SemanticMapConfig {
+    fn default() -> Self {
+        Self {
+            schema_interval_secs: Some(86_400),
+            statistics_interval_secs: Some(3600),
+            dependencies_interval_secs: Some(86_400),
+            hot_spots_interval_secs: Some(3600),
+        }
+    }
+}
+
+/// Where governance metadata, snapshots and proposals are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Process memory; lost on restart (tests and local experiments)
+    Memory,
+    /// The application database
+    #[default]
+    Postgres,
+}
+
+/// Token signing and lifetimes
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    /// Key for access, refresh and share tokens; a development key is used if unset
+    pub jwt_secret: Option<String>,
+    pub access_token_minutes: i64,
+    pub refresh_token_days: i64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: None,
+            access_token_minutes: 15,
+            refresh_token_days: 7,
+        }
+    }
+}
+
+/// Keys for saved connection strings, each base64 of 32 random bytes. A
+/// key held in a KMS is decrypted into the variable at deploy time.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EncryptionConfig {
+    /// Key new connection strings are encrypted with; a development key is used if unset
+    pub key: Option<String>,
+    /// Retired keys, still accepted for decryption until startup has
+    /// re-encrypted their rows with `key`
+    pub previous_keys: Vec<String>,
+}
+
+/// Pools for the databases users connect to
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolConfig {
+    /// Connections kept per target database
+    pub target_max_size: usize,
+    /// Connections opened ahead of use when a connection is added or activated
+    pub target_min_idle: usize,
+    /// Seconds a pooled connection may sit idle before it is closed; `None` keeps them open
+    pub target_idle_teardown_secs: Option<u64>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            target_max_size: 5,
+            target_min_idle: 1,
+            target_idle_teardown_secs: Some(600),
+        }
+    }
+}
+
+/// Limits on API traffic; `None` leaves a limit off
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RateLimitConfig {
+    /// Requests one client may send per minute
+    pub requests_per_minute: Option<u32>,
+    /// Requests handled at once across all clients
+    pub max_concurrent_requests: Option<usize>,
+}
+
+/// Directory searched for `default.toml` and `<profile>.toml`
+const DEFAULT_CONFIG_DIR: &str = "config";
+
+/// Profile used when `APP_ENV` is not set
+const DEFAULT_PROFILE: &str = "development";
+
+/// Configuration sources in increasing precedence: `<dir>/default.toml`,
+/// `<dir>/<profile>.toml`, `SCHEMAFLOW__<SECTION>__<KEY>` variables, and the
+/// flat variables (`PORT`, `SMTP_HOST`, ...) that `.env` deployments set.
+struct Layers {
+    config: config::Config,
+}
+
+impl Layers {
+    fn load(dir: &Path, profile: &str) -> Result<Self, ConfigError> {
+        let config = config::Config::builder()
+            .add_source(config::File::from(dir.join("default.toml")).required(false))
+            .add_source(config::File::from(dir.join(format!("{}.toml", profile))).required(false))
+            .add_source(
+                config::Environment::with_prefix("SCHEMAFLOW")
+                    .prefix_separator("__")
+                    .separator("__"),
+            )
+            .build()
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        Ok(Self { config })
+    }
+
+    /// `key` (e.g. `server.port`) from the flat variable `var` if set,
+    /// otherwise from the files and `SCHEMAFLOW__` variables
+    fn get<T>(&self, key: &str, var: &str) -> Result<Option<T>, ConfigError>
+    where
+        T: FromStr + DeserializeOwned,
+        T::Err: std::fmt::Display,
+    {
+        match std::env::var(var) {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|e: T::Err| ConfigError::InvalidKey { key: var.to_string(), message: e.to_string() }),
+            _ => self.file(key),
+        }
+    }
+
+    /// `key` from the files and `SCHEMAFLOW__` variables only
+    fn file<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        match self.config.get::<T>(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(config::ConfigError::NotFound(_)) => Ok(None),
+            Err(e) => Err(ConfigError::InvalidKey { key: key.to_string(), message: e.to_string() }),
+        }
+    }
+
+    /// A list given as an array in files or comma-separated in variables
+    fn list(&self, key: &str, var: &str) -> Result<Option<Vec<String>>, ConfigError> {
+        let split = |s: &str| s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        if let Ok(raw) = std::env::var(var) {
+            return Ok(Some(split(&raw)));
+        }
+        match self.file::<Vec<String>>(key) {
+            Ok(list) => Ok(list),
+            Err(_) => Ok(self.file::<String>(key)?.map(|s| split(&s))),
+        }
+    }
+
+    /// A limit where unset or 0 means off
+    fn limit<T>(&self, key: &str, var: &str) -> Result<Option<T>, ConfigError>
+    where
+        T: FromStr + DeserializeOwned + PartialEq + Default,
+        T::Err: std::fmt::Display,
+    {
+        Ok(self.get::<T>(key, var)?.filter(|n| *n != T::default()))
+    }
+}
+
 /// Complete application settings
 #[derive(Debug, Clone)]
 pub struct Settings {
+    /// Deployment profile whose `<profile>.toml` was layered over `default.toml`
+    pub profile: String,
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub cors: CorsConfig,
+    pub auth: AuthConfig,
+    pub encryption: EncryptionConfig,
+    pub pool: PoolConfig,
+    pub rate_limits: RateLimitConfig,
+    pub proposal_policy: ProposalPolicyConfig,
+    /// Snapshot archive; disabled unless `archive.bucket` is set
+    pub archive: Option<ArchiveConfig>,
+    /// Email notifications; disabled unless `smtp.host` is set
+    pub smtp: Option<SmtpConfig>,
+    pub storage: StorageBackend,
+    /// Default per-project quotas; unlimited unless `quotas.*` is set
+    pub quotas: ProjectQuota,
+    /// Directory of `<language>.json` message translations; English only if unset
+    pub locales_dir: Option<PathBuf>,
+    /// Pre-execution backups; disabled unless `backup.provider` is set
+    pub backup: Option<BackupConfig>,
+    /// LLM-written proposal summaries; template summaries only unless
+    /// `summaries.llm_endpoint` is set
+    pub summaries: Option<SummaryConfig>,
+    /// Feature flags from the `[features]` table
+    pub features: BTreeMap<String, bool>,
+    /// Rule severities per environment from `[rule_severity.<environment>]`, keyed by rule id
+    pub rule_severity: BTreeMap<String, BTreeMap<String, Severity>>,
+    pub audit_export: AuditExportConfig,
+    pub semantic_map: SemanticMapConfig,
+    pub retention: RetentionConfig,
 }
 
 impl Settings {
-    /// Load settings from environment variables
+    /// Load settings for the `APP_ENV` profile from `CONFIG_DIR` (default
+    /// `config/`) and the environment, then validate them
     pub fn load() -> Result<Self, ConfigError> {
         // Load .env file if it exists (ignore errors if file not found)
         let _ = dotenvy::dotenv();
 
+        let profile = std::env::var("APP_ENV").unwrap_or_else(|_| DEFAULT_PROFILE.to_string());
+        let dir = std::env::var("CONFIG_DIR").unwrap_or_else(|_| DEFAULT_CONFIG_DIR.to_string());
+        let settings = Self::from_layers(&Layers::load(Path::new(&dir), &profile)?, profile)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Built-in defaults and the environment, without config files
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        let layers = Layers::load(&std::env::temp_dir().join("schemaflow-test-config"), "test").unwrap();
+        Self::from_layers(&layers, "test".to_string()).unwrap()
+    }
+
+    fn from_layers(layers: &Layers, profile: String) -> Result<Self, ConfigError> {
+        let server_defaults = ServerConfig::default();
         let server = ServerConfig {
-            host: std::env::var("HOST")
-                .ok()
-                .and_then(|h| h.parse().ok())
-                .unwrap_or_else(|| ServerConfig::default().host),
-            port: std::env::var("PORT")
-                .ok()
-                .and_then(|p| p.parse().ok())
-                .unwrap_or_else(|| ServerConfig::default().port),
-        };
-
-        // Try to load DATABASE_URL first (modern format), fall back to individual vars
-        let database = if let Ok(database_url) = std::env::var("DATABASE_URL") {
-            Self::parse_database_url(&database_url)?
-        } else {
-            // Fall back to individual environment variables
-            DatabaseConfig {
-                host: std::env::var("DB_HOST").unwrap_or_else(|_| "localhost".to_string()),
-                port: std::env::var("DB_PORT")
-                    .ok()
-                    .and_then(|p| p.parse().ok())
-                    .unwrap_or(5432),
-                user: std::env::var("DB_USER").unwrap_or_else(|_| "postgres".to_string()),
-                password: std::env::var("DB_PASSWORD").unwrap_or_default(),
-                database: std::env::var("DB_NAME").unwrap_or_else(|_| "postgres".to_string()),
-                max_pool_size: std::env::var("DB_MAX_CONNECTIONS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(10),
-            }
+            host: layers.get("server.host", "HOST")?.unwrap_or(server_defaults.host),
+            port: layers.get("server.port", "PORT")?.unwrap_or(server_defaults.port),
+        };
+
+        // A URL (DATABASE_URL or database.url) wins over the individual keys
+        let database_defaults = DatabaseConfig::default();
+        let max_pool_size = layers
+            .get("database.max_pool_size", "DB_MAX_CONNECTIONS")?
+            .unwrap_or(database_defaults.max_pool_size);
+        let database = match layers.get::<String>("database.url", "DATABASE_URL")? {
+            Some(database_url) => DatabaseConfig {
+                max_pool_size,
+                ..Self::parse_database_url(&database_url)?
+            },
+            None => DatabaseConfig {
+                host: layers.get("database.host", "DB_HOST")?.unwrap_or(database_defaults.host),
+                port: layers.get("database.port", "DB_PORT")?.unwrap_or(database_defaults.port),
+                user: layers.get("database.user", "DB_USER")?.unwrap_or(database_defaults.user),
+                password: layers.get("database.password", "DB_PASSWORD")?.unwrap_or_default(),
+                database: layers.get("database.name", "DB_NAME")?.unwrap_or(database_defaults.database),
+                max_pool_size,
+            },
         };
 
         let cors = CorsConfig {
-            allowed_origins: std::env::var("ALLOWED_ORIGINS")
-                .ok()
-                .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
+            allowed_origins: layers
+                .list("cors.allowed_origins", "ALLOWED_ORIGINS")?
                 .unwrap_or_else(|| CorsConfig::default().allowed_origins),
         };
 
+        let auth_defaults = AuthConfig::default();
+        let auth = AuthConfig {
+            jwt_secret: layers.get("auth.jwt_secret", "JWT_SECRET")?,
+            access_token_minutes: layers
+                .get("auth.access_token_minutes", "ACCESS_TOKEN_MINUTES")?
+                .unwrap_or(auth_defaults.access_token_minutes),
+            refresh_token_days: layers
+                .get("auth.refresh_token_days", "REFRESH_TOKEN_DAYS")?
+                .unwrap_or(auth_defaults.refresh_token_days),
+        };
+
+        let encryption = EncryptionConfig {
+            key: layers.get("encryption.key", "CONNECTION_ENCRYPTION_KEY")?,
+            previous_keys: layers
+                .list("encryption.previous_keys", "CONNECTION_ENCRYPTION_PREVIOUS_KEYS")?
+                .unwrap_or_default(),
+        };
+
+        let pool_defaults = PoolConfig::default();
+        let pool = PoolConfig {
+            target_max_size: layers
+                .get("pool.target_max_size", "TARGET_POOL_SIZE")?
+                .unwrap_or(pool_defaults.target_max_size),
+            target_min_idle: layers
+                .get("pool.target_min_idle", "TARGET_POOL_MIN_IDLE")?
+                .unwrap_or(pool_defaults.target_min_idle),
+            target_idle_teardown_secs: layers
+                .get::<u64>("pool.target_idle_teardown_secs", "TARGET_POOL_IDLE_TEARDOWN_SECS")?
+                .map_or(pool_defaults.target_idle_teardown_secs, |v| (v > 0).then_some(v)),
+        };
+
+        let rate_limits = RateLimitConfig {
+            requests_per_minute: layers.limit("rate_limits.requests_per_minute", "RATE_LIMIT_PER_MINUTE")?,
+            max_concurrent_requests: layers.limit("rate_limits.max_concurrent_requests", "MAX_CONCURRENT_REQUESTS")?,
+        };
+
+        // A value of 0 disables the corresponding policy
+        let policy_defaults = ProposalPolicyConfig::default();
+        let days = |key: &str, var: &str, default: Option<i64>| -> Result<Option<i64>, ConfigError> {
+            Ok(layers.get::<i64>(key, var)?.map_or(default, |d| (d > 0).then_some(d)))
+        };
+        let proposal_policy = ProposalPolicyConfig {
+            approval_validity_days: days(
+                "proposal_policy.approval_validity_days",
+                "APPROVAL_VALIDITY_DAYS",
+                policy_defaults.approval_validity_days,
+            )?,
+            stale_draft_days: days(
+                "proposal_policy.stale_draft_days",
+                "STALE_DRAFT_DAYS",
+                policy_defaults.stale_draft_days,
+            )?,
+            sweep_interval_secs: layers
+                .get("proposal_policy.sweep_interval_secs", "POLICY_SWEEP_INTERVAL_SECS")?
+                .unwrap_or(policy_defaults.sweep_interval_secs),
+            reanalyze_on_drift: layers
+                .get("proposal_policy.reanalyze_on_drift", "REANALYZE_ON_DRIFT")?
+                .unwrap_or(policy_defaults.reanalyze_on_drift),
+            execution_chunk_size: layers
+                .limit("proposal_policy.execution_chunk_size", "EXECUTION_CHUNK_SIZE")?
+                .or(policy_defaults.execution_chunk_size),
+            block_on_open_statement_threads: layers
+                .get("proposal_policy.block_on_open_statement_threads", "BLOCK_ON_OPEN_STATEMENT_THREADS")?
+                .unwrap_or(policy_defaults.block_on_open_statement_threads),
+            require_statement_approvals: layers
+                .get("proposal_policy.require_statement_approvals", "REQUIRE_STATEMENT_APPROVALS")?
+                .unwrap_or(policy_defaults.require_statement_approvals),
+            reorder_max_table_mb: layers
+                .get::<i64>("proposal_policy.reorder_max_table_mb", "REORDER_MAX_TABLE_MB")?
+                .map_or(policy_defaults.reorder_max_table_mb, |mb| (mb > 0).then_some(mb)),
+            rollback_window_hours: layers
+                .get::<i64>("proposal_policy.rollback_window_hours", "ROLLBACK_WINDOW_HOURS")?
+                .map_or(policy_defaults.rollback_window_hours, |h| (h > 0).then_some(h)),
+            break_glass_review_days: layers
+                .get("proposal_policy.break_glass_review_days", "BREAK_GLASS_REVIEW_DAYS")?
+                .unwrap_or(policy_defaults.break_glass_review_days),
+        };
+
+        let archive = match layers.get::<String>("archive.bucket", "ARCHIVE_BUCKET")? {
+            Some(bucket) => {
+                let endpoint = layers.get::<String>("archive.endpoint", "ARCHIVE_ENDPOINT")?;
+                Some(ArchiveConfig {
+                    path_style: layers
+                        .get("archive.path_style", "ARCHIVE_PATH_STYLE")?
+                        .unwrap_or(endpoint.is_some()),
+                    endpoint,
+                    region: layers.get("archive.region", "ARCHIVE_REGION")?.unwrap_or_else(|| "us-east-1".to_string()),
+                    bucket,
+                    access_key: layers.get("archive.access_key", "ARCHIVE_ACCESS_KEY")?.unwrap_or_default(),
+                    secret_key: layers.get("archive.secret_key", "ARCHIVE_SECRET_KEY")?.unwrap_or_default(),
+                    prefix: layers.get("archive.prefix", "ARCHIVE_PREFIX")?.unwrap_or_else(|| "schemaflow".to_string()),
+                    transition_after_days: layers.get("archive.transition_after_days", "ARCHIVE_TRANSITION_DAYS")?,
+                    transition_storage_class: layers.get("archive.transition_storage_class", "ARCHIVE_STORAGE_CLASS")?,
+                    expire_after_days: layers.get("archive.expire_after_days", "ARCHIVE_EXPIRE_DAYS")?,
+                })
+            }
+            None => None,
+        };
+
+        let smtp = match layers.get::<String>("smtp.host", "SMTP_HOST")? {
+            Some(host) => {
+                let tls = match layers.get::<String>("smtp.tls", "SMTP_TLS")?.map(|s| s.to_lowercase()).as_deref() {
+                    None | Some("starttls") => SmtpTls::StartTls,
+                    Some("none") => SmtpTls::None,
+                    Some("tls") => SmtpTls::Tls,
+                    Some(other) => {
+                        return Err(ConfigError::InvalidKey {
+                            key: "smtp.tls".to_string(),
+                            message: format!("must be none, starttls or tls, not '{}'", other),
+                        })
+                    }
+                };
+                Some(SmtpConfig {
+                    port: layers.get("smtp.port", "SMTP_PORT")?.unwrap_or(tls.default_port()),
+                    tls,
+                    username: layers.get("smtp.username", "SMTP_USERNAME")?,
+                    password: layers.get("smtp.password", "SMTP_PASSWORD")?,
+                    from_address: layers
+                        .get("smtp.from_address", "SMTP_FROM")?
+                        .unwrap_or_else(|| format!("schemaflow@{}", host)),
+                    from_name: layers.get("smtp.from_name", "SMTP_FROM_NAME")?.unwrap_or_else(|| "SchemaFlow".to_string()),
+                    app_base_url: layers.get("smtp.app_base_url", "APP_BASE_URL")?,
+                    host,
+                })
+            }
+            None => None,
+        };
+
+        // Unset or 0 leaves the limit off
+        let quotas = ProjectQuota {
+            max_connections: layers.limit("quotas.max_connections", "QUOTA_MAX_CONNECTIONS")?,
+            max_snapshots_per_day: layers.limit("quotas.max_snapshots_per_day", "QUOTA_MAX_SNAPSHOTS_PER_DAY")?,
+            max_concurrent_executions: layers
+                .limit("quotas.max_concurrent_executions", "QUOTA_MAX_CONCURRENT_EXECUTIONS")?,
+        };
+
+        let backup = match layers.get::<String>("backup.provider", "BACKUP_PROVIDER")?.map(|s| s.to_lowercase()) {
+            Some(provider) => {
+                let provider = match provider.as_str() {
+                    "pg_dump" | "pgdump" => BackupProviderKind::PgDump,
+                    "rds" => BackupProviderKind::Rds,
+                    "cloudsql" => BackupProviderKind::CloudSql,
+                    other => {
+                        return Err(ConfigError::InvalidKey {
+                            key: "backup.provider".to_string(),
+                            message: format!("must be pg_dump, rds or cloudsql, not '{}'", other),
+                        })
+                    }
+                };
+                Some(BackupConfig {
+                    provider,
+                    pg_dump_path: layers.get("backup.pg_dump_path", "PG_DUMP_PATH")?.unwrap_or_else(|| "pg_dump".to_string()),
+                    timeout_secs: layers.limit("backup.timeout_secs", "BACKUP_TIMEOUT_SECS")?.unwrap_or(1800),
+                })
+            }
+            None => None,
+        };
+
+        let summaries = match layers.get::<String>("summaries.llm_endpoint", "SUMMARY_LLM_ENDPOINT")? {
+            Some(llm_endpoint) => Some(SummaryConfig {
+                llm_endpoint,
+                llm_api_key: layers.get("summaries.llm_api_key", "SUMMARY_LLM_API_KEY")?,
+                llm_model: layers
+                    .get("summaries.llm_model", "SUMMARY_LLM_MODEL")?
+                    .unwrap_or_else(|| "gpt-4o-mini".to_string()),
+                timeout_secs: layers.limit("summaries.timeout_secs", "SUMMARY_LLM_TIMEOUT_SECS")?.unwrap_or(30),
+            }),
+            None => None,
+        };
+
+        let storage = match layers.get::<String>("storage.backend", "STORAGE_BACKEND")?.map(|s| s.to_lowercase()).as_deref() {
+            None | Some("postgres") => StorageBackend::Postgres,
+            Some("memory") => StorageBackend::Memory,
+            Some(other) => {
+                return Err(ConfigError::InvalidKey {
+                    key: "storage.backend".to_string(),
+                    message: format!("must be postgres or memory, not '{}'", other),
+                })
+            }
+        };
+
+        let export_defaults = AuditExportConfig::default();
+        let audit_export = AuditExportConfig {
+            directory: layers.get("audit_export.directory", "AUDIT_EXPORT_DIR")?,
+            interval_secs: layers
+                .limit("audit_export.interval_secs", "AUDIT_EXPORT_INTERVAL_SECS")?
+                .unwrap_or(export_defaults.interval_secs),
+            format: match layers.get::<String>("audit_export.format", "AUDIT_EXPORT_FORMAT")? {
+                None => export_defaults.format,
+                Some(format) => AuditExportFormat::parse(&format).ok_or_else(|| ConfigError::InvalidKey {
+                    key: "audit_export.format".to_string(),
+                    message: format!("must be cef or json, not '{}'", format),
+                })?,
+            },
+            signing_key: layers.get("audit_export.signing_key", "AUDIT_EXPORT_SIGNING_KEY")?,
+        };
+
+        // A value of 0 leaves the layer to on-demand refreshes
+        let semantic_defaults = SemanticMapConfig::default();
+        let cadence = |key: &str, var: &str, default: Option<u64>| -> Result<Option<u64>, ConfigError> {
+            Ok(layers.get::<u64>(key, var)?.map_or(default, |v| (v > 0).then_some(v)))
+        };
+        let semantic_map = SemanticMapConfig {
+            schema_interval_secs: cadence(
+                "semantic_map.schema_interval_secs",
+                "SEMANTIC_SCHEMA_INTERVAL_SECS",
+                semantic_defaults.schema_interval_secs,
+            )?,
+            statistics_interval_secs: cadence(
+                "semantic_map.statistics_interval_secs",
+                "SEMANTIC_STATISTICS_INTERVAL_SECS",
+                semantic_defaults.statistics_interval_secs,
+            )?,
+            dependencies_interval_secs: cadence(
+                "semantic_map.dependencies_interval_secs",
+                "SEMANTIC_DEPENDENCIES_INTERVAL_SECS",
+                semantic_defaults.dependencies_interval_secs,
+            )?,
+            hot_spots_interval_secs: cadence(
+                "semantic_map.hot_spots_interval_secs",
+                "SEMANTIC_HOT_SPOTS_INTERVAL_SECS",
+                semantic_defaults.hot_spots_interval_secs,
+            )?,
+        };
+
+        let retention = RetentionConfig {
+            audit_days: layers.limit("retention.audit_days", "AUDIT_RETENTION_DAYS")?,
+            execution_days: layers.limit("retention.execution_days", "EXECUTION_RETENTION_DAYS")?,
+        };
+
+        // Keys come back lowercased from the files; rule ids are upper case
+        let rule_severity = layers
+            .file::<BTreeMap<String, BTreeMap<String, Severity>>>("rule_severity")?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(environment, rules)| {
+                (environment, rules.into_iter().map(|(id, severity)| (id.to_uppercase(), severity)).collect())
+            })
+            .collect();
+
         Ok(Self {
+            profile,
             server,
             database,
             cors,
+            auth,
+            encryption,
+            pool,
+            rate_limits,
+            proposal_policy,
+            archive,
+            smtp,
+            storage,
+            quotas,
+            locales_dir: layers.get("locales_dir", "LOCALES_DIR")?,
+            backup,
+            summaries,
+            features: layers.file("features")?.unwrap_or_default(),
+            rule_severity,
+            audit_export,
+            semantic_map,
+            retention,
         })
     }
 
+    /// Reject values that parse but cannot work, naming the key at fault
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |key: &str, message: &str| {
+            Err(ConfigError::InvalidKey { key: key.to_string(), message: message.to_string() })
+        };
+
+        if self.server.port == 0 {
+            return invalid("server.port", "must be greater than 0");
+        }
+        if self.database.max_pool_size == 0 {
+            return invalid("database.max_pool_size", "must be greater than 0");
+        }
+        if self.pool.target_max_size == 0 {
+            return invalid("pool.target_max_size", "must be greater than 0");
+        }
+        if self.pool.target_min_idle > self.pool.target_max_size {
+            return invalid("pool.target_min_idle", "must not exceed pool.target_max_size");
+        }
+        if self.auth.access_token_minutes <= 0 {
+            return invalid("auth.access_token_minutes", "must be greater than 0");
+        }
+        if self.auth.refresh_token_days <= 0 {
+            return invalid("auth.refresh_token_days", "must be greater than 0");
+        }
+        if self.profile == "production" && self.auth.jwt_secret.as_deref().is_none_or(|s| s.len() < 32) {
+            return invalid("auth.jwt_secret", "must be set to at least 32 characters in production");
+        }
+        if self.profile == "production" && self.encryption.key.is_none() {
+            return invalid("encryption.key", "must be set in production");
+        }
+        if let Err(crate::error::AppError::Config(message)) = crate::crypto::ConnectionCipher::from_config(&self.encryption) {
+            return invalid("encryption.key", &message);
+        }
+        if self.proposal_policy.break_glass_review_days <= 0 {
+            return invalid("proposal_policy.break_glass_review_days", "must be greater than 0");
+        }
+        if self.proposal_policy.sweep_interval_secs == 0 {
+            return invalid("proposal_policy.sweep_interval_secs", "must be greater than 0");
+        }
+        if let Some(archive) = &self.archive {
+            if archive.bucket.trim().is_empty() {
+                return invalid("archive.bucket", "must not be empty");
+            }
+        }
+        if let Some(summaries) = &self.summaries {
+            if !summaries.llm_endpoint.starts_with("http://") && !summaries.llm_endpoint.starts_with("https://") {
+                return invalid("summaries.llm_endpoint", "must be an http(s) URL");
+            }
+        }
+        if let Some(smtp) = &self.smtp {
+            if !smtp.from_address.contains('@') {
+                return invalid("smtp.from_address", "must be an email address");
+            }
+            if smtp.app_base_url.as_deref().is_some_and(|u| !u.starts_with("http://") && !u.starts_with("https://")) {
+                return invalid("smtp.app_base_url", "must start with http:// or https://");
+            }
+        }
+        if self.audit_export.signing_key.as_deref().is_some_and(|k| k.len() < 32) {
+            return invalid("audit_export.signing_key", "must be at least 32 characters");
+        }
+        if let Some(name) = self.features.keys().find(|k| k.is_empty() || k.contains(char::is_whitespace)) {
+            return invalid(&format!("features.{}", name), "flag names must not be empty or contain spaces");
+        }
+        Ok(())
+    }
+
     /// Parse a DATABASE_URL connection string (postgresql://...)
     fn parse_database_url(url: &str) -> Result<DatabaseConfig, ConfigError> {
         match url::Url::parse(url) {
@@ -161,10 +912,7 @@ impl Settings {
                     user,
                     password,
                     database,
-                    max_pool_size: std::env::var("DB_MAX_CONNECTIONS")
-                        .ok()
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(10),
+                    max_pool_size: DatabaseConfig::default().max_pool_size,
                 })
             }
             Err(_) => Err(ConfigError::InvalidValue(
@@ -191,4 +939,50 @@ mod tests {
         assert_eq!(config.host, "localhost");
         assert_eq!(config.port, 5432);
     }
+
+    /// Layers read from a fresh directory holding the given files
+    fn layers(files: &[(&str, &str)], profile: &str) -> Layers {
+        let dir = std::env::temp_dir().join(format!("schemaflow-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+        let layers = Layers::load(&dir, profile).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        layers
+    }
+
+    #[test]
+    fn test_profile_overrides_default() {
+        let layers = layers(
+            &[
+                ("default.toml", "[pool]\ntarget_max_size = 3\n[features]\ncanary = false\n"),
+                (
+                    "staging.toml",
+                    "[pool]\ntarget_max_size = 8\n[features]\ncanary = true\n[rule_severity.staging]\nR004 = \"error\"\n",
+                ),
+            ],
+            "staging",
+        );
+        let settings = Settings::from_layers(&layers, "staging".to_string()).unwrap();
+        assert_eq!(settings.pool.target_max_size, 8);
+        assert_eq!(settings.features.get("canary"), Some(&true));
+        assert_eq!(settings.rule_severity["staging"].get("R004"), Some(&Severity::Error));
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_value_names_key() {
+        let layers = layers(&[("default.toml", "[storage]\nbackend = \"sqlite\"\n")], "development");
+        let err = Settings::from_layers(&layers, "development".to_string()).unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidKey { ref key, .. } if key == "storage.backend"));
+    }
+
+    #[test]
+    fn test_validate_names_key() {
+        let layers = layers(&[("production.toml", "[auth]\njwt_secret = \"short\"\n")], "production");
+        let settings = Settings::from_layers(&layers, "production".to_string()).unwrap();
+        let err = settings.validate().unwrap_err();
+        assert!(err.to_string().contains("auth.jwt_secret"));
+    }
 }
@@ -0,0 +1,265 @@
+//! Encryption of saved connection strings
+//!
+//! Connection strings saved to a project are sealed with AES-256-GCM before
+//! they reach `saved_connections`. A row stores `nonce || ciphertext || tag`
+//! and the id of the key that sealed it, and the project id is bound in as
+//! associated data so a blob copied to another project's row fails to open.
+//!
+//! Rotating the key: make the new key `encryption.key` and move the old one
+//! to `encryption.previous_keys`. Rows sealed with a previous key still
+//! open, and `reencrypt_saved_connections` (run at startup) reseals them, and
+//! any rows saved before encryption existed, with the current key. Once it
+//! has run the old key can be dropped.
+//!
+//! Without `encryption.key`, new strings are sealed with a fixed development
+//! key and startup migrates nothing, so plaintext rows are not moved under a
+//! key anyone can derive. The development key always stays accepted for
+//! decryption, so rows it sealed are resealed once a real key is configured.
+
+use crate::config::EncryptionConfig;
+use crate::error::AppError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use deadpool_postgres::Pool;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// Seed of the development key used when `encryption.key` is not configured
+const DEV_KEY_SEED: &str = "schemaflow-dev-connection-key-change-in-production";
+
+/// One AES-256 key and its id
+#[derive(Debug)]
+struct Key {
+    id: String,
+    key: LessSafeKey,
+}
+
+impl Key {
+    fn new(bytes: &[u8]) -> Result<Self, AppError> {
+        let unbound = UnboundKey::new(&AES_256_GCM, bytes)
+            .map_err(|_| AppError::Config("must decode to exactly 32 bytes".to_string()))?;
+        Ok(Self {
+            id: key_id(bytes),
+            key: LessSafeKey::new(unbound),
+        })
+    }
+
+    fn development() -> Self {
+        Self::new(&Sha256::digest(DEV_KEY_SEED.as_bytes())).expect("SHA-256 is 32 bytes")
+    }
+
+    fn parse(encoded: &str) -> Result<Self, AppError> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| AppError::Config(format!("is not valid base64: {}", e)))?;
+        Self::new(&bytes)
+    }
+}
+
+/// Short, stable id of a key: the start of its SHA-256, so the key itself is never stored
+fn key_id(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A connection string as stored
+#[derive(Debug, Clone)]
+pub struct SealedConnection {
+    pub key_id: String,
+    pub blob: Vec<u8>,
+}
+
+/// Current key and the retired keys that can still decrypt
+#[derive(Debug)]
+pub struct ConnectionCipher {
+    current: Key,
+    previous: Vec<Key>,
+    rng: SystemRandom,
+}
+
+impl ConnectionCipher {
+    pub fn from_config(config: &EncryptionConfig) -> Result<Self, AppError> {
+        let current = match &config.key {
+            Some(key) => Key::parse(key)?,
+            None => return Ok(Self::development()),
+        };
+        let mut previous = config
+            .previous_keys
+            .iter()
+            .map(|key| {
+                Key::parse(key).map_err(|e| match e {
+                    AppError::Config(m) => AppError::Config(format!("previous key {}", m)),
+                    other => other,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        previous.push(Key::development());
+        Ok(Self {
+            current,
+            previous,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Cipher under the fixed development key
+    pub fn development() -> Self {
+        Self {
+            current: Key::development(),
+            previous: Vec::new(),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Whether new connection strings are sealed with the development key
+    pub fn is_development(&self) -> bool {
+        self.current.id == Key::development().id
+    }
+
+    /// Id of the key new connection strings are sealed with
+    pub fn current_key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    /// Seal a connection string saved to `project_id`
+    pub fn encrypt(&self, project_id: i32, plaintext: &str) -> Result<SealedConnection, AppError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| AppError::Internal("Could not generate a nonce".to_string()))?;
+
+        let mut in_out = plaintext.as_bytes().to_vec();
+        self.current
+            .key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), aad(project_id), &mut in_out)
+            .map_err(|_| AppError::Internal("Could not encrypt the connection string".to_string()))?;
+
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&in_out);
+        Ok(SealedConnection {
+            key_id: self.current.id.clone(),
+            blob,
+        })
+    }
+
+    /// Open a connection string saved to `project_id`
+    pub fn decrypt(&self, project_id: i32, sealed: &SealedConnection) -> Result<String, AppError> {
+        let key = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|k| k.id == sealed.key_id)
+            .ok_or_else(|| {
+                AppError::Internal(format!(
+                    "Connection string was encrypted with key {}, which is not configured",
+                    sealed.key_id
+                ))
+            })?;
+        if sealed.blob.len() < NONCE_LEN {
+            return Err(AppError::Internal("Stored connection string is truncated".to_string()));
+        }
+
+        let (nonce, ciphertext) = sealed.blob.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| AppError::Internal("Stored connection string is truncated".to_string()))?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = key
+            .key
+            .open_in_place(nonce, aad(project_id), &mut in_out)
+            .map_err(|_| AppError::Internal("Stored connection string failed authentication".to_string()))?;
+        String::from_utf8(plaintext.to_vec())
+            .map_err(|_| AppError::Internal("Stored connection string is not UTF-8".to_string()))
+    }
+}
+
+fn aad(project_id: i32) -> Aad<[u8; 4]> {
+    Aad::from(project_id.to_be_bytes())
+}
+
+/// Seal rows still holding a plaintext connection string, and reseal rows
+/// sealed with a previous key; returns how many rows were updated. Rows
+/// whose key is not configured are left alone and logged, and nothing is
+/// touched under the development key.
+pub async fn reencrypt_saved_connections(pool: &Pool, cipher: &ConnectionCipher) -> Result<usize, AppError> {
+    if cipher.is_development() {
+        return Ok(0);
+    }
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT id, project_id, connection_string, connection_string_encrypted, encryption_key_id
+             FROM saved_connections
+             WHERE connection_string_encrypted IS NULL OR encryption_key_id IS DISTINCT FROM $1",
+            &[&cipher.current_key_id()],
+        )
+        .await?;
+
+    let mut updated = 0;
+    for row in rows {
+        let id: i32 = row.get("id");
+        let project_id: i32 = row.get("project_id");
+        let blob: Option<Vec<u8>> = row.get("connection_string_encrypted");
+        let plaintext = match (blob, row.get::<_, Option<String>>("encryption_key_id")) {
+            (Some(blob), Some(key_id)) => match cipher.decrypt(project_id, &SealedConnection { key_id, blob }) {
+                Ok(plaintext) => plaintext,
+                Err(e) => {
+                    warn!("⚠️  Saved connection {} left as is: {}", id, e);
+                    continue;
+                }
+            },
+            _ => match row.get::<_, Option<String>>("connection_string") {
+                Some(plaintext) => plaintext,
+                None => continue,
+            },
+        };
+
+        let sealed = cipher.encrypt(project_id, &plaintext)?;
+        client
+            .execute(
+                "UPDATE saved_connections
+                 SET connection_string_encrypted = $1, encryption_key_id = $2, connection_string = NULL
+                 WHERE id = $3",
+                &[&sealed.blob, &sealed.key_id, &id],
+            )
+            .await?;
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> String {
+        STANDARD.encode([byte; 32])
+    }
+
+    #[test]
+    fn test_round_trip_rotation_and_project_binding() {
+        let old = ConnectionCipher::from_config(&EncryptionConfig { key: Some(key(1)), previous_keys: vec![] }).unwrap();
+        let sealed = old.encrypt(7, "postgres://app:secret@db/prod").unwrap();
+        assert!(!sealed.blob.windows(6).any(|w| w == b"secret"));
+        assert_eq!(old.decrypt(7, &sealed).unwrap(), "postgres://app:secret@db/prod");
+        // Bound to the project it was saved to
+        assert!(old.decrypt(8, &sealed).is_err());
+
+        let rotated = ConnectionCipher::from_config(&EncryptionConfig {
+            key: Some(key(2)),
+            previous_keys: vec![key(1)],
+        })
+        .unwrap();
+        assert_ne!(rotated.current_key_id(), sealed.key_id);
+        assert_eq!(rotated.decrypt(7, &sealed).unwrap(), "postgres://app:secret@db/prod");
+
+        let dropped = ConnectionCipher::from_config(&EncryptionConfig { key: Some(key(2)), previous_keys: vec![] }).unwrap();
+        assert!(dropped.decrypt(7, &sealed).is_err());
+
+        // Rows sealed before a key was configured still open
+        let development = ConnectionCipher::development();
+        assert!(development.is_development() && !dropped.is_development());
+        let sealed = development.encrypt(7, "postgres://app:secret@db/dev").unwrap();
+        assert_eq!(dropped.decrypt(7, &sealed).unwrap(), "postgres://app:secret@db/dev");
+
+        let short = EncryptionConfig { key: Some(STANDARD.encode([0u8; 16])), previous_keys: vec![] };
+        assert!(matches!(ConnectionCipher::from_config(&short), Err(AppError::Config(_))));
+    }
+}
@@ -0,0 +1,202 @@
+//! Versioned migrations for SchemaFlow's own tables
+//!
+//! Internal tables evolve through numbered SQL files embedded at build time
+//! (`migrations/V<n>__<name>.sql`). At startup `run` applies the ones the
+//! database has not seen, in order and each in its own transaction, and
+//! records them in `schemaflow_migrations` with a checksum of their SQL.
+//! Released migrations are immutable: one whose SQL no longer matches its
+//! record stops startup, so schema changes always go in a new file. An
+//! advisory lock keeps instances that start together from racing.
+//!
+//! To change an internal table, add the next `V<n>__<name>.sql` and list it
+//! in `MIGRATIONS`.
+
+use crate::error::AppError;
+use deadpool_postgres::Pool;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// One embedded migration
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Every migration, oldest first
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        sql: include_str!("migrations/V1__baseline.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "encrypt_saved_connections",
+        sql: include_str!("migrations/V2__encrypt_saved_connections.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "object_watches",
+        sql: include_str!("migrations/V3__object_watches.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "execution_plans",
+        sql: include_str!("migrations/V4__execution_plans.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "connection_backups",
+        sql: include_str!("migrations/V5__connection_backups.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "audit_payload_digest",
+        sql: include_str!("migrations/V6__audit_payload_digest.sql"),
+    },
+];
+
+/// Advisory lock key held while migrating
+const LOCK_KEY: i64 = 0x5346_4d49_4752;
+
+/// A migration the database has recorded as applied
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub name: String,
+    pub checksum: String,
+}
+
+/// Apply pending migrations; returns the versions applied
+pub async fn run(pool: &Pool) -> Result<Vec<i32>, AppError> {
+    let mut client = pool.get().await?;
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schemaflow_migrations (
+                version INTEGER PRIMARY KEY,
+                name VARCHAR(255) NOT NULL,
+                checksum VARCHAR(64) NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                execution_ms BIGINT NOT NULL
+            )",
+        )
+        .await?;
+
+    client.execute("SELECT pg_advisory_lock($1)", &[&LOCK_KEY]).await?;
+    let result = apply_pending(&mut client).await;
+    client.execute("SELECT pg_advisory_unlock($1)", &[&LOCK_KEY]).await?;
+    result
+}
+
+async fn apply_pending(client: &mut deadpool_postgres::Client) -> Result<Vec<i32>, AppError> {
+    let applied: Vec<AppliedMigration> = client
+        .query("SELECT version, name, checksum FROM schemaflow_migrations ORDER BY version", &[])
+        .await?
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            name: row.get("name"),
+            checksum: row.get("checksum"),
+        })
+        .collect();
+
+    let pending = plan(MIGRATIONS, &applied)?;
+    let mut versions = Vec::new();
+    for migration in pending {
+        let started = Instant::now();
+        let transaction = client.transaction().await?;
+        transaction.batch_execute(migration.sql).await.map_err(|e| {
+            AppError::Internal(format!("Migration V{} ({}) failed: {}", migration.version, migration.name, e))
+        })?;
+        transaction
+            .execute(
+                "INSERT INTO schemaflow_migrations (version, name, checksum, execution_ms) VALUES ($1, $2, $3, $4)",
+                &[
+                    &migration.version,
+                    &migration.name,
+                    &migration.checksum(),
+                    &(started.elapsed().as_millis() as i64),
+                ],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        info!("🗃️  Applied internal migration V{} ({})", migration.version, migration.name);
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+/// Migrations still to apply, after checking the recorded ones against
+/// `migrations`. Versions recorded by a newer release are left alone.
+pub fn plan<'a>(migrations: &'a [Migration], applied: &[AppliedMigration]) -> Result<Vec<&'a Migration>, AppError> {
+    let known: HashMap<i32, &Migration> = migrations.iter().map(|m| (m.version, m)).collect();
+    for record in applied {
+        match known.get(&record.version) {
+            Some(migration) if migration.checksum() != record.checksum => {
+                return Err(AppError::Internal(format!(
+                    "Internal migration V{} ({}) was changed after it was applied; add a new migration instead",
+                    record.version, record.name
+                )));
+            }
+            Some(_) => {}
+            None => warn!(
+                "⚠️  Internal migration V{} ({}) is not known to this release; was the server downgraded?",
+                record.version, record.name
+            ),
+        }
+    }
+
+    let latest = applied.iter().map(|r| r.version).max().unwrap_or(0);
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| !applied.iter().any(|r| r.version == m.version))
+        .collect();
+    if let Some(missing) = pending.iter().find(|m| m.version < latest) {
+        return Err(AppError::Internal(format!(
+            "Internal migration V{} ({}) is older than the applied V{}; it cannot be applied out of order",
+            missing.version, missing.name, latest
+        )));
+    }
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(migration: &Migration, checksum: Option<&str>) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            checksum: checksum.map_or_else(|| migration.checksum(), str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_plan_applies_new_versions_and_rejects_edited_ones() {
+        let migrations = [
+            Migration { version: 1, name: "baseline", sql: "CREATE TABLE a (id int);" },
+            Migration { version: 2, name: "add_b", sql: "ALTER TABLE a ADD COLUMN b int;" },
+        ];
+
+        let versions = |pending: Vec<&Migration>| pending.iter().map(|m| m.version).collect::<Vec<_>>();
+        assert_eq!(versions(plan(&migrations, &[]).unwrap()), vec![1, 2]);
+        assert_eq!(versions(plan(&migrations, &[applied(&migrations[0], None)]).unwrap()), vec![2]);
+        assert!(plan(&migrations, &[applied(&migrations[0], Some("edited"))]).is_err());
+        assert!(plan(&migrations, &[applied(&migrations[1], None)]).is_err());
+
+        // Embedded versions count up from 1 without gaps
+        assert!(MIGRATIONS.iter().enumerate().all(|(i, m)| m.version == i as i32 + 1));
+    }
+}
@@ -0,0 +1,258 @@
+-- V1: SchemaFlow's metadata tables as of the first versioned release
+--
+-- Every statement tolerates objects created by earlier releases, which set
+-- these tables up with ad-hoc CREATE IF NOT EXISTS at startup.
+
+-- Create roles table (for managing user roles)
+CREATE TABLE IF NOT EXISTS roles (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(50) UNIQUE NOT NULL,
+    description TEXT,
+    permissions TEXT[],
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create users table
+CREATE TABLE IF NOT EXISTS users (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    name VARCHAR(255),
+    avatar_url TEXT,
+    role_id INTEGER DEFAULT 1 REFERENCES roles(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create projects table
+CREATE TABLE IF NOT EXISTS projects (
+    id SERIAL PRIMARY KEY,
+    owner_id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    icon VARCHAR(50),
+    color VARCHAR(7),
+    is_private BOOLEAN DEFAULT true,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Create project_members table
+CREATE TABLE IF NOT EXISTS project_members (
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role VARCHAR(50) NOT NULL DEFAULT 'editor',
+    joined_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(project_id, user_id)
+);
+
+-- Create saved_connections table
+CREATE TABLE IF NOT EXISTS saved_connections (
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    connection_string VARCHAR(1024) NOT NULL,
+    encrypted_password TEXT,
+    database_type VARCHAR(50),
+    connection_name VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create notification_preferences table
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id INTEGER PRIMARY KEY,
+    preferences JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Create project_lint_configs table
+CREATE TABLE IF NOT EXISTS project_lint_configs (
+    project_id INTEGER PRIMARY KEY,
+    config JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_proposal_templates table
+CREATE TABLE IF NOT EXISTS project_proposal_templates (
+    project_id INTEGER PRIMARY KEY,
+    template JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_access_policies table
+CREATE TABLE IF NOT EXISTS project_access_policies (
+    project_id INTEGER PRIMARY KEY,
+    policy JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_review_slas table
+CREATE TABLE IF NOT EXISTS project_review_slas (
+    project_id INTEGER PRIMARY KEY,
+    sla JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_execution_policies table
+CREATE TABLE IF NOT EXISTS project_execution_policies (
+    project_id INTEGER PRIMARY KEY,
+    policy JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_teams table
+CREATE TABLE IF NOT EXISTS project_teams (
+    project_id INTEGER PRIMARY KEY,
+    teams JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_quotas table
+CREATE TABLE IF NOT EXISTS project_quotas (
+    project_id INTEGER PRIMARY KEY,
+    quota JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_feature_flags table
+CREATE TABLE IF NOT EXISTS project_feature_flags (
+    project_id INTEGER PRIMARY KEY,
+    overrides JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create project_data_dictionary table
+CREATE TABLE IF NOT EXISTS project_data_dictionary (
+    project_id INTEGER PRIMARY KEY,
+    dictionary JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Create proposal_rule_evaluations table (history for analytics)
+CREATE TABLE IF NOT EXISTS proposal_rule_evaluations (
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    proposal_id UUID NOT NULL,
+    team VARCHAR(255) NOT NULL,
+    author VARCHAR(255) NOT NULL,
+    safety_score INTEGER NOT NULL,
+    violations JSONB NOT NULL,
+    evaluated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Governance storage (STORAGE_BACKEND=postgres)
+CREATE TABLE IF NOT EXISTS governance_proposal_summaries (
+    id UUID PRIMARY KEY,
+    data JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS governance_audit_log (
+    id UUID PRIMARY KEY,
+    action VARCHAR(64) NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    target_type VARCHAR(64) NOT NULL,
+    target_id VARCHAR(255) NOT NULL,
+    details TEXT,
+    timestamp TIMESTAMPTZ NOT NULL,
+    project_id INTEGER
+);
+
+-- Audit logs created before entries were bound to projects
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS project_id INTEGER;
+
+-- Append order and hash chain; entries from before chaining keep NULL hashes
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS seq BIGSERIAL;
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS previous_hash VARCHAR(64);
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS hash VARCHAR(64);
+CREATE INDEX IF NOT EXISTS idx_governance_audit_log_seq ON governance_audit_log(seq);
+
+-- Set when retention erased an entry's content
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS pruned_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS governance_legal_holds (
+    id UUID PRIMARY KEY,
+    data JSONB NOT NULL,
+    placed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS schema_snapshots (
+    id UUID PRIMARY KEY,
+    connection_id UUID NOT NULL,
+    version BIGINT NOT NULL,
+    captured_at TIMESTAMPTZ NOT NULL,
+    checksum VARCHAR(128) NOT NULL,
+    table_count INTEGER NOT NULL,
+    fk_count INTEGER NOT NULL,
+    index_count INTEGER NOT NULL,
+    data JSONB NOT NULL,
+    UNIQUE(connection_id, version)
+);
+
+CREATE TABLE IF NOT EXISTS schema_snapshot_heads (
+    connection_id UUID PRIMARY KEY,
+    latest_version BIGINT NOT NULL,
+    baseline_id UUID
+);
+
+CREATE TABLE IF NOT EXISTS change_proposals (
+    id UUID PRIMARY KEY,
+    connection_id UUID NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    data JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS execution_journal (
+    execution_id UUID PRIMARY KEY,
+    proposal_id UUID NOT NULL,
+    state VARCHAR(32) NOT NULL,
+    data JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key VARCHAR(512) PRIMARY KEY,
+    method VARCHAR(16) NOT NULL,
+    path TEXT NOT NULL,
+    fingerprint VARCHAR(64) NOT NULL,
+    response_status INTEGER,
+    response_body TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Insert default roles if they don't exist
+INSERT INTO roles (name, description, permissions) VALUES
+    ('admin', 'Administrator with full access', '{}'),
+    ('editor', 'Can edit and manage content', '{}'),
+    ('viewer', 'Read-only access', '{}')
+ON CONFLICT (name) DO NOTHING;
+
+-- Create indexes for performance
+CREATE INDEX IF NOT EXISTS idx_projects_owner_id ON projects(owner_id);
+CREATE INDEX IF NOT EXISTS idx_project_members_project_id ON project_members(project_id);
+CREATE INDEX IF NOT EXISTS idx_saved_connections_project_id ON saved_connections(project_id);
+CREATE INDEX IF NOT EXISTS idx_rule_evaluations_project_time ON proposal_rule_evaluations(project_id, evaluated_at);
+CREATE INDEX IF NOT EXISTS idx_governance_audit_log_timestamp ON governance_audit_log(timestamp);
+CREATE INDEX IF NOT EXISTS idx_governance_audit_log_actor ON governance_audit_log(actor, timestamp);
+CREATE INDEX IF NOT EXISTS idx_change_proposals_connection_id ON change_proposals(connection_id);
+CREATE INDEX IF NOT EXISTS idx_execution_journal_state ON execution_journal(state);
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
@@ -0,0 +1,7 @@
+-- Saved connection strings are sealed by the application (see crypto.rs).
+-- Existing plaintext rows are encrypted at startup, which then clears
+-- connection_string; the column stays only for rows not yet migrated.
+ALTER TABLE saved_connections
+    ADD COLUMN IF NOT EXISTS connection_string_encrypted BYTEA,
+    ADD COLUMN IF NOT EXISTS encryption_key_id VARCHAR(16),
+    ALTER COLUMN connection_string DROP NOT NULL;
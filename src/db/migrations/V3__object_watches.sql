@@ -0,0 +1,13 @@
+-- Users watching a schema (table_name NULL) or a single table on a connection
+CREATE TABLE IF NOT EXISTS object_watches (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    connection_id UUID NOT NULL,
+    schema_name VARCHAR(255) NOT NULL,
+    table_name VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_object_watches_unique
+    ON object_watches (user_id, connection_id, schema_name, COALESCE(table_name, ''));
+CREATE INDEX IF NOT EXISTS idx_object_watches_connection ON object_watches (connection_id);
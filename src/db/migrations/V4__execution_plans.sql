@@ -0,0 +1,11 @@
+-- Staged execution plans; the plan and its stage statuses are kept as JSON
+CREATE TABLE IF NOT EXISTS execution_plans (
+    plan_id UUID PRIMARY KEY,
+    proposal_id UUID NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    data JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_execution_plans_proposal ON execution_plans(proposal_id);
+CREATE INDEX IF NOT EXISTS idx_execution_plans_status ON execution_plans(status);
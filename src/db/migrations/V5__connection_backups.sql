@@ -0,0 +1,17 @@
+-- Whole-database logical backups of user databases, reported by backup
+-- tooling or taken by the backup hook. Projects can require a recent one
+-- before destructive changes run.
+CREATE TABLE IF NOT EXISTS connection_backups (
+    id SERIAL PRIMARY KEY,
+    connection_id UUID NOT NULL,
+    provider VARCHAR(64) NOT NULL,
+    location TEXT NOT NULL,
+    size_bytes BIGINT,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL,
+    recorded_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_connection_backups_connection
+    ON connection_backups (connection_id, completed_at DESC);
@@ -0,0 +1,5 @@
+-- Digest of an audit entry's actor, target id and details. The entry hash
+-- covers the digest rather than the fields themselves, so it survives
+-- retention erasing them. Entries chained before this have none and are
+-- left out of pruning.
+ALTER TABLE governance_audit_log ADD COLUMN IF NOT EXISTS payload_digest VARCHAR(64);
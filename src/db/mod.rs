@@ -1,3 +1,4 @@
+pub mod migrations;
 pub mod queries;
 pub mod service;
 
@@ -170,6 +170,11 @@ impl SqlBuilder {
         format!("\"{}\"", ident.replace('"', "\"\""))
     }
 
+    /// Quote a string literal
+    pub fn quote_literal(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
     /// Build CREATE DATABASE query
     pub fn create_database(name: &str) -> String {
         format!("CREATE DATABASE {}", Self::quote_ident(name))
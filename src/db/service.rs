@@ -3,8 +3,22 @@
 // Provides direct database access for users and projects
 
 use crate::error::AppError;
+use crate::features::FeatureOverrides;
+use crate::notifications::NotificationPreferences;
+use crate::pipeline::access::AccessPolicy;
+use crate::pipeline::analytics::RuleEvaluationRecord;
+use crate::pipeline::backup::{BackupReference, RecordedBackup};
+use crate::pipeline::execution_policy::ExecutionPolicy;
+use crate::pipeline::sla::ReviewSla;
+use crate::pipeline::teams::ProjectTeams;
+use crate::pipeline::watches::ObjectWatch;
+use crate::quota::ProjectQuota;
+use crate::pipeline::template::ProposalTemplate;
+use crate::snapshot::dictionary::DataDictionary;
+use crate::snapshot::LintConfig;
 use deadpool_postgres::Pool;
 use chrono::Utc;
+use uuid::Uuid;
 
 // User record from database
 #[derive(Clone, Debug)]
@@ -147,6 +161,128 @@ impl UserService {
         }))
     }
 
+    // Get a user's notification preferences, if they changed the defaults
+    pub async fn get_notification_preferences(&self, user_id: i32) -> Result<Option<NotificationPreferences>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT preferences FROM notification_preferences WHERE user_id = $1",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid notification preferences for user {}: {}", user_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save a user's notification preferences
+    pub async fn set_notification_preferences(
+        &self,
+        user_id: i32,
+        preferences: &NotificationPreferences,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(preferences)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize notification preferences: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO notification_preferences (user_id, preferences, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (user_id) DO UPDATE SET preferences = EXCLUDED.preferences, updated_at = EXCLUDED.updated_at",
+            &[&user_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Watch a schema, or one of its tables, on a connection; watching it again returns the existing watch
+    pub async fn add_watch(
+        &self,
+        user_id: i32,
+        connection_id: Uuid,
+        schema_name: &str,
+        table_name: Option<&str>,
+    ) -> Result<ObjectWatch, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO object_watches (user_id, connection_id, schema_name, table_name)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT DO NOTHING",
+            &[&user_id, &connection_id, &schema_name, &table_name],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        let row = client.query_one(
+            "SELECT id, user_id, connection_id, schema_name, table_name, created_at FROM object_watches
+             WHERE user_id = $1 AND connection_id = $2 AND schema_name = $3
+               AND COALESCE(table_name, '') = COALESCE($4, '')",
+            &[&user_id, &connection_id, &schema_name, &table_name],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(watch_from_row(&row))
+    }
+
+    // List a user's watches
+    pub async fn list_watches(&self, user_id: i32) -> Result<Vec<ObjectWatch>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT id, user_id, connection_id, schema_name, table_name, created_at FROM object_watches
+             WHERE user_id = $1 ORDER BY connection_id, schema_name, table_name NULLS FIRST",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.iter().map(watch_from_row).collect())
+    }
+
+    // Every watch on a connection
+    pub async fn watches_for_connection(&self, connection_id: Uuid) -> Result<Vec<ObjectWatch>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT id, user_id, connection_id, schema_name, table_name, created_at FROM object_watches
+             WHERE connection_id = $1",
+            &[&connection_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.iter().map(watch_from_row).collect())
+    }
+
+    // Stop watching; false when the user has no such watch
+    pub async fn remove_watch(&self, user_id: i32, watch_id: i32) -> Result<bool, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let deleted = client.execute(
+            "DELETE FROM object_watches WHERE id = $1 AND user_id = $2",
+            &[&watch_id, &user_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(deleted > 0)
+    }
+
     // List all users
     pub async fn list_users(&self) -> Result<Vec<DbUser>, AppError> {
         let client = self.pool.get().await
@@ -170,6 +306,31 @@ impl UserService {
             updated_at: r.get(6),
         }).collect())
     }
+
+    // List users with the admin role
+    pub async fn list_admins(&self) -> Result<Vec<DbUser>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT u.id, u.email, u.password_hash, u.name, u.avatar_url, u.created_at, u.updated_at 
+             FROM users u JOIN roles r ON r.id = u.role_id
+             WHERE r.name = 'admin' ORDER BY u.created_at DESC",
+            &[],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.into_iter().map(|r| DbUser {
+            id: r.get(0),
+            email: r.get(1),
+            password_hash: r.get(2),
+            name: r.get(3),
+            avatar_url: r.get(4),
+            created_at: r.get(5),
+            updated_at: r.get(6),
+        }).collect())
+    }
 }
 
 // Project service for database operations
@@ -268,4 +429,530 @@ impl ProjectService {
             updated_at: r.get(8),
         }))
     }
+
+    // Get the lint settings for a project, if any were saved
+    pub async fn get_lint_config(&self, project_id: i32) -> Result<Option<LintConfig>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT config FROM project_lint_configs WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid lint config for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the lint settings for a project
+    pub async fn set_lint_config(&self, project_id: i32, config: &LintConfig) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(config)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize lint config: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_lint_configs (project_id, config, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET config = EXCLUDED.config, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the proposal template for a project, if one was saved
+    pub async fn get_proposal_template(&self, project_id: i32) -> Result<Option<ProposalTemplate>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT template FROM project_proposal_templates WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid proposal template for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the proposal template for a project
+    pub async fn set_proposal_template(&self, project_id: i32, template: &ProposalTemplate) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(template)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize proposal template: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_proposal_templates (project_id, template, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET template = EXCLUDED.template, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the tag access policy for a project, if one was saved
+    pub async fn get_access_policy(&self, project_id: i32) -> Result<Option<AccessPolicy>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT policy FROM project_access_policies WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid access policy for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the tag access policy for a project
+    pub async fn set_access_policy(&self, project_id: i32, policy: &AccessPolicy) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(policy)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize access policy: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_access_policies (project_id, policy, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET policy = EXCLUDED.policy, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the review SLA for a project, if one was saved
+    pub async fn get_review_sla(&self, project_id: i32) -> Result<Option<ReviewSla>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT sla FROM project_review_slas WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid review SLA for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the review SLA for a project
+    pub async fn set_review_sla(&self, project_id: i32, sla: &ReviewSla) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(sla)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize review SLA: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_review_slas (project_id, sla, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET sla = EXCLUDED.sla, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the execution policy for a project, if one was saved
+    pub async fn get_execution_policy(&self, project_id: i32) -> Result<Option<ExecutionPolicy>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT policy FROM project_execution_policies WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid execution policy for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the execution policy for a project
+    pub async fn set_execution_policy(&self, project_id: i32, policy: &ExecutionPolicy) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(policy)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize execution policy: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_execution_policies (project_id, policy, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET policy = EXCLUDED.policy, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the reviewer teams defined for a project, if any were saved
+    pub async fn get_teams(&self, project_id: i32) -> Result<Option<ProjectTeams>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT teams FROM project_teams WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid teams for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the reviewer teams for a project
+    pub async fn set_teams(&self, project_id: i32, teams: &ProjectTeams) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(teams)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize teams: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_teams (project_id, teams, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET teams = EXCLUDED.teams, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the quota overrides for a project, if an admin saved any
+    pub async fn get_quota(&self, project_id: i32) -> Result<Option<ProjectQuota>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT quota FROM project_quotas WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid quota for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the quota overrides for a project
+    pub async fn set_quota(&self, project_id: i32, quota: &ProjectQuota) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(quota)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize quota: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_quotas (project_id, quota, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET quota = EXCLUDED.quota, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the feature flag overrides for a project, if an admin saved any
+    pub async fn get_feature_overrides(&self, project_id: i32) -> Result<Option<FeatureOverrides>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT overrides FROM project_feature_flags WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid feature flags for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the feature flag overrides for a project
+    pub async fn set_feature_overrides(&self, project_id: i32, overrides: &FeatureOverrides) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(overrides)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize feature flags: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_feature_flags (project_id, overrides, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET overrides = EXCLUDED.overrides, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Get the data dictionary (custom field definitions and values) for a project
+    pub async fn get_data_dictionary(&self, project_id: i32) -> Result<Option<DataDictionary>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let row = client.query_opt(
+            "SELECT dictionary FROM project_data_dictionary WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        row.map(|r| {
+            serde_json::from_value(r.get(0))
+                .map_err(|e| AppError::Internal(format!("Invalid data dictionary for project {}: {}", project_id, e)))
+        })
+        .transpose()
+    }
+
+    // Save the data dictionary for a project
+    pub async fn set_data_dictionary(&self, project_id: i32, dictionary: &DataDictionary) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let value = serde_json::to_value(dictionary)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize data dictionary: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO project_data_dictionary (project_id, dictionary, updated_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET dictionary = EXCLUDED.dictionary, updated_at = EXCLUDED.updated_at",
+            &[&project_id, &value, &Utc::now()],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // IDs of the project owner and every member
+    pub async fn member_ids(&self, project_id: i32) -> Result<Vec<i32>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT owner_id FROM projects WHERE id = $1
+             UNION
+             SELECT user_id FROM project_members WHERE project_id = $1",
+            &[&project_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.into_iter().map(|r| r.get(0)).collect())
+    }
+
+    // IDs of the projects a user owns or is a member of
+    pub async fn project_ids_for_member(&self, user_id: i32) -> Result<Vec<i32>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT id FROM projects WHERE owner_id = $1
+             UNION
+             SELECT project_id FROM project_members WHERE user_id = $1",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.into_iter().map(|r| r.get(0)).collect())
+    }
+
+    // Keep a proposal's rules evaluation for analytics
+    pub async fn record_rule_evaluation(&self, record: &RuleEvaluationRecord) -> Result<(), AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let violations = serde_json::to_value(&record.violations)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize violations: {}", e)))?;
+
+        client.execute(
+            "INSERT INTO proposal_rule_evaluations
+             (project_id, proposal_id, team, author, safety_score, violations, evaluated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &[
+                &record.project_id,
+                &record.proposal_id,
+                &record.team,
+                &record.author,
+                &(record.safety_score as i32),
+                &violations,
+                &record.evaluated_at,
+            ],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(())
+    }
+
+    // Rules evaluations for a project since a point in time, oldest first
+    pub async fn list_rule_evaluations(
+        &self,
+        project_id: i32,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<RuleEvaluationRecord>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT project_id, proposal_id, team, author, safety_score, violations, evaluated_at
+             FROM proposal_rule_evaluations
+             WHERE project_id = $1 AND evaluated_at >= $2
+             ORDER BY evaluated_at",
+            &[&project_id, &since],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        rows.into_iter().map(|r| {
+            let violations = serde_json::from_value(r.get(5))
+                .map_err(|e| AppError::Internal(format!("Invalid stored violations: {}", e)))?;
+            Ok(RuleEvaluationRecord {
+                project_id: r.get(0),
+                proposal_id: r.get(1),
+                team: r.get(2),
+                author: r.get(3),
+                safety_score: r.get::<_, i32>(4).max(0) as u32,
+                violations,
+                evaluated_at: r.get(6),
+            })
+        }).collect()
+    }
+
+    // Record a whole-database backup of a connection's database
+    pub async fn record_backup(
+        &self,
+        connection_id: Uuid,
+        backup: &BackupReference,
+        recorded_by: &str,
+    ) -> Result<RecordedBackup, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let size_bytes = backup.size_bytes.map(|size| size as i64);
+        let row = client.query_one(
+            "INSERT INTO connection_backups
+             (connection_id, provider, location, size_bytes, started_at, completed_at, recorded_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id",
+            &[
+                &connection_id,
+                &backup.provider,
+                &backup.location,
+                &size_bytes,
+                &backup.started_at,
+                &backup.completed_at,
+                &recorded_by,
+            ],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(RecordedBackup {
+            id: row.get(0),
+            connection_id,
+            recorded_by: recorded_by.to_string(),
+            backup: BackupReference { tables: Vec::new(), ..backup.clone() },
+        })
+    }
+
+    // A connection's latest backups, newest first
+    pub async fn list_backups(&self, connection_id: Uuid, limit: i64) -> Result<Vec<RecordedBackup>, AppError> {
+        let client = self.pool.get().await
+            .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))?;
+
+        let rows = client.query(
+            "SELECT id, connection_id, provider, location, size_bytes, started_at, completed_at, recorded_by
+             FROM connection_backups
+             WHERE connection_id = $1
+             ORDER BY completed_at DESC
+             LIMIT $2",
+            &[&connection_id, &limit],
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?;
+
+        Ok(rows.into_iter().map(|r| RecordedBackup {
+            id: r.get(0),
+            connection_id: r.get(1),
+            recorded_by: r.get(7),
+            backup: BackupReference {
+                provider: r.get(2),
+                location: r.get(3),
+                tables: Vec::new(),
+                size_bytes: r.get::<_, Option<i64>>(4).map(|size| size.max(0) as u64),
+                started_at: r.get(5),
+                completed_at: r.get(6),
+            },
+        }).collect())
+    }
+}
+
+fn watch_from_row(row: &tokio_postgres::Row) -> ObjectWatch {
+    ObjectWatch {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        connection_id: row.get("connection_id"),
+        schema_name: row.get("schema_name"),
+        table_name: row.get("table_name"),
+        created_at: row.get("created_at"),
+    }
 }
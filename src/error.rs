@@ -53,6 +53,15 @@ pub enum AppError {
 
     #[error("Forbidden: {0}")]
     Forbidden(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] s3::error::S3Error),
 }
 
 /// Error response structure
@@ -165,6 +174,27 @@ impl IntoResponse for AppError {
                 msg.clone(),
                 None,
             ),
+            AppError::QuotaExceeded(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "QUOTA_EXCEEDED",
+                msg.clone(),
+                None,
+            ),
+            AppError::RateLimited(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "RATE_LIMITED",
+                msg.clone(),
+                None,
+            ),
+            AppError::Storage(e) => {
+                error!("Storage error: {:?}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    "STORAGE_ERROR",
+                    "Archive storage request failed".to_string(),
+                    Some(e.to_string()),
+                )
+            }
         };
 
         let body = Json(ErrorResponse {
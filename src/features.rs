@@ -0,0 +1,207 @@
+//! Feature flags
+//!
+//! Experimental pipeline capabilities ship switched off. Operators turn them
+//! on for the whole deployment in the `[features]` config table, and admins
+//! override that per project, so a capability can be rolled out one project
+//! at a time. A project override wins over the config; flags set in neither
+//! stay off.
+
+use crate::error::AppError;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::warn;
+
+/// A capability that can be switched per deployment and per project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Run a migration on a canary connection before the real target
+    CanaryExecution,
+    /// Approve low-risk proposals on submission
+    AutoApproval,
+    /// Dry-run migrations against clones of the affected tables in a scratch schema
+    TempSchemaDryRun,
+    /// Have the configured LLM rewrite proposal summaries
+    LlmSummaries,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 4] = [
+        Feature::CanaryExecution,
+        Feature::AutoApproval,
+        Feature::TempSchemaDryRun,
+        Feature::LlmSummaries,
+    ];
+
+    /// Name used in config, overrides and responses
+    pub fn key(self) -> &'static str {
+        match self {
+            Feature::CanaryExecution => "canary_execution",
+            Feature::AutoApproval => "auto_approval",
+            Feature::TempSchemaDryRun => "temp_schema_dry_run",
+            Feature::LlmSummaries => "llm_summaries",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::CanaryExecution => "Execute on a canary connection first and stop if it fails",
+            Feature::AutoApproval => "Approve proposals automatically when risk analysis rates them low",
+            Feature::TempSchemaDryRun => "Dry-run migrations against empty clones of the affected tables",
+            Feature::LlmSummaries => "Have the configured language model write proposal summaries for non-DBAs",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.key() == key)
+    }
+}
+
+/// Where a flag's effective value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagSource {
+    /// Neither config nor the project sets it
+    Default,
+    Config,
+    Project,
+}
+
+/// Effective state of one flag
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureStatus {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+/// Per-project overrides; flags left out follow the config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeatureOverrides {
+    pub flags: BTreeMap<String, bool>,
+}
+
+impl FeatureOverrides {
+    /// Reject flags this server does not know
+    pub fn validate(&self) -> Result<(), AppError> {
+        let unknown: Vec<&str> = self
+            .flags
+            .keys()
+            .filter(|k| Feature::from_key(k).is_none())
+            .map(String::as_str)
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::BadRequest(format!(
+            "Unknown feature flag(s): {}; known flags are {}",
+            unknown.join(", "),
+            Feature::ALL.map(Feature::key).join(", ")
+        )))
+    }
+}
+
+/// Deployment-wide flag values from config
+pub struct FeatureFlags {
+    defaults: BTreeMap<String, bool>,
+}
+
+impl FeatureFlags {
+    pub fn new(defaults: BTreeMap<String, bool>) -> Self {
+        for key in defaults.keys().filter(|k| Feature::from_key(k).is_none()) {
+            warn!("⚠️  Unknown feature flag `features.{}` in config is ignored", key);
+        }
+        Self { defaults }
+    }
+
+    /// Resolve every flag against a project's overrides
+    pub fn resolve(&self, overrides: &FeatureOverrides) -> Vec<FeatureStatus> {
+        Feature::ALL
+            .into_iter()
+            .map(|feature| {
+                let (enabled, source) = match (overrides.flags.get(feature.key()), self.defaults.get(feature.key())) {
+                    (Some(&on), _) => (on, FlagSource::Project),
+                    (None, Some(&on)) => (on, FlagSource::Config),
+                    (None, None) => (false, FlagSource::Default),
+                };
+                FeatureStatus {
+                    name: feature.key(),
+                    description: feature.description(),
+                    enabled,
+                    source,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self::new(BTreeMap::new())
+    }
+}
+
+/// Effective flags for a project (or the config alone without one)
+pub async fn resolve(state: &AppState, project_id: Option<i32>) -> Result<Vec<FeatureStatus>, AppError> {
+    let overrides = match project_id {
+        Some(id) => state.project_service.get_feature_overrides(id).await?.unwrap_or_default(),
+        None => FeatureOverrides::default(),
+    };
+    Ok(state.features.resolve(&overrides))
+}
+
+pub async fn is_enabled(state: &AppState, project_id: Option<i32>, feature: Feature) -> Result<bool, AppError> {
+    Ok(resolve(state, project_id)
+        .await?
+        .iter()
+        .any(|status| status.name == feature.key() && status.enabled))
+}
+
+/// Refuse a request that needs a feature the project does not have
+pub async fn require(state: &AppState, project_id: Option<i32>, feature: Feature) -> Result<(), AppError> {
+    if is_enabled(state, project_id, feature).await? {
+        return Ok(());
+    }
+    Err(AppError::Forbidden(format!(
+        "Feature `{}` is not enabled{}",
+        feature.key(),
+        if project_id.is_some() { " for this project" } else { "" }
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_override_wins_over_config() {
+        let flags = FeatureFlags::new(BTreeMap::from([
+            ("auto_approval".to_string(), true),
+            ("canary_execution".to_string(), true),
+        ]));
+        let overrides = FeatureOverrides {
+            flags: BTreeMap::from([("canary_execution".to_string(), false)]),
+        };
+        let resolved = flags.resolve(&overrides);
+        let get = |name: &str| resolved.iter().find(|s| s.name == name).unwrap();
+
+        assert!(get("auto_approval").enabled);
+        assert_eq!(get("auto_approval").source, FlagSource::Config);
+        assert!(!get("canary_execution").enabled);
+        assert_eq!(get("canary_execution").source, FlagSource::Project);
+        assert!(!get("temp_schema_dry_run").enabled);
+        assert_eq!(get("temp_schema_dry_run").source, FlagSource::Default);
+    }
+
+    #[test]
+    fn test_unknown_override_rejected() {
+        let overrides = FeatureOverrides {
+            flags: BTreeMap::from([("warp_drive".to_string(), true)]),
+        };
+        assert!(overrides.validate().is_err());
+    }
+}
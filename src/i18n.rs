@@ -0,0 +1,200 @@
+//! Localized messages
+//!
+//! Risk warnings, recommendations and rule violations carry a stable code and
+//! the values they interpolate alongside their English text. Automation should
+//! key off the code; people read the text.
+//!
+//! Translations are JSON files named after the language (`de.json`,
+//! `pt-br.json`) mapping codes to templates with `{param}` placeholders, loaded
+//! from `LOCALES_DIR`. Rule violations use their rule ID as the code, with
+//! `<id>.suggestion` for the suggestion. Requests choose a language with
+//! `Accept-Language`; anything without a translation stays in English.
+
+use crate::error::AppError;
+use axum::http::{header, HeaderMap};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Language of the built-in message text
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Values a message interpolates, by placeholder name
+pub type Params = BTreeMap<String, String>;
+
+/// Stable identity of a user-facing message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub code: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: Params,
+}
+
+impl Message {
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            params: Params::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Translation catalogs by language
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    /// Lowercased language tag -> code -> template
+    catalogs: HashMap<String, HashMap<String, String>>,
+}
+
+impl Translations {
+    /// English only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or extend) the catalog for a language
+    pub fn insert(&mut self, language: &str, templates: HashMap<String, String>) {
+        self.catalogs
+            .entry(language.to_lowercase())
+            .or_default()
+            .extend(templates);
+    }
+
+    /// Load every `<language>.json` file in `dir`
+    pub fn load_dir(dir: &Path) -> Result<Self, AppError> {
+        let mut translations = Self::new();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| AppError::Internal(format!("Cannot read locales from {}: {}", dir.display(), e)))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(language) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| AppError::Internal(format!("Cannot read {}: {}", path.display(), e)))?;
+            let templates: HashMap<String, String> = serde_json::from_str(&content)
+                .map_err(|e| AppError::Internal(format!("Invalid translation file {}: {}", path.display(), e)))?;
+            translations.insert(language, templates);
+        }
+        Ok(translations)
+    }
+
+    /// Languages with a catalog, plus English
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.catalogs.keys().cloned().collect();
+        languages.push(DEFAULT_LANGUAGE.to_string());
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    /// Best supported language for an `Accept-Language` value, by quality.
+    /// A regional tag falls back to its base language (`fr-CH` -> `fr`).
+    pub fn negotiate(&self, accept_language: Option<&str>) -> String {
+        let mut ranges: Vec<(String, f32)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim().to_lowercase();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equal qualities keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (tag, _) in ranges {
+            let base = tag.split('-').next().unwrap_or(&tag);
+            if base == DEFAULT_LANGUAGE {
+                return DEFAULT_LANGUAGE.to_string();
+            }
+            if self.catalogs.contains_key(&tag) {
+                return tag;
+            }
+            if self.catalogs.contains_key(base) {
+                return base.to_string();
+            }
+        }
+        DEFAULT_LANGUAGE.to_string()
+    }
+
+    /// Language for a request
+    pub fn language_for(&self, headers: &HeaderMap) -> String {
+        self.negotiate(headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()))
+    }
+
+    /// `code` rendered in `language`, if that language translates it
+    pub fn render(&self, language: &str, code: &str, params: &Params) -> Option<String> {
+        let language = language.to_lowercase();
+        let base = language.split('-').next().unwrap_or(&language);
+        let template = self
+            .catalogs
+            .get(&language)
+            .and_then(|c| c.get(code))
+            .or_else(|| self.catalogs.get(base).and_then(|c| c.get(code)))?;
+        Some(interpolate(template, params))
+    }
+
+    /// Replace `text` with its translation, keeping the English when there is none
+    pub fn localize(&self, language: &str, message: &Message, text: &mut String) {
+        if let Some(translated) = self.render(language, &message.code, &message.params) {
+            *text = translated;
+        }
+    }
+}
+
+/// Params from name/value pairs
+pub fn params(pairs: &[(&str, &str)]) -> Params {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+/// Fill `{name}` placeholders; unknown ones are left as written
+fn interpolate(template: &str, params: &Params) -> String {
+    let mut out = template.to_string();
+    for (name, value) in params {
+        out = out.replace(&format!("{{{}}}", name), value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_and_falls_back_to_english() {
+        let mut translations = Translations::new();
+        translations.insert(
+            "de",
+            HashMap::from([("risk.drop_table".to_string(), "Das Löschen von '{table}' ist endgültig".to_string())]),
+        );
+
+        assert_eq!(translations.negotiate(Some("fr-CH, de-AT;q=0.8, en;q=0.5")), "de");
+        assert_eq!(translations.negotiate(Some("en-GB, de;q=0.9")), "en");
+        assert_eq!(translations.negotiate(Some("de;q=0, ja")), "en");
+        assert_eq!(translations.negotiate(None), "en");
+
+        let message = Message::new("risk.drop_table").with("table", "users");
+        let mut text = "Dropping table 'users' is destructive and irreversible".to_string();
+        translations.localize("de-at", &message, &mut text);
+        assert_eq!(text, "Das Löschen von 'users' ist endgültig");
+
+        let mut untranslated = "Dropping column".to_string();
+        translations.localize("de", &Message::new("risk.drop_column"), &mut untranslated);
+        assert_eq!(untranslated, "Dropping column");
+    }
+}
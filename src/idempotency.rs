@@ -0,0 +1,255 @@
+//! Idempotency keys
+//!
+//! Clients may send an `Idempotency-Key` header on proposal creation,
+//! approval and execution. The first request with a key runs normally and its
+//! successful response is stored for 24 hours; repeats with the same key and
+//! body get that stored response back instead of running again. Keys are
+//! scoped to the calling user.
+
+use crate::auth::Claims;
+use crate::error::AppError;
+use crate::state::SharedState;
+use crate::storage::{IdempotencyBackend, MemoryIdempotencyBackend};
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses that were replayed from a stored result
+pub const IDEMPOTENT_REPLAY_HEADER: &str = "idempotent-replayed";
+
+/// How long a completed request's response is kept
+const KEY_TTL_HOURS: i64 = 24;
+
+/// How long a key stays locked by a request that never finished (e.g. a crash)
+const IN_FLIGHT_TTL_MINUTES: i64 = 60;
+
+const MAX_KEY_LEN: usize = 255;
+const MAX_REQUEST_BYTES: usize = 2 * 1024 * 1024;
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Response kept for replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// A key and what it was used for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdempotencyRecord {
+    /// `<user id>:<client key>`
+    pub key: String,
+    pub method: String,
+    pub path: String,
+    /// SHA-256 of method, path and body
+    pub fingerprint: String,
+    /// None while the first request is still running
+    pub response: Option<StoredResponse>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Outcome of claiming a key
+pub enum Reservation {
+    /// The key is new (or had expired); the caller runs the request
+    Acquired,
+    /// The key is in use by an earlier request
+    Existing(IdempotencyRecord),
+}
+
+/// Idempotency key store
+pub struct IdempotencyStore {
+    backend: Arc<dyn IdempotencyBackend>,
+}
+
+impl IdempotencyStore {
+    /// In-memory store
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(MemoryIdempotencyBackend::default()))
+    }
+
+    pub fn with_backend(backend: Arc<dyn IdempotencyBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Claim a key for a request, or return whoever holds it
+    pub async fn reserve(&self, actor: &str, key: &str, method: &str, path: &str, fingerprint: &str) -> Result<Reservation, AppError> {
+        let now = Utc::now();
+        let record = IdempotencyRecord {
+            key: format!("{}:{}", actor, key),
+            method: method.to_string(),
+            path: path.to_string(),
+            fingerprint: fingerprint.to_string(),
+            response: None,
+            created_at: now,
+            expires_at: now + Duration::minutes(IN_FLIGHT_TTL_MINUTES),
+        };
+
+        Ok(match self.backend.reserve(record, now).await? {
+            Some(existing) => Reservation::Existing(existing),
+            None => Reservation::Acquired,
+        })
+    }
+
+    /// Store the response for replay and keep the key for 24 hours
+    pub async fn complete(&self, actor: &str, key: &str, response: StoredResponse) -> Result<(), AppError> {
+        let expires_at = Utc::now() + Duration::hours(KEY_TTL_HOURS);
+        self.backend.complete(&format!("{}:{}", actor, key), response, expires_at).await
+    }
+
+    /// Free a key whose request failed so the client can retry with it
+    pub async fn release(&self, actor: &str, key: &str) -> Result<(), AppError> {
+        self.backend.release(&format!("{}:{}", actor, key)).await
+    }
+
+    /// Drop expired keys; returns how many were removed
+    pub async fn purge_expired(&self) -> Result<usize, AppError> {
+        self.backend.purge_expired(Utc::now()).await
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn fingerprint(method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b" ");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert(IDEMPOTENT_REPLAY_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Route middleware honoring the `Idempotency-Key` header. Must run after
+/// `auth_middleware` so keys can be scoped to the caller.
+pub async fn idempotency_middleware(
+    State(state): State<SharedState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| AppError::BadRequest(format!(
+            "Idempotency-Key must be 1-{} visible ASCII characters", MAX_KEY_LEN
+        )))?
+        .to_string();
+    let Some(actor) = request.extensions().get::<Claims>().map(|c| c.sub.clone()) else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_REQUEST_BYTES)
+        .await
+        .map_err(|_| AppError::BadRequest("Request body is too large".to_string()))?;
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let fingerprint = fingerprint(&method, &path, &bytes);
+
+    match state.idempotency.reserve(&actor, &key, &method, &path, &fingerprint).await? {
+        Reservation::Acquired => {}
+        Reservation::Existing(existing) => {
+            if existing.fingerprint != fingerprint {
+                return Err(AppError::BadRequest(format!(
+                    "Idempotency-Key was already used for a different request ({} {})",
+                    existing.method, existing.path
+                )));
+            }
+            return match existing.response {
+                Some(stored) => Ok(replay(stored)),
+                None => Err(AppError::Conflict(
+                    "A request with this Idempotency-Key is still being processed".to_string(),
+                )),
+            };
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    // Only successes are remembered; after an error the client may retry with the same key
+    if !response.status().is_success() {
+        if let Err(e) = state.idempotency.release(&actor, &key).await {
+            tracing::warn!("Failed to release idempotency key: {}", e);
+        }
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_RESPONSE_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let _ = state.idempotency.release(&actor, &key).await;
+            return Err(AppError::Internal(format!("Failed to read response: {}", e)));
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        body: String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    if let Err(e) = state.idempotency.complete(&actor, &key, stored).await {
+        tracing::warn!("Failed to store idempotent response for {} {}: {}", method, path, e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_key_replays_until_released() {
+        let store = IdempotencyStore::new();
+        let fp = fingerprint("POST", "/api/proposals", b"{}");
+
+        assert!(matches!(store.reserve("1", "k", "POST", "/api/proposals", &fp).await.unwrap(), Reservation::Acquired));
+        // In flight: a repeat sees the record without a response
+        match store.reserve("1", "k", "POST", "/api/proposals", &fp).await.unwrap() {
+            Reservation::Existing(record) => assert!(record.response.is_none()),
+            Reservation::Acquired => panic!("key should be held"),
+        }
+        // Keys are per user
+        assert!(matches!(store.reserve("2", "k", "POST", "/api/proposals", &fp).await.unwrap(), Reservation::Acquired));
+
+        store.complete("1", "k", StoredResponse { status: 200, body: "{}".to_string() }).await.unwrap();
+        match store.reserve("1", "k", "POST", "/api/proposals", &fp).await.unwrap() {
+            Reservation::Existing(record) => assert_eq!(record.response.unwrap().status, 200),
+            Reservation::Acquired => panic!("key should be held"),
+        }
+
+        store.release("2", "k").await.unwrap();
+        assert!(matches!(store.reserve("2", "k", "POST", "/api/proposals", &fp).await.unwrap(), Reservation::Acquired));
+        assert_eq!(store.purge_expired().await.unwrap(), 0);
+    }
+}
@@ -8,10 +8,15 @@ use chrono::{DateTime, Utc};
 use deadpool_postgres::Pool;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::debug;
 use uuid::Uuid;
 
+mod mysql;
+
+pub use mysql::MySqlIntrospector;
+pub(crate) use mysql::query_error as mysql_query_error;
+
 /// Complete schema snapshot at a point in time
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +25,19 @@ pub struct SchemaSnapshot {
     pub connection_id: Uuid,
     pub version: u64,
     pub captured_at: DateTime<Utc>,
+    #[serde(default)]
+    pub database: DatabaseMetadata,
+    #[serde(default)]
+    pub schemas: Vec<Namespace>,
+    /// Installed extensions; database-wide, so never narrowed by a scope
+    #[serde(default)]
+    pub extensions: Vec<Extension>,
+    /// Domains and composite types; absent in snapshots captured before they were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub types: Option<UserTypes>,
+    /// Views and materialized views; absent in snapshots captured before they were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub views: Option<Vec<View>>,
     pub tables: Vec<Table>,
     pub foreign_keys: Vec<ForeignKey>,
     pub indexes: Vec<Index>,
@@ -27,8 +45,34 @@ pub struct SchemaSnapshot {
 }
 
 impl SchemaSnapshot {
-    /// Compute checksum from schema content
-    pub fn compute_checksum(tables: &[Table], foreign_keys: &[ForeignKey], _indexes: &[Index]) -> String {
+    /// A snapshot with no objects, for connections that have never been captured
+    pub fn empty(connection_id: Uuid) -> Self {
+        Self {
+            id: Uuid::nil(),
+            connection_id,
+            version: 0,
+            captured_at: Utc::now(),
+            database: DatabaseMetadata::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: Some(UserTypes::default()),
+            views: Some(Vec::new()),
+            tables: Vec::new(),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        }
+    }
+
+    /// Compute checksum from schema content; column order only counts when strict
+    pub fn compute_checksum(
+        tables: &[Table],
+        foreign_keys: &[ForeignKey],
+        _indexes: &[Index],
+        types: Option<&UserTypes>,
+        views: Option<&[View]>,
+        column_order: ColumnOrder,
+    ) -> String {
         let mut hasher = Sha256::new();
         
         // Hash tables in sorted order for consistency
@@ -41,11 +85,19 @@ impl SchemaSnapshot {
             hasher.update(t.as_bytes());
         }
         
-        // Hash columns
+        // Hash columns: by name, or by position when order counts
         for table in tables {
-            for col in &table.columns {
-                hasher.update(format!("{}.{}.{}:{}", 
-                    table.schema, table.name, col.name, col.data_type).as_bytes());
+            let mut columns: Vec<&Column> = table.columns.iter().collect();
+            match column_order {
+                ColumnOrder::Strict => columns.sort_by_key(|c| c.ordinal_position),
+                ColumnOrder::Semantic => columns.sort_by(|a, b| a.name.cmp(&b.name)),
+            }
+            for (position, col) in columns.into_iter().enumerate() {
+                let column = format!("{}.{}.{}:{}", table.schema, table.name, col.name, col.data_type);
+                match column_order {
+                    ColumnOrder::Strict => hasher.update(format!("{}@{}", column, position).as_bytes()),
+                    ColumnOrder::Semantic => hasher.update(column.as_bytes()),
+                }
             }
         }
         
@@ -55,9 +107,241 @@ impl SchemaSnapshot {
                 fk.constraint_name, fk.referenced_table).as_bytes());
         }
         
+        // Hash domain and composite type definitions
+        if let Some(types) = types {
+            for domain in &types.domains {
+                hasher.update(format!("DOMAIN:{}.{}:{}:{}:{:?}:{:?}",
+                    domain.schema, domain.name, domain.base_type, domain.not_null,
+                    domain.default_value, domain.checks).as_bytes());
+            }
+            for composite in &types.composites {
+                hasher.update(format!("TYPE:{}.{}", composite.schema, composite.name).as_bytes());
+                for attribute in &composite.attributes {
+                    hasher.update(format!(":{}:{}", attribute.name, attribute.data_type).as_bytes());
+                }
+            }
+        }
+        
+        // Hash view definitions
+        for view in views.unwrap_or_default() {
+            hasher.update(format!("VIEW:{}:{}:{}", view.qualified_name(), view.materialized, view.definition).as_bytes());
+        }
+        
         let result = hasher.finalize();
         format!("{:x}", result)
     }
+
+    /// Keep only objects in the given schemas; an empty scope keeps everything.
+    /// Used on snapshots taken before a connection's scope was narrowed.
+    pub fn scoped(mut self, scope: &[String]) -> Self {
+        if scope.is_empty() {
+            return self;
+        }
+        let in_scope = |schema: &str| scope.iter().any(|s| s == schema);
+        self.schemas.retain(|n| in_scope(&n.name));
+        self.tables.retain(|t| in_scope(&t.schema));
+        self.foreign_keys.retain(|fk| in_scope(&fk.source_schema));
+        self.indexes.retain(|i| in_scope(&i.schema));
+        if let Some(types) = &mut self.types {
+            types.domains.retain(|d| in_scope(&d.schema));
+            types.composites.retain(|c| in_scope(&c.schema));
+        }
+        if let Some(views) = &mut self.views {
+            views.retain(|v| in_scope(&v.schema));
+        }
+        // Keep the mode the checksum was computed in
+        let column_order = self.checksum_column_order();
+        self.checksum = Self::compute_checksum(
+            &self.tables,
+            &self.foreign_keys,
+            &self.indexes,
+            self.types.as_ref(),
+            self.views.as_deref(),
+            column_order,
+        );
+        self
+    }
+
+    /// Recompute the checksum for a connection's column order mode
+    pub fn with_column_order(mut self, column_order: ColumnOrder) -> Self {
+        self.checksum = Self::compute_checksum(
+            &self.tables,
+            &self.foreign_keys,
+            &self.indexes,
+            self.types.as_ref(),
+            self.views.as_deref(),
+            column_order,
+        );
+        self
+    }
+
+    fn checksum_column_order(&self) -> ColumnOrder {
+        let strict = Self::compute_checksum(
+            &self.tables,
+            &self.foreign_keys,
+            &self.indexes,
+            self.types.as_ref(),
+            self.views.as_deref(),
+            ColumnOrder::Strict,
+        );
+        match self.checksum == strict {
+            true => ColumnOrder::Strict,
+            false => ColumnOrder::Semantic,
+        }
+    }
+}
+
+/// Whether the order of a table's columns counts as part of its schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnOrder {
+    /// Reordered columns change the checksum and show up in diffs and drift
+    Strict,
+    /// Only names and definitions count; `SELECT *` order is not a change
+    #[default]
+    Semantic,
+}
+
+/// Database-level settings that affect how text is stored and compared
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseMetadata {
+    pub name: String,
+    /// Server encoding (e.g. "UTF8")
+    pub encoding: String,
+    /// Default collation (LC_COLLATE)
+    pub collation: String,
+    /// Default character classification (LC_CTYPE)
+    pub ctype: String,
+}
+
+/// Schema (namespace) representation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespace {
+    pub name: String,
+    pub owner: String,
+}
+
+/// Installed extension
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    pub name: String,
+    pub version: String,
+    /// Schema holding the extension's objects
+    pub schema: String,
+    /// Columns and indexes using the extension's types or operator classes
+    #[serde(default)]
+    pub dependent_objects: i64,
+}
+
+/// User-defined types columns can use
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserTypes {
+    pub domains: Vec<Domain>,
+    pub composites: Vec<CompositeType>,
+}
+
+impl UserTypes {
+    pub fn domain(&self, qualified_name: &str) -> Option<&Domain> {
+        self.domains.iter().find(|d| d.qualified_name() == qualified_name)
+    }
+
+    pub fn composite(&self, qualified_name: &str) -> Option<&CompositeType> {
+        self.composites.iter().find(|c| c.qualified_name() == qualified_name)
+    }
+}
+
+/// Domain: a base type with constraints (`CREATE DOMAIN`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Domain {
+    pub schema: String,
+    pub name: String,
+    /// Underlying type, with modifiers (e.g. `character varying(255)`)
+    pub base_type: String,
+    #[serde(default)]
+    pub not_null: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    /// Collation, when it differs from the base type's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collation: Option<String>,
+    /// Sorted by name
+    #[serde(default)]
+    pub checks: Vec<DomainCheck>,
+}
+
+/// Named CHECK constraint of a domain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainCheck {
+    pub name: String,
+    /// Boolean expression over `VALUE`, without the `CHECK` keyword
+    pub expression: String,
+}
+
+/// Composite type (`CREATE TYPE ... AS (...)`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompositeType {
+    pub schema: String,
+    pub name: String,
+    /// In declaration order
+    pub attributes: Vec<CompositeAttribute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompositeAttribute {
+    pub name: String,
+    pub data_type: String,
+}
+
+impl Domain {
+    /// `schema.name`, as columns of the domain report their type
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+}
+
+impl CompositeType {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+}
+
+/// View or materialized view
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct View {
+    pub schema: String,
+    pub name: String,
+    #[serde(default)]
+    pub materialized: bool,
+    /// The query, as `pg_get_viewdef` prints it
+    pub definition: String,
+    /// Tables and views the query reads, as `schema.name`, sorted
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl View {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+
+    pub fn kind(&self) -> &'static str {
+        if self.materialized { "Materialized view" } else { "View" }
+    }
+}
+
+/// The expression of a `pg_get_constraintdef` CHECK definition
+pub fn check_expression(definition: &str) -> &str {
+    let definition = definition.trim().trim_end_matches(" NOT VALID");
+    definition.strip_prefix("CHECK (").and_then(|rest| rest.strip_suffix(')')).unwrap_or(definition)
 }
 
 /// Table representation
@@ -82,6 +366,17 @@ pub struct Table {
     pub governance: TableGovernance,
 }
 
+impl Table {
+    /// Columns classified as PII (anything above `none`)
+    pub fn pii_columns(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|c| c.pii_classification.as_ref().is_some_and(|l| *l != PiiLevel::None))
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+}
+
 /// Column representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -94,6 +389,9 @@ pub struct Column {
     pub is_primary_key: bool,
     pub is_unique: bool,
     pub ordinal_position: i32,
+    /// Explicit collation, if the column does not use the database default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collation: Option<String>,
     
     // Governance
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -102,6 +400,9 @@ pub struct Column {
     pub description: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Data dictionary custom field values, keyed by field
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_fields: BTreeMap<String, String>,
 }
 
 /// Primary key constraint
@@ -147,8 +448,8 @@ pub struct Position {
     pub y: f64,
 }
 
-/// PII classification levels
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// PII classification levels, weakest first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum PiiLevel {
     None,
@@ -158,6 +459,31 @@ pub enum PiiLevel {
     Secret,         // Highest sensitivity
 }
 
+impl PiiLevel {
+    /// Parse a `pii:<level>` tag
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag.strip_prefix("pii:")? {
+            "none" => Some(PiiLevel::None),
+            "internal" => Some(PiiLevel::Internal),
+            "confidential" => Some(PiiLevel::Confidential),
+            "restricted" => Some(PiiLevel::Restricted),
+            "secret" => Some(PiiLevel::Secret),
+            _ => None,
+        }
+    }
+}
+
+/// Bracketed tags in a column comment, e.g. "Card number [pii:restricted] [encrypted]"
+fn comment_tags(comment: &str) -> Vec<String> {
+    comment
+        .split('[')
+        .skip(1)
+        .filter_map(|part| part.split_once(']'))
+        .map(|(tag, _)| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
 /// Table governance metadata
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -166,37 +492,111 @@ pub struct TableGovernance {
     pub description: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Role owning the table
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retention_days: Option<i32>,
+    /// Explicit grants to roles other than the owner
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acl: Vec<AclEntry>,
+    /// Data dictionary custom field values, keyed by field
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_fields: BTreeMap<String, String>,
+    /// Row-level security; absent in snapshots captured before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_security: Option<RowSecurity>,
+}
+
+/// Privileges one role holds on a table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AclEntry {
+    /// Role name, or `PUBLIC`
+    pub grantee: String,
+    /// Privilege names (`SELECT`, `INSERT`, ...), sorted
+    pub privileges: Vec<String>,
+}
+
+/// Row-level security settings and policies of a table
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RowSecurity {
+    pub enabled: bool,
+    /// Policies apply to the table owner too
+    #[serde(default)]
+    pub forced: bool,
+    /// Sorted by name
+    #[serde(default)]
+    pub policies: Vec<RlsPolicy>,
+}
+
+/// Row-level security policy (`pg_policies`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RlsPolicy {
+    pub name: String,
+    /// Permissive policies are OR-ed together; restrictive ones AND-ed on top
+    pub permissive: bool,
+    /// `ALL`, `SELECT`, `INSERT`, `UPDATE` or `DELETE`
+    pub command: String,
+    /// Roles the policy applies to (`public` for everyone)
+    pub roles: Vec<String>,
+    /// Rows visible to (or affected by) the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub using: Option<String>,
+    /// Rows the command may write
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_check: Option<String>,
 }
 
 /// Schema introspector for PostgreSQL
 pub struct PostgresIntrospector;
 
 impl PostgresIntrospector {
-    /// Introspect the complete schema from a PostgreSQL database
-    pub async fn introspect(pool: &Pool, connection_id: Uuid) -> Result<SchemaSnapshot, AppError> {
+    /// Introspect the schema from a PostgreSQL database, limited to the
+    /// schemas in `scope` (all user schemas when empty)
+    pub async fn introspect(pool: &Pool, connection_id: Uuid, scope: &[String]) -> Result<SchemaSnapshot, AppError> {
         let client = pool.get().await?;
         
+        // Get database encoding and collation
+        let database = Self::get_database_metadata(&client).await?;
+        
+        // Get all schemas
+        let schemas = Self::get_schemas(&client, scope).await?;
+        
+        // Get installed extensions
+        let extensions = Self::get_extensions(&client).await?;
+        
+        // Get domains and composite types
+        let types = Self::get_types(&client, scope).await?;
+        
+        // Get views and materialized views
+        let views = Self::get_views(&client, scope).await?;
+        
         // Get all tables
-        let tables = Self::get_tables(&client).await?;
+        let tables = Self::get_tables(&client, scope).await?;
         
         // Get all foreign keys
-        let foreign_keys = Self::get_foreign_keys(&client).await?;
+        let foreign_keys = Self::get_foreign_keys(&client, scope).await?;
         
         // Get all indexes
-        let indexes = Self::get_indexes(&client).await?;
+        let indexes = Self::get_indexes(&client, scope).await?;
         
         // Compute checksum
-        let checksum = SchemaSnapshot::compute_checksum(&tables, &foreign_keys, &indexes);
+        let checksum =
+            SchemaSnapshot::compute_checksum(&tables, &foreign_keys, &indexes, Some(&types), Some(&views), ColumnOrder::Semantic);
         
         let snapshot = SchemaSnapshot {
             id: Uuid::new_v4(),
             connection_id,
             version: 1, // Will be incremented on save
             captured_at: Utc::now(),
+            database,
+            schemas,
+            extensions,
+            types: Some(types),
+            views: Some(views),
             tables,
             foreign_keys,
             indexes,
@@ -212,26 +612,313 @@ impl PostgresIntrospector {
         Ok(snapshot)
     }
     
+    /// Get encoding and default collation of the current database
+    async fn get_database_metadata(client: &deadpool_postgres::Client) -> Result<DatabaseMetadata, AppError> {
+        let query = r#"
+            SELECT
+                d.datname::text as name,
+                pg_encoding_to_char(d.encoding)::text as encoding,
+                d.datcollate::text as collation,
+                d.datctype::text as ctype
+            FROM pg_database d
+            WHERE d.datname = current_database()
+        "#;
+        
+        let row = client.query_one(query, &[]).await?;
+        
+        Ok(DatabaseMetadata {
+            name: row.get("name"),
+            encoding: row.get("encoding"),
+            collation: row.get("collation"),
+            ctype: row.get("ctype"),
+        })
+    }
+    
+    /// Names of all user schemas, ignoring any scope
+    pub async fn schema_names(pool: &Pool) -> Result<Vec<String>, AppError> {
+        let client = pool.get().await?;
+        let schemas = Self::get_schemas(&client, &[]).await?;
+        Ok(schemas.into_iter().map(|n| n.name).collect())
+    }
+    
+    /// Get all user schemas with their owners
+    async fn get_schemas(client: &deadpool_postgres::Client, scope: &[String]) -> Result<Vec<Namespace>, AppError> {
+        let query = r#"
+            SELECT
+                n.nspname::text as name,
+                pg_get_userbyid(n.nspowner)::text as owner
+            FROM pg_namespace n
+            WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND n.nspname NOT LIKE 'pg_toast%'
+              AND n.nspname NOT LIKE 'pg_temp_%'
+              AND (cardinality($1::text[]) = 0 OR n.nspname::text = ANY($1))
+            ORDER BY n.nspname
+        "#;
+        
+        let rows = client.query(query, &[&scope]).await?;
+        
+        let schemas = rows.iter().map(|row| {
+            Namespace {
+                name: row.get("name"),
+                owner: row.get("owner"),
+            }
+        }).collect();
+        
+        Ok(schemas)
+    }
+    
+    /// Get installed extensions with their versions and how much depends on them
+    async fn get_extensions(client: &deadpool_postgres::Client) -> Result<Vec<Extension>, AppError> {
+        let query = r#"
+            SELECT
+                e.extname::text as name,
+                e.extversion::text as version,
+                n.nspname::text as schema,
+                (
+                    SELECT count(*)
+                    FROM pg_attribute a
+                    JOIN pg_class c ON c.oid = a.attrelid
+                    JOIN pg_depend d ON d.classid = 'pg_type'::regclass AND d.objid = a.atttypid
+                    WHERE d.refclassid = 'pg_extension'::regclass AND d.refobjid = e.oid AND d.deptype = 'e'
+                      AND c.relkind IN ('r', 'p', 'm') AND a.attnum > 0 AND NOT a.attisdropped
+                ) + (
+                    SELECT count(DISTINCT i.indexrelid)
+                    FROM pg_index i
+                    JOIN pg_depend d ON d.classid = 'pg_opclass'::regclass AND d.objid = ANY(i.indclass::oid[])
+                    WHERE d.refclassid = 'pg_extension'::regclass AND d.refobjid = e.oid AND d.deptype = 'e'
+                ) as dependent_objects
+            FROM pg_extension e
+            JOIN pg_namespace n ON n.oid = e.extnamespace
+            ORDER BY e.extname
+        "#;
+        
+        let rows = client.query(query, &[]).await?;
+        
+        let extensions = rows.iter().map(|row| {
+            Extension {
+                name: row.get("name"),
+                version: row.get("version"),
+                schema: row.get("schema"),
+                dependent_objects: row.get("dependent_objects"),
+            }
+        }).collect();
+        
+        Ok(extensions)
+    }
+    
+    /// Get domains and composite types, leaving out those extensions own
+    async fn get_types(client: &deadpool_postgres::Client, scope: &[String]) -> Result<UserTypes, AppError> {
+        let domain_query = r#"
+            SELECT
+                n.nspname::text as schema,
+                t.typname::text as name,
+                format_type(t.typbasetype, t.typtypmod) as base_type,
+                t.typnotnull as not_null,
+                t.typdefault as default_value,
+                CASE WHEN t.typcollation <> bt.typcollation THEN co.collname::text END as collation,
+                (
+                    SELECT json_agg(json_build_object('name', c.conname, 'definition', pg_get_constraintdef(c.oid)) ORDER BY c.conname)
+                    FROM pg_constraint c
+                    WHERE c.contypid = t.oid AND c.contype = 'c'
+                ) as checks
+            FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            JOIN pg_type bt ON bt.oid = t.typbasetype
+            LEFT JOIN pg_collation co ON co.oid = t.typcollation
+            WHERE t.typtype = 'd'
+              AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND (cardinality($1::text[]) = 0 OR n.nspname::text = ANY($1))
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_depend d
+                  WHERE d.classid = 'pg_type'::regclass AND d.objid = t.oid AND d.deptype = 'e'
+              )
+            ORDER BY n.nspname, t.typname
+        "#;
+        
+        let composite_query = r#"
+            SELECT
+                n.nspname::text as schema,
+                t.typname::text as name,
+                (
+                    SELECT json_agg(json_build_object('name', a.attname, 'dataType', format_type(a.atttypid, a.atttypmod)) ORDER BY a.attnum)
+                    FROM pg_attribute a
+                    WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                ) as attributes
+            FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            JOIN pg_class c ON c.oid = t.typrelid
+            WHERE t.typtype = 'c' AND c.relkind = 'c'
+              AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND (cardinality($1::text[]) = 0 OR n.nspname::text = ANY($1))
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_depend d
+                  WHERE d.classid = 'pg_type'::regclass AND d.objid = t.oid AND d.deptype = 'e'
+              )
+            ORDER BY n.nspname, t.typname
+        "#;
+        
+        #[derive(Deserialize)]
+        struct CheckRow {
+            name: String,
+            definition: String,
+        }
+        
+        let mut domains = Vec::new();
+        for row in client.query(domain_query, &[&scope]).await? {
+            let schema: String = row.get("schema");
+            let name: String = row.get("name");
+            let checks: Vec<CheckRow> = row
+                .get::<_, Option<serde_json::Value>>("checks")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| AppError::Internal(format!("Invalid checks for domain {}.{}: {}", schema, name, e)))?
+                .unwrap_or_default();
+            domains.push(Domain {
+                base_type: row.get("base_type"),
+                not_null: row.get("not_null"),
+                default_value: row.get("default_value"),
+                collation: row.get("collation"),
+                checks: checks
+                    .into_iter()
+                    .map(|c| DomainCheck { expression: check_expression(&c.definition).to_string(), name: c.name })
+                    .collect(),
+                schema,
+                name,
+            });
+        }
+        
+        let mut composites = Vec::new();
+        for row in client.query(composite_query, &[&scope]).await? {
+            let schema: String = row.get("schema");
+            let name: String = row.get("name");
+            let attributes: Vec<CompositeAttribute> = row
+                .get::<_, Option<serde_json::Value>>("attributes")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| AppError::Internal(format!("Invalid attributes for type {}.{}: {}", schema, name, e)))?
+                .unwrap_or_default();
+            composites.push(CompositeType { schema, name, attributes });
+        }
+        
+        Ok(UserTypes { domains, composites })
+    }
+    
+    /// Get views and materialized views with the relations they read,
+    /// leaving out those extensions own
+    async fn get_views(client: &deadpool_postgres::Client, scope: &[String]) -> Result<Vec<View>, AppError> {
+        let query = r#"
+            SELECT
+                n.nspname::text as schema,
+                c.relname::text as name,
+                c.relkind = 'm' as materialized,
+                pg_get_viewdef(c.oid) as definition,
+                ARRAY(
+                    SELECT DISTINCT dn.nspname::text || '.' || dc.relname::text
+                    FROM pg_rewrite r
+                    JOIN pg_depend d ON d.classid = 'pg_rewrite'::regclass AND d.objid = r.oid
+                    JOIN pg_class dc ON dc.oid = d.refobjid
+                    JOIN pg_namespace dn ON dn.oid = dc.relnamespace
+                    WHERE r.ev_class = c.oid AND d.refobjid <> c.oid AND dc.relkind IN ('r', 'p', 'v', 'm', 'f')
+                    ORDER BY 1
+                ) as depends_on
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.relkind IN ('v', 'm')
+              AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND (cardinality($1::text[]) = 0 OR n.nspname::text = ANY($1))
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_depend d
+                  WHERE d.classid = 'pg_class'::regclass AND d.objid = c.oid AND d.deptype = 'e'
+              )
+            ORDER BY n.nspname, c.relname
+        "#;
+        
+        let views = client
+            .query(query, &[&scope])
+            .await?
+            .iter()
+            .map(|row| View {
+                schema: row.get("schema"),
+                name: row.get("name"),
+                materialized: row.get("materialized"),
+                definition: row.get::<_, Option<String>>("definition").unwrap_or_default().trim().to_string(),
+                depends_on: row.get("depends_on"),
+            })
+            .collect();
+        
+        Ok(views)
+    }
+    
     /// Get all tables with columns
-    async fn get_tables(client: &deadpool_postgres::Client) -> Result<Vec<Table>, AppError> {
+    pub(crate) async fn get_tables(client: &deadpool_postgres::Client, scope: &[String]) -> Result<Vec<Table>, AppError> {
         // Query for tables
         let table_query = r#"
             SELECT 
                 t.table_schema,
-                t.table_name
+                t.table_name,
+                obj_description(c.oid, 'pg_class') as comment,
+                pg_get_userbyid(c.relowner)::text as owner,
+                (
+                    SELECT json_agg(json_build_object('grantee', g.grantee, 'privileges', g.privileges) ORDER BY g.grantee)
+                    FROM (
+                        SELECT
+                            CASE WHEN a.grantee = 0 THEN 'PUBLIC' ELSE pg_get_userbyid(a.grantee)::text END as grantee,
+                            array_agg(a.privilege_type::text ORDER BY a.privilege_type) as privileges
+                        FROM aclexplode(c.relacl) a
+                        WHERE a.grantee <> c.relowner
+                        GROUP BY 1
+                    ) g
+                ) as acl,
+                c.relrowsecurity as rls_enabled,
+                c.relforcerowsecurity as rls_forced,
+                (
+                    SELECT json_agg(json_build_object(
+                        'name', p.policyname,
+                        'permissive', p.permissive = 'PERMISSIVE',
+                        'command', p.cmd,
+                        'roles', p.roles,
+                        'using', p.qual,
+                        'withCheck', p.with_check
+                    ) ORDER BY p.policyname)
+                    FROM pg_policies p
+                    WHERE p.schemaname = t.table_schema AND p.tablename = t.table_name
+                ) as policies
             FROM information_schema.tables t
+            JOIN pg_namespace n ON n.nspname = t.table_schema
+            JOIN pg_class c ON c.relnamespace = n.oid AND c.relname = t.table_name
             WHERE t.table_schema NOT IN ('pg_catalog', 'information_schema')
               AND t.table_type = 'BASE TABLE'
+              AND (cardinality($1::text[]) = 0 OR t.table_schema::text = ANY($1))
             ORDER BY t.table_schema, t.table_name
         "#;
         
-        let table_rows = client.query(table_query, &[]).await?;
+        let table_rows = client.query(table_query, &[&scope]).await?;
         
         let mut tables = Vec::new();
         
         for row in table_rows {
             let schema: String = row.get("table_schema");
             let name: String = row.get("table_name");
+            let comment: Option<String> = row.get("comment");
+            let owner: String = row.get("owner");
+            // No grants beyond the owner's leaves relacl NULL, and the aggregate with it
+            let acl: Vec<AclEntry> = row
+                .get::<_, Option<serde_json::Value>>("acl")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| AppError::Internal(format!("Invalid ACL for {}.{}: {}", schema, name, e)))?
+                .unwrap_or_default();
+            let policies: Vec<RlsPolicy> = row
+                .get::<_, Option<serde_json::Value>>("policies")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| AppError::Internal(format!("Invalid policies for {}.{}: {}", schema, name, e)))?
+                .unwrap_or_default();
+            let row_security = RowSecurity {
+                enabled: row.get("rls_enabled"),
+                forced: row.get("rls_forced"),
+                policies,
+            };
             
             // Get columns for this table
             let columns = Self::get_columns(client, &schema, &name).await?;
@@ -247,7 +934,14 @@ impl PostgresIntrospector {
                 position: None,
                 color: None,
                 collapsed: false,
-                governance: TableGovernance::default(),
+                governance: TableGovernance {
+                    tags: comment.as_deref().map(comment_tags).unwrap_or_default(),
+                    description: comment,
+                    owner: Some(owner),
+                    acl,
+                    row_security: Some(row_security),
+                    ..Default::default()
+                },
             });
         }
         
@@ -263,10 +957,17 @@ impl PostgresIntrospector {
         let query = r#"
             SELECT 
                 c.column_name,
-                c.data_type,
+                -- Domains and other user-defined types by name, not as their base type or USER-DEFINED
+                CASE
+                    WHEN c.domain_name IS NOT NULL THEN format('%I.%I', c.domain_schema, c.domain_name)
+                    WHEN c.data_type = 'USER-DEFINED' THEN format('%I.%I', c.udt_schema, c.udt_name)
+                    ELSE c.data_type
+                END as data_type,
                 c.is_nullable,
                 c.column_default,
                 c.ordinal_position,
+                c.collation_name::text as collation_name,
+                col_description(format('%I.%I', c.table_schema, c.table_name)::regclass, c.ordinal_position::int) as comment,
                 COALESCE(
                     (SELECT true FROM information_schema.table_constraints tc
                      JOIN information_schema.key_column_usage kcu 
@@ -299,6 +1000,8 @@ impl PostgresIntrospector {
         let rows = client.query(query, &[&schema, &table]).await?;
         
         let columns = rows.iter().map(|row| {
+            let comment: Option<String> = row.get("comment");
+            let tags = comment.as_deref().map(comment_tags).unwrap_or_default();
             Column {
                 name: row.get("column_name"),
                 data_type: row.get("data_type"),
@@ -307,9 +1010,11 @@ impl PostgresIntrospector {
                 ordinal_position: row.get("ordinal_position"),
                 is_primary_key: row.get("is_primary_key"),
                 is_unique: row.get("is_unique"),
-                pii_classification: None,
-                description: None,
-                tags: vec![],
+                collation: row.get("collation_name"),
+                pii_classification: tags.iter().find_map(|t| PiiLevel::from_tag(t)),
+                description: comment,
+                tags,
+                custom_fields: BTreeMap::new(),
             }
         }).collect();
         
@@ -351,7 +1056,7 @@ impl PostgresIntrospector {
     }
     
     /// Get all foreign keys
-    async fn get_foreign_keys(client: &deadpool_postgres::Client) -> Result<Vec<ForeignKey>, AppError> {
+    pub(crate) async fn get_foreign_keys(client: &deadpool_postgres::Client, scope: &[String]) -> Result<Vec<ForeignKey>, AppError> {
         let query = r#"
             SELECT
                 tc.constraint_name,
@@ -375,6 +1080,7 @@ impl PostgresIntrospector {
                 AND tc.table_schema = rc.constraint_schema
             WHERE tc.constraint_type = 'FOREIGN KEY'
                 AND tc.table_schema NOT IN ('pg_catalog', 'information_schema')
+                AND (cardinality($1::text[]) = 0 OR tc.table_schema::text = ANY($1))
             GROUP BY 
                 tc.constraint_name,
                 tc.table_schema,
@@ -386,7 +1092,7 @@ impl PostgresIntrospector {
             ORDER BY tc.table_schema, tc.table_name, tc.constraint_name
         "#;
         
-        let rows = client.query(query, &[]).await?;
+        let rows = client.query(query, &[&scope]).await?;
         
         let foreign_keys = rows.iter().map(|row| {
             ForeignKey {
@@ -406,7 +1112,7 @@ impl PostgresIntrospector {
     }
     
     /// Get all indexes
-    async fn get_indexes(client: &deadpool_postgres::Client) -> Result<Vec<Index>, AppError> {
+    async fn get_indexes(client: &deadpool_postgres::Client, scope: &[String]) -> Result<Vec<Index>, AppError> {
         let query = r#"
             SELECT
                 i.relname as index_name,
@@ -424,11 +1130,12 @@ impl PostgresIntrospector {
             JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ANY(ix.indkey)
             WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
               AND t.relkind = 'r'
+              AND (cardinality($1::text[]) = 0 OR n.nspname::text = ANY($1))
             GROUP BY i.relname, n.nspname, t.relname, ix.indisunique, ix.indisprimary, am.amname
             ORDER BY n.nspname, t.relname, i.relname
         "#;
         
-        let rows = client.query(query, &[]).await?;
+        let rows = client.query(query, &[&scope]).await?;
         
         let indexes = rows.iter().map(|row| {
             Index {
@@ -595,9 +1302,11 @@ mod tests {
                         ordinal_position: 1,
                         is_primary_key: true,
                         is_unique: true,
+                        collation: None,
                         pii_classification: None,
                         description: None,
                         tags: vec![],
+                        custom_fields: BTreeMap::new(),
                     }
                 ],
                 primary_key: None,
@@ -608,8 +1317,8 @@ mod tests {
             }
         ];
         
-        let checksum1 = SchemaSnapshot::compute_checksum(&tables, &[], &[]);
-        let checksum2 = SchemaSnapshot::compute_checksum(&tables, &[], &[]);
+        let checksum1 = SchemaSnapshot::compute_checksum(&tables, &[], &[], None, None, ColumnOrder::Semantic);
+        let checksum2 = SchemaSnapshot::compute_checksum(&tables, &[], &[], None, None, ColumnOrder::Semantic);
         
         assert_eq!(checksum1, checksum2);
     }
@@ -0,0 +1,321 @@
+//! MySQL introspection
+//!
+//! Reads `information_schema` into the same snapshot model as PostgreSQL.
+//! MySQL calls its schemas databases: an unscoped connection covers only the
+//! database in its connection string, and a scope lists other databases on
+//! the same server. There are no domains, composite types, extensions, schema
+//! owners or row security, so those parts of the snapshot stay empty.
+
+use super::{
+    comment_tags, Column, ColumnOrder, DatabaseMetadata, ForeignKey, Index, Namespace, PiiLevel, PrimaryKey,
+    SchemaSnapshot, Table, TableGovernance, UserTypes,
+};
+use crate::error::AppError;
+use chrono::Utc;
+use mysql_async::prelude::*;
+use mysql_async::{Conn, Pool};
+use std::collections::BTreeMap;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Databases every server has, never part of a snapshot
+const SYSTEM_SCHEMAS: [&str; 4] = ["mysql", "information_schema", "performance_schema", "sys"];
+
+/// Table, name, type, nullability, default, extra, position, collation,
+/// comment and key of a column
+type ColumnRow = (String, String, String, String, Option<String>, String, i32, Option<String>, String, String);
+
+/// Constraint, table, column, referenced schema, table and column, and the
+/// update and delete rules of one foreign key column
+type ForeignKeyRow = (String, String, String, String, String, String, String, String);
+
+/// Schema introspector for MySQL
+pub struct MySqlIntrospector;
+
+impl MySqlIntrospector {
+    /// Introspect the schema from a MySQL server, limited to the databases
+    /// in `scope` (the connection's own database when empty)
+    pub async fn introspect(pool: &Pool, connection_id: Uuid, scope: &[String]) -> Result<SchemaSnapshot, AppError> {
+        let mut conn = Self::conn(pool).await?;
+
+        let database = Self::get_database_metadata(&mut conn).await?;
+        let schemas = Self::schemas_in_scope(&mut conn, scope).await?;
+        let mut tables = Self::get_tables(&mut conn, &schemas).await?;
+        let foreign_keys = Self::get_foreign_keys(&mut conn, &schemas).await?;
+        let indexes = Self::get_indexes(&mut conn, &schemas).await?;
+
+        // Key order comes from the PRIMARY index, not the order of the columns
+        for table in &mut tables {
+            let index = indexes.iter().find(|i| i.is_primary && i.schema == table.schema && i.table == table.name);
+            if let (Some(primary_key), Some(index)) = (table.primary_key.as_mut(), index) {
+                primary_key.columns = index.columns.clone();
+            }
+        }
+
+        let types = UserTypes::default();
+        let checksum =
+            SchemaSnapshot::compute_checksum(&tables, &foreign_keys, &indexes, Some(&types), None, ColumnOrder::Semantic);
+
+        let snapshot = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id,
+            version: 1,
+            captured_at: Utc::now(),
+            database,
+            schemas: schemas
+                .into_iter()
+                .map(|name| Namespace { name, owner: String::new() })
+                .collect(),
+            extensions: Vec::new(),
+            types: Some(types),
+            views: None,
+            tables,
+            foreign_keys,
+            indexes,
+            checksum,
+        };
+
+        debug!("Introspected MySQL schema with {} tables, {} FKs, {} indexes",
+            snapshot.tables.len(),
+            snapshot.foreign_keys.len(),
+            snapshot.indexes.len()
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Names of all user databases on the server, ignoring any scope
+    pub async fn schema_names(pool: &Pool) -> Result<Vec<String>, AppError> {
+        let mut conn = Self::conn(pool).await?;
+        conn.query("SELECT SCHEMA_NAME FROM information_schema.SCHEMATA ORDER BY SCHEMA_NAME")
+            .await
+            .map(|names: Vec<String>| {
+                names
+                    .into_iter()
+                    .filter(|n| !SYSTEM_SCHEMAS.contains(&n.as_str()))
+                    .collect()
+            })
+            .map_err(query_error)
+    }
+
+    /// Check out a connection from the pool
+    pub(crate) async fn conn(pool: &Pool) -> Result<Conn, AppError> {
+        pool.get_conn()
+            .await
+            .map_err(|e| AppError::Connection(format!("Failed to connect: {}", e)))
+    }
+
+    /// Databases a scope covers: the listed ones, or the connection's own
+    pub(crate) async fn schemas_in_scope(conn: &mut Conn, scope: &[String]) -> Result<Vec<String>, AppError> {
+        if !scope.is_empty() {
+            return Ok(scope.to_vec());
+        }
+        let current: Option<Option<String>> = conn.query_first("SELECT DATABASE()").await.map_err(query_error)?;
+        Ok(current.flatten().into_iter().collect())
+    }
+
+    /// Character set and default collation of the connection's database;
+    /// MySQL has no separate character classification, so it mirrors the collation
+    async fn get_database_metadata(conn: &mut Conn) -> Result<DatabaseMetadata, AppError> {
+        let row: Option<(Option<String>, String, String)> = conn
+            .query_first("SELECT DATABASE(), @@character_set_database, @@collation_database")
+            .await
+            .map_err(query_error)?;
+        let (name, encoding, collation) = row.unwrap_or_default();
+        Ok(DatabaseMetadata {
+            name: name.unwrap_or_default(),
+            encoding,
+            ctype: collation.clone(),
+            collation,
+        })
+    }
+
+    /// Get all base tables with columns and primary keys
+    pub(crate) async fn get_tables(conn: &mut Conn, schemas: &[String]) -> Result<Vec<Table>, AppError> {
+        let mut tables = Vec::new();
+
+        for schema in schemas {
+            let table_rows: Vec<(String, String)> = conn
+                .exec(
+                    "SELECT TABLE_NAME, TABLE_COMMENT
+                     FROM information_schema.TABLES
+                     WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE'
+                     ORDER BY TABLE_NAME",
+                    (schema,),
+                )
+                .await
+                .map_err(query_error)?;
+
+            let column_rows: Vec<ColumnRow> = conn
+                .exec(
+                    "SELECT TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, EXTRA,
+                            ORDINAL_POSITION, COLLATION_NAME, COLUMN_COMMENT, COLUMN_KEY
+                     FROM information_schema.COLUMNS
+                     WHERE TABLE_SCHEMA = ?
+                     ORDER BY TABLE_NAME, ORDINAL_POSITION",
+                    (schema,),
+                )
+                .await
+                .map_err(query_error)?;
+
+            let mut columns: BTreeMap<String, Vec<Column>> = BTreeMap::new();
+            for (table, name, data_type, nullable, default_value, extra, ordinal_position, collation, comment, key) in column_rows {
+                let comment = Some(comment).filter(|c| !c.is_empty());
+                let tags = comment.as_deref().map(comment_tags).unwrap_or_default();
+                columns.entry(table).or_default().push(Column {
+                    name,
+                    data_type,
+                    nullable: nullable == "YES",
+                    default_value: default_value.map(|d| default_expression(d, &extra)),
+                    is_primary_key: key == "PRI",
+                    is_unique: key == "UNI",
+                    ordinal_position,
+                    collation,
+                    pii_classification: tags.iter().find_map(|t| PiiLevel::from_tag(t)),
+                    description: comment,
+                    tags,
+                    custom_fields: BTreeMap::new(),
+                });
+            }
+
+            for (name, comment) in table_rows {
+                let columns = columns.remove(&name).unwrap_or_default();
+                // The primary key is always the index named PRIMARY; its
+                // columns are in table order until `introspect` reads the key order
+                let mut key_columns: Vec<&Column> = columns.iter().filter(|c| c.is_primary_key).collect();
+                key_columns.sort_by_key(|c| c.ordinal_position);
+                let primary_key = (!key_columns.is_empty()).then(|| PrimaryKey {
+                    constraint_name: "PRIMARY".to_string(),
+                    columns: key_columns.iter().map(|c| c.name.clone()).collect(),
+                });
+                let comment = Some(comment).filter(|c| !c.is_empty());
+
+                tables.push(Table {
+                    name,
+                    schema: schema.clone(),
+                    columns,
+                    primary_key,
+                    position: None,
+                    color: None,
+                    collapsed: false,
+                    governance: TableGovernance {
+                        tags: comment.as_deref().map(comment_tags).unwrap_or_default(),
+                        description: comment,
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+
+        Ok(tables)
+    }
+
+    /// Get all foreign keys, columns in constraint order
+    pub(crate) async fn get_foreign_keys(conn: &mut Conn, schemas: &[String]) -> Result<Vec<ForeignKey>, AppError> {
+        let mut foreign_keys: Vec<ForeignKey> = Vec::new();
+
+        for schema in schemas {
+            let rows: Vec<ForeignKeyRow> = conn
+                .exec(
+                    "SELECT k.CONSTRAINT_NAME, k.TABLE_NAME, k.COLUMN_NAME,
+                            k.REFERENCED_TABLE_SCHEMA, k.REFERENCED_TABLE_NAME, k.REFERENCED_COLUMN_NAME,
+                            r.UPDATE_RULE, r.DELETE_RULE
+                     FROM information_schema.KEY_COLUMN_USAGE k
+                     JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+                       ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+                      AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+                      AND r.TABLE_NAME = k.TABLE_NAME
+                     WHERE k.TABLE_SCHEMA = ? AND k.REFERENCED_TABLE_NAME IS NOT NULL
+                     ORDER BY k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION",
+                    (schema,),
+                )
+                .await
+                .map_err(query_error)?;
+
+            for (constraint_name, table, column, referenced_schema, referenced_table, referenced_column, on_update, on_delete) in rows {
+                match foreign_keys.last_mut() {
+                    Some(fk) if fk.source_schema == *schema && fk.source_table == table && fk.constraint_name == constraint_name => {
+                        fk.source_columns.push(column);
+                        fk.referenced_columns.push(referenced_column);
+                    }
+                    _ => foreign_keys.push(ForeignKey {
+                        constraint_name,
+                        source_schema: schema.clone(),
+                        source_table: table,
+                        source_columns: vec![column],
+                        referenced_schema,
+                        referenced_table,
+                        referenced_columns: vec![referenced_column],
+                        on_update,
+                        on_delete,
+                    }),
+                }
+            }
+        }
+
+        Ok(foreign_keys)
+    }
+
+    /// Get all indexes, the primary key included as `PRIMARY`; expression
+    /// parts of functional indexes have no column and are left out
+    async fn get_indexes(conn: &mut Conn, schemas: &[String]) -> Result<Vec<Index>, AppError> {
+        let mut indexes: Vec<Index> = Vec::new();
+
+        for schema in schemas {
+            let rows: Vec<(String, String, i64, String, Option<String>)> = conn
+                .exec(
+                    "SELECT s.TABLE_NAME, s.INDEX_NAME, s.NON_UNIQUE, s.INDEX_TYPE, s.COLUMN_NAME
+                     FROM information_schema.STATISTICS s
+                     JOIN information_schema.TABLES t
+                       ON t.TABLE_SCHEMA = s.TABLE_SCHEMA AND t.TABLE_NAME = s.TABLE_NAME
+                     WHERE s.TABLE_SCHEMA = ? AND t.TABLE_TYPE = 'BASE TABLE'
+                     ORDER BY s.TABLE_NAME, s.INDEX_NAME, s.SEQ_IN_INDEX",
+                    (schema,),
+                )
+                .await
+                .map_err(query_error)?;
+
+            for (table, name, non_unique, index_type, column) in rows {
+                let continues = matches!(
+                    indexes.last(),
+                    Some(index) if index.schema == *schema && index.table == table && index.name == name
+                );
+                if !continues {
+                    indexes.push(Index {
+                        is_primary: name == "PRIMARY",
+                        name,
+                        schema: schema.clone(),
+                        table,
+                        columns: Vec::new(),
+                        is_unique: non_unique == 0,
+                        index_type: index_type.to_lowercase(),
+                    });
+                }
+                if let Some(index) = indexes.last_mut() {
+                    index.columns.extend(column);
+                }
+            }
+        }
+
+        Ok(indexes)
+    }
+}
+
+/// A column default as SQL: MySQL reports literals unquoted and marks
+/// expressions with DEFAULT_GENERATED, which need parentheses unless they
+/// are CURRENT_TIMESTAMP
+fn default_expression(default: String, extra: &str) -> String {
+    if !extra.contains("DEFAULT_GENERATED") {
+        return format!("'{}'", default.replace('\'', "''"));
+    }
+    if default.to_uppercase().starts_with("CURRENT_TIMESTAMP") {
+        default
+    } else {
+        format!("({})", default)
+    }
+}
+
+/// Wrap a failed MySQL query
+pub(crate) fn query_error(e: mysql_async::Error) -> AppError {
+    AppError::Introspection(format!("MySQL query failed: {}", e))
+}
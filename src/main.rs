@@ -12,23 +12,35 @@
 //! - Stage 3 (Simulate): Risk analysis, dry-run validation, impact assessment
 //! - Stage 4 (Execute): Safe execution with rollback capability
 
+mod activity;
 mod auth;
 mod config;
 mod connection;
+mod crypto;
 mod db;
 mod error;
+mod features;
+mod i18n;
+mod idempotency;
 mod introspection;
 mod models;
+mod notifications;
 mod pipeline;
 mod proposal;
+mod quota;
+mod rate_limit;
 mod routes;
 mod simulation;
 mod snapshot;
 mod state;
+mod storage;
+#[cfg(test)]
+mod testing;
 mod users;
 
 use crate::config::Settings;
 use crate::routes::create_router;
+use crate::snapshot::RulesEngine;
 use crate::state::AppState;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -45,26 +57,127 @@ async fn main() -> anyhow::Result<()> {
 
     // Load configuration
     let settings = Settings::load()?;
-    info!("📋 Configuration loaded successfully");
+    info!("📋 Configuration loaded successfully (profile: {})", settings.profile);
     
-    // Get JWT secret from environment or generate a default (for dev only)
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| {
-            warn!("⚠️  JWT_SECRET not set, using default (INSECURE - set in production!)");
-            "schemaflow-dev-secret-change-in-production".to_string()
+    // Validation refuses a missing secret in production; elsewhere fall back to the dev key
+    let jwt_secret = settings.auth.jwt_secret.clone()
+        .unwrap_or_else(|| {
+            warn!("⚠️  auth.jwt_secret not set, using default (INSECURE - set in production!)");
+            auth::DEV_JWT_SECRET.to_string()
         });
+    auth::configure_tokens(&settings.auth);
+
+    // Validation has checked the keys; without one, connection strings are sealed with a dev key
+    if settings.encryption.key.is_none() {
+        warn!("⚠️  encryption.key not set, saved connection strings use a development key and existing rows are not re-encrypted (INSECURE - set in production!)");
+    }
+    let connection_cipher = crypto::ConnectionCipher::from_config(&settings.encryption)?;
+
+    // Snapshot archive is optional; a bad configuration is fatal
+    let archive = match settings.archive.clone() {
+        Some(config) => {
+            let archive = crate::snapshot::SnapshotArchive::new(config)?;
+            if let Err(e) = archive.apply_lifecycle().await {
+                warn!("⚠️  Could not apply archive lifecycle rules: {}", e);
+            }
+            info!("🗄️  Snapshot archive enabled");
+            Some(archive)
+        }
+        None => None,
+    };
+
+    // Backups before destructive executions; pg_dump keeps its own archive client
+    let backup = match &settings.backup {
+        Some(config) => {
+            let archive = settings.archive.clone().map(crate::snapshot::SnapshotArchive::new).transpose()?;
+            let hook = pipeline::backup::BackupHook::from_config(config, archive)?;
+            info!("💾 Pre-execution backups enabled via {}", hook.provider_name());
+            Some(hook)
+        }
+        None => None,
+    };
+
+    // Email is optional as well; without SMTP settings notifications are dropped
+    let email_sender = match &settings.smtp {
+        Some(config) => {
+            let sender = notifications::EmailSender::new(config)?;
+            info!("📧 Email notifications enabled via {}:{}", config.host, config.port);
+            Some(sender)
+        }
+        None => None,
+    };
+
+    // Summaries stay template-only without a model endpoint
+    let summarizer = match &settings.summaries {
+        Some(config) => {
+            let summarizer = pipeline::summary::LlmSummarizer::new(config)?;
+            info!("📝 Proposal summaries can be written by {} for projects with llm_summaries", config.llm_model);
+            Some(summarizer)
+        }
+        None => None,
+    };
+
+    // Without translations every message stays in English
+    let translations = match &settings.locales_dir {
+        Some(dir) => match i18n::Translations::load_dir(dir) {
+            Ok(translations) => {
+                info!("🌐 Message translations loaded: {}", translations.languages().join(", "));
+                translations
+            }
+            Err(e) => {
+                warn!("⚠️  {}; messages will be in English", e);
+                i18n::Translations::new()
+            }
+        },
+        None => i18n::Translations::new(),
+    };
 
     // Initialize database pool - REQUIRED (no fallback to in-memory)
-    let state = match init_database_pool().await {
+    let state = match init_database_pool(settings.database.max_pool_size).await {
         Ok(pool) => {
             info!("✅ Database pool created successfully");
             
-            // Create tables if they don't exist
-            if let Err(e) = create_database_tables(&pool).await {
-                warn!("⚠️  Warning creating tables: {}", e);
+            // Bring SchemaFlow's own tables up to the current schema
+            match db::migrations::run(&pool).await {
+                Ok(applied) if applied.is_empty() => info!("✅ Database tables are up to date"),
+                Ok(applied) => info!("✅ Database tables migrated ({} migration(s) applied)", applied.len()),
+                Err(e) => {
+                    error!("❌ FATAL: Internal schema migration failed: {}", e);
+                    panic!("Cannot start server with an unmigrated database");
+                }
+            }
+
+            // Seal plaintext connection strings and move rows off retired keys
+            match crypto::reencrypt_saved_connections(&pool, &connection_cipher).await {
+                Ok(0) => {}
+                Ok(n) => info!("🔐 Encrypted {} saved connection string(s) with key {}", n, connection_cipher.current_key_id()),
+                Err(e) => warn!("⚠️  Could not re-encrypt saved connections: {}", e),
             }
             
-            Arc::new(AppState::new(pool, jwt_secret))
+            let notifier = notifications::Notifier::new(
+                email_sender,
+                pool.clone(),
+                settings.smtp.as_ref().and_then(|s| s.app_base_url.clone()),
+            );
+            
+            info!("🗃️  Governance storage backend: {:?}", settings.storage);
+            Arc::new(AppState::new(
+                pool,
+                jwt_secret,
+                settings.proposal_policy.clone(),
+                settings.storage,
+                archive,
+                notifier,
+                settings.quotas.clone(),
+            ).with_translations(translations)
+                .with_features(features::FeatureFlags::new(settings.features.clone()))
+                .with_rules(RulesEngine::new().with_environment_severity(&settings.rule_severity))
+                .with_backup(backup)
+                .with_summarizer(summarizer)
+                .with_audit_export(settings.audit_export.clone())
+                .with_retention(settings.retention.clone())
+                .with_connection_cipher(connection_cipher)
+                .with_target_pools(settings.pool.clone()))
         }
         Err(e) => {
             error!("❌ FATAL: Failed to initialize database pool: {}", e);
@@ -73,6 +186,31 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    // Executions the previous process died in the middle of
+    pipeline::journal::recover_interrupted(&state).await;
+    pipeline::plan::recover_interrupted(&state).await;
+
+    // Expire approvals and close stale drafts in the background
+    pipeline::policy::spawn_policy_sweeper(state.clone());
+
+    // Close pooled connections to user databases once they sit idle
+    if let Some(secs) = settings.pool.target_idle_teardown_secs {
+        info!("🧊 Closing pooled connections idle for {}s", secs);
+    }
+    connection::spawn_pool_reaper(state.clone(), &settings.pool);
+
+    // Write audit exports for the SIEM, if a directory is configured
+    if let Some(directory) = &settings.audit_export.directory {
+        info!("📤 Exporting the audit log to {} every {}s", directory.display(), settings.audit_export.interval_secs);
+    }
+    pipeline::audit_export::spawn_audit_exporter(state.clone(), settings.audit_export.clone());
+
+    // Refresh layers of built semantic maps on their own cadences
+    pipeline::mirror::spawn_semantic_refresher(state.clone(), settings.semantic_map.clone());
+
+    // Drop sandbox databases once they expire
+    pipeline::sandbox::spawn_sandbox_reaper(state.clone());
+
     // Build the router
     let app = create_router(state, &settings);
 
@@ -87,33 +225,125 @@ async fn main() -> anyhow::Result<()> {
     info!("   POST /api/auth/register        - Register new account");
     info!("   POST /api/auth/refresh         - Refresh access token");
     info!("   GET  /api/auth/me              - Get current user");
+    info!("   GET  /api/auth/me/notifications - Get email notification preferences");
+    info!("   PUT  /api/auth/me/notifications - Update email notification preferences");
+    info!("   GET  /api/auth/me/watches - List watched schemas and tables");
+    info!("   POST /api/auth/me/watches - Watch a schema or table");
+    info!("   DELETE /api/auth/me/watches/:id - Stop watching");
+    info!("   POST /api/auth/impersonate/:id - Start read-only impersonation (admin)");
+    info!("   GET  /api/auth/impersonations  - List active impersonation sessions");
+    info!("   DELETE /api/auth/impersonations/:id - End an impersonation session");
+    info!("   GET  /api/users/:id/activity   - Governance activity and contribution stats");
     info!("");
     info!("   ─── Connection Management ───");
     info!("   POST /api/connections          - Connect to a database");
     info!("   GET  /api/connections          - List all connections");
     info!("   POST /api/connections/test     - Test a connection");
-    info!("   GET  /api/schema               - Get schema for active connection");
+    info!("   POST /api/connections/:id/clone - Copy a connection's settings with re-entered credentials");
+    info!("   POST /api/connections/:id/sandboxes - Build a disposable database from a snapshot");
+    info!("   GET  /api/connections/:id/sandboxes - List a connection's sandboxes");
+    info!("   GET  /api/sandboxes/:id        - Get a sandbox");
+    info!("   POST /api/sandboxes/:id/extend - Push back a sandbox's expiry");
+    info!("   DELETE /api/sandboxes/:id      - Drop a sandbox before it expires");
+    info!("   GET  /api/schema               - Schema for the active connection from its latest snapshot (?refresh=true)");
+    info!("   GET  /api/connections/:id/activity - Live sessions and locks");
+    info!("   GET  /api/connections/:id/pool - Pool size, idle connections and warm-up history");
+    info!("   GET  /api/connections/:id/partitioning/candidates - Large append-only tables to partition");
+    info!("   POST /api/connections/:id/partitioning/scaffold - Draft a partitioning proposal");
+    info!("   POST /api/connections/:id/tags/bulk - Draft a proposal tagging every table or column matching a pattern");
+    info!("   PUT  /api/connections/:id/schema-scope - Limit a connection to schemas");
+    info!("   GET  /api/connections/:id/diff-ignore - Patterns left out of diffs and drift");
+    info!("   PUT  /api/connections/:id/diff-ignore - Replace ignore patterns");
+    info!("   PUT  /api/connections/:id/diff-mode - Strict or semantic column order in diffs and drift");
+    info!("   PUT  /api/connections/:id/blast-radius-limits - Default traversal limits for blast radius");
+    info!("   POST /api/connections/:id/backups - Record a whole-database backup (admin)");
+    info!("   GET  /api/connections/:id/backups - Latest recorded backups");
+    info!("   POST /api/connections/:id/type-migrations - Suggest and validate USING expressions");
     info!("");
     info!("   ─── Governance Pipeline ───");
+    info!("   POST /api/connections/:id/semantic-map - Build every layer of the semantic map");
+    info!("   GET  /api/connections/:id/semantic-map - Stored semantic map with per-layer timestamps (?fields=)");
+    info!("   POST /api/connections/:id/semantic-map/layers/:layer - Refresh schema, statistics, dependencies or hot-spots");
     info!("   POST /api/proposals            - Create new proposal");
-    info!("   GET  /api/proposals            - List all proposals");
+    info!("   GET  /api/proposals            - List all proposals (?fields=, ?include=)");
+    info!("   GET  /api/connections/:id/changelog?from=&to= - Markdown changelog of executed proposals and drift");
+    info!("   GET  /api/proposals/:id        - Proposal summary (?fields=, ?include=)");
+    info!("   PATCH /api/proposals/:id       - Edit draft (JSON Patch)");
+    info!("   GET  /api/proposals/:id/migrations - Stored migration versions with checksums");
+    info!("   PUT  /api/proposals/:id/parameters/:env - Store placeholder values for an environment");
     info!("   POST /api/proposals/:id/submit - Submit for review");
-    info!("   POST /api/proposals/:id/approve - Approve (Admin only)");
+    info!("   GET  /api/proposals/:id/blast-radius - Blast radius saved on submission");
+    info!("   POST /api/proposals/:id/approve - Approve (Admin only, or a member of an assigned reviewer team)");
+    info!("   GET  /api/proposals/:id/team-reviews - Approval progress of the assigned reviewer teams");
+    info!("   GET  /api/proposals/:id/statements - Migration statements with sign-offs and open threads");
+    info!("   POST /api/proposals/:id/statements/:index/approve - Sign off one statement (Admin only)");
+    info!("   GET  /api/proposals/:id/checklist - Review checklist from the latest risk analysis");
+    info!("   PUT  /api/proposals/:id/checklist/:item_id - Tick or untick a checklist item (Admin only)");
+    info!("   GET  /api/proposals/:id/comments - Comments with schema object references resolved");
+    info!("   POST /api/proposals/:id/comments/:comment_id/resolve - Resolve or reopen a thread");
     info!("   POST /api/proposals/:id/analyze - Risk analysis");
+    info!("   GET  /api/proposals/:id/risk-history - Risk across revisions");
     info!("   POST /api/proposals/:id/execute - Execute migration");
+    info!("   POST /api/proposals/:id/plan - Split an approved migration into stages");
+    info!("   GET  /api/proposals/:id/plans - Execution plans of a proposal");
+    info!("   GET  /api/plans/:id            - Execution plan and its stages");
+    info!("   POST /api/plans/:id/stages/:n/execute - Run the next stage (again, after a failure)");
+    info!("   POST /api/plans/:id/pause|resume|cancel - Hold, release or stop a plan between stages");
+    info!("   GET  /api/proposals/:id/execution - Live execution progress");
+    info!("   GET  /api/proposals/:id/preflight - Privileges the connection's role needs per statement");
+    info!("   GET  /api/executions/interrupted - Executions cut off by a restart (Admin only)");
+    info!("   POST /api/executions/:id/verify - Check an interrupted execution against the database");
+    info!("   POST /api/proposals/:id/clone  - Clone into a new draft");
+    info!("   POST /api/proposals/:id/revert - Draft a revert of an executed proposal");
+    info!("   POST /api/proposals/:id/rollback - Roll back directly within the rollback window");
+    info!("   POST /api/connections/:id/break-glass - Run an emergency change without review (admin)");
+    info!("   POST /api/proposals/:id/retrospective - Record the retrospective of a break-glass change");
+    info!("   GET  /api/break-glass          - Break-glass changes and their retrospectives (?pending=true)");
+    info!("   POST /api/proposals/:id/share-links - Create a read-only share link");
+    info!("   GET  /api/share/:token         - Public read-only proposal view");
+    info!("   GET  /api/proposals/:id/presence - Who has the proposal open, and its edit lock");
+    info!("   GET  /api/proposals/:id/presence/ws - WebSocket presence channel (?access_token= for browsers)");
+    info!("   GET  /api/proposals/:id/events - WebSocket stream of comments, approvals, status, risk and progress");
+    info!("   POST /api/proposals/:id/lock   - Take a soft or exclusive edit lock on a draft");
+    info!("   DELETE /api/proposals/:id/lock - Release the edit lock");
+    info!("   GET  /api/audit-log/export     - Export the audit log as CEF or signed JSON (Admin only)");
+    info!("   GET  /api/audit-log/verify     - Re-validate the audit log hash chain (Admin only)");
+    info!("   GET  /api/legal-holds          - List legal holds (Admin only)");
+    info!("   POST /api/legal-holds          - Place a legal hold on a proposal or audit range (Admin only)");
+    info!("   POST /api/legal-holds/:id/release - Release a legal hold (Admin only)");
     info!("");
     info!("   ─── Impact Analysis (Core Feature) ───");
     info!("   POST /api/connections/:id/snapshots    - Create schema snapshot");
     info!("   GET  /api/connections/:id/snapshots    - List all snapshots");
-    info!("   GET  /api/connections/:id/snapshots/diff - Compare snapshots");
+    info!("   GET  /api/connections/:id/snapshots/diff - Compare snapshots (?format=sql for migration SQL, ?schema=&table= filters, NDJSON with Accept: application/x-ndjson)");
     info!("   POST /api/connections/:id/blast-radius - Analyze impact of changes");
+    info!("   POST /api/connections/:id/benchmark    - Time introspection on a synthetic schema (admin)");
     info!("   GET  /api/connections/:id/schema-drift - Check drift from baseline");
+    info!("   GET  /api/connections/:id/encryption-report - Sensitive columns stored as plaintext");
+    info!("   GET  /api/connections/:id/docs?format=markdown - Documentation bundle for publishing");
+    info!("   GET  /api/connections/:id/dictionary?field=&value= - Search tables and columns by custom field");
+    info!("   GET  /api/connections/:id/governance/coverage?schema= - Documentation coverage and biggest gaps");
+    info!("   GET  /api/connections/:id/governance/classification-propagation - Columns inheriting PII classification via FKs and views");
+    info!("   POST /api/connections/:id/governance/classification-propagation - Draft a proposal applying those classifications");
+    info!("   POST /api/connections/:id/snapshots/archive - Archive old snapshots");
+    info!("   POST /api/connections/:id/snapshots/restore - Restore archived snapshot");
     info!("   GET  /api/rules                        - List governance rules");
+    info!("   GET  /api/projects/:id/analytics/violations?period=90d - Violation and risk trends");
+    info!("   GET  /api/projects/:id/proposals/board - Proposals by status and risk for a board view");
+    info!("   PUT  /api/projects/:id/access-policy   - Reserve tagged tables for teams");
+    info!("   PUT  /api/projects/:id/review-sla      - Review deadline in business days");
+    info!("   PUT  /api/projects/:id/teams           - Reviewer teams: members, approvals needed, mailbox");
+    info!("   PUT  /api/projects/:id/execution-policy - Server settings applied while migrations run");
+    info!("   GET  /api/projects/:id/quotas          - Quota limits and current usage");
+    info!("   GET  /api/features?projectId=N         - Feature flags in effect");
+    info!("   PUT  /api/projects/:id/features        - Per-project feature flag overrides (admin)");
+    info!("   PUT  /api/projects/:id/custom-fields   - Data dictionary custom fields (admin)");
+    info!("   PUT  /api/projects/:id/tags            - Tag taxonomy: allowed tags, colors, descriptions (admin)");
     info!("");
 
     // Create TCP listener and serve
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app)
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
@@ -141,7 +371,7 @@ fn init_tracing() {
 }
 
 /// Initialize database pool from DATABASE_URL
-async fn init_database_pool() -> anyhow::Result<deadpool_postgres::Pool> {
+async fn init_database_pool(max_size: usize) -> anyhow::Result<deadpool_postgres::Pool> {
     // Load .env file first
     let _ = dotenvy::dotenv();
     
@@ -184,7 +414,7 @@ async fn init_database_pool() -> anyhow::Result<deadpool_postgres::Pool> {
     let use_tls = host_str.contains("neon.tech") || database_url.contains("sslmode=require");
 
     // Create deadpool config
-    use deadpool_postgres::{Config, ManagerConfig, RecyclingMethod};
+    use deadpool_postgres::{Config, ManagerConfig, PoolConfig, RecyclingMethod};
     
     let mut cfg = Config::new();
     cfg.host = Some(host_str.clone());
@@ -195,6 +425,7 @@ async fn init_database_pool() -> anyhow::Result<deadpool_postgres::Pool> {
     cfg.manager = Some(ManagerConfig {
         recycling_method: RecyclingMethod::Fast,
     });
+    cfg.pool = Some(PoolConfig::new(max_size));
 
     // Create pool with TLS support if needed
     let pool = if use_tls {
@@ -231,113 +462,6 @@ async fn init_database_pool() -> anyhow::Result<deadpool_postgres::Pool> {
     Ok(pool)
 }
 
-/// Create database tables if they don't exist
-async fn create_database_tables(pool: &deadpool_postgres::Pool) -> anyhow::Result<()> {
-    let client = pool.get().await?;
-
-    // Create roles table (for managing user roles)
-    client.execute(
-        "CREATE TABLE IF NOT EXISTS roles (
-            id SERIAL PRIMARY KEY,
-            name VARCHAR(50) UNIQUE NOT NULL,
-            description TEXT,
-            permissions TEXT[],
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        &[],
-    ).await?;
-
-    // Create users table
-    client.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            id SERIAL PRIMARY KEY,
-            email VARCHAR(255) UNIQUE NOT NULL,
-            password_hash VARCHAR(255) NOT NULL,
-            name VARCHAR(255),
-            avatar_url TEXT,
-            role_id INTEGER DEFAULT 1 REFERENCES roles(id),
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        &[],
-    ).await?;
-
-    // Create projects table
-    client.execute(
-        "CREATE TABLE IF NOT EXISTS projects (
-            id SERIAL PRIMARY KEY,
-            owner_id INTEGER NOT NULL,
-            name VARCHAR(255) NOT NULL,
-            description TEXT,
-            icon VARCHAR(50),
-            color VARCHAR(7),
-            is_private BOOLEAN DEFAULT true,
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE
-        )",
-        &[],
-    ).await?;
-
-    // Create project_members table
-    client.execute(
-        "CREATE TABLE IF NOT EXISTS project_members (
-            id SERIAL PRIMARY KEY,
-            project_id INTEGER NOT NULL,
-            user_id INTEGER NOT NULL,
-            role VARCHAR(50) NOT NULL DEFAULT 'editor',
-            joined_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-            UNIQUE(project_id, user_id)
-        )",
-        &[],
-    ).await?;
-
-    // Create saved_connections table
-    client.execute(
-        "CREATE TABLE IF NOT EXISTS saved_connections (
-            id SERIAL PRIMARY KEY,
-            project_id INTEGER NOT NULL,
-            connection_string VARCHAR(1024) NOT NULL,
-            encrypted_password TEXT,
-            database_type VARCHAR(50),
-            connection_name VARCHAR(255),
-            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
-        )",
-        &[],
-    ).await?;
-
-    // Insert default roles if they don't exist
-    let _ = client.execute(
-        "INSERT INTO roles (name, description, permissions) VALUES 
-         ('admin', 'Administrator with full access', '{}'),
-         ('editor', 'Can edit and manage content', '{}'),
-         ('viewer', 'Read-only access', '{}')
-         ON CONFLICT (name) DO NOTHING",
-        &[],
-    ).await;
-
-    // Create indexes for performance
-    let _ = client.execute(
-        "CREATE INDEX IF NOT EXISTS idx_projects_owner_id ON projects(owner_id)",
-        &[],
-    ).await;
-    let _ = client.execute(
-        "CREATE INDEX IF NOT EXISTS idx_project_members_project_id ON project_members(project_id)",
-        &[],
-    ).await;
-    let _ = client.execute(
-        "CREATE INDEX IF NOT EXISTS idx_saved_connections_project_id ON saved_connections(project_id)",
-        &[],
-    ).await;
-
-    info!("✅ Database tables initialized");
-    Ok(())
-}
-
 /// Graceful shutdown signal handler
 async fn shutdown_signal() {
     let ctrl_c = async {
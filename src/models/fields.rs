@@ -0,0 +1,135 @@
+//! Sparse field selection
+//!
+//! Large responses accept `?fields=` with a comma-separated list of the
+//! top-level fields to return, e.g. `?fields=title,status`; `id` is always
+//! kept. Where a response is a summary of something bigger, `?include=`
+//! names parts of the full resource to embed alongside it, e.g.
+//! `?include=comments,changes` on proposals. Names are the camelCase keys of
+//! the JSON response; unknown names are rejected rather than ignored, so a
+//! typo does not silently return an empty object.
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldSelection {
+    pub fields: Option<String>,
+    pub include: Option<String>,
+}
+
+fn split(list: Option<&String>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn unknown(parameter: &str, name: &str, expected: &[impl AsRef<str>]) -> AppError {
+    let expected: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+    if expected.is_empty() {
+        AppError::Validation(format!("'{}' is not supported here", parameter))
+    } else {
+        AppError::Validation(format!(
+            "Unknown {} '{}'; expected one of: {}",
+            parameter,
+            name,
+            expected.join(", ")
+        ))
+    }
+}
+
+impl FieldSelection {
+    /// Fields asked for, or None when the whole resource is wanted
+    pub fn fields(&self) -> Option<Vec<String>> {
+        self.fields.as_ref().map(|_| split(self.fields.as_ref()))
+    }
+
+    pub fn include(&self) -> Vec<String> {
+        split(self.include.as_ref())
+    }
+
+    /// Reject names the response does not have. `fields` may name anything
+    /// in `known` or in the include list.
+    pub fn check(&self, known: &[impl AsRef<str>], includable: &[impl AsRef<str>]) -> Result<(), AppError> {
+        let include = self.include();
+        if let Some(name) = include.iter().find(|n| !includable.iter().any(|k| k.as_ref() == n.as_str())) {
+            return Err(unknown("include", name, includable));
+        }
+        if let Some(fields) = self.fields() {
+            if let Some(name) = fields
+                .iter()
+                .find(|n| !known.iter().any(|k| k.as_ref() == n.as_str()) && !include.contains(n))
+            {
+                return Err(unknown("fields", name, known));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the selection to one serialized resource, after merging in the
+    /// parts `included` holds
+    pub fn apply(&self, mut value: Value, included: Map<String, Value>) -> Value {
+        if let Value::Object(map) = &mut value {
+            map.extend(included);
+            if let Some(fields) = self.fields() {
+                let include = self.include();
+                map.retain(|key, _| key == "id" || fields.contains(key) || include.contains(key));
+            }
+        }
+        value
+    }
+
+    /// The included parts of `full`, a serialized full resource
+    pub fn included_from(&self, full: &Value) -> Map<String, Value> {
+        let include = self.include();
+        match full {
+            Value::Object(map) => map
+                .iter()
+                .filter(|(key, _)| include.contains(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            _ => Map::new(),
+        }
+    }
+}
+
+/// Top-level keys `value` serializes with
+pub fn keys<T: Serialize>(value: &T) -> Vec<String> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_selection_trims_and_includes() {
+        let selection = FieldSelection {
+            fields: Some("title, status,".to_string()),
+            include: Some("comments".to_string()),
+        };
+        let known = ["id", "title", "status", "description"];
+        assert!(selection.check(&known, &["comments", "changes"]).is_ok());
+
+        let summary = json!({"id": 1, "title": "t", "status": "draft", "description": "long"});
+        let full = json!({"id": 1, "comments": [{"body": "hi"}], "changes": [1, 2, 3]});
+        let sparse = selection.apply(summary.clone(), selection.included_from(&full));
+        assert_eq!(sparse, json!({"id": 1, "title": "t", "status": "draft", "comments": [{"body": "hi"}]}));
+
+        // Nothing asked for leaves the resource whole
+        let everything = FieldSelection::default();
+        assert_eq!(everything.apply(summary.clone(), Map::new()), summary);
+
+        let typo = FieldSelection { fields: Some("titel".to_string()), include: None };
+        assert!(typo.check(&known, &["comments"]).is_err());
+        let unsupported = FieldSelection { fields: None, include: Some("comments".to_string()) };
+        assert!(unsupported.check(&known, &[] as &[&str]).is_err());
+    }
+}
@@ -3,12 +3,14 @@
 //! Contains all request/response structures used by the API.
 
 pub mod database;
+pub mod fields;
 pub mod foreign_key;
 pub mod project;
 pub mod table;
 
 // Re-export commonly used types
 pub use database::*;
+pub use fields::FieldSelection;
 pub use foreign_key::*;
 pub use project::*;
 pub use table::*;
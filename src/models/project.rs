@@ -2,6 +2,7 @@
 //!
 //! Represents database projects, users, and connections with multi-user support
 
+use crate::connection::ConnectionTarget;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -56,7 +57,9 @@ pub struct SavedConnection {
     pub project_id: i32,
     pub name: String,
     #[serde(skip_serializing)] // Never send encrypted string to client
-    pub connection_string_encrypted: Vec<u8>, // Encrypted blob
+    pub connection_string_encrypted: Vec<u8>, // nonce || AES-256-GCM ciphertext || tag
+    #[serde(skip_serializing)]
+    pub encryption_key_id: String, // Key that sealed it, see crypto.rs
     pub connection_type: String, // "postgres", "mysql", etc.
     pub environment: String, // "development", "staging", "production"
     pub is_active: bool,
@@ -125,7 +128,7 @@ pub struct UpdateProjectRequest {
 }
 
 /// Request to save a database connection (connection string is encrypted server-side)
-#[derive(Debug, Deserialize)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SaveConnectionRequest {
     pub name: String,
@@ -134,6 +137,34 @@ pub struct SaveConnectionRequest {
     pub environment: String,
 }
 
+impl std::fmt::Debug for SaveConnectionRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaveConnectionRequest")
+            .field("name", &self.name)
+            .field("connection_string", &crate::connection::redact_connection_string(&self.connection_string))
+            .field("connection_type", &self.connection_type)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+/// Request to reveal the full connection string of a saved connection
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealConnectionRequest {
+    /// Why the credentials are needed; kept in the audit log
+    pub justification: String,
+}
+
+/// Full connection string, only returned by the audited reveal endpoint
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealedConnection {
+    pub id: i32,
+    pub project_id: i32,
+    pub connection_string: String,
+}
+
 /// Request to share project with another user
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -162,6 +193,11 @@ pub struct ConnectionResponse {
     pub name: String,
     pub connection_type: String,
     pub environment: String,
+    /// Host, database and user of the stored string; credentials are never returned
+    pub target: Option<ConnectionTarget>,
+    /// Why the stored string could not be decrypted, e.g. its key is not configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decryption_error: Option<String>,
     pub is_active: bool,
     pub last_tested: Option<DateTime<Utc>>,
     pub test_status: Option<String>,
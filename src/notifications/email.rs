@@ -0,0 +1,64 @@
+//! SMTP email delivery
+
+use crate::config::{SmtpConfig, SmtpTls};
+use crate::error::AppError;
+use crate::notifications::templates::RenderedEmail;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Sends email through the configured SMTP server
+pub struct EmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailSender {
+    pub fn new(config: &SmtpConfig) -> Result<Self, AppError> {
+        let builder = match config.tls {
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)),
+        }
+        .map_err(|e| AppError::Config(format!("Invalid SMTP host '{}': {}", config.host, e)))?;
+
+        let mut builder = builder.port(config.port);
+        if let Some(username) = &config.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        let address = config
+            .from_address
+            .parse()
+            .map_err(|e| AppError::Config(format!("Invalid SMTP from address '{}': {}", config.from_address, e)))?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from: Mailbox::new(Some(config.from_name.clone()), address),
+        })
+    }
+
+    /// Send a rendered email to one address
+    pub async fn send(&self, to_name: Option<&str>, to_address: &str, email: &RenderedEmail) -> Result<(), AppError> {
+        let address = to_address
+            .parse()
+            .map_err(|e| AppError::BadRequest(format!("Invalid email address '{}': {}", to_address, e)))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(to_name.map(String::from), address))
+            .subject(&email.subject)
+            .multipart(MultiPart::alternative_plain_html(email.text.clone(), email.html.clone()))
+            .map_err(|e| AppError::Internal(format!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Internal(format!("SMTP delivery failed: {}", e)))?;
+
+        Ok(())
+    }
+}
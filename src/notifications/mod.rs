@@ -0,0 +1,234 @@
+//! Notifications Module
+//!
+//! Email notifications for reviewer assignments, approval requests,
+//! execution results, drift alerts, risk score changes, changes to watched objects, and drafts closed as stale. Delivery runs in the background so a
+//! slow or failing SMTP server never fails the request that triggered it.
+
+pub mod email;
+pub mod templates;
+
+pub use email::EmailSender;
+
+use crate::db::UserService;
+use crate::pipeline::orchestrator::ExecutionResult;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::watches::WatchEvent;
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Events that send email
+#[derive(Debug, Clone)]
+pub enum Notification {
+    ReviewerAssigned {
+        proposal: SchemaProposal,
+        assigned_by: String,
+    },
+    ApprovalRequested {
+        proposal: SchemaProposal,
+    },
+    ExecutionFinished {
+        proposal: SchemaProposal,
+        result: Box<ExecutionResult>,
+    },
+    DriftDetected {
+        connection_id: Uuid,
+        connection_name: String,
+        changes: usize,
+        breaking: usize,
+        /// Owner changes among them
+        ownership: usize,
+        /// Grant changes among them
+        permissions: usize,
+        /// Extensions installed, dropped or updated
+        extensions: usize,
+        /// Row-level security settings and policies changed
+        policies: usize,
+        /// Domains and composite types created, dropped or redefined
+        types: usize,
+    },
+    RiskScoreChanged {
+        proposal: SchemaProposal,
+        previous_score: u32,
+        reason: String,
+    },
+    VerificationFailed {
+        proposal: SchemaProposal,
+        mismatches: Vec<String>,
+    },
+    /// Row counts moved during an execution that should have kept them
+    RowCountAnomaly {
+        proposal: SchemaProposal,
+        anomalies: Vec<String>,
+    },
+    ReviewSlaBreached {
+        proposal: SchemaProposal,
+    },
+    /// An admin ran changes through the emergency path without review
+    BreakGlassUsed {
+        proposal: SchemaProposal,
+    },
+    RetrospectiveOverdue {
+        proposal: SchemaProposal,
+    },
+    /// The policy sweeper closed the recipient's draft after `days` without activity
+    StaleDraftClosed {
+        proposal: SchemaProposal,
+        days: i64,
+    },
+    /// A schema or table the recipient watches was proposed for change, drifted or changed
+    WatchedObjectChanged {
+        connection_id: Uuid,
+        connection_name: String,
+        event: WatchEvent,
+        /// The watched objects concerned, "schema" or "schema.table"
+        objects: Vec<String>,
+        /// The proposal behind a proposed or executed change
+        proposal: Option<SchemaProposal>,
+    },
+}
+
+/// Per-user opt-outs; every notification is on by default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationPreferences {
+    pub reviewer_assigned: bool,
+    pub approval_requested: bool,
+    pub execution_results: bool,
+    pub drift_alerts: bool,
+    pub risk_changes: bool,
+    pub watched_objects: bool,
+    pub stale_drafts: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            reviewer_assigned: true,
+            approval_requested: true,
+            execution_results: true,
+            drift_alerts: true,
+            risk_changes: true,
+            watched_objects: true,
+            stale_drafts: true,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, notification: &Notification) -> bool {
+        match notification {
+            Notification::ReviewerAssigned { .. } => self.reviewer_assigned,
+            Notification::ApprovalRequested { .. }
+            | Notification::ReviewSlaBreached { .. }
+            | Notification::RetrospectiveOverdue { .. } => self.approval_requested,
+            // Use of the emergency path is never opted out of
+            Notification::BreakGlassUsed { .. } => true,
+            Notification::ExecutionFinished { .. }
+            | Notification::VerificationFailed { .. }
+            | Notification::RowCountAnomaly { .. } => self.execution_results,
+            Notification::DriftDetected { .. } => self.drift_alerts,
+            Notification::RiskScoreChanged { .. } => self.risk_changes,
+            Notification::WatchedObjectChanged { .. } => self.watched_objects,
+            Notification::StaleDraftClosed { .. } => self.stale_drafts,
+        }
+    }
+}
+
+/// Who receives a notification
+#[derive(Debug, Clone)]
+pub enum Audience {
+    /// User ids or email addresses, as stored on proposals
+    Users(Vec<String>),
+    /// Users with the admin role
+    Admins,
+}
+
+/// Dispatches notifications to the users who have not opted out
+#[derive(Clone)]
+pub struct Notifier {
+    sender: Option<Arc<EmailSender>>,
+    pool: Pool,
+    app_base_url: Option<String>,
+}
+
+impl Notifier {
+    pub fn new(sender: Option<EmailSender>, pool: Pool, app_base_url: Option<String>) -> Self {
+        Self {
+            sender: sender.map(Arc::new),
+            pool,
+            app_base_url,
+        }
+    }
+
+    /// Queue a notification; returns immediately
+    pub fn notify(&self, notification: Notification, audience: Audience) {
+        let Some(sender) = self.sender.clone() else {
+            debug!("Email is not configured; dropping {:?} notification", std::mem::discriminant(&notification));
+            return;
+        };
+        let users = UserService::new(self.pool.clone());
+        let base_url = self.app_base_url.clone();
+
+        tokio::spawn(async move {
+            let recipients = match resolve(&users, audience).await {
+                Ok(recipients) => recipients,
+                Err(e) => {
+                    warn!("Could not resolve notification recipients: {}", e);
+                    return;
+                }
+            };
+
+            for (user_id, name, address) in recipients {
+                if let Some(user_id) = user_id {
+                    match users.get_notification_preferences(user_id).await {
+                        Ok(prefs) if !prefs.as_ref().is_none_or(|p| p.allows(&notification)) => continue,
+                        Ok(_) => {}
+                        Err(e) => warn!("Could not load notification preferences for user {}: {}", user_id, e),
+                    }
+                }
+
+                let email = templates::render(&notification, name.as_deref().unwrap_or("there"), base_url.as_deref());
+                if let Err(e) = sender.send(name.as_deref(), &address, &email).await {
+                    warn!("Failed to email {}: {}", address, e);
+                }
+            }
+        });
+    }
+}
+
+/// Resolve an audience to (user id, name, address) triples
+async fn resolve(
+    users: &UserService,
+    audience: Audience,
+) -> Result<Vec<(Option<i32>, Option<String>, String)>, crate::error::AppError> {
+    let mut recipients = Vec::new();
+
+    match audience {
+        Audience::Admins => {
+            for user in users.list_admins().await? {
+                recipients.push((Some(user.id), user.name, user.email));
+            }
+        }
+        Audience::Users(ids) => {
+            for id in ids {
+                let user = match id.parse::<i32>() {
+                    Ok(user_id) => users.find_by_id(user_id).await?,
+                    Err(_) => users.find_by_email(&id).await?,
+                };
+                match user {
+                    Some(user) => recipients.push((Some(user.id), user.name, user.email)),
+                    // Unknown addresses still get mail but have no preferences
+                    None if id.contains('@') => recipients.push((None, None, id)),
+                    None => debug!("Skipping unknown notification recipient '{}'", id),
+                }
+            }
+        }
+    }
+
+    recipients.sort_by(|a, b| a.2.cmp(&b.2));
+    recipients.dedup_by(|a, b| a.2 == b.2);
+    Ok(recipients)
+}
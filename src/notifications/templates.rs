@@ -0,0 +1,322 @@
+//! Email templates
+//!
+//! Each notification renders to a subject, an HTML body, and a plain-text
+//! alternative for clients that do not display HTML.
+
+use crate::notifications::Notification;
+use crate::pipeline::summary;
+use crate::pipeline::watches::WatchEvent;
+
+/// A rendered email ready to send
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Render a notification for one recipient
+pub fn render(notification: &Notification, recipient_name: &str, base_url: Option<&str>) -> RenderedEmail {
+    let link = |path: String| base_url.map(|base| format!("{}{}", base.trim_end_matches('/'), path));
+
+    match notification {
+        Notification::ReviewerAssigned { proposal, assigned_by } => {
+            let subject = format!("Review requested: {}", proposal.title);
+            let lines = vec![
+                format!("{} added you as a reviewer on \"{}\".", assigned_by, proposal.title),
+                format!("The proposal contains {} change(s).", proposal.changes.len()),
+                summary::current(proposal).text,
+            ];
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Open proposal")
+        }
+        Notification::ApprovalRequested { proposal } => {
+            let subject = format!("Approval needed: {}", proposal.title);
+            let mut lines = vec![format!(
+                "\"{}\" by {} was submitted for review and is waiting for approval.",
+                proposal.title, proposal.created_by
+            )];
+            if let Some(risk) = &proposal.risk_analysis {
+                lines.push(format!("Risk score: {} ({:?}).", risk.score, risk.overall_risk));
+            }
+            lines.push(summary::current(proposal).text);
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Review proposal")
+        }
+        Notification::ExecutionFinished { proposal, result } => {
+            let outcome = if result.success { "succeeded" } else { "failed" };
+            let subject = format!("Execution {}: {}", outcome, proposal.title);
+            let mut lines = vec![format!(
+                "The migration for \"{}\" {} after {} ms ({} statement(s)).",
+                proposal.title,
+                outcome,
+                result.duration_ms,
+                result.executed_statements.len()
+            )];
+            if let Some(error) = &result.error {
+                lines.push(format!("Error: {}", error));
+            }
+            if let Some(forensics) = &result.forensics {
+                if let Some(code) = &forensics.error.code {
+                    lines.push(format!("SQLSTATE {}; {} lock holder(s) captured.", code, forensics.lock_holders.len()));
+                }
+            }
+            if let Some(row_counts) = result.row_counts.as_ref().filter(|v| !v.passed()) {
+                lines.push(format!("Row counts changed unexpectedly in {} table(s).", row_counts.anomalies.len()));
+            }
+            if !result.success && result.checkpoint > 0 {
+                lines.push(format!(
+                    "{} of {} statement(s) are committed; the execution can be resumed from there.",
+                    result.checkpoint, result.total_statements
+                ));
+            }
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "View execution")
+        }
+        Notification::DriftDetected {
+            connection_id,
+            connection_name,
+            changes,
+            breaking,
+            ownership,
+            permissions,
+            extensions,
+            policies,
+            types,
+        } => {
+            let subject = format!("Schema drift detected on {}", connection_name);
+            let mut lines = vec![format!(
+                "The live schema of {} no longer matches its baseline: {} change(s) found.",
+                connection_name, changes
+            )];
+            if *breaking > 0 {
+                lines.push(format!("{} of them are breaking.", breaking));
+            }
+            if *ownership > 0 {
+                lines.push(format!("{} object(s) changed owner.", ownership));
+            }
+            if *permissions > 0 {
+                lines.push(format!("Grants changed on {} table(s).", permissions));
+            }
+            if *extensions > 0 {
+                lines.push(format!("{} extension(s) were installed, dropped or updated.", extensions));
+            }
+            if *policies > 0 {
+                lines.push(format!("Row-level security changed in {} place(s).", policies));
+            }
+            if *types > 0 {
+                lines.push(format!("{} domain(s) or composite type(s) were created, dropped or redefined.", types));
+            }
+            build(subject, recipient_name, &lines, link(format!("/connections/{}/drift", connection_id)), "Inspect drift")
+        }
+        Notification::RiskScoreChanged { proposal, previous_score, reason } => {
+            let subject = format!("Risk changed: {}", proposal.title);
+            let mut lines = vec![format!("Risk for \"{}\" was re-analyzed after {}.", proposal.title, reason)];
+            if let Some(risk) = &proposal.risk_analysis {
+                lines.push(format!(
+                    "Score moved from {} to {} ({:?}).",
+                    previous_score, risk.score, risk.overall_risk
+                ));
+            }
+            lines.push(summary::current(proposal).text);
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Review proposal")
+        }
+        Notification::VerificationFailed { proposal, mismatches } => {
+            let subject = format!("Verification failed: {}", proposal.title);
+            let mut lines = vec![format!(
+                "\"{}\" executed, but the resulting schema does not match its changes ({} difference(s)):",
+                proposal.title,
+                mismatches.len()
+            )];
+            lines.extend(mismatches.iter().take(10).cloned());
+            if mismatches.len() > 10 {
+                lines.push(format!("...and {} more.", mismatches.len() - 10));
+            }
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Inspect proposal")
+        }
+        Notification::RowCountAnomaly { proposal, anomalies } => {
+            let subject = format!("Unexpected row count change: {}", proposal.title);
+            let mut lines = vec![format!(
+                "\"{}\" should have left every row in place, but row counts changed in {} table(s):",
+                proposal.title,
+                anomalies.len()
+            )];
+            lines.extend(anomalies.iter().cloned());
+            lines.push("Check for triggers or cascades the migration set off.".to_string());
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "View execution")
+        }
+        Notification::ReviewSlaBreached { proposal } => {
+            let subject = format!("Review overdue: {}", proposal.title);
+            let mut lines = vec![format!(
+                "\"{}\" has been waiting for review past its SLA deadline.",
+                proposal.title
+            )];
+            if let (Some(submitted), Some(due)) = (proposal.submitted_at, proposal.review_due_at) {
+                lines.push(format!(
+                    "Submitted {}, due {}.",
+                    submitted.format("%Y-%m-%d %H:%M UTC"),
+                    due.format("%Y-%m-%d %H:%M UTC")
+                ));
+            }
+            if !proposal.reviewers.is_empty() {
+                lines.push(format!("Assigned reviewers: {}.", proposal.reviewers.join(", ")));
+            }
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Review proposal")
+        }
+        Notification::BreakGlassUsed { proposal } => {
+            let subject = format!("Emergency change executed: {}", proposal.title);
+            let mut lines = vec![format!(
+                "{} ran {} change(s) without review.",
+                proposal.created_by,
+                proposal.changes.len()
+            )];
+            if let Some(break_glass) = &proposal.break_glass {
+                lines.push(format!("Justification: {}", break_glass.justification));
+                lines.push(format!(
+                    "A retrospective review by another admin is due {}.",
+                    break_glass.review_due_at.format("%Y-%m-%d %H:%M UTC")
+                ));
+            }
+            lines.push(summary::current(proposal).text);
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Review change")
+        }
+        Notification::RetrospectiveOverdue { proposal } => {
+            let subject = format!("Retrospective overdue: {}", proposal.title);
+            let mut lines = vec![format!(
+                "The emergency change \"{}\" has not had its retrospective review.",
+                proposal.title
+            )];
+            if let Some(break_glass) = &proposal.break_glass {
+                lines.push(format!(
+                    "Run by {} on {}, review was due {}.",
+                    break_glass.invoked_by,
+                    break_glass.invoked_at.format("%Y-%m-%d %H:%M UTC"),
+                    break_glass.review_due_at.format("%Y-%m-%d %H:%M UTC")
+                ));
+            }
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Review change")
+        }
+        Notification::StaleDraftClosed { proposal, days } => {
+            let subject = format!("Draft closed: {}", proposal.title);
+            let lines = vec![
+                format!("Your draft \"{}\" was closed after {} days without activity.", proposal.title, days),
+                "Copy it into a new draft to keep working on it.".to_string(),
+            ];
+            build(subject, recipient_name, &lines, link(format!("/proposals/{}", proposal.id)), "Open proposal")
+        }
+        Notification::WatchedObjectChanged { connection_id, connection_name, event, objects, proposal } => {
+            let watched = objects.join(", ");
+            let (subject, headline) = match (event, proposal) {
+                (WatchEvent::Proposed, Some(proposal)) => (
+                    format!("Change proposed to {}", watched),
+                    format!("\"{}\" by {} would change {} on {}.", proposal.title, proposal.created_by, watched, connection_name),
+                ),
+                (WatchEvent::Executed, Some(proposal)) => (
+                    format!("{} changed", watched),
+                    format!("\"{}\" changed {} on {}.", proposal.title, watched, connection_name),
+                ),
+                _ => (
+                    format!("Drift on {}", watched),
+                    format!("The live schema of {} no longer matches its baseline for {}.", connection_name, watched),
+                ),
+            };
+            let mut lines = vec![headline];
+            let (path, label) = match proposal {
+                Some(proposal) => {
+                    lines.push(summary::current(proposal).text);
+                    (format!("/proposals/{}", proposal.id), "Open proposal")
+                }
+                None => (format!("/connections/{}/drift", connection_id), "Inspect drift"),
+            };
+            lines.push("You get this because you watch these objects.".to_string());
+            build(subject, recipient_name, &lines, link(path), label)
+        }
+    }
+}
+
+fn build(
+    subject: String,
+    recipient_name: &str,
+    lines: &[String],
+    action_url: Option<String>,
+    action_label: &str,
+) -> RenderedEmail {
+    let greeting = format!("Hi {},", recipient_name);
+
+    let mut text = format!("{}\n\n{}\n", greeting, lines.join("\n"));
+    let mut paragraphs: String = lines.iter().map(|l| format!("<p>{}</p>", escape(l))).collect();
+
+    if let Some(url) = &action_url {
+        text.push_str(&format!("\n{}: {}\n", action_label, url));
+        paragraphs.push_str(&format!(
+            r#"<p><a href="{}" style="display:inline-block;padding:10px 16px;background:#2563eb;color:#fff;border-radius:6px;text-decoration:none">{}</a></p>"#,
+            escape(url),
+            escape(action_label)
+        ));
+    }
+    text.push_str("\nYou can change which emails you receive in your notification settings.\n");
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<body style="font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;color:#111827;background:#f9fafb;padding:24px">
+  <div style="max-width:560px;margin:0 auto;background:#fff;border:1px solid #e5e7eb;border-radius:8px;padding:24px">
+    <h2 style="margin-top:0">{subject}</h2>
+    <p>{greeting}</p>
+    {paragraphs}
+    <p style="color:#6b7280;font-size:12px;margin-top:24px">You can change which emails you receive in your notification settings.</p>
+  </div>
+</body>
+</html>"#,
+        subject = escape(&subject),
+        greeting = escape(&greeting),
+        paragraphs = paragraphs,
+    );
+
+    RenderedEmail { subject, html, text }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::proposal::SchemaProposal;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_render_escapes_user_content() {
+        let proposal = SchemaProposal::new(
+            Uuid::new_v4(),
+            "Drop <script> table".to_string(),
+            String::new(),
+            "dev".to_string(),
+        );
+        let notification = Notification::ApprovalRequested { proposal: proposal.clone() };
+
+        let email = render(&notification, "Ana", Some("https://app.example.com/"));
+
+        assert_eq!(email.subject, "Approval needed: Drop <script> table");
+        assert!(email.html.contains("Drop &lt;script&gt; table"));
+        assert!(!email.html.contains("<script>"));
+        assert!(email.text.contains(&format!("https://app.example.com/proposals/{}", proposal.id)));
+    }
+
+    #[test]
+    fn test_stale_draft_email_names_the_draft_and_period() {
+        let proposal = SchemaProposal::new(Uuid::new_v4(), "Add invoices".to_string(), String::new(), "7".to_string());
+        let notification = Notification::StaleDraftClosed { proposal, days: 90 };
+
+        let email = render(&notification, "Ana", None);
+        assert_eq!(email.subject, "Draft closed: Add invoices");
+        assert!(email.text.contains("closed after 90 days without activity"));
+
+        let mut preferences = crate::notifications::NotificationPreferences::default();
+        assert!(preferences.allows(&notification));
+        preferences.stale_drafts = false;
+        assert!(!preferences.allows(&notification));
+    }
+}
@@ -0,0 +1,305 @@
+//! Tag-based access restrictions
+//!
+//! A project can reserve tagged tables for teams, e.g. only the payments team
+//! may propose or approve changes to tables tagged `payments`. Tags come from
+//! the governance metadata of the connection's latest snapshot (table tags and
+//! column tags, both read from `[tag]` markers in comments). Teams are lists of
+//! project members kept alongside the rules.
+
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::risk::{index_table, split_table_name};
+use crate::pipeline::types::{ReindexTarget, SchemaChange};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Teams allowed to work on tables carrying a tag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRule {
+    pub tag: String,
+    /// Teams that may propose changes (empty = anyone)
+    #[serde(default)]
+    pub propose: Vec<String>,
+    /// Teams that may approve changes (empty = anyone allowed to approve)
+    #[serde(default)]
+    pub approve: Vec<String>,
+}
+
+/// Tag access policy for a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccessPolicy {
+    /// Team name -> member user IDs
+    pub teams: BTreeMap<String, Vec<String>>,
+    pub rules: Vec<TagRule>,
+}
+
+/// What a user is trying to do with a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessAction {
+    Propose,
+    Approve,
+}
+
+impl AccessAction {
+    fn verb(self) -> &'static str {
+        match self {
+            AccessAction::Propose => "propose",
+            AccessAction::Approve => "approve",
+        }
+    }
+}
+
+impl AccessPolicy {
+    /// Reject rules without a tag or naming teams that are not defined
+    pub fn validate(&self) -> Result<(), AppError> {
+        for rule in &self.rules {
+            if rule.tag.trim().is_empty() {
+                return Err(AppError::Validation("Access rules need a tag".to_string()));
+            }
+            if let Some(team) = rule
+                .propose
+                .iter()
+                .chain(&rule.approve)
+                .find(|t| !self.teams.contains_key(t.as_str()))
+            {
+                return Err(AppError::Validation(format!(
+                    "Rule for tag '{}' names unknown team '{}'",
+                    rule.tag, team
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every user ID listed in a team
+    pub fn members(&self) -> BTreeSet<&str> {
+        self.teams.values().flatten().map(String::as_str).collect()
+    }
+
+    /// Check `user_id` against the rules for the tags on `tables` (table -> tags)
+    pub fn check(
+        &self,
+        action: AccessAction,
+        user_id: &str,
+        tables: &BTreeMap<String, BTreeSet<String>>,
+    ) -> Result<(), AppError> {
+        for rule in &self.rules {
+            let allowed = match action {
+                AccessAction::Propose => &rule.propose,
+                AccessAction::Approve => &rule.approve,
+            };
+            if allowed.is_empty() {
+                continue;
+            }
+
+            let tag = rule.tag.trim().to_lowercase();
+            let Some(table) = tables.iter().find(|(_, tags)| tags.contains(&tag)).map(|(t, _)| t) else {
+                continue;
+            };
+
+            let member = allowed
+                .iter()
+                .filter_map(|team| self.teams.get(team))
+                .any(|members| members.iter().any(|m| m == user_id));
+            if !member {
+                return Err(AppError::Forbidden(format!(
+                    "Only members of {} may {} changes to {} (tagged '{}')",
+                    allowed.join(", "),
+                    action.verb(),
+                    table,
+                    tag
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tables a proposal's changes touch, schema-qualified
+pub fn touched_tables(changes: &[SchemaChange], snapshot: &SchemaSnapshot) -> BTreeSet<String> {
+    let qualify = |name: &str| {
+        let (schema, table) = split_table_name(name);
+        format!("{}.{}", schema.unwrap_or("public"), table)
+    };
+
+    let mut tables = BTreeSet::new();
+    for change in changes {
+        match change {
+            SchemaChange::DropSchema { schema_name, .. } | SchemaChange::RenameSchema { old_name: schema_name, .. } => {
+                tables.extend(
+                    snapshot
+                        .tables
+                        .iter()
+                        .filter(|t| &t.schema == schema_name)
+                        .map(|t| format!("{}.{}", t.schema, t.name)),
+                );
+            }
+            // Extensions are database-wide and new types touch no table directly
+            SchemaChange::CreateSchema { .. }
+            | SchemaChange::CreateExtension { .. }
+            | SchemaChange::DropExtension { .. }
+            | SchemaChange::AlterExtensionVersion { .. }
+            | SchemaChange::CreateDomain { .. }
+            | SchemaChange::CreateCompositeType { .. } => {}
+            // A view touches the tables it reads; a new one's are not known until it exists
+            SchemaChange::CreateView { .. } | SchemaChange::CreateMaterializedView { .. } => {}
+            SchemaChange::AlterView { view_name, .. }
+            | SchemaChange::DropView { view_name, .. }
+            | SchemaChange::DropMaterializedView { view_name, .. }
+            | SchemaChange::RefreshMaterializedView { view_name, .. } => {
+                let view_name = qualify(view_name);
+                let reads = snapshot.views.iter().flatten().filter(|v| v.qualified_name() == view_name);
+                tables.extend(
+                    reads
+                        .flat_map(|v| &v.depends_on)
+                        .filter(|d| snapshot.tables.iter().any(|t| &format!("{}.{}", t.schema, t.name) == *d))
+                        .cloned(),
+                );
+            }
+            // Changing or dropping a type touches the tables with columns of that type
+            SchemaChange::AlterDomain { domain_name: type_name, .. }
+            | SchemaChange::DropDomain { domain_name: type_name, .. }
+            | SchemaChange::AlterCompositeType { type_name, .. }
+            | SchemaChange::DropCompositeType { type_name, .. } => {
+                let type_name = qualify(type_name);
+                tables.extend(
+                    snapshot
+                        .tables
+                        .iter()
+                        .filter(|t| t.columns.iter().any(|c| c.data_type == type_name))
+                        .map(|t| format!("{}.{}", t.schema, t.name)),
+                );
+            }
+            SchemaChange::CreatePartition { table_name: partition_name, parent_table, .. }
+            | SchemaChange::AttachPartition { parent_table, partition_name, .. }
+            | SchemaChange::DetachPartition { parent_table, partition_name, .. } => {
+                tables.insert(qualify(parent_table));
+                tables.insert(qualify(partition_name));
+            }
+            // The swap also rewrites the foreign keys of referencing tables
+            SchemaChange::ReorderColumns { table_name, plan, .. } => {
+                tables.insert(qualify(table_name));
+                for fk in plan.iter().flat_map(|p| &p.foreign_keys) {
+                    tables.insert(qualify(&fk.table_name));
+                }
+            }
+            SchemaChange::RenameTable { old_name: table_name, .. }
+            | SchemaChange::CreateTable { table_name, .. }
+            | SchemaChange::DropTable { table_name }
+            | SchemaChange::AddColumn { table_name, .. }
+            | SchemaChange::DropColumn { table_name, .. }
+            | SchemaChange::AlterColumn { table_name, .. }
+            | SchemaChange::RenameColumn { table_name, .. }
+            | SchemaChange::AddIndex { table_name, .. }
+            | SchemaChange::AddForeignKey { table_name, .. }
+            | SchemaChange::DropForeignKey { table_name, .. }
+            | SchemaChange::AddCheck { table_name, .. }
+            | SchemaChange::ValidateConstraint { table_name, .. }
+            | SchemaChange::DropConstraint { table_name, .. }
+            | SchemaChange::Backfill { table_name, .. }
+            | SchemaChange::AddUnique { table_name, .. }
+            | SchemaChange::Vacuum { table_name, .. }
+            | SchemaChange::Analyze { table_name }
+            | SchemaChange::CreatePolicy { table_name, .. }
+            | SchemaChange::AlterPolicy { table_name, .. }
+            | SchemaChange::DropPolicy { table_name, .. }
+            | SchemaChange::SetRowSecurity { table_name, .. }
+            | SchemaChange::SetCustomField { table_name, .. }
+            | SchemaChange::AddTag { table_name, .. }
+            | SchemaChange::RemoveTag { table_name, .. }
+            | SchemaChange::SetClassification { table_name, .. } => {
+                tables.insert(qualify(table_name));
+            }
+            SchemaChange::Reindex { target: ReindexTarget::Table, name, .. } => {
+                tables.insert(qualify(name));
+            }
+            SchemaChange::Reindex { target: ReindexTarget::Index, name: index_name, .. }
+            | SchemaChange::DropIndex { index_name } => {
+                tables.extend(index_table(snapshot, index_name));
+            }
+        }
+    }
+    tables
+}
+
+/// Governance tags of each table, including its columns' tags (lowercased)
+pub fn table_tags(snapshot: &SchemaSnapshot, tables: &BTreeSet<String>) -> BTreeMap<String, BTreeSet<String>> {
+    snapshot
+        .tables
+        .iter()
+        .map(|t| (format!("{}.{}", t.schema, t.name), t))
+        .filter(|(key, _)| tables.contains(key))
+        .map(|(key, table)| {
+            let tags = table
+                .governance
+                .tags
+                .iter()
+                .chain(table.columns.iter().flat_map(|c| &c.tags))
+                .map(|tag| tag.trim().to_lowercase())
+                .collect();
+            (key, tags)
+        })
+        .collect()
+}
+
+/// Enforce the project's tag policy for `user_id` acting on `proposal`.
+/// Proposals outside a project, or without a snapshot to read tags from, are
+/// not restricted.
+pub async fn authorize(
+    state: &AppState,
+    proposal: &SchemaProposal,
+    user_id: &str,
+    action: AccessAction,
+) -> Result<(), AppError> {
+    let Some(project_id) = proposal.project_id else {
+        return Ok(());
+    };
+    let Some(policy) = state.project_service.get_access_policy(project_id).await? else {
+        return Ok(());
+    };
+    if policy.rules.is_empty() {
+        return Ok(());
+    }
+    let Some(snapshot) = state.latest_scoped_snapshot(proposal.connection_id).await? else {
+        return Ok(());
+    };
+
+    let tables = touched_tables(&proposal.changes, &snapshot);
+    policy.check(action, user_id, &table_tags(&snapshot, &tables))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_table_is_reserved_for_team() {
+        let policy = AccessPolicy {
+            teams: BTreeMap::from([("payments".to_string(), vec!["7".to_string()])]),
+            rules: vec![TagRule {
+                tag: "Payments".to_string(),
+                propose: vec!["payments".to_string()],
+                approve: vec![],
+            }],
+        };
+        policy.validate().unwrap();
+
+        let tables = BTreeMap::from([("public.invoices".to_string(), BTreeSet::from(["payments".to_string()]))]);
+        assert!(policy.check(AccessAction::Propose, "7", &tables).is_ok());
+        assert!(matches!(
+            policy.check(AccessAction::Propose, "8", &tables),
+            Err(AppError::Forbidden(_))
+        ));
+        // No approve teams, and untagged tables are open
+        assert!(policy.check(AccessAction::Approve, "8", &tables).is_ok());
+        assert!(policy.check(AccessAction::Propose, "8", &BTreeMap::new()).is_ok());
+
+        let mut unknown = policy.clone();
+        unknown.rules[0].approve = vec!["finance".to_string()];
+        assert!(unknown.validate().is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Live proposal activity
+//!
+//! Comments, approvals, status changes, finished risk analyses and execution
+//! progress are published on one broadcast channel as they happen, and
+//! `GET /api/proposals/{id}/events` streams a proposal's share of it to the
+//! browser. `ProposalService` compares each proposal before and after a
+//! write, so every path that changes one is covered without announcing
+//! anything itself; `ExecutionMonitor` publishes progress as statements run.
+//!
+//! Nothing is kept: a client that connects, or falls behind, refetches the
+//! proposal and follows the stream from there.
+
+use crate::pipeline::progress::ExecutionProgress;
+use crate::pipeline::proposal::{Comment, ProposalStatus, RiskLevel, SchemaProposal};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events a slow subscriber may fall behind by before it misses some
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Something that happened to a proposal
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProposalActivity {
+    CommentAdded {
+        comment: Comment,
+    },
+    /// A reviewing team member approved for their team
+    TeamApproved {
+        team: String,
+        approver: String,
+    },
+    /// The proposal is approved
+    Approved {
+        approver: String,
+    },
+    StatusChanged {
+        from: ProposalStatus,
+        to: ProposalStatus,
+    },
+    RiskAnalyzed {
+        score: u32,
+        overall_risk: RiskLevel,
+    },
+    ExecutionProgress {
+        progress: Box<ExecutionProgress>,
+    },
+}
+
+/// A proposal's activity as published
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalEvent {
+    pub proposal_id: Uuid,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub activity: ProposalActivity,
+}
+
+/// What a proposal looked like before a write, enough to tell what the write did
+#[derive(Debug, Clone)]
+pub struct Marks {
+    status: ProposalStatus,
+    comments: usize,
+    team_approvals: usize,
+    approved: bool,
+    analyzed_at: Option<DateTime<Utc>>,
+}
+
+impl Marks {
+    pub fn of(proposal: &SchemaProposal) -> Self {
+        Self {
+            status: proposal.status,
+            comments: proposal.comments.len(),
+            team_approvals: proposal.team_approvals.len(),
+            approved: proposal.approved_by.is_some(),
+            analyzed_at: proposal.risk_analysis.as_ref().map(|a| a.analyzed_at),
+        }
+    }
+
+    /// Activity between these marks and `after`, in the order it happened
+    pub fn since(&self, after: &SchemaProposal) -> Vec<ProposalActivity> {
+        let mut activity: Vec<ProposalActivity> = after
+            .comments
+            .iter()
+            .skip(self.comments)
+            .map(|comment| ProposalActivity::CommentAdded { comment: comment.clone() })
+            .collect();
+        activity.extend(after.team_approvals.iter().skip(self.team_approvals).map(|approval| {
+            ProposalActivity::TeamApproved {
+                team: approval.team.clone(),
+                approver: approval.approver.clone(),
+            }
+        }));
+        if let Some(approver) = after.approved_by.as_ref().filter(|_| !self.approved) {
+            activity.push(ProposalActivity::Approved { approver: approver.clone() });
+        }
+        if let Some(analysis) = after.risk_analysis.as_ref().filter(|a| Some(a.analyzed_at) != self.analyzed_at) {
+            activity.push(ProposalActivity::RiskAnalyzed {
+                score: analysis.score,
+                overall_risk: analysis.overall_risk,
+            });
+        }
+        if after.status != self.status {
+            activity.push(ProposalActivity::StatusChanged { from: self.status, to: after.status });
+        }
+        activity
+    }
+}
+
+/// The broadcast channel of proposal activity, shared through `AppState`
+#[derive(Clone)]
+pub struct ActivityHub {
+    events: broadcast::Sender<ProposalEvent>,
+}
+
+impl Default for ActivityHub {
+    fn default() -> Self {
+        Self {
+            events: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl ActivityHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProposalEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn publish(&self, proposal_id: Uuid, activity: ProposalActivity) {
+        // Nobody listening is fine
+        let _ = self.events.send(ProposalEvent {
+            proposal_id,
+            at: Utc::now(),
+            activity,
+        });
+    }
+
+    /// Publish what a write did to a proposal
+    pub fn announce(&self, before: &Marks, after: &SchemaProposal) {
+        for activity in before.since(after) {
+            self.publish(after.id, activity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::proposal::{CommentTarget, ProposalService};
+    use crate::pipeline::types::SchemaChange;
+
+    #[tokio::test]
+    async fn test_writes_announce_what_changed() {
+        let hub = ActivityHub::new();
+        let service = ProposalService::new().with_activity(hub.clone());
+        let mut events = hub.subscribe();
+
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Analyze".into(), String::new(), "1".into());
+        proposal.changes = vec![SchemaChange::Analyze { table_name: "orders".to_string() }];
+        let proposal = service.create(proposal).await.unwrap();
+        service.submit(proposal.id, None).await.unwrap();
+        let comment = Comment {
+            id: Uuid::new_v4(),
+            author: "2".to_string(),
+            content: "Looks good".to_string(),
+            target: CommentTarget::Proposal,
+            reply_to: None,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+        };
+        service.add_comment(proposal.id, comment).await.unwrap();
+        service.approve(proposal.id, "2", "dev").await.unwrap();
+
+        let kinds: Vec<String> = std::iter::from_fn(|| events.try_recv().ok())
+            .inspect(|e| assert_eq!(e.proposal_id, proposal.id))
+            .map(|e| serde_json::to_value(&e).unwrap()["type"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(kinds, vec!["status_changed", "comment_added", "approved", "status_changed"]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Migration advisory lock
+//!
+//! The execution quota only serializes runs inside one SchemaFlow process.
+//! A real execution also holds a session-level advisory lock on the target
+//! database under a key reserved for SchemaFlow, so two independent instances
+//! pointed at the same database never migrate it at the same time. A run that
+//! finds the lock taken retries until its wait runs out, publishing who holds
+//! the lock to the execution monitor in the meantime.
+//!
+//! The lock is taken through a `LockedSession`, which owns the pooled
+//! connection. Should the run be cancelled or panic while the lock may be
+//! held, dropping the session takes the connection out of the pool and closes
+//! it, so the lock can't outlive the run on a connection handed to someone else.
+
+use crate::activity::sanitize_query;
+use crate::error::AppError;
+use crate::pipeline::progress::ExecutionMonitor;
+use deadpool_postgres::{Client, Object};
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Advisory lock key every SchemaFlow instance takes ("SchemaFl" in ASCII)
+pub const LOCK_KEY: i64 = 0x5363_6865_6d61_466c;
+
+/// How long an execution waits for the lock unless told otherwise
+pub const DEFAULT_WAIT: Duration = Duration::from_secs(30);
+
+/// Longest wait a caller may ask for
+pub const MAX_WAIT: Duration = Duration::from_secs(600);
+
+/// Pause between attempts while another session holds the lock
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Session holding the migration lock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockHolder {
+    pub pid: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_addr: Option<String>,
+    /// Query text with literals masked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Seconds since the holder's session started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_secs: Option<f64>,
+}
+
+impl LockHolder {
+    fn describe(&self) -> String {
+        let mut parts = vec![format!("pid {}", self.pid)];
+        if let Some(name) = self.application_name.as_deref().filter(|n| !n.is_empty()) {
+            parts.push(format!("application '{}'", name));
+        }
+        if let Some(addr) = &self.client_addr {
+            parts.push(format!("from {}", addr));
+        }
+        parts.join(", ")
+    }
+}
+
+/// How an execution came by the lock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockWait {
+    pub waited_ms: u64,
+    pub attempts: u32,
+    /// Last session seen holding the lock, when the run had to wait
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holder: Option<LockHolder>,
+}
+
+/// Clamp a requested wait to what the server allows
+pub fn wait_for(requested_secs: Option<u64>) -> Duration {
+    requested_secs.map(Duration::from_secs).unwrap_or(DEFAULT_WAIT).min(MAX_WAIT)
+}
+
+/// Pooled connection the lock is taken on
+pub struct LockedSession {
+    client: Option<Client>,
+    /// Whether the session may hold the lock; set before asking for it, so a
+    /// cancelled attempt errs on the side of discarding the connection
+    held: bool,
+}
+
+impl LockedSession {
+    pub fn new(client: Client) -> Self {
+        Self { client: Some(client), held: false }
+    }
+
+    /// Take the lock, retrying for up to `wait`.
+    ///
+    /// Fails with a conflict naming the holder once the wait runs out.
+    pub async fn acquire(
+        &mut self,
+        wait: Duration,
+        tracking: Option<(&ExecutionMonitor, Uuid)>,
+    ) -> Result<LockWait, AppError> {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut holder = None;
+
+        loop {
+            attempts += 1;
+            self.held = true;
+            let row = self.query_one("SELECT pg_try_advisory_lock($1)", &[&LOCK_KEY]).await?;
+            if row.get::<_, bool>(0) {
+                if let Some((monitor, proposal_id)) = tracking {
+                    monitor.waiting_for_lock(proposal_id, false, None).await;
+                }
+                return Ok(LockWait {
+                    waited_ms: started.elapsed().as_millis() as u64,
+                    attempts,
+                    holder,
+                });
+            }
+            self.held = false;
+
+            // The holder may let go between the two queries; keep the last one seen
+            if let Some(current) = find_holder(self).await {
+                holder = Some(current);
+            }
+            if let Some((monitor, proposal_id)) = tracking {
+                monitor.waiting_for_lock(proposal_id, true, holder.clone()).await;
+            }
+
+            let remaining = wait.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(AppError::Conflict(format!(
+                    "Another migration is running on this database ({}); gave up after waiting {}s for the SchemaFlow lock",
+                    holder.as_ref().map(LockHolder::describe).unwrap_or_else(|| "holder unknown".to_string()),
+                    wait.as_secs()
+                )));
+            }
+            tokio::time::sleep(RETRY_INTERVAL.min(remaining)).await;
+        }
+    }
+
+    /// Let go of the lock. A failure is only logged; the connection is then
+    /// discarded when the session is dropped, and the lock goes with it.
+    pub async fn release(&mut self) {
+        match self.query_one("SELECT pg_advisory_unlock($1)", &[&LOCK_KEY]).await {
+            Ok(_) => self.held = false,
+            Err(e) => warn!("Failed to release the migration advisory lock: {}", e),
+        }
+    }
+}
+
+impl Deref for LockedSession {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().expect("session client is only taken on drop")
+    }
+}
+
+impl DerefMut for LockedSession {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().expect("session client is only taken on drop")
+    }
+}
+
+impl Drop for LockedSession {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take().filter(|_| self.held) {
+            warn!("Migration advisory lock was not released; closing its connection instead of returning it to the pool");
+            drop(Object::take(client));
+        }
+    }
+}
+
+/// Session currently holding the lock in this database, if any.
+///
+/// A bigint advisory key shows up in `pg_locks` split into its high
+/// (`classid`) and low (`objid`) halves with `objsubid` 1.
+async fn find_holder(client: &Client) -> Option<LockHolder> {
+    let row = client
+        .query_opt(
+            "SELECT a.pid, a.usename::text, a.application_name, host(a.client_addr), a.query,
+                    EXTRACT(EPOCH FROM (now() - a.backend_start))::float8
+             FROM pg_locks l
+             JOIN pg_stat_activity a ON a.pid = l.pid
+             WHERE l.locktype = 'advisory'
+               AND l.granted
+               AND l.objsubid = 1
+               AND l.database = (SELECT oid FROM pg_database WHERE datname = current_database())
+               AND ((l.classid::bigint << 32) | l.objid::bigint) = $1
+             LIMIT 1",
+            &[&LOCK_KEY],
+        )
+        .await
+        .ok()??;
+
+    Some(LockHolder {
+        pid: row.get(0),
+        username: row.get(1),
+        application_name: row.get(2),
+        client_addr: row.get(3),
+        query: row.get::<_, Option<String>>(4).map(|q| sanitize_query(&q)),
+        session_secs: row.get(5),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_and_holder_description() {
+        assert_eq!(wait_for(None), DEFAULT_WAIT);
+        assert_eq!(wait_for(Some(5)), Duration::from_secs(5));
+        assert_eq!(wait_for(Some(86_400)), MAX_WAIT);
+
+        // The key splits into the halves pg_locks reports
+        assert_eq!((LOCK_KEY >> 32) as u32, 0x5363_6865);
+        assert_eq!(LOCK_KEY as u32, 0x6d61_466c);
+
+        let holder = LockHolder {
+            pid: 4242,
+            username: Some("deploy".to_string()),
+            application_name: Some("schemaflow".to_string()),
+            client_addr: Some("10.0.0.7".to_string()),
+            query: None,
+            session_secs: Some(12.0),
+        };
+        assert_eq!(holder.describe(), "pid 4242, application 'schemaflow', from 10.0.0.7");
+    }
+}
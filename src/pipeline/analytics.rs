@@ -0,0 +1,366 @@
+//! Governance analytics
+//!
+//! Every rules evaluation of a project's proposals is persisted as a
+//! `RuleEvaluationRecord`. The functions here roll those records up into
+//! violation counts by rule and team, average safety scores, and a weekly
+//! trend for a reporting period.
+//!
+//! Teams come from proposal labels of the form `team:<name>`; proposals
+//! without one are counted under "unassigned".
+
+use crate::error::AppError;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::sla::ReviewSlaStats;
+use crate::snapshot::rules::{RuleViolation, RulesResult, Severity};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Label prefix that assigns a proposal to a team
+pub const TEAM_LABEL_PREFIX: &str = "team:";
+
+/// Team name used when a proposal has no team label
+pub const UNASSIGNED_TEAM: &str = "unassigned";
+
+/// Longest period a report may cover
+const MAX_PERIOD_DAYS: i64 = 730;
+
+/// One persisted rules evaluation of a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleEvaluationRecord {
+    pub proposal_id: Uuid,
+    pub project_id: i32,
+    pub team: String,
+    pub author: String,
+    /// 0-100, higher is safer; derived from the risk score
+    pub safety_score: u32,
+    pub violations: Vec<RuleViolation>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+impl RuleEvaluationRecord {
+    pub fn new(proposal: &SchemaProposal, project_id: i32, risk_score: u32, rules: &RulesResult) -> Self {
+        Self {
+            proposal_id: proposal.id,
+            project_id,
+            team: team_of(proposal),
+            author: proposal.created_by.clone(),
+            safety_score: safety_score(risk_score),
+            violations: rules.violations.clone(),
+            evaluated_at: Utc::now(),
+        }
+    }
+}
+
+/// Team a proposal belongs to, from its first `team:` label
+pub fn team_of(proposal: &SchemaProposal) -> String {
+    proposal
+        .labels
+        .iter()
+        .find_map(|l| l.strip_prefix(TEAM_LABEL_PREFIX))
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| UNASSIGNED_TEAM.to_string())
+}
+
+/// Invert a risk score into a 0-100 safety score
+pub fn safety_score(risk_score: u32) -> u32 {
+    100u32.saturating_sub(risk_score)
+}
+
+/// Parse a reporting period such as "90d", "12w" or "48h"
+pub fn parse_period(period: &str) -> Result<Duration, AppError> {
+    let period = period.trim();
+    let invalid = || AppError::BadRequest(format!("Invalid period '{}'; use e.g. 90d, 12w or 48h", period));
+
+    let (amount, hours_per_unit) = if let Some(amount) = period.strip_suffix('h') {
+        (amount, 1)
+    } else if let Some(amount) = period.strip_suffix('d') {
+        (amount, 24)
+    } else if let Some(amount) = period.strip_suffix('w') {
+        (amount, 24 * 7)
+    } else {
+        return Err(invalid());
+    };
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+
+    // Bounded before building the duration, which panics on huge amounts
+    match amount.checked_mul(hours_per_unit) {
+        Some(hours) if hours <= MAX_PERIOD_DAYS * 24 => Ok(Duration::hours(hours)),
+        _ => Err(AppError::BadRequest(format!("Period cannot exceed {} days", MAX_PERIOD_DAYS))),
+    }
+}
+
+/// Violations of one rule over the period
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleViolationCount {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub severity: Severity,
+    pub count: usize,
+    /// Distinct proposals that hit the rule
+    pub proposals: usize,
+}
+
+/// Violations and safety of one team's proposals
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamViolationSummary {
+    pub team: String,
+    pub evaluations: usize,
+    pub violations: usize,
+    pub blockers: usize,
+    pub average_safety_score: f64,
+}
+
+/// One week of the trend, starting on `week_start` (a Monday)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub week_start: DateTime<Utc>,
+    pub evaluations: usize,
+    pub violations: usize,
+    pub average_safety_score: f64,
+}
+
+/// Violation analytics for a project over a period
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViolationAnalytics {
+    pub project_id: i32,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub evaluations: usize,
+    pub proposals: usize,
+    pub total_violations: usize,
+    pub violations_by_severity: BTreeMap<String, usize>,
+    pub average_safety_score: f64,
+    pub by_rule: Vec<RuleViolationCount>,
+    pub by_team: Vec<TeamViolationSummary>,
+    pub trend: Vec<TrendPoint>,
+    /// Review SLA figures for proposals submitted in the period; filled in by
+    /// the caller since they come from live proposals, not evaluation records
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_sla: Option<ReviewSlaStats>,
+}
+
+/// Roll up evaluations between `since` and `until`.
+///
+/// A proposal is usually analyzed several times; only its latest evaluation
+/// in the period counts towards the rule, team and overall figures, while the
+/// trend uses the latest evaluation per proposal within each week.
+pub fn summarize(
+    project_id: i32,
+    records: &[RuleEvaluationRecord],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> ViolationAnalytics {
+    let in_period: Vec<&RuleEvaluationRecord> = records
+        .iter()
+        .filter(|r| r.project_id == project_id && r.evaluated_at >= since && r.evaluated_at <= until)
+        .collect();
+
+    let latest = latest_per_key(in_period.iter().copied(), |r| r.proposal_id);
+
+    let mut violations_by_severity = BTreeMap::new();
+    let mut by_rule: HashMap<&str, RuleViolationCount> = HashMap::new();
+    let mut by_team: BTreeMap<&str, (usize, usize, usize, u64)> = BTreeMap::new();
+
+    for record in &latest {
+        let team = by_team.entry(record.team.as_str()).or_default();
+        team.0 += 1;
+        team.1 += record.violations.len();
+        team.3 += record.safety_score as u64;
+
+        let mut seen_rules = Vec::new();
+        for v in &record.violations {
+            let key = format!("{:?}", v.severity).to_lowercase();
+            *violations_by_severity.entry(key).or_insert(0) += 1;
+            if v.severity == Severity::Block {
+                team.2 += 1;
+            }
+
+            let count = by_rule.entry(v.rule_id.as_str()).or_insert_with(|| RuleViolationCount {
+                rule_id: v.rule_id.clone(),
+                rule_name: v.rule_name.clone(),
+                severity: v.severity,
+                count: 0,
+                proposals: 0,
+            });
+            count.count += 1;
+            if !seen_rules.contains(&v.rule_id.as_str()) {
+                seen_rules.push(v.rule_id.as_str());
+                count.proposals += 1;
+            }
+        }
+    }
+
+    let mut by_rule: Vec<_> = by_rule.into_values().collect();
+    by_rule.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.rule_id.cmp(&b.rule_id)));
+
+    let by_team = by_team
+        .into_iter()
+        .map(|(team, (evaluations, violations, blockers, safety))| TeamViolationSummary {
+            team: team.to_string(),
+            evaluations,
+            violations,
+            blockers,
+            average_safety_score: average(safety, evaluations),
+        })
+        .collect();
+
+    ViolationAnalytics {
+        project_id,
+        since,
+        until,
+        evaluations: in_period.len(),
+        proposals: latest.len(),
+        total_violations: latest.iter().map(|r| r.violations.len()).sum(),
+        violations_by_severity,
+        average_safety_score: average(latest.iter().map(|r| r.safety_score as u64).sum(), latest.len()),
+        by_rule,
+        by_team,
+        trend: weekly_trend(&in_period),
+        review_sla: None,
+    }
+}
+
+fn weekly_trend(records: &[&RuleEvaluationRecord]) -> Vec<TrendPoint> {
+    let latest = latest_per_key(records.iter().copied(), |r| (week_start(r.evaluated_at), r.proposal_id));
+
+    let mut weeks: BTreeMap<DateTime<Utc>, (usize, usize, u64)> = BTreeMap::new();
+    for record in latest {
+        let week = weeks.entry(week_start(record.evaluated_at)).or_default();
+        week.0 += 1;
+        week.1 += record.violations.len();
+        week.2 += record.safety_score as u64;
+    }
+
+    weeks
+        .into_iter()
+        .map(|(week_start, (evaluations, violations, safety))| TrendPoint {
+            week_start,
+            evaluations,
+            violations,
+            average_safety_score: average(safety, evaluations),
+        })
+        .collect()
+}
+
+/// Keep the most recent record for each key
+fn latest_per_key<'a, K: std::hash::Hash + Eq>(
+    records: impl Iterator<Item = &'a RuleEvaluationRecord>,
+    key: impl Fn(&RuleEvaluationRecord) -> K,
+) -> Vec<&'a RuleEvaluationRecord> {
+    let mut latest: HashMap<K, &RuleEvaluationRecord> = HashMap::new();
+    for record in records {
+        latest
+            .entry(key(record))
+            .and_modify(|current| {
+                if record.evaluated_at > current.evaluated_at {
+                    *current = record;
+                }
+            })
+            .or_insert(record);
+    }
+    latest.into_values().collect()
+}
+
+/// Midnight UTC on the Monday of the week containing `at`
+pub(crate) fn week_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    use chrono::Datelike;
+    let monday = at.date_naive() - Duration::days(at.weekday().num_days_from_monday() as i64);
+    monday.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc()
+}
+
+fn average(total: u64, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        (total as f64 / count as f64 * 10.0).round() / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(rule_id: &str, severity: Severity) -> RuleViolation {
+        RuleViolation {
+            rule_id: rule_id.to_string(),
+            rule_name: rule_id.to_string(),
+            severity,
+            message: String::new(),
+            affected_object: "users".to_string(),
+            suggestion: None,
+            params: Default::default(),
+        }
+    }
+
+    fn record(proposal_id: Uuid, team: &str, safety: u32, violations: Vec<RuleViolation>, days_ago: i64) -> RuleEvaluationRecord {
+        RuleEvaluationRecord {
+            proposal_id,
+            project_id: 1,
+            team: team.to_string(),
+            author: "1".to_string(),
+            safety_score: safety,
+            violations,
+            evaluated_at: Utc::now() - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("90d").unwrap(), Duration::days(90));
+        assert_eq!(parse_period("12w").unwrap(), Duration::weeks(12));
+        assert!(parse_period("0d").is_err());
+        assert!(parse_period("90").is_err());
+        assert!(parse_period("5y").is_err());
+        assert!(parse_period("1000d").is_err());
+        assert!(parse_period("9é").is_err());
+        assert!(parse_period("é").is_err());
+        assert!(parse_period("9999999999999999w").is_err());
+        assert!(parse_period(&format!("{}h", i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_summarize_counts_latest_evaluation_per_proposal() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let records = vec![
+            // Superseded by the re-analysis below
+            record(a, "billing", 20, vec![violation("drop_table", Severity::Block)], 10),
+            record(a, "billing", 80, vec![violation("naming", Severity::Warning)], 3),
+            record(b, UNASSIGNED_TEAM, 40, vec![violation("naming", Severity::Warning), violation("drop_table", Severity::Block)], 2),
+            // Outside the period
+            record(Uuid::new_v4(), "billing", 0, vec![violation("drop_table", Severity::Block)], 200),
+        ];
+
+        let report = summarize(1, &records, Utc::now() - Duration::days(90), Utc::now());
+        assert_eq!(report.evaluations, 3);
+        assert_eq!(report.proposals, 2);
+        assert_eq!(report.total_violations, 3);
+        assert_eq!(report.average_safety_score, 60.0);
+        assert_eq!(report.by_rule[0].rule_id, "naming");
+        assert_eq!(report.by_rule[0].count, 2);
+        assert_eq!(report.by_rule[1].count, 1);
+
+        let billing = report.by_team.iter().find(|t| t.team == "billing").unwrap();
+        assert_eq!((billing.evaluations, billing.violations, billing.blockers), (1, 1, 0));
+        assert!(!report.trend.is_empty());
+    }
+
+    #[test]
+    fn test_team_from_label() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "1".to_string());
+        assert_eq!(team_of(&proposal), UNASSIGNED_TEAM);
+        proposal.labels = vec!["urgent".to_string(), "team: Billing".to_string()];
+        assert_eq!(team_of(&proposal), "billing");
+        assert_eq!(safety_score(250), 0);
+    }
+}
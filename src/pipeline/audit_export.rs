@@ -0,0 +1,311 @@
+//! Audit log export for SIEMs
+//!
+//! Renders audit entries as CEF lines (one event per line, for syslog-style
+//! collectors) or as a JSON document following the `schemaflow.audit-export/v1`
+//! schema. Both carry a hash chain: each record's hash covers the previous
+//! hash and the record itself, so dropping, reordering or editing a record
+//! breaks every hash after it. With `audit_export.signing_key` set, the JSON
+//! document also carries an HMAC-SHA256 of the chain head.
+//!
+//! Records also list the active legal holds covering them. Holds come and go
+//! after the fact, so they stay outside the hash.
+//!
+//! Exports run on demand and, when `audit_export.directory` is set, on an
+//! interval into that directory. The first periodic export after startup
+//! covers the whole log; later ones cover entries added since.
+
+use crate::config::{AuditExportConfig, AuditExportFormat};
+use crate::pipeline::metadata::{AuditEntry, GENESIS_HASH};
+use crate::pipeline::retention::{self, LegalHold};
+use crate::state::SharedState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Schema identifier of the JSON export
+pub const JSON_SCHEMA: &str = "schemaflow.audit-export/v1";
+
+/// One audit entry with its place in the export's hash chain. The entry
+/// keeps its own link in the audit log's chain, which a partial export
+/// cannot check on its own.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRecord {
+    pub entry: AuditEntry,
+    pub previous_hash: String,
+    pub hash: String,
+    /// Active legal holds covering the entry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub legal_holds: Vec<Uuid>,
+}
+
+/// JSON export document
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDocument {
+    pub schema: &'static str,
+    pub generated_at: DateTime<Utc>,
+    pub records: Vec<ExportRecord>,
+    /// Hash of the last record, or the genesis hash for an empty export
+    pub chain_head: String,
+    /// Hex HMAC-SHA256 of `chain_head`; absent without a signing key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash of a record chained onto `previous`
+fn record_hash(previous: &str, entry: &AuditEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(serde_json::to_vec(entry).unwrap_or_default());
+    hex(&hasher.finalize())
+}
+
+/// HMAC-SHA256 (RFC 2104)
+fn hmac_sha256(key: &[u8], message: &[u8]) -> String {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    hex(&outer.finalize())
+}
+
+/// Chain entries in order, oldest first
+pub fn chain(entries: Vec<AuditEntry>, holds: &[LegalHold]) -> Vec<ExportRecord> {
+    let mut previous = GENESIS_HASH.to_string();
+    entries
+        .into_iter()
+        .map(|entry| {
+            let hash = record_hash(&previous, &entry);
+            let legal_holds = retention::holds_on(&entry, holds);
+            let record = ExportRecord { entry, previous_hash: previous.clone(), hash: hash.clone(), legal_holds };
+            previous = hash;
+            record
+        })
+        .collect()
+}
+
+pub fn to_document(entries: Vec<AuditEntry>, holds: &[LegalHold], signing_key: Option<&str>) -> ExportDocument {
+    let records = chain(entries, holds);
+    let chain_head = records.last().map(|r| r.hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string());
+    ExportDocument {
+        schema: JSON_SCHEMA,
+        generated_at: Utc::now(),
+        signature: signing_key.map(|key| hmac_sha256(key.as_bytes(), chain_head.as_bytes())),
+        records,
+        chain_head,
+    }
+}
+
+/// Escape a CEF header field
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Escape a CEF extension value
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Snake-case name of the entry's action, as used in the API
+fn action_name(entry: &AuditEntry) -> String {
+    serde_json::to_value(&entry.action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", entry.action))
+}
+
+/// CEF severity (0-10) of an action
+fn cef_severity(action: &str) -> u8 {
+    if action.contains("failed") || action.contains("denied") || action.contains("breached") {
+        7
+    } else if action.contains("executed") || action.contains("deleted") || action.contains("rolled_back") {
+        5
+    } else {
+        3
+    }
+}
+
+/// One CEF line for a chained record
+pub fn to_cef_line(record: &ExportRecord) -> String {
+    let entry = &record.entry;
+    let action = action_name(entry);
+    let mut extensions = vec![
+        format!("rt={}", entry.timestamp.timestamp_millis()),
+        format!("externalId={}", entry.id),
+        format!("suser={}", cef_value(&entry.actor)),
+        format!("cs1Label=targetType cs1={}", cef_value(&entry.target_type)),
+        format!("cs2Label=targetId cs2={}", cef_value(&entry.target_id)),
+        format!("cs3Label=previousHash cs3={}", record.previous_hash),
+        format!("cs4Label=hash cs4={}", record.hash),
+    ];
+    if let Some(project_id) = entry.project_id {
+        extensions.push(format!("cn1Label=projectId cn1={}", project_id));
+    }
+    if !record.legal_holds.is_empty() {
+        let ids: Vec<String> = record.legal_holds.iter().map(Uuid::to_string).collect();
+        extensions.push(format!("cs5Label=legalHolds cs5={}", ids.join(",")));
+    }
+    if let Some(details) = &entry.details {
+        extensions.push(format!("msg={}", cef_value(details)));
+    }
+
+    format!(
+        "CEF:0|SchemaFlow|SchemaFlow API|{}|{}|{}|{}|{}",
+        cef_header(env!("CARGO_PKG_VERSION")),
+        cef_header(&action),
+        cef_header(&action.replace('_', " ")),
+        cef_severity(&action),
+        extensions.join(" ")
+    )
+}
+
+/// Render entries, oldest first, in `format`
+pub fn render(
+    entries: Vec<AuditEntry>,
+    holds: &[LegalHold],
+    format: AuditExportFormat,
+    signing_key: Option<&str>,
+) -> String {
+    match format {
+        AuditExportFormat::Cef => {
+            let mut lines: Vec<String> = chain(entries, holds).iter().map(to_cef_line).collect();
+            lines.push(String::new());
+            lines.join("\n")
+        }
+        AuditExportFormat::Json => {
+            serde_json::to_string_pretty(&to_document(entries, holds, signing_key)).unwrap_or_default()
+        }
+    }
+}
+
+/// Entries newer than `since`, in log order
+pub fn select(mut entries: Vec<AuditEntry>, since: Option<DateTime<Utc>>) -> Vec<AuditEntry> {
+    entries.retain(|e| since.is_none_or(|since| e.timestamp > since));
+    entries
+}
+
+/// Spawn the background task writing exports into the configured directory
+pub fn spawn_audit_exporter(state: SharedState, config: AuditExportConfig) -> Option<tokio::task::JoinHandle<()>> {
+    let directory = config.directory.clone()?;
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+        let mut since: Option<DateTime<Utc>> = None;
+        loop {
+            interval.tick().await;
+            let entries = match state.metadata.get_audit_log().await {
+                Ok(entries) => select(entries, since),
+                Err(e) => {
+                    warn!("Audit export skipped; could not read the audit log: {}", e);
+                    continue;
+                }
+            };
+            let Some(newest) = entries.last().map(|e| e.timestamp) else {
+                continue;
+            };
+            let holds = match state.metadata.list_legal_holds().await {
+                Ok(holds) => holds,
+                Err(e) => {
+                    warn!("Audit export skipped; could not read legal holds: {}", e);
+                    continue;
+                }
+            };
+
+            let path = directory.join(format!(
+                "audit-{}.{}",
+                Utc::now().format("%Y%m%dT%H%M%SZ"),
+                config.format.extension()
+            ));
+            let body = render(entries, &holds, config.format, config.signing_key.as_deref());
+            let written = match tokio::fs::create_dir_all(&directory).await {
+                Ok(()) => tokio::fs::write(&path, body).await,
+                Err(e) => Err(e),
+            };
+            match written {
+                Ok(()) => {
+                    info!("Exported audit log to {}", path.display());
+                    since = Some(newest);
+                }
+                Err(e) => warn!("Failed to write audit export {}: {}", path.display(), e),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::metadata::AuditAction;
+
+    #[test]
+    fn test_chain_detects_edits() {
+        let entries = vec![
+            AuditEntry::new(AuditAction::ProposalCreated, "alice", "proposal", "p1"),
+            AuditEntry::new(AuditAction::ProposalApproved, "bob", "proposal", "p1").with_details("Looks good"),
+        ];
+        let records = chain(entries.clone(), &[]);
+        assert_eq!(records[0].previous_hash, GENESIS_HASH);
+        assert_eq!(records[1].previous_hash, records[0].hash);
+
+        let mut edited = entries;
+        edited[0].actor = "mallory".to_string();
+        let tampered = chain(edited, &[]);
+        assert_ne!(tampered[0].hash, records[0].hash);
+        assert_ne!(tampered[1].hash, records[1].hash);
+    }
+
+    #[test]
+    fn test_cef_line_escapes_values() {
+        let entry = AuditEntry::new(AuditAction::ProposalCreated, "alice", "proposal", "p1")
+            .with_project(Some(4))
+            .with_details("a=b\nc\\d");
+        let hold = LegalHold::new(
+            retention::HoldScope::Proposal { proposal_id: Uuid::nil() },
+            "Audit".to_string(),
+            "1",
+        )
+        .unwrap();
+        let covered = AuditEntry::new(AuditAction::ProposalCreated, "alice", "proposal", &Uuid::nil().to_string());
+        let records = chain(vec![entry, covered], std::slice::from_ref(&hold));
+        assert!(records[0].legal_holds.is_empty());
+        assert!(to_cef_line(&records[1]).contains(&format!("cs5Label=legalHolds cs5={}", hold.id)));
+
+        let line = to_cef_line(&records[0]);
+        assert!(line.starts_with("CEF:0|SchemaFlow|SchemaFlow API|"));
+        assert!(line.contains("|proposal_created|proposal created|3|"));
+        assert!(line.contains("msg=a\\=b\\nc\\\\d"));
+        assert!(line.contains("cn1=4"));
+    }
+
+    #[test]
+    fn test_hmac_sha256_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
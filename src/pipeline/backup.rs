@@ -0,0 +1,384 @@
+//! Pre-execution backups
+//!
+//! When a backup provider is configured, a real execution of a destructive
+//! proposal (dropping a schema, table or column, changing a column's type, or
+//! dropping an extension with CASCADE) first takes a backup and only proceeds once the provider reports success.
+//! The backup's reference is recorded on the execution result so whoever has
+//! to undo the change knows where to restore from.
+//!
+//! `pg_dump` backups cover just the tables the proposal destroys (the whole
+//! database when it drops a schema or cascades an extension drop) and are uploaded to the archive bucket.
+//! Provider snapshot APIs (RDS, Cloud SQL) are not wired up yet; selecting one
+//! blocks destructive executions with an explanation rather than skipping the
+//! backup.
+//!
+//! Separately, a project's execution policy can demand a whole-database
+//! logical backup of the target, completed within `backupMaxAgeHours`, before
+//! a destructive proposal starts. Backup tooling reports the backups it takes
+//! through `POST /api/connections/{id}/backups`, and full backups taken by the
+//! hook are recorded the same way. Break-glass changes are exempt.
+
+use crate::config::{BackupConfig, BackupProviderKind};
+use crate::connection::ConnectionParams;
+use crate::db::queries::SqlBuilder;
+use crate::error::AppError;
+use crate::pipeline::execution_policy::ExecutionPolicy;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::SnapshotArchive;
+use crate::state::SharedState;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// What to back up before a proposal runs
+#[derive(Debug, Clone)]
+pub struct BackupRequest {
+    pub proposal_id: Uuid,
+    pub connection: ConnectionParams,
+    /// Tables the proposal destroys, schema-qualified
+    pub tables: BTreeSet<String>,
+    /// Dropping a schema or cascading an extension drop needs the whole database
+    pub full: bool,
+}
+
+impl BackupRequest {
+    /// Backup scope for a proposal's changes; `None` when nothing is destroyed
+    pub fn for_changes(proposal_id: Uuid, connection: ConnectionParams, changes: &[SchemaChange]) -> Option<Self> {
+        let (tables, full) = destroyed_by(changes)?;
+        Some(Self { proposal_id, connection, tables, full })
+    }
+}
+
+/// Tables the changes destroy, schema-qualified, and whether the whole
+/// database needs backing up; `None` when nothing is destroyed
+pub fn destroyed_by(changes: &[SchemaChange]) -> Option<(BTreeSet<String>, bool)> {
+    let mut tables = BTreeSet::new();
+    let mut full = false;
+    for change in changes {
+        match change {
+            // CASCADE drops whatever uses the extension, wherever it lives
+            SchemaChange::DropSchema { .. } | SchemaChange::DropExtension { cascade: true, .. } => full = true,
+            SchemaChange::DropTable { table_name }
+            | SchemaChange::DropColumn { table_name, .. }
+            | SchemaChange::AlterColumn { table_name, new_type: Some(_), .. } => {
+                let (schema, table) = split_table_name(table_name);
+                tables.insert(format!("{}.{}", schema.unwrap_or("public"), table));
+            }
+            _ => {}
+        }
+    }
+    (full || !tables.is_empty()).then_some((tables, full))
+}
+
+/// Where a backup ended up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReference {
+    pub provider: String,
+    /// Object key, snapshot ID or similar, depending on the provider
+    pub location: String,
+    /// Tables covered; empty for a full backup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl BackupReference {
+    /// Whether the backup covers the whole database rather than some tables
+    pub fn is_full(&self) -> bool {
+        self.tables.is_empty()
+    }
+}
+
+/// A whole-database backup on record for a connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedBackup {
+    pub id: i32,
+    pub connection_id: Uuid,
+    /// User who reported the backup, or the one whose execution took it
+    pub recorded_by: String,
+    #[serde(flatten)]
+    pub backup: BackupReference,
+}
+
+/// Backups looked at, and listed, per connection
+pub const RECENT_BACKUPS: i64 = 20;
+
+/// The newest of `backups` if it completed within `max_age_hours` of `now`
+pub fn recent_backup(
+    backups: &[RecordedBackup],
+    max_age_hours: u32,
+    now: DateTime<Utc>,
+) -> Result<&RecordedBackup, AppError> {
+    let newest = backups
+        .iter()
+        .filter(|b| b.backup.is_full() && b.backup.completed_at <= now)
+        .max_by_key(|b| b.backup.completed_at);
+    match newest {
+        Some(backup) if backup.backup.completed_at >= now - ChronoDuration::hours(max_age_hours as i64) => Ok(backup),
+        Some(backup) => Err(AppError::Conflict(format!(
+            "Destructive changes need a whole-database backup completed in the last {} hours; the newest, {}, completed {}",
+            max_age_hours,
+            backup.backup.location,
+            backup.backup.completed_at.format("%Y-%m-%d %H:%M UTC")
+        ))),
+        None => Err(AppError::Conflict(format!(
+            "Destructive changes need a whole-database backup completed in the last {} hours, and none is on record for this connection",
+            max_age_hours
+        ))),
+    }
+}
+
+/// Enforce the policy's backup requirement for a proposal about to start.
+/// Returns the backup that satisfied it, `None` when nothing was required.
+pub async fn require_recent(
+    state: &SharedState,
+    policy: &ExecutionPolicy,
+    proposal: &SchemaProposal,
+) -> Result<Option<RecordedBackup>, AppError> {
+    let Some(max_age_hours) = policy.backup_max_age_hours else {
+        return Ok(None);
+    };
+    if destroyed_by(&proposal.changes).is_none() {
+        return Ok(None);
+    }
+    let backups = state.project_service.list_backups(proposal.connection_id, RECENT_BACKUPS).await?;
+    recent_backup(&backups, max_age_hours, Utc::now()).cloned().map(Some)
+}
+
+/// Something that can take a backup and say where it went
+#[async_trait]
+pub trait BackupProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Take the backup, returning only once it is complete
+    async fn backup(&self, request: &BackupRequest) -> Result<BackupReference, AppError>;
+}
+
+/// `pg_dump --format=custom` uploaded to the archive bucket
+pub struct PgDumpProvider {
+    pg_dump_path: String,
+    archive: SnapshotArchive,
+}
+
+impl PgDumpProvider {
+    pub fn new(pg_dump_path: String, archive: SnapshotArchive) -> Self {
+        Self { pg_dump_path, archive }
+    }
+}
+
+#[async_trait]
+impl BackupProvider for PgDumpProvider {
+    fn name(&self) -> &'static str {
+        "pg_dump"
+    }
+
+    async fn backup(&self, request: &BackupRequest) -> Result<BackupReference, AppError> {
+        let started_at = Utc::now();
+        let output = Command::new(&self.pg_dump_path)
+            .args(pg_dump_args(request))
+            .env("PGPASSWORD", &request.connection.password)
+            .env("PGSSLMODE", if request.connection.use_tls { "require" } else { "prefer" })
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| AppError::Internal(format!("Could not run {}: {}", self.pg_dump_path, e)))?;
+
+        if !output.status.success() {
+            return Err(AppError::Internal(format!(
+                "pg_dump failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let file_name = format!("{}.dump", started_at.format("%Y%m%dT%H%M%SZ"));
+        let object = self.archive.store_backup(request.proposal_id, &file_name, &output.stdout).await?;
+
+        Ok(BackupReference {
+            provider: self.name().to_string(),
+            location: object.key,
+            tables: request.tables.iter().cloned().collect(),
+            size_bytes: Some(object.size),
+            started_at,
+            completed_at: Utc::now(),
+        })
+    }
+}
+
+/// Managed database snapshot APIs, not implemented yet
+pub struct SnapshotApiProvider {
+    kind: BackupProviderKind,
+}
+
+#[async_trait]
+impl BackupProvider for SnapshotApiProvider {
+    fn name(&self) -> &'static str {
+        match self.kind {
+            BackupProviderKind::Rds => "rds",
+            BackupProviderKind::CloudSql => "cloudsql",
+            BackupProviderKind::PgDump => "pg_dump",
+        }
+    }
+
+    async fn backup(&self, _request: &BackupRequest) -> Result<BackupReference, AppError> {
+        Err(AppError::Internal(format!(
+            "The {} snapshot API is not supported yet; set BACKUP_PROVIDER=pg_dump to back up destructive proposals",
+            self.name()
+        )))
+    }
+}
+
+/// The configured provider with its time limit
+#[derive(Clone)]
+pub struct BackupHook {
+    provider: Arc<dyn BackupProvider>,
+    timeout: Duration,
+}
+
+impl BackupHook {
+    pub fn new(provider: Arc<dyn BackupProvider>, timeout: Duration) -> Self {
+        Self { provider, timeout }
+    }
+
+    /// Build the provider named in the configuration. `pg_dump` stores its
+    /// dumps in the archive bucket, so it needs one.
+    pub fn from_config(config: &BackupConfig, archive: Option<SnapshotArchive>) -> Result<Self, AppError> {
+        let provider: Arc<dyn BackupProvider> = match config.provider {
+            BackupProviderKind::PgDump => {
+                let archive = archive.ok_or_else(|| {
+                    AppError::Config("BACKUP_PROVIDER=pg_dump needs an archive bucket (ARCHIVE_BUCKET)".to_string())
+                })?;
+                Arc::new(PgDumpProvider::new(config.pg_dump_path.clone(), archive))
+            }
+            kind => Arc::new(SnapshotApiProvider { kind }),
+        };
+        Ok(Self::new(provider, Duration::from_secs(config.timeout_secs)))
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Take the backup, failing if it does not finish in time
+    pub async fn run(&self, request: &BackupRequest) -> Result<BackupReference, AppError> {
+        match tokio::time::timeout(self.timeout, self.provider.backup(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(AppError::Internal(format!(
+                "The {} backup did not finish within {}s",
+                self.provider.name(),
+                self.timeout.as_secs()
+            ))),
+        }
+    }
+}
+
+/// Command-line arguments for `pg_dump`; the password goes in the environment
+fn pg_dump_args(request: &BackupRequest) -> Vec<String> {
+    let connection = &request.connection;
+    let mut args = vec![
+        "--format=custom".to_string(),
+        "--no-password".to_string(),
+        format!("--host={}", connection.host),
+        format!("--port={}", connection.port),
+        format!("--username={}", connection.user),
+        format!("--dbname={}", connection.database),
+    ];
+    if !request.full {
+        // Quoted so mixed-case names are matched exactly rather than as patterns
+        args.extend(request.tables.iter().map(|table| {
+            let (schema, name) = split_table_name(table);
+            format!(
+                "--table={}.{}",
+                SqlBuilder::quote_ident(schema.unwrap_or("public")),
+                SqlBuilder::quote_ident(name)
+            )
+        }));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::DatabaseType;
+    use crate::pipeline::types::ColumnDef;
+
+    fn params() -> ConnectionParams {
+        ConnectionParams {
+            host: "db.internal".to_string(),
+            port: 5432,
+            user: "app".to_string(),
+            password: "secret".to_string(),
+            database: "shop".to_string(),
+            db_type: DatabaseType::Postgres,
+            use_tls: true,
+        }
+    }
+
+    #[test]
+    fn test_only_destructive_changes_need_a_backup() {
+        let additive = vec![SchemaChange::AddColumn {
+            table_name: "orders".to_string(),
+            column: ColumnDef {
+                name: "note".to_string(),
+                data_type: "text".to_string(),
+                nullable: true,
+                default_value: None,
+                is_primary_key: false,
+                collation: None,
+            },
+        }];
+        assert!(BackupRequest::for_changes(Uuid::nil(), params(), &additive).is_none());
+
+        let destructive = vec![
+            SchemaChange::DropColumn { table_name: "orders".to_string(), column_name: "legacy".to_string() },
+            SchemaChange::DropTable { table_name: "billing.Invoices".to_string() },
+        ];
+        let request = BackupRequest::for_changes(Uuid::nil(), params(), &destructive).unwrap();
+        assert!(!request.full);
+
+        let args = pg_dump_args(&request);
+        assert!(args.contains(&"--table=\"billing\".\"Invoices\"".to_string()));
+        assert!(args.contains(&"--table=\"public\".\"orders\"".to_string()));
+        assert!(!args.iter().any(|a| a.contains("secret")));
+    }
+
+    #[test]
+    fn test_recent_backup_must_be_full_and_fresh() {
+        let now = Utc::now();
+        let recorded = |hours_ago: i64, tables: Vec<String>| RecordedBackup {
+            id: 1,
+            connection_id: Uuid::nil(),
+            recorded_by: "1".to_string(),
+            backup: BackupReference {
+                provider: "pgbackrest".to_string(),
+                location: format!("full-{}", hours_ago),
+                tables,
+                size_bytes: None,
+                started_at: now - ChronoDuration::hours(hours_ago + 1),
+                completed_at: now - ChronoDuration::hours(hours_ago),
+            },
+        };
+
+        assert!(matches!(recent_backup(&[], 24, now), Err(AppError::Conflict(_))));
+        let backups = vec![recorded(30, Vec::new()), recorded(2, vec!["public.orders".to_string()])];
+        // A table-scoped backup does not count, and the full one is too old
+        assert!(recent_backup(&backups, 24, now).is_err());
+        assert_eq!(recent_backup(&backups, 36, now).unwrap().backup.location, "full-30");
+
+        let backups = vec![recorded(30, Vec::new()), recorded(5, Vec::new())];
+        assert_eq!(recent_backup(&backups, 24, now).unwrap().backup.location, "full-5");
+    }
+}
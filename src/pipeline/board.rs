@@ -0,0 +1,189 @@
+//! Proposal board
+//!
+//! A project's proposals grouped into one column per status, each card
+//! carrying just what a board view shows. Columns always come in workflow
+//! order, empty ones included, so the UI can lay them out without knowing
+//! the statuses; within a column the riskiest and then the oldest
+//! proposals come first.
+
+use crate::pipeline::proposal::{ProposalStatus, RiskLevel, SchemaProposal};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Columns, left to right
+const COLUMNS: [ProposalStatus; 10] = [
+    ProposalStatus::Draft,
+    ProposalStatus::PendingReview,
+    ProposalStatus::Approved,
+    ProposalStatus::Executing,
+    ProposalStatus::Executed,
+    ProposalStatus::VerificationFailed,
+    ProposalStatus::Failed,
+    ProposalStatus::RolledBack,
+    ProposalStatus::Rejected,
+    ProposalStatus::Closed,
+];
+
+/// One proposal on the board
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalCard {
+    pub id: Uuid,
+    pub title: String,
+    pub author: String,
+    /// `None` until the proposal has been analyzed
+    pub risk_level: Option<RiskLevel>,
+    /// Distinct people who have approved, overall or for a team
+    pub approvals: usize,
+    pub change_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub age_secs: i64,
+}
+
+/// Proposals in one status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardColumn {
+    pub status: &'static str,
+    pub count: usize,
+    /// Cards per risk level; unanalyzed proposals count as `unanalyzed`
+    pub by_risk: BTreeMap<&'static str, usize>,
+    pub cards: Vec<ProposalCard>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalBoard {
+    pub project_id: i32,
+    pub total: usize,
+    pub columns: Vec<BoardColumn>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Board of `proposals`, which should all belong to `project_id`
+pub fn build(project_id: i32, proposals: &[SchemaProposal], now: DateTime<Utc>) -> ProposalBoard {
+    let columns = COLUMNS
+        .iter()
+        .map(|status| {
+            let mut cards: Vec<ProposalCard> = proposals
+                .iter()
+                .filter(|p| &p.status == status)
+                .map(|p| card(p, now))
+                .collect();
+            cards.sort_by(|a, b| rank(b.risk_level).cmp(&rank(a.risk_level)).then(a.created_at.cmp(&b.created_at)));
+
+            let mut by_risk = BTreeMap::new();
+            for card in &cards {
+                *by_risk.entry(risk_key(card.risk_level)).or_insert(0) += 1;
+            }
+            BoardColumn {
+                status: status.as_str(),
+                count: cards.len(),
+                by_risk,
+                cards,
+            }
+        })
+        .collect();
+
+    ProposalBoard {
+        project_id,
+        total: proposals.len(),
+        columns,
+        generated_at: now,
+    }
+}
+
+fn card(proposal: &SchemaProposal, now: DateTime<Utc>) -> ProposalCard {
+    let approvers: BTreeSet<&str> = proposal
+        .team_approvals
+        .iter()
+        .map(|a| a.approver.as_str())
+        .chain(proposal.approved_by.as_deref())
+        .collect();
+    ProposalCard {
+        id: proposal.id,
+        title: proposal.title.clone(),
+        author: proposal.created_by.clone(),
+        risk_level: proposal.risk_analysis.as_ref().map(|r| r.overall_risk),
+        approvals: approvers.len(),
+        change_count: proposal.changes.len(),
+        created_at: proposal.created_at,
+        age_secs: (now - proposal.created_at).num_seconds().max(0),
+    }
+}
+
+fn rank(risk: Option<RiskLevel>) -> u8 {
+    match risk {
+        None => 0,
+        Some(RiskLevel::Low) => 1,
+        Some(RiskLevel::Medium) => 2,
+        Some(RiskLevel::High) => 3,
+        Some(RiskLevel::Critical) => 4,
+    }
+}
+
+fn risk_key(risk: Option<RiskLevel>) -> &'static str {
+    match risk {
+        None => "unanalyzed",
+        Some(RiskLevel::Low) => "low",
+        Some(RiskLevel::Medium) => "medium",
+        Some(RiskLevel::High) => "high",
+        Some(RiskLevel::Critical) => "critical",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::proposal::RiskAnalysis;
+    use chrono::Duration;
+
+    fn proposal(title: &str, status: ProposalStatus, risk: Option<RiskLevel>, age_days: i64) -> SchemaProposal {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), title.to_string(), String::new(), "dev".to_string());
+        proposal.status = status;
+        proposal.created_at = Utc::now() - Duration::days(age_days);
+        proposal.risk_analysis = risk.map(|overall_risk| RiskAnalysis {
+            overall_risk,
+            score: 0,
+            warnings: Vec::new(),
+            recommendations: Vec::new(),
+            warning_codes: Vec::new(),
+            recommendation_codes: Vec::new(),
+            estimated_duration_secs: 0,
+            requires_downtime: false,
+            affected_tables: Vec::new(),
+            analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: Vec::new(),
+        });
+        proposal
+    }
+
+    #[test]
+    fn test_board_groups_by_status_and_orders_riskiest_first() {
+        let mut approved = proposal("Approved", ProposalStatus::Approved, Some(RiskLevel::Low), 1);
+        approved.approved_by = Some("admin".to_string());
+        let proposals = vec![
+            proposal("Old low", ProposalStatus::PendingReview, Some(RiskLevel::Low), 5),
+            proposal("New high", ProposalStatus::PendingReview, Some(RiskLevel::High), 1),
+            proposal("Unanalyzed", ProposalStatus::PendingReview, None, 9),
+            approved,
+        ];
+        let board = build(7, &proposals, Utc::now());
+
+        assert_eq!(board.columns.len(), COLUMNS.len());
+        assert_eq!(board.total, 4);
+        let review = board.columns.iter().find(|c| c.status == "pending_review").unwrap();
+        let titles: Vec<&str> = review.cards.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["New high", "Old low", "Unanalyzed"]);
+        assert_eq!(review.by_risk.get("unanalyzed"), Some(&1));
+        assert!(review.cards[1].age_secs >= 5 * 86_400);
+
+        let approved = board.columns.iter().find(|c| c.status == "approved").unwrap();
+        assert_eq!(approved.cards[0].approvals, 1);
+        assert_eq!(board.columns.iter().find(|c| c.status == "draft").unwrap().count, 0);
+    }
+}
@@ -0,0 +1,172 @@
+//! Break-glass emergency changes
+//!
+//! During an incident an admin can run changes against a connection without
+//! waiting for review. The changes still become a proposal, created already
+//! executing and marked break-glass with the admin's justification, so they
+//! show up in history, listings and the audit log like any other. Every
+//! break-glass proposal owes a retrospective review by someone other than the
+//! admin who ran it, within `proposal_policy.break_glass_review_days`; the
+//! policy sweeper escalates those that miss the deadline.
+
+use crate::error::AppError;
+use crate::pipeline::proposal::{ProposalStatus, SchemaProposal};
+use crate::pipeline::types::SchemaChange;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Shortest justification accepted for an emergency change
+pub const MIN_JUSTIFICATION: usize = 20;
+
+/// Emergency execution of a proposal and its retrospective review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakGlass {
+    pub invoked_by: String,
+    pub justification: String,
+    pub invoked_at: DateTime<Utc>,
+    /// Deadline of the retrospective review
+    pub review_due_at: DateTime<Utc>,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_notes: Option<String>,
+    /// When the missed review was escalated
+    pub escalated_at: Option<DateTime<Utc>>,
+}
+
+impl BreakGlass {
+    pub fn new(invoked_by: &str, justification: &str, now: DateTime<Utc>, review_days: i64) -> Result<Self, AppError> {
+        let justification = justification.trim();
+        if justification.len() < MIN_JUSTIFICATION {
+            return Err(AppError::Validation(format!(
+                "A justification of at least {} characters is required",
+                MIN_JUSTIFICATION
+            )));
+        }
+        Ok(Self {
+            invoked_by: invoked_by.to_string(),
+            justification: justification.to_string(),
+            invoked_at: now,
+            review_due_at: now + Duration::days(review_days),
+            reviewed_by: None,
+            reviewed_at: None,
+            review_notes: None,
+            escalated_at: None,
+        })
+    }
+
+    pub fn is_reviewed(&self) -> bool {
+        self.reviewed_at.is_some()
+    }
+
+    /// Unreviewed past the deadline
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        !self.is_reviewed() && self.review_due_at <= now
+    }
+
+    /// Sign off the retrospective; the admin who broke the glass cannot
+    pub fn record_review(&mut self, reviewer: &str, notes: &str, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.is_reviewed() {
+            return Err(AppError::Conflict("The retrospective review has already been recorded".to_string()));
+        }
+        if reviewer == self.invoked_by {
+            return Err(AppError::Forbidden(
+                "The retrospective must be reviewed by someone other than the admin who made the change".to_string(),
+            ));
+        }
+        if notes.trim().is_empty() {
+            return Err(AppError::Validation("Review notes are required".to_string()));
+        }
+        self.reviewed_by = Some(reviewer.to_string());
+        self.reviewed_at = Some(now);
+        self.review_notes = Some(notes.trim().to_string());
+        Ok(())
+    }
+}
+
+/// Retroactive proposal for changes run through the emergency path, ready to execute
+pub fn proposal(
+    connection_id: Uuid,
+    project_id: Option<i32>,
+    title: &str,
+    changes: Vec<SchemaChange>,
+    break_glass: BreakGlass,
+) -> SchemaProposal {
+    let description = format!(
+        "Emergency change run without review by {}.\n\nJustification: {}\n\nRetrospective review due {}.",
+        break_glass.invoked_by,
+        break_glass.justification,
+        break_glass.review_due_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let mut proposal = SchemaProposal::new(
+        connection_id,
+        format!("[Break-glass] {}", title.trim()),
+        description,
+        break_glass.invoked_by.clone(),
+    );
+    proposal.project_id = project_id;
+    proposal.changes = changes;
+    proposal.labels = vec!["break-glass".to_string()];
+    proposal.set_status(ProposalStatus::Executing, break_glass.invoked_at);
+    proposal.break_glass = Some(break_glass);
+    proposal
+}
+
+/// A break-glass proposal in the retrospective listing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakGlassEntry {
+    pub proposal_id: Uuid,
+    pub project_id: Option<i32>,
+    pub title: String,
+    pub status: ProposalStatus,
+    pub overdue: bool,
+    #[serde(flatten)]
+    pub break_glass: BreakGlass,
+}
+
+/// Break-glass proposals, unreviewed first, by review deadline
+pub fn retrospectives(proposals: &[SchemaProposal], pending_only: bool, now: DateTime<Utc>) -> Vec<BreakGlassEntry> {
+    let mut entries: Vec<BreakGlassEntry> = proposals
+        .iter()
+        .filter_map(|p| p.break_glass.as_ref().map(|b| (p, b)))
+        .filter(|(_, b)| !pending_only || !b.is_reviewed())
+        .map(|(p, b)| BreakGlassEntry {
+            proposal_id: p.id,
+            project_id: p.project_id,
+            title: p.title.clone(),
+            status: p.status,
+            overdue: b.is_overdue(now),
+            break_glass: b.clone(),
+        })
+        .collect();
+    entries.sort_by_key(|e| (e.break_glass.is_reviewed(), e.break_glass.review_due_at));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retrospective_is_due_and_needs_a_second_admin() {
+        let now = Utc::now();
+        assert!(BreakGlass::new("1", "outage", now, 3).is_err());
+
+        let break_glass = BreakGlass::new("1", "Checkout is down; index needed to stop seq scans", now, 3).unwrap();
+        let mut proposal = proposal(Uuid::new_v4(), Some(4), "Add index", Vec::new(), break_glass);
+        assert_eq!(proposal.status, ProposalStatus::Executing);
+        assert!(proposal.title.starts_with("[Break-glass]"));
+
+        let later = now + Duration::days(4);
+        let listed = retrospectives(std::slice::from_ref(&proposal), true, later);
+        assert!(listed[0].overdue);
+
+        let retro = proposal.break_glass.as_mut().unwrap();
+        assert!(matches!(retro.record_review("1", "fine", later), Err(AppError::Forbidden(_))));
+        retro.record_review("2", "Index was right; follow-up proposal to drop the old one", later).unwrap();
+        assert!(!retro.is_overdue(later));
+        assert!(retro.record_review("3", "again", later).is_err());
+        assert!(retrospectives(&[proposal], true, later).is_empty());
+    }
+}
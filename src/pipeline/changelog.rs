@@ -0,0 +1,586 @@
+//! Connection changelogs
+//!
+//! A markdown changelog of what changed on a connection over a time range,
+//! ready to paste into release notes. It lists the changes of proposals
+//! executed in the range, with a link to each proposal and who executed it,
+//! and drift: differences between consecutive snapshots captured in the
+//! range that no proposal executed between them explains. Entries are
+//! grouped by table; changes to schemas and extensions go under "Database".
+
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::access::touched_tables;
+use crate::pipeline::metadata::AuditAction;
+use crate::pipeline::proposal::{ProposalStatus, SchemaProposal};
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::diff::ObjectType;
+use crate::snapshot::{DiffEngine, SchemaDiffItem};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Range covered when the request doesn't give a start
+pub const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// Heading of changes that belong to no table
+const DATABASE_GROUP: &str = "Database";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChangelogQuery {
+    /// RFC 3339 timestamp or date; defaults to 30 days before `to`
+    pub from: Option<String>,
+    /// RFC 3339 timestamp or date (inclusive); defaults to now
+    pub to: Option<String>,
+}
+
+/// Parse a range bound; a date means the start of that day, or its end for `end_of_day`
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, AppError> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::Validation(format!("'{}' is not a date or RFC 3339 timestamp", value)))?;
+    let day = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+    let at = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    Ok(if end_of_day { at - Duration::nanoseconds(1) } else { at })
+}
+
+impl ChangelogQuery {
+    pub fn range(&self, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), AppError> {
+        let to = self.to.as_deref().map(|v| parse_bound(v, true)).transpose()?.unwrap_or(now);
+        let from = match self.from.as_deref() {
+            Some(value) => parse_bound(value, false)?,
+            None => to - Duration::days(DEFAULT_RANGE_DAYS),
+        };
+        if from > to {
+            return Err(AppError::Validation("from must not be after to".to_string()));
+        }
+        Ok((from, to))
+    }
+}
+
+/// Where a changelog entry comes from
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EntrySource {
+    Proposal {
+        proposal_id: Uuid,
+        title: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        executed_by: Option<String>,
+        href: String,
+    },
+    /// Found between two snapshots with no proposal to account for it
+    Drift { from_version: u64, to_version: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogEntry {
+    pub at: DateTime<Utc>,
+    pub description: String,
+    pub source: EntrySource,
+}
+
+/// Entries for one table, or for the database as a whole
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogGroup {
+    pub table: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changelog {
+    pub connection_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub groups: Vec<ChangelogGroup>,
+    pub markdown: String,
+}
+
+/// An executed proposal and who executed it
+pub struct ExecutedProposal<'a> {
+    pub proposal: &'a SchemaProposal,
+    pub executed_by: Option<String>,
+}
+
+/// Differences between two consecutive snapshots
+pub struct DriftWindow {
+    pub from_version: u64,
+    pub to_version: u64,
+    pub captured_at: DateTime<Utc>,
+    pub changes: Vec<SchemaDiffItem>,
+}
+
+fn qualify(name: &str) -> String {
+    if name.contains('.') {
+        name.to_string()
+    } else {
+        format!("public.{}", name)
+    }
+}
+
+/// One line describing a change, for release notes
+pub fn describe(change: &SchemaChange) -> String {
+    match change {
+        SchemaChange::CreateSchema { schema_name, .. } => format!("Created schema `{}`", schema_name),
+        SchemaChange::DropSchema { schema_name, .. } => format!("Dropped schema `{}`", schema_name),
+        SchemaChange::RenameSchema { old_name, new_name } => {
+            format!("Renamed schema `{}` to `{}`", old_name, new_name)
+        }
+        SchemaChange::CreateTable { table_name, columns, .. } => format!(
+            "Created table `{}` ({})",
+            table_name,
+            columns.iter().map(|c| format!("`{}` {}", c.name, c.data_type)).collect::<Vec<_>>().join(", ")
+        ),
+        SchemaChange::DropTable { table_name } => format!("Dropped table `{}`", table_name),
+        SchemaChange::RenameTable { old_name, new_name } => format!("Renamed table `{}` to `{}`", old_name, new_name),
+        SchemaChange::AddColumn { column, .. } => format!(
+            "Added column `{}` {}{}",
+            column.name,
+            column.data_type,
+            if column.nullable { "" } else { " NOT NULL" }
+        ),
+        SchemaChange::DropColumn { column_name, .. } => format!("Dropped column `{}`", column_name),
+        SchemaChange::AlterColumn { column_name, new_type, new_nullable, new_default, .. } => {
+            let mut parts = Vec::new();
+            if let Some(data_type) = new_type {
+                parts.push(format!("type {}", data_type));
+            }
+            match new_nullable {
+                Some(true) => parts.push("nullable".to_string()),
+                Some(false) => parts.push("NOT NULL".to_string()),
+                None => {}
+            }
+            if let Some(default) = new_default {
+                parts.push(format!("default {}", default));
+            }
+            format!("Changed column `{}`: {}", column_name, parts.join(", "))
+        }
+        SchemaChange::RenameColumn { old_name, new_name, .. } => {
+            format!("Renamed column `{}` to `{}`", old_name, new_name)
+        }
+        SchemaChange::AddIndex { index_name, columns, unique, .. } => format!(
+            "Added {}index `{}` on ({})",
+            if *unique { "unique " } else { "" },
+            index_name,
+            columns.join(", ")
+        ),
+        SchemaChange::DropIndex { index_name } => format!("Dropped index `{}`", index_name),
+        SchemaChange::AddForeignKey { constraint_name, columns, ref_table, ref_columns, .. } => format!(
+            "Added foreign key `{}` ({}) → `{}` ({})",
+            constraint_name,
+            columns.join(", "),
+            ref_table,
+            ref_columns.join(", ")
+        ),
+        SchemaChange::AddUnique { constraint_name, columns, .. } => {
+            format!("Added unique constraint `{}` on ({})", constraint_name, columns.join(", "))
+        }
+        SchemaChange::AddCheck { constraint_name, expression, .. } => {
+            format!("Added check `{}`: {}", constraint_name, expression)
+        }
+        SchemaChange::DropForeignKey { constraint_name, .. } | SchemaChange::DropConstraint { constraint_name, .. } => {
+            format!("Dropped constraint `{}`", constraint_name)
+        }
+        SchemaChange::ValidateConstraint { constraint_name, .. } => format!("Validated constraint `{}`", constraint_name),
+        SchemaChange::Backfill { column_name, .. } => format!("Backfilled column `{}`", column_name),
+        SchemaChange::CreateExtension { name, version, .. } => match version {
+            Some(version) => format!("Installed extension `{}` {}", name, version),
+            None => format!("Installed extension `{}`", name),
+        },
+        SchemaChange::DropExtension { name, .. } => format!("Removed extension `{}`", name),
+        SchemaChange::AlterExtensionVersion { name, version } => format!("Updated extension `{}` to {}", name, version),
+        SchemaChange::CreatePartition { table_name, parent_table, .. } => {
+            format!("Created partition `{}` of `{}`", table_name, parent_table)
+        }
+        SchemaChange::AttachPartition { parent_table, partition_name, .. } => {
+            format!("Attached partition `{}` to `{}`", partition_name, parent_table)
+        }
+        SchemaChange::DetachPartition { parent_table, partition_name, .. } => {
+            format!("Detached partition `{}` from `{}`", partition_name, parent_table)
+        }
+        SchemaChange::ReorderColumns { column_order, .. } => format!("Reordered columns: {}", column_order.join(", ")),
+        SchemaChange::CreatePolicy { policy_name, .. } => format!("Added row-level security policy `{}`", policy_name),
+        SchemaChange::AlterPolicy { policy_name, .. } => format!("Changed row-level security policy `{}`", policy_name),
+        SchemaChange::DropPolicy { policy_name, .. } => format!("Dropped row-level security policy `{}`", policy_name),
+        SchemaChange::SetRowSecurity { enabled, .. } => {
+            format!("{} row-level security", if *enabled { "Enabled" } else { "Disabled" })
+        }
+        SchemaChange::SetCustomField { column_name, field, value, .. } => {
+            let target = column_name.as_deref().map(|c| format!(" on `{}`", c)).unwrap_or_default();
+            match value {
+                Some(value) => format!("Set {}{} to {}", field, target, value),
+                None => format!("Cleared {}{}", field, target),
+            }
+        }
+        SchemaChange::AddTag { column_name, tag, .. } => {
+            format!("Tagged {}with `{}`", column_name.as_deref().map(|c| format!("`{}` ", c)).unwrap_or_default(), tag)
+        }
+        SchemaChange::RemoveTag { column_name, tag, .. } => format!(
+            "Removed tag `{}`{}",
+            tag,
+            column_name.as_deref().map(|c| format!(" from `{}`", c)).unwrap_or_default()
+        ),
+        SchemaChange::SetClassification { column_name, level, .. } => match level {
+            Some(level) => format!("Classified `{}` as {:?}", column_name, level),
+            None => format!("Cleared the classification of `{}`", column_name),
+        },
+        SchemaChange::CreateDomain { domain_name, base_type, .. } => {
+            format!("Created domain `{}` over {}", domain_name, base_type)
+        }
+        SchemaChange::AlterDomain { domain_name, .. } => format!("Changed domain `{}`", domain_name),
+        SchemaChange::DropDomain { domain_name, .. } => format!("Dropped domain `{}`", domain_name),
+        SchemaChange::CreateCompositeType { type_name, attributes } => format!(
+            "Created type `{}` ({})",
+            type_name,
+            attributes.iter().map(|a| format!("`{}` {}", a.name, a.data_type)).collect::<Vec<_>>().join(", ")
+        ),
+        SchemaChange::AlterCompositeType { type_name, .. } => format!("Changed type `{}`", type_name),
+        SchemaChange::DropCompositeType { type_name, .. } => format!("Dropped type `{}`", type_name),
+        SchemaChange::CreateView { view_name, .. } => format!("Created view `{}`", view_name),
+        SchemaChange::AlterView { view_name, .. } => format!("Redefined view `{}`", view_name),
+        SchemaChange::DropView { view_name, .. } => format!("Dropped view `{}`", view_name),
+        SchemaChange::CreateMaterializedView { view_name, .. } => {
+            format!("Created materialized view `{}`", view_name)
+        }
+        SchemaChange::DropMaterializedView { view_name, .. } => format!("Dropped materialized view `{}`", view_name),
+        // Refreshing leaves the schema as it was
+        SchemaChange::RefreshMaterializedView { .. } => String::new(),
+        // Maintenance leaves the schema as it was
+        SchemaChange::Reindex { .. } | SchemaChange::Vacuum { .. } | SchemaChange::Analyze { .. } => String::new(),
+    }
+}
+
+/// Whether a change affects the database rather than particular tables
+fn is_database_level(change: &SchemaChange) -> bool {
+    matches!(
+        change,
+        SchemaChange::CreateSchema { .. }
+            | SchemaChange::DropSchema { .. }
+            | SchemaChange::RenameSchema { .. }
+            | SchemaChange::CreateExtension { .. }
+            | SchemaChange::DropExtension { .. }
+            | SchemaChange::AlterExtensionVersion { .. }
+            | SchemaChange::CreateDomain { .. }
+            | SchemaChange::AlterDomain { .. }
+            | SchemaChange::DropDomain { .. }
+            | SchemaChange::CreateCompositeType { .. }
+            | SchemaChange::AlterCompositeType { .. }
+            | SchemaChange::DropCompositeType { .. }
+    )
+}
+
+/// Tables a change belongs under, schema-qualified
+fn change_tables(change: &SchemaChange, snapshot: &SchemaSnapshot) -> BTreeSet<String> {
+    match change {
+        SchemaChange::DropIndex { index_name } => {
+            // Placed with its table when the snapshot still knows it
+            let qualified = qualify(index_name);
+            snapshot
+                .indexes
+                .iter()
+                .find(|i| format!("{}.{}", i.schema, i.name) == qualified)
+                .map(|i| BTreeSet::from([format!("{}.{}", i.schema, i.table)]))
+                .unwrap_or_default()
+        }
+        _ => touched_tables(std::slice::from_ref(change), snapshot),
+    }
+}
+
+/// Table a drift item belongs to; None for schemas, extensions, types and the database
+pub fn drift_table(item: &SchemaDiffItem) -> Option<String> {
+    match item.object_type {
+        ObjectType::Database
+        | ObjectType::Schema
+        | ObjectType::Extension
+        | ObjectType::Domain
+        | ObjectType::CompositeType => None,
+        ObjectType::Index => {
+            let index = item.after.as_ref().or(item.before.as_ref())?;
+            Some(format!("{}.{}", index["schema"].as_str()?, index["table"].as_str()?))
+        }
+        _ => {
+            let mut parts = item.object_path.splitn(3, '.');
+            Some(format!("{}.{}", parts.next()?, parts.next()?))
+        }
+    }
+}
+
+/// Build the changelog of proposals executed and drift detected in `from..=to`
+pub fn build(
+    connection_id: Uuid,
+    connection_name: &str,
+    (from, to): (DateTime<Utc>, DateTime<Utc>),
+    executed: &[ExecutedProposal],
+    drift: &[DriftWindow],
+    snapshot: &SchemaSnapshot,
+) -> Changelog {
+    let in_range = |at: DateTime<Utc>| at >= from && at <= to;
+    let mut groups: BTreeMap<String, Vec<ChangelogEntry>> = BTreeMap::new();
+
+    for executed_proposal in executed {
+        let proposal = executed_proposal.proposal;
+        let Some(at) = proposal.executed_at.filter(|at| in_range(*at)) else {
+            continue;
+        };
+        let source = EntrySource::Proposal {
+            proposal_id: proposal.id,
+            title: proposal.title.clone(),
+            executed_by: executed_proposal.executed_by.clone(),
+            href: format!("/proposals/{}", proposal.id),
+        };
+        for change in &proposal.changes {
+            let description = describe(change);
+            if description.is_empty() {
+                continue;
+            }
+            let tables = change_tables(change, snapshot);
+            let keys = if tables.is_empty() { vec![DATABASE_GROUP.to_string()] } else { tables.into_iter().collect() };
+            for key in keys {
+                groups.entry(key).or_default().push(ChangelogEntry {
+                    at,
+                    description: description.clone(),
+                    source: source.clone(),
+                });
+            }
+        }
+    }
+
+    for window in drift.iter().filter(|w| in_range(w.captured_at)) {
+        for item in &window.changes {
+            groups
+                .entry(drift_table(item).unwrap_or_else(|| DATABASE_GROUP.to_string()))
+                .or_default()
+                .push(ChangelogEntry {
+                    at: window.captured_at,
+                    description: item.description.clone(),
+                    source: EntrySource::Drift { from_version: window.from_version, to_version: window.to_version },
+                });
+        }
+    }
+
+    // Database-wide changes first, then tables by name
+    let mut groups: Vec<ChangelogGroup> = groups
+        .into_iter()
+        .map(|(table, mut entries)| {
+            entries.sort_by_key(|e| e.at);
+            ChangelogGroup { table, entries }
+        })
+        .collect();
+    groups.sort_by_key(|g| g.table != DATABASE_GROUP);
+
+    let markdown = render(connection_name, from, to, &groups);
+    Changelog { connection_id, from, to, groups, markdown }
+}
+
+fn render(connection_name: &str, from: DateTime<Utc>, to: DateTime<Utc>, groups: &[ChangelogGroup]) -> String {
+    let mut out = format!(
+        "# Changelog: {}\n\n{} to {}\n",
+        connection_name,
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d")
+    );
+    if groups.is_empty() {
+        out.push_str("\nNo schema changes.\n");
+    }
+    for group in groups {
+        out.push_str(&format!("\n## {}\n\n", group.table));
+        for entry in &group.entries {
+            let source = match &entry.source {
+                EntrySource::Proposal { title, executed_by, href, .. } => format!(
+                    "[{}]({}){}",
+                    title,
+                    href,
+                    executed_by.as_deref().map(|by| format!(", executed by {}", by)).unwrap_or_default()
+                ),
+                EntrySource::Drift { from_version, to_version } => {
+                    format!("drift between snapshots v{} and v{}", from_version, to_version)
+                }
+            };
+            out.push_str(&format!("- {} ({}, {})\n", entry.description, source, entry.at.format("%Y-%m-%d")));
+        }
+    }
+    out
+}
+
+/// Drop drift items that a proposal executed in the window accounts for
+fn unexplained(changes: Vec<SchemaDiffItem>, proposals: &[&SchemaProposal], snapshot: &SchemaSnapshot) -> Vec<SchemaDiffItem> {
+    let mut tables = BTreeSet::new();
+    let mut database_level = false;
+    for change in proposals.iter().flat_map(|p| &p.changes) {
+        tables.extend(change_tables(change, snapshot));
+        database_level |= is_database_level(change);
+    }
+    changes
+        .into_iter()
+        .filter(|item| match drift_table(item) {
+            Some(table) => !tables.contains(&table),
+            None => !database_level,
+        })
+        .collect()
+}
+
+/// Gather executed proposals, executors and drift, and build the changelog
+pub async fn generate(
+    state: &AppState,
+    connection_id: Uuid,
+    range: (DateTime<Utc>, DateTime<Utc>),
+) -> Result<Changelog, AppError> {
+    let (from, to) = range;
+    let connection_name = state
+        .connections
+        .get_connection(connection_id)
+        .await
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| connection_id.to_string());
+    let scope = state.connections.schema_scope(connection_id).await;
+    let snapshot = state
+        .snapshots
+        .get_latest(connection_id)
+        .await?
+        .map(|s| s.scoped(&scope))
+        .unwrap_or_else(|| SchemaSnapshot::empty(connection_id));
+
+    let proposals: Vec<SchemaProposal> = state
+        .pipeline_proposals
+        .list()
+        .await?
+        .into_iter()
+        .filter(|p| p.connection_id == connection_id && p.executed_at.is_some())
+        .filter(|p| matches!(p.status, ProposalStatus::Executed | ProposalStatus::VerificationFailed))
+        .collect();
+
+    // The real execution is the first one audited once the proposal was marked executed
+    let audit_log = state.metadata.get_audit_log().await?;
+    let executed: Vec<ExecutedProposal> = proposals
+        .iter()
+        .map(|proposal| ExecutedProposal {
+            executed_by: audit_log
+                .iter()
+                .filter(|e| matches!(e.action, AuditAction::ProposalExecuted))
+                .filter(|e| e.target_id == proposal.id.to_string())
+                .find(|e| proposal.executed_at.is_some_and(|at| e.timestamp >= at))
+                .map(|e| e.actor.clone()),
+            proposal,
+        })
+        .collect();
+
+    let mut versions = state.snapshots.list(connection_id).await?;
+    versions.sort_by_key(|m| m.version);
+    let ignore = state.connections.diff_ignore(connection_id).await?;
+    let mut drift = Vec::new();
+    for pair in versions.windows(2) {
+        let (older, newer) = (&pair[0], &pair[1]);
+        if newer.captured_at < from || newer.captured_at > to {
+            continue;
+        }
+        let (Some(before), Some(after)) = (
+            state.snapshots.get_version(connection_id, older.version).await?,
+            state.snapshots.get_version(connection_id, newer.version).await?,
+        ) else {
+            continue;
+        };
+        let before = before.scoped(&scope);
+        let diff = DiffEngine::diff_ignoring(&before, &after.scoped(&scope), &ignore);
+        let between: Vec<&SchemaProposal> = proposals
+            .iter()
+            .filter(|p| p.executed_at.is_some_and(|at| at > older.captured_at && at <= newer.captured_at))
+            .collect();
+        let changes = unexplained(diff.changes, &between, &before);
+        if !changes.is_empty() {
+            drift.push(DriftWindow {
+                from_version: older.version,
+                to_version: newer.version,
+                captured_at: newer.captured_at,
+                changes,
+            });
+        }
+    }
+
+    Ok(build(connection_id, &connection_name, (from, to), &executed, &drift, &snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::types::ColumnDef;
+    use crate::snapshot::diff::RiskLevel;
+    use crate::snapshot::ChangeType;
+
+    #[test]
+    fn test_range_accepts_dates() {
+        let now = Utc::now();
+        let query = ChangelogQuery { from: Some("2026-10-01".to_string()), to: Some("2026-10-31".to_string()) };
+        let (from, to) = query.range(now).unwrap();
+        assert_eq!(from.to_rfc3339(), "2026-10-01T00:00:00+00:00");
+        assert_eq!(to.format("%Y-%m-%d %H:%M:%S").to_string(), "2026-10-31 23:59:59");
+
+        let (from, to) = ChangelogQuery::default().range(now).unwrap();
+        assert_eq!(to, now);
+        assert_eq!(to - from, Duration::days(DEFAULT_RANGE_DAYS));
+
+        let backwards = ChangelogQuery { from: Some("2026-11-01".to_string()), to: Some("2026-10-01".to_string()) };
+        assert!(backwards.range(now).is_err());
+    }
+
+    #[test]
+    fn test_build_groups_proposals_and_drift_by_table() {
+        let connection_id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut proposal = SchemaProposal::new(connection_id, "Add emails".to_string(), String::new(), "alice".to_string());
+        proposal.executed_at = Some(now - Duration::days(2));
+        proposal.changes = vec![
+            SchemaChange::AddColumn {
+                table_name: "users".to_string(),
+                column: ColumnDef {
+                    name: "email".to_string(),
+                    data_type: "text".to_string(),
+                    nullable: false,
+                    default_value: None,
+                    is_primary_key: false,
+                    collation: None,
+                },
+            },
+            SchemaChange::CreateExtension { name: "citext".to_string(), schema: None, version: None },
+            SchemaChange::Analyze { table_name: "users".to_string() },
+        ];
+        let executed = [ExecutedProposal { proposal: &proposal, executed_by: Some("bob".to_string()) }];
+        let drift = [DriftWindow {
+            from_version: 3,
+            to_version: 4,
+            captured_at: now - Duration::days(1),
+            changes: vec![SchemaDiffItem {
+                change_type: ChangeType::Removed,
+                object_type: ObjectType::Column,
+                object_path: "public.orders.legacy".to_string(),
+                description: "Column public.orders.legacy removed".to_string(),
+                before: None,
+                after: None,
+                risk_level: RiskLevel::High,
+                is_breaking: true,
+            }],
+        }];
+
+        let range = (now - Duration::days(7), now);
+        let changelog = build(connection_id, "prod", range, &executed, &drift, &SchemaSnapshot::empty(connection_id));
+        let tables: Vec<_> = changelog.groups.iter().map(|g| g.table.as_str()).collect();
+        assert_eq!(tables, vec!["Database", "public.orders", "public.users"]);
+        assert_eq!(changelog.groups[2].entries.len(), 1);
+        assert!(changelog.markdown.contains(&format!(
+            "- Added column `email` text NOT NULL ([Add emails](/proposals/{}), executed by bob,",
+            proposal.id
+        )));
+        assert!(changelog.markdown.contains("drift between snapshots v3 and v4"));
+
+        let earlier = (now - Duration::days(30), now - Duration::days(10));
+        let empty = build(connection_id, "prod", earlier, &executed, &drift, &SchemaSnapshot::empty(connection_id));
+        assert!(empty.groups.is_empty());
+        assert!(empty.markdown.ends_with("No schema changes.\n"));
+    }
+}
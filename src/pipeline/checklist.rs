@@ -0,0 +1,182 @@
+//! Review checklists
+//!
+//! Each risk analysis turns what it found into a checklist on the proposal:
+//! confirm a backup of whatever the changes destroy, tell the owners of
+//! views in the blast radius, act on every recommendation and rule
+//! violation. Backups, error and blocking rule violations, and the
+//! recommendations of high or critical risk proposals are required; the
+//! proposal cannot be approved until an approver has ticked them. The rest
+//! are advice.
+//!
+//! Items are keyed by the finding behind them, so ticks survive a fresh
+//! analysis as long as the finding does, and go when it goes.
+
+use crate::pipeline::backup;
+use crate::pipeline::proposal::{RiskAnalysis, RiskLevel, SchemaProposal};
+use crate::snapshot::blast_radius::ImpactType;
+use crate::snapshot::{RuleViolation, Severity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Finding an item comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecklistSource {
+    Backup,
+    BlastRadius,
+    Recommendation,
+    Rule,
+}
+
+/// Something to confirm before approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistItem {
+    /// Stable key of the finding, e.g. `rule:no-drop-table:public.users`
+    pub id: String,
+    pub text: String,
+    pub source: ChecklistSource,
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ChecklistItem {
+    fn new(id: String, text: String, source: ChecklistSource, required: bool) -> Self {
+        Self { id, text, source, required, checked_by: None, checked_at: None, note: None }
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked_at.is_some()
+    }
+}
+
+/// Checklist for a proposal from its latest findings
+pub fn build(proposal: &SchemaProposal, analysis: &RiskAnalysis, violations: &[RuleViolation]) -> Vec<ChecklistItem> {
+    let mut items = Vec::new();
+
+    if let Some((tables, full)) = backup::destroyed_by(&proposal.changes) {
+        let text = match full {
+            true => "Confirm a backup of the whole database exists".to_string(),
+            false => format!(
+                "Confirm a backup of {} exists",
+                tables.iter().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        };
+        items.push(ChecklistItem::new("backup".to_string(), text, ChecklistSource::Backup, true));
+    }
+
+    let views: BTreeSet<&str> = proposal
+        .blast_radius
+        .iter()
+        .flat_map(|report| &report.objects)
+        .flat_map(|radius| &radius.impacted)
+        .filter(|object| object.object_type == ImpactType::View)
+        .map(|object| object.path.as_str())
+        .collect();
+    for view in views {
+        items.push(ChecklistItem::new(
+            format!("notify:{}", view),
+            format!("Notify the owners of view {}", view),
+            ChecklistSource::BlastRadius,
+            false,
+        ));
+    }
+
+    let high_risk = matches!(analysis.overall_risk, RiskLevel::High | RiskLevel::Critical);
+    for (text, message) in analysis.recommendations.iter().zip(&analysis.recommendation_codes) {
+        let mut id = format!("risk:{}", message.code);
+        for value in message.params.values() {
+            id.push(':');
+            id.push_str(value);
+        }
+        items.push(ChecklistItem::new(id, text.clone(), ChecklistSource::Recommendation, high_risk));
+    }
+
+    for violation in violations.iter().filter(|v| v.severity != Severity::Info) {
+        items.push(ChecklistItem::new(
+            format!("rule:{}:{}", violation.rule_id, violation.affected_object),
+            violation.suggestion.clone().unwrap_or_else(|| format!("Resolve: {}", violation.message)),
+            ChecklistSource::Rule,
+            matches!(violation.severity, Severity::Error | Severity::Block),
+        ));
+    }
+
+    let mut seen = BTreeSet::new();
+    items.retain(|item| seen.insert(item.id.clone()));
+    items
+}
+
+/// Keep the ticks of items that are still on the list
+pub fn carry_over(items: &mut [ChecklistItem], previous: &[ChecklistItem]) {
+    for item in items {
+        if let Some(old) = previous.iter().find(|old| old.id == item.id && old.is_checked()) {
+            item.checked_by = old.checked_by.clone();
+            item.checked_at = old.checked_at;
+            item.note = old.note.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Message;
+    use crate::pipeline::types::SchemaChange;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_checklist_from_findings_keeps_ticks() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Drop legacy".to_string(), String::new(), "dev".to_string());
+        proposal.changes = vec![SchemaChange::DropTable { table_name: "legacy".to_string() }];
+
+        let mut analysis = RiskAnalysis {
+            overall_risk: RiskLevel::Medium,
+            score: 40,
+            warnings: vec![],
+            recommendations: vec!["Consider testing this migration on a staging environment first".to_string()],
+            warning_codes: vec![],
+            recommendation_codes: vec![Message::new("risk.test_on_staging")],
+            estimated_duration_secs: 4,
+            requires_downtime: false,
+            affected_tables: vec!["legacy".to_string()],
+            analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: vec![],
+        };
+        let violation = |severity: Severity| RuleViolation {
+            rule_id: "no-drop-table".to_string(),
+            rule_name: "No dropped tables".to_string(),
+            severity,
+            message: "Dropping table legacy".to_string(),
+            affected_object: "public.legacy".to_string(),
+            suggestion: None,
+            params: Default::default(),
+        };
+        let rules = vec![violation(Severity::Error), violation(Severity::Info)];
+
+        let items = build(&proposal, &analysis, &rules);
+        let summary: Vec<(&str, bool)> = items.iter().map(|i| (i.id.as_str(), i.required)).collect();
+        assert_eq!(
+            summary,
+            vec![("backup", true), ("risk:risk.test_on_staging", false), ("rule:no-drop-table:public.legacy", true)]
+        );
+        assert_eq!(items[0].text, "Confirm a backup of public.legacy exists");
+
+        // A tick survives re-analysis; recommendations of risky proposals become required
+        let mut ticked = items.clone();
+        ticked[0].checked_by = Some("lead".to_string());
+        ticked[0].checked_at = Some(Utc::now());
+        analysis.overall_risk = RiskLevel::High;
+        let mut rebuilt = build(&proposal, &analysis, &rules);
+        carry_over(&mut rebuilt, &ticked);
+        assert!(rebuilt[0].is_checked());
+        assert!(rebuilt[1].required && !rebuilt[1].is_checked());
+    }
+}
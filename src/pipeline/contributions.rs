@@ -0,0 +1,122 @@
+//! Per-user governance activity
+//!
+//! Rolls a user's audit entries up into counts of proposals authored,
+//! reviews given, executions performed and comments written, with a weekly
+//! breakdown for team leads tracking participation.
+
+use crate::pipeline::analytics::week_start;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How many of the latest entries are returned as the feed
+const FEED_LIMIT: usize = 50;
+
+/// Contribution counts for a period
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionCounts {
+    pub proposals_authored: usize,
+    /// Approvals and rejections
+    pub reviews_given: usize,
+    pub executions_performed: usize,
+    pub comments: usize,
+}
+
+impl ContributionCounts {
+    fn add(&mut self, action: &AuditAction) {
+        match action {
+            AuditAction::ProposalCreated => self.proposals_authored += 1,
+            AuditAction::ProposalApproved | AuditAction::TeamApprovalRecorded | AuditAction::ProposalRejected => {
+                self.reviews_given += 1
+            }
+            AuditAction::ProposalExecuted => self.executions_performed += 1,
+            AuditAction::CommentAdded => self.comments += 1,
+            _ => {}
+        }
+    }
+}
+
+/// One week of activity, starting on `week_start` (a Monday)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyContributions {
+    pub week_start: DateTime<Utc>,
+    #[serde(flatten)]
+    pub counts: ContributionCounts,
+}
+
+/// A user's activity over a period
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivity {
+    pub user_id: String,
+    pub since: DateTime<Utc>,
+    pub totals: ContributionCounts,
+    pub weekly: Vec<WeeklyContributions>,
+    /// Latest entries first
+    pub recent: Vec<AuditEntry>,
+}
+
+/// Summarize a user's audit entries captured since `since`
+pub fn summarize(user_id: &str, entries: Vec<AuditEntry>, since: DateTime<Utc>) -> UserActivity {
+    let mut entries: Vec<AuditEntry> = entries
+        .into_iter()
+        .filter(|e| e.actor == user_id && e.timestamp >= since)
+        .collect();
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    let mut totals = ContributionCounts::default();
+    let mut weeks: BTreeMap<DateTime<Utc>, ContributionCounts> = BTreeMap::new();
+    for entry in &entries {
+        totals.add(&entry.action);
+        weeks.entry(week_start(entry.timestamp)).or_default().add(&entry.action);
+    }
+
+    entries.truncate(FEED_LIMIT);
+    UserActivity {
+        user_id: user_id.to_string(),
+        since,
+        totals,
+        weekly: weeks
+            .into_iter()
+            .map(|(week_start, counts)| WeeklyContributions { week_start, counts })
+            .collect(),
+        recent: entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(action: AuditAction, actor: &str, days_ago: i64) -> AuditEntry {
+        let mut entry = AuditEntry::new(action, actor, "proposal", "p");
+        entry.timestamp = Utc::now() - Duration::days(days_ago);
+        entry
+    }
+
+    #[test]
+    fn test_summarize_counts_user_actions_in_period() {
+        let entries = vec![
+            entry(AuditAction::ProposalCreated, "7", 1),
+            entry(AuditAction::ProposalApproved, "7", 2),
+            entry(AuditAction::ProposalRejected, "7", 3),
+            entry(AuditAction::CommentAdded, "7", 3),
+            entry(AuditAction::RiskReanalyzed, "7", 3),
+            entry(AuditAction::ProposalExecuted, "8", 1),
+            entry(AuditAction::ProposalExecuted, "7", 40),
+        ];
+
+        let activity = summarize("7", entries, Utc::now() - Duration::days(30));
+        assert_eq!(
+            activity.totals,
+            ContributionCounts { proposals_authored: 1, reviews_given: 2, executions_performed: 0, comments: 1 }
+        );
+        assert_eq!(activity.recent.len(), 5);
+        assert!(activity.recent[0].timestamp >= activity.recent[1].timestamp);
+        assert!(!activity.weekly.is_empty());
+    }
+}
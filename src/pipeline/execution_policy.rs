@@ -0,0 +1,138 @@
+//! Execution session settings
+//!
+//! A project's execution policy can tune the server for migration work, such
+//! as more `maintenance_work_mem` and parallel workers for index builds, or
+//! `synchronous_commit = off` where losing the last commits on a crash is
+//! acceptable. The orchestrator applies the settings with `SET LOCAL` inside
+//! each transaction, so they never outlive the migration on a pooled
+//! connection, and records them with the execution result. The policy also
+//! holds the statement watchdog's kill policy, if the project wants one, and
+//! how recent a whole-database backup destructive changes require.
+
+use crate::error::AppError;
+use crate::pipeline::watchdog::WatchdogPolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings a policy may override; anything touching roles, search paths or
+/// security stays out of reach
+pub const ALLOWED_SETTINGS: &[&str] = &[
+    "maintenance_work_mem",
+    "max_parallel_maintenance_workers",
+    "max_parallel_workers_per_gather",
+    "work_mem",
+    "synchronous_commit",
+    "statement_timeout",
+    "lock_timeout",
+    "idle_in_transaction_session_timeout",
+];
+
+const SYNCHRONOUS_COMMIT_VALUES: &[&str] = &["on", "off", "local", "remote_write", "remote_apply"];
+
+/// Longest value accepted for a setting
+const MAX_VALUE_LEN: usize = 32;
+
+/// Per-project settings applied to migration sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExecutionPolicy {
+    /// Setting name to value, e.g. `maintenance_work_mem` = `1GB`
+    pub settings: BTreeMap<String, String>,
+    /// Cancel statements running far past their estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<WatchdogPolicy>,
+    /// Destructive proposals only start with a whole-database backup of the
+    /// target completed within this many hours
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_max_age_hours: Option<u32>,
+}
+
+/// Oldest backup a policy can accept, in hours
+pub const MAX_BACKUP_AGE_HOURS: u32 = 24 * 30;
+
+impl ExecutionPolicy {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.validate()?;
+        }
+        if let Some(hours) = self.backup_max_age_hours {
+            if hours == 0 || hours > MAX_BACKUP_AGE_HOURS {
+                return Err(AppError::Validation(format!(
+                    "backupMaxAgeHours must be between 1 and {}",
+                    MAX_BACKUP_AGE_HOURS
+                )));
+            }
+        }
+        for (name, value) in &self.settings {
+            if !ALLOWED_SETTINGS.contains(&name.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Setting '{}' cannot be overridden; allowed: {}",
+                    name,
+                    ALLOWED_SETTINGS.join(", ")
+                )));
+            }
+            // Values are quoted into SET statements, so keep them to what
+            // sizes, durations, counts and enum values need
+            let well_formed = !value.is_empty()
+                && value.len() <= MAX_VALUE_LEN
+                && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            if !well_formed {
+                return Err(AppError::Validation(format!("Invalid value '{}' for setting '{}'", value, name)));
+            }
+            if name == "synchronous_commit" && !SYNCHRONOUS_COMMIT_VALUES.contains(&value.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "synchronous_commit must be one of {}",
+                    SYNCHRONOUS_COMMIT_VALUES.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `SET LOCAL` statements for a transaction, or session-level `SET`s for
+/// statements that cannot run inside one
+pub fn set_statements(settings: &BTreeMap<String, String>, local: bool) -> String {
+    let scope = if local { "SET LOCAL" } else { "SET" };
+    settings
+        .iter()
+        .map(|(name, value)| format!("{} {} = '{}';", scope, name, value.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Undo session-level settings before the connection goes back to the pool
+pub fn reset_statements(settings: &BTreeMap<String, String>) -> String {
+    settings.keys().map(|name| format!("RESET {};", name)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(pairs: &[(&str, &str)]) -> ExecutionPolicy {
+        ExecutionPolicy {
+            settings: pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            watchdog: None,
+            backup_max_age_hours: None,
+        }
+    }
+
+    #[test]
+    fn test_validation_and_statements() {
+        let tuned = policy(&[("maintenance_work_mem", "1GB"), ("synchronous_commit", "off")]);
+        assert!(tuned.validate().is_ok());
+        assert_eq!(
+            set_statements(&tuned.settings, true),
+            "SET LOCAL maintenance_work_mem = '1GB'; SET LOCAL synchronous_commit = 'off';"
+        );
+        assert_eq!(
+            reset_statements(&tuned.settings),
+            "RESET maintenance_work_mem; RESET synchronous_commit;"
+        );
+
+        assert!(policy(&[("role", "postgres")]).validate().is_err());
+        assert!(policy(&[("work_mem", "64MB'; DROP TABLE users; --")]).validate().is_err());
+        assert!(policy(&[("synchronous_commit", "sometimes")]).validate().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Planner cost estimates for dry runs
+//!
+//! A dry run asks the planner about every statement it can explain (data
+//! changes such as backfills and data migrations) with `EXPLAIN (FORMAT JSON)`,
+//! which plans without executing. The top plan node's costs and row estimate
+//! are kept per statement and added up into a cost summary for the proposal,
+//! so reviewers see how heavy the data work is rather than just pass/fail.
+//!
+//! DDL has no plan and is listed as skipped, as are statements the planner
+//! rejects, typically because they use objects created earlier in the same
+//! migration.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+
+/// Planner estimate for one statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementEstimate {
+    /// Position in the migration, from 0
+    pub index: usize,
+    /// Top plan node, e.g. "ModifyTable"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_cost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost: Option<f64>,
+    /// Rows the planner expects the statement to produce or touch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_rows: Option<i64>,
+    /// Why the statement has no estimate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+impl StatementEstimate {
+    fn skipped(index: usize, reason: impl Into<String>) -> Self {
+        Self {
+            index,
+            node_type: None,
+            startup_cost: None,
+            total_cost: None,
+            plan_rows: None,
+            skipped: Some(reason.into()),
+        }
+    }
+}
+
+/// Planner estimates for a proposal's migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostSummary {
+    pub statements: Vec<StatementEstimate>,
+    pub explained: usize,
+    pub skipped: usize,
+    /// Sum of the explained statements' total costs, in planner units
+    pub total_cost: f64,
+    pub total_rows: i64,
+    /// Index of the statement with the highest total cost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub most_expensive: Option<usize>,
+    pub estimated_at: DateTime<Utc>,
+}
+
+impl CostSummary {
+    pub fn from_estimates(statements: Vec<StatementEstimate>) -> Self {
+        let explained: Vec<&StatementEstimate> = statements.iter().filter(|s| s.total_cost.is_some()).collect();
+        let most_expensive = explained
+            .iter()
+            .max_by(|a, b| a.total_cost.unwrap_or(0.0).total_cmp(&b.total_cost.unwrap_or(0.0)))
+            .map(|s| s.index);
+
+        Self {
+            explained: explained.len(),
+            skipped: statements.len() - explained.len(),
+            total_cost: explained.iter().filter_map(|s| s.total_cost).sum(),
+            total_rows: explained.iter().filter_map(|s| s.plan_rows).sum(),
+            most_expensive,
+            estimated_at: Utc::now(),
+            statements,
+        }
+    }
+}
+
+/// Whether EXPLAIN accepts the statement
+pub fn explainable(statement: &str) -> bool {
+    let keyword = statement
+        .lines()
+        .map(str::trim_start)
+        .find(|l| !l.is_empty() && !l.starts_with("--"))
+        .and_then(|l| l.split_whitespace().next())
+        .unwrap_or_default()
+        .to_uppercase();
+    matches!(keyword.as_str(), "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "WITH" | "VALUES")
+}
+
+/// Read the top plan node out of `EXPLAIN (FORMAT JSON)` output
+pub fn parse_plan(index: usize, output: &serde_json::Value) -> StatementEstimate {
+    let Some(plan) = output.get(0).and_then(|o| o.get("Plan")) else {
+        return StatementEstimate::skipped(index, "EXPLAIN returned no plan");
+    };
+    StatementEstimate {
+        index,
+        node_type: plan.get("Node Type").and_then(|v| v.as_str()).map(str::to_string),
+        startup_cost: plan.get("Startup Cost").and_then(|v| v.as_f64()),
+        total_cost: plan.get("Total Cost").and_then(|v| v.as_f64()),
+        plan_rows: plan.get("Plan Rows").and_then(|v| v.as_i64()),
+        skipped: None,
+    }
+}
+
+/// Explain `statements[start_at..]`. A statement the planner rejects is
+/// recorded as skipped rather than failing the dry run.
+pub async fn estimate(pool: &Pool, statements: &[String], start_at: usize) -> Result<CostSummary, AppError> {
+    let client = pool.get().await?;
+    let mut estimates = Vec::with_capacity(statements.len().saturating_sub(start_at));
+
+    for (index, statement) in statements.iter().enumerate().skip(start_at) {
+        if !explainable(statement) {
+            estimates.push(StatementEstimate::skipped(index, "No plan for DDL"));
+            continue;
+        }
+        let sql = format!("EXPLAIN (FORMAT JSON) {}", statement.trim_end().trim_end_matches(';'));
+        match client.query_one(&sql, &[]).await {
+            Ok(row) => estimates.push(parse_plan(index, &row.get::<_, serde_json::Value>(0))),
+            Err(e) => {
+                let reason = e.as_db_error().map(|d| d.message().to_string()).unwrap_or_else(|| e.to_string());
+                estimates.push(StatementEstimate::skipped(index, format!("Planner rejected it: {}", reason)));
+            }
+        }
+    }
+
+    Ok(CostSummary::from_estimates(estimates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_adds_up_explained_statements() {
+        assert!(explainable("-- backfill batch 1/2\nUPDATE t SET c = 0 WHERE c IS NULL;"));
+        assert!(!explainable("ALTER TABLE t ADD COLUMN c integer;"));
+
+        let output = serde_json::json!([{
+            "Plan": { "Node Type": "ModifyTable", "Startup Cost": 0.0, "Total Cost": 1520.5, "Plan Rows": 10000 }
+        }]);
+        let summary = CostSummary::from_estimates(vec![
+            StatementEstimate::skipped(0, "No plan for DDL"),
+            parse_plan(1, &output),
+            parse_plan(2, &serde_json::json!([{ "Plan": { "Total Cost": 20.0, "Plan Rows": 5 } }])),
+        ]);
+
+        assert_eq!(summary.explained, 2);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.total_cost, 1540.5);
+        assert_eq!(summary.total_rows, 10005);
+        assert_eq!(summary.most_expensive, Some(1));
+        assert_eq!(summary.statements[1].node_type.as_deref(), Some("ModifyTable"));
+    }
+}
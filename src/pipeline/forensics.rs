@@ -0,0 +1,206 @@
+//! Failed execution forensics
+//!
+//! When a migration statement fails, the database is asked right away who
+//! holds locks on the affected tables and what autovacuum has been doing to
+//! them. The answers are stored with the execution so a post-mortem does not
+//! depend on someone reaching the database before the evidence is gone.
+
+use crate::activity::sanitize_query;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+
+/// Context captured when a migration statement fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureForensics {
+    /// Zero-based position of the failing statement in the migration
+    pub statement_index: usize,
+    pub failed_statement: String,
+    pub error: ServerError,
+    pub lock_holders: Vec<LockHolder>,
+    pub autovacuum: Vec<AutovacuumActivity>,
+    /// Set when the follow-up queries themselves could not run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_error: Option<String>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Error fields reported by PostgreSQL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerError {
+    /// SQLSTATE code, e.g. 55P03 for lock_not_available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl From<&tokio_postgres::Error> for ServerError {
+    fn from(e: &tokio_postgres::Error) -> Self {
+        match e.as_db_error() {
+            Some(db) => Self {
+                code: Some(db.code().code().to_string()),
+                message: db.message().to_string(),
+                detail: db.detail().map(str::to_string),
+                hint: db.hint().map(str::to_string),
+            },
+            // Connection-level failure; there is no server error to report
+            None => Self {
+                code: None,
+                message: e.to_string(),
+                detail: None,
+                hint: None,
+            },
+        }
+    }
+}
+
+/// A session holding or waiting for a lock on an affected table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockHolder {
+    pub pid: i32,
+    pub relation: String,
+    pub mode: String,
+    pub granted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Query text with literals masked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_secs: Option<f64>,
+}
+
+/// Autovacuum history and progress for an affected table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutovacuumActivity {
+    pub table_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_autovacuum: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_autoanalyze: Option<DateTime<Utc>>,
+    pub autovacuum_count: i64,
+    pub dead_tuples: i64,
+    /// Phase of a vacuum running on the table right now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running_phase: Option<String>,
+}
+
+/// Collect forensics for a failed statement on a fresh connection
+pub async fn capture(
+    pool: &Pool,
+    statement_index: usize,
+    failed_statement: &str,
+    error: &tokio_postgres::Error,
+    tables: &[String],
+) -> FailureForensics {
+    let mut forensics = FailureForensics {
+        statement_index,
+        failed_statement: failed_statement.to_string(),
+        error: ServerError::from(error),
+        lock_holders: Vec::new(),
+        autovacuum: Vec::new(),
+        capture_error: None,
+        captured_at: Utc::now(),
+    };
+
+    match query_context(pool, tables).await {
+        Ok((lock_holders, autovacuum)) => {
+            forensics.lock_holders = lock_holders;
+            forensics.autovacuum = autovacuum;
+        }
+        Err(e) => forensics.capture_error = Some(e.to_string()),
+    }
+
+    forensics
+}
+
+async fn query_context(
+    pool: &Pool,
+    tables: &[String],
+) -> Result<(Vec<LockHolder>, Vec<AutovacuumActivity>), AppError> {
+    let client = pool.get().await?;
+    let tables: Vec<&str> = tables.iter().map(String::as_str).collect();
+
+    // to_regclass yields NULL for tables that do not exist (yet), instead of failing
+    let lock_query = r#"
+        SELECT
+            l.pid,
+            l.relation::regclass::text as relation,
+            l.mode,
+            l.granted,
+            a.usename::text as username,
+            a.application_name,
+            a.state,
+            a.query,
+            EXTRACT(EPOCH FROM (now() - a.xact_start))::float8 as transaction_secs
+        FROM pg_locks l
+        JOIN pg_stat_activity a ON a.pid = l.pid
+        WHERE l.locktype = 'relation'
+          AND l.pid <> pg_backend_pid()
+          AND l.relation IN (SELECT to_regclass(t)::oid FROM unnest($1::text[]) t)
+        ORDER BY l.granted DESC, a.xact_start NULLS LAST
+    "#;
+
+    let lock_holders = client
+        .query(lock_query, &[&tables])
+        .await?
+        .iter()
+        .map(|row| {
+            let query: Option<String> = row.get("query");
+            LockHolder {
+                pid: row.get("pid"),
+                relation: row.get("relation"),
+                mode: row.get("mode"),
+                granted: row.get("granted"),
+                username: row.get("username"),
+                application_name: row.get("application_name"),
+                state: row.get("state"),
+                query: query.map(|q| sanitize_query(&q)),
+                transaction_secs: row.get("transaction_secs"),
+            }
+        })
+        .collect();
+
+    let vacuum_query = r#"
+        SELECT
+            s.schemaname || '.' || s.relname as table_name,
+            s.last_autovacuum,
+            s.last_autoanalyze,
+            s.autovacuum_count,
+            s.n_dead_tup,
+            p.phase as running_phase
+        FROM pg_stat_user_tables s
+        LEFT JOIN pg_stat_progress_vacuum p ON p.relid = s.relid
+        WHERE s.relid IN (SELECT to_regclass(t)::oid FROM unnest($1::text[]) t)
+        ORDER BY 1
+    "#;
+
+    let autovacuum = client
+        .query(vacuum_query, &[&tables])
+        .await?
+        .iter()
+        .map(|row| AutovacuumActivity {
+            table_name: row.get("table_name"),
+            last_autovacuum: row.get("last_autovacuum"),
+            last_autoanalyze: row.get("last_autoanalyze"),
+            autovacuum_count: row.get("autovacuum_count"),
+            dead_tuples: row.get("n_dead_tup"),
+            running_phase: row.get("running_phase"),
+        })
+        .collect();
+
+    Ok((lock_holders, autovacuum))
+}
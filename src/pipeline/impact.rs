@@ -0,0 +1,158 @@
+//! Blast radius reports for proposals
+//!
+//! When a proposal is submitted, the blast radius of every existing object it
+//! touches is computed against the latest snapshot and stored on the
+//! proposal. Reviewers read the stored report instead of re-running the
+//! analysis, and the snapshot it was computed from is recorded so the result
+//! can be reproduced at approval time.
+
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::access::touched_tables;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::{BlastRadius, BlastRadiusAnalyzer, TraversalLimits};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Stored blast radius of a proposal's changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlastRadiusReport {
+    /// Snapshot the analysis ran against
+    pub snapshot_id: Uuid,
+    pub snapshot_version: u64,
+    pub computed_at: DateTime<Utc>,
+    /// One analysis per affected table or column
+    pub objects: Vec<BlastRadius>,
+}
+
+/// Object a change affects: (schema, table or view, column)
+type Target = (String, String, Option<String>);
+
+/// Analyze every object in the snapshot that the proposal's changes touch,
+/// within the connection's traversal `limits`. Objects the proposal creates
+/// have no dependents yet and are skipped.
+pub fn compute(proposal: &SchemaProposal, snapshot: &SchemaSnapshot, limits: TraversalLimits) -> BlastRadiusReport {
+    let mut targets: BTreeSet<Target> = BTreeSet::new();
+    for change in &proposal.changes {
+        // A view's dependents are the views reading it, not its base tables
+        if let SchemaChange::AlterView { view_name, .. }
+        | SchemaChange::DropView { view_name, .. }
+        | SchemaChange::DropMaterializedView { view_name, .. }
+        | SchemaChange::RefreshMaterializedView { view_name, .. } = change
+        {
+            let (schema, view) = split_table_name(view_name);
+            targets.insert((schema.unwrap_or("public").to_string(), view.to_string(), None));
+            continue;
+        }
+
+        let column = match change {
+            SchemaChange::DropColumn { table_name, column_name }
+            | SchemaChange::AlterColumn { table_name, column_name, .. }
+            | SchemaChange::RenameColumn { table_name, old_name: column_name, .. } => Some((table_name, column_name)),
+            _ => None,
+        };
+
+        match column {
+            Some((table, column)) => {
+                let (schema, table) = split_table_name(table);
+                targets.insert((schema.unwrap_or("public").to_string(), table.to_string(), Some(column.clone())));
+            }
+            None => {
+                for table in touched_tables(std::slice::from_ref(change), snapshot) {
+                    let (schema, table) = split_table_name(&table);
+                    targets.insert((schema.unwrap_or("public").to_string(), table.to_string(), None));
+                }
+            }
+        }
+    }
+
+    let objects = targets
+        .into_iter()
+        .filter(|(schema, table, column)| exists(snapshot, schema, table, column.as_deref()))
+        .map(|(schema, table, column)| match column {
+            Some(column) => BlastRadiusAnalyzer::analyze_column_within(snapshot, &schema, &table, &column, limits),
+            None => BlastRadiusAnalyzer::analyze_table_within(snapshot, &schema, &table, limits),
+        })
+        .collect();
+
+    BlastRadiusReport {
+        snapshot_id: snapshot.id,
+        snapshot_version: snapshot.version,
+        computed_at: Utc::now(),
+        objects,
+    }
+}
+
+pub(crate) fn exists(snapshot: &SchemaSnapshot, schema: &str, table: &str, column: Option<&str>) -> bool {
+    let is_view = || column.is_none() && snapshot.views.iter().flatten().any(|v| v.schema == schema && v.name == table);
+    snapshot
+        .tables
+        .iter()
+        .find(|t| t.schema == schema && t.name == table)
+        .is_some_and(|t| column.is_none_or(|c| t.columns.iter().any(|col| col.name == c)))
+        || is_view()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, Table};
+
+    #[test]
+    fn test_report_covers_existing_objects_only() {
+        let column = |name: &str| Column {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+        };
+        let snapshot = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 7,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: vec![Table {
+                name: "users".to_string(),
+                schema: "public".to_string(),
+                columns: vec![column("id"), column("email")],
+                primary_key: None,
+                position: None,
+                color: None,
+                collapsed: false,
+                governance: Default::default(),
+            }],
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        };
+
+        let mut proposal = SchemaProposal::new(snapshot.connection_id, "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes = vec![
+            SchemaChange::DropColumn { table_name: "users".to_string(), column_name: "email".to_string() },
+            SchemaChange::Analyze { table_name: "public.users".to_string() },
+            SchemaChange::CreateTable { table_name: "audit".to_string(), columns: vec![], partition_by: None },
+        ];
+
+        let report = compute(&proposal, &snapshot, TraversalLimits::default());
+        assert_eq!(report.snapshot_version, 7);
+        let sources: Vec<_> = report.objects.iter().map(|o| o.source_path.as_str()).collect();
+        assert_eq!(sources, vec!["public.users", "public.users.email"]);
+    }
+}
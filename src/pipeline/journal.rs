@@ -0,0 +1,477 @@
+//! Execution journal
+//!
+//! Every real execution writes its state transitions to durable storage: a
+//! record when it starts, an update each time a chunk commits, and a final
+//! one when it finishes. Each transition also stores a checksum of the live
+//! schema, so the database state at the last checkpoint is known.
+//!
+//! A record still marked running when the server starts belongs to an
+//! execution the process died in the middle of. Recovery marks it
+//! interrupted and puts its proposal back as failed, with the checkpoint as
+//! the place a resume would start. Target connections do not survive a
+//! restart, so the database is checked once it is reconnected: a live
+//! checksum equal to the checkpoint's means resuming is safe, anything else
+//! needs a person to look before resuming or rolling back.
+
+use crate::error::AppError;
+use crate::introspection::{ColumnOrder, PostgresIntrospector};
+use crate::pipeline::metadata::{AuditAction, AuditEntry, ProposalSummary};
+use crate::pipeline::orchestrator::ExecutionResult;
+use crate::pipeline::proposal::{ProposalService, ProposalStatus, SchemaProposal};
+use crate::state::SharedState;
+use crate::storage::{ExecutionJournalBackend, MemoryExecutionJournalBackend};
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Where an execution stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionState {
+    Running,
+    Completed,
+    Failed,
+    /// The server stopped while the execution was running
+    Interrupted,
+    /// A later run picked up where an interrupted one stopped
+    Resumed,
+}
+
+/// How the live schema compares with the journal after an interruption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryVerdict {
+    /// The database has not been checked yet
+    Unverified,
+    /// The schema is as it was right after the last checkpoint
+    AtCheckpoint,
+    /// The schema is as it was before the run; its commits are gone
+    AtStart,
+    /// The schema matches neither; a statement may have partly applied
+    Diverged,
+}
+
+/// Outcome of checking an interrupted execution against the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    pub verdict: RecoveryVerdict,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_checksum: Option<String>,
+    /// Statement a resume should start at, when it is safe to resume
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_at: Option<usize>,
+    pub guidance: String,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Durable record of one execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionRecord {
+    pub execution_id: Uuid,
+    pub proposal_id: Uuid,
+    pub connection_id: Uuid,
+    /// Proposal as it was when the run started, restored after a restart
+    pub proposal: SchemaProposal,
+    pub state: ExecutionState,
+    pub total_statements: usize,
+    pub resumed_from: usize,
+    /// Statements committed, counting earlier runs
+    pub checkpoint: usize,
+    /// Schema checksum before the run's first statement
+    pub start_checksum: String,
+    /// Schema checksum right after the latest checkpoint
+    pub checkpoint_checksum: String,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryReport>,
+}
+
+impl ExecutionRecord {
+    /// Failed execution result standing in for the run that never reported back
+    pub fn interrupted_result(&self) -> ExecutionResult {
+        ExecutionResult {
+            id: self.execution_id,
+            proposal_id: self.proposal_id,
+            success: false,
+            dry_run: false,
+            executed_statements: Vec::new(),
+            error: Some(format!(
+                "Execution interrupted by a server restart after {} of {} statements",
+                self.checkpoint, self.total_statements
+            )),
+            forensics: None,
+            total_statements: self.total_statements,
+            resumed_from: self.resumed_from,
+            checkpoint: self.checkpoint,
+            chunks: Vec::new(),
+            paused_at_gate: None,
+            cost_summary: None,
+            backup: None,
+            lock: None,
+            session_settings: Default::default(),
+            permissions: None,
+            row_counts: None,
+            template_values: Default::default(),
+            watchdog: None,
+            deep_dry_run: None,
+            migration_version: self.proposal.migration.as_ref().map(|m| m.version).filter(|v| *v > 0),
+            migration_checksum: self.proposal.migration.as_ref().map(|m| m.checksum.clone()),
+            duration_ms: (self.updated_at - self.started_at).num_milliseconds().max(0) as u64,
+            executed_at: self.started_at,
+        }
+    }
+}
+
+/// Compare a live schema checksum with what the journal recorded
+pub fn assess(record: &ExecutionRecord, live_checksum: &str) -> RecoveryReport {
+    let (verdict, resume_at, guidance) = if live_checksum == record.checkpoint_checksum {
+        (
+            RecoveryVerdict::AtCheckpoint,
+            Some(record.checkpoint),
+            format!(
+                "The schema matches the state after statement {} of {}; resume the execution to run the rest.",
+                record.checkpoint, record.total_statements
+            ),
+        )
+    } else if live_checksum == record.start_checksum {
+        (
+            RecoveryVerdict::AtStart,
+            Some(record.resumed_from),
+            format!(
+                "The schema matches the state before the run, so nothing it committed is left; resume to start again at statement {}.",
+                record.resumed_from + 1
+            ),
+        )
+    } else {
+        (
+            RecoveryVerdict::Diverged,
+            None,
+            format!(
+                "The schema matches neither the last checkpoint nor the state before the run; statement {} may have partly applied outside a transaction. Inspect it, then roll back with the down migration or repair the schema before resuming.",
+                record.checkpoint + 1
+            ),
+        )
+    };
+
+    RecoveryReport {
+        verdict,
+        live_checksum: Some(live_checksum.to_string()),
+        resume_at,
+        guidance,
+        checked_at: Utc::now(),
+    }
+}
+
+/// Checksum of every user schema, independent of any connection's scope
+async fn schema_checksum(pool: &Pool) -> Result<String, AppError> {
+    // Strict, so a run that only reorders columns still moves the checksum
+    let snapshot = PostgresIntrospector::introspect(pool, Uuid::nil(), &[]).await?;
+    Ok(snapshot.with_column_order(ColumnOrder::Strict).checksum)
+}
+
+/// Journal of execution state transitions
+#[derive(Clone)]
+pub struct ExecutionJournal {
+    backend: Arc<dyn ExecutionJournalBackend>,
+}
+
+impl ExecutionJournal {
+    /// In-memory journal
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(MemoryExecutionJournalBackend::default()))
+    }
+
+    pub fn with_backend(backend: Arc<dyn ExecutionJournalBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Record the start of a run; without this record the run does not start
+    pub async fn begin(
+        &self,
+        pool: &Pool,
+        proposal: &SchemaProposal,
+        execution_id: Uuid,
+        start_at: usize,
+        total_statements: usize,
+    ) -> Result<(), AppError> {
+        let checksum = schema_checksum(pool).await?;
+        let now = Utc::now();
+
+        for mut earlier in self.backend.list_by_state(ExecutionState::Interrupted).await? {
+            if earlier.proposal_id == proposal.id {
+                earlier.state = ExecutionState::Resumed;
+                earlier.updated_at = now;
+                self.backend.put(&earlier).await?;
+            }
+        }
+
+        self.backend
+            .put(&ExecutionRecord {
+                execution_id,
+                proposal_id: proposal.id,
+                connection_id: proposal.connection_id,
+                proposal: proposal.clone(),
+                state: ExecutionState::Running,
+                total_statements,
+                resumed_from: start_at,
+                checkpoint: start_at,
+                start_checksum: checksum.clone(),
+                checkpoint_checksum: checksum,
+                started_at: now,
+                updated_at: now,
+                recovery: None,
+            })
+            .await
+    }
+
+    /// Record a committed chunk. The run goes on if this fails; recovery then
+    /// works from the previous checkpoint and reports the schema as diverged.
+    pub async fn checkpoint(&self, pool: &Pool, execution_id: Uuid, checkpoint: usize) {
+        let result = async {
+            let checksum = schema_checksum(pool).await?;
+            self.update(execution_id, |record| {
+                record.checkpoint = checkpoint;
+                record.checkpoint_checksum = checksum;
+            })
+            .await
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("Failed to journal checkpoint {} of execution {}: {}", checkpoint, execution_id, e);
+        }
+    }
+
+    /// Record the end of a run
+    pub async fn finish(&self, execution_id: Uuid, success: bool) {
+        let state = if success { ExecutionState::Completed } else { ExecutionState::Failed };
+        if let Err(e) = self.update(execution_id, |record| record.state = state).await {
+            warn!("Failed to journal the end of execution {}: {}", execution_id, e);
+        }
+    }
+
+    /// Records last updated before `before`, for retention
+    pub async fn updated_before(&self, before: DateTime<Utc>) -> Result<Vec<ExecutionRecord>, AppError> {
+        self.backend.list_updated_before(before).await
+    }
+
+    pub async fn delete(&self, execution_ids: &[Uuid]) -> Result<usize, AppError> {
+        self.backend.delete(execution_ids).await
+    }
+
+    pub async fn get(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, AppError> {
+        self.backend.get(execution_id).await
+    }
+
+    /// Interrupted executions nobody has resumed yet
+    pub async fn interrupted(&self) -> Result<Vec<ExecutionRecord>, AppError> {
+        let mut records = self.backend.list_by_state(ExecutionState::Interrupted).await?;
+        records.sort_by_key(|r| r.started_at);
+        Ok(records)
+    }
+
+    /// Mark runs left over from before a restart as interrupted and put their
+    /// proposals back as failed at the last checkpoint
+    pub async fn recover(&self, proposals: &ProposalService) -> Result<Vec<ExecutionRecord>, AppError> {
+        let mut recovered = Vec::new();
+        for mut record in self.backend.list_by_state(ExecutionState::Running).await? {
+            let now = Utc::now();
+            record.state = ExecutionState::Interrupted;
+            record.updated_at = now;
+            record.recovery = Some(RecoveryReport {
+                verdict: RecoveryVerdict::Unverified,
+                live_checksum: None,
+                resume_at: None,
+                guidance: "Reconnect the database and verify this execution before resuming or rolling back.".to_string(),
+                checked_at: now,
+            });
+            self.backend.put(&record).await?;
+
+            // An in-memory service lost the proposal with the restart
+            if proposals.get(record.proposal_id).await?.is_none() {
+                proposals.create(record.proposal.clone()).await?;
+            }
+            let interrupted = record.interrupted_result();
+            proposals
+                .update(record.proposal_id, |proposal| {
+                    proposal.set_status(ProposalStatus::Failed, now);
+                    proposal.last_execution = Some(interrupted);
+                    proposal.updated_at = now;
+                })
+                .await?;
+
+            recovered.push(record);
+        }
+        Ok(recovered)
+    }
+
+    /// Check an interrupted execution against the database behind `pool`,
+    /// which `connection_id` now refers to. When resuming is safe, the
+    /// proposal's checkpoint is moved to where the resume should start.
+    pub async fn verify(
+        &self,
+        pool: &Pool,
+        execution_id: Uuid,
+        connection_id: Uuid,
+        proposals: &ProposalService,
+    ) -> Result<ExecutionRecord, AppError> {
+        let record = self
+            .get(execution_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Execution {} not found", execution_id)))?;
+        if record.state != ExecutionState::Interrupted {
+            return Err(AppError::BadRequest(format!("Execution {} was not interrupted", execution_id)));
+        }
+
+        let report = assess(&record, &schema_checksum(pool).await?);
+        let resume_at = report.resume_at;
+        let record = self
+            .update(execution_id, |record| {
+                record.connection_id = connection_id;
+                record.recovery = Some(report);
+            })
+            .await?;
+
+        if proposals.get(record.proposal_id).await?.is_none() {
+            proposals.create(record.proposal.clone()).await?;
+        }
+        let interrupted = record.interrupted_result();
+        proposals
+            .update(record.proposal_id, |proposal| {
+                proposal.connection_id = connection_id;
+                let mut result = proposal.last_execution.take().unwrap_or(interrupted);
+                if let Some(resume_at) = resume_at {
+                    result.checkpoint = resume_at;
+                }
+                proposal.last_execution = Some(result);
+                proposal.updated_at = Utc::now();
+            })
+            .await?;
+
+        Ok(record)
+    }
+
+    async fn update(
+        &self,
+        execution_id: Uuid,
+        change: impl FnOnce(&mut ExecutionRecord),
+    ) -> Result<ExecutionRecord, AppError> {
+        let mut record = self
+            .get(execution_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Execution {} not found", execution_id)))?;
+        change(&mut record);
+        record.updated_at = Utc::now();
+        self.backend.put(&record).await?;
+        Ok(record)
+    }
+}
+
+impl Default for ExecutionJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Startup recovery: mark executions the previous process died in as
+/// interrupted and record each one in the audit log
+pub async fn recover_interrupted(state: &SharedState) {
+    let recovered = match state.journal.recover(&state.pipeline_proposals).await {
+        Ok(recovered) => recovered,
+        Err(e) => {
+            warn!("Could not check the execution journal for interrupted executions: {}", e);
+            return;
+        }
+    };
+
+    for record in &recovered {
+        if let Ok(Some(proposal)) = state.pipeline_proposals.get(record.proposal_id).await {
+            state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+        }
+        let entry = AuditEntry::new(AuditAction::ExecutionInterrupted, "system", "proposal", &record.proposal_id.to_string())
+            .with_project(record.proposal.project_id)
+            .with_details(&format!(
+                "Execution {} stopped after {} of {} statements",
+                record.execution_id, record.checkpoint, record.total_statements
+            ));
+        state.metadata.record_audit_entry(entry).await;
+        warn!(
+            "Execution {} of proposal {} was interrupted after {} of {} statements",
+            record.execution_id, record.proposal_id, record.checkpoint, record.total_statements
+        );
+    }
+
+    if !recovered.is_empty() {
+        info!("Recovered {} interrupted execution(s); verify them before resuming", recovered.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(checkpoint: usize) -> ExecutionRecord {
+        let proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        let now = Utc::now();
+        ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            proposal_id: proposal.id,
+            connection_id: proposal.connection_id,
+            proposal,
+            state: ExecutionState::Running,
+            total_statements: 5,
+            resumed_from: 1,
+            checkpoint,
+            start_checksum: "before".to_string(),
+            checkpoint_checksum: "after-checkpoint".to_string(),
+            started_at: now,
+            updated_at: now,
+            recovery: None,
+        }
+    }
+
+    #[test]
+    fn test_assess_against_checksums() {
+        let record = record(3);
+
+        let at_checkpoint = assess(&record, "after-checkpoint");
+        assert_eq!(at_checkpoint.verdict, RecoveryVerdict::AtCheckpoint);
+        assert_eq!(at_checkpoint.resume_at, Some(3));
+
+        let at_start = assess(&record, "before");
+        assert_eq!(at_start.verdict, RecoveryVerdict::AtStart);
+        assert_eq!(at_start.resume_at, Some(1));
+
+        let diverged = assess(&record, "something-else");
+        assert_eq!(diverged.verdict, RecoveryVerdict::Diverged);
+        assert_eq!(diverged.resume_at, None);
+        assert!(diverged.guidance.contains("statement 4"));
+    }
+
+    #[tokio::test]
+    async fn test_recover_restores_proposal_as_failed() {
+        let journal = ExecutionJournal::new();
+        let proposals = ProposalService::new();
+        let running = record(3);
+        journal.backend.put(&running).await.unwrap();
+        journal.backend.put(&ExecutionRecord { state: ExecutionState::Completed, ..record(5) }).await.unwrap();
+
+        let recovered = journal.recover(&proposals).await.unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(journal.interrupted().await.unwrap().len(), 1);
+
+        let proposal = proposals.get(running.proposal_id).await.unwrap().unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Failed);
+        let last = proposal.last_execution.unwrap();
+        assert!(!last.success);
+        assert_eq!(last.checkpoint, 3);
+
+        // Nothing is left running, so a second recovery finds nothing
+        assert!(journal.recover(&proposals).await.unwrap().is_empty());
+    }
+}
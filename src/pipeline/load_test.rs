@@ -0,0 +1,365 @@
+//! Simulated read load during a dry run
+//!
+//! A rough "will this hurt production reads?" signal for risky migrations.
+//! The affected tables are cloned, with a sample of their rows, into a
+//! scratch schema that is committed so other connections can read it.
+//! Workers replay user-provided SELECTs against the clones, first on their
+//! own to measure a baseline and then while the migration runs there in a
+//! transaction that is rolled back. Reads blocked by the migration's locks
+//! or slowed by its rewrites show up as higher latencies. The scratch schema
+//! is dropped afterwards.
+//!
+//! Clones hold at most a sample of rows, so latencies understate what the
+//! real tables would see. Workers run read-only transactions with the
+//! scratch schema first on the `search_path`; schema-qualified names in the
+//! workload read the real tables.
+
+use crate::error::AppError;
+use crate::pipeline::scratch::{clone_tables, db_message, run_statements, ScratchRunResult, LOCK_TIMEOUT_MS};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Barrier;
+use uuid::Uuid;
+
+pub const MAX_QUERIES: usize = 20;
+pub const MAX_CONCURRENCY: usize = 4;
+pub const MAX_SAMPLE_ROWS: i64 = 10_000;
+pub const MAX_BASELINE_ROUNDS: usize = 20;
+
+/// Longest a workload query may take before it counts as an error
+const QUERY_TIMEOUT_MS: u32 = 10_000;
+
+/// Read workload to replay during a dry run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTest {
+    /// SELECT statements, replayed in order by every worker
+    pub queries: Vec<String>,
+    /// Workers replaying the queries at the same time
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Rows copied into each clone
+    #[serde(default = "default_sample_rows")]
+    pub sample_rows: i64,
+    /// Passes over the queries, per worker, before the migration starts
+    #[serde(default = "default_baseline_rounds")]
+    pub baseline_rounds: usize,
+}
+
+fn default_concurrency() -> usize {
+    2
+}
+
+fn default_sample_rows() -> i64 {
+    1000
+}
+
+fn default_baseline_rounds() -> usize {
+    3
+}
+
+/// Whether `query` is a single SELECT (or WITH ... SELECT) statement
+fn is_select(query: &str) -> bool {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    let first = query.split_whitespace().next().unwrap_or_default().to_lowercase();
+    matches!(first.as_str(), "select" | "with") && !query.contains(';')
+}
+
+impl LoadTest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.queries.is_empty() || self.queries.len() > MAX_QUERIES {
+            return Err(AppError::Validation(format!(
+                "A load test needs between 1 and {} queries",
+                MAX_QUERIES
+            )));
+        }
+        if let Some(index) = self.queries.iter().position(|q| !is_select(q)) {
+            return Err(AppError::Validation(format!(
+                "Load test query {} must be a single SELECT statement",
+                index + 1
+            )));
+        }
+        if self.concurrency == 0 || self.concurrency > MAX_CONCURRENCY {
+            return Err(AppError::Validation(format!(
+                "Load test concurrency must be between 1 and {}",
+                MAX_CONCURRENCY
+            )));
+        }
+        if !(0..=MAX_SAMPLE_ROWS).contains(&self.sample_rows) {
+            return Err(AppError::Validation(format!(
+                "Load test sampleRows must be between 0 and {}",
+                MAX_SAMPLE_ROWS
+            )));
+        }
+        if self.baseline_rounds == 0 || self.baseline_rounds > MAX_BASELINE_ROUNDS {
+            return Err(AppError::Validation(format!(
+                "Load test baselineRounds must be between 1 and {}",
+                MAX_BASELINE_ROUNDS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Latencies of the workload queries in one phase
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub queries: usize,
+    /// Queries that failed or timed out; not part of the latencies
+    pub errors: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<f64>, errors: usize) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            if samples.is_empty() {
+                return 0.0;
+            }
+            let rank = ((p * samples.len() as f64).ceil() as usize).clamp(1, samples.len());
+            samples[rank - 1]
+        };
+        Self {
+            queries: samples.len(),
+            errors,
+            p50_ms: percentile(0.50),
+            p95_ms: percentile(0.95),
+            max_ms: samples.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Outcome of a dry run under a simulated read workload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestResult {
+    /// The migration run against the clones
+    pub migration: ScratchRunResult,
+    pub baseline: LatencyStats,
+    pub during_migration: LatencyStats,
+    /// p95 during the migration over the baseline p95
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p95_slowdown: Option<f64>,
+    /// First error a workload query returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_query_error: Option<String>,
+}
+
+/// Latencies and errors one worker saw
+#[derive(Default)]
+struct WorkerSamples {
+    latencies: Vec<f64>,
+    errors: usize,
+    first_error: Option<String>,
+}
+
+/// Replay `queries` for `rounds` passes, or until `stop` is set when `rounds` is None
+async fn replay(
+    pool: Pool,
+    search_path: String,
+    queries: Arc<Vec<String>>,
+    rounds: Option<usize>,
+    start: Arc<Barrier>,
+    stop: Arc<AtomicBool>,
+) -> Result<WorkerSamples, AppError> {
+    let mut client = pool.get().await?;
+    let mut samples = WorkerSamples::default();
+    start.wait().await;
+
+    let mut round = 0;
+    while rounds.map_or(round == 0 || !stop.load(Ordering::Relaxed), |rounds| round < rounds) {
+        for query in queries.iter() {
+            // Settings are local to the transaction, so the pooled connection comes back unchanged
+            let tx = client.build_transaction().read_only(true).start().await?;
+            tx.batch_execute(&format!(
+                "SET LOCAL statement_timeout = {}; SET LOCAL search_path TO {}",
+                QUERY_TIMEOUT_MS, search_path
+            ))
+            .await?;
+            let started = Instant::now();
+            match tx.simple_query(query).await {
+                Ok(_) => samples.latencies.push(started.elapsed().as_secs_f64() * 1000.0),
+                Err(e) => {
+                    samples.errors += 1;
+                    samples.first_error.get_or_insert_with(|| db_message(&e));
+                }
+            }
+            let _ = tx.rollback().await;
+        }
+        round += 1;
+    }
+    Ok(samples)
+}
+
+/// Spawn `load.concurrency` workers replaying the workload
+fn run_workers(
+    pool: &Pool,
+    search_path: &str,
+    load: &LoadTest,
+    rounds: Option<usize>,
+    stop: Arc<AtomicBool>,
+    start: Arc<Barrier>,
+) -> Vec<tokio::task::JoinHandle<Result<WorkerSamples, AppError>>> {
+    let queries = Arc::new(load.queries.clone());
+    (0..load.concurrency)
+        .map(|_| {
+            tokio::spawn(replay(
+                pool.clone(),
+                search_path.to_string(),
+                queries.clone(),
+                rounds,
+                start.clone(),
+                stop.clone(),
+            ))
+        })
+        .collect()
+}
+
+/// Wait for the workers and merge what they saw
+async fn collect(
+    handles: Vec<tokio::task::JoinHandle<Result<WorkerSamples, AppError>>>,
+    first_error: &mut Option<String>,
+) -> Result<LatencyStats, AppError> {
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    for handle in handles {
+        let samples = handle
+            .await
+            .map_err(|e| AppError::Internal(format!("Load test worker failed: {}", e)))??;
+        latencies.extend(samples.latencies);
+        errors += samples.errors;
+        if first_error.is_none() {
+            *first_error = samples.first_error;
+        }
+    }
+    Ok(LatencyStats::from_samples(latencies, errors))
+}
+
+/// Clone `tables` with sample rows, then measure `load` before and while `statements` run there
+pub async fn run(pool: &Pool, statements: &[String], tables: &[String], load: &LoadTest) -> Result<LoadTestResult, AppError> {
+    // Workers wait for each other, so they must all get a connection next to the setup one
+    if pool.status().max_size <= load.concurrency {
+        return Err(AppError::BadRequest(format!(
+            "The connection pool holds {} connections; a load test with concurrency {} needs {}",
+            pool.status().max_size,
+            load.concurrency,
+            load.concurrency + 1
+        )));
+    }
+
+    let started = Instant::now();
+    let schema = format!("schemaflow_scratch_{}", Uuid::new_v4().simple());
+    let mut migration = ScratchRunResult::new(&schema);
+
+    // Committed, so the workers' connections can see the clones
+    let mut client = pool.get().await?;
+    let search_path: String = client.query_one("SHOW search_path", &[]).await?.get(0);
+    let search_path = format!("{}, {}", schema, search_path);
+    let setup = async {
+        let tx = client.transaction().await?;
+        tx.batch_execute(&format!("SET LOCAL lock_timeout = {}", LOCK_TIMEOUT_MS)).await?;
+        tx.batch_execute(&format!("CREATE SCHEMA {}", schema)).await?;
+        clone_tables(&tx, &schema, tables, Some(load.sample_rows), &mut migration).await?;
+        tx.commit().await?;
+        Ok::<_, AppError>(())
+    }
+    .await;
+
+    let measured = match setup {
+        Ok(()) => measure(pool, &mut client, statements, load, &search_path, &mut migration).await,
+        Err(e) => Err(e),
+    };
+
+    // Drop the clones whatever happened
+    if let Err(e) = client.batch_execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE", schema)).await {
+        migration.warnings.push(format!("Scratch schema {} could not be dropped: {}", schema, e));
+    }
+    let (baseline, during_migration, first_query_error) = measured?;
+
+    migration.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(LoadTestResult {
+        p95_slowdown: (baseline.p95_ms > 0.0).then(|| during_migration.p95_ms / baseline.p95_ms),
+        migration,
+        baseline,
+        during_migration,
+        first_query_error,
+    })
+}
+
+async fn measure(
+    pool: &Pool,
+    client: &mut deadpool_postgres::Object,
+    statements: &[String],
+    load: &LoadTest,
+    search_path: &str,
+    migration: &mut ScratchRunResult,
+) -> Result<(LatencyStats, LatencyStats, Option<String>), AppError> {
+    let mut first_error = None;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let start = Arc::new(Barrier::new(load.concurrency));
+    let handles = run_workers(pool, search_path, load, Some(load.baseline_rounds), stop.clone(), start);
+    let baseline = collect(handles, &mut first_error).await?;
+
+    // The migration starts once every worker holds a connection
+    let start = Arc::new(Barrier::new(load.concurrency + 1));
+    let handles = run_workers(pool, search_path, load, None, stop.clone(), start.clone());
+    start.wait().await;
+    let tx = client.transaction().await?;
+    tx.batch_execute(&format!(
+        "SET LOCAL lock_timeout = {}; SET LOCAL search_path TO {}",
+        LOCK_TIMEOUT_MS, search_path
+    ))
+    .await?;
+    run_statements(&tx, statements, migration).await;
+    // Never keep the migration, even on the clones
+    let _ = tx.rollback().await;
+    stop.store(true, Ordering::Relaxed);
+    let during_migration = collect(handles, &mut first_error).await?;
+
+    Ok((baseline, during_migration, first_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_accepts_selects_only() {
+        let mut load = LoadTest {
+            queries: vec!["SELECT * FROM orders WHERE id = 1;".to_string(), "with t as (select 1) select * from t".to_string()],
+            concurrency: default_concurrency(),
+            sample_rows: default_sample_rows(),
+            baseline_rounds: default_baseline_rounds(),
+        };
+        assert!(load.validate().is_ok());
+
+        load.queries.push("SELECT 1; DELETE FROM orders".to_string());
+        assert!(load.validate().is_err());
+        load.queries.pop();
+        load.queries.push("UPDATE orders SET total = 0".to_string());
+        assert!(load.validate().is_err());
+        load.queries.pop();
+        load.concurrency = MAX_CONCURRENCY + 1;
+        assert!(load.validate().is_err());
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let samples: Vec<f64> = (1..=20).map(f64::from).collect();
+        let stats = LatencyStats::from_samples(samples, 2);
+        assert_eq!(stats.queries, 20);
+        assert_eq!(stats.errors, 2);
+        assert_eq!(stats.p50_ms, 10.0);
+        assert_eq!(stats.p95_ms, 19.0);
+        assert_eq!(stats.max_ms, 20.0);
+        assert_eq!(LatencyStats::from_samples(vec![], 0).p95_ms, 0.0);
+    }
+}
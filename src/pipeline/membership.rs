@@ -0,0 +1,120 @@
+//! Project membership checks
+//!
+//! Proposals, snapshots, semantic maps and audit entries belong to the project
+//! of the connection they were made against. Only the project's owner and its
+//! members may read or act on them; admins see every project. Work on
+//! connections opened without a project stays visible to every signed-in user.
+
+use crate::auth::Claims;
+use crate::error::AppError;
+use crate::state::AppState;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Projects a user may see; `None` means all of them
+pub type Visibility = Option<HashSet<i32>>;
+
+/// Whether work in `project_id` is visible under `visibility`
+pub fn can_see(visibility: &Visibility, project_id: Option<i32>) -> bool {
+    match (visibility, project_id) {
+        (None, _) | (_, None) => true,
+        (Some(projects), Some(id)) => projects.contains(&id),
+    }
+}
+
+/// Project a proposal belongs to: its connection's project. Naming a
+/// different project than the connection's is refused.
+pub fn resolve_project(requested: Option<i32>, connection_project: Option<i32>) -> Result<Option<i32>, AppError> {
+    match (requested, connection_project) {
+        (Some(requested), Some(actual)) if requested != actual => Err(AppError::BadRequest(format!(
+            "The connection belongs to project {}, not project {}",
+            actual, requested
+        ))),
+        (Some(requested), None) => Err(AppError::BadRequest(format!(
+            "The connection is not part of project {}; save it to the project first",
+            requested
+        ))),
+        (_, actual) => Ok(actual),
+    }
+}
+
+/// Projects the caller may see
+pub async fn visible_projects(state: &AppState, claims: &Claims) -> Result<Visibility, AppError> {
+    if claims.role.can_approve() {
+        return Ok(None);
+    }
+    let user_id = user_id(claims)?;
+    let projects = state.project_service.project_ids_for_member(user_id).await?;
+    Ok(Some(projects.into_iter().collect()))
+}
+
+/// Refuse callers outside `project_id`
+pub async fn require_project(state: &AppState, claims: &Claims, project_id: Option<i32>) -> Result<(), AppError> {
+    let Some(project_id) = project_id else {
+        return Ok(());
+    };
+    if claims.role.can_approve() {
+        return Ok(());
+    }
+    let user_id = user_id(claims)?;
+    if state.project_service.member_ids(project_id).await?.contains(&user_id) {
+        return Ok(());
+    }
+    Err(AppError::Forbidden(format!("You are not a member of project {}", project_id)))
+}
+
+/// Refuse callers outside the connection's project; returns that project
+pub async fn require_connection(state: &AppState, claims: &Claims, connection_id: Uuid) -> Result<Option<i32>, AppError> {
+    let project_id = state.connections.project_id(connection_id).await;
+    require_project(state, claims, project_id).await?;
+    Ok(project_id)
+}
+
+/// Refuse callers outside the proposal's project; returns that project.
+/// Proposals created before they were bound fall back to their connection's.
+pub async fn require_proposal(state: &AppState, claims: &Claims, id: Uuid) -> Result<Option<i32>, AppError> {
+    let (project_id, connection_id) = match state.pipeline_proposals.get(id).await? {
+        Some(proposal) => (proposal.project_id, proposal.connection_id),
+        None => {
+            let summary = state
+                .metadata
+                .get_proposal(id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+            (summary.project_id, summary.connection_id)
+        }
+    };
+    let project_id = match project_id {
+        Some(id) => Some(id),
+        None => state.connections.project_id(connection_id).await,
+    };
+    require_project(state, claims, project_id).await?;
+    Ok(project_id)
+}
+
+fn user_id(claims: &Claims) -> Result<i32, AppError> {
+    claims
+        .sub
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposals_follow_their_connection_project() {
+        assert_eq!(resolve_project(None, Some(4)).unwrap(), Some(4));
+        assert_eq!(resolve_project(Some(4), Some(4)).unwrap(), Some(4));
+        assert_eq!(resolve_project(None, None).unwrap(), None);
+        assert!(matches!(resolve_project(Some(5), Some(4)), Err(AppError::BadRequest(_))));
+        assert!(matches!(resolve_project(Some(5), None), Err(AppError::BadRequest(_))));
+
+        let member_of_four = Some(HashSet::from([4]));
+        assert!(can_see(&member_of_four, Some(4)));
+        assert!(!can_see(&member_of_four, Some(5)));
+        assert!(can_see(&member_of_four, None));
+        assert!(can_see(&None, Some(5)));
+    }
+}
@@ -1,51 +1,105 @@
 //! Metadata storage for the governance pipeline
 //!
 //! Stores proposals, audit logs, and schema snapshots.
+//!
+//! Audit entries are hash-chained as they are appended: each stores the hash
+//! of the entry before it and a hash over its own content and that link, so
+//! editing, removing or reordering an entry is detected by `verify_chain`.
+//! Entries recorded before chaining was introduced carry no hashes and are
+//! only accepted ahead of the first chained entry.
+//!
+//! The hash covers the fields retention never touches plus a digest of the
+//! actor, target id and details. Pruning erases that payload but keeps the
+//! digest, so a pruned entry's action, time and place in the chain are still
+//! verified; only its erased content can no longer be checked. Entries chained
+//! before the digest was introduced hash their full content and are never
+//! pruned.
 
-use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use crate::pipeline::proposal::{ProposalStatus, SchemaProposal};
+use crate::pipeline::references::ObjectReference;
+use crate::pipeline::retention::LegalHold;
+use crate::pipeline::summary::ExecutiveSummary;
+use crate::storage::{MemoryMetadataBackend, MetadataBackend};
+use chrono::{DateTime, SubsecRound, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use uuid::Uuid;
 
 /// Metadata store for governance data
 pub struct MetadataStore {
-    proposals: Arc<RwLock<HashMap<Uuid, ProposalSummary>>>,
-    audit_log: Arc<RwLock<Vec<AuditEntry>>>,
+    backend: Arc<dyn MetadataBackend>,
 }
 
 impl MetadataStore {
+    /// In-memory store
     pub fn new() -> Self {
-        Self {
-            proposals: Arc::new(RwLock::new(HashMap::new())),
-            audit_log: Arc::new(RwLock::new(Vec::new())),
-        }
+        Self::with_backend(Arc::new(MemoryMetadataBackend::default()))
+    }
+
+    pub fn with_backend(backend: Arc<dyn MetadataBackend>) -> Self {
+        Self { backend }
     }
 
+    /// Record a proposal summary. The summary mirrors a proposal that is
+    /// already saved, so a storage failure is logged rather than returned.
     pub async fn add_proposal(&self, proposal: ProposalSummary) {
-        let mut proposals = self.proposals.write().await;
-        proposals.insert(proposal.id, proposal);
+        let id = proposal.id;
+        if let Err(e) = self.backend.put_proposal(proposal).await {
+            tracing::error!("Failed to store summary of proposal {}: {}", id, e);
+        }
+    }
+
+    pub async fn get_proposal(&self, id: Uuid) -> Result<Option<ProposalSummary>, AppError> {
+        self.backend.get_proposal(id).await
+    }
+
+    pub async fn list_proposals(&self) -> Result<Vec<ProposalSummary>, AppError> {
+        self.backend.list_proposals().await
+    }
+
+    /// Append to the audit log. A failure is returned so governance actions
+    /// are never reported done without their audit record.
+    pub async fn add_audit_entry(&self, entry: AuditEntry) -> Result<(), AppError> {
+        let stored = self.backend.append_audit_entry(entry.clone()).await;
+        if let Err(e) = &stored {
+            tracing::error!("Failed to store audit entry {:?} on {} {}: {}", entry.action, entry.target_type, entry.target_id, e);
+        }
+        stored
+    }
+
+    /// Append to the audit log on behalf of the system, where there is no
+    /// caller to report a failure to; it is logged instead
+    pub async fn record_audit_entry(&self, entry: AuditEntry) {
+        let _ = self.add_audit_entry(entry).await;
+    }
+
+    pub async fn get_audit_log(&self) -> Result<Vec<AuditEntry>, AppError> {
+        self.backend.list_audit_entries().await
+    }
+
+    /// Re-validate the audit log's hash chain
+    pub async fn verify_audit_chain(&self) -> Result<ChainVerification, AppError> {
+        Ok(verify_chain(&self.backend.list_audit_entries().await?))
     }
 
-    pub async fn get_proposal(&self, id: Uuid) -> Option<ProposalSummary> {
-        let proposals = self.proposals.read().await;
-        proposals.get(&id).cloned()
+    /// Audit entries recorded for one actor since `since`, oldest first
+    pub async fn get_user_audit_log(&self, actor: &str, since: DateTime<Utc>) -> Result<Vec<AuditEntry>, AppError> {
+        self.backend.list_audit_entries_by_actor(actor, since).await
     }
 
-    pub async fn list_proposals(&self) -> Vec<ProposalSummary> {
-        let proposals = self.proposals.read().await;
-        proposals.values().cloned().collect()
+    /// Prune entries under the retention policy; returns how many were pruned
+    pub async fn prune_audit_entries(&self, ids: &[Uuid], at: DateTime<Utc>) -> Result<usize, AppError> {
+        self.backend.prune_audit_entries(ids, at).await
     }
 
-    pub async fn add_audit_entry(&self, entry: AuditEntry) {
-        let mut log = self.audit_log.write().await;
-        log.push(entry);
+    pub async fn put_legal_hold(&self, hold: &LegalHold) -> Result<(), AppError> {
+        self.backend.put_legal_hold(hold).await
     }
 
-    pub async fn get_audit_log(&self) -> Vec<AuditEntry> {
-        let log = self.audit_log.read().await;
-        log.clone()
+    pub async fn list_legal_holds(&self) -> Result<Vec<LegalHold>, AppError> {
+        self.backend.list_legal_holds().await
     }
 }
 
@@ -61,6 +115,8 @@ impl Default for MetadataStore {
 pub struct ProposalSummary {
     pub id: Uuid,
     pub connection_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i32>,
     pub title: String,
     pub description: String,
     pub status: String,
@@ -68,6 +124,100 @@ pub struct ProposalSummary {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub change_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Review deadline under the project's SLA
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_due_at: Option<DateTime<Utc>>,
+    /// Seconds left until the review deadline (negative once overdue); only
+    /// set on listings, for proposals waiting for review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sla_remaining_secs: Option<i64>,
+    /// End of the window for rolling back without a revert proposal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_window_ends_at: Option<DateTime<Utc>>,
+    /// Seconds left in the rollback window; only set on the proposal detail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_remaining_secs: Option<i64>,
+    /// Schema objects the description links to; only set on the proposal detail
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ObjectReference>,
+    /// Plain-English summary for non-DBAs; only set on the proposal detail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executive_summary: Option<ExecutiveSummary>,
+}
+
+impl ProposalSummary {
+    /// Every field a summary can serialize with, for `?fields=`
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "connectionId",
+        "projectId",
+        "title",
+        "description",
+        "status",
+        "createdBy",
+        "createdAt",
+        "updatedAt",
+        "changeCount",
+        "approvalExpiresAt",
+        "closedAt",
+        "reviewDueAt",
+        "slaRemainingSecs",
+        "rollbackWindowEndsAt",
+        "rollbackRemainingSecs",
+        "references",
+        "executiveSummary",
+    ];
+
+    /// Fill in the SLA countdown as of `now`
+    pub fn with_sla_countdown(mut self, now: DateTime<Utc>) -> Self {
+        self.sla_remaining_secs = self
+            .review_due_at
+            .filter(|_| self.status == ProposalStatus::PendingReview.as_str())
+            .map(|due| (due - now).num_seconds());
+        self
+    }
+
+    /// Fill in the time left to roll back directly as of `now`
+    pub fn with_rollback_countdown(mut self, now: DateTime<Utc>) -> Self {
+        self.rollback_remaining_secs = self
+            .rollback_window_ends_at
+            .filter(|ends| *ends > now)
+            .filter(|_| {
+                self.status == ProposalStatus::Executed.as_str()
+                    || self.status == ProposalStatus::VerificationFailed.as_str()
+            })
+            .map(|ends| (ends - now).num_seconds());
+        self
+    }
+}
+
+impl From<&SchemaProposal> for ProposalSummary {
+    fn from(proposal: &SchemaProposal) -> Self {
+        Self {
+            id: proposal.id,
+            connection_id: proposal.connection_id,
+            project_id: proposal.project_id,
+            title: proposal.title.clone(),
+            description: proposal.description.clone(),
+            status: proposal.status.as_str().to_string(),
+            created_by: proposal.created_by.clone(),
+            created_at: proposal.created_at,
+            updated_at: proposal.updated_at,
+            change_count: proposal.changes.len(),
+            approval_expires_at: proposal.approval_expires_at,
+            closed_at: proposal.closed_at,
+            review_due_at: proposal.review_due_at,
+            sla_remaining_secs: None,
+            rollback_window_ends_at: proposal.rollback_window_ends_at,
+            rollback_remaining_secs: None,
+            references: Vec::new(),
+            executive_summary: None,
+        }
+    }
 }
 
 /// Audit log entry
@@ -81,6 +231,21 @@ pub struct AuditEntry {
     pub target_id: String,
     pub details: Option<String>,
     pub timestamp: DateTime<Utc>,
+    /// Project the audited object belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i32>,
+    /// Hash of the entry appended before this one; set by the store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_hash: Option<String>,
+    /// Hash of this entry's content and `previous_hash`; set by the store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Digest of the actor, target id and details, kept when they are pruned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_digest: Option<String>,
+    /// When retention pruned the entry's actor, target id and details
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruned_at: Option<DateTime<Utc>>,
 }
 
 impl AuditEntry {
@@ -93,6 +258,11 @@ impl AuditEntry {
             target_id: target_id.to_string(),
             details: None,
             timestamp: Utc::now(),
+            project_id: None,
+            previous_hash: None,
+            hash: None,
+            payload_digest: None,
+            pruned_at: None,
         }
     }
 
@@ -100,6 +270,163 @@ impl AuditEntry {
         self.details = Some(details.to_string());
         self
     }
+
+    pub fn with_project(mut self, project_id: Option<i32>) -> Self {
+        self.project_id = project_id;
+        self
+    }
+
+    /// Link the entry after the entry hashed `previous`. The timestamp is
+    /// cut to microseconds first, the precision PostgreSQL keeps, so the
+    /// hash still matches once the entry has been stored and read back.
+    pub fn chained(mut self, previous: &str) -> Self {
+        self.timestamp = self.timestamp.trunc_subsecs(6);
+        self.previous_hash = Some(previous.to_string());
+        let digest = self.compute_payload_digest();
+        self.hash = Some(self.compute_hash(previous, &digest));
+        self.payload_digest = Some(digest);
+        self
+    }
+
+    /// Erase what identifies who did what, keeping the action, time, hashes
+    /// and payload digest
+    pub fn prune(&mut self, at: DateTime<Utc>) {
+        self.actor = String::new();
+        self.target_id = String::new();
+        self.details = None;
+        self.pruned_at = Some(at);
+    }
+
+    fn compute_payload_digest(&self) -> String {
+        let payload = serde_json::json!([self.actor, self.target_id, self.details]);
+        format!("{:x}", Sha256::digest(payload.to_string().as_bytes()))
+    }
+
+    fn compute_hash(&self, previous: &str, payload_digest: &str) -> String {
+        let content = serde_json::json!([
+            previous,
+            self.id,
+            self.action,
+            self.target_type,
+            self.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.project_id,
+            payload_digest,
+        ]);
+        format!("{:x}", Sha256::digest(content.to_string().as_bytes()))
+    }
+
+    /// Hash of entries chained before the payload digest was introduced
+    fn compute_legacy_hash(&self, previous: &str) -> String {
+        let content = serde_json::json!([
+            previous,
+            self.id,
+            self.action,
+            self.actor,
+            self.target_type,
+            self.target_id,
+            self.details,
+            self.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.project_id,
+        ]);
+        format!("{:x}", Sha256::digest(content.to_string().as_bytes()))
+    }
+}
+
+/// Hash the first chained entry links to
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hash of the last chained entry, which the next entry links to
+pub fn chain_head(entries: &[AuditEntry]) -> &str {
+    entries.iter().rev().find_map(|e| e.hash.as_deref()).unwrap_or(GENESIS_HASH)
+}
+
+/// First entry that fails verification
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TamperedEntry {
+    /// Position in the log, oldest first
+    pub position: usize,
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub reason: String,
+}
+
+/// Result of re-validating the audit log
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub total_entries: usize,
+    /// Chained entries that verified before the first failure
+    pub verified_entries: usize,
+    /// Entries from before chaining was introduced
+    pub unchained_entries: usize,
+    /// Entries pruned under the retention policy; everything but their erased
+    /// payload is checked
+    pub pruned_entries: usize,
+    pub chain_head: String,
+    pub first_tampered: Option<TamperedEntry>,
+}
+
+/// Walk the log in append order and report the first entry that breaks the chain
+pub fn verify_chain(entries: &[AuditEntry]) -> ChainVerification {
+    let mut report = ChainVerification {
+        valid: true,
+        total_entries: entries.len(),
+        verified_entries: 0,
+        unchained_entries: 0,
+        pruned_entries: 0,
+        chain_head: GENESIS_HASH.to_string(),
+        first_tampered: None,
+    };
+
+    let mut previous: Option<&str> = None;
+    for (position, entry) in entries.iter().enumerate() {
+        let reason = match (&entry.hash, &entry.previous_hash, previous) {
+            (None, None, None) => {
+                report.unchained_entries += 1;
+                continue;
+            }
+            (None, _, _) | (_, None, _) => Some("hash missing; the entry was inserted or edited outside SchemaFlow"),
+            (Some(_), Some(link), expected) if link != expected.unwrap_or(GENESIS_HASH) => {
+                Some("does not link to the entry before it; an entry was removed, inserted or reordered")
+            }
+            (Some(hash), Some(link), _) => match (&entry.payload_digest, entry.pruned_at) {
+                (None, Some(_)) => Some("pruned without a payload digest; the entry was edited"),
+                (None, None) if *hash != entry.compute_legacy_hash(link) => {
+                    Some("content does not match its hash; the entry was edited")
+                }
+                (Some(digest), _) if *hash != entry.compute_hash(link, digest) => {
+                    Some("content does not match its hash; the entry was edited")
+                }
+                (Some(digest), None) if *digest != entry.compute_payload_digest() => {
+                    Some("content does not match its hash; the entry was edited")
+                }
+                (_, pruned_at) => {
+                    previous = Some(hash);
+                    if pruned_at.is_some() {
+                        report.pruned_entries += 1;
+                    } else {
+                        report.verified_entries += 1;
+                    }
+                    None
+                }
+            },
+        };
+        if let Some(reason) = reason {
+            report.valid = false;
+            report.first_tampered = Some(TamperedEntry {
+                position,
+                id: entry.id,
+                timestamp: entry.timestamp,
+                reason: reason.to_string(),
+            });
+            break;
+        }
+    }
+
+    report.chain_head = previous.unwrap_or(GENESIS_HASH).to_string();
+    report
 }
 
 /// Audit action types
@@ -110,10 +437,118 @@ pub enum AuditAction {
     ProposalUpdated,
     ProposalSubmitted,
     ProposalApproved,
+    TeamApprovalRecorded,
+    StatementApproved,
+    ChecklistItemChecked,
+    ChecklistItemUnchecked,
     ProposalRejected,
     ProposalExecuted,
+    ProposalVerificationFailed,
     ProposalRolledBack,
+    ExecutionInterrupted,
+    ExecutionVerified,
+    ProposalApprovalExpired,
+    ProposalClosed,
+    ReviewSlaBreached,
+    BreakGlassExecuted,
+    RetrospectiveReviewed,
+    RetrospectiveOverdue,
+    CommentAdded,
+    CommentThreadResolved,
     SchemaChanged,
     ConnectionCreated,
     ConnectionDeleted,
+    ConnectionCloned,
+    ConnectionRevealed,
+    SandboxCreated,
+    SandboxExtended,
+    SandboxDropped,
+    BenchmarkRun,
+    SchemaScopeChanged,
+    DiffIgnoreChanged,
+    DiffModeChanged,
+    BlastRadiusLimitsChanged,
+    BackupRecorded,
+    CustomFieldsChanged,
+    TagTaxonomyChanged,
+    TeamsChanged,
+    LegalHoldPlaced,
+    LegalHoldReleased,
+    RetentionApplied,
+    ExecutionPolicyChanged,
+    QuotaOverridden,
+    ImpersonationStarted,
+    ImpersonationEnded,
+    ImpersonatedRequest,
+    RiskAnalysisStale,
+    RiskReanalyzed,
+    ShareLinkCreated,
+    ShareLinkRevoked,
+    ShareLinkAccessed,
+    EditLockTakenOver,
+    EditLockReleased,
+    AuditLogExported,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chained_log() -> Vec<AuditEntry> {
+        let mut log = vec![AuditEntry::new(AuditAction::ProposalCreated, "alice", "proposal", "p0")];
+        for i in 1..4 {
+            let entry = AuditEntry::new(AuditAction::CommentAdded, "bob", "proposal", &format!("p{}", i));
+            log.push(entry.chained(chain_head(&log)));
+        }
+        log
+    }
+
+    #[test]
+    fn test_verify_chain_accepts_legacy_prefix() {
+        let report = verify_chain(&chained_log());
+        assert!(report.valid);
+        assert_eq!(report.unchained_entries, 1);
+        assert_eq!(report.verified_entries, 3);
+    }
+
+    #[test]
+    fn test_verify_chain_reports_first_tampered_entry() {
+        let mut edited = chained_log();
+        edited[2].actor = "mallory".to_string();
+        let report = verify_chain(&edited);
+        assert!(!report.valid);
+        assert_eq!(report.first_tampered.unwrap().position, 2);
+
+        let mut removed = chained_log();
+        removed.remove(2);
+        let tampered = verify_chain(&removed).first_tampered.unwrap();
+        assert_eq!(tampered.position, 2);
+        assert!(tampered.reason.contains("removed"));
+
+        // Pruned entries keep their place in the chain
+        let mut pruned = chained_log();
+        pruned[2].prune(Utc::now());
+        let report = verify_chain(&pruned);
+        assert!(report.valid);
+        assert_eq!(report.pruned_entries, 1);
+
+        // and what retention keeps of them is still checked
+        let mut relabelled = pruned.clone();
+        relabelled[2].action = AuditAction::ProposalApproved;
+        assert_eq!(verify_chain(&relabelled).first_tampered.unwrap().position, 2);
+        let mut digest_swapped = pruned.clone();
+        digest_swapped[2].payload_digest = pruned[3].payload_digest.clone();
+        assert_eq!(verify_chain(&digest_swapped).first_tampered.unwrap().position, 2);
+
+        // Marking an entry pruned doesn't hide an edit to its payload
+        let mut hidden = chained_log();
+        hidden[2].actor = "mallory".to_string();
+        hidden[2].pruned_at = Some(Utc::now());
+        hidden[2].payload_digest = None;
+        assert_eq!(verify_chain(&hidden).first_tampered.unwrap().position, 2);
+
+        let mut injected = chained_log();
+        injected.push(AuditEntry::new(AuditAction::ProposalApproved, "mallory", "proposal", "p9"));
+        assert_eq!(verify_chain(&injected).first_tampered.unwrap().position, 4);
+    }
 }
@@ -1,11 +1,44 @@
 //! Mirror service - Schema introspection and semantic mapping
+//!
+//! A semantic map is built in layers that go stale at different speeds:
+//! the schema (tables and columns), table statistics, the foreign key
+//! dependency graph and the hot spots ranked from the statistics. Each layer
+//! can be refreshed on its own and carries its own timestamp, so cheap
+//! statistics can be refreshed hourly while the schema and dependency layers
+//! are rebuilt daily. Maps are kept in memory; a restart rebuilds them on
+//! the next full build.
 
+use crate::config::SemanticMapConfig;
+use crate::connection::ConnectionPool;
 use crate::error::AppError;
+use crate::introspection::{mysql_query_error, ForeignKey, MySqlIntrospector, PostgresIntrospector, Table};
+use crate::state::SharedState;
 use chrono::{DateTime, Utc};
+use deadpool_postgres::Client;
+use mysql_async::prelude::Queryable;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Hot spots kept per kind
+const MAX_HOT_SPOTS_PER_KIND: usize = 10;
+
+/// Tables smaller than this are not flagged for sequential scans
+const SEQ_SCAN_MIN_ROWS: i64 = 10_000;
+
+/// Dead tuples below this count are not flagged, whatever the ratio
+const DEAD_TUPLES_MIN: i64 = 1_000;
+
+/// Share of dead tuples from which a table is flagged
+const DEAD_TUPLE_RATIO: f64 = 0.2;
+
+/// How often the background refresher looks for layers that are due
+const REFRESH_TICK: Duration = Duration::from_secs(60);
+
 /// Mirror service for schema introspection
 pub struct MirrorService;
 
@@ -14,17 +47,34 @@ impl MirrorService {
         Self
     }
 
-    /// Build a semantic map from the database schema
+    /// Build a semantic map from the database schema, every layer at once
     pub async fn build_semantic_map(
         &self,
-        _connection_id: Uuid,
+        pool: &ConnectionPool,
+        connection_id: Uuid,
+        scope: &[String],
     ) -> Result<SemanticMap, AppError> {
-        Ok(SemanticMap {
-            id: Uuid::new_v4(),
-            connection_id: _connection_id,
-            tables: HashMap::new(),
-            relationships: Vec::new(),
-            created_at: Utc::now(),
+        let mut map = SemanticMap::empty(connection_id);
+        for layer in SemanticLayer::ALL {
+            map.apply(self.refresh_layer(pool, scope, layer).await?, Utc::now());
+        }
+        Ok(map)
+    }
+
+    /// Read one layer from the database, to be merged into a stored map
+    pub async fn refresh_layer(&self, pool: &ConnectionPool, scope: &[String], layer: SemanticLayer) -> Result<LayerData, AppError> {
+        let pool = match pool {
+            ConnectionPool::Postgres(pool) => pool,
+            ConnectionPool::MySql(pool) => return mysql_layer(pool, scope, layer).await,
+        };
+        let client = pool.get().await?;
+        Ok(match layer {
+            SemanticLayer::Schema => LayerData::Schema(PostgresIntrospector::get_tables(&client, scope).await?),
+            SemanticLayer::Statistics => LayerData::Statistics(table_statistics(&client, scope).await?),
+            SemanticLayer::Dependencies => {
+                LayerData::Dependencies(PostgresIntrospector::get_foreign_keys(&client, scope).await?)
+            }
+            SemanticLayer::HotSpots => LayerData::HotSpots(table_statistics(&client, scope).await?),
         })
     }
 
@@ -48,17 +98,169 @@ impl Default for MirrorService {
     }
 }
 
+/// Independently refreshed part of a semantic map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SemanticLayer {
+    Schema,
+    Statistics,
+    Dependencies,
+    HotSpots,
+}
+
+impl SemanticLayer {
+    /// In build order: the schema first, so later layers have tables to attach to
+    pub const ALL: [SemanticLayer; 4] = [
+        SemanticLayer::Schema,
+        SemanticLayer::Statistics,
+        SemanticLayer::Dependencies,
+        SemanticLayer::HotSpots,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "schema" => Some(Self::Schema),
+            "statistics" => Some(Self::Statistics),
+            "dependencies" => Some(Self::Dependencies),
+            "hot-spots" => Some(Self::HotSpots),
+            _ => None,
+        }
+    }
+
+    /// Whether refreshing the layer can change what risk analyses see
+    pub fn affects_risk(self) -> bool {
+        matches!(self, Self::Schema | Self::Dependencies)
+    }
+}
+
+/// What one layer refresh read from the database
+#[derive(Debug, Clone)]
+pub enum LayerData {
+    Schema(Vec<Table>),
+    /// Statistics by `schema.table`
+    Statistics(HashMap<String, TableStatistics>),
+    Dependencies(Vec<ForeignKey>),
+    /// Statistics the hot spots are ranked from
+    HotSpots(HashMap<String, TableStatistics>),
+}
+
+impl LayerData {
+    pub fn layer(&self) -> SemanticLayer {
+        match self {
+            Self::Schema(_) => SemanticLayer::Schema,
+            Self::Statistics(_) => SemanticLayer::Statistics,
+            Self::Dependencies(_) => SemanticLayer::Dependencies,
+            Self::HotSpots(_) => SemanticLayer::HotSpots,
+        }
+    }
+}
+
 /// Semantic map of the database schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SemanticMap {
     pub id: Uuid,
     pub connection_id: Uuid,
+    /// Tables by `schema.table`
     pub tables: HashMap<String, TableSemantic>,
     pub relationships: Vec<Relationship>,
+    #[serde(default)]
+    pub hot_spots: Vec<HotSpot>,
+    /// When each layer was last refreshed
+    #[serde(default)]
+    pub layers: BTreeMap<SemanticLayer, DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
+impl SemanticMap {
+    pub fn empty(connection_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            connection_id,
+            tables: HashMap::new(),
+            relationships: Vec::new(),
+            hot_spots: Vec::new(),
+            layers: BTreeMap::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Merge a refreshed layer, leaving the others as they were
+    pub fn apply(&mut self, data: LayerData, at: DateTime<Utc>) {
+        self.layers.insert(data.layer(), at);
+        match data {
+            LayerData::Schema(tables) => {
+                let mut previous = std::mem::take(&mut self.tables);
+                self.tables = tables
+                    .iter()
+                    .map(|table| {
+                        let key = format!("{}.{}", table.schema, table.name);
+                        let mut semantic = TableSemantic::from(table);
+                        // Statistics belong to their own layer and survive a schema refresh
+                        if let Some(old) = previous.remove(&key) {
+                            semantic.row_count_estimate = old.row_count_estimate;
+                            semantic.statistics = old.statistics;
+                        }
+                        (key, semantic)
+                    })
+                    .collect();
+                let tables = &self.tables;
+                self.relationships
+                    .retain(|r| tables.contains_key(&r.from_table) && tables.contains_key(&r.to_table));
+                self.hot_spots.retain(|h| tables.contains_key(&h.table));
+            }
+            LayerData::Statistics(mut statistics) => {
+                for (key, table) in self.tables.iter_mut() {
+                    table.statistics = statistics.remove(key);
+                    table.row_count_estimate = table.statistics.as_ref().map(|s| s.live_rows);
+                }
+            }
+            LayerData::Dependencies(foreign_keys) => {
+                self.relationships = foreign_keys.iter().map(|fk| self.relationship(fk)).collect();
+            }
+            LayerData::HotSpots(statistics) => {
+                let tables = &self.tables;
+                self.hot_spots = rank_hot_spots(&statistics);
+                self.hot_spots.retain(|h| tables.is_empty() || tables.contains_key(&h.table));
+            }
+        }
+    }
+
+    /// A foreign key on a unique or primary key column links one row to one row
+    fn relationship(&self, fk: &ForeignKey) -> Relationship {
+        let from_table = format!("{}.{}", fk.source_schema, fk.source_table);
+        let unique = match fk.source_columns.as_slice() {
+            [column] => self
+                .tables
+                .get(&from_table)
+                .and_then(|t| t.columns.get(column))
+                .is_some_and(|c| c.unique),
+            _ => false,
+        };
+        Relationship {
+            from_column: fk.source_columns.join(", "),
+            to_table: format!("{}.{}", fk.referenced_schema, fk.referenced_table),
+            to_column: fk.referenced_columns.join(", "),
+            relationship_type: if unique { RelationshipType::OneToOne } else { RelationshipType::ManyToOne },
+            from_table,
+        }
+    }
+
+    /// Layers older than their configured cadence
+    pub fn due_layers(&self, config: &SemanticMapConfig, now: DateTime<Utc>) -> Vec<SemanticLayer> {
+        SemanticLayer::ALL
+            .into_iter()
+            .filter(|layer| {
+                config.interval(*layer).is_some_and(|interval| {
+                    self.layers.get(layer).is_none_or(|at| {
+                        (now - *at).to_std().unwrap_or_default() >= interval
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
 /// Semantic information about a table
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -68,6 +270,35 @@ pub struct TableSemantic {
     pub description: Option<String>,
     pub columns: HashMap<String, ColumnSemantic>,
     pub row_count_estimate: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<TableStatistics>,
+}
+
+impl From<&Table> for TableSemantic {
+    fn from(table: &Table) -> Self {
+        Self {
+            name: table.name.clone(),
+            display_name: display_name(&table.name),
+            description: table.governance.description.clone(),
+            columns: table
+                .columns
+                .iter()
+                .map(|c| {
+                    let semantic = ColumnSemantic {
+                        name: c.name.clone(),
+                        display_name: display_name(&c.name),
+                        data_type: c.data_type.clone(),
+                        semantic_type: semantic_type(&c.name, &c.data_type),
+                        description: c.description.clone(),
+                        unique: c.is_primary_key || c.is_unique,
+                    };
+                    (c.name.clone(), semantic)
+                })
+                .collect(),
+            row_count_estimate: None,
+            statistics: None,
+        }
+    }
 }
 
 /// Semantic information about a column
@@ -79,6 +310,45 @@ pub struct ColumnSemantic {
     pub data_type: String,
     pub semantic_type: Option<String>,
     pub description: Option<String>,
+    /// Primary key or unique on its own
+    #[serde(default)]
+    pub unique: bool,
+}
+
+/// Activity counters of a table, cumulative since the last statistics reset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStatistics {
+    pub live_rows: i64,
+    pub dead_rows: i64,
+    pub seq_scans: i64,
+    pub index_scans: i64,
+    pub rows_inserted: i64,
+    pub rows_updated: i64,
+    pub rows_deleted: i64,
+    pub total_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_analyzed_at: Option<DateTime<Utc>>,
+}
+
+/// Table standing out in the statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotSpot {
+    /// `schema.table`
+    pub table: String,
+    pub kind: HotSpotKind,
+    /// Rows written, share of dead tuples or sequential scans, by kind
+    pub value: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotSpotKind {
+    WriteChurn,
+    DeadTuples,
+    SequentialScans,
 }
 
 /// Relationship between tables
@@ -120,3 +390,341 @@ pub struct DriftChange {
     pub object_name: String,
     pub details: String,
 }
+
+/// Stored semantic maps, by connection
+#[derive(Clone, Default)]
+pub struct SemanticMapStore {
+    maps: Arc<RwLock<HashMap<Uuid, SemanticMap>>>,
+}
+
+impl SemanticMapStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get(&self, connection_id: Uuid) -> Option<SemanticMap> {
+        self.maps.read().await.get(&connection_id).cloned()
+    }
+
+    pub async fn put(&self, map: SemanticMap) {
+        self.maps.write().await.insert(map.connection_id, map);
+    }
+
+    pub async fn remove(&self, connection_id: Uuid) {
+        self.maps.write().await.remove(&connection_id);
+    }
+
+    /// Merge a refreshed layer into a connection's map, if it has one
+    pub async fn apply(&self, connection_id: Uuid, data: LayerData) -> Option<SemanticMap> {
+        let mut maps = self.maps.write().await;
+        let map = maps.get_mut(&connection_id)?;
+        map.apply(data, Utc::now());
+        Some(map.clone())
+    }
+
+    /// Layers due for a refresh, by connection
+    pub async fn due(&self, config: &SemanticMapConfig, now: DateTime<Utc>) -> Vec<(Uuid, Vec<SemanticLayer>)> {
+        self.maps
+            .read()
+            .await
+            .values()
+            .map(|map| (map.connection_id, map.due_layers(config, now)))
+            .filter(|(_, layers)| !layers.is_empty())
+            .collect()
+    }
+}
+
+/// Spawn the background task refreshing stored maps' layers on their cadences
+pub fn spawn_semantic_refresher(state: SharedState, config: SemanticMapConfig) -> Option<tokio::task::JoinHandle<()>> {
+    if SemanticLayer::ALL.iter().all(|layer| config.interval(*layer).is_none()) {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mirror = MirrorService::new();
+        let mut interval = tokio::time::interval(REFRESH_TICK);
+        loop {
+            interval.tick().await;
+            for (connection_id, layers) in state.semantic_maps.due(&config, Utc::now()).await {
+                // Maps of connections that went away are dropped, not retried
+                let Ok(pool) = state.connections.get_connection_pool(connection_id).await else {
+                    state.semantic_maps.remove(connection_id).await;
+                    continue;
+                };
+                let scope = state.connections.schema_scope(connection_id).await;
+                for layer in layers {
+                    match mirror.refresh_layer(&pool, &scope, layer).await {
+                        Ok(data) => {
+                            state.semantic_maps.apply(connection_id, data).await;
+                            info!("Refreshed the {:?} layer of the semantic map for {}", layer, connection_id);
+                        }
+                        Err(e) => warn!("Semantic map {:?} refresh failed for {}: {}", layer, connection_id, e),
+                    }
+                }
+            }
+        }
+    }))
+}
+
+/// Statistics of every user table in scope, by `schema.table`
+async fn table_statistics(client: &Client, scope: &[String]) -> Result<HashMap<String, TableStatistics>, AppError> {
+    let rows = client
+        .query(
+            "SELECT s.schemaname::text, s.relname::text, s.n_live_tup, s.n_dead_tup,
+                    COALESCE(s.seq_scan, 0), COALESCE(s.idx_scan, 0),
+                    s.n_tup_ins, s.n_tup_upd, s.n_tup_del,
+                    pg_total_relation_size(s.relid),
+                    GREATEST(s.last_analyze, s.last_autoanalyze)
+             FROM pg_stat_user_tables s
+             WHERE cardinality($1::text[]) = 0 OR s.schemaname::text = ANY($1)",
+            &[&scope],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let key = format!("{}.{}", row.get::<_, String>(0), row.get::<_, String>(1));
+            let statistics = TableStatistics {
+                live_rows: row.get(2),
+                dead_rows: row.get(3),
+                seq_scans: row.get(4),
+                index_scans: row.get(5),
+                rows_inserted: row.get(6),
+                rows_updated: row.get(7),
+                rows_deleted: row.get(8),
+                total_bytes: row.get(9),
+                last_analyzed_at: row.get(10),
+            };
+            (key, statistics)
+        })
+        .collect())
+}
+
+/// Read one layer from a MySQL server
+async fn mysql_layer(pool: &mysql_async::Pool, scope: &[String], layer: SemanticLayer) -> Result<LayerData, AppError> {
+    let mut conn = MySqlIntrospector::conn(pool).await?;
+    let schemas = MySqlIntrospector::schemas_in_scope(&mut conn, scope).await?;
+    Ok(match layer {
+        SemanticLayer::Schema => LayerData::Schema(MySqlIntrospector::get_tables(&mut conn, &schemas).await?),
+        SemanticLayer::Statistics => LayerData::Statistics(mysql_table_statistics(&mut conn, &schemas).await?),
+        SemanticLayer::Dependencies => {
+            LayerData::Dependencies(MySqlIntrospector::get_foreign_keys(&mut conn, &schemas).await?)
+        }
+        SemanticLayer::HotSpots => LayerData::HotSpots(mysql_table_statistics(&mut conn, &schemas).await?),
+    })
+}
+
+/// Estimated rows and size of every table in scope on MySQL, which keeps no
+/// scan, write or dead-row counters in `information_schema`; those stay zero
+async fn mysql_table_statistics(
+    conn: &mut mysql_async::Conn,
+    schemas: &[String],
+) -> Result<HashMap<String, TableStatistics>, AppError> {
+    let mut statistics = HashMap::new();
+    for schema in schemas {
+        let rows: Vec<(String, Option<i64>, Option<i64>)> = conn
+            .exec(
+                "SELECT TABLE_NAME, TABLE_ROWS, DATA_LENGTH + INDEX_LENGTH
+                 FROM information_schema.TABLES
+                 WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE'",
+                (schema,),
+            )
+            .await
+            .map_err(mysql_query_error)?;
+        for (table, live_rows, total_bytes) in rows {
+            let table_statistics = TableStatistics {
+                live_rows: live_rows.unwrap_or_default(),
+                total_bytes: total_bytes.unwrap_or_default(),
+                ..Default::default()
+            };
+            statistics.insert(format!("{}.{}", schema, table), table_statistics);
+        }
+    }
+    Ok(statistics)
+}
+
+/// Tables with the most writes, the most dead tuples and the most
+/// sequential scans of large tables, busiest first within each kind
+pub fn rank_hot_spots(statistics: &HashMap<String, TableStatistics>) -> Vec<HotSpot> {
+    let mut churn = Vec::new();
+    let mut dead = Vec::new();
+    let mut scans = Vec::new();
+
+    for (table, s) in statistics {
+        let written = s.rows_inserted + s.rows_updated + s.rows_deleted;
+        if written > 0 {
+            churn.push(HotSpot {
+                table: table.clone(),
+                kind: HotSpotKind::WriteChurn,
+                value: written as f64,
+                detail: format!(
+                    "{} inserted, {} updated, {} deleted",
+                    s.rows_inserted, s.rows_updated, s.rows_deleted
+                ),
+            });
+        }
+
+        let total = s.live_rows + s.dead_rows;
+        let ratio = if total > 0 { s.dead_rows as f64 / total as f64 } else { 0.0 };
+        if s.dead_rows >= DEAD_TUPLES_MIN && ratio >= DEAD_TUPLE_RATIO {
+            dead.push(HotSpot {
+                table: table.clone(),
+                kind: HotSpotKind::DeadTuples,
+                value: (ratio * 1000.0).round() / 1000.0,
+                detail: format!("{} dead of {} tuples", s.dead_rows, total),
+            });
+        }
+
+        if s.live_rows >= SEQ_SCAN_MIN_ROWS && s.seq_scans > s.index_scans {
+            scans.push(HotSpot {
+                table: table.clone(),
+                kind: HotSpotKind::SequentialScans,
+                value: s.seq_scans as f64,
+                detail: format!(
+                    "{} sequential vs {} index scans over ~{} rows",
+                    s.seq_scans, s.index_scans, s.live_rows
+                ),
+            });
+        }
+    }
+
+    let mut hot_spots = Vec::new();
+    for mut kind in [churn, dead, scans] {
+        kind.sort_by(|a, b| b.value.total_cmp(&a.value).then_with(|| a.table.cmp(&b.table)));
+        kind.truncate(MAX_HOT_SPOTS_PER_KIND);
+        hot_spots.extend(kind);
+    }
+    hot_spots
+}
+
+/// `order_items` -> `Order Items`
+fn display_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Guess what a column holds from its name and type
+fn semantic_type(name: &str, data_type: &str) -> Option<String> {
+    let name = name.to_lowercase();
+    let data_type = data_type.to_lowercase();
+    let kind = if name == "id" || name.ends_with("_id") || data_type == "uuid" {
+        "identifier"
+    } else if name.contains("email") {
+        "email"
+    } else if name.contains("phone") {
+        "phone"
+    } else if data_type.starts_with("timestamp") || data_type == "date" || name.ends_with("_at") {
+        "timestamp"
+    } else if data_type == "money" || ["price", "amount", "cost", "total"].iter().any(|w| name.contains(w)) {
+        "amount"
+    } else if data_type == "boolean" || name.starts_with("is_") || name.starts_with("has_") {
+        "flag"
+    } else {
+        return None;
+    };
+    Some(kind.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::Column;
+
+    fn table(name: &str, columns: &[(&str, bool)]) -> Table {
+        Table {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            columns: columns
+                .iter()
+                .map(|(column, unique)| Column {
+                    name: column.to_string(),
+                    data_type: "integer".to_string(),
+                    nullable: true,
+                    default_value: None,
+                    is_primary_key: false,
+                    is_unique: *unique,
+                    ordinal_position: 1,
+                    collation: None,
+                    pii_classification: None,
+                    description: None,
+                    tags: vec![],
+                    custom_fields: Default::default(),
+                })
+                .collect(),
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: Default::default(),
+        }
+    }
+
+    fn fk(source: &str, column: &str, target: &str) -> ForeignKey {
+        ForeignKey {
+            constraint_name: format!("{}_{}_fk", source, column),
+            source_schema: "public".to_string(),
+            source_table: source.to_string(),
+            source_columns: vec![column.to_string()],
+            referenced_schema: "public".to_string(),
+            referenced_table: target.to_string(),
+            referenced_columns: vec!["id".to_string()],
+            on_update: "NO ACTION".to_string(),
+            on_delete: "NO ACTION".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_layers_merge_independently() {
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        let mut map = SemanticMap::empty(Uuid::new_v4());
+        map.apply(
+            LayerData::Schema(vec![
+                table("users", &[("id", true)]),
+                table("order_items", &[("id", true), ("user_id", false)]),
+                table("profiles", &[("user_id", true)]),
+            ]),
+            hour_ago,
+        );
+        map.apply(
+            LayerData::Dependencies(vec![fk("order_items", "user_id", "users"), fk("profiles", "user_id", "users")]),
+            hour_ago,
+        );
+        let statistics = HashMap::from([(
+            "public.order_items".to_string(),
+            TableStatistics { live_rows: 50_000, dead_rows: 20_000, seq_scans: 40, index_scans: 3, rows_updated: 9, ..Default::default() },
+        )]);
+        map.apply(LayerData::Statistics(statistics.clone()), Utc::now());
+        map.apply(LayerData::HotSpots(statistics), Utc::now());
+
+        assert_eq!(map.tables["public.order_items"].display_name, "Order Items");
+        assert_eq!(map.tables["public.order_items"].columns["user_id"].semantic_type.as_deref(), Some("identifier"));
+        assert_eq!(map.tables["public.order_items"].row_count_estimate, Some(50_000));
+        let kinds: Vec<_> = map.relationships.iter().map(|r| (r.from_table.as_str(), format!("{:?}", r.relationship_type))).collect();
+        assert_eq!(kinds, vec![("public.order_items", "ManyToOne".to_string()), ("public.profiles", "OneToOne".to_string())]);
+        let hot: Vec<_> = map.hot_spots.iter().map(|h| h.kind).collect();
+        assert_eq!(hot, vec![HotSpotKind::WriteChurn, HotSpotKind::DeadTuples, HotSpotKind::SequentialScans]);
+
+        // A schema refresh keeps statistics and drops what belonged to removed tables
+        map.apply(LayerData::Schema(vec![table("users", &[("id", true)]), table("order_items", &[("id", true)])]), Utc::now());
+        assert_eq!(map.tables["public.order_items"].row_count_estimate, Some(50_000));
+        assert_eq!(map.relationships.len(), 1);
+        assert_eq!(map.layers.len(), 4);
+
+        // Statistics are due hourly, the schema and dependencies daily
+        let config = SemanticMapConfig::default();
+        let in_two_hours = Utc::now() + chrono::Duration::hours(2);
+        assert_eq!(map.due_layers(&config, in_two_hours), vec![SemanticLayer::Statistics, SemanticLayer::HotSpots]);
+        assert_eq!(map.due_layers(&config, Utc::now() + chrono::Duration::days(2)).len(), 4);
+        assert_eq!(SemanticLayer::parse("hot-spots"), Some(SemanticLayer::HotSpots));
+    }
+}
@@ -3,11 +3,61 @@
 //! This module provides the legacy governance pipeline infrastructure.
 //! The new v2 proposal system is in the `proposal` module.
 
+pub mod access;
+pub mod activity;
+pub mod advisory_lock;
+pub mod analytics;
+pub mod audit_export;
+pub mod backup;
+pub mod board;
+pub mod break_glass;
+pub mod contributions;
+pub mod execution_policy;
+pub mod explain;
+pub mod forensics;
+pub mod impact;
+pub mod journal;
+pub mod membership;
 pub mod metadata;
 pub mod mirror;
+pub mod mysql;
+pub mod not_null;
 pub mod orchestrator;
+pub mod partitioning;
+pub mod patch;
+pub mod plan;
+pub mod policy;
+pub mod preflight;
+pub mod presence;
+pub mod progress;
 pub mod proposal;
+pub mod reanalysis;
+pub mod references;
+pub mod reorder;
+pub mod refresh;
+pub mod retention;
+pub mod row_counts;
+pub mod revert;
 pub mod risk;
+pub mod risk_history;
+pub mod sandbox;
+pub mod scratch;
+pub mod load_test;
+pub mod changelog;
+pub mod checklist;
+pub mod share;
+pub mod sla;
+pub mod summary;
+pub mod teams;
+pub mod templating;
+pub mod template;
+pub mod type_migration;
 pub mod types;
+pub mod uniqueness;
+pub mod verification;
+pub mod watches;
+pub mod watchdog;
 
 pub use metadata::MetadataStore;
+pub use proposal::ProposalService;
+pub use share::ShareLinkRegistry;
@@ -0,0 +1,321 @@
+//! MySQL dialect of generated migrations
+//!
+//! `Orchestrator::generate_migration` writes PostgreSQL. Proposals on MySQL
+//! connections are translated here instead, statement for statement in the
+//! same layout so splitting, reviews and irreversible-step detection work
+//! unchanged. `MODIFY COLUMN` restates the whole column, so column changes
+//! are resolved against the current schema; changes MySQL has no equivalent
+//! for are refused together rather than emitted as SQL that cannot run.
+
+use crate::error::AppError;
+use crate::introspection::{Column, SchemaSnapshot};
+use crate::pipeline::orchestrator::Orchestrator;
+use crate::pipeline::proposal::{MigrationArtifacts, SchemaProposal};
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::{ColumnDef, SchemaChange};
+
+/// Generate up and down SQL for a proposal on a MySQL connection; `current`
+/// is the live schema column and index changes are resolved against
+pub fn generate_migration(proposal: &SchemaProposal, current: &SchemaSnapshot) -> Result<MigrationArtifacts, AppError> {
+    let mut up_statements = Vec::new();
+    let mut down_statements = Vec::new();
+    let mut unsupported = Vec::new();
+
+    for change in &proposal.changes {
+        match change {
+            SchemaChange::CreateSchema { schema_name, owner: None } => {
+                up_statements.push(format!("CREATE DATABASE {};", schema_name));
+                down_statements.push(format!("DROP DATABASE IF EXISTS {};", schema_name));
+            }
+            SchemaChange::DropSchema { schema_name, cascade: true } => {
+                up_statements.push(format!("DROP DATABASE {};", schema_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP DATABASE {}", schema_name));
+            }
+            SchemaChange::CreateTable { table_name, columns, partition_by: None } => {
+                let cols: Vec<String> = columns.iter().map(column_definition).collect();
+                up_statements.push(format!("CREATE TABLE {} (\n  {}\n);", table_name, cols.join(",\n  ")));
+                down_statements.push(format!("DROP TABLE IF EXISTS {};", table_name));
+            }
+            SchemaChange::DropTable { table_name } => {
+                up_statements.push(format!("DROP TABLE {};", table_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP TABLE {}", table_name));
+            }
+            SchemaChange::AddColumn { table_name, column } => {
+                let mut column = column.clone();
+                column.is_primary_key = false;
+                up_statements.push(format!("ALTER TABLE {} ADD COLUMN {};", table_name, column_definition(&column)));
+                down_statements.push(format!("ALTER TABLE {} DROP COLUMN {};", table_name, column.name));
+            }
+            SchemaChange::DropColumn { table_name, column_name } => {
+                up_statements.push(format!("ALTER TABLE {} DROP COLUMN {};", table_name, column_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP COLUMN {}.{}", table_name, column_name));
+            }
+            SchemaChange::AlterColumn { table_name, column_name, new_type, new_nullable, new_default, using: None } => {
+                let before = find_column(current, table_name, column_name).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Column {}.{} is not in the live schema; MySQL needs its current definition to modify it",
+                        table_name, column_name
+                    ))
+                })?;
+                let after = ColumnDef {
+                    name: before.name.clone(),
+                    data_type: new_type.clone().unwrap_or_else(|| before.data_type.clone()),
+                    nullable: new_nullable.unwrap_or(before.nullable),
+                    default_value: match new_default {
+                        Some(default) if default.is_empty() || default.eq_ignore_ascii_case("NULL") => None,
+                        Some(default) => Some(default.clone()),
+                        None => before.default_value.clone(),
+                    },
+                    is_primary_key: false,
+                    collation: before.collation.clone(),
+                };
+                let restore = ColumnDef {
+                    name: before.name.clone(),
+                    data_type: before.data_type.clone(),
+                    nullable: before.nullable,
+                    default_value: before.default_value.clone(),
+                    is_primary_key: false,
+                    collation: before.collation.clone(),
+                };
+                up_statements.push(format!("ALTER TABLE {} MODIFY COLUMN {};", table_name, column_definition(&after)));
+                down_statements.push(format!("ALTER TABLE {} MODIFY COLUMN {};", table_name, column_definition(&restore)));
+            }
+            SchemaChange::RenameTable { old_name, new_name } => {
+                up_statements.push(format!("RENAME TABLE {} TO {};", old_name, new_name));
+                down_statements.push(format!("RENAME TABLE {} TO {};", new_name, old_name));
+            }
+            SchemaChange::RenameColumn { table_name, old_name, new_name } => {
+                up_statements.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {};", table_name, old_name, new_name));
+                down_statements.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {};", table_name, new_name, old_name));
+            }
+            SchemaChange::AddIndex { table_name, index_name, columns, unique, concurrently } => {
+                let unique_str = if *unique { "UNIQUE " } else { "" };
+                // Online DDL is MySQL's way of building an index without blocking writes
+                let online_str = if *concurrently { " ALGORITHM=INPLACE LOCK=NONE" } else { "" };
+                up_statements.push(format!(
+                    "CREATE {}INDEX {} ON {} ({}){};",
+                    unique_str, index_name, table_name, columns.join(", "), online_str
+                ));
+                down_statements.push(format!("DROP INDEX {} ON {};", index_name, table_name));
+            }
+            SchemaChange::DropIndex { index_name } => {
+                let table_name = index_table(current, index_name)?;
+                let (_, name) = split_table_name(index_name);
+                up_statements.push(format!("DROP INDEX {} ON {};", name, table_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP INDEX {}", index_name));
+            }
+            SchemaChange::AddForeignKey { table_name, constraint_name, columns, ref_table, ref_columns } => {
+                up_statements.push(format!(
+                    "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({});",
+                    table_name, constraint_name, columns.join(", "), ref_table, ref_columns.join(", ")
+                ));
+                down_statements.push(format!("ALTER TABLE {} DROP FOREIGN KEY {};", table_name, constraint_name));
+            }
+            SchemaChange::DropForeignKey { table_name, constraint_name } => {
+                up_statements.push(format!("ALTER TABLE {} DROP FOREIGN KEY {};", table_name, constraint_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP FOREIGN KEY {}.{}", table_name, constraint_name));
+            }
+            SchemaChange::AddCheck { table_name, constraint_name, expression, not_valid: false } => {
+                up_statements.push(format!(
+                    "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});",
+                    table_name, constraint_name, expression
+                ));
+                down_statements.push(format!("ALTER TABLE {} DROP CHECK {};", table_name, constraint_name));
+            }
+            SchemaChange::DropConstraint { table_name, constraint_name } => {
+                up_statements.push(format!("ALTER TABLE {} DROP CONSTRAINT {};", table_name, constraint_name));
+                down_statements.push(format!("-- Cannot auto-rollback DROP CONSTRAINT {}.{}", table_name, constraint_name));
+            }
+            SchemaChange::Vacuum { table_name, full, analyze } if *full || *analyze => {
+                // OPTIMIZE rebuilds the table like VACUUM FULL; plain VACUUM has no counterpart
+                if *full {
+                    up_statements.push(format!("OPTIMIZE TABLE {};", table_name));
+                    down_statements.push(format!("-- Nothing to roll back for OPTIMIZE TABLE {}", table_name));
+                }
+                if *analyze {
+                    up_statements.push(format!("ANALYZE TABLE {};", table_name));
+                    down_statements.push(format!("-- Nothing to roll back for ANALYZE TABLE {}", table_name));
+                }
+            }
+            SchemaChange::Analyze { table_name } => {
+                up_statements.push(format!("ANALYZE TABLE {};", table_name));
+                down_statements.push(format!("-- Nothing to roll back for ANALYZE TABLE {}", table_name));
+            }
+            SchemaChange::SetCustomField { .. }
+            | SchemaChange::AddTag { .. }
+            | SchemaChange::RemoveTag { .. }
+            | SchemaChange::SetClassification { .. } => {
+                // Governance notes are comments, the same in every dialect
+                let mut single = proposal.clone();
+                single.changes = vec![change.clone()];
+                let migration = Orchestrator::new().generate_migration(&single);
+                up_statements.push(migration.up_sql);
+                down_statements.push(migration.down_sql);
+            }
+            other => unsupported.push(unsupported_reason(other)),
+        }
+    }
+
+    if !unsupported.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "These changes cannot be generated for MySQL: {}",
+            unsupported.join("; ")
+        )));
+    }
+
+    Ok(MigrationArtifacts::new(
+        up_statements.join("\n\n"),
+        down_statements.into_iter().rev().collect::<Vec<_>>().join("\n\n"),
+    ))
+}
+
+/// Column definition as `CREATE TABLE`, `ADD COLUMN` and `MODIFY COLUMN` take it
+fn column_definition(column: &ColumnDef) -> String {
+    let mut def = format!("{} {}", column.name, column.data_type);
+    if let Some(collation) = &column.collation {
+        def.push_str(&format!(" COLLATE {}", collation));
+    }
+    def.push_str(if column.nullable { " NULL" } else { " NOT NULL" });
+    if let Some(default) = &column.default_value {
+        def.push_str(&format!(" DEFAULT {}", default));
+    }
+    if column.is_primary_key {
+        def.push_str(" PRIMARY KEY");
+    }
+    def
+}
+
+/// A column of the snapshot; unqualified tables are in the connection's database
+fn find_column<'a>(snapshot: &'a SchemaSnapshot, table_name: &str, column_name: &str) -> Option<&'a Column> {
+    let (schema, table) = split_table_name(table_name);
+    let schema = schema.unwrap_or(&snapshot.database.name);
+    snapshot
+        .tables
+        .iter()
+        .find(|t| t.name == table && t.schema == schema)?
+        .columns
+        .iter()
+        .find(|c| c.name == column_name)
+}
+
+/// Table an index belongs to; MySQL drops indexes through their table, so
+/// the name has to identify exactly one
+fn index_table(snapshot: &SchemaSnapshot, index_name: &str) -> Result<String, AppError> {
+    let (schema, name) = split_table_name(index_name);
+    let schema = schema.unwrap_or(&snapshot.database.name);
+    let tables: Vec<String> = snapshot
+        .indexes
+        .iter()
+        .filter(|i| i.name == name && i.schema == schema)
+        .map(|i| format!("{}.{}", i.schema, i.table))
+        .collect();
+    match tables.as_slice() {
+        [table] => Ok(table.clone()),
+        [] => Err(AppError::BadRequest(format!("Index {} is not in the live schema", index_name))),
+        _ => Err(AppError::BadRequest(format!(
+            "Index name {} is used on several tables ({}); MySQL index names are per table",
+            index_name,
+            tables.join(", ")
+        ))),
+    }
+}
+
+/// Why a change has no MySQL translation
+fn unsupported_reason(change: &SchemaChange) -> String {
+    match change {
+        SchemaChange::CreateSchema { schema_name, .. } => {
+            format!("database {} cannot be created with an owner", schema_name)
+        }
+        SchemaChange::DropSchema { schema_name, .. } => {
+            format!("dropping database {} drops every table in it; set cascade to confirm", schema_name)
+        }
+        SchemaChange::CreateTable { table_name, .. } => {
+            format!("partitioned table {} uses PostgreSQL partitioning", table_name)
+        }
+        SchemaChange::AlterColumn { table_name, column_name, .. } => {
+            format!("type change of {}.{} has a USING expression", table_name, column_name)
+        }
+        SchemaChange::AddCheck { constraint_name, .. } => {
+            format!("check {} is NOT VALID, which MySQL cannot defer", constraint_name)
+        }
+        other => {
+            let kind = serde_json::to_value(other)
+                .ok()
+                .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(str::to_string))
+                .unwrap_or_else(|| "change".to_string());
+            format!("{} has no MySQL equivalent", kind)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{DatabaseMetadata, Table};
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn column(name: &str, data_type: &str, nullable: bool, default_value: Option<&str>) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default_value: default_value.map(str::to_string),
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_modify_column_restates_the_definition() {
+        let mut current = SchemaSnapshot::empty(Uuid::new_v4());
+        current.database = DatabaseMetadata { name: "shop".to_string(), ..Default::default() };
+        current.tables.push(Table {
+            name: "orders".to_string(),
+            schema: "shop".to_string(),
+            columns: vec![column("status", "varchar(20)", false, Some("'new'"))],
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: Default::default(),
+        });
+
+        let mut proposal = SchemaProposal::new(current.connection_id, "Widen".into(), String::new(), "dev".into());
+        proposal.changes = vec![
+            SchemaChange::AlterColumn {
+                table_name: "orders".to_string(),
+                column_name: "status".to_string(),
+                new_type: Some("varchar(50)".to_string()),
+                new_nullable: None,
+                new_default: None,
+                using: None,
+            },
+            SchemaChange::RenameTable { old_name: "orders".to_string(), new_name: "purchases".to_string() },
+        ];
+
+        let migration = generate_migration(&proposal, &current).unwrap();
+        assert_eq!(
+            migration.up_sql,
+            "ALTER TABLE orders MODIFY COLUMN status varchar(50) NOT NULL DEFAULT 'new';\n\nRENAME TABLE orders TO purchases;"
+        );
+        assert_eq!(
+            migration.down_sql,
+            "RENAME TABLE purchases TO orders;\n\nALTER TABLE orders MODIFY COLUMN status varchar(20) NOT NULL DEFAULT 'new';"
+        );
+
+        proposal.changes.push(SchemaChange::Reindex {
+            target: crate::pipeline::types::ReindexTarget::Table,
+            name: "orders".to_string(),
+            concurrently: false,
+        });
+        let error = generate_migration(&proposal, &current).unwrap_err().to_string();
+        assert!(error.contains("reindex has no MySQL equivalent"), "{}", error);
+    }
+}
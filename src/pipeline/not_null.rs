@@ -0,0 +1,230 @@
+//! NOT NULL backfill planning
+//!
+//! `ALTER COLUMN ... SET NOT NULL` fails on the first NULL and otherwise scans
+//! the whole table under an ACCESS EXCLUSIVE lock. When a proposal tightens a
+//! nullable column, the change is expanded into a plan that avoids both:
+//!
+//! 1. any type or default change in the original request
+//! 2. a batched backfill of the existing NULLs, sized from the table statistics
+//! 3. `CHECK (col IS NOT NULL) NOT VALID`, which takes the lock only briefly
+//! 4. `VALIDATE CONSTRAINT`, which scans without blocking writes
+//! 5. `SET NOT NULL`, which PostgreSQL 12+ proves from the valid check
+//! 6. dropping the helper check
+
+use crate::db::queries::SqlBuilder;
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::type_migration::quote_table;
+use crate::pipeline::types::SchemaChange;
+use deadpool_postgres::Pool;
+use serde::Serialize;
+
+/// Rows each backfill statement updates unless the request says otherwise
+pub const DEFAULT_BATCH_SIZE: u32 = 10_000;
+
+/// PostgreSQL truncates identifiers longer than this
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// How many NULLs a column holds
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NullEstimate {
+    pub rows: i64,
+    pub nulls: i64,
+    /// Counted rather than derived from planner statistics
+    pub exact: bool,
+}
+
+/// The column a change sets NOT NULL on, if it is nullable in the snapshot
+pub fn tightened_column<'a>(change: &'a SchemaChange, snapshot: &SchemaSnapshot) -> Option<(&'a str, &'a str)> {
+    let SchemaChange::AlterColumn { table_name, column_name, new_nullable: Some(false), .. } = change else {
+        return None;
+    };
+    let (schema, table) = split_table_name(table_name);
+    let nullable = snapshot
+        .tables
+        .iter()
+        .find(|t| t.name == table && t.schema == schema.unwrap_or("public"))
+        .and_then(|t| t.columns.iter().find(|c| &c.name == column_name))
+        .is_some_and(|c| c.nullable);
+    nullable.then_some((table_name.as_str(), column_name.as_str()))
+}
+
+/// Estimate NULLs from `pg_class.reltuples` and `pg_stats.null_frac`, counting
+/// them only when the table has never been analyzed
+pub async fn estimate_nulls(pool: &Pool, table: &str, column: &str) -> Result<NullEstimate, AppError> {
+    let client = pool.get().await?;
+    let (schema, name) = split_table_name(table);
+    let row = client
+        .query_opt(
+            "SELECT c.reltuples::bigint, s.null_frac::float8
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             LEFT JOIN pg_stats s ON s.schemaname = n.nspname AND s.tablename = c.relname AND s.attname = $3
+             WHERE n.nspname = $1 AND c.relname = $2",
+            &[&schema.unwrap_or("public"), &name, &column],
+        )
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Table {} not found", table)))?;
+
+    let rows: i64 = row.get(0);
+    let null_frac: Option<f64> = row.get(1);
+    if let (true, Some(fraction)) = (rows >= 0, null_frac) {
+        return Ok(NullEstimate {
+            rows,
+            nulls: (rows as f64 * fraction).ceil() as i64,
+            exact: false,
+        });
+    }
+
+    let row = client
+        .query_one(
+            &format!(
+                "SELECT count(*), count(*) FILTER (WHERE {} IS NULL) FROM {}",
+                SqlBuilder::quote_ident(column),
+                quote_table(table)
+            ),
+            &[],
+        )
+        .await?;
+    Ok(NullEstimate {
+        rows: row.get(0),
+        nulls: row.get(1),
+        exact: true,
+    })
+}
+
+/// Expand a NOT NULL change into the safe sequence. `value` fills existing
+/// NULLs and defaults to the change's new default; it is only required when
+/// there are NULLs to fill.
+pub fn plan(
+    change: &SchemaChange,
+    estimate: &NullEstimate,
+    value: Option<&str>,
+    batch_size: u32,
+) -> Result<Vec<SchemaChange>, AppError> {
+    let SchemaChange::AlterColumn { table_name, column_name, new_type, new_default, using, .. } = change else {
+        return Err(AppError::BadRequest("Only column changes can be planned".to_string()));
+    };
+    if batch_size == 0 {
+        return Err(AppError::Validation("Backfill batch size must be at least 1".to_string()));
+    }
+
+    let mut steps = Vec::new();
+    if new_type.is_some() || new_default.is_some() {
+        steps.push(SchemaChange::AlterColumn {
+            table_name: table_name.clone(),
+            column_name: column_name.clone(),
+            new_type: new_type.clone(),
+            new_nullable: None,
+            new_default: new_default.clone(),
+            using: using.clone(),
+        });
+    }
+
+    if estimate.nulls > 0 {
+        let value = value.or(new_default.as_deref()).ok_or_else(|| {
+            AppError::Validation(format!(
+                "{}.{} has {}{} NULL(s); provide a backfill value or set a default",
+                table_name,
+                column_name,
+                if estimate.exact { "" } else { "about " },
+                estimate.nulls
+            ))
+        })?;
+        let mut batches = (estimate.nulls as u64).div_ceil(batch_size as u64) as u32;
+        // Statistics lag behind writes; one spare batch absorbs the drift
+        if !estimate.exact {
+            batches += 1;
+        }
+        steps.push(SchemaChange::Backfill {
+            table_name: table_name.clone(),
+            column_name: column_name.clone(),
+            value: value.to_string(),
+            batch_size,
+            batches,
+        });
+    }
+
+    let constraint_name = check_name(table_name, column_name);
+    steps.extend([
+        SchemaChange::AddCheck {
+            table_name: table_name.clone(),
+            constraint_name: constraint_name.clone(),
+            expression: format!("{} IS NOT NULL", SqlBuilder::quote_ident(column_name)),
+            not_valid: true,
+        },
+        SchemaChange::ValidateConstraint {
+            table_name: table_name.clone(),
+            constraint_name: constraint_name.clone(),
+        },
+        SchemaChange::AlterColumn {
+            table_name: table_name.clone(),
+            column_name: column_name.clone(),
+            new_type: None,
+            new_nullable: Some(false),
+            new_default: None,
+            using: None,
+        },
+        SchemaChange::DropConstraint {
+            table_name: table_name.clone(),
+            constraint_name,
+        },
+    ]);
+    Ok(steps)
+}
+
+/// Name of the helper check, kept within PostgreSQL's identifier limit
+fn check_name(table_name: &str, column_name: &str) -> String {
+    let table = split_table_name(table_name).1;
+    let mut name = format!("{}_{}_not_null", table, column_name);
+    if name.len() > MAX_IDENTIFIER_LEN {
+        let mut cut = MAX_IDENTIFIER_LEN - "_not_null".len();
+        while !name.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        name = format!("{}_not_null", &name[..cut]);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_not_null(new_default: Option<&str>) -> SchemaChange {
+        SchemaChange::AlterColumn {
+            table_name: "public.orders".to_string(),
+            column_name: "status".to_string(),
+            new_type: None,
+            new_nullable: Some(false),
+            new_default: new_default.map(String::from),
+            using: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_backfills_then_validates() {
+        let estimate = NullEstimate { rows: 100_000, nulls: 25_000, exact: false };
+
+        assert!(matches!(plan(&set_not_null(None), &estimate, None, 10_000), Err(AppError::Validation(_))));
+
+        let steps = plan(&set_not_null(Some("'new'")), &estimate, None, 10_000).unwrap();
+        assert_eq!(steps.len(), 6);
+        assert!(matches!(&steps[0], SchemaChange::AlterColumn { new_nullable: None, new_default: Some(_), .. }));
+        assert!(matches!(
+            &steps[1],
+            SchemaChange::Backfill { value, batches: 4, .. } if value == "'new'"
+        ));
+        assert!(matches!(&steps[2], SchemaChange::AddCheck { not_valid: true, constraint_name, .. } if constraint_name == "orders_status_not_null"));
+        assert!(matches!(&steps[3], SchemaChange::ValidateConstraint { .. }));
+        assert!(matches!(&steps[4], SchemaChange::AlterColumn { new_nullable: Some(false), new_default: None, .. }));
+        assert!(matches!(&steps[5], SchemaChange::DropConstraint { .. }));
+
+        // No NULLs: nothing to backfill and no value needed
+        let clean = NullEstimate { rows: 10, nulls: 0, exact: true };
+        let steps = plan(&set_not_null(None), &clean, None, 10_000).unwrap();
+        assert_eq!(steps.len(), 4);
+    }
+}
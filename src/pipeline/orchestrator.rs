@@ -1,78 +1,366 @@
 //! Orchestrator - Safe execution of schema migrations
 
+use crate::activity::string_literal_len;
+use crate::db::queries::SqlBuilder;
 use crate::error::AppError;
+use crate::pipeline::advisory_lock::{self, LockWait, LockedSession};
+use crate::pipeline::backup::BackupReference;
+use crate::pipeline::execution_policy;
+use crate::pipeline::explain::{self, CostSummary};
+use crate::pipeline::forensics::{self, FailureForensics};
+use crate::pipeline::journal::ExecutionJournal;
+use crate::pipeline::preflight::{self, PermissionReport};
+use crate::pipeline::progress::{self, ExecutionMonitor};
 use crate::pipeline::proposal::{MigrationArtifacts, SchemaProposal};
+use crate::pipeline::reorder;
+use crate::pipeline::risk::RiskEngine;
+use crate::pipeline::row_counts::{self, RowCountVerification};
+use crate::pipeline::watchdog::{self, StatementLimit, WatchdogKill};
+use crate::simulation::{DeepDryRun, DryRunner};
 use chrono::{DateTime, Utc};
+use deadpool_postgres::{Object, Pool};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tokio_postgres::error::SqlState;
+use tracing::warn;
 use uuid::Uuid;
 
 /// Orchestrator for safely executing schema migrations
-pub struct Orchestrator;
+pub struct Orchestrator {
+    monitor: Option<ExecutionMonitor>,
+    journal: Option<ExecutionJournal>,
+}
 
 impl Orchestrator {
     pub fn new() -> Self {
-        Self
+        Self { monitor: None, journal: None }
+    }
+
+    /// Report live progress of executions to `monitor`
+    pub fn with_monitor(mut self, monitor: ExecutionMonitor) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    /// Write state transitions of real executions to `journal`
+    pub fn with_journal(mut self, journal: ExecutionJournal) -> Self {
+        self.journal = Some(journal);
+        self
     }
 
-    /// Execute a migration against the database
+    /// Execute a migration against the database.
+    ///
+    /// Statements are grouped into chunks of at most `chunk_size`, each run in
+    /// its own transaction and checkpointed on commit; without a chunk size the
+    /// whole migration is one transaction. Statements that cannot run inside a
+    /// transaction (VACUUM, CONCURRENTLY) form chunks of their own. The first
+    /// failure stops the run and captures forensics for the post-mortem, and a
+    /// later run can pick up at the checkpoint via `start_at`.
+    ///
+    /// A dry run executes nothing; it asks the planner for cost and row
+    /// estimates of the statements it can explain. A deep dry run also
+    /// executes the statements in a transaction that is always rolled back,
+    /// and fails on whatever the existing data would make fail.
+    ///
+    /// A real run stops before the next confirmation gate after `start_at`
+    /// and reports it in `paused_at_gate`; resuming at the gate passes it.
+    ///
+    /// A real run holds the SchemaFlow advisory lock on the target database
+    /// from its first statement to its last, waiting up to `lock_wait` for
+    /// another instance's migration to finish before giving up.
+    ///
+    /// A run of changes that should leave every row in place counts the rows
+    /// of the affected tables before and after, flagging unexpected deltas.
+    ///
+    /// With a monitor attached, the running statement and samples of the
+    /// progress views for the session are published while the run lasts.
+    /// With a journal attached, the start, every checkpoint and the end are
+    /// recorded durably so a run cut off by a crash can be recovered.
+    ///
+    /// With a watchdog in the options, each statement runs under a
+    /// `statement_timeout` derived from its estimate; one cancelled by it
+    /// fails the run like any other error and is reported in `watchdog`.
     pub async fn execute(
         &self,
-        _proposal: &SchemaProposal,
-        _dry_run: bool,
+        pool: &Pool,
+        proposal: &SchemaProposal,
+        options: ExecutionOptions,
     ) -> Result<ExecutionResult, AppError> {
-        // In a real implementation, this would:
-        // 1. Start a transaction
-        // 2. Execute each statement in the migration
-        // 3. Record the execution in audit log
-        // 4. Commit or rollback based on success
-        
-        Ok(ExecutionResult {
+        let started = Instant::now();
+        let statements = proposal
+            .migration
+            .as_ref()
+            .map(|m| split_statements(&m.up_sql))
+            .unwrap_or_default();
+
+        if options.start_at > statements.len() {
+            return Err(AppError::BadRequest(format!(
+                "Cannot resume at statement {}; the migration has {} statements",
+                options.start_at + 1,
+                statements.len()
+            )));
+        }
+        let end = options.stop_at.map_or(statements.len(), |stop_at| stop_at.min(statements.len()));
+        if end < options.start_at {
+            return Err(AppError::BadRequest(format!(
+                "Cannot stop before statement {} when starting at statement {}",
+                end + 1,
+                options.start_at + 1
+            )));
+        }
+
+        let mut result = ExecutionResult {
             id: Uuid::new_v4(),
-            proposal_id: _proposal.id,
+            proposal_id: proposal.id,
             success: true,
-            dry_run: _dry_run,
-            executed_statements: _proposal.migration
-                .as_ref()
-                .map(|m| vec![m.up_sql.clone()])
-                .unwrap_or_default(),
+            dry_run: options.dry_run,
+            executed_statements: Vec::new(),
             error: None,
-            duration_ms: 100,
+            forensics: None,
+            total_statements: statements.len(),
+            resumed_from: options.start_at,
+            checkpoint: options.start_at,
+            chunks: plan_chunks(&statements[..end], options.chunk_size, options.start_at),
+            paused_at_gate: None,
+            cost_summary: None,
+            backup: None,
+            lock: None,
+            session_settings: BTreeMap::new(),
+            permissions: None,
+            row_counts: None,
+            template_values: BTreeMap::new(),
+            watchdog: None,
+            deep_dry_run: None,
+            migration_version: proposal.migration.as_ref().map(|m| m.version).filter(|v| *v > 0),
+            migration_checksum: proposal.migration.as_ref().map(|m| m.checksum.clone()),
+            duration_ms: 0,
             executed_at: Utc::now(),
-        })
+        };
+
+        if options.dry_run {
+            let client = pool.get().await?;
+            result.permissions = Some(preflight::check(&client, &statements, options.start_at).await?);
+            result.executed_statements = statements[options.start_at..end].to_vec();
+            result.cost_summary = Some(explain::estimate(pool, &statements, options.start_at).await?);
+            if options.deep {
+                let deep = DryRunner::deep(pool, &statements[options.start_at..end], options.start_at, &options.settings).await?;
+                if let Some(failure) = deep.first_failure() {
+                    result.success = false;
+                    result.error = Some(format!(
+                        "Statement {} would fail: {}",
+                        failure.statement_index + 1,
+                        failure.error.as_deref().unwrap_or("unknown error")
+                    ));
+                }
+                result.deep_dry_run = Some(deep);
+            }
+            result.duration_ms = started.elapsed().as_millis() as u64;
+            return Ok(result);
+        }
+
+        // Gates only hold real runs back; a dry run shows the whole migration
+        let gate = statements
+            .iter()
+            .enumerate()
+            .take(end)
+            .skip(options.start_at + 1)
+            .find_map(|(index, statement)| reorder::gate_label(statement).map(|label| (index, label)));
+        if let Some((index, label)) = gate {
+            result.chunks = plan_chunks(&statements[..index], options.chunk_size, options.start_at);
+            result.paused_at_gate = Some(label.to_string());
+        }
+
+        result.session_settings = options.settings.clone();
+
+        // Fail before the first statement rather than halfway through
+        let mut client = LockedSession::new(pool.get().await?);
+        let permissions = preflight::check(&client, &statements, options.start_at).await?;
+        if !permissions.passed() {
+            return Err(permissions.failure());
+        }
+        result.permissions = Some(permissions);
+
+        let pid = match &self.monitor {
+            Some(monitor) => {
+                let pid = client
+                    .query_one("SELECT pg_backend_pid()", &[])
+                    .await
+                    .ok()
+                    .map(|row| row.get::<_, i32>(0));
+                monitor.begin(proposal.id, statements.len(), options.start_at, pid).await;
+                pid
+            }
+            None => None,
+        };
+        let tracking = self.monitor.as_ref().map(|monitor| (monitor, proposal.id));
+
+        // Another instance may be migrating the same database; nothing is
+        // journaled until this run has the database to itself
+        let wait = options.lock_wait.unwrap_or(advisory_lock::DEFAULT_WAIT);
+        let started_run = match client.acquire(wait, tracking).await {
+            Ok(lock) => {
+                result.lock = Some(lock);
+                match &self.journal {
+                    Some(journal) => journal.begin(pool, proposal, result.id, options.start_at, statements.len()).await,
+                    None => Ok(()),
+                }
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = started_run {
+            if result.lock.is_some() {
+                client.release().await;
+            }
+            if let Some(monitor) = &self.monitor {
+                monitor.finish(proposal.id).await;
+            }
+            return Err(e);
+        }
+
+        let tables = affected_tables(proposal);
+        let counted_tables = match row_counts::expects_stable_counts(&proposal.changes) && !tables.is_empty() {
+            true => match row_counts::count(&client, &tables).await {
+                Ok(before) => Some(before),
+                Err(e) => {
+                    warn!("Row counts before executing proposal {} failed; skipping verification: {}", proposal.id, e);
+                    None
+                }
+            },
+            false => None,
+        };
+
+        let poller = match (&self.monitor, pid) {
+            (Some(monitor), Some(pid)) => Some(progress::spawn_poller(monitor.clone(), pool.clone(), proposal.id, pid)),
+            _ => None,
+        };
+
+        let limits = match &options.watchdog {
+            Some(policy) => policy.limits(
+                statements.len(),
+                estimated_duration_secs(proposal),
+                proposal.cost_summary.as_ref(),
+                &options.settings,
+            ),
+            None => Vec::new(),
+        };
+
+        let mut failure = None;
+        for chunk in result.chunks.iter_mut() {
+            let chunk_started = Instant::now();
+            let outcome = run_chunk(&mut client, &statements, chunk, &options.settings, &limits, tracking).await;
+            chunk.duration_ms = chunk_started.elapsed().as_millis() as u64;
+
+            match outcome {
+                Ok(()) => {
+                    let range = chunk.first_statement..chunk.first_statement + chunk.statement_count;
+                    result.executed_statements.extend_from_slice(&statements[range.clone()]);
+                    result.checkpoint = range.end;
+                    chunk.status = ChunkStatus::Committed;
+                    chunk.committed_at = Some(Utc::now());
+                    if let Some(journal) = &self.journal {
+                        journal.checkpoint(pool, result.id, range.end).await;
+                    }
+                }
+                Err(e) => {
+                    chunk.status = ChunkStatus::Failed;
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+
+        if let (Some(before), None) = (counted_tables, &failure) {
+            match row_counts::count(&client, &tables).await {
+                Ok(after) => {
+                    let verification = row_counts::verify(&tables, before, after);
+                    for anomaly in &verification.anomalies {
+                        warn!("Unexpected row count change executing proposal {}: {}", proposal.id, anomaly);
+                    }
+                    result.row_counts = Some(verification);
+                }
+                Err(e) => warn!("Row counts after executing proposal {} failed: {}", proposal.id, e),
+            }
+        }
+
+        client.release().await;
+        if let Some(poller) = poller {
+            poller.abort();
+        }
+        if let Some(monitor) = &self.monitor {
+            monitor.finish(proposal.id).await;
+        }
+        if let Some(journal) = &self.journal {
+            journal.finish(result.id, failure.is_none()).await;
+        }
+
+        if let Some((index, statement, e)) = failure {
+            let forensics = forensics::capture(pool, index, &statement, &e, &tables).await;
+
+            result.success = false;
+            result.paused_at_gate = None;
+            result.error = Some(format!("Statement {} failed: {}", index + 1, forensics.error.message));
+            result.forensics = Some(forensics);
+
+            if let Some(limit) = limits.get(index).filter(|_| e.code() == Some(&SqlState::QUERY_CANCELED)) {
+                let kill = WatchdogKill { statement_index: index, statement, limit: *limit };
+                warn!("Watchdog stopped proposal {}: {}", proposal.id, kill.describe());
+                result.error = Some(format!("{}; its chunk was rolled back", kill.describe()));
+                result.watchdog = Some(kill);
+            }
+        }
+
+        result.duration_ms = started.elapsed().as_millis() as u64;
+        Ok(result)
     }
 
-    /// Rollback a previously executed migration
-    pub async fn rollback(
-        &self,
-        _proposal: &SchemaProposal,
-    ) -> Result<ExecutionResult, AppError> {
-        Ok(ExecutionResult {
-            id: Uuid::new_v4(),
-            proposal_id: _proposal.id,
-            success: true,
-            dry_run: false,
-            executed_statements: _proposal.migration
-                .as_ref()
-                .map(|m| vec![m.down_sql.clone()])
-                .unwrap_or_default(),
-            error: None,
-            duration_ms: 50,
-            executed_at: Utc::now(),
-        })
+    /// Roll back an executed migration by running its down SQL the way
+    /// `execute` runs the up SQL
+    pub async fn rollback(&self, pool: &Pool, proposal: &SchemaProposal) -> Result<ExecutionResult, AppError> {
+        let migration = proposal
+            .migration
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest("The proposal has no migration to roll back".to_string()))?;
+        let mut inverse = proposal.clone();
+        inverse.migration = Some(MigrationArtifacts {
+            up_sql: migration.down_sql.clone(),
+            down_sql: migration.up_sql.clone(),
+            ..migration.clone()
+        });
+        self.execute(pool, &inverse, ExecutionOptions::default()).await
     }
 
     /// Generate migration SQL from a proposal
     pub fn generate_migration(&self, proposal: &SchemaProposal) -> MigrationArtifacts {
-        use crate::pipeline::types::SchemaChange;
+        use crate::pipeline::types::{ReindexTarget, SchemaChange};
         
         let mut up_statements = Vec::new();
         let mut down_statements = Vec::new();
 
         for change in &proposal.changes {
             match change {
-                SchemaChange::CreateTable { table_name, columns } => {
+                SchemaChange::CreateSchema { schema_name, owner } => {
+                    match owner {
+                        Some(owner) => up_statements.push(format!("CREATE SCHEMA {} AUTHORIZATION {};", schema_name, owner)),
+                        None => up_statements.push(format!("CREATE SCHEMA {};", schema_name)),
+                    }
+                    down_statements.push(format!("DROP SCHEMA IF EXISTS {};", schema_name));
+                }
+                SchemaChange::DropSchema { schema_name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP SCHEMA {}{};", schema_name, cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP SCHEMA {}", schema_name));
+                }
+                SchemaChange::RenameSchema { old_name, new_name } => {
+                    up_statements.push(format!("ALTER SCHEMA {} RENAME TO {};", old_name, new_name));
+                    down_statements.push(format!("ALTER SCHEMA {} RENAME TO {};", new_name, old_name));
+                }
+                SchemaChange::CreateTable { table_name, columns, partition_by } => {
                     let cols: Vec<String> = columns.iter().map(|c| {
                         let mut def = format!("{} {}", c.name, c.data_type);
+                        if let Some(collation) = &c.collation {
+                            def.push_str(&format!(" COLLATE \"{}\"", collation));
+                        }
                         if !c.nullable {
                             def.push_str(" NOT NULL");
                         }
@@ -84,7 +372,8 @@ impl Orchestrator {
                         }
                         def
                     }).collect();
-                    up_statements.push(format!("CREATE TABLE {} (\n  {}\n);", table_name, cols.join(",\n  ")));
+                    let partitioning = partition_by.as_ref().map(|key| format!(" {}", key.to_sql())).unwrap_or_default();
+                    up_statements.push(format!("CREATE TABLE {} (\n  {}\n){};", table_name, cols.join(",\n  "), partitioning));
                     down_statements.push(format!("DROP TABLE IF EXISTS {};", table_name));
                 }
                 SchemaChange::DropTable { table_name } => {
@@ -93,6 +382,9 @@ impl Orchestrator {
                 }
                 SchemaChange::AddColumn { table_name, column } => {
                     let mut def = format!("{} {}", column.name, column.data_type);
+                    if let Some(collation) = &column.collation {
+                        def.push_str(&format!(" COLLATE \"{}\"", collation));
+                    }
                     if !column.nullable {
                         def.push_str(" NOT NULL");
                     }
@@ -106,6 +398,31 @@ impl Orchestrator {
                     up_statements.push(format!("ALTER TABLE {} DROP COLUMN {};", table_name, column_name));
                     down_statements.push(format!("-- Cannot auto-rollback DROP COLUMN {}.{}", table_name, column_name));
                 }
+                SchemaChange::AlterColumn { table_name, column_name, new_type, new_nullable, new_default, using } => {
+                    let alter = format!("ALTER TABLE {} ALTER COLUMN {}", table_name, column_name);
+                    if let Some(new_type) = new_type {
+                        match using {
+                            Some(using) => up_statements.push(format!("{} TYPE {} USING {};", alter, new_type, using)),
+                            None => up_statements.push(format!("{} TYPE {};", alter, new_type)),
+                        }
+                        down_statements.push(format!("-- Cannot auto-rollback type change of {}.{}", table_name, column_name));
+                    }
+                    match new_nullable {
+                        Some(false) => {
+                            up_statements.push(format!("{} SET NOT NULL;", alter));
+                            down_statements.push(format!("{} DROP NOT NULL;", alter));
+                        }
+                        Some(true) => {
+                            up_statements.push(format!("{} DROP NOT NULL;", alter));
+                            down_statements.push(format!("{} SET NOT NULL;", alter));
+                        }
+                        None => {}
+                    }
+                    if let Some(default) = new_default {
+                        up_statements.push(format!("{} SET DEFAULT {};", alter, default));
+                        down_statements.push(format!("-- Cannot auto-rollback default of {}.{}", table_name, column_name));
+                    }
+                }
                 SchemaChange::RenameTable { old_name, new_name } => {
                     up_statements.push(format!("ALTER TABLE {} RENAME TO {};", old_name, new_name));
                     down_statements.push(format!("ALTER TABLE {} RENAME TO {};", new_name, old_name));
@@ -114,9 +431,10 @@ impl Orchestrator {
                     up_statements.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {};", table_name, old_name, new_name));
                     down_statements.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {};", table_name, new_name, old_name));
                 }
-                SchemaChange::AddIndex { table_name, index_name, columns, unique } => {
+                SchemaChange::AddIndex { table_name, index_name, columns, unique, concurrently } => {
                     let unique_str = if *unique { "UNIQUE " } else { "" };
-                    up_statements.push(format!("CREATE {}INDEX {} ON {} ({});", unique_str, index_name, table_name, columns.join(", ")));
+                    let concurrently_str = if *concurrently { "CONCURRENTLY " } else { "" };
+                    up_statements.push(format!("CREATE {}INDEX {}{} ON {} ({});", unique_str, concurrently_str, index_name, table_name, columns.join(", ")));
                     down_statements.push(format!("DROP INDEX IF EXISTS {};", index_name));
                 }
                 SchemaChange::DropIndex { index_name } => {
@@ -134,16 +452,558 @@ impl Orchestrator {
                     up_statements.push(format!("ALTER TABLE {} DROP CONSTRAINT {};", table_name, constraint_name));
                     down_statements.push(format!("-- Cannot auto-rollback DROP CONSTRAINT {}.{}", table_name, constraint_name));
                 }
+                SchemaChange::AddCheck { table_name, constraint_name, expression, not_valid } => {
+                    let not_valid_str = if *not_valid { " NOT VALID" } else { "" };
+                    up_statements.push(format!(
+                        "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({}){};",
+                        table_name, constraint_name, expression, not_valid_str
+                    ));
+                    down_statements.push(format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};", table_name, constraint_name));
+                }
+                SchemaChange::ValidateConstraint { table_name, constraint_name } => {
+                    up_statements.push(format!("ALTER TABLE {} VALIDATE CONSTRAINT {};", table_name, constraint_name));
+                    down_statements.push(format!("-- Nothing to roll back for VALIDATE CONSTRAINT {}", constraint_name));
+                }
+                SchemaChange::DropConstraint { table_name, constraint_name } => {
+                    up_statements.push(format!("ALTER TABLE {} DROP CONSTRAINT {};", table_name, constraint_name));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP CONSTRAINT {}.{}", table_name, constraint_name));
+                }
+                SchemaChange::Backfill { table_name, column_name, value, batch_size, batches } => {
+                    // One statement per batch so each commits on its own and holds row locks briefly
+                    for batch in 1..=*batches {
+                        up_statements.push(format!(
+                            "{} {}/{}\nUPDATE {table} SET {column} = {} WHERE ctid = ANY (ARRAY(SELECT ctid FROM {table} WHERE {column} IS NULL LIMIT {}));",
+                            BACKFILL_BATCH_COMMENT,
+                            batch,
+                            batches,
+                            value,
+                            batch_size,
+                            table = table_name,
+                            column = column_name
+                        ));
+                    }
+                    down_statements.push(format!("-- Cannot auto-rollback backfill of {}.{}", table_name, column_name));
+                }
+                SchemaChange::Reindex { target, name, concurrently } => {
+                    let target_str = match target {
+                        ReindexTarget::Index => "INDEX",
+                        ReindexTarget::Table => "TABLE",
+                    };
+                    let concurrently_str = if *concurrently { "CONCURRENTLY " } else { "" };
+                    up_statements.push(format!("REINDEX {} {}{};", target_str, concurrently_str, name));
+                    down_statements.push(format!("-- Nothing to roll back for REINDEX {} {}", target_str, name));
+                }
+                SchemaChange::Vacuum { table_name, full, analyze } => {
+                    let options: Vec<&str> = [(*full, "FULL"), (*analyze, "ANALYZE")]
+                        .into_iter()
+                        .filter_map(|(on, option)| on.then_some(option))
+                        .collect();
+                    if options.is_empty() {
+                        up_statements.push(format!("VACUUM {};", table_name));
+                    } else {
+                        up_statements.push(format!("VACUUM ({}) {};", options.join(", "), table_name));
+                    }
+                    down_statements.push(format!("-- Nothing to roll back for VACUUM {}", table_name));
+                }
+                SchemaChange::Analyze { table_name } => {
+                    up_statements.push(format!("ANALYZE {};", table_name));
+                    down_statements.push(format!("-- Nothing to roll back for ANALYZE {}", table_name));
+                }
+                SchemaChange::CreateExtension { name, schema, version } => {
+                    let mut create = format!("CREATE EXTENSION IF NOT EXISTS {}", SqlBuilder::quote_ident(name));
+                    if let Some(schema) = schema {
+                        create.push_str(&format!(" SCHEMA {}", schema));
+                    }
+                    if let Some(version) = version {
+                        create.push_str(&format!(" VERSION {}", SqlBuilder::quote_literal(version)));
+                    }
+                    up_statements.push(format!("{};", create));
+                    down_statements.push(format!("DROP EXTENSION IF EXISTS {};", SqlBuilder::quote_ident(name)));
+                }
+                SchemaChange::DropExtension { name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP EXTENSION {}{};", SqlBuilder::quote_ident(name), cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP EXTENSION {}", name));
+                }
+                SchemaChange::AlterExtensionVersion { name, version } => {
+                    up_statements.push(format!(
+                        "ALTER EXTENSION {} UPDATE TO {};",
+                        SqlBuilder::quote_ident(name),
+                        SqlBuilder::quote_literal(version)
+                    ));
+                    // Extensions rarely ship downgrade scripts
+                    down_statements.push(format!("-- Cannot auto-rollback update of extension {}", name));
+                }
+                SchemaChange::CreatePolicy { table_name, policy_name, restrictive, command, roles, using, with_check } => {
+                    let mut create = format!(
+                        "CREATE POLICY {} ON {} AS {} FOR {}",
+                        policy_name,
+                        table_name,
+                        if *restrictive { "RESTRICTIVE" } else { "PERMISSIVE" },
+                        command.as_deref().unwrap_or("ALL").to_uppercase()
+                    );
+                    if !roles.is_empty() {
+                        create.push_str(&format!(" TO {}", roles.join(", ")));
+                    }
+                    if let Some(using) = using {
+                        create.push_str(&format!(" USING ({})", using));
+                    }
+                    if let Some(with_check) = with_check {
+                        create.push_str(&format!(" WITH CHECK ({})", with_check));
+                    }
+                    up_statements.push(format!("{};", create));
+                    down_statements.push(format!("DROP POLICY IF EXISTS {} ON {};", policy_name, table_name));
+                }
+                SchemaChange::AlterPolicy { table_name, policy_name, roles, using, with_check } => {
+                    let mut alter = format!("ALTER POLICY {} ON {}", policy_name, table_name);
+                    if let Some(roles) = roles.as_ref().filter(|r| !r.is_empty()) {
+                        alter.push_str(&format!(" TO {}", roles.join(", ")));
+                    }
+                    if let Some(using) = using {
+                        alter.push_str(&format!(" USING ({})", using));
+                    }
+                    if let Some(with_check) = with_check {
+                        alter.push_str(&format!(" WITH CHECK ({})", with_check));
+                    }
+                    up_statements.push(format!("{};", alter));
+                    down_statements.push(format!("-- Cannot auto-rollback ALTER POLICY {} ON {}", policy_name, table_name));
+                }
+                SchemaChange::DropPolicy { table_name, policy_name } => {
+                    up_statements.push(format!("DROP POLICY {} ON {};", policy_name, table_name));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP POLICY {} ON {}", policy_name, table_name));
+                }
+                SchemaChange::SetRowSecurity { table_name, enabled, forced } => {
+                    let toggle = |on: bool| if on { "ENABLE" } else { "DISABLE" };
+                    let force = |on: bool| if on { "FORCE" } else { "NO FORCE" };
+                    up_statements.push(format!("ALTER TABLE {} {} ROW LEVEL SECURITY;", table_name, toggle(*enabled)));
+                    down_statements.push(format!("ALTER TABLE {} {} ROW LEVEL SECURITY;", table_name, toggle(!*enabled)));
+                    if let Some(forced) = forced {
+                        up_statements.push(format!("ALTER TABLE {} {} ROW LEVEL SECURITY;", table_name, force(*forced)));
+                        down_statements.push(format!("ALTER TABLE {} {} ROW LEVEL SECURITY;", table_name, force(!*forced)));
+                    }
+                }
+                SchemaChange::CreateDomain { domain_name, base_type, not_null, default_value, checks } => {
+                    let mut create = format!("CREATE DOMAIN {} AS {}", domain_name, base_type);
+                    if let Some(default) = default_value {
+                        create.push_str(&format!(" DEFAULT {}", default));
+                    }
+                    if *not_null {
+                        create.push_str(" NOT NULL");
+                    }
+                    for check in checks {
+                        create.push_str(&format!(" CONSTRAINT {} CHECK ({})", check.name, check.expression));
+                    }
+                    up_statements.push(format!("{};", create));
+                    down_statements.push(format!("DROP DOMAIN IF EXISTS {};", domain_name));
+                }
+                SchemaChange::AlterDomain { domain_name, set_default, drop_default, not_null, add_checks, drop_checks } => {
+                    if let Some(default) = set_default {
+                        up_statements.push(format!("ALTER DOMAIN {} SET DEFAULT {};", domain_name, default));
+                    } else if *drop_default {
+                        up_statements.push(format!("ALTER DOMAIN {} DROP DEFAULT;", domain_name));
+                    }
+                    match not_null {
+                        Some(true) => {
+                            up_statements.push(format!("ALTER DOMAIN {} SET NOT NULL;", domain_name));
+                            down_statements.push(format!("ALTER DOMAIN {} DROP NOT NULL;", domain_name));
+                        }
+                        Some(false) => {
+                            up_statements.push(format!("ALTER DOMAIN {} DROP NOT NULL;", domain_name));
+                            down_statements.push(format!("ALTER DOMAIN {} SET NOT NULL;", domain_name));
+                        }
+                        None => {}
+                    }
+                    for name in drop_checks {
+                        up_statements.push(format!("ALTER DOMAIN {} DROP CONSTRAINT {};", domain_name, name));
+                        down_statements.push(format!("-- Cannot auto-rollback DROP CONSTRAINT {} on domain {}", name, domain_name));
+                    }
+                    for check in add_checks {
+                        up_statements.push(format!(
+                            "ALTER DOMAIN {} ADD CONSTRAINT {} CHECK ({});",
+                            domain_name, check.name, check.expression
+                        ));
+                        down_statements.push(format!("ALTER DOMAIN {} DROP CONSTRAINT IF EXISTS {};", domain_name, check.name));
+                    }
+                    if set_default.is_some() || *drop_default {
+                        down_statements.push(format!("-- Cannot auto-rollback default change of domain {}", domain_name));
+                    }
+                }
+                SchemaChange::DropDomain { domain_name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP DOMAIN {}{};", domain_name, cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP DOMAIN {}", domain_name));
+                }
+                SchemaChange::CreateCompositeType { type_name, attributes } => {
+                    let attributes: Vec<String> = attributes.iter().map(|a| format!("{} {}", a.name, a.data_type)).collect();
+                    up_statements.push(format!("CREATE TYPE {} AS ({});", type_name, attributes.join(", ")));
+                    down_statements.push(format!("DROP TYPE IF EXISTS {};", type_name));
+                }
+                SchemaChange::AlterCompositeType { type_name, add_attributes, drop_attributes, alter_attributes } => {
+                    let mut actions = Vec::new();
+                    for name in drop_attributes {
+                        actions.push(format!("DROP ATTRIBUTE {}", name));
+                        down_statements.push(format!("-- Cannot auto-rollback DROP ATTRIBUTE {} of type {}", name, type_name));
+                    }
+                    for attribute in alter_attributes {
+                        actions.push(format!("ALTER ATTRIBUTE {} TYPE {}", attribute.name, attribute.data_type));
+                        down_statements.push(format!(
+                            "-- Cannot auto-rollback ALTER ATTRIBUTE {} of type {}",
+                            attribute.name, type_name
+                        ));
+                    }
+                    for attribute in add_attributes {
+                        actions.push(format!("ADD ATTRIBUTE {} {}", attribute.name, attribute.data_type));
+                        down_statements.push(format!("ALTER TYPE {} DROP ATTRIBUTE IF EXISTS {};", type_name, attribute.name));
+                    }
+                    if !actions.is_empty() {
+                        up_statements.push(format!("ALTER TYPE {} {};", type_name, actions.join(", ")));
+                    }
+                }
+                SchemaChange::DropCompositeType { type_name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP TYPE {}{};", type_name, cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP TYPE {}", type_name));
+                }
+                SchemaChange::CreateView { view_name, query } => {
+                    up_statements.push(format!("CREATE VIEW {} AS {};", view_name, view_query(query)));
+                    down_statements.push(format!("DROP VIEW IF EXISTS {};", view_name));
+                }
+                SchemaChange::AlterView { view_name, query } => {
+                    up_statements.push(format!("CREATE OR REPLACE VIEW {} AS {};", view_name, view_query(query)));
+                    down_statements.push(format!("-- Cannot auto-rollback redefinition of view {}", view_name));
+                }
+                SchemaChange::DropView { view_name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP VIEW {}{};", view_name, cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP VIEW {}", view_name));
+                }
+                SchemaChange::CreateMaterializedView { view_name, query, with_no_data } => {
+                    let data_str = if *with_no_data { " WITH NO DATA" } else { "" };
+                    up_statements.push(format!("CREATE MATERIALIZED VIEW {} AS {}{};", view_name, view_query(query), data_str));
+                    down_statements.push(format!("DROP MATERIALIZED VIEW IF EXISTS {};", view_name));
+                }
+                SchemaChange::DropMaterializedView { view_name, cascade } => {
+                    let cascade_str = if *cascade { " CASCADE" } else { "" };
+                    up_statements.push(format!("DROP MATERIALIZED VIEW {}{};", view_name, cascade_str));
+                    down_statements.push(format!("-- Cannot auto-rollback DROP MATERIALIZED VIEW {}", view_name));
+                }
+                SchemaChange::RefreshMaterializedView { view_name, concurrently } => {
+                    let concurrently_str = if *concurrently { " CONCURRENTLY" } else { "" };
+                    up_statements.push(format!("REFRESH MATERIALIZED VIEW{} {};", concurrently_str, view_name));
+                }
+                SchemaChange::CreatePartition { table_name, parent_table, bound } => {
+                    up_statements.push(format!("CREATE TABLE {} PARTITION OF {} {};", table_name, parent_table, bound.to_sql()));
+                    down_statements.push(format!("DROP TABLE IF EXISTS {};", table_name));
+                }
+                SchemaChange::AttachPartition { parent_table, partition_name, bound } => {
+                    up_statements.push(format!(
+                        "ALTER TABLE {} ATTACH PARTITION {} {};",
+                        parent_table, partition_name, bound.to_sql()
+                    ));
+                    down_statements.push(format!("ALTER TABLE {} DETACH PARTITION {};", parent_table, partition_name));
+                }
+                SchemaChange::DetachPartition { parent_table, partition_name, concurrently } => {
+                    let concurrently_str = if *concurrently { " CONCURRENTLY" } else { "" };
+                    up_statements.push(format!(
+                        "ALTER TABLE {} DETACH PARTITION {}{};",
+                        parent_table, partition_name, concurrently_str
+                    ));
+                    down_statements.push(format!(
+                        "-- Cannot auto-rollback DETACH PARTITION {} (re-attach it with its bound)",
+                        partition_name
+                    ));
+                }
+                SchemaChange::ReorderColumns { table_name, plan: Some(plan), .. } => {
+                    up_statements.extend(reorder::statements(table_name, plan));
+                    down_statements.push(format!(
+                        "-- Cannot auto-rollback column reorder of {}; the original table is kept as {}__old until the last gate",
+                        table_name,
+                        table_name
+                    ));
+                }
+                SchemaChange::ReorderColumns { table_name, plan: None, .. } => {
+                    up_statements.push(format!("-- Column reorder of {} was never planned; add it to the proposal again", table_name));
+                    down_statements.push(format!("-- Nothing to roll back for the unplanned reorder of {}", table_name));
+                }
+                SchemaChange::SetCustomField { table_name, column_name, field, value } => {
+                    // Recorded in the data dictionary once the proposal executes
+                    let object = match column_name {
+                        Some(column) => format!("{}.{}", table_name, column),
+                        None => table_name.clone(),
+                    };
+                    let note = match value {
+                        Some(value) => format!(
+                            "-- governance: set custom field {} on {} to {}",
+                            field,
+                            object,
+                            SqlBuilder::quote_literal(value)
+                        ),
+                        None => format!("-- governance: clear custom field {} on {}", field, object),
+                    };
+                    // A line break would let the rest of the note run as SQL
+                    up_statements.push(note.replace(['\r', '\n'], " "));
+                    down_statements.push(
+                        format!("-- governance: restore custom field {} on {} by reverting", field, object)
+                            .replace(['\r', '\n'], " "),
+                    );
+                }
+                SchemaChange::AddTag { table_name, column_name, tag }
+                | SchemaChange::RemoveTag { table_name, column_name, tag } => {
+                    // Recorded in the data dictionary once the proposal executes
+                    let object = match column_name {
+                        Some(column) => format!("{}.{}", table_name, column),
+                        None => table_name.clone(),
+                    };
+                    let (verb, undo) = if matches!(change, SchemaChange::AddTag { .. }) {
+                        ("add", "remove")
+                    } else {
+                        ("remove", "add")
+                    };
+                    up_statements.push(format!("-- governance: {} tag {} on {}", verb, tag, object).replace(['\r', '\n'], " "));
+                    down_statements.push(
+                        format!("-- governance: {} tag {} on {}", undo, tag, object).replace(['\r', '\n'], " "),
+                    );
+                }
+                SchemaChange::SetClassification { table_name, column_name, level } => {
+                    // Recorded in the data dictionary once the proposal executes
+                    let note = match level {
+                        Some(level) => format!("-- governance: classify {}.{} as {:?}", table_name, column_name, level),
+                        None => format!("-- governance: clear the classification of {}.{}", table_name, column_name),
+                    };
+                    up_statements.push(note.replace(['\r', '\n'], " "));
+                    down_statements.push(
+                        format!("-- governance: restore the classification of {}.{} by reverting", table_name, column_name)
+                            .replace(['\r', '\n'], " "),
+                    );
+                }
                 _ => {}
             }
         }
 
-        MigrationArtifacts {
-            up_sql: up_statements.join("\n\n"),
-            down_sql: down_statements.into_iter().rev().collect::<Vec<_>>().join("\n\n"),
-            generated_at: Utc::now(),
+        MigrationArtifacts::new(
+            up_statements.join("\n\n"),
+            down_statements.into_iter().rev().collect::<Vec<_>>().join("\n\n"),
+        )
+    }
+}
+
+/// A view query without the trailing semicolon `pg_get_viewdef` prints, or
+/// blank lines
+fn view_query(query: &str) -> String {
+    let query = query.trim().trim_end_matches(';');
+    query.lines().filter(|l| !l.trim().is_empty()).collect::<Vec<_>>().join("\n")
+}
+
+/// Steps of generated down SQL that cannot be undone automatically
+pub fn irreversible_steps(down_sql: &str) -> Vec<String> {
+    down_sql
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("-- Cannot auto-rollback "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split migration SQL into statements at semicolons outside string
+/// literals, dollar-quoted bodies, quoted identifiers and comments, so blank
+/// lines inside a statement do not break it. Comment lines directly above a
+/// statement stay with it (batch and gate markers); comment blocks set off by
+/// a blank line are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = sql[i..].chars().next() {
+        let rest = &sql[i..];
+        let in_word = prev.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '$');
+        let len = if let Some(len) = string_literal_len(rest, in_word) {
+            len
+        } else if rest.starts_with("--") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            body.find("*/").map_or(rest.len(), |end| end + 4)
+        } else if c == '"' || c == '`' {
+            rest[1..].find(c).map_or(rest.len(), |end| end + 2)
+        } else if c == ';' {
+            // A comment trailing the semicolon on its line belongs to this statement
+            let line_end = rest.find('\n').unwrap_or(rest.len());
+            let tail = rest[1..line_end].trim_start();
+            let end = match tail.is_empty() || tail.starts_with("--") {
+                true => i + line_end,
+                false => i + 1,
+            };
+            statements.extend(statement_text(&sql[start..end]));
+            start = end;
+            i = end;
+            prev = Some(';');
+            continue;
+        } else {
+            c.len_utf8()
+        };
+        prev = sql[i..i + len].chars().last();
+        i += len;
+    }
+    statements.extend(statement_text(&sql[start..]));
+    statements
+}
+
+/// A statement without the comment blocks set off above it, or None if
+/// nothing but comments is left
+fn statement_text(segment: &str) -> Option<String> {
+    let mut keep = 0;
+    let mut offset = 0;
+    for line in segment.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            keep = offset + line.len();
+        } else if !trimmed.starts_with("--") {
+            break;
+        }
+        offset += line.len();
+    }
+    let statement = segment[keep..].trim();
+    match statement.lines().all(|l| l.trim().is_empty() || l.trim_start().starts_with("--")) {
+        true => None,
+        false => Some(statement.to_string()),
+    }
+}
+
+/// Marks each statement of a batched backfill
+const BACKFILL_BATCH_COMMENT: &str = "-- backfill batch";
+
+/// Statements PostgreSQL refuses to run inside a transaction block, and those
+/// that must commit on their own to keep their locks short: backfill batches,
+/// and NOT VALID constraints and their validation
+pub(crate) fn requires_autocommit(statement: &str) -> bool {
+    let upper = statement.to_uppercase();
+    upper.starts_with("VACUUM")
+        || upper.contains(" CONCURRENTLY ")
+        || upper.ends_with(" CONCURRENTLY;")
+        || statement.starts_with(BACKFILL_BATCH_COMMENT)
+        || statement.starts_with(reorder::COPY_BATCH_COMMENT)
+        || upper.ends_with(" NOT VALID;")
+        || upper.contains(" VALIDATE CONSTRAINT ")
+}
+
+/// Group statements into chunks, starting at the first one not yet committed
+pub fn plan_chunks(statements: &[String], chunk_size: Option<usize>, start_at: usize) -> Vec<ChunkResult> {
+    let limit = chunk_size.filter(|n| *n > 0).unwrap_or(usize::MAX);
+    let mut chunks: Vec<ChunkResult> = Vec::new();
+
+    for (index, statement) in statements.iter().enumerate().skip(start_at) {
+        let transactional = !requires_autocommit(statement);
+        match chunks.last_mut() {
+            Some(chunk) if transactional && chunk.transactional && chunk.statement_count < limit => {
+                chunk.statement_count += 1;
+            }
+            _ => chunks.push(ChunkResult {
+                index: chunks.len(),
+                first_statement: index,
+                statement_count: 1,
+                transactional,
+                status: ChunkStatus::Planned,
+                duration_ms: 0,
+                committed_at: None,
+            }),
         }
     }
+
+    chunks
+}
+
+/// Run one chunk; on failure, report the absolute statement index and text
+async fn run_chunk(
+    client: &mut Object,
+    statements: &[String],
+    chunk: &ChunkResult,
+    settings: &BTreeMap<String, String>,
+    limits: &[StatementLimit],
+    tracking: Option<(&ExecutionMonitor, Uuid)>,
+) -> Result<(), (usize, String, tokio_postgres::Error)> {
+    let range = chunk.first_statement..chunk.first_statement + chunk.statement_count;
+    let started = |index: usize| async move {
+        if let Some((monitor, proposal_id)) = tracking {
+            monitor.statement_started(proposal_id, index, &statements[index]).await;
+        }
+    };
+
+    if !chunk.transactional {
+        // Without a transaction to scope them, settings last for the session
+        // and are reset before the connection returns to the pool
+        if !settings.is_empty() {
+            let set = execution_policy::set_statements(settings, false);
+            client.batch_execute(&set).await.map_err(|e| (range.start, set, e))?;
+        }
+        let mut outcome = Ok(());
+        for index in range {
+            started(index).await;
+            if let Some(limit) = limits.get(index) {
+                let set = watchdog::timeout_statement(limit.limit_ms, false);
+                if let Err(e) = client.batch_execute(&set).await {
+                    outcome = Err((index, set, e));
+                    break;
+                }
+            }
+            if let Err(e) = client.batch_execute(&statements[index]).await {
+                outcome = Err((index, statements[index].clone(), e));
+                break;
+            }
+        }
+        if !settings.is_empty() {
+            if let Err(e) = client.batch_execute(&execution_policy::reset_statements(settings)).await {
+                warn!("Failed to reset execution settings: {}", e);
+            }
+        }
+        if !limits.is_empty() && !settings.contains_key("statement_timeout") {
+            if let Err(e) = client.batch_execute("RESET statement_timeout;").await {
+                warn!("Failed to reset the watchdog's statement timeout: {}", e);
+            }
+        }
+        return outcome;
+    }
+
+    let tx = client
+        .transaction()
+        .await
+        .map_err(|e| (range.start, "BEGIN".to_string(), e))?;
+    if !settings.is_empty() {
+        let set = execution_policy::set_statements(settings, true);
+        tx.batch_execute(&set).await.map_err(|e| (range.start, set, e))?;
+    }
+    for index in range.clone() {
+        started(index).await;
+        if let Some(limit) = limits.get(index) {
+            let set = watchdog::timeout_statement(limit.limit_ms, true);
+            tx.batch_execute(&set).await.map_err(|e| (index, set, e))?;
+        }
+        // Dropping the transaction on error rolls the chunk back
+        tx.batch_execute(&statements[index])
+            .await
+            .map_err(|e| (index, statements[index].clone(), e))?;
+    }
+    tx.commit().await.map_err(|e| (range.end, "COMMIT".to_string(), e))
+}
+
+/// Estimated duration of the whole migration, from the proposal's risk
+/// analysis or a fresh one
+fn estimated_duration_secs(proposal: &SchemaProposal) -> u64 {
+    proposal
+        .risk_analysis
+        .as_ref()
+        .map(|a| a.estimated_duration_secs)
+        .or_else(|| RiskEngine::new().analyze(proposal, None).ok().map(|a| a.estimated_duration_secs))
+        .unwrap_or(0)
+}
+
+/// Tables the proposal touches, from its risk analysis or a fresh one
+fn affected_tables(proposal: &SchemaProposal) -> Vec<String> {
+    proposal
+        .risk_analysis
+        .as_ref()
+        .map(|a| a.affected_tables.clone())
+        .or_else(|| RiskEngine::new().analyze(proposal, None).ok().map(|a| a.affected_tables))
+        .unwrap_or_default()
 }
 
 impl Default for Orchestrator {
@@ -152,6 +1012,53 @@ impl Default for Orchestrator {
     }
 }
 
+/// How a migration should be run
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    pub dry_run: bool,
+    /// On a dry run, also execute the statements against the real data and
+    /// roll them back, reporting what the data would make fail
+    pub deep: bool,
+    /// Statements per transaction (None = the whole migration in one)
+    pub chunk_size: Option<usize>,
+    /// Index of the first statement to run, for resuming after a failure
+    pub start_at: usize,
+    /// Index of the statement to stop before (None = run to the end), for
+    /// running one stage of an execution plan
+    pub stop_at: Option<usize>,
+    /// How long to wait for the database's migration lock
+    /// (None = `advisory_lock::DEFAULT_WAIT`)
+    pub lock_wait: Option<std::time::Duration>,
+    /// Server settings applied to every transaction of the run
+    pub settings: BTreeMap<String, String>,
+    /// Kill policy for statements running far past their estimate
+    pub watchdog: Option<watchdog::WatchdogPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStatus {
+    Planned,
+    Committed,
+    Failed,
+}
+
+/// One transaction's worth of statements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkResult {
+    pub index: usize,
+    /// Zero-based position of the chunk's first statement in the migration
+    pub first_statement: usize,
+    pub statement_count: usize,
+    /// False for statements that had to run in autocommit mode
+    pub transactional: bool,
+    pub status: ChunkStatus,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committed_at: Option<DateTime<Utc>>,
+}
+
 /// Result of executing a migration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -162,6 +1069,186 @@ pub struct ExecutionResult {
     pub dry_run: bool,
     pub executed_statements: Vec<String>,
     pub error: Option<String>,
+    /// Captured when a statement fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forensics: Option<FailureForensics>,
+    #[serde(default)]
+    pub total_statements: usize,
+    /// Statement the run started at (non-zero when resuming)
+    #[serde(default)]
+    pub resumed_from: usize,
+    /// Statements committed so far, counting earlier runs; a resume starts here
+    #[serde(default)]
+    pub checkpoint: usize,
+    #[serde(default)]
+    pub chunks: Vec<ChunkResult>,
+    /// Confirmation gate the run stopped before; `checkpoint` points at it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_at_gate: Option<String>,
+    /// Planner estimates, on dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_summary: Option<CostSummary>,
+    /// Backup taken before a destructive execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupReference>,
+    /// How the run came by the database's migration lock; real runs only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<LockWait>,
+    /// Server settings the run applied from the project's execution policy
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub session_settings: BTreeMap<String, String>,
+    /// Privileges the executing role was found to hold before the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<PermissionReport>,
+    /// Row counts of the affected tables around a run expected to keep them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_counts: Option<RowCountVerification>,
+    /// Values the proposal's placeholders were rendered with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub template_values: BTreeMap<String, String>,
+    /// Statement the watchdog cancelled, ending the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<WatchdogKill>,
+    /// Row counts and failures from executing a dry run against the real
+    /// data, all of it rolled back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deep_dry_run: Option<DeepDryRun>,
+    /// Stored migration version the run executed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migration_version: Option<u32>,
+    /// Checksum of the migration the run executed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migration_checksum: Option<String>,
     pub duration_ms: u64,
     pub executed_at: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "CREATE TABLE t (\n  id integer\n);\n\n-- Nothing to roll back for ANALYZE t\n\nVACUUM (ANALYZE) t;";
+        let statements = split_statements(sql);
+
+        assert_eq!(statements, vec!["CREATE TABLE t (\n  id integer\n);", "VACUUM (ANALYZE) t;"]);
+        assert!(!requires_autocommit(&statements[0]));
+        assert!(requires_autocommit(&statements[1]));
+        assert!(requires_autocommit("REINDEX INDEX CONCURRENTLY idx_t_id;"));
+    }
+
+    #[test]
+    fn test_split_statements_keeps_blank_lines_inside_statements() {
+        let sql = concat!(
+            "-- Migration header\n\n",
+            "CREATE TABLE t (\n  id integer,\n\n  note text DEFAULT 'a;\n\nb'\n);\n\n",
+            "CREATE FUNCTION f() RETURNS trigger AS $body$\nBEGIN\n  NEW.note := 'x';\n\n  RETURN NEW;\nEND\n$body$ LANGUAGE plpgsql;\n",
+            "-- backfill batch 1\nUPDATE \"odd;\n\nname\" SET note = E'it\\'s;' /* not; here */ WHERE id < 10; -- first batch\n\n",
+            "-- governance: add tag pii on t.note"
+        );
+        let statements = split_statements(sql);
+
+        assert_eq!(statements.len(), 3, "statements: {:?}", statements);
+        assert_eq!(statements[0], "CREATE TABLE t (\n  id integer,\n\n  note text DEFAULT 'a;\n\nb'\n);");
+        assert!(statements[1].starts_with("CREATE FUNCTION") && statements[1].ends_with("plpgsql;"));
+        assert!(statements[2].starts_with(BACKFILL_BATCH_COMMENT));
+        assert!(statements[2].ends_with("WHERE id < 10; -- first batch"));
+        assert!(requires_autocommit(&statements[2]));
+    }
+
+    #[test]
+    fn test_plan_chunks() {
+        let statements: Vec<String> = ["CREATE TABLE a ();", "CREATE TABLE b ();", "CREATE TABLE c ();", "VACUUM a;", "CREATE TABLE d ();"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let shape = |chunks: Vec<ChunkResult>| {
+            chunks
+                .iter()
+                .map(|c| (c.first_statement, c.statement_count, c.transactional))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            shape(plan_chunks(&statements, Some(2), 0)),
+            vec![(0, 2, true), (2, 1, true), (3, 1, false), (4, 1, true)]
+        );
+        assert_eq!(shape(plan_chunks(&statements, None, 0)), vec![(0, 3, true), (3, 1, false), (4, 1, true)]);
+        assert_eq!(shape(plan_chunks(&statements, None, 2)), vec![(2, 1, true), (3, 1, false), (4, 1, true)]);
+    }
+
+    #[test]
+    fn test_alter_column_type_with_using() {
+        use crate::pipeline::types::SchemaChange;
+
+        let mut proposal = SchemaProposal::new(uuid::Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::AlterColumn {
+            table_name: "events".to_string(),
+            column_name: "payload".to_string(),
+            new_type: Some("jsonb".to_string()),
+            new_nullable: Some(false),
+            new_default: None,
+            using: Some("payload::jsonb".to_string()),
+        });
+
+        let migration = Orchestrator::new().generate_migration(&proposal);
+        assert_eq!(
+            migration.up_sql,
+            "ALTER TABLE events ALTER COLUMN payload TYPE jsonb USING payload::jsonb;\n\nALTER TABLE events ALTER COLUMN payload SET NOT NULL;"
+        );
+        assert!(migration.down_sql.starts_with("ALTER TABLE events ALTER COLUMN payload DROP NOT NULL;"));
+    }
+
+    #[test]
+    fn test_view_changes() {
+        use crate::pipeline::types::SchemaChange;
+
+        let mut proposal = SchemaProposal::new(uuid::Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::CreateView {
+            view_name: "open_orders".to_string(),
+            query: " SELECT id\n\n   FROM orders\n  WHERE NOT shipped;".to_string(),
+        });
+        proposal.changes.push(SchemaChange::CreateMaterializedView {
+            view_name: "order_totals".to_string(),
+            query: "SELECT sum(total) FROM orders".to_string(),
+            with_no_data: true,
+        });
+        proposal.changes.push(SchemaChange::RefreshMaterializedView {
+            view_name: "order_totals".to_string(),
+            concurrently: true,
+        });
+
+        let migration = Orchestrator::new().generate_migration(&proposal);
+        assert_eq!(
+            split_statements(&migration.up_sql),
+            vec![
+                "CREATE VIEW open_orders AS SELECT id\n   FROM orders\n  WHERE NOT shipped;",
+                "CREATE MATERIALIZED VIEW order_totals AS SELECT sum(total) FROM orders WITH NO DATA;",
+                "REFRESH MATERIALIZED VIEW CONCURRENTLY order_totals;",
+            ]
+        );
+        assert_eq!(
+            migration.down_sql,
+            "DROP MATERIALIZED VIEW IF EXISTS order_totals;\n\nDROP VIEW IF EXISTS open_orders;"
+        );
+    }
+
+    #[test]
+    fn test_custom_field_change_runs_no_sql() {
+        use crate::pipeline::types::SchemaChange;
+
+        let mut proposal = SchemaProposal::new(uuid::Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::SetCustomField {
+            table_name: "orders".to_string(),
+            column_name: Some("email".to_string()),
+            field: "data_steward".to_string(),
+            value: Some("alice\n\nDROP TABLE orders;".to_string()),
+        });
+
+        let migration = Orchestrator::new().generate_migration(&proposal);
+        assert!(migration.up_sql.starts_with("-- governance: set custom field data_steward on orders.email"));
+        assert!(split_statements(&migration.up_sql).is_empty());
+        assert!(split_statements(&migration.down_sql).is_empty());
+    }
+}
@@ -0,0 +1,499 @@
+//! Table partitioning advisor
+//!
+//! Flags large, mostly append-only tables as partitioning candidates, using
+//! the size and write statistics PostgreSQL keeps per table. Inserts that
+//! dwarf updates and deletes, on a table with a timestamp column, are the
+//! classic shape for range partitioning by time: old partitions can be
+//! detached and archived instead of deleted row by row.
+//!
+//! `scaffold` turns a candidate into a change plan that swaps the table for
+//! a partitioned one without copying rows. The existing table is renamed
+//! and attached as the partition holding everything before a cutoff, after
+//! a validated CHECK constraint lets the attach skip its scan, and empty
+//! partitions are created for the periods that follow.
+
+use crate::error::AppError;
+use crate::introspection::{SchemaSnapshot, Table};
+use crate::pipeline::revert::column_def;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::{PartitionBound, PartitionKey, PartitionStrategy, SchemaChange};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+
+/// Types a time-range partition key can have
+const TIME_TYPES: [&str; 4] = ["timestamp with time zone", "timestamp without time zone", "date", "timestamptz"];
+
+/// Column names preferred as the partition key, in order
+const PREFERRED_KEYS: [&str; 5] = ["created_at", "inserted_at", "occurred_at", "event_time", "timestamp"];
+
+/// Size and write statistics of one table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    pub schema: String,
+    pub table: String,
+    pub total_bytes: i64,
+    pub estimated_rows: i64,
+    pub inserts: i64,
+    pub updates: i64,
+    pub deletes: i64,
+    /// Sequential scans since statistics were reset; large tables read this
+    /// way are the hot spots partition pruning helps most
+    pub seq_scans: i64,
+}
+
+impl TableStats {
+    /// Share of writes that were inserts (1.0 for a purely append-only table)
+    pub fn append_ratio(&self) -> f64 {
+        let writes = self.inserts + self.updates + self.deletes;
+        if writes == 0 {
+            return 0.0;
+        }
+        self.inserts as f64 / writes as f64
+    }
+}
+
+/// When a table counts as a candidate
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AdvisorThresholds {
+    pub min_bytes: i64,
+    pub min_rows: i64,
+    pub min_append_ratio: f64,
+}
+
+impl Default for AdvisorThresholds {
+    fn default() -> Self {
+        Self {
+            min_bytes: 10 * 1024 * 1024 * 1024,
+            min_rows: 50_000_000,
+            min_append_ratio: 0.95,
+        }
+    }
+}
+
+/// A table worth partitioning, and why
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionCandidate {
+    /// Schema-qualified table name
+    pub table: String,
+    pub stats: TableStats,
+    pub append_ratio: f64,
+    /// Time column to range-partition on, if the table has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_key: Option<String>,
+    pub reasons: Vec<String>,
+}
+
+/// Statistics of every ordinary (not yet partitioned) table
+pub async fn table_stats(pool: &Pool) -> Result<Vec<TableStats>, AppError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            r#"
+            SELECT s.schemaname::text, s.relname::text,
+                   pg_total_relation_size(s.relid)::bigint,
+                   GREATEST(c.reltuples, 0)::bigint,
+                   s.n_tup_ins, s.n_tup_upd, s.n_tup_del,
+                   COALESCE(s.seq_scan, 0)
+            FROM pg_stat_user_tables s
+            JOIN pg_class c ON c.oid = s.relid
+            WHERE c.relkind = 'r' AND NOT c.relispartition
+            ORDER BY 3 DESC
+            "#,
+            &[],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| TableStats {
+            schema: row.get(0),
+            table: row.get(1),
+            total_bytes: row.get(2),
+            estimated_rows: row.get(3),
+            inserts: row.get(4),
+            updates: row.get(5),
+            deletes: row.get(6),
+            seq_scans: row.get(7),
+        })
+        .collect())
+}
+
+/// Time column a table would be range-partitioned on
+pub fn suggest_key(table: &Table) -> Option<String> {
+    let time_columns: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| TIME_TYPES.contains(&c.data_type.to_lowercase().as_str()))
+        .map(|c| c.name.as_str())
+        .collect();
+    PREFERRED_KEYS
+        .iter()
+        .find(|key| time_columns.contains(key))
+        .or(time_columns.first())
+        .map(|key| key.to_string())
+}
+
+fn human_bytes(bytes: i64) -> String {
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    format!("{:.1} GiB", bytes as f64 / GIB)
+}
+
+/// Tables over the thresholds, biggest first
+pub fn evaluate(
+    stats: Vec<TableStats>,
+    snapshot: Option<&SchemaSnapshot>,
+    thresholds: &AdvisorThresholds,
+) -> Vec<PartitionCandidate> {
+    let mut candidates: Vec<PartitionCandidate> = stats
+        .into_iter()
+        .filter(|s| s.total_bytes >= thresholds.min_bytes || s.estimated_rows >= thresholds.min_rows)
+        .filter(|s| s.append_ratio() >= thresholds.min_append_ratio)
+        .map(|stats| {
+            let append_ratio = stats.append_ratio();
+            let suggested_key = snapshot
+                .and_then(|s| s.tables.iter().find(|t| t.schema == stats.schema && t.name == stats.table))
+                .and_then(suggest_key);
+
+            let mut reasons = vec![
+                format!("{} across about {} rows", human_bytes(stats.total_bytes), stats.estimated_rows),
+                format!("{:.1}% of writes are inserts", append_ratio * 100.0),
+            ];
+            if stats.seq_scans > 0 {
+                reasons.push(format!(
+                    "{} sequential scans; queries filtering on the key would read only matching partitions",
+                    stats.seq_scans
+                ));
+            }
+            match &suggested_key {
+                Some(key) => reasons.push(format!("'{}' suits range partitioning by time", key)),
+                None => reasons.push("No time column found; pick a key before scaffolding".to_string()),
+            }
+
+            PartitionCandidate {
+                table: format!("{}.{}", stats.schema, stats.table),
+                stats,
+                append_ratio,
+                suggested_key,
+                reasons,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.stats.total_bytes.cmp(&a.stats.total_bytes));
+    candidates
+}
+
+/// Length of each time-range partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionInterval {
+    Day,
+    Week,
+    #[default]
+    Month,
+}
+
+impl PartitionInterval {
+    /// Start of the period containing `date`
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            PartitionInterval::Day => date,
+            PartitionInterval::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            PartitionInterval::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn next(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            PartitionInterval::Day => date + Duration::days(1),
+            PartitionInterval::Week => date + Duration::weeks(1),
+            PartitionInterval::Month => date.checked_add_months(Months::new(1)).unwrap_or(date),
+        }
+    }
+
+    fn suffix(self, date: NaiveDate) -> String {
+        match self {
+            PartitionInterval::Month => date.format("%Y%m").to_string(),
+            PartitionInterval::Day | PartitionInterval::Week => date.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+/// What to scaffold
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaffoldRequest {
+    /// Table to partition, schema-qualified or in `public`
+    pub table: String,
+    /// Time column to partition on; the advisor's suggestion by default
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub interval: PartitionInterval,
+    /// Empty partitions to create after the cutoff
+    #[serde(default = "default_periods_ahead")]
+    pub periods_ahead: u32,
+    #[serde(default)]
+    pub project_id: Option<i32>,
+}
+
+fn default_periods_ahead() -> u32 {
+    3
+}
+
+/// Upper bound of `periods_ahead`
+pub const MAX_PERIODS_AHEAD: u32 = 60;
+
+/// Changes for the swap, plus the plan explaining them
+#[derive(Debug, Clone)]
+pub struct Scaffold {
+    pub changes: Vec<SchemaChange>,
+    pub plan: Vec<String>,
+}
+
+/// Build the change plan that partitions `req.table` from `now` on
+pub fn scaffold(snapshot: &SchemaSnapshot, req: &ScaffoldRequest, now: DateTime<Utc>) -> Result<Scaffold, AppError> {
+    if req.periods_ahead == 0 || req.periods_ahead > MAX_PERIODS_AHEAD {
+        return Err(AppError::Validation(format!("periodsAhead must be between 1 and {}", MAX_PERIODS_AHEAD)));
+    }
+    let (schema, name) = split_table_name(&req.table);
+    let schema = schema.unwrap_or("public");
+    let table = snapshot
+        .tables
+        .iter()
+        .find(|t| t.schema == schema && t.name == name)
+        .ok_or_else(|| AppError::NotFound(format!("Table {}.{} not in the latest snapshot", schema, name)))?;
+    let key = match &req.key {
+        Some(key) => key.clone(),
+        None => suggest_key(table).ok_or_else(|| {
+            AppError::Validation(format!("{}.{} has no time column; name the partition key", schema, name))
+        })?,
+    };
+    if !table.columns.iter().any(|c| c.name == key) {
+        return Err(AppError::Validation(format!("{}.{} has no column '{}'", schema, name, key)));
+    }
+
+    let qualified = format!("{}.{}", schema, name);
+    let staging = format!("{}_partitioned", name);
+    let legacy = format!("{}_legacy", name);
+    let check_name = format!("{}_partition_bound", legacy);
+    let cutoff = req.interval.start_of(now.date_naive());
+    let cutoff_literal = format!("'{}'", cutoff);
+
+    let mut columns: Vec<_> = table.columns.iter().collect();
+    columns.sort_by_key(|c| c.ordinal_position);
+    // Unique constraints on a partitioned table must include the partition key
+    let columns = columns
+        .into_iter()
+        .map(|c| {
+            let mut def = column_def(c);
+            def.is_primary_key = false;
+            def
+        })
+        .collect();
+
+    let mut changes = vec![
+        SchemaChange::AddCheck {
+            table_name: qualified.clone(),
+            constraint_name: check_name.clone(),
+            expression: format!("{} IS NOT NULL AND {} < {}", key, key, cutoff_literal),
+            not_valid: true,
+        },
+        SchemaChange::ValidateConstraint { table_name: qualified.clone(), constraint_name: check_name },
+        SchemaChange::CreateTable {
+            table_name: format!("{}.{}", schema, staging),
+            columns,
+            partition_by: Some(PartitionKey { strategy: PartitionStrategy::Range, columns: vec![key.clone()] }),
+        },
+    ];
+    if let Some(pk) = &table.primary_key {
+        let mut pk_columns = pk.columns.clone();
+        if !pk_columns.contains(&key) {
+            pk_columns.push(key.clone());
+        }
+        changes.push(SchemaChange::AddUnique {
+            table_name: format!("{}.{}", schema, staging),
+            constraint_name: format!("{}_{}_key", staging, pk_columns.join("_")),
+            columns: pk_columns,
+        });
+    }
+    // Index builds on a partitioned table cascade to its partitions and
+    // cannot run concurrently; the legacy partition keeps its own indexes
+    let indexes: Vec<_> = snapshot
+        .indexes
+        .iter()
+        .filter(|i| i.schema == schema && i.table == name && !i.is_primary)
+        .collect();
+    for index in &indexes {
+        let mut columns = index.columns.clone();
+        if index.is_unique && !columns.contains(&key) {
+            columns.push(key.clone());
+        }
+        changes.push(SchemaChange::AddIndex {
+            table_name: format!("{}.{}", schema, staging),
+            index_name: format!("{}_partitioned", index.name),
+            columns,
+            unique: index.is_unique,
+            concurrently: false,
+        });
+    }
+    changes.extend([
+        SchemaChange::RenameTable { old_name: qualified.clone(), new_name: legacy.clone() },
+        SchemaChange::RenameTable { old_name: format!("{}.{}", schema, staging), new_name: name.to_string() },
+        SchemaChange::AttachPartition {
+            parent_table: qualified.clone(),
+            partition_name: format!("{}.{}", schema, legacy),
+            bound: PartitionBound::Range { from: vec!["MINVALUE".to_string()], to: vec![cutoff_literal.clone()] },
+        },
+    ]);
+    let mut start = cutoff;
+    for _ in 0..req.periods_ahead {
+        let end = req.interval.next(start);
+        changes.push(SchemaChange::CreatePartition {
+            table_name: format!("{}.{}_p{}", schema, name, req.interval.suffix(start)),
+            parent_table: qualified.clone(),
+            bound: PartitionBound::Range { from: vec![format!("'{}'", start)], to: vec![format!("'{}'", end)] },
+        });
+        start = end;
+    }
+
+    let referencing: Vec<String> = snapshot
+        .foreign_keys
+        .iter()
+        .filter(|fk| fk.referenced_schema == schema && fk.referenced_table == name)
+        .map(|fk| format!("{}.{} ({})", fk.source_schema, fk.source_table, fk.constraint_name))
+        .collect();
+    let mut plan = vec![
+        format!(
+            "Add CHECK ({} IS NOT NULL AND {} < {}) to {} and validate it outside the swap transaction, so the attach does not rescan the table",
+            key, key, cutoff_literal, qualified
+        ),
+        format!("Create {}.{} partitioned by RANGE ({}) with the same columns", schema, staging, key),
+        format!("Rename {} to {} and {} to {}", qualified, legacy, staging, name),
+        format!("Attach {} as the partition holding every row before {}; no rows are copied", legacy, cutoff),
+        format!("Create {} empty partition(s) from {} on", req.periods_ahead, cutoff),
+        "Create further partitions ahead of time; inserts past the last one fail".to_string(),
+    ];
+    if table.primary_key.is_some() {
+        plan.push(format!("The primary key becomes a unique constraint that includes '{}'", key));
+    }
+    if table.columns.iter().any(|c| c.name == key && c.nullable) {
+        plan.push(format!("Rows with a NULL '{}' fail the CHECK; backfill them first", key));
+    }
+    if !referencing.is_empty() {
+        plan.push(format!(
+            "Foreign keys referencing the table keep pointing at {} and must be recreated: {}",
+            legacy,
+            referencing.join(", ")
+        ));
+    }
+    if !indexes.is_empty() {
+        plan.push(format!(
+            "Recreate {} index(es) on the new parent; unique ones gain '{}', and the attach adopts matching legacy indexes or builds them",
+            indexes.len(),
+            key
+        ));
+    }
+
+    Ok(Scaffold { changes, plan })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::Column;
+
+    fn stats(table: &str, bytes: i64, inserts: i64, updates: i64) -> TableStats {
+        TableStats {
+            schema: "public".to_string(),
+            table: table.to_string(),
+            total_bytes: bytes,
+            estimated_rows: 0,
+            inserts,
+            updates,
+            deletes: 0,
+            seq_scans: 0,
+        }
+    }
+
+    fn column(name: &str, data_type: &str, position: i32) -> Column {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "dataType": data_type,
+            "nullable": false,
+            "isPrimaryKey": name == "id",
+            "isUnique": false,
+            "ordinalPosition": position,
+        }))
+        .unwrap()
+    }
+
+    fn snapshot() -> SchemaSnapshot {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "connectionId": uuid::Uuid::new_v4(),
+            "version": 1,
+            "capturedAt": Utc::now(),
+            "tables": [{
+                "name": "events",
+                "schema": "public",
+                "columns": [
+                    column("id", "bigint", 1),
+                    column("created_at", "timestamp with time zone", 2),
+                ],
+                "primaryKey": { "constraintName": "events_pkey", "columns": ["id"] },
+            }],
+            "foreignKeys": [],
+            "indexes": [],
+            "checksum": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_keeps_large_append_only_tables() {
+        let gib = 1024 * 1024 * 1024;
+        let candidates = evaluate(
+            vec![
+                stats("events", 20 * gib, 1_000_000, 10),
+                stats("accounts", 30 * gib, 1_000, 5_000),
+                stats("small", gib, 1_000_000, 0),
+            ],
+            Some(&snapshot()),
+            &AdvisorThresholds::default(),
+        );
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].table, "public.events");
+        assert_eq!(candidates[0].suggested_key.as_deref(), Some("created_at"));
+    }
+
+    #[test]
+    fn test_scaffold_swaps_without_copying() {
+        let req = ScaffoldRequest {
+            table: "events".to_string(),
+            key: None,
+            interval: PartitionInterval::Month,
+            periods_ahead: 2,
+            project_id: None,
+        };
+        let now = "2024-03-15T12:00:00Z".parse().unwrap();
+        let scaffold = scaffold(&snapshot(), &req, now).unwrap();
+
+        assert!(matches!(&scaffold.changes[0], SchemaChange::AddCheck { not_valid: true, .. }));
+        assert!(scaffold.changes.iter().any(|c| matches!(c,
+            SchemaChange::AddUnique { columns, .. } if columns == &["id".to_string(), "created_at".to_string()])));
+        let partitions: Vec<&str> = scaffold
+            .changes
+            .iter()
+            .filter_map(|c| match c {
+                SchemaChange::CreatePartition { table_name, .. } => Some(table_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(partitions, vec!["public.events_p202403", "public.events_p202404"]);
+        assert!(matches!(scaffold.changes.last(), Some(SchemaChange::CreatePartition { .. })));
+    }
+}
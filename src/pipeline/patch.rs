@@ -0,0 +1,268 @@
+//! JSON Patch support for partial proposal updates
+//!
+//! Applies a subset of RFC 6902 operations (add, remove, replace) to the
+//! editable fields of a draft proposal.
+
+use crate::error::AppError;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::templating::TemplateParameter;
+use crate::pipeline::types::SchemaChange;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Supported patch operation kinds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchOp {
+    Add,
+    Remove,
+    Replace,
+}
+
+/// A single JSON Patch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchOperation {
+    pub op: PatchOp,
+    /// JSON Pointer into the proposal (e.g. "/title", "/changes/2", "/labels/-")
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+/// Editable proposal fields addressable by a patch path
+enum PatchTarget {
+    Title,
+    Description,
+    Reviewers(Option<ListIndex>),
+    ReviewerTeams(Option<ListIndex>),
+    Labels(Option<ListIndex>),
+    Changes(Option<ListIndex>),
+    Parameters(Option<ListIndex>),
+}
+
+/// Position within an array field
+enum ListIndex {
+    /// "-" - the end of the array (only valid for add)
+    End,
+    At(usize),
+}
+
+impl PatchTarget {
+    fn parse(path: &str) -> Result<Self, AppError> {
+        let segments: Vec<&str> = path
+            .strip_prefix('/')
+            .ok_or_else(|| AppError::Validation(format!("Invalid patch path '{}': must start with '/'", path)))?
+            .split('/')
+            .collect();
+
+        let index = match segments.get(1) {
+            None => None,
+            Some(&"-") => Some(ListIndex::End),
+            Some(raw) => Some(ListIndex::At(raw.parse().map_err(|_| {
+                AppError::Validation(format!("Invalid array index '{}' in patch path '{}'", raw, path))
+            })?)),
+        };
+
+        if segments.len() > 2 {
+            return Err(AppError::Validation(format!("Unsupported patch path '{}'", path)));
+        }
+
+        match (segments[0], index) {
+            ("title", None) => Ok(PatchTarget::Title),
+            ("description", None) => Ok(PatchTarget::Description),
+            ("reviewers", idx) => Ok(PatchTarget::Reviewers(idx)),
+            ("reviewerTeams", idx) => Ok(PatchTarget::ReviewerTeams(idx)),
+            ("labels", idx) => Ok(PatchTarget::Labels(idx)),
+            ("changes", idx) => Ok(PatchTarget::Changes(idx)),
+            ("parameters", idx) => Ok(PatchTarget::Parameters(idx)),
+            _ => Err(AppError::Validation(format!("Unsupported patch path '{}'", path))),
+        }
+    }
+}
+
+impl PatchOperation {
+    fn value<T: serde::de::DeserializeOwned>(&self) -> Result<T, AppError> {
+        let value = self.value.clone().ok_or_else(|| {
+            AppError::Validation(format!("Patch operation on '{}' requires a value", self.path))
+        })?;
+        serde_json::from_value(value)
+            .map_err(|e| AppError::Validation(format!("Invalid value for '{}': {}", self.path, e)))
+    }
+}
+
+/// Apply a list of patch operations to a proposal.
+///
+/// Operations are applied in order; if any operation fails the proposal is
+/// left untouched. Returns `true` if the changes array was modified.
+pub fn apply_patch(proposal: &mut SchemaProposal, operations: &[PatchOperation]) -> Result<bool, AppError> {
+    if operations.is_empty() {
+        return Err(AppError::Validation("Patch must contain at least one operation".to_string()));
+    }
+
+    let mut patched = proposal.clone();
+    let mut changes_modified = false;
+
+    for operation in operations {
+        match PatchTarget::parse(&operation.path)? {
+            PatchTarget::Title => {
+                let title: String = scalar_value(operation)?;
+                if title.trim().is_empty() {
+                    return Err(AppError::Validation("Title cannot be empty".to_string()));
+                }
+                patched.title = title;
+            }
+            PatchTarget::Description => {
+                patched.description = match operation.op {
+                    PatchOp::Remove => String::new(),
+                    _ => operation.value()?,
+                };
+            }
+            PatchTarget::Reviewers(index) => {
+                apply_list_op(&mut patched.reviewers, index, operation)?;
+                dedup(&mut patched.reviewers);
+            }
+            PatchTarget::ReviewerTeams(index) => {
+                apply_list_op(&mut patched.reviewer_teams, index, operation)?;
+                dedup(&mut patched.reviewer_teams);
+            }
+            PatchTarget::Labels(index) => {
+                apply_list_op(&mut patched.labels, index, operation)?;
+                dedup(&mut patched.labels);
+            }
+            PatchTarget::Changes(index) => {
+                apply_list_op::<SchemaChange>(&mut patched.changes, index, operation)?;
+                changes_modified = true;
+            }
+            PatchTarget::Parameters(index) => {
+                apply_list_op::<TemplateParameter>(&mut patched.parameters, index, operation)?;
+            }
+        }
+    }
+
+    *proposal = patched;
+    Ok(changes_modified)
+}
+
+/// Scalar fields only support replace (add behaves the same per RFC 6902)
+fn scalar_value<T: serde::de::DeserializeOwned>(operation: &PatchOperation) -> Result<T, AppError> {
+    if operation.op == PatchOp::Remove {
+        return Err(AppError::Validation(format!("Cannot remove required field '{}'", operation.path)));
+    }
+    operation.value()
+}
+
+/// Remove duplicate entries while keeping the first occurrence
+fn dedup(list: &mut Vec<String>) {
+    let mut seen = HashSet::new();
+    list.retain(|item| seen.insert(item.clone()));
+}
+
+fn apply_list_op<T: serde::de::DeserializeOwned>(
+    list: &mut Vec<T>,
+    index: Option<ListIndex>,
+    operation: &PatchOperation,
+) -> Result<(), AppError> {
+    let len = list.len();
+    let out_of_range = |i: usize| {
+        AppError::Validation(format!("Index {} out of range for '{}' ({} items)", i, operation.path, len))
+    };
+
+    match (operation.op, index) {
+        // Whole-array operations
+        (PatchOp::Add | PatchOp::Replace, None) => *list = operation.value()?,
+        (PatchOp::Remove, None) => list.clear(),
+
+        (PatchOp::Add, Some(ListIndex::End)) => list.push(operation.value()?),
+        (PatchOp::Add, Some(ListIndex::At(i))) => {
+            if i > list.len() {
+                return Err(out_of_range(i));
+            }
+            list.insert(i, operation.value()?);
+        }
+        (PatchOp::Replace, Some(ListIndex::At(i))) => {
+            if i >= list.len() {
+                return Err(out_of_range(i));
+            }
+            list[i] = operation.value()?;
+        }
+        (PatchOp::Remove, Some(ListIndex::At(i))) => {
+            if i >= list.len() {
+                return Err(out_of_range(i));
+            }
+            list.remove(i);
+        }
+        (_, Some(ListIndex::End)) => {
+            return Err(AppError::Validation(format!(
+                "'-' index is only valid for add operations ('{}')",
+                operation.path
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn proposal() -> SchemaProposal {
+        SchemaProposal::new(Uuid::new_v4(), "Original".to_string(), String::new(), "tester".to_string())
+    }
+
+    fn op(value: serde_json::Value) -> PatchOperation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_replace_title_and_add_label() {
+        let mut p = proposal();
+        let ops = vec![
+            op(json!({ "op": "replace", "path": "/title", "value": "Renamed" })),
+            op(json!({ "op": "add", "path": "/labels/-", "value": "billing" })),
+        ];
+
+        let changes_modified = apply_patch(&mut p, &ops).unwrap();
+
+        assert!(!changes_modified);
+        assert_eq!(p.title, "Renamed");
+        assert_eq!(p.labels, vec!["billing".to_string()]);
+    }
+
+    #[test]
+    fn test_changes_add_and_remove_by_index() {
+        let mut p = proposal();
+        let ops = vec![
+            op(json!({ "op": "add", "path": "/changes/-", "value": { "type": "drop_table", "table_name": "a" } })),
+            op(json!({ "op": "add", "path": "/changes/0", "value": { "type": "drop_table", "table_name": "b" } })),
+            op(json!({ "op": "remove", "path": "/changes/1" })),
+        ];
+
+        assert!(apply_patch(&mut p, &ops).unwrap());
+        assert_eq!(p.changes.len(), 1);
+        assert!(matches!(&p.changes[0], SchemaChange::DropTable { table_name } if table_name == "b"));
+    }
+
+    #[test]
+    fn test_failed_patch_leaves_proposal_untouched() {
+        let mut p = proposal();
+        let ops = vec![
+            op(json!({ "op": "replace", "path": "/title", "value": "Renamed" })),
+            op(json!({ "op": "remove", "path": "/changes/3" })),
+        ];
+
+        assert!(apply_patch(&mut p, &ops).is_err());
+        assert_eq!(p.title, "Original");
+    }
+
+    #[test]
+    fn test_rejects_unknown_path() {
+        let mut p = proposal();
+        let ops = vec![op(json!({ "op": "replace", "path": "/status", "value": "approved" }))];
+
+        assert!(apply_patch(&mut p, &ops).is_err());
+    }
+}
@@ -0,0 +1,427 @@
+//! Staged execution plans
+//!
+//! A plan splits an approved proposal's migration into stages that are run
+//! one request at a time, each through the orchestrator like any execution.
+//! Stages follow the transaction chunks the run would use, and a
+//! confirmation gate always starts a stage of its own. Stage status and the
+//! last committed statement are stored, so a stage that fails, or that was
+//! running when the server stopped, runs again from where it got to.
+//!
+//! A plan can be paused between stages, resumed and cancelled; a stage that
+//! is running always runs to its end. Cancelling a plan that has changed the
+//! database leaves the proposal failed at its checkpoint, where it can be
+//! resumed with a plain execution or rolled back.
+
+use crate::error::AppError;
+use crate::pipeline::orchestrator::{plan_chunks, split_statements, ExecutionResult};
+use crate::pipeline::proposal::{MigrationArtifacts, SchemaProposal};
+use crate::pipeline::reorder;
+use crate::state::SharedState;
+use crate::storage::{ExecutionPlanBackend, MemoryExecutionPlanBackend};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Where a plan stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    /// The next stage can run
+    Ready,
+    Running,
+    /// Held between stages until resumed
+    Paused,
+    /// A stage failed; running it again resumes it
+    Failed,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A run of consecutive migration statements executed together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanStage {
+    pub index: usize,
+    /// Zero-based position of the stage's first statement in the migration
+    pub first_statement: usize,
+    pub statement_count: usize,
+    pub statements: Vec<String>,
+    /// False for statements that run in autocommit mode
+    pub transactional: bool,
+    /// Confirmation gate the stage opens; running it takes the label
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gate: Option<String>,
+    pub status: StageStatus,
+    /// First statement not yet committed; a failed stage resumes here
+    pub checkpoint: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl PlanStage {
+    /// Index of the statement after the stage's last
+    pub fn end(&self) -> usize {
+        self.first_statement + self.statement_count
+    }
+}
+
+/// A proposal's migration split into stages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPlan {
+    pub id: Uuid,
+    pub proposal_id: Uuid,
+    pub connection_id: Uuid,
+    /// Migration version the plan runs; stages refuse to run once it is not the proposal's
+    pub migration_version: u32,
+    pub migration_checksum: String,
+    /// Statements per transaction within a stage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    pub total_statements: usize,
+    pub status: PlanStatus,
+    pub stages: Vec<PlanStage>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExecutionPlan {
+    /// Split `migration` into stages of at most `chunk_size` statements
+    pub fn build(
+        proposal: &SchemaProposal,
+        migration: &MigrationArtifacts,
+        chunk_size: Option<usize>,
+        created_by: &str,
+    ) -> Result<Self, AppError> {
+        let statements = split_statements(&migration.up_sql);
+        if statements.is_empty() {
+            return Err(AppError::BadRequest("The migration has no statements to plan".to_string()));
+        }
+
+        let mut bounds = Vec::new();
+        for chunk in plan_chunks(&statements, chunk_size, 0) {
+            let end = chunk.first_statement + chunk.statement_count;
+            let mut first = chunk.first_statement;
+            for gate in (first + 1..end).filter(|i| reorder::gate_label(&statements[*i]).is_some()) {
+                bounds.push((first, gate, chunk.transactional));
+                first = gate;
+            }
+            bounds.push((first, end, chunk.transactional));
+        }
+
+        let stages = bounds
+            .into_iter()
+            .enumerate()
+            .map(|(index, (first, end, transactional))| PlanStage {
+                index,
+                first_statement: first,
+                statement_count: end - first,
+                statements: statements[first..end].to_vec(),
+                transactional,
+                gate: reorder::gate_label(&statements[first]).map(str::to_string),
+                status: StageStatus::Pending,
+                checkpoint: first,
+                execution_id: None,
+                error: None,
+                started_at: None,
+                finished_at: None,
+            })
+            .collect();
+
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            proposal_id: proposal.id,
+            connection_id: proposal.connection_id,
+            migration_version: migration.version,
+            migration_checksum: migration.checksum.clone(),
+            chunk_size,
+            total_statements: statements.len(),
+            status: PlanStatus::Ready,
+            stages,
+            created_by: created_by.to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Still able to run stages, or waiting to
+    pub fn is_active(&self) -> bool {
+        !matches!(self.status, PlanStatus::Completed | PlanStatus::Cancelled)
+    }
+
+    /// Whether any stage has committed statements
+    pub fn has_started(&self) -> bool {
+        self.stages.iter().any(|s| s.checkpoint > s.first_statement)
+    }
+
+    /// First stage not completed
+    pub fn next_stage(&self) -> Option<&PlanStage> {
+        self.stages.iter().find(|s| s.status != StageStatus::Completed)
+    }
+
+    /// Mark stage `index` running; only the next stage of a ready or failed plan can start
+    pub fn start_stage(&mut self, index: usize, now: DateTime<Utc>) -> Result<PlanStage, AppError> {
+        match self.status {
+            PlanStatus::Ready | PlanStatus::Failed => {}
+            PlanStatus::Paused => return Err(AppError::Conflict("The plan is paused; resume it first".to_string())),
+            PlanStatus::Running => return Err(AppError::Conflict("A stage of this plan is already running".to_string())),
+            status => return Err(AppError::Conflict(format!("The plan is {:?}; nothing more runs", status))),
+        }
+        let next = self.next_stage().map(|s| s.index);
+        if next != Some(index) {
+            return Err(AppError::Conflict(match next {
+                Some(next) => format!("Stages run in order; stage {} is next", next),
+                None => "Every stage has run".to_string(),
+            }));
+        }
+
+        let stage = &mut self.stages[index];
+        stage.status = StageStatus::Running;
+        stage.started_at = Some(now);
+        stage.finished_at = None;
+        stage.error = None;
+        let stage = stage.clone();
+        self.status = PlanStatus::Running;
+        self.updated_at = now;
+        Ok(stage)
+    }
+
+    /// Record how a run of stage `index` went
+    pub fn finish_stage(&mut self, index: usize, result: &ExecutionResult, now: DateTime<Utc>) {
+        let stage = &mut self.stages[index];
+        stage.checkpoint = result.checkpoint.clamp(stage.first_statement, stage.end());
+        stage.execution_id = Some(result.id);
+        stage.finished_at = Some(now);
+        if result.success && stage.checkpoint == stage.end() {
+            stage.status = StageStatus::Completed;
+        } else {
+            stage.status = StageStatus::Failed;
+            stage.error = Some(result.error.clone().unwrap_or_else(|| "The stage stopped early".to_string()));
+        }
+        self.settle(now);
+    }
+
+    /// Record a stage that could not start running
+    pub fn abort_stage(&mut self, index: usize, error: &str, now: DateTime<Utc>) {
+        let stage = &mut self.stages[index];
+        stage.status = StageStatus::Failed;
+        stage.error = Some(error.to_string());
+        stage.finished_at = Some(now);
+        self.settle(now);
+    }
+
+    fn settle(&mut self, now: DateTime<Utc>) {
+        self.status = if self.stages.iter().any(|s| s.status == StageStatus::Failed) {
+            PlanStatus::Failed
+        } else if self.next_stage().is_none() {
+            PlanStatus::Completed
+        } else {
+            PlanStatus::Ready
+        };
+        self.updated_at = now;
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) -> Result<(), AppError> {
+        match self.status {
+            PlanStatus::Ready | PlanStatus::Running | PlanStatus::Failed => {
+                // A running stage finishes first; the plan then stays put
+                self.status = PlanStatus::Paused;
+                self.updated_at = now;
+                Ok(())
+            }
+            status => Err(AppError::Conflict(format!("A {:?} plan cannot be paused", status))),
+        }
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.status != PlanStatus::Paused {
+            return Err(AppError::Conflict("Only a paused plan can be resumed".to_string()));
+        }
+        if self.stages.iter().any(|s| s.status == StageStatus::Running) {
+            self.status = PlanStatus::Running;
+            self.updated_at = now;
+        } else {
+            self.settle(now);
+        }
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if !self.is_active() {
+            return Err(AppError::Conflict(format!("A {:?} plan cannot be cancelled", self.status)));
+        }
+        if self.stages.iter().any(|s| s.status == StageStatus::Running) {
+            return Err(AppError::Conflict("A stage is running; cancel once it finishes".to_string()));
+        }
+        self.status = PlanStatus::Cancelled;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// A stage still marked running after a restart died with the process.
+    /// Returns whether the plan changed.
+    fn interrupt(&mut self, now: DateTime<Utc>) -> bool {
+        let mut interrupted = false;
+        for stage in self.stages.iter_mut().filter(|s| s.status == StageStatus::Running) {
+            stage.status = StageStatus::Failed;
+            stage.error = Some("Interrupted by a server restart".to_string());
+            stage.finished_at = Some(now);
+            interrupted = true;
+        }
+        if interrupted && self.status != PlanStatus::Paused {
+            self.settle(now);
+        }
+        interrupted
+    }
+}
+
+/// Stored execution plans
+#[derive(Clone)]
+pub struct PlanStore {
+    backend: Arc<dyn ExecutionPlanBackend>,
+}
+
+impl PlanStore {
+    /// In-memory store
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(MemoryExecutionPlanBackend::default()))
+    }
+
+    pub fn with_backend(backend: Arc<dyn ExecutionPlanBackend>) -> Self {
+        Self { backend }
+    }
+
+    pub async fn put(&self, plan: &ExecutionPlan) -> Result<(), AppError> {
+        self.backend.put(plan).await
+    }
+
+    pub async fn get(&self, plan_id: Uuid) -> Result<ExecutionPlan, AppError> {
+        self.backend
+            .get(plan_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Execution plan {} not found", plan_id)))
+    }
+
+    /// A proposal's plans, oldest first
+    pub async fn for_proposal(&self, proposal_id: Uuid) -> Result<Vec<ExecutionPlan>, AppError> {
+        self.backend.list_for_proposal(proposal_id).await
+    }
+
+    /// Startup recovery: fail the stages the previous process was running.
+    /// Returns the plans that had one.
+    pub async fn recover(&self) -> Result<Vec<ExecutionPlan>, AppError> {
+        let now = Utc::now();
+        let mut recovered = Vec::new();
+        for status in [PlanStatus::Running, PlanStatus::Paused] {
+            for mut plan in self.backend.list_by_status(status).await? {
+                if plan.interrupt(now) {
+                    self.backend.put(&plan).await?;
+                    recovered.push(plan);
+                }
+            }
+        }
+        Ok(recovered)
+    }
+}
+
+impl Default for PlanStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fail the plan stages the previous process died running. Runs after the
+/// journal has put their proposals back as failed at their checkpoints, which
+/// is where running the stage again picks up.
+pub async fn recover_interrupted(state: &SharedState) {
+    match state.plans.recover().await {
+        Ok(recovered) => {
+            for plan in &recovered {
+                warn!("A stage of execution plan {} (proposal {}) was interrupted", plan.id, plan.proposal_id);
+            }
+            if !recovered.is_empty() {
+                info!("Recovered {} interrupted execution plan(s)", recovered.len());
+            }
+        }
+        Err(e) => warn!("Could not check execution plans for interrupted stages: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, checkpoint: usize) -> ExecutionResult {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "proposalId": Uuid::nil(),
+            "success": success,
+            "dryRun": false,
+            "executedStatements": [],
+            "error": if success { None } else { Some("lock timeout") },
+            "checkpoint": checkpoint,
+            "durationMs": 0,
+            "executedAt": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stages_run_in_order_and_resume_after_failure() {
+        let proposal = SchemaProposal::new(Uuid::new_v4(), "Reorder".into(), String::new(), "1".into());
+        let migration = MigrationArtifacts::new(
+            format!(
+                "ALTER TABLE a ADD COLUMN x int;\n\nALTER TABLE a ADD COLUMN y int;\n\n\
+                 CREATE INDEX CONCURRENTLY idx_a_x ON a (x);\n\n{} swap tables\nALTER TABLE a RENAME TO a_old;",
+                reorder::GATE_COMMENT
+            ),
+            String::new(),
+        );
+        let mut plan = ExecutionPlan::build(&proposal, &migration, None, "1").unwrap();
+        let shape: Vec<(usize, usize, bool)> =
+            plan.stages.iter().map(|s| (s.first_statement, s.statement_count, s.transactional)).collect();
+        assert_eq!(shape, vec![(0, 2, true), (2, 1, false), (3, 1, true)]);
+        assert_eq!(plan.stages[2].gate.as_deref(), Some("swap tables"));
+
+        let now = Utc::now();
+        assert!(matches!(plan.start_stage(1, now), Err(AppError::Conflict(_))));
+        plan.start_stage(0, now).unwrap();
+        assert!(plan.start_stage(0, now).is_err());
+        plan.finish_stage(0, &result(true, 2), now);
+        assert_eq!(plan.status, PlanStatus::Ready);
+
+        plan.start_stage(1, now).unwrap();
+        plan.finish_stage(1, &result(false, 2), now);
+        assert_eq!(plan.status, PlanStatus::Failed);
+        assert_eq!(plan.next_stage().unwrap().checkpoint, 2);
+
+        plan.pause(now).unwrap();
+        assert!(plan.start_stage(1, now).is_err());
+        plan.resume(now).unwrap();
+        plan.start_stage(1, now).unwrap();
+        plan.finish_stage(1, &result(true, 3), now);
+        plan.start_stage(2, now).unwrap();
+        plan.finish_stage(2, &result(true, 4), now);
+        assert_eq!(plan.status, PlanStatus::Completed);
+        assert!(plan.cancel(now).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Proposal lifecycle policy enforcement
+//!
+//! Periodically expires old approvals, closes drafts that have gone stale, and
+//! escalates reviews that missed their SLA and break-glass changes still
+//! waiting for their retrospective.
+
+use crate::notifications::{Audience, Notification};
+use crate::pipeline::metadata::{AuditAction, AuditEntry, ProposalSummary};
+use crate::pipeline::retention;
+use crate::state::{AppState, SharedState};
+use chrono::Utc;
+use std::time::Duration;
+use tracing::info;
+
+/// Spawn the background task that enforces the proposal policy
+pub fn spawn_policy_sweeper(state: SharedState) -> tokio::task::JoinHandle<()> {
+    let interval_secs = state.pipeline_proposals.policy().sweep_interval_secs.max(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            enforce_policy(&state).await;
+
+            match state.idempotency.purge_expired().await {
+                Ok(0) => {}
+                Ok(removed) => info!("Purged {} expired idempotency keys", removed),
+                Err(e) => tracing::warn!("Failed to purge expired idempotency keys: {}", e),
+            }
+
+            if let Err(e) = retention::enforce(&state, &state.retention, Utc::now()).await {
+                tracing::warn!("Failed to apply the retention policy: {}", e);
+            }
+        }
+    })
+}
+
+/// Run one policy sweep, updating listings and recording audit entries
+pub async fn enforce_policy(state: &AppState) {
+    let result = match state.pipeline_proposals.sweep(Utc::now()).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::warn!("Proposal policy sweep failed: {}", e);
+            return;
+        }
+    };
+
+    for proposal in &result.expired_approvals {
+        info!("Approval for proposal '{}' ({}) expired; re-approval required", proposal.title, proposal.id);
+        state.metadata.add_proposal(ProposalSummary::from(proposal)).await;
+
+        let entry = AuditEntry::new(
+            AuditAction::ProposalApprovalExpired,
+            "system",
+            "proposal",
+            &proposal.id.to_string(),
+        )
+        .with_details("Approval expired; proposal returned to review");
+        state.metadata.record_audit_entry(entry).await;
+    }
+
+    let stale_days = state.pipeline_proposals.policy().stale_draft_days.unwrap_or_default();
+    for proposal in &result.closed_drafts {
+        info!("Closed stale draft '{}' ({}) created by {}", proposal.title, proposal.id, proposal.created_by);
+        state.metadata.add_proposal(ProposalSummary::from(proposal)).await;
+
+        let entry = AuditEntry::new(
+            AuditAction::ProposalClosed,
+            "system",
+            "proposal",
+            &proposal.id.to_string(),
+        )
+        .with_details(&format!(
+            "Stale draft closed after {} days without activity; notifying {}",
+            stale_days, proposal.created_by
+        ));
+        state.metadata.record_audit_entry(entry).await;
+
+        state.notifier.notify(
+            Notification::StaleDraftClosed { proposal: proposal.clone(), days: stale_days },
+            Audience::Users(vec![proposal.created_by.clone()]),
+        );
+    }
+
+    for proposal in &result.breached_reviews {
+        info!("Review of proposal '{}' ({}) is past its SLA; escalating", proposal.title, proposal.id);
+        state.metadata.add_proposal(ProposalSummary::from(proposal)).await;
+
+        let due = proposal.review_due_at.map(|d| d.to_rfc3339()).unwrap_or_default();
+        let entry = AuditEntry::new(
+            AuditAction::ReviewSlaBreached,
+            "system",
+            "proposal",
+            &proposal.id.to_string(),
+        )
+        .with_details(&format!("Review was due {}; escalated to admins", due));
+        state.metadata.record_audit_entry(entry).await;
+
+        state.notifier.notify(
+            Notification::ReviewSlaBreached { proposal: proposal.clone() },
+            Audience::Admins,
+        );
+    }
+
+    for proposal in &result.overdue_retrospectives {
+        info!("Retrospective of break-glass proposal '{}' ({}) is overdue; escalating", proposal.title, proposal.id);
+
+        let due = proposal.break_glass.as_ref().map(|b| b.review_due_at.to_rfc3339()).unwrap_or_default();
+        let entry = AuditEntry::new(
+            AuditAction::RetrospectiveOverdue,
+            "system",
+            "proposal",
+            &proposal.id.to_string(),
+        )
+        .with_project(proposal.project_id)
+        .with_details(&format!("Retrospective was due {}; escalated to admins", due));
+        state.metadata.record_audit_entry(entry).await;
+
+        state.notifier.notify(
+            Notification::RetrospectiveOverdue { proposal: proposal.clone() },
+            Audience::Admins,
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! Permission preflight
+//!
+//! A migration that fails halfway because the connection's role does not own
+//! a table leaves the database between two schemas. Before a real execution
+//! the orchestrator works out what each statement needs (ownership of the
+//! table it alters, CREATE on the schema it creates objects in, a table
+//! privilege for data changes) and asks the server whether the current role
+//! has it, refusing to start if anything is missing.
+//!
+//! Statements whose needs cannot be read from their text are listed as not
+//! checked; objects that do not exist yet and are not created earlier in the
+//! same migration are listed as unverified. Neither blocks an execution.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// What a statement needs from the executing role
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Requirement {
+    /// CREATE on the current database (new schemas)
+    CreateInDatabase,
+    /// CREATE on a schema; None is the current schema
+    CreateInSchema(Option<String>),
+    OwnSchema(String),
+    /// Ownership of a table, index, view or sequence
+    OwnRelation(String),
+    TablePrivilege(String, &'static str),
+}
+
+impl Requirement {
+    fn describe(&self) -> String {
+        match self {
+            Requirement::CreateInDatabase => "CREATE on the database".to_string(),
+            Requirement::CreateInSchema(Some(schema)) => format!("CREATE on schema {}", schema),
+            Requirement::CreateInSchema(None) => "CREATE on the current schema".to_string(),
+            Requirement::OwnSchema(schema) => format!("ownership of schema {}", schema),
+            Requirement::OwnRelation(name) => format!("ownership of {}", name),
+            Requirement::TablePrivilege(name, privilege) => format!("{} on {}", privilege, name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Missing,
+    /// The object was not found, so the privilege could not be checked
+    Unverified,
+    /// The statement's needs could not be worked out from its text
+    NotChecked,
+}
+
+/// What one statement needs and whether the role has it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementPermission {
+    /// Position in the migration, from 0
+    pub index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+    pub status: PermissionStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Privileges the executing role holds for a migration, statement by statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionReport {
+    pub role: String,
+    pub superuser: bool,
+    pub statements: Vec<StatementPermission>,
+    pub missing: usize,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl PermissionReport {
+    pub fn passed(&self) -> bool {
+        self.missing == 0
+    }
+
+    /// Error naming every statement the role cannot run
+    pub fn failure(&self) -> AppError {
+        let missing: Vec<String> = self
+            .statements
+            .iter()
+            .filter(|s| s.status == PermissionStatus::Missing)
+            .map(|s| format!("statement {} needs {}", s.index + 1, s.requirement.as_deref().unwrap_or("privileges")))
+            .collect();
+        AppError::Forbidden(format!(
+            "Role '{}' lacks privileges for {} statement(s), so nothing was executed: {}",
+            self.role,
+            self.missing,
+            missing.join("; ")
+        ))
+    }
+}
+
+/// Check the role's privileges for `statements[start_at..]`
+pub async fn check(client: &Client, statements: &[String], start_at: usize) -> Result<PermissionReport, AppError> {
+    let row = client
+        .query_one("SELECT current_user::text, rolsuper FROM pg_roles WHERE rolname = current_user", &[])
+        .await?;
+    let role: String = row.get(0);
+    let superuser: bool = row.get(1);
+
+    let mut created = Created::default();
+    let mut answers: HashMap<Requirement, Result<Option<bool>, String>> = HashMap::new();
+    let mut report = Vec::new();
+
+    for (index, statement) in statements.iter().enumerate().skip(start_at) {
+        let Some(requirement) = requirement(statement) else {
+            report.push(StatementPermission {
+                index,
+                requirement: None,
+                status: PermissionStatus::NotChecked,
+                detail: None,
+            });
+            continue;
+        };
+
+        let (status, detail) = if superuser {
+            (PermissionStatus::Granted, Some("superuser".to_string()))
+        } else if created.covers(&requirement) {
+            (PermissionStatus::Granted, Some("created earlier in this migration".to_string()))
+        } else {
+            if !answers.contains_key(&requirement) {
+                let answer = holds(client, &requirement).await.map_err(|e| e.to_string());
+                answers.insert(requirement.clone(), answer);
+            }
+            match &answers[&requirement] {
+                Ok(Some(true)) => (PermissionStatus::Granted, None),
+                Ok(Some(false)) => (PermissionStatus::Missing, None),
+                Ok(None) => (PermissionStatus::Unverified, Some("object not found".to_string())),
+                Err(e) => (PermissionStatus::Unverified, Some(e.clone())),
+            }
+        };
+
+        created.record(statement);
+        report.push(StatementPermission {
+            index,
+            requirement: Some(requirement.describe()),
+            status,
+            detail,
+        });
+    }
+
+    Ok(PermissionReport {
+        role,
+        superuser,
+        missing: report.iter().filter(|s| s.status == PermissionStatus::Missing).count(),
+        statements: report,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Ask the server whether the current role meets a requirement.
+/// None when the object does not exist.
+async fn holds(client: &Client, requirement: &Requirement) -> Result<Option<bool>, tokio_postgres::Error> {
+    let row = match requirement {
+        Requirement::CreateInDatabase => {
+            client.query_opt("SELECT has_database_privilege(current_database(), 'CREATE')", &[]).await?
+        }
+        Requirement::CreateInSchema(schema) => {
+            client
+                .query_opt(
+                    "SELECT has_schema_privilege(n.oid, 'CREATE') FROM pg_namespace n
+                     WHERE n.nspname = COALESCE($1, current_schema())",
+                    &[schema],
+                )
+                .await?
+        }
+        Requirement::OwnSchema(schema) => {
+            client
+                .query_opt(
+                    "SELECT pg_has_role(current_user, nspowner, 'USAGE') FROM pg_namespace WHERE nspname = $1",
+                    &[schema],
+                )
+                .await?
+        }
+        Requirement::OwnRelation(name) => {
+            client
+                .query_opt(
+                    "SELECT pg_has_role(current_user, c.relowner, 'USAGE') FROM pg_class c WHERE c.oid = to_regclass($1)",
+                    &[name],
+                )
+                .await?
+        }
+        Requirement::TablePrivilege(name, privilege) => {
+            client
+                .query_opt(
+                    "SELECT has_table_privilege(c.oid, $2) FROM pg_class c WHERE c.oid = to_regclass($1)",
+                    &[name, privilege],
+                )
+                .await?
+        }
+    };
+    Ok(row.map(|r| r.get(0)))
+}
+
+/// Schemas and relations the migration creates before a statement runs;
+/// the role will own them
+#[derive(Default)]
+struct Created {
+    schemas: HashSet<String>,
+    relations: HashSet<String>,
+}
+
+impl Created {
+    fn record(&mut self, statement: &str) {
+        let words = words(statement);
+        let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+        if upper.first().map(String::as_str) != Some("CREATE") {
+            return;
+        }
+        let mut i = 1;
+        skip(&upper, &mut i, &["OR", "REPLACE", "UNIQUE", "UNLOGGED", "MATERIALIZED"]);
+        let kind = upper.get(i).cloned().unwrap_or_default();
+        i += 1;
+        skip(&upper, &mut i, &["IF", "NOT", "EXISTS", "CONCURRENTLY"]);
+        let Some(name) = words.get(i).map(|w| object_name(w)) else {
+            return;
+        };
+        match kind.as_str() {
+            "SCHEMA" => {
+                self.schemas.insert(normalize(&name));
+            }
+            "TABLE" | "VIEW" | "SEQUENCE" | "INDEX" => {
+                self.relations.insert(normalize(&name));
+            }
+            _ => {}
+        }
+    }
+
+    fn covers(&self, requirement: &Requirement) -> bool {
+        match requirement {
+            Requirement::CreateInSchema(Some(schema)) | Requirement::OwnSchema(schema) => {
+                self.schemas.contains(&normalize(schema))
+            }
+            Requirement::OwnRelation(name) | Requirement::TablePrivilege(name, _) => {
+                self.relations.contains(&normalize(name))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What a statement needs, read from its leading keywords
+fn requirement(statement: &str) -> Option<Requirement> {
+    let words = words(statement);
+    let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+    let keyword = |i: usize| upper.get(i).map(String::as_str).unwrap_or_default();
+    let name_at = |i: usize| words.get(i).map(|w| object_name(w)).filter(|n| !n.is_empty());
+
+    match (keyword(0), keyword(1)) {
+        ("CREATE", "SCHEMA") => Some(Requirement::CreateInDatabase),
+        ("ALTER" | "DROP", "SCHEMA") => {
+            let mut i = 2;
+            skip(&upper, &mut i, &["IF", "EXISTS"]);
+            name_at(i).map(|n| Requirement::OwnSchema(unquote(&n)))
+        }
+        ("CREATE", _) => {
+            let mut i = 1;
+            skip(&upper, &mut i, &["OR", "REPLACE", "UNIQUE", "UNLOGGED", "MATERIALIZED"]);
+            match keyword(i) {
+                "INDEX" => {
+                    let on = upper.iter().skip(i).position(|w| w == "ON")? + i + 1;
+                    let mut i = on;
+                    skip(&upper, &mut i, &["ONLY"]);
+                    name_at(i).map(Requirement::OwnRelation)
+                }
+                "TABLE" | "VIEW" | "SEQUENCE" | "TYPE" | "DOMAIN" | "FUNCTION" | "PROCEDURE" => {
+                    i += 1;
+                    skip(&upper, &mut i, &["IF", "NOT", "EXISTS"]);
+                    let name = name_at(i)?;
+                    Some(Requirement::CreateInSchema(schema_of(&name)))
+                }
+                _ => None,
+            }
+        }
+        ("ALTER" | "DROP", "TABLE" | "INDEX" | "VIEW" | "SEQUENCE" | "MATERIALIZED") => {
+            let mut i = 1;
+            skip(&upper, &mut i, &["MATERIALIZED", "TABLE", "INDEX", "VIEW", "SEQUENCE"]);
+            skip(&upper, &mut i, &["CONCURRENTLY", "IF", "EXISTS", "ONLY"]);
+            name_at(i).map(Requirement::OwnRelation)
+        }
+        ("COMMENT", "ON") => {
+            let name = name_at(3)?;
+            match keyword(2) {
+                "TABLE" | "INDEX" | "VIEW" | "SEQUENCE" => Some(Requirement::OwnRelation(name)),
+                // Drop the column to get at the table
+                "COLUMN" => name.rsplit_once('.').map(|(table, _)| Requirement::OwnRelation(table.to_string())),
+                _ => None,
+            }
+        }
+        ("TRUNCATE", _) => {
+            let mut i = 1;
+            skip(&upper, &mut i, &["TABLE", "ONLY"]);
+            name_at(i).map(|n| Requirement::TablePrivilege(n, "TRUNCATE"))
+        }
+        ("UPDATE", _) => {
+            let mut i = 1;
+            skip(&upper, &mut i, &["ONLY"]);
+            name_at(i).map(|n| Requirement::TablePrivilege(n, "UPDATE"))
+        }
+        ("INSERT", "INTO") => name_at(2).map(|n| Requirement::TablePrivilege(n, "INSERT")),
+        ("DELETE", "FROM") => {
+            let mut i = 2;
+            skip(&upper, &mut i, &["ONLY"]);
+            name_at(i).map(|n| Requirement::TablePrivilege(n, "DELETE"))
+        }
+        // Maintenance commands take their table last, after any options
+        ("ANALYZE" | "VACUUM", _) if words.len() > 1 => {
+            let name = object_name(words.last()?);
+            (!name.is_empty() && !name.ends_with(')')).then_some(Requirement::OwnRelation(name))
+        }
+        _ => None,
+    }
+}
+
+/// Words of the statement with comment lines left out
+fn words(statement: &str) -> Vec<&str> {
+    statement
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("--"))
+        .flat_map(str::split_whitespace)
+        .collect()
+}
+
+fn skip(upper: &[String], i: &mut usize, optional: &[&str]) {
+    while upper.get(*i).is_some_and(|w| optional.contains(&w.as_str())) {
+        *i += 1;
+    }
+}
+
+/// Object name without a trailing column list or semicolon
+fn object_name(word: &str) -> String {
+    word.split('(').next().unwrap_or_default().trim_end_matches([';', ',']).to_string()
+}
+
+/// Schema part of a qualified name
+fn schema_of(name: &str) -> Option<String> {
+    name.rsplit_once('.').map(|(schema, _)| unquote(schema))
+}
+
+/// Identifier as PostgreSQL stores it: quoted names keep their case
+fn unquote(identifier: &str) -> String {
+    match identifier.strip_prefix('"').and_then(|i| i.strip_suffix('"')) {
+        Some(quoted) => quoted.to_string(),
+        None => identifier.to_lowercase(),
+    }
+}
+
+/// Comparable form of a name; unqualified names are taken to be in public
+fn normalize(name: &str) -> String {
+    let name = unquote(name);
+    name.strip_prefix("public.").map(str::to_string).unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requirements_from_statements() {
+        let cases = [
+            ("CREATE SCHEMA audit;", Some(Requirement::CreateInDatabase)),
+            (
+                "CREATE TABLE IF NOT EXISTS audit.events (\n  id BIGINT\n);",
+                Some(Requirement::CreateInSchema(Some("audit".to_string()))),
+            ),
+            ("CREATE TABLE events (id int);", Some(Requirement::CreateInSchema(None))),
+            (
+                "CREATE UNIQUE INDEX CONCURRENTLY idx_users_email ON ONLY users(email);",
+                Some(Requirement::OwnRelation("users".to_string())),
+            ),
+            ("ALTER TABLE IF EXISTS public.users ADD COLUMN age int;", Some(Requirement::OwnRelation("public.users".to_string()))),
+            ("DROP INDEX CONCURRENTLY IF EXISTS idx_old;", Some(Requirement::OwnRelation("idx_old".to_string()))),
+            ("COMMENT ON COLUMN users.email IS 'x';", Some(Requirement::OwnRelation("users".to_string()))),
+            (
+                "-- backfill batch 1/4\nUPDATE users SET age = 0 WHERE id < 100;",
+                Some(Requirement::TablePrivilege("users".to_string(), "UPDATE")),
+            ),
+            ("VACUUM (ANALYZE) orders;", Some(Requirement::OwnRelation("orders".to_string()))),
+            ("SELECT 1;", None),
+        ];
+        for (statement, expected) in cases {
+            assert_eq!(requirement(statement), expected, "{}", statement);
+        }
+
+        // Objects the migration creates are the role's own
+        let mut created = Created::default();
+        created.record("CREATE TABLE public.events (id int);");
+        assert!(created.covers(&Requirement::OwnRelation("events".to_string())));
+        assert!(!created.covers(&Requirement::OwnRelation("users".to_string())));
+    }
+}
@@ -0,0 +1,342 @@
+//! Live presence and edit locks on proposals
+//!
+//! Everyone with a proposal open holds a WebSocket session on it and shows
+//! up as a viewer; a session switches to editing while its user changes the
+//! draft. Whoever is about to make larger edits can take the proposal's edit
+//! lock. A soft lock only warns others that their edits may collide; an
+//! exclusive lock rejects their edits until it is released, expires or is
+//! taken over. Locks expire unless their holder keeps a session alive, so a
+//! closed laptop does not block a draft for good.
+//!
+//! All of this lives in memory: presence is only meaningful while the
+//! sessions behind it are connected.
+
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+/// A session that has not pinged for this long no longer counts as present
+pub const PRESENCE_TIMEOUT_SECS: i64 = 60;
+
+/// How long a lock lasts without its holder being present
+pub const LOCK_TTL_SECS: i64 = 120;
+
+/// What a user is doing with a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceActivity {
+    Viewing,
+    Editing,
+}
+
+/// One user with a proposal open
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPresence {
+    pub user_id: String,
+    pub email: String,
+    pub activity: PresenceActivity,
+    pub connected_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// A user's claim on editing a draft
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditLock {
+    pub holder: String,
+    pub holder_email: String,
+    /// Exclusive locks reject other users' edits; soft locks only warn
+    pub exclusive: bool,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Holder of the lock this one replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taken_over_from: Option<String>,
+}
+
+impl EditLock {
+    fn is_active(&self) -> bool {
+        Utc::now() < self.expires_at
+    }
+}
+
+/// Who is on a proposal right now
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceSnapshot {
+    pub proposal_id: Uuid,
+    /// One entry per user, editing if any of their sessions is
+    pub users: Vec<UserPresence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock: Option<EditLock>,
+}
+
+/// In-memory presence sessions and edit locks, with a channel announcing
+/// which proposal changed
+#[derive(Clone)]
+pub struct PresenceHub {
+    sessions: Arc<RwLock<HashMap<Uuid, HashMap<Uuid, UserPresence>>>>,
+    locks: Arc<RwLock<HashMap<Uuid, EditLock>>>,
+    changes: broadcast::Sender<Uuid>,
+}
+
+impl Default for PresenceHub {
+    fn default() -> Self {
+        Self {
+            sessions: Arc::default(),
+            locks: Arc::default(),
+            changes: broadcast::channel(256).0,
+        }
+    }
+}
+
+impl PresenceHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Proposal ids whose presence or lock changed
+    pub fn subscribe(&self) -> broadcast::Receiver<Uuid> {
+        self.changes.subscribe()
+    }
+
+    fn changed(&self, proposal_id: Uuid) {
+        // Nobody listening is fine
+        let _ = self.changes.send(proposal_id);
+    }
+
+    /// Open a session as a viewer; returns its id
+    pub async fn join(&self, proposal_id: Uuid, user_id: &str, email: &str) -> Uuid {
+        let now = Utc::now();
+        let session_id = Uuid::new_v4();
+        let presence = UserPresence {
+            user_id: user_id.to_string(),
+            email: email.to_string(),
+            activity: PresenceActivity::Viewing,
+            connected_at: now,
+            last_seen: now,
+        };
+        self.sessions.write().await.entry(proposal_id).or_default().insert(session_id, presence);
+        self.changed(proposal_id);
+        session_id
+    }
+
+    /// Keep a session alive, optionally switching its activity; also renews
+    /// the user's lock on the proposal
+    pub async fn touch(&self, proposal_id: Uuid, session_id: Uuid, activity: Option<PresenceActivity>) {
+        let now = Utc::now();
+        let mut switched = false;
+        let user_id = {
+            let mut sessions = self.sessions.write().await;
+            let Some(presence) = sessions.get_mut(&proposal_id).and_then(|s| s.get_mut(&session_id)) else {
+                return;
+            };
+            presence.last_seen = now;
+            if let Some(activity) = activity.filter(|a| *a != presence.activity) {
+                presence.activity = activity;
+                switched = true;
+            }
+            presence.user_id.clone()
+        };
+
+        if let Some(lock) = self.locks.write().await.get_mut(&proposal_id) {
+            if lock.holder == user_id && lock.is_active() {
+                lock.expires_at = now + Duration::seconds(LOCK_TTL_SECS);
+            }
+        }
+        if switched {
+            self.changed(proposal_id);
+        }
+    }
+
+    /// Close a session
+    pub async fn leave(&self, proposal_id: Uuid, session_id: Uuid) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(open) = sessions.get_mut(&proposal_id) {
+            open.remove(&session_id);
+            if open.is_empty() {
+                sessions.remove(&proposal_id);
+            }
+        }
+        drop(sessions);
+        self.changed(proposal_id);
+    }
+
+    /// Present users and the active lock, dropping stale sessions and expired locks
+    pub async fn snapshot(&self, proposal_id: Uuid) -> PresenceSnapshot {
+        let cutoff = Utc::now() - Duration::seconds(PRESENCE_TIMEOUT_SECS);
+        let mut users: Vec<UserPresence> = Vec::new();
+        {
+            let mut sessions = self.sessions.write().await;
+            if let Some(open) = sessions.get_mut(&proposal_id) {
+                open.retain(|_, p| p.last_seen >= cutoff);
+                for presence in open.values() {
+                    match users.iter_mut().find(|u| u.user_id == presence.user_id) {
+                        Some(user) => {
+                            if presence.activity == PresenceActivity::Editing {
+                                user.activity = PresenceActivity::Editing;
+                            }
+                            user.connected_at = user.connected_at.min(presence.connected_at);
+                            user.last_seen = user.last_seen.max(presence.last_seen);
+                        }
+                        None => users.push(presence.clone()),
+                    }
+                }
+            }
+        }
+        users.sort_by(|a, b| a.connected_at.cmp(&b.connected_at));
+
+        let lock = {
+            let mut locks = self.locks.write().await;
+            locks.retain(|_, l| l.is_active());
+            locks.get(&proposal_id).cloned()
+        };
+
+        PresenceSnapshot { proposal_id, users, lock }
+    }
+
+    /// Take the edit lock on a draft. Another user's exclusive lock is only
+    /// replaced with `takeover`; another user's soft lock is replaced and
+    /// recorded as taken over.
+    pub async fn acquire_lock(
+        &self,
+        proposal_id: Uuid,
+        user_id: &str,
+        email: &str,
+        exclusive: bool,
+        takeover: bool,
+    ) -> Result<EditLock, AppError> {
+        let now = Utc::now();
+        let mut locks = self.locks.write().await;
+        let current = locks.get(&proposal_id).filter(|l| l.is_active());
+
+        let taken_over_from = match current {
+            Some(lock) if lock.holder == user_id => lock.taken_over_from.clone(),
+            Some(lock) if lock.exclusive && !takeover => {
+                return Err(AppError::Conflict(format!(
+                    "{} holds an exclusive edit lock on this proposal until {}; take it over to edit",
+                    lock.holder_email,
+                    lock.expires_at.to_rfc3339()
+                )));
+            }
+            Some(lock) => Some(lock.holder.clone()),
+            None => None,
+        };
+        let acquired_at = match current {
+            Some(lock) if lock.holder == user_id => lock.acquired_at,
+            _ => now,
+        };
+
+        let lock = EditLock {
+            holder: user_id.to_string(),
+            holder_email: email.to_string(),
+            exclusive,
+            acquired_at,
+            expires_at: now + Duration::seconds(LOCK_TTL_SECS),
+            taken_over_from,
+        };
+        locks.insert(proposal_id, lock.clone());
+        drop(locks);
+        self.changed(proposal_id);
+        Ok(lock)
+    }
+
+    /// Release the lock; only its holder may, unless `force`. Returns the
+    /// released lock, or None if there was none.
+    pub async fn release_lock(&self, proposal_id: Uuid, user_id: &str, force: bool) -> Result<Option<EditLock>, AppError> {
+        let mut locks = self.locks.write().await;
+        let Some(lock) = locks.get(&proposal_id).filter(|l| l.is_active()) else {
+            locks.remove(&proposal_id);
+            return Ok(None);
+        };
+        if lock.holder != user_id && !force {
+            return Err(AppError::Forbidden(format!(
+                "The edit lock is held by {}; only they or an admin can release it",
+                lock.holder_email
+            )));
+        }
+        let released = locks.remove(&proposal_id);
+        drop(locks);
+        self.changed(proposal_id);
+        Ok(released)
+    }
+
+    /// Whether a user may edit a proposal now: an error under another user's
+    /// exclusive lock, otherwise a warning if someone else holds a soft lock
+    /// or is editing
+    pub async fn check_edit(&self, proposal_id: Uuid, user_id: &str) -> Result<Option<String>, AppError> {
+        let snapshot = self.snapshot(proposal_id).await;
+        if let Some(lock) = snapshot.lock.filter(|l| l.holder != user_id) {
+            if lock.exclusive {
+                return Err(AppError::Conflict(format!(
+                    "{} holds an exclusive edit lock on this proposal until {}; take it over to edit",
+                    lock.holder_email,
+                    lock.expires_at.to_rfc3339()
+                )));
+            }
+            return Ok(Some(format!("{} is also editing this proposal", lock.holder_email)));
+        }
+
+        let editors: Vec<&str> = snapshot
+            .users
+            .iter()
+            .filter(|u| u.user_id != user_id && u.activity == PresenceActivity::Editing)
+            .map(|u| u.email.as_str())
+            .collect();
+        Ok(match editors.is_empty() {
+            true => None,
+            false => Some(format!("{} also editing this proposal", match editors.len() {
+                1 => format!("{} is", editors[0]),
+                _ => format!("{} are", editors.join(", ")),
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_locks_warn_block_and_take_over() {
+        let hub = PresenceHub::new();
+        let proposal_id = Uuid::new_v4();
+        let mut changes = hub.subscribe();
+
+        let alice = hub.join(proposal_id, "1", "alice@example.com").await;
+        let second_tab = hub.join(proposal_id, "1", "alice@example.com").await;
+        hub.join(proposal_id, "2", "bob@example.com").await;
+        assert_eq!(changes.recv().await.unwrap(), proposal_id);
+
+        // Sessions of one user merge; editing in any of them shows
+        hub.touch(proposal_id, second_tab, Some(PresenceActivity::Editing)).await;
+        let snapshot = hub.snapshot(proposal_id).await;
+        assert_eq!(snapshot.users.len(), 2);
+        assert_eq!(snapshot.users[0].activity, PresenceActivity::Editing);
+        assert_eq!(
+            hub.check_edit(proposal_id, "2").await.unwrap().as_deref(),
+            Some("alice@example.com is also editing this proposal")
+        );
+        assert_eq!(hub.check_edit(proposal_id, "1").await.unwrap(), None);
+
+        // An exclusive lock blocks others until taken over
+        hub.acquire_lock(proposal_id, "1", "alice@example.com", true, false).await.unwrap();
+        assert!(matches!(hub.check_edit(proposal_id, "2").await, Err(AppError::Conflict(_))));
+        assert!(hub.acquire_lock(proposal_id, "2", "bob@example.com", false, false).await.is_err());
+        assert!(hub.release_lock(proposal_id, "2", false).await.is_err());
+
+        let lock = hub.acquire_lock(proposal_id, "2", "bob@example.com", false, true).await.unwrap();
+        assert_eq!(lock.taken_over_from.as_deref(), Some("1"));
+        hub.touch(proposal_id, alice, Some(PresenceActivity::Viewing)).await;
+        hub.touch(proposal_id, second_tab, Some(PresenceActivity::Viewing)).await;
+        assert!(hub.check_edit(proposal_id, "1").await.unwrap().is_some());
+
+        assert!(hub.release_lock(proposal_id, "2", false).await.unwrap().is_some());
+        assert_eq!(hub.check_edit(proposal_id, "1").await.unwrap(), None);
+    }
+}
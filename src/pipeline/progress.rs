@@ -0,0 +1,275 @@
+//! Live execution progress
+//!
+//! While a migration runs, the orchestrator records which statement its
+//! session is on, and a poller on a second pooled connection samples
+//! PostgreSQL's progress views for that session's backend:
+//! `pg_stat_progress_create_index` (CREATE INDEX, REINDEX),
+//! `pg_stat_progress_cluster` (CLUSTER, VACUUM FULL) and
+//! `pg_stat_progress_copy` (COPY, PostgreSQL 14+). A statement none of them
+//! covers reports no fraction of its own and counts as not started until it
+//! finishes.
+
+use crate::pipeline::activity::{ActivityHub, ProposalActivity};
+use crate::pipeline::advisory_lock::LockHolder;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{Client, Pool};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// How often the progress views are sampled
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One sample of a progress view
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    /// View the sample came from: `create_index`, `cluster` or `copy`
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    pub done: i64,
+    /// Zero when PostgreSQL does not know the total yet
+    pub total: i64,
+    /// What `done` and `total` count: blocks, tuples or bytes
+    pub unit: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    pub sampled_at: DateTime<Utc>,
+}
+
+impl OperationProgress {
+    fn new(source: &str, command: Option<String>, phase: Option<String>, done: i64, total: i64, unit: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            command,
+            phase,
+            done,
+            total,
+            unit: unit.to_string(),
+            percent: (total > 0).then(|| round((done as f64 / total as f64 * 100.0).clamp(0.0, 100.0))),
+            sampled_at: Utc::now(),
+        }
+    }
+}
+
+/// Where a running execution is
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionProgress {
+    pub proposal_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub total_statements: usize,
+    pub resumed_from: usize,
+    /// Statement running now, from 0
+    pub current_statement: usize,
+    pub statement: String,
+    /// Backend running the migration, as seen in `pg_stat_activity`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend_pid: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<OperationProgress>,
+    pub percent_complete: f64,
+    /// True while the run waits for another session's migration lock
+    pub waiting_for_lock: bool,
+    /// Session holding that lock, when it could be identified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_holder: Option<LockHolder>,
+}
+
+/// Share of the run's statements done, counting the current one's progress
+pub fn percent_complete(
+    total_statements: usize,
+    resumed_from: usize,
+    current_statement: usize,
+    operation: Option<&OperationProgress>,
+) -> f64 {
+    let remaining = total_statements.saturating_sub(resumed_from);
+    if remaining == 0 {
+        return 100.0;
+    }
+    let current = operation.and_then(|o| o.percent).unwrap_or(0.0) / 100.0;
+    let done = current_statement.saturating_sub(resumed_from) as f64 + current;
+    round((done / remaining as f64 * 100.0).min(100.0))
+}
+
+fn round(percent: f64) -> f64 {
+    (percent * 10.0).round() / 10.0
+}
+
+/// Progress of every running execution, by proposal
+#[derive(Clone, Default)]
+pub struct ExecutionMonitor {
+    runs: Arc<RwLock<HashMap<Uuid, ExecutionProgress>>>,
+    activity: Option<ActivityHub>,
+}
+
+impl ExecutionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish every progress update on `activity`
+    pub fn with_activity(mut self, activity: ActivityHub) -> Self {
+        self.activity = Some(activity);
+        self
+    }
+
+    fn publish(&self, progress: &ExecutionProgress) {
+        if let Some(activity) = &self.activity {
+            activity.publish(
+                progress.proposal_id,
+                ProposalActivity::ExecutionProgress { progress: Box::new(progress.clone()) },
+            );
+        }
+    }
+
+    pub async fn begin(&self, proposal_id: Uuid, total_statements: usize, start_at: usize, backend_pid: Option<i32>) {
+        let progress = ExecutionProgress {
+            proposal_id,
+            started_at: Utc::now(),
+            total_statements,
+            resumed_from: start_at,
+            current_statement: start_at,
+            statement: String::new(),
+            backend_pid,
+            operation: None,
+            percent_complete: percent_complete(total_statements, start_at, start_at, None),
+            waiting_for_lock: false,
+            lock_holder: None,
+        };
+        self.publish(&progress);
+        self.runs.write().await.insert(proposal_id, progress);
+    }
+
+    pub async fn waiting_for_lock(&self, proposal_id: Uuid, waiting: bool, holder: Option<LockHolder>) {
+        if let Some(run) = self.runs.write().await.get_mut(&proposal_id) {
+            run.waiting_for_lock = waiting;
+            run.lock_holder = holder;
+            self.publish(run);
+        }
+    }
+
+    pub async fn statement_started(&self, proposal_id: Uuid, index: usize, statement: &str) {
+        if let Some(run) = self.runs.write().await.get_mut(&proposal_id) {
+            run.current_statement = index;
+            run.statement = statement.to_string();
+            run.operation = None;
+            run.percent_complete = percent_complete(run.total_statements, run.resumed_from, index, None);
+            self.publish(run);
+        }
+    }
+
+    pub async fn record(&self, proposal_id: Uuid, operation: Option<OperationProgress>) {
+        if let Some(run) = self.runs.write().await.get_mut(&proposal_id) {
+            run.percent_complete = percent_complete(
+                run.total_statements,
+                run.resumed_from,
+                run.current_statement,
+                operation.as_ref(),
+            );
+            run.operation = operation;
+            self.publish(run);
+        }
+    }
+
+    pub async fn finish(&self, proposal_id: Uuid) {
+        self.runs.write().await.remove(&proposal_id);
+    }
+
+    pub async fn get(&self, proposal_id: Uuid) -> Option<ExecutionProgress> {
+        self.runs.read().await.get(&proposal_id).cloned()
+    }
+}
+
+/// Sample the progress views for one backend. Views missing on older servers
+/// are skipped.
+pub async fn sample(client: &Client, pid: i32) -> Option<OperationProgress> {
+    if let Ok(Some(row)) = client
+        .query_opt(
+            "SELECT command, phase, blocks_done, blocks_total, tuples_done, tuples_total
+             FROM pg_stat_progress_create_index WHERE pid = $1",
+            &[&pid],
+        )
+        .await
+    {
+        let (blocks_total, tuples_total): (i64, i64) = (row.get(3), row.get(5));
+        return Some(if blocks_total > 0 {
+            OperationProgress::new("create_index", row.get(0), row.get(1), row.get(2), blocks_total, "blocks")
+        } else {
+            OperationProgress::new("create_index", row.get(0), row.get(1), row.get(4), tuples_total, "tuples")
+        });
+    }
+
+    if let Ok(Some(row)) = client
+        .query_opt(
+            "SELECT command, phase, heap_blks_scanned, heap_blks_total
+             FROM pg_stat_progress_cluster WHERE pid = $1",
+            &[&pid],
+        )
+        .await
+    {
+        return Some(OperationProgress::new("cluster", row.get(0), row.get(1), row.get(2), row.get(3), "blocks"));
+    }
+
+    if let Ok(Some(row)) = client
+        .query_opt(
+            "SELECT command, bytes_processed, bytes_total, tuples_processed
+             FROM pg_stat_progress_copy WHERE pid = $1",
+            &[&pid],
+        )
+        .await
+    {
+        let bytes_total: i64 = row.get(2);
+        return Some(if bytes_total > 0 {
+            OperationProgress::new("copy", row.get(0), None, row.get(1), bytes_total, "bytes")
+        } else {
+            OperationProgress::new("copy", row.get(0), None, row.get(3), 0, "tuples")
+        });
+    }
+
+    None
+}
+
+/// Poll the progress views for `pid` until the run finishes or the task is aborted
+pub fn spawn_poller(monitor: ExecutionMonitor, pool: Pool, proposal_id: Uuid, pid: i32) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if monitor.get(proposal_id).await.is_none() {
+                break;
+            }
+            // A busy pool only delays the next sample
+            let Ok(client) = pool.get().await else {
+                continue;
+            };
+            let operation = sample(&client, pid).await;
+            monitor.record(proposal_id, operation).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_counts_the_running_statement() {
+        let index_build = OperationProgress::new("create_index", None, None, 250, 1000, "blocks");
+        assert_eq!(index_build.percent, Some(25.0));
+        assert_eq!(OperationProgress::new("copy", None, None, 40, 0, "tuples").percent, None);
+
+        // Statements 0 and 1 done, statement 2 a quarter through
+        assert_eq!(percent_complete(4, 0, 2, Some(&index_build)), 56.3);
+        assert_eq!(percent_complete(4, 0, 2, None), 50.0);
+        // Resumed at statement 2: only the remaining two count
+        assert_eq!(percent_complete(4, 2, 2, Some(&index_build)), 12.5);
+        assert_eq!(percent_complete(0, 0, 0, None), 100.0);
+    }
+}
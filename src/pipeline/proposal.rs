@@ -1,46 +1,802 @@
 //! Proposal service - Schema change proposal management (legacy)
 
+use crate::config::ProposalPolicyConfig;
 use crate::error::AppError;
+use crate::pipeline::activity::{ActivityHub, Marks};
+use crate::i18n::{Message, Translations};
+use crate::pipeline::break_glass::BreakGlass;
+use crate::pipeline::checklist::{self, ChecklistItem};
+use crate::pipeline::explain::CostSummary;
+use crate::pipeline::impact::BlastRadiusReport;
+use crate::pipeline::orchestrator::{split_statements, ExecutionResult, Orchestrator};
+use crate::pipeline::patch::{apply_patch, PatchOperation};
+use crate::pipeline::risk_history::{self, RiskRecord};
+use crate::pipeline::sla::ReviewSla;
+use crate::pipeline::summary::ExecutiveSummary;
+use crate::pipeline::teams::{self, Team, TeamApproval};
+use crate::pipeline::templating::{self, ParameterValues, TemplateParameter};
 use crate::pipeline::types::SchemaChange;
-use chrono::{DateTime, Utc};
+use crate::pipeline::uniqueness::{self, DuplicateCheck};
+use crate::pipeline::verification::VerificationReport;
+use crate::storage::{MemoryProposalBackend, ProposalBackend};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use uuid::Uuid;
 
 /// Proposal service for managing schema change proposals
 pub struct ProposalService {
-    proposals: Arc<RwLock<HashMap<Uuid, SchemaProposal>>>,
+    backend: Arc<dyn ProposalBackend>,
+    policy: ProposalPolicyConfig,
+    activity: ActivityHub,
 }
 
 impl ProposalService {
     pub fn new() -> Self {
+        Self::with_policy(ProposalPolicyConfig::default())
+    }
+
+    /// In-memory service
+    pub fn with_policy(policy: ProposalPolicyConfig) -> Self {
         Self {
-            proposals: Arc::new(RwLock::new(HashMap::new())),
+            backend: Arc::new(MemoryProposalBackend::default()),
+            policy,
+            activity: ActivityHub::new(),
         }
     }
 
+    /// Keep proposals in `backend`
+    pub fn with_backend(mut self, backend: Arc<dyn ProposalBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Publish what writes do to proposals on `activity`
+    pub fn with_activity(mut self, activity: ActivityHub) -> Self {
+        self.activity = activity;
+        self
+    }
+
+    pub fn policy(&self) -> &ProposalPolicyConfig {
+        &self.policy
+    }
+
     pub async fn create(&self, proposal: SchemaProposal) -> Result<SchemaProposal, AppError> {
-        let mut proposals = self.proposals.write().await;
-        proposals.insert(proposal.id, proposal.clone());
+        self.backend.insert(&proposal).await?;
+        Ok(proposal)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<SchemaProposal>, AppError> {
+        self.backend.get(id).await
+    }
+
+    /// Apply `change` to a stored proposal with no other write in between
+    pub async fn update(
+        &self,
+        id: Uuid,
+        change: impl FnOnce(&mut SchemaProposal) + Send,
+    ) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self.modify(id, |proposal| {
+            change(proposal);
+            Ok(())
+        })
+        .await?;
+        Ok(proposal)
+    }
+
+    pub async fn list(&self) -> Result<Vec<SchemaProposal>, AppError> {
+        self.backend.list().await
+    }
+
+    /// Run `change` on the stored proposal, announce what it did and return
+    /// the proposal with whatever `change` returned. An error leaves the
+    /// proposal as it was.
+    async fn modify<T: Send>(
+        &self,
+        id: Uuid,
+        change: impl FnOnce(&mut SchemaProposal) -> Result<T, AppError> + Send,
+    ) -> Result<(SchemaProposal, T), AppError> {
+        let mut marks = None;
+        let mut output = None;
+        let proposal = self
+            .backend
+            .update(
+                id,
+                Box::new(|proposal| {
+                    marks = Some(Marks::of(proposal));
+                    output = Some(change(proposal)?);
+                    Ok(())
+                }),
+            )
+            .await?;
+        if let Some(marks) = marks {
+            self.activity.announce(&marks, &proposal);
+        }
+        let output = output.ok_or_else(|| AppError::Internal(format!("Proposal {} was not updated", id)))?;
+        Ok((proposal, output))
+    }
+
+    /// Append changes to a draft proposal
+    pub async fn add_changes(&self, id: Uuid, changes: Vec<SchemaChange>) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::Draft {
+                    return Err(AppError::BadRequest(
+                        "Cannot modify a proposal that is not in draft status".to_string()
+                    ));
+                }
+
+                proposal.changes.extend(changes);
+                proposal.migration = None;
+                proposal.approved_migration_version = None;
+                proposal.risk_analysis = None;
+                proposal.updated_at = Utc::now();
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Move a draft proposal to pending review, starting the review SLA clock
+    pub async fn submit(&self, id: Uuid, sla: Option<&ReviewSla>) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::Draft {
+                    return Err(AppError::BadRequest("Only draft proposals can be submitted".to_string()));
+                }
+                if proposal.changes.is_empty() {
+                    return Err(AppError::BadRequest("Cannot submit a proposal without changes".to_string()));
+                }
+                templating::validate_declaration(&proposal.parameters, &proposal.changes)?;
+
+                let now = Utc::now();
+                proposal.set_status(ProposalStatus::PendingReview, now);
+                proposal.submitted_at = Some(now);
+                proposal.review_due_at = sla.and_then(|sla| sla.due_at(now));
+                proposal.sla_breached_at = None;
+                proposal.updated_at = now;
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Approve a proposal that is pending review. The approval expires per policy.
+    /// A templated proposal is rendered for `environment` and the result pinned.
+    pub async fn approve(&self, id: Uuid, approver: &str, environment: &str) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::PendingReview {
+                    return Err(AppError::BadRequest("Only proposals pending review can be approved".to_string()));
+                }
+                let blockers = proposal.approval_blockers(&self.policy);
+                if !blockers.is_empty() {
+                    return Err(AppError::Conflict(format!("Proposal cannot be approved yet: {}", blockers.join("; "))));
+                }
+
+                proposal.mark_approved(approver, &self.policy, Utc::now(), environment)
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Record a team member's approval for each assigned team they belong to.
+    /// The proposal is approved once every team in `teams` has enough
+    /// approvals; until then it stays pending review.
+    pub async fn approve_for_teams(
+        &self,
+        id: Uuid,
+        approver: &str,
+        teams: &[Team],
+        environment: &str,
+    ) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::PendingReview {
+                    return Err(AppError::BadRequest("Only proposals pending review can be approved".to_string()));
+                }
+                let blockers = proposal.approval_blockers(&self.policy);
+                if !blockers.is_empty() {
+                    return Err(AppError::Conflict(format!("Proposal cannot be approved yet: {}", blockers.join("; "))));
+                }
+
+                let own_teams: Vec<&Team> = teams.iter().filter(|t| t.is_member(approver)).collect();
+                if own_teams.is_empty() {
+                    return Err(AppError::Forbidden(format!(
+                        "Only members of the reviewing team(s) {} can approve this proposal",
+                        teams.iter().map(|t| t.slug.as_str()).collect::<Vec<_>>().join(", ")
+                    )));
+                }
+
+                let now = Utc::now();
+                for team in own_teams {
+                    let already = proposal.team_approvals.iter().any(|a| a.team == team.slug && a.approver == approver);
+                    if !already {
+                        proposal.team_approvals.push(TeamApproval {
+                            team: team.slug.clone(),
+                            approver: approver.to_string(),
+                            approved_at: now,
+                        });
+                    }
+                }
+
+                if teams::reviews(proposal, teams).iter().all(|r| r.complete) {
+                    proposal.mark_approved(approver, &self.policy, now, environment)?;
+                } else {
+                    proposal.updated_at = now;
+                }
+                Ok(())
+            })
+            .await?;
         Ok(proposal)
     }
 
-    pub async fn get(&self, id: Uuid) -> Option<SchemaProposal> {
-        let proposals = self.proposals.read().await;
-        proposals.get(&id).cloned()
+    /// Tick or untick a checklist item of a proposal pending review
+    pub async fn check_item(
+        &self,
+        id: Uuid,
+        item_id: &str,
+        checked_by: &str,
+        checked: bool,
+        note: Option<String>,
+    ) -> Result<(SchemaProposal, ChecklistItem), AppError> {
+        self.modify(id, |proposal| {
+            if proposal.status != ProposalStatus::PendingReview {
+                return Err(AppError::BadRequest("Only checklists of proposals pending review can be ticked".to_string()));
+            }
+            let item = proposal
+                .checklist
+                .iter_mut()
+                .find(|item| item.id == item_id)
+                .ok_or_else(|| AppError::NotFound(format!("Checklist item '{}' not found", item_id)))?;
+
+            let now = Utc::now();
+            item.checked_by = checked.then(|| checked_by.to_string());
+            item.checked_at = checked.then_some(now);
+            item.note = note;
+            let item = item.clone();
+            proposal.updated_at = now;
+            Ok(item)
+        })
+        .await
     }
 
-    pub async fn update(&self, proposal: SchemaProposal) -> Result<SchemaProposal, AppError> {
-        let mut proposals = self.proposals.write().await;
-        proposals.insert(proposal.id, proposal.clone());
+    /// Sign off one statement of the generated migration, replacing the
+    /// approver's earlier sign-off on it
+    pub async fn approve_statement(
+        &self,
+        id: Uuid,
+        index: usize,
+        approver: &str,
+        comment: Option<String>,
+    ) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::PendingReview {
+                    return Err(AppError::BadRequest("Only proposals pending review can be signed off".to_string()));
+                }
+                let statements = proposal.statements();
+                let statement = statements.get(index).ok_or_else(|| statement_not_found(index, statements.len()))?;
+                let statement_hash = statement_hash(statement);
+
+                let now = Utc::now();
+                proposal.statement_approvals.retain(|a| !(a.index == index && a.approver == approver));
+                proposal.statement_approvals.push(StatementApproval {
+                    index,
+                    statement_hash,
+                    approver: approver.to_string(),
+                    comment,
+                    approved_at: now,
+                });
+                proposal.updated_at = now;
+                Ok(())
+            })
+            .await?;
         Ok(proposal)
     }
 
-    pub async fn list(&self) -> Vec<SchemaProposal> {
-        let proposals = self.proposals.read().await;
-        proposals.values().cloned().collect()
+    /// Add a comment, or a reply to a thread. Replies join the thread's root
+    /// and take its target.
+    pub async fn add_comment(&self, id: Uuid, mut comment: Comment) -> Result<Comment, AppError> {
+        let (_, comment) = self
+            .modify(id, |proposal| {
+                if let Some(parent_id) = comment.reply_to {
+                    let parent = proposal
+                        .comments
+                        .iter()
+                        .find(|c| c.id == parent_id)
+                        .ok_or_else(|| AppError::NotFound(format!("Comment {} not found", parent_id)))?;
+                    let root_id = parent.reply_to.unwrap_or(parent.id);
+                    let root = proposal
+                        .comments
+                        .iter()
+                        .find(|c| c.id == root_id)
+                        .ok_or_else(|| AppError::NotFound(format!("Comment {} not found", root_id)))?;
+                    comment.reply_to = Some(root.id);
+                    comment.target = root.target.clone();
+                } else {
+                    match comment.target {
+                        CommentTarget::Proposal => {}
+                        CommentTarget::Change { index } if index < proposal.changes.len() => {}
+                        CommentTarget::Change { index } => {
+                            return Err(AppError::BadRequest(format!(
+                                "Change {} not found; the proposal has {} changes",
+                                index,
+                                proposal.changes.len()
+                            )));
+                        }
+                        CommentTarget::Statement { index } => {
+                            if proposal.migration.is_none() {
+                                return Err(AppError::BadRequest(
+                                    "Generate the migration before commenting on its statements".to_string(),
+                                ));
+                            }
+                            let count = proposal.statements().len();
+                            if index >= count {
+                                return Err(statement_not_found(index, count));
+                            }
+                        }
+                    }
+                }
+
+                proposal.comments.push(comment.clone());
+                proposal.updated_at = Utc::now();
+                Ok(comment)
+            })
+            .await?;
+        Ok(comment)
+    }
+
+    /// Resolve (or reopen) a comment thread. Only the thread's author or a
+    /// moderator may do so.
+    pub async fn resolve_thread(
+        &self,
+        id: Uuid,
+        comment_id: Uuid,
+        by: &str,
+        moderator: bool,
+        resolved: bool,
+    ) -> Result<Comment, AppError> {
+        let (_, comment) = self
+            .modify(id, |proposal| {
+                let comment = proposal
+                    .comments
+                    .iter_mut()
+                    .find(|c| c.id == comment_id)
+                    .ok_or_else(|| AppError::NotFound(format!("Comment {} not found", comment_id)))?;
+
+                if comment.reply_to.is_some() {
+                    return Err(AppError::BadRequest("Only the first comment of a thread can be resolved".to_string()));
+                }
+                if !moderator && comment.author != by {
+                    return Err(AppError::Forbidden("Only the thread's author or an admin can resolve it".to_string()));
+                }
+
+                let now = Utc::now();
+                if resolved {
+                    comment.resolved_by = Some(by.to_string());
+                    comment.resolved_at = Some(now);
+                } else {
+                    comment.resolved_by = None;
+                    comment.resolved_at = None;
+                }
+                let comment = comment.clone();
+                proposal.updated_at = now;
+                Ok(comment)
+            })
+            .await?;
+        Ok(comment)
+    }
+
+    /// Reject a proposal that is pending review
+    pub async fn reject(&self, id: Uuid) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::PendingReview {
+                    return Err(AppError::BadRequest("Only proposals pending review can be rejected".to_string()));
+                }
+
+                let now = Utc::now();
+                proposal.set_status(ProposalStatus::Rejected, now);
+                proposal.updated_at = now;
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Store the parameter values to use when executing in `environment`;
+    /// empty values remove the environment's entry
+    pub async fn set_parameter_values(
+        &self,
+        id: Uuid,
+        environment: &str,
+        values: ParameterValues,
+    ) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if !proposal.is_open() {
+                    return Err(AppError::BadRequest("Parameter values can only be set on open proposals".to_string()));
+                }
+                // Approval covers the values stored at that point
+                if proposal.status == ProposalStatus::Approved {
+                    return Err(AppError::Conflict(
+                        "Parameter values are fixed once the proposal is approved".to_string(),
+                    ));
+                }
+                templating::check_values(&proposal.parameters, &values)?;
+
+                if values.is_empty() {
+                    proposal.parameter_values.remove(environment);
+                } else {
+                    proposal.parameter_values.insert(environment.to_string(), values);
+                }
+                proposal.updated_at = Utc::now();
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Store generated migration SQL on a proposal as a new artifact version
+    /// (or the existing one, when the SQL is unchanged)
+    pub async fn set_migration(&self, id: Uuid, migration: MigrationArtifacts) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            proposal.record_migration(migration);
+            proposal.updated_at = Utc::now();
+        })
+        .await
+    }
+
+    /// Store the blast radius computed for a submitted proposal
+    pub async fn set_blast_radius(&self, id: Uuid, report: BlastRadiusReport) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            proposal.blast_radius = Some(report);
+            proposal.updated_at = Utc::now();
+        })
+        .await
+    }
+
+    /// Store a fresh checklist and risk analysis; `analyzed_by` is recorded in
+    /// the risk history
+    pub async fn record_analysis(
+        &self,
+        id: Uuid,
+        checklist: Vec<ChecklistItem>,
+        analysis: RiskAnalysis,
+        analyzed_by: &str,
+    ) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            proposal.set_checklist(checklist);
+            proposal.set_risk_analysis(analysis, analyzed_by);
+        })
+        .await
+    }
+
+    /// Store a model-written summary; None if the proposal is gone
+    pub async fn set_executive_summary(&self, id: Uuid, summary: ExecutiveSummary) -> Result<Option<SchemaProposal>, AppError> {
+        match self.update(id, |proposal| proposal.executive_summary = Some(summary)).await {
+            Ok(proposal) => Ok(Some(proposal)),
+            Err(AppError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Store the planner estimates of a dry run
+    pub async fn set_cost_summary(&self, id: Uuid, summary: CostSummary) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            proposal.cost_summary = Some(summary);
+            proposal.updated_at = Utc::now();
+        })
+        .await
+    }
+
+    /// Sign off the retrospective of a break-glass proposal
+    pub async fn record_retrospective(&self, id: Uuid, reviewer: &str, notes: &str) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                let break_glass = proposal
+                    .break_glass
+                    .as_mut()
+                    .ok_or_else(|| AppError::BadRequest("Only break-glass proposals have a retrospective".to_string()))?;
+
+                let now = Utc::now();
+                break_glass.record_review(reviewer, notes, now)?;
+                proposal.updated_at = now;
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
+    }
+
+    /// Record the outcome of a real (non dry-run) execution
+    pub async fn mark_executed(&self, id: Uuid, result: &ExecutionResult) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            let now = Utc::now();
+            let status = match (result.success, &result.paused_at_gate) {
+                // Still mid-run, waiting for someone to confirm the gate
+                (true, Some(_)) => ProposalStatus::Executing,
+                // One stage of an execution plan; later stages are still to run
+                (true, None) if result.checkpoint < result.total_statements => ProposalStatus::Executing,
+                (true, None) => ProposalStatus::Executed,
+                (false, _) => ProposalStatus::Failed,
+            };
+            proposal.set_status(status, now);
+            if status == ProposalStatus::Executed {
+                proposal.executed_at = Some(now);
+                proposal.rollback_window_ends_at = self.policy.rollback_window_hours.map(|hours| now + Duration::hours(hours));
+            }
+            proposal.last_execution = Some(result.clone());
+            proposal.updated_at = now;
+        })
+        .await
+    }
+
+    /// Record a direct rollback; a failed one leaves the proposal executed
+    /// and the window open for another attempt
+    pub async fn mark_rolled_back(&self, id: Uuid, result: &ExecutionResult) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            let now = Utc::now();
+            if result.success {
+                proposal.set_status(ProposalStatus::RolledBack, now);
+                proposal.rollback_window_ends_at = None;
+            }
+            proposal.last_execution = Some(result.clone());
+            proposal.updated_at = now;
+        })
+        .await
+    }
+
+    /// Link the snapshot taken after execution to the proposal
+    pub async fn set_result_snapshot(&self, id: Uuid, snapshot_id: Uuid) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            proposal.result_snapshot_id = Some(snapshot_id);
+            proposal.updated_at = Utc::now();
+        })
+        .await
+    }
+
+    /// Store the post-execution verification; a failed one moves an executed
+    /// proposal to `VerificationFailed`
+    pub async fn record_verification(&self, id: Uuid, report: VerificationReport) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            if !report.passed && proposal.status == ProposalStatus::Executed {
+                proposal.set_status(ProposalStatus::VerificationFailed, report.verified_at);
+            }
+            proposal.verification = Some(report);
+            proposal.updated_at = Utc::now();
+        })
+        .await
+    }
+
+    /// Enforce the lifecycle policy: expire old approvals and close stale drafts
+    pub async fn sweep(&self, now: DateTime<Utc>) -> Result<SweepResult, AppError> {
+        let mut result = SweepResult::default();
+
+        for candidate in self.list().await? {
+            if !self.needs_sweep(&candidate, now) {
+                continue;
+            }
+            // Decided again on the stored proposal, which may have moved on
+            let outcome = self
+                .modify(candidate.id, |proposal| Ok(self.sweep_one(proposal, now)))
+                .await;
+            let (proposal, outcome) = match outcome {
+                Ok(swept) => swept,
+                Err(AppError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if outcome.retrospective_overdue {
+                result.overdue_retrospectives.push(proposal.clone());
+            }
+            match outcome.action {
+                Some(SweepAction::ExpiredApproval) => result.expired_approvals.push(proposal),
+                Some(SweepAction::ClosedDraft) => result.closed_drafts.push(proposal),
+                Some(SweepAction::BreachedReview) => result.breached_reviews.push(proposal),
+                None => {}
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Whether a sweep at `now` would change `proposal`
+    fn needs_sweep(&self, proposal: &SchemaProposal, now: DateTime<Utc>) -> bool {
+        let mut copy = proposal.clone();
+        let outcome = self.sweep_one(&mut copy, now);
+        outcome.retrospective_overdue || outcome.action.is_some()
+    }
+
+    fn sweep_one(&self, proposal: &mut SchemaProposal, now: DateTime<Utc>) -> SweepOutcome {
+        let mut outcome = SweepOutcome::default();
+        // Escalated once, whatever the proposal's status
+        if let Some(break_glass) = proposal.break_glass.as_mut().filter(|b| b.escalated_at.is_none() && b.is_overdue(now)) {
+            break_glass.escalated_at = Some(now);
+            outcome.retrospective_overdue = true;
+        }
+
+        match proposal.status {
+            ProposalStatus::Approved if proposal.approval_expired(now) => {
+                // Back to review; the approval no longer counts
+                proposal.set_status(ProposalStatus::PendingReview, now);
+                proposal.approved_at = None;
+                proposal.approved_by = None;
+                proposal.approval_expires_at = None;
+                proposal.team_approvals.clear();
+                proposal.updated_at = now;
+                outcome.action = Some(SweepAction::ExpiredApproval);
+            }
+            ProposalStatus::Draft => {
+                let Some(days) = self.policy.stale_draft_days else {
+                    return outcome;
+                };
+                if now - proposal.updated_at < Duration::days(days) {
+                    return outcome;
+                }
+
+                proposal.set_status(ProposalStatus::Closed, now);
+                proposal.closed_at = Some(now);
+                proposal.updated_at = now;
+                proposal.comments.push(Comment {
+                    id: Uuid::new_v4(),
+                    author: "system".to_string(),
+                    content: format!("Closed automatically after {} days without activity", days),
+                    target: CommentTarget::Proposal,
+                    reply_to: None,
+                    resolved_by: None,
+                    resolved_at: None,
+                    created_at: now,
+                });
+                outcome.action = Some(SweepAction::ClosedDraft);
+            }
+            ProposalStatus::PendingReview
+                if proposal.sla_breached_at.is_none()
+                    && proposal.reviewed_at().is_none()
+                    && proposal.review_due_at.is_some_and(|due| due <= now) =>
+            {
+                // Escalated once; the flag stays for the listing
+                proposal.sla_breached_at = Some(now);
+                outcome.action = Some(SweepAction::BreachedReview);
+            }
+            _ => {}
+        }
+
+        outcome
+    }
+
+    /// Attach fresh duplicate checks to the proposal's risk analysis, if it
+    /// has one
+    pub async fn set_duplicate_checks(&self, id: Uuid, checks: Vec<DuplicateCheck>) -> Result<SchemaProposal, AppError> {
+        self.update(id, |proposal| {
+            if let Some(analysis) = proposal.risk_analysis.as_mut() {
+                uniqueness::attach(analysis, checks);
+                proposal.updated_at = Utc::now();
+            }
+        })
+        .await
+    }
+
+    /// Flag the risk analyses of open proposals on a connection as stale.
+    /// Returns the proposals that were newly marked.
+    pub async fn mark_risk_stale(&self, connection_id: Uuid, reason: &str) -> Result<Vec<SchemaProposal>, AppError> {
+        let mut marked = Vec::new();
+
+        for candidate in self.list().await? {
+            if candidate.connection_id != connection_id || !candidate.is_open() {
+                continue;
+            }
+            if candidate.risk_analysis.as_ref().is_none_or(|a| a.stale) {
+                continue;
+            }
+            let outcome = self
+                .modify(candidate.id, |proposal| {
+                    let Some(analysis) = proposal.risk_analysis.as_mut().filter(|a| !a.stale) else {
+                        return Ok(false);
+                    };
+                    analysis.stale = true;
+                    analysis.stale_reason = Some(reason.to_string());
+                    Ok(true)
+                })
+                .await;
+            match outcome {
+                Ok((proposal, true)) => marked.push(proposal),
+                Ok((_, false)) | Err(AppError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(marked)
+    }
+
+    /// Store a fresh risk analysis for a proposal that is still open, leaving a
+    /// system comment when the score moved. Returns the updated proposal and
+    /// the analysis it replaced, or None if the proposal is gone or closed.
+    pub async fn refresh_risk(
+        &self,
+        id: Uuid,
+        analysis: RiskAnalysis,
+    ) -> Result<Option<(SchemaProposal, Option<RiskAnalysis>)>, AppError> {
+        let outcome = self
+            .modify(id, |proposal| {
+                if !proposal.is_open() {
+                    return Err(AppError::Conflict(format!("Proposal {} is closed", id)));
+                }
+
+                let new_score = analysis.score;
+                let trigger = format!(
+                    "re-analysis after {}",
+                    proposal
+                        .risk_analysis
+                        .as_ref()
+                        .and_then(|old| old.stale_reason.as_deref())
+                        .unwrap_or("a schema change")
+                );
+                let previous = proposal.set_risk_analysis(analysis, &trigger);
+                if let Some(old) = previous.as_ref().filter(|old| old.score != new_score) {
+                    proposal.comments.push(Comment {
+                        id: Uuid::new_v4(),
+                        author: "system".to_string(),
+                        content: format!(
+                            "Risk re-analyzed after {}: score changed from {} to {}",
+                            old.stale_reason.as_deref().unwrap_or("a schema change"),
+                            old.score,
+                            new_score
+                        ),
+                        target: CommentTarget::Proposal,
+                        reply_to: None,
+                        resolved_by: None,
+                        resolved_at: None,
+                        created_at: Utc::now(),
+                    });
+                }
+                Ok(previous)
+            })
+            .await;
+
+        match outcome {
+            Ok(refreshed) => Ok(Some(refreshed)),
+            Err(AppError::NotFound(_)) | Err(AppError::Conflict(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Apply JSON Patch operations to a draft proposal and record a revision
+    pub async fn patch(
+        &self,
+        id: Uuid,
+        operations: Vec<PatchOperation>,
+        author: &str,
+    ) -> Result<SchemaProposal, AppError> {
+        let (proposal, ()) = self
+            .modify(id, |proposal| {
+                if proposal.status != ProposalStatus::Draft {
+                    return Err(AppError::BadRequest(
+                        "Cannot modify a proposal that is not in draft status".to_string()
+                    ));
+                }
+
+                let changes_modified = apply_patch(proposal, &operations)?;
+                if changes_modified {
+                    // Generated artifacts are stale once the changes move
+                    proposal.migration = None;
+                    proposal.approved_migration_version = None;
+                    proposal.risk_analysis = None;
+                }
+
+                let now = Utc::now();
+                proposal.revisions.push(ProposalRevision {
+                    revision: proposal.revisions.len() as u32 + 1,
+                    author: author.to_string(),
+                    operations,
+                    created_at: now,
+                });
+                proposal.updated_at = now;
+                Ok(())
+            })
+            .await?;
+        Ok(proposal)
     }
 }
 
@@ -50,18 +806,63 @@ impl Default for ProposalService {
     }
 }
 
+/// What a sweep did to one proposal
+#[derive(Default)]
+struct SweepOutcome {
+    retrospective_overdue: bool,
+    action: Option<SweepAction>,
+}
+
+enum SweepAction {
+    ExpiredApproval,
+    ClosedDraft,
+    BreachedReview,
+}
+
+/// Proposals changed by a policy sweep
+#[derive(Debug, Default)]
+pub struct SweepResult {
+    pub expired_approvals: Vec<SchemaProposal>,
+    pub closed_drafts: Vec<SchemaProposal>,
+    /// Proposals that just passed their review deadline unreviewed
+    pub breached_reviews: Vec<SchemaProposal>,
+    /// Break-glass proposals that just passed their retrospective deadline
+    pub overdue_retrospectives: Vec<SchemaProposal>,
+}
+
 /// A schema change proposal (like a GitHub PR for databases)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchemaProposal {
     pub id: Uuid,
     pub connection_id: Uuid,
+    /// Project whose lint settings apply to this proposal
+    #[serde(default)]
+    pub project_id: Option<i32>,
     pub title: String,
     pub description: String,
     pub status: ProposalStatus,
     pub changes: Vec<SchemaChange>,
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Slugs of the project teams assigned to review
+    #[serde(default)]
+    pub reviewer_teams: Vec<String>,
+    /// Approvals members have given on behalf of the assigned teams
+    #[serde(default)]
+    pub team_approvals: Vec<TeamApproval>,
     pub comments: Vec<Comment>,
     pub migration: Option<MigrationArtifacts>,
+    /// Every distinct migration generated for the proposal, oldest first.
+    /// Stored versions are never modified.
+    #[serde(default)]
+    pub migration_history: Vec<MigrationArtifacts>,
+    /// Migration version current when the proposal was approved; this is
+    /// what executes, even if the SQL is regenerated afterwards
+    #[serde(default)]
+    pub approved_migration_version: Option<u32>,
     pub risk_analysis: Option<RiskAnalysis>,
     pub created_by: String,
     pub created_at: DateTime<Utc>,
@@ -69,7 +870,69 @@ pub struct SchemaProposal {
     pub submitted_at: Option<DateTime<Utc>>,
     pub approved_at: Option<DateTime<Utc>>,
     pub approved_by: Option<String>,
+    /// When the current approval lapses and the proposal needs re-approval
+    #[serde(default)]
+    pub approval_expires_at: Option<DateTime<Utc>>,
     pub executed_at: Option<DateTime<Utc>>,
+    /// Until when the execution can be rolled back without a revert proposal
+    #[serde(default)]
+    pub rollback_window_ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Edit history recorded for each applied patch
+    #[serde(default)]
+    pub revisions: Vec<ProposalRevision>,
+    /// Proposal this one was cloned from
+    #[serde(default)]
+    pub cloned_from: Option<Uuid>,
+    /// Executed proposal this one reverts
+    #[serde(default)]
+    pub reverts: Option<Uuid>,
+    /// Outcome of the most recent real execution, including failure forensics
+    #[serde(default)]
+    pub last_execution: Option<ExecutionResult>,
+    /// Snapshot introspected after the successful execution
+    #[serde(default)]
+    pub result_snapshot_id: Option<Uuid>,
+    /// Comparison of the result snapshot with the schema the changes should produce
+    #[serde(default)]
+    pub verification: Option<VerificationReport>,
+    /// Every status the proposal has entered, oldest first
+    #[serde(default)]
+    pub status_history: Vec<StatusChange>,
+    /// Review deadline under the project's SLA, set on submission
+    #[serde(default)]
+    pub review_due_at: Option<DateTime<Utc>>,
+    /// When the missed review deadline was escalated
+    #[serde(default)]
+    pub sla_breached_at: Option<DateTime<Utc>>,
+    /// Blast radius of the changes, computed on submission
+    #[serde(default)]
+    pub blast_radius: Option<BlastRadiusReport>,
+    /// Planner estimates from the latest dry run
+    #[serde(default)]
+    pub cost_summary: Option<CostSummary>,
+    /// Every risk analysis so far, oldest first
+    #[serde(default)]
+    pub risk_history: Vec<RiskRecord>,
+    /// Reviewer sign-offs on individual migration statements
+    #[serde(default)]
+    pub statement_approvals: Vec<StatementApproval>,
+    /// Placeholders the changes may use, e.g. `{{retention_days}}`
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+    /// Parameter values by environment key (`development`, `production`, ...)
+    #[serde(default)]
+    pub parameter_values: BTreeMap<String, ParameterValues>,
+    /// Things to confirm before approval, from the latest risk analysis
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Summary the language model wrote; see `summary::current`
+    #[serde(default)]
+    pub executive_summary: Option<ExecutiveSummary>,
+    /// Set when the changes ran through the emergency path without review
+    #[serde(default)]
+    pub break_glass: Option<BreakGlass>,
 }
 
 impl SchemaProposal {
@@ -78,12 +941,19 @@ impl SchemaProposal {
         Self {
             id: Uuid::new_v4(),
             connection_id,
+            project_id: None,
             title,
             description,
             status: ProposalStatus::Draft,
             changes: Vec::new(),
+            reviewers: Vec::new(),
+            reviewer_teams: Vec::new(),
+            team_approvals: Vec::new(),
+            labels: Vec::new(),
             comments: Vec::new(),
             migration: None,
+            migration_history: Vec::new(),
+            approved_migration_version: None,
             risk_analysis: None,
             created_by,
             created_at: now,
@@ -91,11 +961,258 @@ impl SchemaProposal {
             submitted_at: None,
             approved_at: None,
             approved_by: None,
+            approval_expires_at: None,
             executed_at: None,
+            rollback_window_ends_at: None,
+            closed_at: None,
+            revisions: Vec::new(),
+            cloned_from: None,
+            reverts: None,
+            last_execution: None,
+            result_snapshot_id: None,
+            verification: None,
+            status_history: vec![StatusChange {
+                status: ProposalStatus::Draft,
+                at: now,
+            }],
+            review_due_at: None,
+            sla_breached_at: None,
+            blast_radius: None,
+            cost_summary: None,
+            risk_history: Vec::new(),
+            statement_approvals: Vec::new(),
+            parameters: Vec::new(),
+            parameter_values: BTreeMap::new(),
+            checklist: Vec::new(),
+            executive_summary: None,
+            break_glass: None,
+        }
+    }
+
+    /// Copy this proposal's content into a new draft owned by `author`
+    pub fn duplicate(&self, author: &str) -> Self {
+        let mut copy = Self::new(
+            self.connection_id,
+            format!("Copy of {}", self.title),
+            self.description.clone(),
+            author.to_string(),
+        );
+        copy.project_id = self.project_id;
+        copy.changes = self.changes.clone();
+        copy.reviewers = self.reviewers.clone();
+        copy.reviewer_teams = self.reviewer_teams.clone();
+        copy.labels = self.labels.clone();
+        copy.parameters = self.parameters.clone();
+        copy.parameter_values = self.parameter_values.clone();
+        copy.cloned_from = Some(self.id);
+        copy
+    }
+
+    /// Make `migration` the current migration. SQL identical to the latest
+    /// version reuses it; anything else is stored as the next version.
+    pub fn record_migration(&mut self, mut migration: MigrationArtifacts) -> MigrationArtifacts {
+        if migration.checksum.is_empty() {
+            migration.checksum = migration_checksum(&migration.up_sql, &migration.down_sql);
         }
+        let stored = match self.migration_history.last() {
+            Some(latest) if latest.checksum == migration.checksum => latest.clone(),
+            latest => {
+                migration.version = latest.map_or(0, |m| m.version) + 1;
+                self.migration_history.push(migration.clone());
+                migration
+            }
+        };
+        self.migration = Some(stored.clone());
+        stored
+    }
+
+    /// Stored migration version `version`
+    pub fn migration_version(&self, version: u32) -> Option<&MigrationArtifacts> {
+        self.migration_history.iter().find(|m| m.version == version)
+    }
+
+    /// The migration an execution should run: the version the last run used
+    /// when resuming, otherwise the version pinned at approval, falling back
+    /// to the current migration
+    pub fn migration_for_run(&self, resume: bool) -> Option<&MigrationArtifacts> {
+        let pinned = if resume {
+            self.last_execution.as_ref().and_then(|e| e.migration_version)
+        } else {
+            self.approved_migration_version
+        };
+        pinned
+            .and_then(|v| self.migration_version(v))
+            .or(self.migration.as_ref())
+    }
+
+    /// Store a new risk analysis, keeping it in the history against the
+    /// current revision. Returns the analysis it replaced.
+    pub fn set_risk_analysis(&mut self, analysis: RiskAnalysis, trigger: &str) -> Option<RiskAnalysis> {
+        risk_history::record(
+            &mut self.risk_history,
+            RiskRecord {
+                revision: self.revisions.len() as u32,
+                change_count: self.changes.len(),
+                trigger: trigger.to_string(),
+                analysis: analysis.clone(),
+            },
+        );
+        self.risk_analysis.replace(analysis)
+    }
+
+    /// Replace the checklist, keeping ticks on items still listed
+    pub fn set_checklist(&mut self, mut items: Vec<ChecklistItem>) {
+        checklist::carry_over(&mut items, &self.checklist);
+        self.checklist = items;
+    }
+
+    /// Required checklist items nobody has ticked
+    pub fn unchecked_required_items(&self) -> Vec<&ChecklistItem> {
+        self.checklist.iter().filter(|item| item.required && !item.is_checked()).collect()
+    }
+
+    /// Still heading towards execution
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            ProposalStatus::Draft | ProposalStatus::PendingReview | ProposalStatus::Approved
+        )
+    }
+
+    /// Whether the approval has lapsed as of `now`
+    pub fn approval_expired(&self, now: DateTime<Utc>) -> bool {
+        self.approval_expires_at.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the execution can still be rolled back directly as of `now`
+    pub fn rollback_window_open(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.status, ProposalStatus::Executed | ProposalStatus::VerificationFailed)
+            && self.rollback_window_ends_at.is_some_and(|ends| ends > now)
+    }
+
+    /// Statements of the generated migration, in execution order
+    pub fn statements(&self) -> Vec<String> {
+        self.migration.as_ref().map(|m| split_statements(&m.up_sql)).unwrap_or_default()
+    }
+
+    /// Sign-offs on statement `index` that still match its text
+    pub fn statement_approvals_for(&self, index: usize, statement: &str) -> Vec<&StatementApproval> {
+        let hash = statement_hash(statement);
+        self.statement_approvals
+            .iter()
+            .filter(|a| a.index == index && a.statement_hash == hash)
+            .collect()
+    }
+
+    /// Unresolved threads on migration statements
+    pub fn open_statement_threads(&self) -> Vec<&Comment> {
+        self.comments
+            .iter()
+            .filter(|c| c.reply_to.is_none() && c.resolved_at.is_none())
+            .filter(|c| matches!(c.target, CommentTarget::Statement { .. }))
+            .collect()
+    }
+
+    /// Why the review policy refuses approval right now; empty when it allows it
+    pub fn approval_blockers(&self, policy: &ProposalPolicyConfig) -> Vec<String> {
+        let mut blockers = Vec::new();
+        let unchecked = self.unchecked_required_items();
+        if !unchecked.is_empty() {
+            blockers.push(format!(
+                "required checklist item(s) not ticked: {}",
+                unchecked.iter().map(|item| item.text.as_str()).collect::<Vec<_>>().join("; ")
+            ));
+        }
+        if policy.block_on_open_statement_threads {
+            let mut open: Vec<usize> = self
+                .open_statement_threads()
+                .iter()
+                .filter_map(|c| match c.target {
+                    CommentTarget::Statement { index } => Some(index + 1),
+                    _ => None,
+                })
+                .collect();
+            open.dedup();
+            if !open.is_empty() {
+                blockers.push(format!(
+                    "unresolved comment threads on statement(s) {}",
+                    open.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+        if policy.require_statement_approvals {
+            if self.migration.is_none() {
+                blockers.push("the migration has not been generated for statement sign-off".to_string());
+            }
+            let unsigned: Vec<String> = self
+                .statements()
+                .iter()
+                .enumerate()
+                .filter(|(i, sql)| self.statement_approvals_for(*i, sql).is_empty())
+                .map(|(i, _)| (i + 1).to_string())
+                .collect();
+            if !unsigned.is_empty() {
+                blockers.push(format!("statement(s) {} not signed off", unsigned.join(", ")));
+            }
+        }
+        blockers
+    }
+
+    fn mark_approved(
+        &mut self,
+        approver: &str,
+        policy: &ProposalPolicyConfig,
+        now: DateTime<Utc>,
+        environment: &str,
+    ) -> Result<(), AppError> {
+        // Execution runs exactly the SQL rendered with the values approved here
+        if templating::is_templated(self) {
+            let (rendered, _) = templating::render_for(self, environment, &ParameterValues::new())?;
+            self.record_migration(Orchestrator::new().generate_migration(&rendered));
+        }
+        self.set_status(ProposalStatus::Approved, now);
+        self.approved_at = Some(now);
+        self.approved_by = Some(approver.to_string());
+        self.approval_expires_at = policy.approval_validity_days.map(|days| now + Duration::days(days));
+        self.approved_migration_version = self.migration.as_ref().map(|m| m.version);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Change status and record the transition
+    pub fn set_status(&mut self, status: ProposalStatus, at: DateTime<Utc>) {
+        self.status = status;
+        self.status_history.push(StatusChange { status, at });
+    }
+
+    /// First approval or rejection since the latest submission
+    pub fn reviewed_at(&self) -> Option<DateTime<Utc>> {
+        let submitted_at = self.submitted_at?;
+        self.status_history
+            .iter()
+            .find(|c| c.at >= submitted_at && matches!(c.status, ProposalStatus::Approved | ProposalStatus::Rejected))
+            .map(|c| c.at)
+    }
+
+    /// Seconds spent in each status up to `now`, keyed by status name
+    pub fn time_in_status(&self, now: DateTime<Utc>) -> BTreeMap<String, i64> {
+        let mut totals = BTreeMap::new();
+        for (i, change) in self.status_history.iter().enumerate() {
+            let until = self.status_history.get(i + 1).map_or(now, |next| next.at);
+            *totals.entry(change.status.as_str().to_string()).or_insert(0) += (until - change.at).num_seconds().max(0);
+        }
+        totals
     }
 }
 
+/// A status a proposal entered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub status: ProposalStatus,
+    pub at: DateTime<Utc>,
+}
+
 /// Proposal status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -106,8 +1223,38 @@ pub enum ProposalStatus {
     Rejected,
     Executing,
     Executed,
+    /// Executed, but the resulting schema differs from what the changes describe
+    VerificationFailed,
     Failed,
     RolledBack,
+    Closed,
+}
+
+impl ProposalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStatus::Draft => "draft",
+            ProposalStatus::PendingReview => "pending_review",
+            ProposalStatus::Approved => "approved",
+            ProposalStatus::Rejected => "rejected",
+            ProposalStatus::Executing => "executing",
+            ProposalStatus::Executed => "executed",
+            ProposalStatus::VerificationFailed => "verification_failed",
+            ProposalStatus::Failed => "failed",
+            ProposalStatus::RolledBack => "rolled_back",
+            ProposalStatus::Closed => "closed",
+        }
+    }
+}
+
+/// A recorded edit to a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalRevision {
+    pub revision: u32,
+    pub author: String,
+    pub operations: Vec<PatchOperation>,
+    pub created_at: DateTime<Utc>,
 }
 
 /// A comment on a proposal
@@ -118,6 +1265,14 @@ pub struct Comment {
     pub author: String,
     pub content: String,
     pub target: CommentTarget,
+    /// First comment of the thread this one replies to
+    #[serde(default)]
+    pub reply_to: Option<Uuid>,
+    /// Set on a thread's first comment once the discussion is settled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -127,6 +1282,29 @@ pub struct Comment {
 pub enum CommentTarget {
     Proposal,
     Change { index: usize },
+    /// A statement of the generated migration, by position in `up_sql`
+    Statement { index: usize },
+}
+
+/// A reviewer's sign-off on one migration statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementApproval {
+    pub index: usize,
+    /// SHA-256 of the statement; the sign-off lapses if the statement changes
+    pub statement_hash: String,
+    pub approver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub approved_at: DateTime<Utc>,
+}
+
+fn statement_hash(statement: &str) -> String {
+    format!("{:x}", Sha256::digest(statement.as_bytes()))
+}
+
+fn statement_not_found(index: usize, count: usize) -> AppError {
+    AppError::BadRequest(format!("Statement {} not found; the migration has {} statements", index, count))
 }
 
 /// Migration artifacts
@@ -136,6 +1314,33 @@ pub struct MigrationArtifacts {
     pub up_sql: String,
     pub down_sql: String,
     pub generated_at: DateTime<Utc>,
+    /// Position in the proposal's migration history, from 1; 0 until stored
+    #[serde(default)]
+    pub version: u32,
+    /// SHA-256 over the up and down SQL
+    #[serde(default)]
+    pub checksum: String,
+}
+
+impl MigrationArtifacts {
+    pub fn new(up_sql: String, down_sql: String) -> Self {
+        let checksum = migration_checksum(&up_sql, &down_sql);
+        Self {
+            up_sql,
+            down_sql,
+            generated_at: Utc::now(),
+            version: 0,
+            checksum,
+        }
+    }
+}
+
+fn migration_checksum(up_sql: &str, down_sql: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(up_sql.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(down_sql.as_bytes());
+    format!("{:x}", hasher.finalize())
 }
 
 /// Risk analysis results
@@ -146,10 +1351,36 @@ pub struct RiskAnalysis {
     pub score: u32,
     pub warnings: Vec<String>,
     pub recommendations: Vec<String>,
+    /// Stable codes of `warnings`, in the same order
+    #[serde(default)]
+    pub warning_codes: Vec<Message>,
+    /// Stable codes of `recommendations`, in the same order
+    #[serde(default)]
+    pub recommendation_codes: Vec<Message>,
     pub estimated_duration_secs: u64,
     pub requires_downtime: bool,
     pub affected_tables: Vec<String>,
     pub analyzed_at: DateTime<Utc>,
+    /// Set when the schema moved underneath the analysis
+    #[serde(default)]
+    pub stale: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_reason: Option<String>,
+    /// Duplicate keys found behind the unique constraints and indexes added
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_checks: Vec<DuplicateCheck>,
+}
+
+impl RiskAnalysis {
+    /// Translate warnings and recommendations into `language` where possible
+    pub fn localize(&mut self, translations: &Translations, language: &str) {
+        for (text, message) in self.warnings.iter_mut().zip(&self.warning_codes) {
+            translations.localize(language, message, text);
+        }
+        for (text, message) in self.recommendations.iter_mut().zip(&self.recommendation_codes) {
+            translations.localize(language, message, text);
+        }
+    }
 }
 
 /// Risk level
@@ -161,3 +1392,298 @@ pub enum RiskLevel {
     High,
     Critical,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sweep_expires_approvals_and_closes_stale_drafts() {
+        let service = ProposalService::new();
+
+        let mut approved = SchemaProposal::new(Uuid::new_v4(), "Approved".to_string(), String::new(), "dev".to_string());
+        approved.status = ProposalStatus::Approved;
+        approved.approval_expires_at = Some(Utc::now() - Duration::days(1));
+        let approved = service.create(approved).await.unwrap();
+
+        let mut stale = SchemaProposal::new(Uuid::new_v4(), "Stale".to_string(), String::new(), "dev".to_string());
+        stale.updated_at = Utc::now() - Duration::days(91);
+        let stale = service.create(stale).await.unwrap();
+
+        let fresh = service
+            .create(SchemaProposal::new(Uuid::new_v4(), "Fresh".to_string(), String::new(), "dev".to_string()))
+            .await
+            .unwrap();
+
+        let result = service.sweep(Utc::now()).await.unwrap();
+        assert_eq!(result.expired_approvals.len(), 1);
+        assert_eq!(result.closed_drafts.len(), 1);
+
+        assert_eq!(service.get(approved.id).await.unwrap().unwrap().status, ProposalStatus::PendingReview);
+        let stale = service.get(stale.id).await.unwrap().unwrap();
+        assert_eq!(stale.status, ProposalStatus::Closed);
+        assert_eq!(stale.comments.len(), 1);
+        assert_eq!(service.get(fresh.id).await.unwrap().unwrap().status, ProposalStatus::Draft);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_escalates_overdue_review_once() {
+        let service = ProposalService::new();
+
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Slow".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::Analyze { table_name: "users".to_string() });
+        let proposal = service.create(proposal).await.unwrap();
+        let sla = ReviewSla { review_business_days: Some(2) };
+        let submitted = service.submit(proposal.id, Some(&sla)).await.unwrap();
+        let due = submitted.review_due_at.unwrap();
+
+        assert!(service.sweep(due - Duration::hours(1)).await.unwrap().breached_reviews.is_empty());
+        assert_eq!(service.sweep(due).await.unwrap().breached_reviews.len(), 1);
+        assert!(service.sweep(due + Duration::hours(1)).await.unwrap().breached_reviews.is_empty());
+
+        let later = due + Duration::days(1);
+        let times = service.get(proposal.id).await.unwrap().unwrap().time_in_status(later);
+        assert!(times["pending_review"] >= (later - due).num_seconds());
+    }
+
+    #[tokio::test]
+    async fn test_team_approvals_reach_quorum() {
+        let service = ProposalService::new();
+
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Teams".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::Analyze { table_name: "users".to_string() });
+        proposal.reviewer_teams = vec!["dba".to_string()];
+        let proposal = service.create(proposal).await.unwrap();
+        service.submit(proposal.id, None).await.unwrap();
+
+        let dba = vec![Team {
+            slug: "dba".to_string(),
+            name: "DBA".to_string(),
+            members: vec!["1".to_string(), "2".to_string()],
+            required_approvals: 2,
+            channel: None,
+        }];
+
+        assert!(matches!(service.approve_for_teams(proposal.id, "9", &dba, "dev").await, Err(AppError::Forbidden(_))));
+
+        // The same member approving twice still counts once
+        service.approve_for_teams(proposal.id, "1", &dba, "dev").await.unwrap();
+        let pending = service.approve_for_teams(proposal.id, "1", &dba, "dev").await.unwrap();
+        assert_eq!(pending.status, ProposalStatus::PendingReview);
+        assert_eq!(pending.team_approvals.len(), 1);
+
+        let approved = service.approve_for_teams(proposal.id, "2", &dba, "dev").await.unwrap();
+        assert_eq!(approved.status, ProposalStatus::Approved);
+        assert_eq!(approved.approved_by.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_risk_is_refreshed_with_comment() {
+        let service = ProposalService::new();
+        let connection_id = Uuid::new_v4();
+
+        let mut proposal = SchemaProposal::new(connection_id, "Risky".to_string(), String::new(), "dev".to_string());
+        proposal.risk_analysis = Some(RiskAnalysis {
+            overall_risk: RiskLevel::Low,
+            score: 10,
+            warnings: Vec::new(),
+            recommendations: Vec::new(),
+            warning_codes: Vec::new(),
+            recommendation_codes: Vec::new(),
+            estimated_duration_secs: 1,
+            requires_downtime: false,
+            affected_tables: Vec::new(),
+            analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: Vec::new(),
+        });
+        let proposal = service.create(proposal).await.unwrap();
+
+        assert!(service.mark_risk_stale(Uuid::new_v4(), "schema drift").await.unwrap().is_empty());
+        assert_eq!(service.mark_risk_stale(connection_id, "schema drift").await.unwrap().len(), 1);
+        // Already stale, so nothing new to mark
+        assert!(service.mark_risk_stale(connection_id, "schema drift").await.unwrap().is_empty());
+
+        let mut fresh = proposal.risk_analysis.clone().unwrap();
+        fresh.score = 60;
+        fresh.overall_risk = RiskLevel::High;
+        let (updated, previous) = service.refresh_risk(proposal.id, fresh).await.unwrap().unwrap();
+
+        assert!(previous.unwrap().stale);
+        assert!(!updated.risk_analysis.unwrap().stale);
+        assert_eq!(updated.comments.len(), 1);
+        assert!(updated.comments[0].content.contains("after schema drift"));
+        assert_eq!(updated.risk_history.len(), 1);
+        assert_eq!(updated.risk_history[0].trigger, "re-analysis after schema drift");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_are_not_lost() {
+        let service = Arc::new(ProposalService::new());
+        let proposal = service
+            .create(SchemaProposal::new(Uuid::new_v4(), "Busy".to_string(), String::new(), "dev".to_string()))
+            .await
+            .unwrap();
+
+        let writers: Vec<_> = (0..20)
+            .map(|i| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    let mut comment = statement_comment(&i.to_string(), 0, None);
+                    comment.target = CommentTarget::Proposal;
+                    service.add_comment(proposal.id, comment).await.unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        assert_eq!(service.get(proposal.id).await.unwrap().unwrap().comments.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_parameter_values_are_fixed_once_approved() {
+        use crate::pipeline::templating::ParameterType;
+
+        let service = ProposalService::new();
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Analyze".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::Analyze { table_name: "{{ schema }}.events".to_string() });
+        proposal.parameters = vec![TemplateParameter {
+            name: "schema".to_string(),
+            kind: ParameterType::Identifier,
+            description: None,
+            default: None,
+        }];
+        let proposal = service.create(proposal).await.unwrap();
+        let values: ParameterValues = [("schema".to_string(), "app".to_string())].into();
+        service.set_parameter_values(proposal.id, "production", values.clone()).await.unwrap();
+        service.submit(proposal.id, None).await.unwrap();
+
+        let approved = service.approve(proposal.id, "admin", "production").await.unwrap();
+        let pinned = approved.migration_for_run(false).unwrap();
+        assert_eq!(approved.approved_migration_version, Some(pinned.version));
+        assert!(pinned.up_sql.contains("app.events"));
+
+        let other: ParameterValues = [("schema".to_string(), "audit".to_string())].into();
+        assert!(matches!(
+            service.set_parameter_values(proposal.id, "production", other).await,
+            Err(AppError::Conflict(_))
+        ));
+        let stored = service.get(proposal.id).await.unwrap().unwrap();
+        assert_eq!(stored.parameter_values["production"], values);
+    }
+
+    fn statement_comment(author: &str, index: usize, reply_to: Option<Uuid>) -> Comment {
+        Comment {
+            id: Uuid::new_v4(),
+            author: author.to_string(),
+            content: "Is this safe?".to_string(),
+            target: if reply_to.is_some() { CommentTarget::Proposal } else { CommentTarget::Statement { index } },
+            reply_to,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_statement_thread_blocks_approval() {
+        let service = ProposalService::new();
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Index".to_string(), String::new(), "dev".to_string());
+        proposal.changes.push(SchemaChange::Analyze { table_name: "users".to_string() });
+        proposal.record_migration(MigrationArtifacts::new(
+            "ALTER TABLE users ADD COLUMN age int;\n\nANALYZE users;".to_string(),
+            String::new(),
+        ));
+        let proposal = service.create(proposal).await.unwrap();
+        service.submit(proposal.id, None).await.unwrap();
+
+        let thread = service.add_comment(proposal.id, statement_comment("bob", 1, None)).await.unwrap();
+        let reply = service
+            .add_comment(proposal.id, statement_comment("alice", 0, Some(thread.id)))
+            .await
+            .unwrap();
+        assert!(matches!(reply.target, CommentTarget::Statement { index: 1 }));
+        assert!(service.add_comment(proposal.id, statement_comment("bob", 5, None)).await.is_err());
+
+        assert!(matches!(service.approve(proposal.id, "admin", "dev").await, Err(AppError::Conflict(_))));
+        assert!(service.resolve_thread(proposal.id, thread.id, "carol", false, true).await.is_err());
+        service.resolve_thread(proposal.id, thread.id, "bob", false, true).await.unwrap();
+
+        service.approve_statement(proposal.id, 0, "admin", None).await.unwrap();
+        let approved = service.approve(proposal.id, "admin", "dev").await.unwrap();
+        assert_eq!(approved.status, ProposalStatus::Approved);
+        let first = approved.statements().remove(0);
+        assert_eq!(approved.statement_approvals_for(0, &first).len(), 1);
+    }
+
+    fn execution(proposal_id: Uuid, success: bool, paused_at_gate: Option<&str>) -> ExecutionResult {
+        ExecutionResult {
+            id: Uuid::new_v4(),
+            proposal_id,
+            success,
+            dry_run: false,
+            executed_statements: Vec::new(),
+            error: None,
+            forensics: None,
+            total_statements: 0,
+            resumed_from: 0,
+            checkpoint: 0,
+            chunks: Vec::new(),
+            paused_at_gate: paused_at_gate.map(str::to_string),
+            cost_summary: None,
+            backup: None,
+            lock: None,
+            session_settings: Default::default(),
+            permissions: None,
+            row_counts: None,
+            template_values: Default::default(),
+            watchdog: None,
+            deep_dry_run: None,
+            migration_version: None,
+            migration_checksum: None,
+            duration_ms: 0,
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollback_window_follows_execution() {
+        let service = ProposalService::new();
+        let proposal = service
+            .create(SchemaProposal::new(Uuid::new_v4(), "Swap".to_string(), String::new(), "dev".to_string()))
+            .await
+            .unwrap();
+
+        let paused = service.mark_executed(proposal.id, &execution(proposal.id, true, Some("swap"))).await.unwrap();
+        assert_eq!(paused.status, ProposalStatus::Executing);
+        assert!(!paused.rollback_window_open(Utc::now()));
+
+        let executed = service.mark_executed(proposal.id, &execution(proposal.id, true, None)).await.unwrap();
+        assert!(executed.rollback_window_open(Utc::now()));
+        assert!(!executed.rollback_window_open(Utc::now() + Duration::hours(25)));
+
+        let failed = service.mark_rolled_back(proposal.id, &execution(proposal.id, false, None)).await.unwrap();
+        assert!(failed.rollback_window_open(Utc::now()));
+        let rolled_back = service.mark_rolled_back(proposal.id, &execution(proposal.id, true, None)).await.unwrap();
+        assert_eq!(rolled_back.status, ProposalStatus::RolledBack);
+        assert!(!rolled_back.rollback_window_open(Utc::now()));
+    }
+    #[test]
+    fn test_regenerated_migration_keeps_approved_version() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Index".to_string(), String::new(), "dev".to_string());
+        let first = proposal.record_migration(MigrationArtifacts::new("CREATE INDEX a ON t (x);".to_string(), String::new()));
+        let again = proposal.record_migration(MigrationArtifacts::new("CREATE INDEX a ON t (x);".to_string(), String::new()));
+        assert_eq!((first.version, again.version), (1, 1));
+        assert_eq!(first.checksum, again.checksum);
+
+        proposal.mark_approved("alice", &ProposalPolicyConfig::default(), Utc::now(), "dev").unwrap();
+        let second = proposal.record_migration(MigrationArtifacts::new("CREATE INDEX b ON t (y);".to_string(), String::new()));
+        assert_eq!(second.version, 2);
+        assert_eq!(proposal.migration_history.len(), 2);
+        assert_eq!(proposal.migration_for_run(false).map(|m| m.version), Some(1));
+        assert_eq!(proposal.migration_version(1).unwrap().up_sql, "CREATE INDEX a ON t (x);");
+    }
+}
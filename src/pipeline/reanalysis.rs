@@ -0,0 +1,99 @@
+//! Risk re-analysis
+//!
+//! A risk analysis describes the schema as it was when the analysis ran. When
+//! drift is detected or a new semantic map is built, analyses of open
+//! proposals on that connection are marked stale and, if the policy allows,
+//! re-run in the background. Reviewers hear about any score that moved.
+
+use crate::notifications::Notification;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::risk::RiskEngine;
+use crate::pipeline::summary;
+use crate::pipeline::teams;
+use crate::state::SharedState;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Mark cached risk analyses for a connection stale and queue re-analysis
+pub async fn invalidate_risk(state: &SharedState, connection_id: Uuid, reason: &str) {
+    let stale = match state.pipeline_proposals.mark_risk_stale(connection_id, reason).await {
+        Ok(stale) => stale,
+        Err(e) => {
+            warn!("Could not mark risk analyses stale on connection {}: {}", connection_id, e);
+            return;
+        }
+    };
+    if stale.is_empty() {
+        return;
+    }
+
+    info!("Marked {} risk analysis(es) stale on connection {}: {}", stale.len(), connection_id, reason);
+    for proposal in &stale {
+        let entry = AuditEntry::new(AuditAction::RiskAnalysisStale, "system", "proposal", &proposal.id.to_string())
+            .with_details(reason);
+        state.metadata.record_audit_entry(entry).await;
+    }
+
+    if !state.pipeline_proposals.policy().reanalyze_on_drift {
+        return;
+    }
+
+    let state = state.clone();
+    let reason = reason.to_string();
+    tokio::spawn(async move {
+        let snapshot = match state.latest_scoped_snapshot(connection_id).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                warn!("Risk re-analysis skipped for connection {}: {}", connection_id, e);
+                return;
+            }
+        };
+        let engine = RiskEngine::new();
+
+        for proposal in stale {
+            // The proposal may have been edited since it was marked; analyze what is there now
+            let Ok(Some(current)) = state.pipeline_proposals.get(proposal.id).await else {
+                continue;
+            };
+            let analysis = match engine.analyze(&current, snapshot.as_ref()) {
+                Ok(analysis) => analysis,
+                Err(e) => {
+                    warn!("Risk re-analysis failed for proposal {}: {}", proposal.id, e);
+                    continue;
+                }
+            };
+
+            let (updated, previous) = match state.pipeline_proposals.refresh_risk(proposal.id, analysis).await {
+                Ok(Some(refreshed)) => refreshed,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Could not store the re-analysis of proposal {}: {}", proposal.id, e);
+                    continue;
+                }
+            };
+            let previous_score = previous.map(|a| a.score).unwrap_or_default();
+            let new_score = updated.risk_analysis.as_ref().map(|a| a.score).unwrap_or_default();
+
+            let entry = AuditEntry::new(AuditAction::RiskReanalyzed, "system", "proposal", &updated.id.to_string())
+                .with_details(&format!("Score {} -> {} after {}", previous_score, new_score, reason));
+            state.metadata.record_audit_entry(entry).await;
+            summary::schedule(&state, &updated);
+
+            if previous_score != new_score {
+                let assigned = teams::for_project(&state, updated.project_id)
+                    .await
+                    .map(|t| t.assigned_to(&updated))
+                    .unwrap_or_default();
+                let audience = teams::review_audience(&updated.reviewers, &assigned);
+                state.notifier.notify(
+                    Notification::RiskScoreChanged {
+                        proposal: updated,
+                        previous_score,
+                        reason: reason.clone(),
+                    },
+                    audience,
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,296 @@
+//! Schema object references in proposal descriptions and comments
+//!
+//! Descriptions and comments are markdown. `[[public.users.email]]` refers
+//! to a column, `[[public.users]]` or `[[users]]` to a table (unqualified
+//! names are in `public`). References inside code spans and fenced code
+//! blocks are left alone. Each reference is resolved against the latest
+//! snapshot and the proposal's own changes, so reviewers can see whether
+//! the object exists, which changes touch it and how risky those are.
+
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::access::touched_tables;
+use crate::pipeline::impact::exists;
+use crate::pipeline::proposal::{Comment, RiskLevel, SchemaProposal};
+use crate::pipeline::risk::{split_table_name, RiskEngine};
+use crate::pipeline::types::SchemaChange;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A `[[...]]` reference found in markdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawReference {
+    /// Byte range of the reference, brackets included
+    pub start: usize,
+    pub end: usize,
+    pub schema: String,
+    pub table: String,
+    pub column: Option<String>,
+}
+
+/// Whether a referenced object is there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceStatus {
+    /// In the latest snapshot
+    Exists,
+    /// Not in the snapshot, but the proposal creates it
+    Created,
+    Missing,
+    /// No snapshot to check against
+    Unknown,
+}
+
+/// A reference resolved into a link with annotations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectReference {
+    /// Schema-qualified object path
+    pub reference: String,
+    pub start: usize,
+    pub end: usize,
+    pub schema: String,
+    pub table: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    pub status: ReferenceStatus,
+    /// The latest snapshot, filtered to the object's table
+    pub href: String,
+    /// Changes of the proposal that touch the object's table
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub change_indexes: Vec<usize>,
+    /// Risk of those changes on their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<RiskLevel>,
+}
+
+/// A comment with its resolved references
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedComment {
+    #[serde(flatten)]
+    pub comment: Comment,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ObjectReference>,
+}
+
+fn is_identifier(part: &str) -> bool {
+    !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Parse the text between the brackets; None if it isn't an object path
+fn parse_path(text: &str) -> Option<(String, String, Option<String>)> {
+    let parts: Vec<&str> = text.trim().split('.').collect();
+    if !parts.iter().all(|p| is_identifier(p)) {
+        return None;
+    }
+    let owned = |s: &str| s.to_string();
+    match parts.as_slice() {
+        [table] => Some(("public".to_string(), owned(table), None)),
+        [schema, table] => Some((owned(schema), owned(table), None)),
+        [schema, table, column] => Some((owned(schema), owned(table), Some(owned(column)))),
+        _ => None,
+    }
+}
+
+/// References in `markdown`, in order, skipping code
+pub fn parse(markdown: &str) -> Vec<RawReference> {
+    let mut references = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut i = 0;
+        let bytes = line.as_bytes();
+        while i < bytes.len() {
+            if bytes[i] == b'`' {
+                in_code = !in_code;
+                i += 1;
+                continue;
+            }
+            if !in_code && line[i..].starts_with("[[") {
+                if let Some(close) = line[i + 2..].find("]]") {
+                    let inner = &line[i + 2..i + 2 + close];
+                    let end = i + 2 + close + 2;
+                    if let Some((schema, table, column)) = parse_path(inner) {
+                        references.push(RawReference {
+                            start: line_start + i,
+                            end: line_start + end,
+                            schema,
+                            table,
+                            column,
+                        });
+                    }
+                    i = end;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+    }
+    references
+}
+
+/// Whether a change creates the object, under its final name
+fn creates(change: &SchemaChange, schema: &str, table: &str, column: Option<&str>) -> bool {
+    let same_table = |name: &str| {
+        let (s, t) = split_table_name(name);
+        s.unwrap_or("public") == schema && t == table
+    };
+    match (change, column) {
+        (SchemaChange::CreateTable { table_name, .. }, None) => same_table(table_name),
+        (SchemaChange::CreateTable { table_name, columns, .. }, Some(column)) => {
+            same_table(table_name) && columns.iter().any(|c| c.name == column)
+        }
+        (SchemaChange::RenameTable { new_name, .. }, None) => same_table(new_name),
+        (SchemaChange::AddColumn { table_name, column: def }, Some(column)) => {
+            same_table(table_name) && def.name == column
+        }
+        (SchemaChange::RenameColumn { table_name, new_name, .. }, Some(column)) => {
+            same_table(table_name) && new_name == column
+        }
+        _ => false,
+    }
+}
+
+/// Resolves references for one proposal
+pub struct Resolver<'a> {
+    proposal: &'a SchemaProposal,
+    snapshot: Option<&'a SchemaSnapshot>,
+    /// Tables each change touches, schema-qualified
+    change_tables: Vec<BTreeSet<String>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(proposal: &'a SchemaProposal, snapshot: Option<&'a SchemaSnapshot>) -> Self {
+        let empty = SchemaSnapshot::empty(proposal.connection_id);
+        let change_tables = proposal
+            .changes
+            .iter()
+            .map(|change| touched_tables(std::slice::from_ref(change), snapshot.unwrap_or(&empty)))
+            .collect();
+        Self { proposal, snapshot, change_tables }
+    }
+
+    fn resolve_one(&self, raw: RawReference) -> ObjectReference {
+        let column = raw.column.as_deref();
+        let created = || self.proposal.changes.iter().any(|c| creates(c, &raw.schema, &raw.table, column));
+        let status = match self.snapshot {
+            Some(snapshot) if exists(snapshot, &raw.schema, &raw.table, column) => ReferenceStatus::Exists,
+            _ if created() => ReferenceStatus::Created,
+            Some(_) => ReferenceStatus::Missing,
+            None => ReferenceStatus::Unknown,
+        };
+
+        let qualified = format!("{}.{}", raw.schema, raw.table);
+        let change_indexes: Vec<usize> = self
+            .change_tables
+            .iter()
+            .enumerate()
+            .filter(|(_, tables)| tables.contains(&qualified))
+            .map(|(index, _)| index)
+            .collect();
+        let risk = if change_indexes.is_empty() {
+            None
+        } else {
+            let mut subset = self.proposal.clone();
+            subset.changes = change_indexes.iter().map(|&i| self.proposal.changes[i].clone()).collect();
+            RiskEngine::new().analyze(&subset, self.snapshot).ok().map(|a| a.overall_risk)
+        };
+
+        let mut href = format!(
+            "/api/connections/{}/snapshots/latest?schema={}&table={}",
+            self.proposal.connection_id, raw.schema, raw.table
+        );
+        if let Some(column) = column {
+            href.push('#');
+            href.push_str(column);
+        }
+
+        ObjectReference {
+            reference: match column {
+                Some(column) => format!("{}.{}", qualified, column),
+                None => qualified,
+            },
+            start: raw.start,
+            end: raw.end,
+            schema: raw.schema,
+            table: raw.table,
+            column: raw.column,
+            status,
+            href,
+            change_indexes,
+            risk,
+        }
+    }
+
+    /// Resolve every reference in `markdown`
+    pub fn resolve(&self, markdown: &str) -> Vec<ObjectReference> {
+        parse(markdown).into_iter().map(|raw| self.resolve_one(raw)).collect()
+    }
+
+    pub fn annotate(&self, comment: Comment) -> AnnotatedComment {
+        let references = self.resolve(&comment.content);
+        AnnotatedComment { comment, references }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_skips_code_and_invalid_paths() {
+        let markdown = "Drops [[public.users.email]] and [[orders]].\n\
+                        Not `[[public.skip]]` or [[a b]] or [[a.b.c.d]].\n\
+                        ```\n[[public.fenced]]\n```\n\
+                        Done: [[audit.events]]";
+        let refs = parse(markdown);
+        let paths: Vec<_> = refs
+            .iter()
+            .map(|r| (r.schema.as_str(), r.table.as_str(), r.column.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("public", "users", Some("email")),
+                ("public", "orders", None),
+                ("audit", "events", None)
+            ]
+        );
+        assert_eq!(&markdown[refs[0].start..refs[0].end], "[[public.users.email]]");
+        assert_eq!(&markdown[refs[2].start..refs[2].end], "[[audit.events]]");
+    }
+
+    #[test]
+    fn test_resolve_without_snapshot_uses_proposal_changes() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes = vec![SchemaChange::CreateTable {
+            table_name: "audit".to_string(),
+            columns: vec![],
+            partition_by: None,
+        }];
+        let resolver = Resolver::new(&proposal, None);
+
+        let refs = resolver.resolve("[[audit]] and [[sales.orders.id]]");
+        assert_eq!(refs[0].reference, "public.audit");
+        assert_eq!(refs[0].status, ReferenceStatus::Created);
+        assert_eq!(refs[0].change_indexes, vec![0]);
+        assert!(refs[0].risk.is_some());
+        assert_eq!(refs[1].status, ReferenceStatus::Unknown);
+        assert!(refs[1].change_indexes.is_empty());
+        assert!(refs[1].href.ends_with("?schema=sales&table=orders#id"));
+    }
+}
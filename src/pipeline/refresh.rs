@@ -0,0 +1,81 @@
+//! Post-execution refresh
+//!
+//! Once a proposal has executed, the stored snapshot describes the schema it
+//! replaced. A background job re-introspects the connection, saves the result
+//! as the proposal's result snapshot and verifies it against the proposal's
+//! changes. A verified snapshot becomes the drift baseline, so drift checks
+//! compare against the schema that was just approved; an unverified one is
+//! left out of the baseline and admins are alerted.
+
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::notifications::{Audience, Notification};
+use crate::pipeline::metadata::{AuditAction, AuditEntry, ProposalSummary};
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::reanalysis::invalidate_risk;
+use crate::pipeline::verification::verify;
+use crate::snapshot::dictionary;
+use crate::state::SharedState;
+use tracing::{info, warn};
+
+/// Queue a refresh of the proposal's connection after a successful execution
+pub fn schedule_refresh(state: &SharedState, proposal: &SchemaProposal) {
+    let state = state.clone();
+    let proposal = proposal.clone();
+    tokio::spawn(async move {
+        if let Err(e) = refresh(&state, &proposal).await {
+            warn!("Post-execution refresh failed for proposal {}: {}", proposal.id, e);
+        }
+    });
+}
+
+async fn refresh(state: &SharedState, proposal: &SchemaProposal) -> Result<SchemaSnapshot, AppError> {
+    let connection_id = proposal.connection_id;
+    let scope = state.connections.schema_scope(connection_id).await;
+
+    let mut snapshot = state
+        .connections
+        .introspect(connection_id)
+        .await?
+        .with_column_order(state.connections.column_order(connection_id).await);
+    dictionary::annotate(state, &mut snapshot).await?;
+    let snapshot = state.snapshots.save(snapshot).await?;
+    state.pipeline_proposals.set_result_snapshot(proposal.id, snapshot.id).await?;
+
+    let entry = AuditEntry::new(AuditAction::SchemaChanged, "system", "snapshot", &snapshot.id.to_string())
+        .with_details(&format!("Snapshot v{} taken after executing proposal {}", snapshot.version, proposal.id));
+    state.metadata.record_audit_entry(entry).await;
+
+    let report = verify(proposal, &snapshot, &scope);
+    let passed = report.passed;
+    let mismatches = report.mismatches.clone();
+    let updated = state.pipeline_proposals.record_verification(proposal.id, report).await?;
+
+    if passed {
+        state.snapshots.set_baseline(connection_id, snapshot.id).await?;
+    } else {
+        state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+
+        let entry = AuditEntry::new(AuditAction::ProposalVerificationFailed, "system", "proposal", &proposal.id.to_string())
+            .with_details(&mismatches.join("; "));
+        state.metadata.record_audit_entry(entry).await;
+
+        warn!("Proposal {} failed verification: {} mismatch(es)", proposal.id, mismatches.len());
+        state.notifier.notify(
+            Notification::VerificationFailed { proposal: updated, mismatches },
+            Audience::Admins,
+        );
+    }
+
+    // Other open proposals on this connection were analyzed against the old schema
+    invalidate_risk(state, connection_id, "proposal execution").await;
+
+    info!(
+        "Refreshed connection {} after proposal {}: snapshot v{} ({})",
+        connection_id,
+        proposal.id,
+        snapshot.version,
+        if passed { "new baseline" } else { "verification failed" }
+    );
+    Ok(snapshot)
+}
@@ -0,0 +1,440 @@
+//! Column reorder emulation
+//!
+//! PostgreSQL stores columns in the order they were added and cannot move
+//! them. A `ReorderColumns` change is carried out the way it would be by
+//! hand, in stages separated by confirmation gates:
+//!
+//! 1. create `<table>__reorder` with the columns in the new order, and a
+//!    trigger mirroring every write on the table into it
+//! 2. copy the existing rows in batches, each committing on its own, then
+//!    sweep up whatever the row estimate missed and build the indexes
+//! 3. gate: swap the tables under a brief ACCESS EXCLUSIVE lock, moving the
+//!    primary key and index names, sequences and foreign keys over
+//! 4. validate the recreated foreign keys without blocking writes
+//! 5. gate: drop `<table>__old`
+//!
+//! Execution pauses at each gate until it is resumed with the gate's label.
+//! Tables above `proposal_policy.reorder_max_table_mb` are refused: copying
+//! them doubles their disk footprint and takes hours.
+
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::revert::column_def;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::{ForeignKeyCopy, IndexCopy, ReorderPlan, SchemaChange};
+use deadpool_postgres::Pool;
+
+/// Rows each copy statement moves
+pub const DEFAULT_BATCH_SIZE: u32 = 10_000;
+
+/// Marks each statement of the batched copy
+pub const COPY_BATCH_COMMENT: &str = "-- reorder copy batch";
+
+/// First line of a statement execution must not run without confirmation
+pub const GATE_COMMENT: &str = "-- confirmation gate:";
+
+/// Label of the gate a statement opens, if any
+pub fn gate_label(statement: &str) -> Option<&str> {
+    statement.lines().next()?.strip_prefix(GATE_COMMENT).map(str::trim)
+}
+
+/// Size of a table on disk and its estimated row count
+pub async fn table_size(pool: &Pool, table: &str) -> Result<(i64, i64), AppError> {
+    let client = pool.get().await?;
+    let (schema, name) = split_table_name(table);
+    let row = client
+        .query_opt(
+            "SELECT pg_total_relation_size(c.oid)::bigint, GREATEST(c.reltuples, 0)::bigint
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE n.nspname = $1 AND c.relname = $2",
+            &[&schema.unwrap_or("public"), &name],
+        )
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Table {} not found", table)))?;
+    Ok((row.get(0), row.get(1)))
+}
+
+/// Fill in the plan of a `ReorderColumns` change, refusing tables without a
+/// primary key or above `max_bytes`
+pub fn plan(
+    change: &SchemaChange,
+    snapshot: &SchemaSnapshot,
+    (total_bytes, estimated_rows): (i64, i64),
+    max_bytes: Option<i64>,
+    batch_size: u32,
+) -> Result<SchemaChange, AppError> {
+    let SchemaChange::ReorderColumns { table_name, column_order, .. } = change else {
+        return Err(AppError::BadRequest("Only column reorders can be planned".to_string()));
+    };
+    if batch_size == 0 {
+        return Err(AppError::Validation("Copy batch size must be at least 1".to_string()));
+    }
+    if let Some(max_bytes) = max_bytes.filter(|max| total_bytes > *max) {
+        return Err(AppError::Validation(format!(
+            "{} is {} MB, above the {} MB limit for column reorders; add new columns at the end or expose the order through a view instead",
+            table_name,
+            total_bytes / (1024 * 1024),
+            max_bytes / (1024 * 1024)
+        )));
+    }
+
+    let (schema, name) = split_table_name(table_name);
+    let schema = schema.unwrap_or("public");
+    let table = snapshot
+        .tables
+        .iter()
+        .find(|t| t.schema == schema && t.name == name)
+        .ok_or_else(|| AppError::NotFound(format!("Table {}.{} not in the latest snapshot", schema, name)))?;
+    let primary_key = table.primary_key.as_ref().ok_or_else(|| {
+        AppError::Validation(format!("{}.{} has no primary key to copy it in batches by", schema, name))
+    })?;
+
+    let mut current: Vec<_> = table.columns.iter().collect();
+    current.sort_by_key(|c| c.ordinal_position);
+    let mut requested = column_order.clone();
+    requested.sort();
+    let mut existing: Vec<String> = current.iter().map(|c| c.name.clone()).collect();
+    existing.sort();
+    if requested != existing {
+        return Err(AppError::Validation(format!(
+            "columnOrder must list every column of {}.{} exactly once",
+            schema, name
+        )));
+    }
+    if current.iter().map(|c| &c.name).eq(column_order.iter()) {
+        return Err(AppError::Validation(format!("{}.{} is already in that order", schema, name)));
+    }
+
+    let columns = column_order
+        .iter()
+        .filter_map(|name| current.iter().find(|c| &c.name == name))
+        .map(|c| {
+            let mut def = column_def(c);
+            def.is_primary_key = false;
+            def
+        })
+        .collect();
+    let indexes = snapshot
+        .indexes
+        .iter()
+        .filter(|i| i.schema == schema && i.table == name && !i.is_primary)
+        .map(|i| IndexCopy {
+            name: i.name.clone(),
+            columns: i.columns.clone(),
+            unique: i.is_unique,
+            method: i.index_type.clone(),
+        })
+        .collect();
+    let foreign_keys = snapshot
+        .foreign_keys
+        .iter()
+        .filter(|fk| {
+            (fk.source_schema == schema && fk.source_table == name)
+                || (fk.referenced_schema == schema && fk.referenced_table == name)
+        })
+        .map(|fk| ForeignKeyCopy {
+            constraint_name: fk.constraint_name.clone(),
+            table_name: format!("{}.{}", fk.source_schema, fk.source_table),
+            columns: fk.source_columns.clone(),
+            ref_table: format!("{}.{}", fk.referenced_schema, fk.referenced_table),
+            ref_columns: fk.referenced_columns.clone(),
+            on_update: fk.on_update.clone(),
+            on_delete: fk.on_delete.clone(),
+        })
+        .collect();
+
+    // Statistics lag behind writes; one spare batch absorbs the drift, and
+    // the sweep after the batches copies whatever is left
+    let batches = (estimated_rows.max(0) as u64).div_ceil(batch_size as u64) as u32 + 1;
+
+    Ok(SchemaChange::ReorderColumns {
+        table_name: format!("{}.{}", schema, name),
+        column_order: column_order.clone(),
+        plan: Some(ReorderPlan {
+            columns,
+            primary_key_name: primary_key.constraint_name.clone(),
+            key_columns: primary_key.columns.clone(),
+            indexes,
+            foreign_keys,
+            batch_size,
+            batches,
+            estimated_rows,
+            total_bytes,
+        }),
+    })
+}
+
+/// Sequence a `nextval('...')` default draws from
+fn owned_sequence(default: &str) -> Option<&str> {
+    let rest = default.strip_prefix("nextval('")?;
+    rest.split('\'').next()
+}
+
+fn foreign_key_sql(fk: &ForeignKeyCopy) -> String {
+    let mut sql = format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+        fk.table_name,
+        fk.constraint_name,
+        fk.columns.join(", "),
+        fk.ref_table,
+        fk.ref_columns.join(", ")
+    );
+    if fk.on_update != "NO ACTION" {
+        sql.push_str(&format!(" ON UPDATE {}", fk.on_update));
+    }
+    if fk.on_delete != "NO ACTION" {
+        sql.push_str(&format!(" ON DELETE {}", fk.on_delete));
+    }
+    sql.push_str(" NOT VALID;");
+    sql
+}
+
+/// Up statements of a planned reorder, gates included
+pub fn statements(table_name: &str, plan: &ReorderPlan) -> Vec<String> {
+    let (schema, name) = split_table_name(table_name);
+    let schema = schema.unwrap_or("public");
+    let table = format!("{}.{}", schema, name);
+    let staging = format!("{}.{}__reorder", schema, name);
+    let legacy = format!("{}__old", name);
+    let sync = format!("{}__reorder_sync", name);
+    let column_list = plan.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ");
+    let key_match = |left: &str, right: &str| {
+        let pair = |alias: &str| {
+            plan.key_columns.iter().map(|k| format!("{}.{}", alias, k)).collect::<Vec<_>>().join(", ")
+        };
+        format!("({}) = ({})", pair(left), pair(right))
+    };
+    let missing = format!(
+        "INSERT INTO {staging} ({columns}) SELECT {columns} FROM {table} o WHERE NOT EXISTS (SELECT 1 FROM {staging} n WHERE {matching}) LIMIT {limit} FOR SHARE ON CONFLICT DO NOTHING;",
+        staging = staging,
+        columns = column_list,
+        table = table,
+        matching = key_match("n", "o"),
+        limit = plan.batch_size
+    );
+
+    let mut definitions: Vec<String> = plan
+        .columns
+        .iter()
+        .map(|c| {
+            let mut def = format!("{} {}", c.name, c.data_type);
+            if let Some(collation) = &c.collation {
+                def.push_str(&format!(" COLLATE \"{}\"", collation));
+            }
+            if !c.nullable {
+                def.push_str(" NOT NULL");
+            }
+            if let Some(default) = &c.default_value {
+                def.push_str(&format!(" DEFAULT {}", default));
+            }
+            def
+        })
+        .collect();
+    definitions.push(format!(
+        "CONSTRAINT {}__reorder PRIMARY KEY ({})",
+        plan.primary_key_name,
+        plan.key_columns.join(", ")
+    ));
+    let new_values = plan.columns.iter().map(|c| format!("NEW.{}", c.name)).collect::<Vec<_>>().join(", ");
+    let updates = plan
+        .columns
+        .iter()
+        .filter(|c| !plan.key_columns.contains(&c.name))
+        .map(|c| format!("{} = EXCLUDED.{}", c.name, c.name))
+        .collect::<Vec<_>>();
+    let on_conflict = match updates.is_empty() {
+        true => "DO NOTHING".to_string(),
+        false => format!("DO UPDATE SET {}", updates.join(", ")),
+    };
+    let old_key = plan.key_columns.iter().map(|k| format!("OLD.{}", k)).collect::<Vec<_>>().join(", ");
+
+    let mut up = vec![
+        format!("CREATE TABLE {} (\n  {}\n);", staging, definitions.join(",\n  ")),
+        format!(
+            "CREATE FUNCTION {schema}.{sync}() RETURNS trigger LANGUAGE plpgsql AS $$\nBEGIN\n  IF TG_OP IN ('UPDATE', 'DELETE') THEN\n    DELETE FROM {staging} WHERE ({keys}) = ({old_key});\n  END IF;\n  IF TG_OP IN ('INSERT', 'UPDATE') THEN\n    INSERT INTO {staging} ({columns}) VALUES ({new_values})\n    ON CONFLICT ({keys}) {on_conflict};\n  END IF;\n  RETURN NULL;\nEND\n$$;",
+            schema = schema,
+            sync = sync,
+            staging = staging,
+            keys = plan.key_columns.join(", "),
+            old_key = old_key,
+            columns = column_list,
+            new_values = new_values,
+            on_conflict = on_conflict
+        ),
+        format!(
+            "CREATE TRIGGER {sync} AFTER INSERT OR UPDATE OR DELETE ON {table} FOR EACH ROW EXECUTE FUNCTION {schema}.{sync}();",
+            sync = sync,
+            table = table,
+            schema = schema
+        ),
+    ];
+    for batch in 1..=plan.batches {
+        up.push(format!("{} {}/{}\n{}", COPY_BATCH_COMMENT, batch, plan.batches, missing));
+    }
+    // Commits per batch, so it has to run outside a transaction block
+    up.push(format!(
+        "{} sweep\nDO $$\nDECLARE\n  copied bigint;\nBEGIN\n  LOOP\n    {}\n    GET DIAGNOSTICS copied = ROW_COUNT;\n    COMMIT;\n    EXIT WHEN copied = 0 AND NOT EXISTS (SELECT 1 FROM {} o WHERE NOT EXISTS (SELECT 1 FROM {} n WHERE {}));\n  END LOOP;\nEND\n$$;",
+        COPY_BATCH_COMMENT,
+        missing,
+        table,
+        staging,
+        key_match("n", "o")
+    ));
+    for index in &plan.indexes {
+        up.push(format!(
+            "CREATE {}INDEX CONCURRENTLY {}__reorder ON {} USING {} ({});",
+            if index.unique { "UNIQUE " } else { "" },
+            index.name,
+            staging,
+            index.method,
+            index.columns.join(", ")
+        ));
+    }
+
+    let mut swap = vec![
+        format!("{} swap {}", GATE_COMMENT, table),
+        format!("LOCK TABLE {} IN ACCESS EXCLUSIVE MODE;", table),
+        format!("DROP TRIGGER {} ON {};", sync, table),
+        format!("DROP FUNCTION {}.{}();", schema, sync),
+        format!("ALTER TABLE {} RENAME TO {};", table, legacy),
+        format!("ALTER TABLE {} RENAME TO {};", staging, name),
+        format!(
+            "ALTER TABLE {}.{} RENAME CONSTRAINT {} TO {}__old;",
+            schema, legacy, plan.primary_key_name, plan.primary_key_name
+        ),
+        format!(
+            "ALTER TABLE {} RENAME CONSTRAINT {}__reorder TO {};",
+            table, plan.primary_key_name, plan.primary_key_name
+        ),
+    ];
+    for index in &plan.indexes {
+        swap.push(format!("ALTER INDEX {}.{} RENAME TO {}__old;", schema, index.name, index.name));
+        swap.push(format!("ALTER INDEX {}.{}__reorder RENAME TO {};", schema, index.name, index.name));
+    }
+    for column in &plan.columns {
+        if let Some(sequence) = column.default_value.as_deref().and_then(owned_sequence) {
+            swap.push(format!("ALTER SEQUENCE {} OWNED BY {}.{};", sequence, table, column.name));
+        }
+    }
+    // After the renames the table's name refers to the new table
+    for fk in &plan.foreign_keys {
+        if fk.table_name != table {
+            // Incoming keys followed the old table through its rename
+            swap.push(format!("ALTER TABLE {} DROP CONSTRAINT {};", fk.table_name, fk.constraint_name));
+        }
+        swap.push(foreign_key_sql(fk));
+    }
+    up.push(swap.join("\n"));
+
+    for fk in &plan.foreign_keys {
+        up.push(format!("ALTER TABLE {} VALIDATE CONSTRAINT {};", fk.table_name, fk.constraint_name));
+    }
+    up.push(format!("ANALYZE {};", table));
+    up.push(format!("{} drop {}.{}\nDROP TABLE {}.{};", GATE_COMMENT, schema, legacy, schema, legacy));
+    up
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn snapshot() -> SchemaSnapshot {
+        let column = |name: &str, data_type: &str, position: i32, default: Option<&str>| {
+            serde_json::json!({
+                "name": name,
+                "dataType": data_type,
+                "nullable": name == "notes",
+                "defaultValue": default,
+                "isPrimaryKey": name == "id",
+                "isUnique": false,
+                "ordinalPosition": position,
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "connectionId": uuid::Uuid::new_v4(),
+            "version": 1,
+            "capturedAt": Utc::now(),
+            "tables": [{
+                "name": "users",
+                "schema": "public",
+                "columns": [
+                    column("id", "bigint", 1, Some("nextval('users_id_seq'::regclass)")),
+                    column("notes", "text", 2, None),
+                    column("email", "text", 3, None),
+                ],
+                "primaryKey": { "constraintName": "users_pkey", "columns": ["id"] },
+            }],
+            "foreignKeys": [{
+                "constraintName": "orders_user_id_fkey",
+                "sourceSchema": "public",
+                "sourceTable": "orders",
+                "sourceColumns": ["user_id"],
+                "referencedSchema": "public",
+                "referencedTable": "users",
+                "referencedColumns": ["id"],
+                "onUpdate": "NO ACTION",
+                "onDelete": "CASCADE",
+            }],
+            "indexes": [{
+                "name": "users_email_key",
+                "schema": "public",
+                "table": "users",
+                "columns": ["email"],
+                "isUnique": true,
+                "isPrimary": false,
+                "indexType": "btree",
+            }],
+            "checksum": "",
+        }))
+        .unwrap()
+    }
+
+    fn reorder(order: &[&str]) -> SchemaChange {
+        SchemaChange::ReorderColumns {
+            table_name: "users".to_string(),
+            column_order: order.iter().map(|c| c.to_string()).collect(),
+            plan: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_refuses_large_tables_and_bad_orders() {
+        let snapshot = snapshot();
+        let change = reorder(&["id", "email", "notes"]);
+        assert!(plan(&change, &snapshot, (10 << 30, 1), Some(1 << 30), DEFAULT_BATCH_SIZE).is_err());
+        assert!(plan(&reorder(&["id", "email"]), &snapshot, (1, 1), None, DEFAULT_BATCH_SIZE).is_err());
+        assert!(plan(&reorder(&["id", "notes", "email"]), &snapshot, (1, 1), None, DEFAULT_BATCH_SIZE).is_err());
+
+        let planned = plan(&change, &snapshot, (1 << 20, 25_000), Some(1 << 30), DEFAULT_BATCH_SIZE).unwrap();
+        let SchemaChange::ReorderColumns { plan: Some(plan), .. } = planned else {
+            panic!("expected a planned reorder");
+        };
+        assert_eq!(plan.batches, 4);
+        assert_eq!(plan.columns[1].name, "email");
+        assert_eq!(plan.foreign_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_statements_gate_the_swap_and_drop() {
+        let change = plan(&reorder(&["id", "email", "notes"]), &snapshot(), (1, 100), None, 50).unwrap();
+        let SchemaChange::ReorderColumns { table_name, plan: Some(plan), .. } = change else {
+            panic!("expected a planned reorder");
+        };
+        let statements = statements(&table_name, &plan);
+
+        assert!(statements[0].contains("id bigint NOT NULL DEFAULT nextval('users_id_seq'::regclass)"));
+        assert!(statements[0].find("email").unwrap() < statements[0].find("notes").unwrap());
+        let gates: Vec<&str> = statements.iter().filter_map(|s| gate_label(s)).collect();
+        assert_eq!(gates, vec!["swap public.users", "drop public.users__old"]);
+
+        let swap = statements.iter().find(|s| gate_label(s).is_some()).unwrap();
+        assert!(swap.contains("ALTER SEQUENCE users_id_seq OWNED BY public.users.id;"));
+        assert!(swap.contains("ALTER TABLE public.orders DROP CONSTRAINT orders_user_id_fkey;"));
+        assert!(swap.contains("REFERENCES public.users (id) ON DELETE CASCADE NOT VALID;"));
+        assert!(!swap.contains("\n\n"));
+        assert_eq!(statements.iter().filter(|s| s.starts_with(COPY_BATCH_COMMENT)).count(), 4);
+    }
+}
@@ -0,0 +1,253 @@
+//! Retention and legal holds
+//!
+//! With `retention.audit_days` set, the policy sweeper prunes older audit
+//! entries: their actor, target id and details are erased while the id,
+//! action, time and hashes stay, so the hash chain still verifies. With
+//! `retention.execution_days` set, journal records of finished executions
+//! older than that are deleted. Both are off by default.
+//!
+//! An admin can place a legal hold on a proposal, covering its audit entries
+//! and execution records, or on a time range of the audit log, optionally
+//! limited to one project. Nothing under an active hold is pruned however old
+//! it is; once the hold is released the next sweep catches up. Exports list
+//! the holds covering each entry.
+
+use crate::config::RetentionConfig;
+use crate::error::AppError;
+use crate::pipeline::journal::ExecutionState;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+/// What a legal hold keeps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HoldScope {
+    /// A proposal's audit entries and execution records
+    #[serde(rename_all = "camelCase")]
+    Proposal { proposal_id: Uuid },
+    /// Audit entries recorded in a time range, of one project or all
+    #[serde(rename_all = "camelCase")]
+    AuditRange {
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project_id: Option<i32>,
+    },
+}
+
+impl HoldScope {
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self {
+            HoldScope::AuditRange { from, until, .. } if from >= until => {
+                Err(AppError::Validation("A hold's range must end after it starts".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            HoldScope::Proposal { proposal_id } => format!("proposal {}", proposal_id),
+            HoldScope::AuditRange { from, until, project_id } => format!(
+                "audit entries {} to {}{}",
+                from.to_rfc3339(),
+                until.to_rfc3339(),
+                project_id.map(|id| format!(" in project {}", id)).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// A hold that keeps records out of reach of retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub scope: HoldScope,
+    pub reason: String,
+    pub placed_by: String,
+    pub placed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl LegalHold {
+    pub fn new(scope: HoldScope, reason: String, placed_by: &str) -> Result<Self, AppError> {
+        scope.validate()?;
+        if reason.trim().is_empty() {
+            return Err(AppError::Validation("A legal hold needs a reason".to_string()));
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            scope,
+            reason,
+            placed_by: placed_by.to_string(),
+            placed_at: Utc::now(),
+            released_by: None,
+            released_at: None,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.released_at.is_none()
+    }
+
+    pub fn describe(&self) -> String {
+        self.scope.describe()
+    }
+
+    pub fn covers_entry(&self, entry: &AuditEntry) -> bool {
+        match &self.scope {
+            HoldScope::Proposal { proposal_id } => {
+                entry.target_type == "proposal" && entry.target_id == proposal_id.to_string()
+            }
+            HoldScope::AuditRange { from, until, project_id } => {
+                entry.timestamp >= *from
+                    && entry.timestamp <= *until
+                    && project_id.is_none_or(|id| entry.project_id == Some(id))
+            }
+        }
+    }
+
+    pub fn covers_proposal(&self, id: Uuid) -> bool {
+        matches!(self.scope, HoldScope::Proposal { proposal_id } if proposal_id == id)
+    }
+}
+
+/// Active holds covering an audit entry
+pub fn holds_on(entry: &AuditEntry, holds: &[LegalHold]) -> Vec<Uuid> {
+    holds.iter().filter(|h| h.is_active() && h.covers_entry(entry)).map(|h| h.id).collect()
+}
+
+/// What one retention sweep did
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionResult {
+    pub pruned_audit_entries: usize,
+    /// Entries past retention kept because a hold covers them
+    pub held_audit_entries: usize,
+    pub deleted_executions: usize,
+    pub held_executions: usize,
+}
+
+/// Ids of the entries older than `cutoff` that retention may prune, and how
+/// many it must keep for a hold
+pub fn prunable_entries(entries: &[AuditEntry], holds: &[LegalHold], cutoff: DateTime<Utc>) -> (Vec<Uuid>, usize) {
+    let mut held = 0;
+    let ids = entries
+        .iter()
+        .filter(|e| e.timestamp < cutoff && e.pruned_at.is_none())
+        .filter(|e| {
+            let keep = !holds_on(e, holds).is_empty();
+            held += keep as usize;
+            !keep
+        })
+        .map(|e| e.id)
+        .collect();
+    (ids, held)
+}
+
+/// Apply the retention policy, leaving held records alone
+pub async fn enforce(state: &AppState, config: &RetentionConfig, now: DateTime<Utc>) -> Result<RetentionResult, AppError> {
+    let mut result = RetentionResult::default();
+    if config.audit_days.is_none() && config.execution_days.is_none() {
+        return Ok(result);
+    }
+    let holds: Vec<LegalHold> = state.metadata.list_legal_holds().await?.into_iter().filter(LegalHold::is_active).collect();
+
+    if let Some(days) = config.audit_days {
+        let cutoff = now - Duration::days(days as i64);
+        let (ids, held) = prunable_entries(&state.metadata.get_audit_log().await?, &holds, cutoff);
+        result.held_audit_entries = held;
+        if !ids.is_empty() {
+            result.pruned_audit_entries = state.metadata.prune_audit_entries(&ids, now).await?;
+        }
+    }
+
+    if let Some(days) = config.execution_days {
+        let cutoff = now - Duration::days(days as i64);
+        // Interrupted runs stay until someone has dealt with them
+        let finished = state
+            .journal
+            .updated_before(cutoff)
+            .await?
+            .into_iter()
+            .filter(|r| matches!(r.state, ExecutionState::Completed | ExecutionState::Failed | ExecutionState::Resumed));
+        let (held, expired): (Vec<_>, Vec<_>) =
+            finished.partition(|r| holds.iter().any(|h| h.covers_proposal(r.proposal_id)));
+        result.held_executions = held.len();
+        let ids: Vec<Uuid> = expired.iter().map(|r| r.execution_id).collect();
+        if !ids.is_empty() {
+            result.deleted_executions = state.journal.delete(&ids).await?;
+        }
+    }
+
+    if result.pruned_audit_entries > 0 || result.deleted_executions > 0 {
+        info!(
+            "Retention pruned {} audit entries and deleted {} execution records ({} and {} kept under legal hold)",
+            result.pruned_audit_entries, result.deleted_executions, result.held_audit_entries, result.held_executions
+        );
+        let entry = AuditEntry::new(AuditAction::RetentionApplied, "system", "audit_log", "retention").with_details(
+            &format!(
+                "Pruned {} audit entries and deleted {} execution records; {} entries and {} records kept under legal hold",
+                result.pruned_audit_entries, result.deleted_executions, result.held_audit_entries, result.held_executions
+            ),
+        );
+        state.metadata.record_audit_entry(entry).await;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holds_keep_entries_from_retention() {
+        let now = Utc::now();
+        let proposal_id = Uuid::new_v4();
+        let aged = |days: i64, target: &str, project: Option<i32>| {
+            let mut entry = AuditEntry::new(AuditAction::CommentAdded, "bob", "proposal", target).with_project(project);
+            entry.timestamp = now - Duration::days(days);
+            entry
+        };
+        let entries = vec![
+            aged(400, &proposal_id.to_string(), Some(1)),
+            aged(300, "other", Some(1)),
+            aged(300, "other", Some(2)),
+            aged(200, "other", Some(1)),
+            aged(10, "other", Some(1)),
+        ];
+
+        let on_proposal = LegalHold::new(HoldScope::Proposal { proposal_id }, "Litigation".to_string(), "1").unwrap();
+        let on_range = LegalHold::new(
+            HoldScope::AuditRange { from: now - Duration::days(310), until: now - Duration::days(290), project_id: Some(1) },
+            "Regulator request".to_string(),
+            "1",
+        )
+        .unwrap();
+        let mut released = LegalHold::new(
+            HoldScope::AuditRange { from: now - Duration::days(210), until: now - Duration::days(190), project_id: None },
+            "Closed matter".to_string(),
+            "1",
+        )
+        .unwrap();
+        released.released_at = Some(now);
+
+        let holds = vec![on_proposal, on_range, released];
+        let (ids, held) = prunable_entries(&entries, &holds, now - Duration::days(90));
+        assert_eq!(held, 2);
+        assert_eq!(ids, vec![entries[2].id, entries[3].id]);
+        assert_eq!(holds_on(&entries[0], &holds), vec![holds[0].id]);
+
+        let backwards = HoldScope::AuditRange { from: now, until: now - Duration::days(1), project_id: None };
+        assert!(LegalHold::new(backwards, "x".to_string(), "1").is_err());
+    }
+}
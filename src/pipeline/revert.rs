@@ -0,0 +1,522 @@
+//! Revert generation - inverse proposals for executed changes
+
+use crate::introspection::{Column, CompositeType, Domain, RlsPolicy, SchemaSnapshot, Table, View};
+use crate::pipeline::orchestrator::Orchestrator;
+use crate::pipeline::proposal::{MigrationArtifacts, SchemaProposal};
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::{ColumnDef, SchemaChange};
+
+/// Inverse of a set of changes
+#[derive(Debug, Default)]
+pub struct Inversion {
+    /// Changes that undo the originals, in reverse order
+    pub changes: Vec<SchemaChange>,
+    /// Originals that could not be inverted and need manual handling
+    pub manual_steps: Vec<String>,
+}
+
+/// Invert changes using the schema as it was before they ran.
+///
+/// Without a snapshot, drops and alterations cannot be reconstructed and are
+/// reported as manual steps.
+pub fn invert_changes(changes: &[SchemaChange], before: Option<&SchemaSnapshot>) -> Inversion {
+    let mut inversion = Inversion::default();
+
+    for change in changes.iter().rev() {
+        match invert(change, before) {
+            Ok(inverse) => inversion.changes.extend(inverse),
+            Err(step) => inversion.manual_steps.push(step),
+        }
+    }
+
+    inversion
+}
+
+/// Build a draft proposal that reverts an executed one.
+///
+/// The stored rollback SQL is reused when it fully undoes the original;
+/// otherwise the migration is generated from the inverted changes.
+pub fn build_revert(
+    original: &SchemaProposal,
+    before: Option<&SchemaSnapshot>,
+    author: &str,
+) -> (SchemaProposal, Vec<String>) {
+    let inversion = invert_changes(&original.changes, before);
+
+    let mut description = format!("Reverts proposal {} ({}).", original.id, original.title);
+    if !inversion.manual_steps.is_empty() {
+        description.push_str("\n\nManual steps required:\n- ");
+        description.push_str(&inversion.manual_steps.join("\n- "));
+    }
+
+    let mut revert = SchemaProposal::new(
+        original.connection_id,
+        format!("Revert \"{}\"", original.title),
+        description,
+        author.to_string(),
+    );
+    revert.project_id = original.project_id;
+    revert.reverts = Some(original.id);
+    revert.labels = vec!["revert".to_string()];
+    revert.changes = inversion.changes;
+
+    let stored = original
+        .migration
+        .as_ref()
+        .filter(|m| !m.down_sql.is_empty() && !m.down_sql.contains("-- Cannot auto-rollback"));
+    let migration = match stored {
+        Some(m) => MigrationArtifacts::new(m.down_sql.clone(), m.up_sql.clone()),
+        None => Orchestrator::new().generate_migration(&revert),
+    };
+    revert.record_migration(migration);
+
+    (revert, inversion.manual_steps)
+}
+
+fn invert(change: &SchemaChange, before: Option<&SchemaSnapshot>) -> Result<Vec<SchemaChange>, String> {
+    let inverse = match change {
+        SchemaChange::CreateSchema { schema_name, .. } => SchemaChange::DropSchema {
+            schema_name: schema_name.clone(),
+            cascade: false,
+        },
+        SchemaChange::DropSchema { schema_name, .. } => {
+            let owner = before
+                .and_then(|s| s.schemas.iter().find(|n| &n.name == schema_name))
+                .map(|n| n.owner.clone());
+            let mut inverse = vec![SchemaChange::CreateSchema {
+                schema_name: schema_name.clone(),
+                owner,
+            }];
+            // Recreate the tables that lived in it; their data is gone either way
+            if let Some(snapshot) = before {
+                inverse.extend(
+                    snapshot
+                        .tables
+                        .iter()
+                        .filter(|t| &t.schema == schema_name)
+                        .map(|t| create_table(&format!("{}.{}", t.schema, t.name), t)),
+                );
+            }
+            return Ok(inverse);
+        }
+        SchemaChange::RenameSchema { old_name, new_name } => SchemaChange::RenameSchema {
+            old_name: new_name.clone(),
+            new_name: old_name.clone(),
+        },
+        SchemaChange::CreateTable { table_name, .. } => SchemaChange::DropTable {
+            table_name: table_name.clone(),
+        },
+        SchemaChange::DropTable { table_name } => {
+            let table = find_table(before, table_name)
+                .ok_or_else(|| format!("Recreate dropped table {} (no prior snapshot)", table_name))?;
+            create_table(table_name, table)
+        }
+        SchemaChange::AddColumn { table_name, column } => SchemaChange::DropColumn {
+            table_name: table_name.clone(),
+            column_name: column.name.clone(),
+        },
+        SchemaChange::DropColumn { table_name, column_name } => {
+            let column = find_table(before, table_name)
+                .and_then(|t| t.columns.iter().find(|c| &c.name == column_name))
+                .ok_or_else(|| format!("Re-add dropped column {}.{} (no prior snapshot)", table_name, column_name))?;
+            SchemaChange::AddColumn {
+                table_name: table_name.clone(),
+                column: column_def(column),
+            }
+        }
+        SchemaChange::AlterColumn { table_name, column_name, new_type, new_nullable, new_default, .. } => {
+            let column = find_table(before, table_name)
+                .and_then(|t| t.columns.iter().find(|c| &c.name == column_name))
+                .ok_or_else(|| format!("Restore column {}.{} definition (no prior snapshot)", table_name, column_name))?;
+            if new_default.is_some() && column.default_value.is_none() {
+                return Err(format!("Drop the default on {}.{}", table_name, column_name));
+            }
+            SchemaChange::AlterColumn {
+                table_name: table_name.clone(),
+                column_name: column_name.clone(),
+                new_type: new_type.as_ref().map(|_| column.data_type.clone()),
+                new_nullable: new_nullable.map(|_| column.nullable),
+                new_default: new_default.as_ref().and(column.default_value.clone()),
+                using: None,
+            }
+        }
+        SchemaChange::RenameTable { old_name, new_name } => SchemaChange::RenameTable {
+            old_name: new_name.clone(),
+            new_name: old_name.clone(),
+        },
+        SchemaChange::RenameColumn { table_name, old_name, new_name } => SchemaChange::RenameColumn {
+            table_name: table_name.clone(),
+            old_name: new_name.clone(),
+            new_name: old_name.clone(),
+        },
+        SchemaChange::AddIndex { index_name, .. } => SchemaChange::DropIndex {
+            index_name: index_name.clone(),
+        },
+        SchemaChange::DropIndex { index_name } => {
+            let (schema, name) = split_table_name(index_name);
+            let index = before
+                .and_then(|s| s.indexes.iter().find(|i| i.name == name && schema.is_none_or(|sc| sc == i.schema)))
+                .ok_or_else(|| format!("Recreate dropped index {} (no prior snapshot)", index_name))?;
+            SchemaChange::AddIndex {
+                table_name: format!("{}.{}", index.schema, index.table),
+                index_name: index.name.clone(),
+                columns: index.columns.clone(),
+                unique: index.is_unique,
+                concurrently: true,
+            }
+        }
+        SchemaChange::AddForeignKey { table_name, constraint_name, .. } => SchemaChange::DropForeignKey {
+            table_name: table_name.clone(),
+            constraint_name: constraint_name.clone(),
+        },
+        SchemaChange::DropForeignKey { table_name, constraint_name } => {
+            let fk = before
+                .and_then(|s| s.foreign_keys.iter().find(|fk| &fk.constraint_name == constraint_name))
+                .ok_or_else(|| format!("Recreate foreign key {} (no prior snapshot)", constraint_name))?;
+            SchemaChange::AddForeignKey {
+                table_name: table_name.clone(),
+                constraint_name: constraint_name.clone(),
+                columns: fk.source_columns.clone(),
+                ref_table: format!("{}.{}", fk.referenced_schema, fk.referenced_table),
+                ref_columns: fk.referenced_columns.clone(),
+            }
+        }
+        SchemaChange::AddCheck { table_name, constraint_name, .. }
+        | SchemaChange::AddUnique { table_name, constraint_name, .. } => {
+            return Err(format!("Drop constraint {} on {}", constraint_name, table_name));
+        }
+        SchemaChange::DropConstraint { table_name, constraint_name } => {
+            return Err(format!("Recreate constraint {} on {} if it is still needed", constraint_name, table_name));
+        }
+        SchemaChange::Backfill { table_name, column_name, .. } => {
+            return Err(format!("Backfilled values in {}.{} are kept", table_name, column_name));
+        }
+        SchemaChange::ReorderColumns { table_name, .. } => {
+            return Err(format!("Reorder the columns of {} back with a new reorder, or swap {}__old back in before it is dropped", table_name, table_name));
+        }
+        SchemaChange::ValidateConstraint { .. } => return Ok(Vec::new()),
+        // Maintenance leaves the schema as it was
+        SchemaChange::Reindex { .. } | SchemaChange::Vacuum { .. } | SchemaChange::Analyze { .. } => {
+            return Ok(Vec::new());
+        }
+        SchemaChange::CreateExtension { name, .. } => SchemaChange::DropExtension {
+            name: name.clone(),
+            cascade: false,
+        },
+        SchemaChange::DropExtension { name, .. } => {
+            let extension = before
+                .and_then(|s| s.extensions.iter().find(|e| &e.name == name))
+                .ok_or_else(|| format!("Recreate extension {} (no prior snapshot)", name))?;
+            SchemaChange::CreateExtension {
+                name: name.clone(),
+                schema: Some(extension.schema.clone()),
+                version: Some(extension.version.clone()),
+            }
+        }
+        SchemaChange::AlterExtensionVersion { name, .. } => {
+            let extension = before
+                .and_then(|s| s.extensions.iter().find(|e| &e.name == name))
+                .ok_or_else(|| format!("Restore the previous version of extension {} (no prior snapshot)", name))?;
+            SchemaChange::AlterExtensionVersion {
+                name: name.clone(),
+                version: extension.version.clone(),
+            }
+        }
+        SchemaChange::CreatePartition { table_name, .. } => SchemaChange::DropTable {
+            table_name: table_name.clone(),
+        },
+        SchemaChange::AttachPartition { parent_table, partition_name, .. } => SchemaChange::DetachPartition {
+            parent_table: parent_table.clone(),
+            partition_name: partition_name.clone(),
+            concurrently: false,
+        },
+        SchemaChange::DetachPartition { parent_table, partition_name, .. } => {
+            // Snapshots do not record partition bounds
+            return Err(format!("Re-attach {} to {} with its previous bound", partition_name, parent_table));
+        }
+        SchemaChange::SetCustomField { table_name, column_name, field, .. } => {
+            let table = find_table(before, table_name)
+                .ok_or_else(|| format!("Restore custom field {} on {} (no prior snapshot)", field, table_name))?;
+            let previous = match column_name {
+                Some(column) => table
+                    .columns
+                    .iter()
+                    .find(|c| &c.name == column)
+                    .and_then(|c| c.custom_fields.get(field)),
+                None => table.governance.custom_fields.get(field),
+            };
+            SchemaChange::SetCustomField {
+                table_name: table_name.clone(),
+                column_name: column_name.clone(),
+                field: field.clone(),
+                value: previous.cloned(),
+            }
+        }
+        SchemaChange::SetClassification { table_name, column_name, .. } => {
+            let column = find_table(before, table_name)
+                .and_then(|t| t.columns.iter().find(|c| &c.name == column_name))
+                .ok_or_else(|| {
+                    format!("Restore the classification of {}.{} (no prior snapshot)", table_name, column_name)
+                })?;
+            SchemaChange::SetClassification {
+                table_name: table_name.clone(),
+                column_name: column_name.clone(),
+                level: column.pii_classification.clone(),
+            }
+        }
+        SchemaChange::CreatePolicy { table_name, policy_name, .. } => SchemaChange::DropPolicy {
+            table_name: table_name.clone(),
+            policy_name: policy_name.clone(),
+        },
+        SchemaChange::DropPolicy { table_name, policy_name } => {
+            let policy = find_policy(before, table_name, policy_name)
+                .ok_or_else(|| format!("Recreate policy {} on {} (no prior snapshot)", policy_name, table_name))?;
+            create_policy(table_name, policy)
+        }
+        SchemaChange::AlterPolicy { table_name, policy_name, using, with_check, .. } => {
+            let policy = find_policy(before, table_name, policy_name)
+                .ok_or_else(|| format!("Restore policy {} on {} (no prior snapshot)", policy_name, table_name))?;
+            // ALTER POLICY can replace an expression but not remove one
+            if (using.is_some() && policy.using.is_none()) || (with_check.is_some() && policy.with_check.is_none()) {
+                return Err(format!("Recreate policy {} on {} without the added expression", policy_name, table_name));
+            }
+            SchemaChange::AlterPolicy {
+                table_name: table_name.clone(),
+                policy_name: policy_name.clone(),
+                roles: Some(policy.roles.clone()),
+                using: using.as_ref().and(policy.using.clone()),
+                with_check: with_check.as_ref().and(policy.with_check.clone()),
+            }
+        }
+        SchemaChange::SetRowSecurity { table_name, forced, .. } => {
+            let rls = find_table(before, table_name)
+                .and_then(|t| t.governance.row_security.as_ref())
+                .ok_or_else(|| format!("Restore row-level security on {} (no prior snapshot)", table_name))?;
+            SchemaChange::SetRowSecurity {
+                table_name: table_name.clone(),
+                enabled: rls.enabled,
+                forced: forced.map(|_| rls.forced),
+            }
+        }
+        SchemaChange::AddTag { table_name, column_name, tag } => SchemaChange::RemoveTag {
+            table_name: table_name.clone(),
+            column_name: column_name.clone(),
+            tag: tag.clone(),
+        },
+        SchemaChange::RemoveTag { table_name, column_name, tag } => SchemaChange::AddTag {
+            table_name: table_name.clone(),
+            column_name: column_name.clone(),
+            tag: tag.clone(),
+        },
+        SchemaChange::CreateDomain { domain_name, .. } => SchemaChange::DropDomain {
+            domain_name: domain_name.clone(),
+            cascade: false,
+        },
+        SchemaChange::DropDomain { domain_name, .. } => {
+            let domain = find_domain(before, domain_name)
+                .ok_or_else(|| format!("Recreate domain {} (no prior snapshot)", domain_name))?;
+            SchemaChange::CreateDomain {
+                domain_name: domain_name.clone(),
+                base_type: domain.base_type.clone(),
+                not_null: domain.not_null,
+                default_value: domain.default_value.clone(),
+                checks: domain.checks.clone(),
+            }
+        }
+        SchemaChange::AlterDomain { domain_name, set_default, drop_default, not_null, add_checks, drop_checks } => {
+            let domain = find_domain(before, domain_name)
+                .ok_or_else(|| format!("Restore domain {} definition (no prior snapshot)", domain_name))?;
+            let default_changed = set_default.is_some() || *drop_default;
+            let dropped = drop_checks
+                .iter()
+                .map(|name| {
+                    domain
+                        .checks
+                        .iter()
+                        .find(|c| &c.name == name)
+                        .cloned()
+                        .ok_or_else(|| format!("Recreate check {} on domain {}", name, domain_name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            SchemaChange::AlterDomain {
+                domain_name: domain_name.clone(),
+                set_default: domain.default_value.clone().filter(|_| default_changed),
+                drop_default: default_changed && domain.default_value.is_none(),
+                not_null: not_null.map(|_| domain.not_null),
+                add_checks: dropped,
+                drop_checks: add_checks.iter().map(|c| c.name.clone()).collect(),
+            }
+        }
+        SchemaChange::CreateCompositeType { type_name, .. } => SchemaChange::DropCompositeType {
+            type_name: type_name.clone(),
+            cascade: false,
+        },
+        SchemaChange::DropCompositeType { type_name, .. } => {
+            let composite = find_composite(before, type_name)
+                .ok_or_else(|| format!("Recreate type {} (no prior snapshot)", type_name))?;
+            SchemaChange::CreateCompositeType {
+                type_name: type_name.clone(),
+                attributes: composite.attributes.clone(),
+            }
+        }
+        SchemaChange::CreateView { view_name, .. } => SchemaChange::DropView {
+            view_name: view_name.clone(),
+            cascade: false,
+        },
+        SchemaChange::AlterView { view_name, .. } => {
+            let view = find_view(before, view_name, false)
+                .ok_or_else(|| format!("Restore the definition of view {} (no prior snapshot)", view_name))?;
+            SchemaChange::AlterView {
+                view_name: view_name.clone(),
+                query: view.definition.clone(),
+            }
+        }
+        SchemaChange::DropView { view_name, .. } => {
+            let view = find_view(before, view_name, false)
+                .ok_or_else(|| format!("Recreate view {} (no prior snapshot)", view_name))?;
+            SchemaChange::CreateView {
+                view_name: view_name.clone(),
+                query: view.definition.clone(),
+            }
+        }
+        SchemaChange::CreateMaterializedView { view_name, .. } => SchemaChange::DropMaterializedView {
+            view_name: view_name.clone(),
+            cascade: false,
+        },
+        SchemaChange::DropMaterializedView { view_name, .. } => {
+            let view = find_view(before, view_name, true)
+                .ok_or_else(|| format!("Recreate materialized view {} (no prior snapshot)", view_name))?;
+            SchemaChange::CreateMaterializedView {
+                view_name: view_name.clone(),
+                query: view.definition.clone(),
+                with_no_data: false,
+            }
+        }
+        // Refreshed rows are what the query returns now; there is nothing to restore
+        SchemaChange::RefreshMaterializedView { .. } => return Ok(Vec::new()),
+        SchemaChange::AlterCompositeType { type_name, add_attributes, drop_attributes, alter_attributes } => {
+            let composite = find_composite(before, type_name)
+                .ok_or_else(|| format!("Restore type {} definition (no prior snapshot)", type_name))?;
+            let previous = |name: &String| {
+                composite
+                    .attributes
+                    .iter()
+                    .find(|a| &a.name == name)
+                    .cloned()
+                    .ok_or_else(|| format!("Restore attribute {} of type {}", name, type_name))
+            };
+            SchemaChange::AlterCompositeType {
+                type_name: type_name.clone(),
+                add_attributes: drop_attributes.iter().map(previous).collect::<Result<_, _>>()?,
+                drop_attributes: add_attributes.iter().map(|a| a.name.clone()).collect(),
+                alter_attributes: alter_attributes.iter().map(|a| previous(&a.name)).collect::<Result<_, _>>()?,
+            }
+        }
+    };
+
+    Ok(vec![inverse])
+}
+
+fn find_table<'a>(before: Option<&'a SchemaSnapshot>, table_name: &str) -> Option<&'a Table> {
+    let (schema, name) = split_table_name(table_name);
+    before?
+        .tables
+        .iter()
+        .find(|t| t.name == name && t.schema == schema.unwrap_or("public"))
+}
+
+fn find_policy<'a>(before: Option<&'a SchemaSnapshot>, table_name: &str, policy_name: &str) -> Option<&'a RlsPolicy> {
+    find_table(before, table_name)?
+        .governance
+        .row_security
+        .as_ref()?
+        .policies
+        .iter()
+        .find(|p| p.name == policy_name)
+}
+
+fn find_domain<'a>(before: Option<&'a SchemaSnapshot>, domain_name: &str) -> Option<&'a Domain> {
+    let (schema, name) = split_table_name(domain_name);
+    before?.types.as_ref()?.domain(&format!("{}.{}", schema.unwrap_or("public"), name))
+}
+
+fn find_composite<'a>(before: Option<&'a SchemaSnapshot>, type_name: &str) -> Option<&'a CompositeType> {
+    let (schema, name) = split_table_name(type_name);
+    before?.types.as_ref()?.composite(&format!("{}.{}", schema.unwrap_or("public"), name))
+}
+
+fn find_view<'a>(before: Option<&'a SchemaSnapshot>, view_name: &str, materialized: bool) -> Option<&'a View> {
+    let (schema, name) = split_table_name(view_name);
+    let schema = schema.unwrap_or("public");
+    before?.views.as_ref()?.iter().find(|v| v.schema == schema && v.name == name && v.materialized == materialized)
+}
+
+pub(crate) fn create_policy(table_name: &str, policy: &RlsPolicy) -> SchemaChange {
+    SchemaChange::CreatePolicy {
+        table_name: table_name.to_string(),
+        policy_name: policy.name.clone(),
+        restrictive: !policy.permissive,
+        command: Some(policy.command.clone()),
+        roles: policy.roles.clone(),
+        using: policy.using.clone(),
+        with_check: policy.with_check.clone(),
+    }
+}
+
+pub(crate) fn create_table(table_name: &str, table: &Table) -> SchemaChange {
+    let mut columns: Vec<&Column> = table.columns.iter().collect();
+    columns.sort_by_key(|c| c.ordinal_position);
+
+    SchemaChange::CreateTable {
+        table_name: table_name.to_string(),
+        columns: columns.into_iter().map(column_def).collect(),
+        partition_by: None,
+    }
+}
+
+pub(crate) fn column_def(column: &Column) -> ColumnDef {
+    ColumnDef {
+        name: column.name.clone(),
+        data_type: column.data_type.clone(),
+        nullable: column.nullable,
+        default_value: column.default_value.clone(),
+        is_primary_key: column.is_primary_key,
+        collation: column.collation.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert_changes_reverses_order() {
+        let changes = vec![
+            SchemaChange::RenameTable { old_name: "users".into(), new_name: "accounts".into() },
+            SchemaChange::AddColumn {
+                table_name: "accounts".into(),
+                column: ColumnDef {
+                    name: "email".into(),
+                    data_type: "text".into(),
+                    nullable: true,
+                    default_value: None,
+                    is_primary_key: false,
+                    collation: None,
+                },
+            },
+            SchemaChange::DropTable { table_name: "legacy".into() },
+        ];
+
+        let inversion = invert_changes(&changes, None);
+
+        assert_eq!(inversion.manual_steps.len(), 1);
+        assert!(matches!(
+            &inversion.changes[0],
+            SchemaChange::DropColumn { table_name, column_name } if table_name == "accounts" && column_name == "email"
+        ));
+        assert!(matches!(
+            &inversion.changes[1],
+            SchemaChange::RenameTable { old_name, new_name } if old_name == "accounts" && new_name == "users"
+        ));
+    }
+}
@@ -1,43 +1,114 @@
 //! Risk analysis engine
 
 use crate::error::AppError;
+use crate::i18n::Message;
+use crate::introspection::{DatabaseMetadata, SchemaSnapshot, Table};
 use crate::pipeline::proposal::{RiskAnalysis, RiskLevel, SchemaProposal};
-use crate::pipeline::types::SchemaChange;
+use crate::pipeline::types::{ReindexTarget, SchemaChange};
 use chrono::Utc;
 
 /// Risk analysis engine
 pub struct RiskEngine;
 
+/// Risk level of a score
+pub fn level_for(score: u32) -> RiskLevel {
+    match score {
+        0..=20 => RiskLevel::Low,
+        21..=50 => RiskLevel::Medium,
+        51..=100 => RiskLevel::High,
+        _ => RiskLevel::Critical,
+    }
+}
+
+/// English messages with their codes, kept in step
+#[derive(Default)]
+struct Findings {
+    text: Vec<String>,
+    codes: Vec<Message>,
+}
+
+impl Findings {
+    fn push(&mut self, message: Message, text: String) {
+        self.codes.push(message);
+        self.text.push(text);
+    }
+}
+
 impl RiskEngine {
     pub fn new() -> Self {
         Self
     }
 
-    /// Analyze the risk of a proposal
-    pub fn analyze(&self, proposal: &SchemaProposal) -> Result<RiskAnalysis, AppError> {
+    /// Analyze the risk of a proposal, optionally against the latest snapshot of the target database
+    pub fn analyze(
+        &self,
+        proposal: &SchemaProposal,
+        snapshot: Option<&SchemaSnapshot>,
+    ) -> Result<RiskAnalysis, AppError> {
         let mut score = 0u32;
-        let mut warnings = Vec::new();
-        let mut recommendations = Vec::new();
+        let mut warnings = Findings::default();
+        let mut recommendations = Findings::default();
         let mut affected_tables = Vec::new();
         let mut requires_downtime = false;
 
         for change in &proposal.changes {
             match change {
+                SchemaChange::DropSchema { schema_name, cascade } => {
+                    let table_count = snapshot
+                        .map(|s| s.tables.iter().filter(|t| &t.schema == schema_name).count())
+                        .unwrap_or(0);
+                    if table_count > 0 {
+                        // Exceeds the critical threshold on its own
+                        score += 200;
+                        let code = if *cascade { "risk.drop_schema_cascade" } else { "risk.drop_schema_not_empty" };
+                        warnings.push(
+                            Message::new(code).with("schema", schema_name).with("tables", table_count),
+                            format!(
+                                "Schema '{}' still contains {} table(s); {}",
+                                schema_name,
+                                table_count,
+                                if *cascade { "CASCADE will drop all of them" } else { "the drop will fail until they are moved or dropped" }
+                            ),
+                        );
+                    } else {
+                        score += 20;
+                    }
+                    requires_downtime = requires_downtime || *cascade;
+                }
+                SchemaChange::RenameSchema { old_name, new_name } => {
+                    score += 30;
+                    warnings.push(
+                        Message::new("risk.rename_schema").with("old", old_name).with("new", new_name),
+                        format!(
+                            "Renaming schema '{}' to '{}' breaks every query and search_path that references it",
+                            old_name, new_name
+                        ),
+                    );
+                }
                 SchemaChange::DropTable { table_name } => {
                     score += 100;
-                    warnings.push(format!("Dropping table '{}' is destructive and irreversible", table_name));
+                    warnings.push(
+                        Message::new("risk.drop_table").with("table", table_name),
+                        format!("Dropping table '{}' is destructive and irreversible", table_name),
+                    );
                     affected_tables.push(table_name.clone());
                     requires_downtime = true;
                 }
                 SchemaChange::DropColumn { table_name, column_name } => {
                     score += 50;
-                    warnings.push(format!("Dropping column '{}' from '{}' is destructive", column_name, table_name));
+                    warnings.push(
+                        Message::new("risk.drop_column").with("table", table_name).with("column", column_name),
+                        format!("Dropping column '{}' from '{}' is destructive", column_name, table_name),
+                    );
                     affected_tables.push(table_name.clone());
                 }
                 SchemaChange::AlterColumn { table_name, column_name, new_type, .. } => {
                     if new_type.is_some() {
                         score += 30;
-                        warnings.push(format!("Changing type of '{}' in '{}' may cause data loss", column_name, table_name));
+                        warnings.push(
+                            Message::new("risk.type_change").with("table", table_name).with("column", column_name),
+                            format!("Changing type of '{}' in '{}' may cause data loss", column_name, table_name),
+                        );
                     }
                     affected_tables.push(table_name.clone());
                 }
@@ -48,54 +119,619 @@ impl RiskEngine {
                 SchemaChange::AddColumn { table_name, column, .. } => {
                     if !column.nullable && column.default_value.is_none() {
                         score += 20;
-                        warnings.push(format!("Adding non-nullable column '{}' without default to '{}' may fail on existing rows", column.name, table_name));
+                        warnings.push(
+                            Message::new("risk.not_null_without_default").with("table", table_name).with("column", &column.name),
+                            format!("Adding non-nullable column '{}' without default to '{}' may fail on existing rows", column.name, table_name),
+                        );
                     }
                     affected_tables.push(table_name.clone());
                 }
-                SchemaChange::AddIndex { table_name, .. } => {
+                SchemaChange::AddIndex { table_name, concurrently, .. } => {
                     score += 10;
-                    recommendations.push(format!("Consider using CONCURRENTLY for index on '{}'", table_name));
+                    if !concurrently {
+                        recommendations.push(
+                            Message::new("risk.index_concurrently").with("table", table_name),
+                            format!("Consider using CONCURRENTLY for index on '{}'", table_name),
+                        );
+                    }
                     affected_tables.push(table_name.clone());
                 }
                 SchemaChange::AddForeignKey { table_name, .. } => {
                     score += 15;
                     affected_tables.push(table_name.clone());
                 }
+                SchemaChange::Reindex { target, name, concurrently } => {
+                    let table_name = match target {
+                        ReindexTarget::Table => Some(name.clone()),
+                        ReindexTarget::Index => snapshot.and_then(|s| index_table(s, name)),
+                    };
+                    let index_count = match (target, snapshot, &table_name) {
+                        (ReindexTarget::Table, Some(s), Some(table)) => table_indexes(s, table).max(1),
+                        _ => 1,
+                    };
+
+                    if *concurrently {
+                        score += 5 * index_count;
+                        recommendations.push(
+                            Message::new("risk.reindex_outside_transaction").with("target", name),
+                            format!(
+                                "REINDEX CONCURRENTLY {} cannot run inside a transaction block; it will run on its own",
+                                name
+                            ),
+                        );
+                    } else {
+                        // Blocks writes to the table and reads that would use the index until done
+                        score += 20 * index_count;
+                        requires_downtime = true;
+                        let message = match &table_name {
+                            Some(table) => Message::new("risk.reindex_blocks_table_writes").with("table", table),
+                            None => Message::new("risk.reindex_blocks_writes"),
+                        };
+                        warnings.push(
+                            message.with("target", name).with("indexes", index_count),
+                            format!(
+                                "REINDEX {} blocks writes{} while {} index(es) rebuild",
+                                name,
+                                table_name.as_ref().map(|t| format!(" to '{}'", t)).unwrap_or_default(),
+                                index_count
+                            ),
+                        );
+                        recommendations.push(
+                            Message::new("risk.reindex_concurrently").with("target", name),
+                            format!("Use REINDEX CONCURRENTLY for {}", name),
+                        );
+                    }
+                    affected_tables.extend(table_name);
+                }
+                SchemaChange::Vacuum { table_name, full, .. } => {
+                    if *full {
+                        score += 60;
+                        requires_downtime = true;
+                        warnings.push(
+                            Message::new("risk.vacuum_full").with("table", table_name),
+                            format!(
+                                "VACUUM FULL rewrites '{}' under an ACCESS EXCLUSIVE lock and needs free disk space equal to its size",
+                                table_name
+                            ),
+                        );
+                        recommendations.push(
+                            Message::new("risk.pg_repack").with("table", table_name),
+                            format!("Consider pg_repack for '{}' if it cannot be taken offline", table_name),
+                        );
+                    } else {
+                        score += 2;
+                    }
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::Analyze { table_name } => {
+                    score += 1;
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::Backfill { table_name, .. } => {
+                    // Batched, so row locks are short; the writes still add load
+                    score += 10;
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::ReorderColumns { table_name, plan, .. } => {
+                    affected_tables.push(table_name.clone());
+                    match plan {
+                        Some(plan) => {
+                            // Every row is rewritten and the table briefly locked for the swap
+                            score += 40;
+                            warnings.push(
+                                Message::new("risk.reorder_copies_table")
+                                    .with("table", table_name)
+                                    .with("rows", plan.estimated_rows),
+                                format!(
+                                    "Reordering {} copies about {} rows into a new table, doubling its disk usage until the old copy is dropped",
+                                    table_name, plan.estimated_rows
+                                ),
+                            );
+                            recommendations.push(
+                                Message::new("risk.reorder_not_copied").with("table", table_name),
+                                format!(
+                                    "Triggers, policies, grants, identity columns and dependent views of {} are not moved to the new table; recreate them before the swap",
+                                    table_name
+                                ),
+                            );
+                        }
+                        None => {
+                            score += 50;
+                            warnings.push(
+                                Message::new("risk.reorder_unplanned").with("table", table_name),
+                                format!("The reorder of {} was never planned and generates no SQL", table_name),
+                            );
+                        }
+                    }
+                }
+                SchemaChange::DropExtension { name, cascade } => {
+                    let dependents = snapshot
+                        .and_then(|s| s.extensions.iter().find(|e| &e.name == name))
+                        .map(|e| e.dependent_objects)
+                        .unwrap_or(0);
+                    if dependents > 0 {
+                        score += if *cascade { 150 } else { 60 };
+                        warnings.push(
+                            Message::new("risk.drop_extension_dependents")
+                                .with("extension", name)
+                                .with("dependents", dependents),
+                            format!(
+                                "Extension '{}' is used by {} column(s) or index(es); {}",
+                                name,
+                                dependents,
+                                if *cascade { "CASCADE will drop them" } else { "the drop will fail until they are removed" }
+                            ),
+                        );
+                    } else {
+                        score += 20;
+                    }
+                    requires_downtime = requires_downtime || *cascade;
+                }
+                SchemaChange::AlterExtensionVersion { name, version } => {
+                    score += 20;
+                    let current = snapshot
+                        .and_then(|s| s.extensions.iter().find(|e| &e.name == name))
+                        .map(|e| e.version.clone())
+                        .unwrap_or_else(|| "?".to_string());
+                    recommendations.push(
+                        Message::new("risk.extension_update")
+                            .with("extension", name)
+                            .with("from", &current)
+                            .with("to", version),
+                        format!(
+                            "Test the update of extension '{}' from {} to {} on staging; update scripts can change function behavior",
+                            name, current, version
+                        ),
+                    );
+                }
+                SchemaChange::CreatePartition { table_name, parent_table, .. } => {
+                    score += 5;
+                    affected_tables.push(parent_table.clone());
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::AttachPartition { parent_table, partition_name, .. } => {
+                    score += 25;
+                    warnings.push(
+                        Message::new("risk.attach_partition_scan")
+                            .with("table", partition_name)
+                            .with("parent", parent_table),
+                        format!(
+                            "Attaching '{}' to '{}' locks '{}' and scans every row against the bound",
+                            partition_name, parent_table, partition_name
+                        ),
+                    );
+                    recommendations.push(
+                        Message::new("risk.attach_partition_check").with("table", partition_name),
+                        format!(
+                            "Add and validate a CHECK constraint matching the bound on '{}' first so the attach can skip the scan",
+                            partition_name
+                        ),
+                    );
+                    affected_tables.push(parent_table.clone());
+                    affected_tables.push(partition_name.clone());
+                }
+                SchemaChange::DetachPartition { parent_table, partition_name, concurrently } => {
+                    if *concurrently {
+                        score += 10;
+                        recommendations.push(
+                            Message::new("risk.detach_outside_transaction").with("table", partition_name),
+                            format!(
+                                "DETACH PARTITION {} CONCURRENTLY cannot run inside a transaction block; it will run on its own",
+                                partition_name
+                            ),
+                        );
+                    } else {
+                        score += 30;
+                        requires_downtime = true;
+                        warnings.push(
+                            Message::new("risk.detach_partition_blocks").with("parent", parent_table),
+                            format!("Detaching '{}' blocks all queries on '{}' until it completes", partition_name, parent_table),
+                        );
+                        recommendations.push(
+                            Message::new("risk.detach_concurrently").with("table", partition_name),
+                            format!("Use DETACH PARTITION {} CONCURRENTLY (PostgreSQL 14+)", partition_name),
+                        );
+                    }
+                    affected_tables.push(parent_table.clone());
+                    affected_tables.push(partition_name.clone());
+                }
+                SchemaChange::SetRowSecurity { table_name, enabled: false, .. } => {
+                    let pii = snapshot
+                        .and_then(|s| snapshot_table(s, table_name))
+                        .map(|t| t.pii_columns())
+                        .unwrap_or_default();
+                    if pii.is_empty() {
+                        score += 40;
+                        warnings.push(
+                            Message::new("risk.disable_rls").with("table", table_name),
+                            format!("Disabling row-level security on '{}' makes every row visible to anyone with table privileges", table_name),
+                        );
+                    } else {
+                        // Exceeds the critical threshold on its own
+                        score += 120;
+                        warnings.push(
+                            Message::new("risk.disable_rls_pii")
+                                .with("table", table_name)
+                                .with("columns", pii.join(", ")),
+                            format!(
+                                "Disabling row-level security on '{}' exposes every row of its PII column(s) {}",
+                                table_name,
+                                pii.join(", ")
+                            ),
+                        );
+                    }
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::SetRowSecurity { table_name, enabled: true, .. } => {
+                    let existing = snapshot
+                        .and_then(|s| snapshot_table(s, table_name))
+                        .and_then(|t| t.governance.row_security.as_ref())
+                        .is_some_and(|rls| !rls.policies.is_empty());
+                    let proposed = proposal.changes.iter().any(
+                        |c| matches!(c, SchemaChange::CreatePolicy { table_name: t, .. } if t == table_name),
+                    );
+                    if existing || proposed {
+                        score += 15;
+                    } else {
+                        score += 60;
+                        warnings.push(
+                            Message::new("risk.rls_without_policies").with("table", table_name),
+                            format!(
+                                "'{}' has no row-level security policies; enabling it hides every row from roles other than the owner",
+                                table_name
+                            ),
+                        );
+                    }
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::DropPolicy { table_name, policy_name } => {
+                    score += 25;
+                    let restrictive = snapshot
+                        .and_then(|s| snapshot_table(s, table_name))
+                        .and_then(|t| t.governance.row_security.as_ref())
+                        .and_then(|rls| rls.policies.iter().find(|p| &p.name == policy_name))
+                        .is_some_and(|p| !p.permissive);
+                    if restrictive {
+                        score += 25;
+                        warnings.push(
+                            Message::new("risk.drop_restrictive_policy")
+                                .with("policy", policy_name)
+                                .with("table", table_name),
+                            format!("Dropping restrictive policy '{}' widens which rows of '{}' are visible", policy_name, table_name),
+                        );
+                    }
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::CreatePolicy { table_name, .. } | SchemaChange::AlterPolicy { table_name, .. } => {
+                    score += 15;
+                    let disabled = snapshot
+                        .and_then(|s| snapshot_table(s, table_name))
+                        .and_then(|t| t.governance.row_security.as_ref())
+                        .is_some_and(|rls| !rls.enabled);
+                    let enabling = proposal.changes.iter().any(
+                        |c| matches!(c, SchemaChange::SetRowSecurity { table_name: t, enabled: true, .. } if t == table_name),
+                    );
+                    if disabled && !enabling {
+                        recommendations.push(
+                            Message::new("risk.policy_without_rls").with("table", table_name),
+                            format!("Policies on '{}' have no effect until row-level security is enabled on it", table_name),
+                        );
+                    }
+                    affected_tables.push(table_name.clone());
+                }
+                SchemaChange::DropDomain { domain_name: type_name, cascade }
+                | SchemaChange::DropCompositeType { type_name, cascade } => {
+                    let columns = snapshot.map(|s| columns_of_type(s, type_name)).unwrap_or_default();
+                    if columns.is_empty() {
+                        score += 20;
+                    } else {
+                        score += if *cascade { 150 } else { 60 };
+                        warnings.push(
+                            Message::new("risk.drop_type_columns")
+                                .with("type", type_name)
+                                .with("columns", columns.join(", ")),
+                            format!(
+                                "Type '{}' is used by {}; {}",
+                                type_name,
+                                columns.join(", "),
+                                if *cascade { "CASCADE will drop those columns" } else { "the drop will fail until they are changed" }
+                            ),
+                        );
+                        affected_tables.extend(columns.iter().filter_map(|c| c.rsplit_once('.')).map(|(t, _)| t.to_string()));
+                    }
+                    requires_downtime = requires_downtime || *cascade;
+                }
+                // New checks and NOT NULL are validated against every column using the domain
+                SchemaChange::AlterDomain { domain_name, not_null, add_checks, .. } => {
+                    score += 10;
+                    let columns = snapshot.map(|s| columns_of_type(s, domain_name)).unwrap_or_default();
+                    if (*not_null == Some(true) || !add_checks.is_empty()) && !columns.is_empty() {
+                        score += 30;
+                        warnings.push(
+                            Message::new("risk.domain_validation_scan")
+                                .with("domain", domain_name)
+                                .with("columns", columns.join(", ")),
+                            format!(
+                                "Tightening domain '{}' scans {} and fails if any existing value violates it",
+                                domain_name,
+                                columns.join(", ")
+                            ),
+                        );
+                        affected_tables.extend(columns.iter().filter_map(|c| c.rsplit_once('.')).map(|(t, _)| t.to_string()));
+                    }
+                }
+                // Dropping or retyping attributes rewrites the tables storing the type
+                SchemaChange::AlterCompositeType { type_name, drop_attributes, alter_attributes, .. } => {
+                    score += 10;
+                    let columns = snapshot.map(|s| columns_of_type(s, type_name)).unwrap_or_default();
+                    if (!drop_attributes.is_empty() || !alter_attributes.is_empty()) && !columns.is_empty() {
+                        score += 60;
+                        requires_downtime = true;
+                        warnings.push(
+                            Message::new("risk.composite_rewrite")
+                                .with("type", type_name)
+                                .with("columns", columns.join(", ")),
+                            format!(
+                                "Changing attributes of type '{}' rewrites {} under an exclusive lock",
+                                type_name,
+                                columns.join(", ")
+                            ),
+                        );
+                        affected_tables.extend(columns.iter().filter_map(|c| c.rsplit_once('.')).map(|(t, _)| t.to_string()));
+                    }
+                }
+                SchemaChange::CreateView { .. } | SchemaChange::AlterView { .. } => {
+                    score += 5;
+                }
+                // Populating runs the full query against its base tables
+                SchemaChange::CreateMaterializedView { with_no_data, .. } => {
+                    score += if *with_no_data { 5 } else { 20 };
+                }
+                SchemaChange::DropView { view_name, cascade } | SchemaChange::DropMaterializedView { view_name, cascade } => {
+                    let dependents = snapshot.map(|s| views_reading(s, view_name)).unwrap_or_default();
+                    if dependents.is_empty() {
+                        score += 20;
+                    } else {
+                        score += if *cascade { 100 } else { 40 };
+                        warnings.push(
+                            Message::new("risk.drop_view_dependents")
+                                .with("view", view_name)
+                                .with("views", dependents.join(", ")),
+                            format!(
+                                "'{}' is read by {}; {}",
+                                view_name,
+                                dependents.join(", "),
+                                if *cascade { "CASCADE will drop those views" } else { "the drop will fail until they are dropped" }
+                            ),
+                        );
+                    }
+                }
+                SchemaChange::RefreshMaterializedView { view_name, concurrently } => {
+                    score += 10;
+                    if !*concurrently {
+                        score += 20;
+                        recommendations.push(
+                            Message::new("risk.refresh_concurrently").with("view", view_name),
+                            format!("Refreshing '{}' blocks reads of it; REFRESH CONCURRENTLY avoids that given a unique index", view_name),
+                        );
+                    }
+                }
+                // Governance-only; nothing runs against the database
+                SchemaChange::SetCustomField { .. }
+                | SchemaChange::AddTag { .. }
+                | SchemaChange::RemoveTag { .. }
+                | SchemaChange::SetClassification { .. } => {}
                 _ => {
                     score += 5;
                 }
             }
         }
 
+        if let Some(snapshot) = snapshot {
+            Self::collation_warnings(proposal, &snapshot.database, &mut warnings);
+        }
+
+        // Unindexed FK columns make parent lookups and cascades seq-scan the child table
+        for index in Self::missing_fk_indexes(proposal, snapshot) {
+            if let SchemaChange::AddIndex { table_name, index_name, columns, .. } = &index {
+                score += 10;
+                warnings.push(
+                    Message::new("risk.unindexed_foreign_key")
+                        .with("table", table_name)
+                        .with("columns", columns.join(", ")),
+                    format!(
+                        "Foreign key columns {}({}) have no supporting index; lookups and cascades will seq-scan",
+                        table_name,
+                        columns.join(", ")
+                    ),
+                );
+                recommendations.push(
+                    Message::new("risk.create_foreign_key_index")
+                        .with("index", index_name)
+                        .with("table", table_name)
+                        .with("columns", columns.join(", ")),
+                    format!("CREATE INDEX CONCURRENTLY {} ON {} ({});", index_name, table_name, columns.join(", ")),
+                );
+            }
+        }
+
         // Deduplicate affected tables
         affected_tables.sort();
         affected_tables.dedup();
 
-        let overall_risk = match score {
-            0..=20 => RiskLevel::Low,
-            21..=50 => RiskLevel::Medium,
-            51..=100 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
+        let overall_risk = level_for(score);
 
         if score > 50 {
-            recommendations.push("Consider testing this migration on a staging environment first".to_string());
+            recommendations.push(
+                Message::new("risk.test_on_staging"),
+                "Consider testing this migration on a staging environment first".to_string(),
+            );
         }
         if score > 100 {
-            recommendations.push("Schedule this migration during a maintenance window".to_string());
+            recommendations.push(
+                Message::new("risk.maintenance_window"),
+                "Schedule this migration during a maintenance window".to_string(),
+            );
         }
 
         Ok(RiskAnalysis {
             overall_risk,
             score,
-            warnings,
-            recommendations,
+            warnings: warnings.text,
+            recommendations: recommendations.text,
+            warning_codes: warnings.codes,
+            recommendation_codes: recommendations.codes,
             estimated_duration_secs: (score as u64 / 10).max(1),
             requires_downtime,
             affected_tables,
             analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: Vec::new(),
         })
     }
+
+    /// Build AddIndex changes for foreign keys in the proposal whose source columns
+    /// are not covered by an existing or proposed index
+    pub fn missing_fk_indexes(proposal: &SchemaProposal, snapshot: Option<&SchemaSnapshot>) -> Vec<SchemaChange> {
+        let mut missing = Vec::new();
+
+        for change in &proposal.changes {
+            let SchemaChange::AddForeignKey { table_name, columns, .. } = change else {
+                continue;
+            };
+
+            let proposed = proposal.changes.iter().any(|c| match c {
+                SchemaChange::AddIndex { table_name: t, columns: cols, .. }
+                | SchemaChange::AddUnique { table_name: t, columns: cols, .. } => {
+                    t == table_name && covers(cols, columns)
+                }
+                _ => false,
+            });
+
+            let (schema, table) = split_table_name(table_name);
+            let existing = snapshot.is_some_and(|s| {
+                s.indexes.iter().any(|idx| {
+                    idx.table == table
+                        && schema.is_none_or(|schema| idx.schema == schema)
+                        && covers(&idx.columns, columns)
+                })
+            });
+
+            if !proposed && !existing {
+                missing.push(SchemaChange::AddIndex {
+                    table_name: table_name.clone(),
+                    index_name: format!("idx_{}_{}", table, columns.join("_")),
+                    columns: columns.clone(),
+                    unique: false,
+                    concurrently: true,
+                });
+            }
+        }
+
+        missing
+    }
+
+    /// Warn about new columns whose explicit collation differs from the database default
+    fn collation_warnings(proposal: &SchemaProposal, database: &DatabaseMetadata, warnings: &mut Findings) {
+        if database.collation.is_empty() {
+            return;
+        }
+
+        for change in &proposal.changes {
+            let (table_name, columns) = match change {
+                SchemaChange::CreateTable { table_name, columns, .. } => (table_name, columns.iter().collect::<Vec<_>>()),
+                SchemaChange::AddColumn { table_name, column } => (table_name, vec![column]),
+                _ => continue,
+            };
+
+            for column in columns {
+                if let Some(collation) = &column.collation {
+                    if collation != &database.collation {
+                        warnings.push(
+                            Message::new("risk.collation_mismatch")
+                                .with("column", format!("{}.{}", table_name, column.name))
+                                .with("collation", collation)
+                                .with("database", &database.name)
+                                .with("default", &database.collation),
+                            format!(
+                                "Column '{}.{}' uses collation '{}' but database '{}' defaults to '{}'",
+                                table_name, column.name, collation, database.name, database.collation
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An index can serve FK lookups if the FK columns form its leading columns
+fn covers(index_columns: &[String], fk_columns: &[String]) -> bool {
+    !fk_columns.is_empty() && index_columns.starts_with(fk_columns)
+}
+
+/// Split an optionally schema-qualified table name
+pub(crate) fn split_table_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('.') {
+        Some((schema, table)) => (Some(schema), table),
+        None => (None, name),
+    }
+}
+
+/// Table (schema-qualified) that owns an index in the snapshot
+pub(crate) fn index_table(snapshot: &SchemaSnapshot, index_name: &str) -> Option<String> {
+    let (schema, name) = split_table_name(index_name);
+    snapshot
+        .indexes
+        .iter()
+        .find(|i| i.name == name && schema.is_none_or(|sc| sc == i.schema))
+        .map(|i| format!("{}.{}", i.schema, i.table))
+}
+
+/// A table of the snapshot by possibly qualified name; unqualified names are in `public`
+fn snapshot_table<'a>(snapshot: &'a SchemaSnapshot, table_name: &str) -> Option<&'a Table> {
+    let (schema, table) = split_table_name(table_name);
+    snapshot
+        .tables
+        .iter()
+        .find(|t| t.name == table && t.schema == schema.unwrap_or("public"))
+}
+
+/// Columns (`schema.table.column`) whose type is the given domain or composite
+fn columns_of_type(snapshot: &SchemaSnapshot, type_name: &str) -> Vec<String> {
+    let (schema, name) = split_table_name(type_name);
+    let qualified = format!("{}.{}", schema.unwrap_or("public"), name);
+    snapshot
+        .tables
+        .iter()
+        .flat_map(|t| t.columns.iter().map(move |c| (t, c)))
+        .filter(|(_, c)| c.data_type == qualified)
+        .map(|(t, c)| format!("{}.{}.{}", t.schema, t.name, c.name))
+        .collect()
+}
+
+/// Views and materialized views reading the view `view_name`
+fn views_reading(snapshot: &SchemaSnapshot, view_name: &str) -> Vec<String> {
+    let (schema, name) = split_table_name(view_name);
+    let qualified = format!("{}.{}", schema.unwrap_or("public"), name);
+    snapshot
+        .views
+        .iter()
+        .flatten()
+        .filter(|v| v.depends_on.contains(&qualified))
+        .map(|v| v.qualified_name())
+        .collect()
+}
+
+/// Number of indexes REINDEX TABLE would rebuild
+fn table_indexes(snapshot: &SchemaSnapshot, table_name: &str) -> u32 {
+    let (schema, table) = split_table_name(table_name);
+    snapshot
+        .indexes
+        .iter()
+        .filter(|i| i.table == table && i.schema == schema.unwrap_or("public"))
+        .count() as u32
 }
 
 impl Default for RiskEngine {
@@ -103,3 +739,75 @@ impl Default for RiskEngine {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn add_fk() -> SchemaChange {
+        SchemaChange::AddForeignKey {
+            table_name: "orders".to_string(),
+            constraint_name: "fk_orders_customer".to_string(),
+            columns: vec!["customer_id".to_string()],
+            ref_table: "customers".to_string(),
+            ref_columns: vec!["id".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_missing_fk_index_is_suggested() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "FK".to_string(), String::new(), "tester".to_string());
+        proposal.changes.push(add_fk());
+
+        let missing = RiskEngine::missing_fk_indexes(&proposal, None);
+        assert_eq!(missing.len(), 1);
+        assert!(matches!(
+            &missing[0],
+            SchemaChange::AddIndex { index_name, concurrently: true, .. } if index_name == "idx_orders_customer_id"
+        ));
+
+        let analysis = RiskEngine::new().analyze(&proposal, None).unwrap();
+        assert!(analysis.recommendations.iter().any(|r| r.contains("CONCURRENTLY idx_orders_customer_id")));
+    }
+
+    #[test]
+    fn test_proposed_index_covers_fk() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "FK".to_string(), String::new(), "tester".to_string());
+        proposal.changes.push(add_fk());
+        proposal.changes.push(SchemaChange::AddIndex {
+            table_name: "orders".to_string(),
+            index_name: "idx_orders_customer_created".to_string(),
+            columns: vec!["customer_id".to_string(), "created_at".to_string()],
+            unique: false,
+            concurrently: false,
+        });
+
+        assert!(RiskEngine::missing_fk_indexes(&proposal, None).is_empty());
+    }
+
+    #[test]
+    fn test_maintenance_risk() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Maintenance".to_string(), String::new(), "tester".to_string());
+        proposal.changes.push(SchemaChange::Reindex {
+            target: ReindexTarget::Index,
+            name: "idx_orders_customer_id".to_string(),
+            concurrently: true,
+        });
+        proposal.changes.push(SchemaChange::Analyze { table_name: "orders".to_string() });
+
+        let analysis = RiskEngine::new().analyze(&proposal, None).unwrap();
+        assert_eq!(analysis.overall_risk, RiskLevel::Low);
+        assert!(!analysis.requires_downtime);
+
+        proposal.changes.push(SchemaChange::Vacuum {
+            table_name: "orders".to_string(),
+            full: true,
+            analyze: false,
+        });
+        let analysis = RiskEngine::new().analyze(&proposal, None).unwrap();
+        assert_eq!(analysis.overall_risk, RiskLevel::High);
+        assert!(analysis.requires_downtime);
+        assert!(analysis.warnings.iter().any(|w| w.contains("VACUUM FULL")));
+    }
+}
@@ -0,0 +1,149 @@
+//! Risk history across revisions
+//!
+//! Every risk analysis a proposal receives is kept alongside the revision it
+//! analyzed, whether a reviewer asked for it or drift triggered a re-run.
+//! Comparing consecutive analyses shows how the score, the risk factors
+//! (warnings) and the affected tables moved as the proposal was edited, which
+//! is how an author demonstrates that review feedback was addressed.
+
+use crate::pipeline::proposal::{RiskAnalysis, RiskLevel};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Analyses kept per proposal; older ones are dropped
+pub const MAX_RISK_HISTORY: usize = 50;
+
+/// One stored analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskRecord {
+    /// Proposal revision analyzed (0 before the first patch)
+    pub revision: u32,
+    /// Number of changes the proposal had
+    pub change_count: usize,
+    /// Who or what asked for the analysis
+    pub trigger: String,
+    pub analysis: RiskAnalysis,
+}
+
+/// An analysis compared with the one before it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskHistoryEntry {
+    pub revision: u32,
+    pub change_count: usize,
+    pub trigger: String,
+    pub analyzed_at: DateTime<Utc>,
+    pub score: u32,
+    pub overall_risk: RiskLevel,
+    pub factors: Vec<String>,
+    pub affected_tables: Vec<String>,
+    /// Score minus the previous analysis's score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_change: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub new_factors: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resolved_factors: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tables_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tables_removed: Vec<String>,
+}
+
+/// Append an analysis, dropping the oldest beyond the limit
+pub fn record(history: &mut Vec<RiskRecord>, record: RiskRecord) {
+    history.push(record);
+    if history.len() > MAX_RISK_HISTORY {
+        history.drain(..history.len() - MAX_RISK_HISTORY);
+    }
+}
+
+/// Each analysis with what changed since the previous one, oldest first
+pub fn compare(history: &[RiskRecord]) -> Vec<RiskHistoryEntry> {
+    let only_in = |a: &[String], b: &[String]| -> Vec<String> {
+        a.iter().filter(|item| !b.contains(item)).cloned().collect()
+    };
+
+    history
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let current = &record.analysis;
+            let previous = i.checked_sub(1).map(|p| &history[p].analysis);
+            RiskHistoryEntry {
+                revision: record.revision,
+                change_count: record.change_count,
+                trigger: record.trigger.clone(),
+                analyzed_at: current.analyzed_at,
+                score: current.score,
+                overall_risk: current.overall_risk,
+                factors: current.warnings.clone(),
+                affected_tables: current.affected_tables.clone(),
+                score_change: previous.map(|p| current.score as i64 - p.score as i64),
+                new_factors: previous.map(|p| only_in(&current.warnings, &p.warnings)).unwrap_or_default(),
+                resolved_factors: previous.map(|p| only_in(&p.warnings, &current.warnings)).unwrap_or_default(),
+                tables_added: previous
+                    .map(|p| only_in(&current.affected_tables, &p.affected_tables))
+                    .unwrap_or_default(),
+                tables_removed: previous
+                    .map(|p| only_in(&p.affected_tables, &current.affected_tables))
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analysis(score: u32, warnings: &[&str], tables: &[&str]) -> RiskAnalysis {
+        RiskAnalysis {
+            overall_risk: RiskLevel::Low,
+            score,
+            warnings: warnings.iter().map(|w| w.to_string()).collect(),
+            recommendations: Vec::new(),
+            warning_codes: Vec::new(),
+            recommendation_codes: Vec::new(),
+            estimated_duration_secs: 1,
+            requires_downtime: false,
+            affected_tables: tables.iter().map(|t| t.to_string()).collect(),
+            analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_reports_addressed_feedback() {
+        let mut history = Vec::new();
+        record(&mut history, RiskRecord {
+            revision: 0,
+            change_count: 2,
+            trigger: "alice".to_string(),
+            analysis: analysis(130, &["Dropping table 'orders' is destructive"], &["orders", "users"]),
+        });
+        record(&mut history, RiskRecord {
+            revision: 1,
+            change_count: 1,
+            trigger: "alice".to_string(),
+            analysis: analysis(5, &[], &["users"]),
+        });
+
+        let entries = compare(&history);
+        assert_eq!(entries[0].score_change, None);
+        assert!(entries[0].resolved_factors.is_empty());
+        assert_eq!(entries[1].score_change, Some(-125));
+        assert_eq!(entries[1].resolved_factors, vec!["Dropping table 'orders' is destructive"]);
+        assert_eq!(entries[1].tables_removed, vec!["orders"]);
+        assert!(entries[1].tables_added.is_empty());
+
+        let latest = history[1].clone();
+        for _ in 0..MAX_RISK_HISTORY {
+            record(&mut history, latest.clone());
+        }
+        assert_eq!(history.len(), MAX_RISK_HISTORY);
+    }
+}
@@ -0,0 +1,155 @@
+//! Row count verification
+//!
+//! A migration that only adds or reshapes structure should leave the number
+//! of rows in its tables alone. For such migrations the orchestrator counts
+//! the rows of the affected tables before the first statement and after the
+//! last, and flags tables whose count moved by more than concurrent writes
+//! plausibly explain: a trigger deleting rows as a side effect, say, or an
+//! unexpected cascade.
+//!
+//! Counts are exact, so tables the planner estimates above
+//! `EXACT_COUNT_LIMIT` rows are left out rather than scanned. Tables that do
+//! not exist on one side are listed but not compared.
+
+use crate::error::AppError;
+use crate::pipeline::type_migration::quote_table;
+use crate::pipeline::types::SchemaChange;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+
+/// Estimated rows above which a table is not counted
+pub const EXACT_COUNT_LIMIT: i64 = 5_000_000;
+
+/// Share of a table's rows concurrent writes may add or remove during a run
+/// before the change counts as unexpected
+const TOLERANCE: f64 = 0.01;
+
+/// One table's row counts around the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRowCount {
+    pub table: String,
+    /// None when the table was missing or too large to count
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+}
+
+impl TableRowCount {
+    pub fn delta(&self) -> Option<i64> {
+        Some(self.after? - self.before?)
+    }
+
+    /// Whether the count moved by more than the tolerance
+    pub fn is_anomalous(&self) -> bool {
+        match (self.before, self.delta()) {
+            (Some(before), Some(delta)) => delta.abs() > (before as f64 * TOLERANCE) as i64,
+            _ => false,
+        }
+    }
+}
+
+/// Row counts of the affected tables, attached to the execution result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowCountVerification {
+    pub tables: Vec<TableRowCount>,
+    /// One line per table whose count moved unexpectedly
+    pub anomalies: Vec<String>,
+    pub verified_at: DateTime<Utc>,
+}
+
+impl RowCountVerification {
+    pub fn passed(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Whether `changes` should leave every row in place. Dropping tables or
+/// schemas, cascading extension drops and moving partitions in or out of a
+/// parent all change counts on purpose.
+pub fn expects_stable_counts(changes: &[SchemaChange]) -> bool {
+    !changes.iter().any(|change| {
+        matches!(
+            change,
+            SchemaChange::DropSchema { .. }
+                | SchemaChange::DropTable { .. }
+                | SchemaChange::DropExtension { cascade: true, .. }
+                | SchemaChange::AttachPartition { .. }
+                | SchemaChange::DetachPartition { .. }
+        )
+    })
+}
+
+/// Exact row count of each table, or None for tables that do not exist or
+/// are too large to count
+pub async fn count(client: &Client, tables: &[String]) -> Result<Vec<Option<i64>>, AppError> {
+    let mut counts = Vec::with_capacity(tables.len());
+    for table in tables {
+        // to_regclass yields NULL for tables that do not exist, instead of failing
+        let estimate = client
+            .query_one("SELECT (SELECT reltuples::bigint FROM pg_class WHERE oid = to_regclass($1))", &[table])
+            .await?
+            .get::<_, Option<i64>>(0);
+        let rows = match estimate {
+            Some(estimate) if estimate <= EXACT_COUNT_LIMIT => {
+                let row = client.query_one(&format!("SELECT count(*) FROM {}", quote_table(table)), &[]).await?;
+                Some(row.get::<_, i64>(0))
+            }
+            _ => None,
+        };
+        counts.push(rows);
+    }
+    Ok(counts)
+}
+
+/// Compare counts taken before and after a run
+pub fn verify(tables: &[String], before: Vec<Option<i64>>, after: Vec<Option<i64>>) -> RowCountVerification {
+    let tables: Vec<TableRowCount> = tables
+        .iter()
+        .zip(before.into_iter().zip(after))
+        .map(|(table, (before, after))| TableRowCount { table: table.clone(), before, after })
+        .collect();
+    let anomalies = tables
+        .iter()
+        .filter(|t| t.is_anomalous())
+        .map(|t| {
+            format!(
+                "{}: {} rows before, {} after ({:+})",
+                t.table,
+                t.before.unwrap_or_default(),
+                t.after.unwrap_or_default(),
+                t.delta().unwrap_or_default()
+            )
+        })
+        .collect();
+    RowCountVerification { tables, anomalies, verified_at: Utc::now() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_flags_unexpected_deltas() {
+        let tables: Vec<String> = ["orders", "events", "audit", "new_table"].iter().map(|t| t.to_string()).collect();
+        let verification = verify(
+            &tables,
+            vec![Some(1200), Some(10_000), Some(40), None],
+            vec![Some(950), Some(10_050), Some(40), Some(0)],
+        );
+        assert!(!verification.passed());
+        assert_eq!(verification.anomalies, vec!["orders: 1200 rows before, 950 after (-250)"]);
+        assert_eq!(verification.tables[3].delta(), None);
+
+        let additive = vec![SchemaChange::AddIndex {
+            table_name: "orders".to_string(),
+            index_name: "idx_orders_customer".to_string(),
+            columns: vec!["customer_id".to_string()],
+            unique: false,
+            concurrently: false,
+        }];
+        assert!(expects_stable_counts(&additive));
+        assert!(!expects_stable_counts(&[SchemaChange::DropTable { table_name: "orders".to_string() }]));
+    }
+}
@@ -0,0 +1,528 @@
+//! Developer sandbox databases
+//!
+//! A sandbox is a disposable database on the same server as a connection,
+//! holding the structure of one of its snapshots and none of its rows, so a
+//! proposal author can try changes against real structure without going near
+//! the real database. It is built by a schema-only restore: the snapshot is
+//! diffed against an empty database and the resulting migration SQL runs in
+//! a fresh `CREATE DATABASE`. Copying the live database with `TEMPLATE` is
+//! not offered; it would copy rows too, and needs every other session off
+//! the source, including SchemaFlow's own pool.
+//!
+//! Each sandbox is registered as a development connection of the source's
+//! project, reached with the source's credentials (whose role needs
+//! `CREATEDB`). It expires after a TTL that can be extended; the reaper then
+//! disconnects it and drops the database, as does deleting it early.
+//!
+//! The registry lives in memory, so each sandbox database also carries its
+//! expiry in a database comment. On every pass the reaper scans the servers
+//! of connected connections for sandbox databases their role owns that no
+//! live sandbox accounts for, such as those left by a restart, and drops the
+//! ones past their stamped expiry or without one.
+
+use crate::db::queries::SqlBuilder;
+use crate::error::AppError;
+use crate::introspection::{Extension, SchemaSnapshot};
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::orchestrator::split_statements;
+use crate::pipeline::scratch::db_message;
+use crate::snapshot::migration::{self, DiffMigration};
+use crate::snapshot::DiffEngine;
+use crate::state::{AppState, SharedState};
+use chrono::{DateTime, Duration, Utc};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio_postgres::error::SqlState;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// TTL of a sandbox created without one
+pub const DEFAULT_SANDBOX_HOURS: i64 = 8;
+
+/// Longest a sandbox may live before it has to be extended
+pub const MAX_SANDBOX_HOURS: i64 = 24 * 7;
+
+/// How often the reaper looks for expired sandboxes
+const REAP_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxStatus {
+    Ready,
+    Dropped,
+}
+
+/// A disposable database built from a snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sandbox {
+    pub id: Uuid,
+    /// Connection whose snapshot the sandbox was built from
+    pub source_connection_id: Uuid,
+    /// The sandbox's own connection
+    pub connection_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i32>,
+    pub database: String,
+    pub snapshot_id: Uuid,
+    pub snapshot_version: u64,
+    /// Statements the restore ran
+    pub statements: usize,
+    /// Parts of the snapshot the restore could not reproduce
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub manual_steps: Vec<String>,
+    pub status: SandboxStatus,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_by: Option<String>,
+}
+
+impl Sandbox {
+    pub fn is_active(&self) -> bool {
+        self.status == SandboxStatus::Ready
+    }
+}
+
+/// In-memory registry of sandboxes
+#[derive(Clone, Default)]
+pub struct SandboxRegistry {
+    sandboxes: Arc<RwLock<HashMap<Uuid, Sandbox>>>,
+}
+
+impl SandboxRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn insert(&self, sandbox: Sandbox) {
+        self.sandboxes.write().await.insert(sandbox.id, sandbox);
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<Sandbox> {
+        self.sandboxes.read().await.get(&id).cloned()
+    }
+
+    /// Sandboxes built from a connection, newest first, including dropped ones
+    pub async fn list_for_connection(&self, connection_id: Uuid) -> Vec<Sandbox> {
+        let sandboxes = self.sandboxes.read().await;
+        let mut found: Vec<_> = sandboxes
+            .values()
+            .filter(|s| s.source_connection_id == connection_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        found
+    }
+
+    /// Push an active sandbox's expiry to `hours` from now, clamped to
+    /// 1..=MAX_SANDBOX_HOURS; None if unknown or already dropped
+    pub async fn extend(&self, id: Uuid, hours: i64) -> Option<Sandbox> {
+        let mut sandboxes = self.sandboxes.write().await;
+        let sandbox = sandboxes.get_mut(&id).filter(|s| s.is_active())?;
+        sandbox.expires_at = Utc::now() + Duration::hours(hours.clamp(1, MAX_SANDBOX_HOURS));
+        Some(sandbox.clone())
+    }
+
+    async fn mark_dropped(&self, id: Uuid, dropped_by: &str) -> Option<Sandbox> {
+        let mut sandboxes = self.sandboxes.write().await;
+        let sandbox = sandboxes.get_mut(&id).filter(|s| s.is_active())?;
+        sandbox.status = SandboxStatus::Dropped;
+        sandbox.dropped_at = Some(Utc::now());
+        sandbox.dropped_by = Some(dropped_by.to_string());
+        Some(sandbox.clone())
+    }
+
+    /// Active sandboxes past their expiry
+    pub async fn expired(&self, now: DateTime<Utc>) -> Vec<Sandbox> {
+        let sandboxes = self.sandboxes.read().await;
+        sandboxes.values().filter(|s| s.is_active() && s.expires_at <= now).cloned().collect()
+    }
+
+    /// Database names and connections of the active sandboxes
+    async fn active(&self) -> (HashSet<String>, HashSet<Uuid>) {
+        let sandboxes = self.sandboxes.read().await;
+        sandboxes.values().filter(|s| s.is_active()).map(|s| (s.database.clone(), s.connection_id)).unzip()
+    }
+}
+
+/// Start of every sandbox database name
+const DATABASE_PREFIX: &str = "schemaflow_sandbox_";
+
+/// Start of the comment stamping a sandbox database with its expiry
+const EXPIRY_COMMENT: &str = "SchemaFlow sandbox, expires ";
+
+/// Database name of a sandbox
+pub fn database_name(id: Uuid) -> String {
+    format!("{}{}", DATABASE_PREFIX, id.simple())
+}
+
+fn expiry_comment(database: &str, expires_at: DateTime<Utc>) -> String {
+    format!(
+        "COMMENT ON DATABASE {} IS {}",
+        SqlBuilder::quote_ident(database),
+        SqlBuilder::quote_literal(&format!("{}{}", EXPIRY_COMMENT, expires_at.to_rfc3339()))
+    )
+}
+
+/// Whether a sandbox database no live sandbox accounts for should go: it
+/// has expired by its comment, or carries none
+fn is_orphan(database: &str, comment: Option<&str>, tracked: &HashSet<String>, now: DateTime<Utc>) -> bool {
+    if !database.starts_with(DATABASE_PREFIX) || tracked.contains(database) {
+        return false;
+    }
+    comment
+        .and_then(|c| c.strip_prefix(EXPIRY_COMMENT))
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_none_or(|expires_at| expires_at <= now)
+}
+
+/// SQL building the snapshot's structure in a fresh database. The `public`
+/// schema and `plpgsql` already exist there, and created schemas are owned
+/// by the sandbox's role rather than the original owners.
+pub fn restore_sql(snapshot: &SchemaSnapshot) -> DiffMigration {
+    let mut target = snapshot.clone();
+    for schema in &mut target.schemas {
+        schema.owner = "CURRENT_USER".to_string();
+    }
+
+    let mut fresh = SchemaSnapshot::empty(snapshot.connection_id);
+    fresh.database = target.database.clone();
+    fresh.schemas = target.schemas.iter().filter(|s| s.name == "public").cloned().collect();
+    fresh.extensions = target
+        .extensions
+        .iter()
+        .filter(|e| e.name == "plpgsql")
+        .map(|e| Extension { dependent_objects: 0, ..e.clone() })
+        .collect();
+
+    migration::from_diff(&DiffEngine::diff(&fresh, &target), &fresh)
+}
+
+/// Build a sandbox from `snapshot` next to the connection it was taken of
+pub async fn provision(
+    state: &AppState,
+    snapshot: &SchemaSnapshot,
+    created_by: &str,
+    hours: i64,
+) -> Result<Sandbox, AppError> {
+    let source_id = snapshot.connection_id;
+    let source = state
+        .connections
+        .get_connection(source_id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Connection {} not found", source_id)))?;
+
+    let id = Uuid::new_v4();
+    let database = database_name(id);
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(hours.clamp(1, MAX_SANDBOX_HOURS));
+    let source_pool = source.postgres_pool()?;
+    let client = source_pool.get().await?;
+    client.batch_execute(&SqlBuilder::create_database(&database)).await.map_err(|e| {
+        AppError::BadRequest(format!(
+            "Could not create the sandbox database (the connection's role needs CREATEDB): {}",
+            db_message(&e)
+        ))
+    })?;
+    // Without its expiry the reaper would take it for an orphan
+    if let Err(e) = client.batch_execute(&expiry_comment(&database, expires_at)).await {
+        drop(client);
+        drop_database(source_pool, &database).await;
+        return Err(AppError::Internal(format!("Could not stamp the sandbox database: {}", db_message(&e))));
+    }
+    drop(client);
+
+    let name = format!("{} sandbox v{}", source.name, snapshot.version);
+    let info = match state.connections.attach_database(source_id, database.clone(), name).await {
+        Ok(info) => info,
+        Err(e) => {
+            drop_database(source_pool, &database).await;
+            return Err(e);
+        }
+    };
+
+    let restore = restore_sql(snapshot);
+    let statements = split_statements(&restore.up_sql);
+    if let Err(e) = run_restore(&state.connections.get_pool(info.id).await?, &statements).await {
+        let _ = state.connections.disconnect(info.id).await;
+        drop_database(source_pool, &database).await;
+        return Err(e);
+    }
+
+    let sandbox = Sandbox {
+        id,
+        source_connection_id: source_id,
+        connection_id: info.id,
+        project_id: source.project_id,
+        database,
+        snapshot_id: snapshot.id,
+        snapshot_version: snapshot.version,
+        statements: statements.len(),
+        manual_steps: restore.manual_steps,
+        status: SandboxStatus::Ready,
+        created_by: created_by.to_string(),
+        created_at: now,
+        expires_at,
+        dropped_at: None,
+        dropped_by: None,
+    };
+    state.sandboxes.insert(sandbox.clone()).await;
+    info!("Sandbox {} built from snapshot v{} of connection {}", sandbox.database, snapshot.version, source_id);
+    Ok(sandbox)
+}
+
+/// Run the restore in autocommit mode; the database is thrown away on failure
+async fn run_restore(pool: &Pool, statements: &[String]) -> Result<(), AppError> {
+    let client = pool.get().await?;
+    for (index, statement) in statements.iter().enumerate() {
+        client.batch_execute(statement).await.map_err(|e| {
+            AppError::BadRequest(format!(
+                "Restoring the snapshot failed at statement {}: {}",
+                index + 1,
+                db_message(&e)
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Drop a sandbox database, ending sessions still on it; logs rather than
+/// failing, and a database already gone counts as dropped
+async fn drop_database(pool: &Pool, database: &str) -> bool {
+    let outcome = match pool.get().await {
+        Ok(client) => match client.batch_execute(&format!("{} WITH (FORCE)", SqlBuilder::drop_database(database))).await {
+            Err(e) if e.code() == Some(&SqlState::INVALID_CATALOG_NAME) => Ok(()),
+            other => other.map_err(|e| db_message(&e)),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = &outcome {
+        warn!("Failed to drop sandbox database {}: {}", database, e);
+    }
+    outcome.is_ok()
+}
+
+/// Stamp a sandbox database with its current expiry, after an extension.
+/// Logs rather than failing; the reaper only reads the stamp once the
+/// registry has lost the sandbox.
+pub async fn stamp_expiry(state: &AppState, sandbox: &Sandbox) {
+    let stamped = match state.connections.get_pool(sandbox.source_connection_id).await {
+        Ok(pool) => match pool.get().await {
+            Ok(client) => client
+                .batch_execute(&expiry_comment(&sandbox.database, sandbox.expires_at))
+                .await
+                .map_err(|e| db_message(&e)),
+            Err(e) => Err(e.to_string()),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = stamped {
+        warn!("Failed to stamp sandbox database {} with its new expiry: {}", sandbox.database, e);
+    }
+}
+
+/// Drop the sandbox databases on connected servers that no live sandbox
+/// accounts for and that have expired; returns how many were dropped
+async fn reap_orphans(state: &AppState) -> usize {
+    let (tracked, sandbox_connections) = state.sandboxes.active().await;
+    let now = Utc::now();
+    let mut dropped = 0;
+    for connection in state.connections.list_connections().await {
+        if sandbox_connections.contains(&connection.id) {
+            continue;
+        }
+        let Ok(pool) = state.connections.get_pool(connection.id).await else {
+            continue;
+        };
+        // Only what this role owns, and so could have created and may drop
+        let rows = match pool.get().await {
+            Ok(client) => client
+                .query(
+                    "SELECT d.datname::text, shobj_description(d.oid, 'pg_database')
+                     FROM pg_database d
+                     WHERE d.datname LIKE 'schemaflow\\_sandbox\\_%'
+                       AND d.datdba = (SELECT oid FROM pg_roles WHERE rolname = current_user)",
+                    &[],
+                )
+                .await
+                .map_err(|e| db_message(&e)),
+            Err(e) => Err(e.to_string()),
+        };
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("Could not look for orphaned sandboxes on connection {}: {}", connection.id, e);
+                continue;
+            }
+        };
+        for row in rows {
+            let database: String = row.get(0);
+            if !is_orphan(&database, row.get(1), &tracked, now) || !drop_database(&pool, &database).await {
+                continue;
+            }
+            info!("Dropped orphaned sandbox {}", database);
+            let entry = AuditEntry::new(AuditAction::SandboxDropped, "system", "connection", &connection.id.to_string())
+                .with_project(connection.project_id)
+                .with_details(&format!("Sandbox {} was no longer tracked and had expired", database));
+            state.metadata.record_audit_entry(entry).await;
+            dropped += 1;
+        }
+    }
+    dropped
+}
+
+/// Disconnect a sandbox and drop its database. Dropping goes through the
+/// source connection, so it has to still be connected.
+pub async fn tear_down(state: &AppState, sandbox: &Sandbox, dropped_by: &str) -> Result<Sandbox, AppError> {
+    let pool = state.connections.get_pool(sandbox.source_connection_id).await.map_err(|_| {
+        AppError::Conflict(format!(
+            "Reconnect connection {} to drop sandbox {}",
+            sandbox.source_connection_id, sandbox.database
+        ))
+    })?;
+    let _ = state.connections.disconnect(sandbox.connection_id).await;
+    if !drop_database(&pool, &sandbox.database).await {
+        return Err(AppError::Internal(format!("Could not drop sandbox database {}", sandbox.database)));
+    }
+    state
+        .sandboxes
+        .mark_dropped(sandbox.id, dropped_by)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("No active sandbox {}", sandbox.id)))
+}
+
+/// Spawn the background task dropping sandboxes once they expire
+pub fn spawn_sandbox_reaper(state: SharedState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(REAP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            for sandbox in state.sandboxes.expired(Utc::now()).await {
+                // Left for the next tick when the source is not connected
+                match tear_down(&state, &sandbox, "system").await {
+                    Ok(dropped) => {
+                        info!("Dropped expired sandbox {}", dropped.database);
+                        let entry = AuditEntry::new(AuditAction::SandboxDropped, "system", "connection", &sandbox.source_connection_id.to_string())
+                            .with_project(sandbox.project_id)
+                            .with_details(&format!("Sandbox {} expired", sandbox.database));
+                        state.metadata.record_audit_entry(entry).await;
+                    }
+                    Err(e) => warn!("Expired sandbox {} not dropped yet: {}", sandbox.database, e),
+                }
+            }
+            reap_orphans(&state).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, Namespace, Table, TableGovernance};
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_restore_sql_and_lifecycle() {
+        let mut snapshot = SchemaSnapshot::empty(Uuid::new_v4());
+        snapshot.version = 4;
+        snapshot.schemas = vec![
+            Namespace { name: "public".to_string(), owner: "postgres".to_string() },
+            Namespace { name: "billing".to_string(), owner: "billing_owner".to_string() },
+        ];
+        snapshot.extensions = vec![Extension {
+            name: "plpgsql".to_string(),
+            version: "1.0".to_string(),
+            schema: "pg_catalog".to_string(),
+            dependent_objects: 0,
+        }];
+        snapshot.tables = vec![Table {
+            name: "invoices".to_string(),
+            schema: "billing".to_string(),
+            columns: vec![Column {
+                name: "id".to_string(),
+                data_type: "integer".to_string(),
+                nullable: false,
+                default_value: None,
+                is_primary_key: false,
+                is_unique: false,
+                ordinal_position: 1,
+                collation: None,
+                pii_classification: None,
+                description: None,
+                tags: vec![],
+                custom_fields: BTreeMap::new(),
+            }],
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: TableGovernance::default(),
+        }];
+
+        // What a fresh database already has is left alone
+        let restore = restore_sql(&snapshot);
+        assert!(restore.up_sql.contains("CREATE SCHEMA billing AUTHORIZATION CURRENT_USER"));
+        assert!(restore.up_sql.contains("invoices"));
+        assert!(!restore.up_sql.contains("SCHEMA public"));
+        assert!(!restore.up_sql.contains("plpgsql"));
+
+        let registry = SandboxRegistry::new();
+        let now = Utc::now();
+        let sandbox = Sandbox {
+            id: Uuid::new_v4(),
+            source_connection_id: snapshot.connection_id,
+            connection_id: Uuid::new_v4(),
+            project_id: None,
+            database: database_name(Uuid::new_v4()),
+            snapshot_id: snapshot.id,
+            snapshot_version: 4,
+            statements: 2,
+            manual_steps: vec![],
+            status: SandboxStatus::Ready,
+            created_by: "1".to_string(),
+            created_at: now - Duration::hours(2),
+            expires_at: now - Duration::minutes(1),
+            dropped_at: None,
+            dropped_by: None,
+        };
+        registry.insert(sandbox.clone()).await;
+        assert_eq!(registry.expired(now).await.len(), 1);
+
+        let extended = registry.extend(sandbox.id, 10_000).await.unwrap();
+        assert!(extended.expires_at > now + Duration::hours(MAX_SANDBOX_HOURS - 1));
+        assert!(registry.expired(now).await.is_empty());
+
+        registry.mark_dropped(sandbox.id, "1").await.unwrap();
+        assert!(registry.extend(sandbox.id, 1).await.is_none());
+        assert_eq!(registry.list_for_connection(snapshot.connection_id).await[0].status, SandboxStatus::Dropped);
+    }
+
+    #[test]
+    fn test_orphaned_sandboxes_are_reaped_once_expired() {
+        let now = Utc::now();
+        let live = database_name(Uuid::new_v4());
+        let orphan = database_name(Uuid::new_v4());
+        let tracked = HashSet::from([live.clone()]);
+        let stamp = |at: DateTime<Utc>| format!("{}{}", EXPIRY_COMMENT, at.to_rfc3339());
+
+        // Tracked sandboxes are the registry's to drop, whatever their stamp says
+        assert!(!is_orphan(&live, Some(&stamp(now - Duration::hours(1))), &tracked, now));
+        assert!(!is_orphan("orders", None, &tracked, now));
+
+        // Untracked ones may belong to another instance until their expiry
+        assert!(!is_orphan(&orphan, Some(&stamp(now + Duration::hours(1))), &tracked, now));
+        assert!(is_orphan(&orphan, Some(&stamp(now - Duration::hours(1))), &tracked, now));
+        assert!(is_orphan(&orphan, None, &tracked, now));
+        assert!(is_orphan(&orphan, Some("hand-written comment"), &tracked, now));
+
+        let sql = expiry_comment(&orphan, now);
+        assert!(sql.starts_with(&format!("COMMENT ON DATABASE \"{}\" IS 'SchemaFlow sandbox, expires ", orphan)));
+    }
+}
@@ -0,0 +1,140 @@
+//! Temp-schema dry runs
+//!
+//! The planner-only dry run never executes anything, so it cannot catch a
+//! statement that fails against the real structure. A temp-schema dry run
+//! clones the affected tables (structure only, no rows) into a scratch
+//! schema placed first on the `search_path`, executes the migration there,
+//! and rolls the whole transaction back, scratch schema included.
+//!
+//! Unqualified names resolve to the clones. Schema-qualified names still
+//! reach the real tables inside the rolled-back transaction, so a short lock
+//! timeout keeps such a run from queueing behind production traffic.
+
+use crate::error::AppError;
+use deadpool_postgres::{Pool, Transaction};
+use serde::Serialize;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Longest wait for a lock on a real table before the run gives up
+pub(crate) const LOCK_TIMEOUT_MS: u32 = 2000;
+
+/// Outcome of a migration run against scratch clones
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScratchRunResult {
+    pub success: bool,
+    /// Scratch schema the run used; it no longer exists afterwards
+    pub schema: String,
+    /// Existing tables cloned into the scratch schema
+    pub cloned_tables: Vec<String>,
+    /// Statements that ran before the first failure (or all of them)
+    pub executed_statements: usize,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+    pub duration_ms: u64,
+}
+
+impl ScratchRunResult {
+    pub(crate) fn new(schema: &str) -> Self {
+        Self {
+            success: true,
+            schema: schema.to_string(),
+            cloned_tables: Vec::new(),
+            executed_statements: 0,
+            error: None,
+            warnings: Vec::new(),
+            duration_ms: 0,
+        }
+    }
+}
+
+/// Clone the existing `tables` into `schema`, copying up to `sample_rows` rows of each
+pub(crate) async fn clone_tables(
+    tx: &Transaction<'_>,
+    schema: &str,
+    tables: &[String],
+    sample_rows: Option<i64>,
+    result: &mut ScratchRunResult,
+) -> Result<(), AppError> {
+    for table in tables {
+        let exists: bool = tx
+            .query_one("SELECT to_regclass($1::text) IS NOT NULL", &[table])
+            .await?
+            .get(0);
+        // Tables the migration creates do not exist yet
+        if !exists {
+            continue;
+        }
+        let name = table.rsplit('.').next().unwrap_or(table);
+        if result.cloned_tables.iter().any(|t| t.rsplit('.').next() == Some(name)) {
+            result.warnings.push(format!("{} shares a name with another cloned table and was not cloned", table));
+            continue;
+        }
+        tx.batch_execute(&format!("CREATE TABLE {}.{} (LIKE {} INCLUDING ALL)", schema, name, table))
+            .await?;
+        result.cloned_tables.push(table.clone());
+
+        if let Some(rows) = sample_rows.filter(|&rows| rows > 0) {
+            // Generated columns refuse copied values; such clones stay empty
+            tx.batch_execute("SAVEPOINT sample_rows").await?;
+            let copy = format!(
+                "INSERT INTO {}.{} OVERRIDING SYSTEM VALUE SELECT * FROM {} LIMIT {}",
+                schema, name, table, rows
+            );
+            match tx.batch_execute(&copy).await {
+                Ok(()) => tx.batch_execute("RELEASE SAVEPOINT sample_rows").await?,
+                Err(e) => {
+                    tx.batch_execute("ROLLBACK TO SAVEPOINT sample_rows").await?;
+                    result.warnings.push(format!("Rows of {} could not be copied: {}", table, db_message(&e)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn db_message(e: &tokio_postgres::Error) -> String {
+    e.as_db_error().map(|db| db.message().to_string()).unwrap_or_else(|| e.to_string())
+}
+
+/// Run `statements` in order, stopping at the first failure
+pub(crate) async fn run_statements(tx: &Transaction<'_>, statements: &[String], result: &mut ScratchRunResult) {
+    for (index, statement) in statements.iter().enumerate() {
+        let upper = statement.to_uppercase();
+        if upper.starts_with("VACUUM") || upper.contains(" CONCURRENTLY ") || upper.ends_with(" CONCURRENTLY;") {
+            result.warnings.push(format!("Statement {} cannot run inside a transaction and was skipped", index + 1));
+            continue;
+        }
+        if let Err(e) = tx.batch_execute(statement).await {
+            result.success = false;
+            result.error = Some(format!("Statement {} failed: {}", index + 1, db_message(&e)));
+            break;
+        }
+        result.executed_statements += 1;
+    }
+}
+
+/// Clone `tables` into a scratch schema, run `statements` there and roll back
+pub async fn dry_run(pool: &Pool, statements: &[String], tables: &[String]) -> Result<ScratchRunResult, AppError> {
+    let started = Instant::now();
+    let mut client = pool.get().await?;
+    let tx = client.transaction().await?;
+
+    let schema = format!("schemaflow_scratch_{}", Uuid::new_v4().simple());
+    let mut result = ScratchRunResult::new(&schema);
+
+    tx.batch_execute(&format!("SET LOCAL lock_timeout = {}", LOCK_TIMEOUT_MS)).await?;
+    tx.batch_execute(&format!("CREATE SCHEMA {}", schema)).await?;
+    clone_tables(&tx, &schema, tables, None, &mut result).await?;
+
+    let search_path: String = tx.query_one("SHOW search_path", &[]).await?.get(0);
+    tx.batch_execute(&format!("SET LOCAL search_path TO {}, {}", schema, search_path)).await?;
+    run_statements(&tx, statements, &mut result).await;
+
+    // Never keep anything, including the scratch schema
+    let _ = tx.rollback().await;
+
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(result)
+}
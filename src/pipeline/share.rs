@@ -0,0 +1,141 @@
+//! Proposal share links
+//!
+//! A share link gives someone without an account a read-only view of one
+//! proposal: its plan, risk analysis and SQL. The link carries a signed token
+//! that expires with it; the registry below is what allows a link to be
+//! revoked before then and records every time it is opened.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Longest a share link may stay valid
+pub const MAX_SHARE_LINK_HOURS: i64 = 24 * 30;
+
+/// Accesses kept per link; older ones are dropped
+const MAX_ACCESS_LOG: usize = 200;
+
+/// One visit through a share link
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareAccess {
+    pub accessed_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+/// A read-only link to a proposal
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    pub id: Uuid,
+    pub proposal_id: Uuid,
+    /// Who the link is for, e.g. "External DBA review"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_by: Option<String>,
+    pub access_count: u64,
+    pub accesses: Vec<ShareAccess>,
+}
+
+impl ShareLink {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && Utc::now() < self.expires_at
+    }
+}
+
+/// In-memory registry of share links
+#[derive(Clone, Default)]
+pub struct ShareLinkRegistry {
+    links: Arc<RwLock<HashMap<Uuid, ShareLink>>>,
+}
+
+impl ShareLinkRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a link; `hours` is clamped to 1..=MAX_SHARE_LINK_HOURS
+    pub async fn create(&self, proposal_id: Uuid, label: Option<String>, created_by: &str, hours: i64) -> ShareLink {
+        let now = Utc::now();
+        let link = ShareLink {
+            id: Uuid::new_v4(),
+            proposal_id,
+            label,
+            created_by: created_by.to_string(),
+            created_at: now,
+            expires_at: now + Duration::hours(hours.clamp(1, MAX_SHARE_LINK_HOURS)),
+            revoked_at: None,
+            revoked_by: None,
+            access_count: 0,
+            accesses: Vec::new(),
+        };
+
+        self.links.write().await.insert(link.id, link.clone());
+        link
+    }
+
+    /// All links for a proposal, newest first, including expired and revoked ones
+    pub async fn list_for_proposal(&self, proposal_id: Uuid) -> Vec<ShareLink> {
+        let links = self.links.read().await;
+        let mut found: Vec<_> = links.values().filter(|l| l.proposal_id == proposal_id).cloned().collect();
+        found.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        found
+    }
+
+    /// Revoke a link of the given proposal; returns None if unknown or no longer active
+    pub async fn revoke(&self, proposal_id: Uuid, id: Uuid, revoked_by: &str) -> Option<ShareLink> {
+        let mut links = self.links.write().await;
+        let link = links
+            .get_mut(&id)
+            .filter(|l| l.proposal_id == proposal_id && l.is_active())?;
+        link.revoked_at = Some(Utc::now());
+        link.revoked_by = Some(revoked_by.to_string());
+        Some(link.clone())
+    }
+
+    /// Log a visit; returns None (and logs nothing) if the link cannot be used
+    pub async fn record_access(&self, id: Uuid, access: ShareAccess) -> Option<ShareLink> {
+        let mut links = self.links.write().await;
+        let link = links.get_mut(&id).filter(|l| l.is_active())?;
+        link.access_count += 1;
+        link.accesses.push(access);
+        if link.accesses.len() > MAX_ACCESS_LOG {
+            link.accesses.remove(0);
+        }
+        Some(link.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_revoked_link_stops_working() {
+        let registry = ShareLinkRegistry::new();
+        let proposal_id = Uuid::new_v4();
+        let link = registry.create(proposal_id, None, "1", 10_000).await;
+        assert_eq!(link.expires_at - link.created_at, Duration::hours(MAX_SHARE_LINK_HOURS));
+
+        let access = || ShareAccess { accessed_at: Utc::now(), client_ip: None, user_agent: None };
+        assert_eq!(registry.record_access(link.id, access()).await.unwrap().access_count, 1);
+
+        // Revocation is scoped to the proposal the link belongs to
+        assert!(registry.revoke(Uuid::new_v4(), link.id, "1").await.is_none());
+        assert!(registry.revoke(proposal_id, link.id, "1").await.is_some());
+        assert!(registry.record_access(link.id, access()).await.is_none());
+        assert_eq!(registry.list_for_proposal(proposal_id).await[0].access_count, 1);
+    }
+}
@@ -0,0 +1,154 @@
+//! Review SLAs
+//!
+//! A project can require proposals to be reviewed within a number of business
+//! days of submission. The deadline is fixed when a proposal is submitted; the
+//! policy sweeper escalates proposals still waiting past it, and the analytics
+//! report how reviews fared against it along with the time proposals spend in
+//! each status.
+
+use crate::error::AppError;
+use crate::pipeline::proposal::SchemaProposal;
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest review SLA a project may set
+const MAX_REVIEW_BUSINESS_DAYS: u32 = 30;
+
+/// Review SLA for a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReviewSla {
+    /// Business days a submitted proposal may wait for review (None = no SLA)
+    pub review_business_days: Option<u32>,
+}
+
+impl ReviewSla {
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self.review_business_days {
+            Some(days) if days == 0 || days > MAX_REVIEW_BUSINESS_DAYS => Err(AppError::Validation(format!(
+                "Review SLA must be between 1 and {} business days",
+                MAX_REVIEW_BUSINESS_DAYS
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Review deadline for a proposal submitted at `submitted_at`
+    pub fn due_at(&self, submitted_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.review_business_days.map(|days| add_business_days(submitted_at, days))
+    }
+}
+
+/// `start` moved forward by `days` weekdays, keeping the time of day.
+/// A start on a weekend counts from the following Monday.
+pub fn add_business_days(start: DateTime<Utc>, days: u32) -> DateTime<Utc> {
+    let is_weekend = |at: DateTime<Utc>| matches!(at.weekday(), Weekday::Sat | Weekday::Sun);
+
+    let mut at = start;
+    let mut remaining = days;
+    while remaining > 0 {
+        at += Duration::days(1);
+        if !is_weekend(at) {
+            remaining -= 1;
+        }
+    }
+    at
+}
+
+/// How a project's reviews fared against its SLA
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSlaStats {
+    /// Proposals submitted in the period with a review deadline
+    pub with_sla: usize,
+    pub reviewed_within_sla: usize,
+    pub reviewed_late: usize,
+    /// Still waiting for review past the deadline
+    pub overdue: usize,
+    /// Share of reviewed proposals that met the deadline, 0-100
+    pub compliance_percent: f64,
+    pub average_review_hours: f64,
+    /// Average hours spent in each status by proposals submitted in the period
+    pub average_hours_in_status: BTreeMap<String, f64>,
+}
+
+/// Roll up SLA figures for proposals submitted between `since` and `until`
+pub fn stats(proposals: &[SchemaProposal], since: DateTime<Utc>, until: DateTime<Utc>, now: DateTime<Utc>) -> ReviewSlaStats {
+    let submitted: Vec<&SchemaProposal> = proposals
+        .iter()
+        .filter(|p| p.submitted_at.is_some_and(|at| at >= since && at <= until))
+        .collect();
+
+    let mut stats = ReviewSlaStats::default();
+    let mut review_hours = Vec::new();
+    for proposal in &submitted {
+        let submitted_at = proposal.submitted_at.expect("filtered on submitted_at");
+        let reviewed_at = proposal.reviewed_at();
+        if let Some(reviewed_at) = reviewed_at {
+            review_hours.push(hours(reviewed_at - submitted_at));
+        }
+
+        let Some(due) = proposal.review_due_at else {
+            continue;
+        };
+        stats.with_sla += 1;
+        match reviewed_at {
+            Some(at) if at <= due => stats.reviewed_within_sla += 1,
+            Some(_) => stats.reviewed_late += 1,
+            None if due < now => stats.overdue += 1,
+            None => {}
+        }
+    }
+
+    let reviewed = stats.reviewed_within_sla + stats.reviewed_late;
+    if reviewed > 0 {
+        stats.compliance_percent = round1(stats.reviewed_within_sla as f64 * 100.0 / reviewed as f64);
+    }
+    if !review_hours.is_empty() {
+        stats.average_review_hours = round1(review_hours.iter().sum::<f64>() / review_hours.len() as f64);
+    }
+
+    let mut totals: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+    for proposal in &submitted {
+        for (status, seconds) in proposal.time_in_status(now) {
+            let total = totals.entry(status).or_default();
+            total.0 += seconds as f64 / 3600.0;
+            total.1 += 1;
+        }
+    }
+    stats.average_hours_in_status = totals
+        .into_iter()
+        .map(|(status, (total, count))| (status, round1(total / count as f64)))
+        .collect();
+
+    stats
+}
+
+fn hours(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / 3600.0
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_business_days_skip_weekends() {
+        // Thursday 10:00 + 2 business days = Monday 10:00
+        let thursday = Utc.with_ymd_and_hms(2024, 5, 2, 10, 0, 0).unwrap();
+        assert_eq!(add_business_days(thursday, 2), Utc.with_ymd_and_hms(2024, 5, 6, 10, 0, 0).unwrap());
+
+        // Saturday counts from Monday
+        let saturday = Utc.with_ymd_and_hms(2024, 5, 4, 9, 0, 0).unwrap();
+        assert_eq!(add_business_days(saturday, 1), Utc.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap());
+
+        assert!(ReviewSla { review_business_days: Some(0) }.validate().is_err());
+        assert_eq!(ReviewSla::default().due_at(thursday), None);
+    }
+}
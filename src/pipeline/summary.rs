@@ -0,0 +1,423 @@
+//! Plain-English proposal summaries
+//!
+//! Product owners and managers get asked to sign off on schema changes they
+//! cannot read. Every proposal has a short executive summary built from its
+//! changes, risk analysis and blast radius: what it does, whether it deletes
+//! data, how risky and slow it is and what else it touches. It shows on the
+//! proposal detail and in review emails.
+//!
+//! Projects with the `llm_summaries` feature have the configured language
+//! model rewrite those facts into smoother prose once a proposal is
+//! submitted or analyzed. The rewrite is kept only while the facts it was
+//! written from still hold; after any change the template summary is shown
+//! again until the model writes a new one.
+
+use crate::config::SummaryConfig;
+use crate::error::AppError;
+use crate::features::{self, Feature};
+use crate::pipeline::backup;
+use crate::pipeline::changelog;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::blast_radius::ImpactType;
+use crate::state::SharedState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Changes listed in the prompt; the counts in the facts cover the rest
+const MAX_PROMPT_CHANGES: usize = 40;
+
+const SYSTEM_PROMPT: &str = "You summarize database schema change proposals for non-technical \
+stakeholders such as product managers. Write at most four short sentences of plain English. \
+Do not use SQL, do not invent facts, and keep every risk, data loss and downtime statement \
+from the facts you are given.";
+
+/// Who wrote a summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummarySource {
+    Template,
+    Llm,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutiveSummary {
+    pub text: String,
+    pub source: SummarySource,
+    pub generated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Digest of the template facts the summary was written from
+    pub facts_digest: String,
+}
+
+impl ExecutiveSummary {
+    fn template(text: String) -> Self {
+        Self {
+            facts_digest: digest(&text),
+            text,
+            source: SummarySource::Template,
+            generated_at: Utc::now(),
+            model: None,
+        }
+    }
+}
+
+/// The summary to show: the model's, if written from the current facts,
+/// otherwise the template's
+pub fn current(proposal: &SchemaProposal) -> ExecutiveSummary {
+    let facts = template(proposal);
+    match &proposal.executive_summary {
+        Some(summary) if summary.facts_digest == digest(&facts) => summary.clone(),
+        _ => ExecutiveSummary::template(facts),
+    }
+}
+
+/// Summary written from the proposal's changes, risk analysis and blast radius
+pub fn template(proposal: &SchemaProposal) -> String {
+    let mut sentences = Vec::new();
+
+    let counts = change_counts(&proposal.changes);
+    sentences.push(match counts.is_empty() {
+        true => "This proposal does not change anything yet.".to_string(),
+        false => format!("This proposal {}.", join_phrases(&counts)),
+    });
+
+    if let Some((tables, full)) = backup::destroyed_by(&proposal.changes) {
+        sentences.push(match full {
+            true => "It permanently deletes data, so a backup of the whole database must be confirmed before it runs."
+                .to_string(),
+            false => format!(
+                "It permanently deletes data in {}, so a backup must be confirmed before it runs.",
+                tables.into_iter().collect::<Vec<_>>().join(", ")
+            ),
+        });
+    }
+
+    match &proposal.risk_analysis {
+        Some(risk) => sentences.push(format!(
+            "Risk is rated {} ({} out of 100){}; it should take {} and {}.",
+            format!("{:?}", risk.overall_risk).to_lowercase(),
+            risk.score,
+            if risk.stale { ", though the analysis is out of date" } else { "" },
+            duration(risk.estimated_duration_secs),
+            if risk.requires_downtime { "needs downtime" } else { "should not need downtime" }
+        )),
+        None => sentences.push("Its risk has not been analyzed yet.".to_string()),
+    }
+
+    if let Some(report) = &proposal.blast_radius {
+        let impacted: BTreeSet<(&str, bool)> = report
+            .objects
+            .iter()
+            .flat_map(|radius| &radius.impacted)
+            .map(|object| (object.path.as_str(), object.object_type == ImpactType::View))
+            .collect();
+        let views: Vec<&str> = impacted.iter().filter(|(_, view)| *view).map(|(path, _)| *path).take(3).collect();
+        sentences.push(match (impacted.len(), views.is_empty()) {
+            (0, _) => "Nothing else in the database depends on what it changes.".to_string(),
+            (n, true) => format!("{} dependent object(s) may be affected.", n),
+            (n, false) => format!(
+                "{} dependent object(s) may be affected, including the view(s) {}.",
+                n,
+                views.join(", ")
+            ),
+        });
+    }
+
+    sentences.join(" ")
+}
+
+/// What each change does, as (verb, singular, plural)
+fn category(change: &SchemaChange) -> (&'static str, &'static str, &'static str) {
+    match change {
+        SchemaChange::CreateSchema { .. } => ("creates", "schema", "schemas"),
+        SchemaChange::DropSchema { .. } => ("removes", "schema", "schemas"),
+        SchemaChange::CreateTable { .. } | SchemaChange::CreatePartition { .. } => ("creates", "table", "tables"),
+        SchemaChange::DropTable { .. } => ("removes", "table", "tables"),
+        SchemaChange::RenameSchema { .. } | SchemaChange::RenameTable { .. } | SchemaChange::RenameColumn { .. } => {
+            ("renames", "object", "objects")
+        }
+        SchemaChange::AddColumn { .. } => ("adds", "column", "columns"),
+        SchemaChange::DropColumn { .. } => ("removes", "column", "columns"),
+        SchemaChange::AlterColumn { .. } | SchemaChange::Backfill { .. } => ("changes", "column", "columns"),
+        SchemaChange::ReorderColumns { .. } => ("rearranges", "table", "tables"),
+        SchemaChange::AddIndex { .. } => ("adds", "index", "indexes"),
+        SchemaChange::DropIndex { .. } => ("removes", "index", "indexes"),
+        SchemaChange::AddForeignKey { .. }
+        | SchemaChange::AddCheck { .. }
+        | SchemaChange::AddUnique { .. }
+        | SchemaChange::ValidateConstraint { .. } => ("enforces", "data rule", "data rules"),
+        SchemaChange::DropForeignKey { .. } | SchemaChange::DropConstraint { .. } => {
+            ("removes", "data rule", "data rules")
+        }
+        SchemaChange::CreateExtension { .. } => ("installs", "extension", "extensions"),
+        SchemaChange::DropExtension { .. } => ("removes", "extension", "extensions"),
+        SchemaChange::AlterExtensionVersion { .. } => ("updates", "extension", "extensions"),
+        SchemaChange::AttachPartition { .. } | SchemaChange::DetachPartition { .. } => {
+            ("moves", "partition", "partitions")
+        }
+        SchemaChange::CreatePolicy { .. }
+        | SchemaChange::AlterPolicy { .. }
+        | SchemaChange::DropPolicy { .. }
+        | SchemaChange::SetRowSecurity { .. } => ("changes", "access rule", "access rules"),
+        SchemaChange::CreateDomain { .. } | SchemaChange::CreateCompositeType { .. } => {
+            ("defines", "custom type", "custom types")
+        }
+        SchemaChange::AlterDomain { .. } | SchemaChange::AlterCompositeType { .. } => {
+            ("changes", "custom type", "custom types")
+        }
+        SchemaChange::DropDomain { .. } | SchemaChange::DropCompositeType { .. } => {
+            ("removes", "custom type", "custom types")
+        }
+        SchemaChange::CreateView { .. } | SchemaChange::CreateMaterializedView { .. } => {
+            ("creates", "saved query", "saved queries")
+        }
+        SchemaChange::AlterView { .. } => ("changes", "saved query", "saved queries"),
+        SchemaChange::DropView { .. } | SchemaChange::DropMaterializedView { .. } => {
+            ("removes", "saved query", "saved queries")
+        }
+        SchemaChange::RefreshMaterializedView { .. } => ("refreshes", "saved query", "saved queries"),
+        SchemaChange::SetCustomField { .. }
+        | SchemaChange::AddTag { .. }
+        | SchemaChange::RemoveTag { .. }
+        | SchemaChange::SetClassification { .. } => ("updates", "catalog entry", "catalog entries"),
+        SchemaChange::Reindex { .. } | SchemaChange::Vacuum { .. } | SchemaChange::Analyze { .. } => {
+            ("runs", "maintenance task", "maintenance tasks")
+        }
+    }
+}
+
+/// "creates 1 table", "adds 2 columns", ... in order of first appearance
+fn change_counts(changes: &[SchemaChange]) -> Vec<String> {
+    let mut counts: Vec<((&str, &str, &str), usize)> = Vec::new();
+    for change in changes {
+        let key = category(change);
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((key, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((verb, one, many), n)| format!("{} {} {}", verb, n, if n == 1 { one } else { many }))
+        .collect()
+}
+
+fn join_phrases(phrases: &[String]) -> String {
+    match phrases {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn duration(secs: u64) -> String {
+    match secs {
+        0..=59 => "under a minute".to_string(),
+        60..=3599 => {
+            let minutes = secs.div_ceil(60);
+            format!("about {} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+        }
+        _ => {
+            let hours = secs.div_ceil(3600);
+            format!("about {} hour{}", hours, if hours == 1 { "" } else { "s" })
+        }
+    }
+}
+
+fn digest(facts: &str) -> String {
+    format!("{:x}", Sha256::digest(facts.as_bytes()))
+}
+
+/// Chat completions client that rewrites template summaries
+pub struct LlmSummarizer {
+    client: reqwest::Client,
+    config: SummaryConfig,
+}
+
+impl LlmSummarizer {
+    pub fn new(config: &SummaryConfig) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AppError::Config(format!("Cannot build the summary HTTP client: {}", e)))?;
+        Ok(Self { client, config: config.clone() })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.config.llm_model
+    }
+
+    /// The model's summary of a proposal, written from the template facts
+    pub async fn summarize(&self, proposal: &SchemaProposal, facts: &str) -> Result<String, AppError> {
+        let mut changes: Vec<String> = proposal
+            .changes
+            .iter()
+            .map(changelog::describe)
+            .filter(|line| !line.is_empty())
+            .take(MAX_PROMPT_CHANGES)
+            .map(|line| format!("- {}", line))
+            .collect();
+        if proposal.changes.len() > MAX_PROMPT_CHANGES {
+            changes.push(format!("- ...and {} more", proposal.changes.len() - MAX_PROMPT_CHANGES));
+        }
+        let prompt = format!(
+            "Title: {}\nDescription: {}\n\nChanges:\n{}\n\nFacts:\n{}",
+            proposal.title,
+            proposal.description,
+            changes.join("\n"),
+            facts
+        );
+        let body = json!({
+            "model": self.config.llm_model,
+            "temperature": 0.2,
+            "messages": [
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": prompt },
+            ],
+        });
+
+        let mut request = self
+            .client
+            .post(&self.config.llm_endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string());
+        if let Some(key) = &self.config.llm_api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Summary request failed: {}", e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AppError::Internal(format!("Summary response unreadable: {}", e)))?;
+        if !status.is_success() {
+            return Err(AppError::Internal(format!("Summary endpoint answered {}: {}", status, text)));
+        }
+
+        let reply: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| AppError::Internal(format!("Summary response is not JSON: {}", e)))?;
+        reply["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::trim)
+            .filter(|content| !content.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Internal("Summary response has no message content".to_string()))
+    }
+}
+
+/// Have the model rewrite a proposal's summary in the background, if the
+/// project has the feature and a model is configured. Failures leave the
+/// template summary in place.
+pub fn schedule(state: &SharedState, proposal: &SchemaProposal) {
+    if state.summarizer.is_none() {
+        return;
+    }
+    let state = state.clone();
+    let proposal = proposal.clone();
+    tokio::spawn(async move {
+        let Some(summarizer) = state.summarizer.as_ref() else { return };
+        match features::is_enabled(&state, proposal.project_id, Feature::LlmSummaries).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                warn!("Skipped the summary of proposal {}: {}", proposal.id, e);
+                return;
+            }
+        }
+
+        let facts = template(&proposal);
+        match summarizer.summarize(&proposal, &facts).await {
+            Ok(text) => {
+                let summary = ExecutiveSummary {
+                    text,
+                    source: SummarySource::Llm,
+                    generated_at: Utc::now(),
+                    model: Some(summarizer.model().to_string()),
+                    facts_digest: digest(&facts),
+                };
+                match state.pipeline_proposals.set_executive_summary(proposal.id, summary).await {
+                    Ok(Some(_)) => info!("Summary of proposal {} written by {}", proposal.id, summarizer.model()),
+                    Ok(None) => {}
+                    Err(e) => warn!("Summary of proposal {} could not be stored: {}", proposal.id, e),
+                }
+            }
+            Err(e) => warn!("Summary of proposal {} stays the template: {}", proposal.id, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::proposal::{RiskAnalysis, RiskLevel};
+    use crate::pipeline::types::ColumnDef;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_template_states_changes_data_loss_and_risk() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "Retire legacy".to_string(), String::new(), "dev".to_string());
+        assert_eq!(
+            template(&proposal),
+            "This proposal does not change anything yet. Its risk has not been analyzed yet."
+        );
+
+        let column = |name: &str| ColumnDef {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            collation: None,
+        };
+        proposal.changes = vec![
+            SchemaChange::AddColumn { table_name: "users".to_string(), column: column("nickname") },
+            SchemaChange::DropTable { table_name: "legacy".to_string() },
+            SchemaChange::AddColumn { table_name: "users".to_string(), column: column("pronouns") },
+        ];
+        proposal.risk_analysis = Some(RiskAnalysis {
+            overall_risk: RiskLevel::High,
+            score: 70,
+            warnings: vec![],
+            recommendations: vec![],
+            warning_codes: vec![],
+            recommendation_codes: vec![],
+            estimated_duration_secs: 90,
+            requires_downtime: false,
+            affected_tables: vec![],
+            analyzed_at: Utc::now(),
+            stale: false,
+            stale_reason: None,
+            duplicate_checks: vec![],
+        });
+        let facts = template(&proposal);
+        assert_eq!(
+            facts,
+            "This proposal adds 2 columns and removes 1 table. \
+             It permanently deletes data in public.legacy, so a backup must be confirmed before it runs. \
+             Risk is rated high (70 out of 100); it should take about 2 minutes and should not need downtime."
+        );
+
+        // A model's summary shows only while the facts it was written from hold
+        proposal.executive_summary = Some(ExecutiveSummary {
+            text: "Adds two optional profile fields and deletes the old legacy table.".to_string(),
+            source: SummarySource::Llm,
+            generated_at: Utc::now(),
+            model: Some("test".to_string()),
+            facts_digest: digest(&facts),
+        });
+        assert_eq!(current(&proposal).source, SummarySource::Llm);
+        proposal.changes.pop();
+        assert_eq!(current(&proposal).source, SummarySource::Template);
+    }
+}
@@ -0,0 +1,236 @@
+//! Reviewer teams
+//!
+//! A project can group its members into named teams and assign a whole team
+//! to review a proposal instead of listing people one by one. Approval from
+//! any member counts towards the team, and a team can ask for more than one
+//! of its members to approve before its review is complete. Review requests
+//! go to every member plus the team's shared mailbox, if it has one.
+
+use crate::error::AppError;
+use crate::notifications::Audience;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::state::SharedState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Most teams a project may define
+const MAX_TEAMS: usize = 50;
+
+/// A named group of project members that reviews as one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Team {
+    /// Short identifier proposals refer to (lowercase letters, digits, '-')
+    pub slug: String,
+    pub name: String,
+    /// User ids of the members
+    pub members: Vec<String>,
+    /// Members who must approve before the team's review is complete
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: u32,
+    /// Shared mailbox that also receives the team's review requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+fn default_required_approvals() -> u32 {
+    1
+}
+
+impl Team {
+    pub fn is_member(&self, user_id: &str) -> bool {
+        self.members.iter().any(|m| m == user_id)
+    }
+}
+
+/// Teams defined for a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectTeams {
+    pub teams: Vec<Team>,
+}
+
+impl ProjectTeams {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.teams.len() > MAX_TEAMS {
+            return Err(AppError::Validation(format!("A project can have at most {} teams", MAX_TEAMS)));
+        }
+
+        let mut slugs = HashSet::new();
+        for team in &self.teams {
+            let valid_slug = !team.slug.is_empty()
+                && team.slug.len() <= 64
+                && team.slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+            if !valid_slug {
+                return Err(AppError::Validation(format!(
+                    "Invalid team slug '{}': use up to 64 lowercase letters, digits and '-'",
+                    team.slug
+                )));
+            }
+            if !slugs.insert(team.slug.as_str()) {
+                return Err(AppError::Validation(format!("Duplicate team slug '{}'", team.slug)));
+            }
+            if team.name.trim().is_empty() {
+                return Err(AppError::Validation(format!("Team '{}' needs a name", team.slug)));
+            }
+            if team.members.is_empty() {
+                return Err(AppError::Validation(format!("Team '{}' has no members", team.slug)));
+            }
+            if team.required_approvals == 0 || team.required_approvals as usize > team.members.len() {
+                return Err(AppError::Validation(format!(
+                    "Team '{}' requires {} approvals but has {} members",
+                    team.slug,
+                    team.required_approvals,
+                    team.members.len()
+                )));
+            }
+            if team.channel.as_deref().is_some_and(|c| !c.contains('@')) {
+                return Err(AppError::Validation(format!(
+                    "Team '{}' channel must be an email address",
+                    team.slug
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Everyone who belongs to at least one team
+    pub fn members(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.teams
+            .iter()
+            .flat_map(|t| t.members.iter())
+            .filter(|m| seen.insert(m.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    pub fn find(&self, slug: &str) -> Option<&Team> {
+        self.teams.iter().find(|t| t.slug == slug)
+    }
+
+    /// The teams assigned to `proposal` that still exist, in assignment order
+    pub fn assigned_to(&self, proposal: &SchemaProposal) -> Vec<Team> {
+        proposal.reviewer_teams.iter().filter_map(|slug| self.find(slug)).cloned().collect()
+    }
+
+    /// Fail on slugs that name no team of the project
+    pub fn check_slugs(&self, slugs: &[String]) -> Result<(), AppError> {
+        match slugs.iter().find(|slug| self.find(slug).is_none()) {
+            Some(unknown) => Err(AppError::Validation(format!("Unknown reviewer team '{}'", unknown))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A member's approval given on behalf of a team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamApproval {
+    pub team: String,
+    pub approver: String,
+    pub approved_at: DateTime<Utc>,
+}
+
+/// How far a team's review of a proposal has got
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamReview {
+    pub team: String,
+    pub required_approvals: u32,
+    pub approvers: Vec<String>,
+    pub complete: bool,
+}
+
+/// Review progress of each assigned team
+pub fn reviews(proposal: &SchemaProposal, teams: &[Team]) -> Vec<TeamReview> {
+    teams
+        .iter()
+        .map(|team| {
+            let approvers: Vec<String> = proposal
+                .team_approvals
+                .iter()
+                .filter(|a| a.team == team.slug)
+                .map(|a| a.approver.clone())
+                .collect();
+            TeamReview {
+                team: team.slug.clone(),
+                required_approvals: team.required_approvals,
+                complete: approvers.len() >= team.required_approvals as usize,
+                approvers,
+            }
+        })
+        .collect()
+}
+
+/// Teams defined for a project; projectless proposals have none
+pub async fn for_project(state: &SharedState, project_id: Option<i32>) -> Result<ProjectTeams, AppError> {
+    match project_id {
+        Some(id) => Ok(state.project_service.get_teams(id).await?.unwrap_or_default()),
+        None => Ok(ProjectTeams::default()),
+    }
+}
+
+/// Individual reviewers plus every member and mailbox of `teams`
+pub fn review_audience(reviewers: &[String], teams: &[Team]) -> Audience {
+    let mut seen = HashSet::new();
+    let recipients = reviewers
+        .iter()
+        .chain(teams.iter().flat_map(|t| t.members.iter().chain(t.channel.iter())))
+        .filter(|r| seen.insert(r.as_str()))
+        .cloned()
+        .collect();
+    Audience::Users(recipients)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn team(slug: &str, members: &[&str], required: u32) -> Team {
+        Team {
+            slug: slug.to_string(),
+            name: slug.to_uppercase(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+            required_approvals: required,
+            channel: Some(format!("{}@example.com", slug)),
+        }
+    }
+
+    #[test]
+    fn test_validation_and_review_progress() {
+        let teams = ProjectTeams { teams: vec![team("dba", &["1", "2"], 2), team("platform", &["2", "3"], 1)] };
+        assert!(teams.validate().is_ok());
+        assert_eq!(teams.members(), vec!["1", "2", "3"]);
+
+        let too_strict = ProjectTeams { teams: vec![team("dba", &["1"], 2)] };
+        assert!(too_strict.validate().is_err());
+        let bad_slug = ProjectTeams { teams: vec![team("DBA Team", &["1"], 1)] };
+        assert!(bad_slug.validate().is_err());
+
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "1".to_string());
+        proposal.reviewers = vec!["3".to_string()];
+        proposal.reviewer_teams = vec!["dba".to_string(), "gone".to_string()];
+        assert!(teams.check_slugs(&proposal.reviewer_teams).is_err());
+
+        // A removed team no longer takes part in the review
+        let assigned = teams.assigned_to(&proposal);
+        assert_eq!(assigned.len(), 1);
+
+        proposal.team_approvals.push(TeamApproval {
+            team: "dba".to_string(),
+            approver: "1".to_string(),
+            approved_at: Utc::now(),
+        });
+        let progress = reviews(&proposal, &assigned);
+        assert_eq!(progress[0].approvers, vec!["1"]);
+        assert!(!progress[0].complete);
+
+        let Audience::Users(recipients) = review_audience(&proposal.reviewers, &assigned) else {
+            panic!("expected a user audience");
+        };
+        assert_eq!(recipients, vec!["3", "1", "2", "dba@example.com"]);
+    }
+}
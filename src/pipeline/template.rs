@@ -0,0 +1,172 @@
+//! Proposal templates
+//!
+//! A project can require every proposal description to contain certain
+//! markdown sections (e.g. "## Rollback plan") and titles to follow a pattern.
+//! New proposals start from the template, and submission is refused until
+//! each required section has real content. Projects that never set one start
+//! from the default template's skeleton, but nothing is enforced on them.
+
+use crate::error::AppError;
+use crate::pipeline::proposal::SchemaProposal;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A section the description must contain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSection {
+    /// Heading text, matched case-insensitively
+    pub heading: String,
+    /// Guidance shown under the heading in new proposals
+    #[serde(default)]
+    pub prompt: String,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// Proposal template for a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProposalTemplate {
+    /// Regex the title must match (None = any title)
+    pub title_pattern: Option<String>,
+    /// Shown to authors when the title does not match
+    pub title_hint: Option<String>,
+    pub sections: Vec<TemplateSection>,
+}
+
+impl Default for ProposalTemplate {
+    fn default() -> Self {
+        let section = |heading: &str, prompt: &str| TemplateSection {
+            heading: heading.to_string(),
+            prompt: prompt.to_string(),
+            required: true,
+        };
+        Self {
+            title_pattern: None,
+            title_hint: None,
+            sections: vec![
+                section("Motivation", "Why is this change needed?"),
+                section("Rollback plan", "How will this be undone if something goes wrong?"),
+                section("Affected services", "Which applications read or write these objects?"),
+            ],
+        }
+    }
+}
+
+impl ProposalTemplate {
+    /// Reject templates that could never be satisfied
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(pattern) = &self.title_pattern {
+            Regex::new(pattern)
+                .map_err(|e| AppError::BadRequest(format!("Invalid title pattern: {}", e)))?;
+        }
+        if self.sections.iter().any(|s| s.heading.trim().is_empty()) {
+            return Err(AppError::BadRequest("Template section headings cannot be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Markdown skeleton for a new proposal description
+    pub fn render_description(&self) -> String {
+        self.sections
+            .iter()
+            .map(|s| {
+                if s.prompt.is_empty() {
+                    format!("## {}\n", s.heading)
+                } else {
+                    format!("## {}\n\n<!-- {} -->\n", s.heading, s.prompt)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Everything that keeps a proposal from being submitted
+    pub fn problems(&self, proposal: &SchemaProposal) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(pattern) = self.title_pattern.as_deref().and_then(|p| Regex::new(p).ok()) {
+            if !pattern.is_match(&proposal.title) {
+                problems.push(match &self.title_hint {
+                    Some(hint) => format!("Title does not follow the project convention: {}", hint),
+                    None => format!("Title must match /{}/", pattern.as_str()),
+                });
+            }
+        }
+
+        let sections = parse_sections(&proposal.description);
+        for required in self.sections.iter().filter(|s| s.required) {
+            let filled = sections
+                .iter()
+                .any(|(heading, body)| heading.eq_ignore_ascii_case(required.heading.trim()) && has_content(body));
+            if !filled {
+                problems.push(format!("Section '{}' is missing or empty", required.heading));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Split markdown into (heading, body) pairs at any ATX heading level
+fn parse_sections(markdown: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') {
+            let heading = trimmed.trim_start_matches('#');
+            if heading.is_empty() || heading.starts_with(' ') {
+                sections.push((heading.trim().trim_end_matches('#').trim().to_string(), String::new()));
+                continue;
+            }
+        }
+        if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    sections
+}
+
+/// A body counts as filled once the template's HTML comments are removed
+fn has_content(body: &str) -> bool {
+    let mut rest = body;
+    let mut text = String::new();
+    while let Some(start) = rest.find("<!--") {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    !text.trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_required_sections_must_be_filled() {
+        let template = ProposalTemplate::default();
+        let mut proposal = SchemaProposal::new(
+            Uuid::new_v4(),
+            "Add orders index".to_string(),
+            template.render_description(),
+            "dev".to_string(),
+        );
+        assert_eq!(template.problems(&proposal).len(), 3);
+
+        proposal.description = "## Motivation\nSlow checkout queries.\n\n### rollback PLAN\nDrop the index.\n\n## Affected services\n<!-- Which applications? -->\n".to_string();
+        assert_eq!(template.problems(&proposal), vec!["Section 'Affected services' is missing or empty"]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Proposal templating
+//!
+//! Change definitions may contain placeholders such as `{{retention_days}}`
+//! or `{{partition_start}}`, so one proposal can carry literals that differ
+//! between development, staging and production. A proposal declares the
+//! parameters it uses, each with a type and an optional default. Values are
+//! stored on the proposal per environment and can be given or overridden
+//! when executing; the changes are rendered for the target connection's
+//! environment right before its migration is generated.
+//!
+//! Values are checked against their parameter's type before substitution.
+//! Text values may not contain quotes, backslashes, semicolons or comment
+//! markers, since they land inside SQL as written.
+
+use crate::error::AppError;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most parameters a proposal may declare
+const MAX_PARAMETERS: usize = 50;
+
+/// Values of a proposal's parameters, by name
+pub type ParameterValues = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterType {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+    /// YYYY-MM-DD
+    Date,
+    /// RFC 3339
+    Timestamp,
+    /// A table, column or schema name
+    Identifier,
+}
+
+impl ParameterType {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            ParameterType::Text => {
+                !value.contains(['\'', '\\', ';']) && !value.contains("--") && !value.contains("/*")
+            }
+            ParameterType::Integer => value.parse::<i64>().is_ok(),
+            ParameterType::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            ParameterType::Boolean => matches!(value, "true" | "false"),
+            ParameterType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            ParameterType::Timestamp => DateTime::parse_from_rfc3339(value).is_ok(),
+            ParameterType::Identifier => is_name(value),
+        }
+    }
+}
+
+/// A placeholder a proposal's changes may use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ParameterType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Used when an environment has no value of its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Lowercase letters, digits and '_', not starting with a digit
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Replace each `{{name}}` in `text` with what `value` returns for it,
+/// leaving placeholders it returns None for as they are
+fn substitute(text: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            rest = &rest[open..];
+            break;
+        };
+        let name = after[..close].trim();
+        match is_name(name).then(|| value(name)).flatten() {
+            Some(replacement) => out.push_str(&replacement),
+            None => out.push_str(&rest[open..open + 2 + close + 2]),
+        }
+        rest = &after[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn visit_strings(value: &mut Value, f: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(items) => items.iter_mut().for_each(|v| visit_strings(v, f)),
+        Value::Object(map) => map.values_mut().for_each(|v| visit_strings(v, f)),
+        _ => {}
+    }
+}
+
+/// Names of the placeholders used anywhere in `changes`
+pub fn placeholders(changes: &[SchemaChange]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for change in changes {
+        let Ok(mut value) = serde_json::to_value(change) else {
+            continue;
+        };
+        visit_strings(&mut value, &mut |s| {
+            substitute(s, |name| {
+                names.insert(name.to_string());
+                None
+            });
+        });
+    }
+    names
+}
+
+/// Whether the proposal needs rendering before it can run
+pub fn is_templated(proposal: &SchemaProposal) -> bool {
+    !proposal.parameters.is_empty() || !placeholders(&proposal.changes).is_empty()
+}
+
+/// Check the declared parameters and that they cover every placeholder
+pub fn validate_declaration(parameters: &[TemplateParameter], changes: &[SchemaChange]) -> Result<(), AppError> {
+    if parameters.len() > MAX_PARAMETERS {
+        return Err(AppError::Validation(format!("A proposal can declare at most {} parameters", MAX_PARAMETERS)));
+    }
+    let mut declared = BTreeSet::new();
+    for parameter in parameters {
+        if !is_name(&parameter.name) {
+            return Err(AppError::Validation(format!(
+                "Invalid parameter name '{}': use lowercase letters, digits and '_'",
+                parameter.name
+            )));
+        }
+        if !declared.insert(parameter.name.as_str()) {
+            return Err(AppError::Validation(format!("Parameter '{}' is declared twice", parameter.name)));
+        }
+        if let Some(default) = &parameter.default {
+            check_value(parameter, default)?;
+        }
+    }
+    match placeholders(changes).into_iter().find(|name| !declared.contains(name.as_str())) {
+        Some(undeclared) => Err(AppError::Validation(format!(
+            "Placeholder '{{{{{}}}}}' is used but no such parameter is declared",
+            undeclared
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn check_value(parameter: &TemplateParameter, value: &str) -> Result<(), AppError> {
+    if parameter.kind.accepts(value) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "Invalid value '{}' for parameter '{}' of type {:?}",
+            value, parameter.name, parameter.kind
+        )))
+    }
+}
+
+/// Check values against the declared parameters
+pub fn check_values(parameters: &[TemplateParameter], values: &ParameterValues) -> Result<(), AppError> {
+    for (name, value) in values {
+        let parameter = parameters
+            .iter()
+            .find(|p| &p.name == name)
+            .ok_or_else(|| AppError::Validation(format!("Unknown parameter '{}'", name)))?;
+        check_value(parameter, value)?;
+    }
+    Ok(())
+}
+
+/// Values for `environment`: defaults, overridden by the values stored for
+/// the environment, overridden by `provided`
+pub fn resolve(
+    proposal: &SchemaProposal,
+    environment: &str,
+    provided: &ParameterValues,
+) -> Result<ParameterValues, AppError> {
+    validate_declaration(&proposal.parameters, &proposal.changes)?;
+    check_values(&proposal.parameters, provided)?;
+    let stored = proposal.parameter_values.get(environment);
+
+    let mut values = ParameterValues::new();
+    for parameter in &proposal.parameters {
+        let value = provided
+            .get(&parameter.name)
+            .or_else(|| stored.and_then(|s| s.get(&parameter.name)))
+            .or(parameter.default.as_ref())
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "No value for parameter '{}' in environment '{}'",
+                    parameter.name, environment
+                ))
+            })?;
+        values.insert(parameter.name.clone(), value.clone());
+    }
+    Ok(values)
+}
+
+/// The changes with every placeholder replaced by its value
+pub fn render(changes: &[SchemaChange], values: &ParameterValues) -> Result<Vec<SchemaChange>, AppError> {
+    changes
+        .iter()
+        .map(|change| {
+            let mut value = serde_json::to_value(change)
+                .map_err(|e| AppError::Internal(format!("Failed to render change: {}", e)))?;
+            visit_strings(&mut value, &mut |s| *s = substitute(s, |name| values.get(name).cloned()));
+            serde_json::from_value(value)
+                .map_err(|e| AppError::Validation(format!("A rendered change is no longer valid: {}", e)))
+        })
+        .collect()
+}
+
+/// A copy of the proposal rendered for `environment`, without generated
+/// artifacts, and the values used
+pub fn render_for(
+    proposal: &SchemaProposal,
+    environment: &str,
+    provided: &ParameterValues,
+) -> Result<(SchemaProposal, ParameterValues), AppError> {
+    let values = resolve(proposal, environment, provided)?;
+    let mut rendered = proposal.clone();
+    rendered.changes = render(&proposal.changes, &values)?;
+    rendered.migration = None;
+    Ok((rendered, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn parameter(name: &str, kind: ParameterType, default: Option<&str>) -> TemplateParameter {
+        TemplateParameter { name: name.to_string(), kind, description: None, default: default.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_resolve_and_render_per_environment() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "1".to_string());
+        proposal.changes = vec![SchemaChange::AlterColumn {
+            table_name: "{{ schema }}.events".to_string(),
+            column_name: "retention_days".to_string(),
+            new_type: None,
+            new_nullable: None,
+            new_default: Some("{{retention_days}}".to_string()),
+            using: None,
+        }];
+        assert!(validate_declaration(&proposal.parameters, &proposal.changes).is_err());
+
+        proposal.parameters = vec![
+            parameter("retention_days", ParameterType::Integer, Some("7")),
+            parameter("schema", ParameterType::Identifier, None),
+        ];
+        proposal.parameter_values.insert(
+            "production".to_string(),
+            [("retention_days".to_string(), "365".to_string()), ("schema".to_string(), "app".to_string())].into(),
+        );
+
+        // Development has no stored values and `schema` has no default
+        assert!(resolve(&proposal, "development", &ParameterValues::new()).is_err());
+        let provided: ParameterValues = [("schema".to_string(), "dev".to_string())].into();
+        let (dev, values) = render_for(&proposal, "development", &provided).unwrap();
+        assert_eq!(values["retention_days"], "7");
+        let SchemaChange::AlterColumn { table_name, new_default, .. } = &dev.changes[0] else {
+            panic!("expected an AlterColumn");
+        };
+        assert_eq!(table_name, "dev.events");
+        assert_eq!(new_default.as_deref(), Some("7"));
+
+        let (prod, _) = render_for(&proposal, "production", &ParameterValues::new()).unwrap();
+        let SchemaChange::AlterColumn { new_default, .. } = &prod.changes[0] else {
+            panic!("expected an AlterColumn");
+        };
+        assert_eq!(new_default.as_deref(), Some("365"));
+
+        let injected: ParameterValues = [("schema".to_string(), "app; DROP TABLE users".to_string())].into();
+        assert!(resolve(&proposal, "production", &injected).is_err());
+        let unknown: ParameterValues = [("other".to_string(), "1".to_string())].into();
+        assert!(resolve(&proposal, "production", &unknown).is_err());
+    }
+}
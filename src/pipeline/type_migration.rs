@@ -0,0 +1,290 @@
+//! Column type migration assistant
+//!
+//! Many type changes need a USING clause PostgreSQL cannot infer (text to
+//! jsonb, varchar to uuid). `suggest` proposes expressions for a from/to type
+//! pair, and `validate` runs an expression over a sample of the column's
+//! values in a read-only transaction, reporting the values it fails on. The
+//! chosen expression goes into `SchemaChange::AlterColumn::using`.
+
+use crate::db::queries::SqlBuilder;
+use crate::error::AppError;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::verification::normalize_type;
+use deadpool_postgres::Pool;
+use serde::Serialize;
+
+/// Failures listed per validation; the rest are only counted
+const MAX_REPORTED_FAILURES: usize = 10;
+
+/// A candidate USING expression
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsingSuggestion {
+    pub expression: String,
+    pub description: String,
+    /// Values the expression cannot convert become NULL or a fallback instead of failing
+    pub lossy: bool,
+}
+
+/// A sampled value the expression could not convert
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionFailure {
+    pub value: String,
+    pub error: String,
+}
+
+/// Result of running an expression over sampled data
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsingValidation {
+    pub sampled: usize,
+    pub failed: usize,
+    pub failures: Vec<ConversionFailure>,
+}
+
+impl UsingValidation {
+    pub fn is_valid(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeFamily {
+    Text,
+    Integer,
+    Decimal,
+    Boolean,
+    Json,
+    Uuid,
+    Date,
+    Timestamp,
+    Other,
+}
+
+fn family(normalized: &str) -> TypeFamily {
+    match normalized {
+        "text" | "character varying" | "character" | "citext" => TypeFamily::Text,
+        "smallint" | "integer" | "bigint" => TypeFamily::Integer,
+        "numeric" | "real" | "double precision" | "money" => TypeFamily::Decimal,
+        "boolean" => TypeFamily::Boolean,
+        "json" | "jsonb" => TypeFamily::Json,
+        "uuid" => TypeFamily::Uuid,
+        "date" => TypeFamily::Date,
+        "timestamp with time zone" | "timestamp without time zone" => TypeFamily::Timestamp,
+        _ => TypeFamily::Other,
+    }
+}
+
+/// Propose USING expressions for changing `column` from `from_type` to
+/// `to_type`, the plain cast first
+pub fn suggest(column: &str, from_type: &str, to_type: &str) -> Vec<UsingSuggestion> {
+    let c = SqlBuilder::quote_ident(column);
+    let to = to_type.trim();
+    let to_normalized = normalize_type(to);
+
+    let mut suggestions = vec![suggestion(format!("{}::{}", c, to), "Plain cast; fails on any value that does not convert", false)];
+
+    use TypeFamily::*;
+    match (family(&normalize_type(from_type)), family(&to_normalized)) {
+        (Text, Json) => {
+            suggestions.push(suggestion(
+                format!("NULLIF(trim({}), '')::{}", c, to),
+                "Parse as JSON, treating blank strings as NULL",
+                false,
+            ));
+            let wrap = if to_normalized == "json" { "to_json" } else { "to_jsonb" };
+            suggestions.push(suggestion(
+                format!("{}({})", wrap, c),
+                "Store each value as a JSON string without parsing it",
+                false,
+            ));
+        }
+        (Text, Uuid) => {
+            suggestions.push(suggestion(
+                format!("NULLIF(trim({}), '')::uuid", c),
+                "Trim whitespace and treat blank strings as NULL",
+                false,
+            ));
+        }
+        (Text, Integer) => {
+            suggestions.push(suggestion(format!("trim({})::{}", c, to), "Trim whitespace before casting", false));
+            suggestions.push(suggestion(
+                format!("NULLIF(regexp_replace({}, '[^0-9-]', '', 'g'), '')::{}", c, to),
+                "Strip everything but digits and minus signs; values without digits become NULL",
+                true,
+            ));
+        }
+        (Text, Decimal) => {
+            suggestions.push(suggestion(
+                format!("NULLIF(regexp_replace({}, '[^0-9.eE+-]', '', 'g'), '')::{}", c, to),
+                "Strip currency symbols, separators and other non-numeric characters",
+                true,
+            ));
+        }
+        (Text, Boolean) => {
+            suggestions.push(suggestion(
+                format!("lower(trim({})) IN ('t', 'true', 'y', 'yes', 'on', '1')", c),
+                "Common truthy spellings become true, everything else false",
+                true,
+            ));
+        }
+        (Text, Date) => {
+            suggestions.push(suggestion(
+                format!("to_date({}, 'YYYY-MM-DD')", c),
+                "Parse with an explicit format instead of the session DateStyle",
+                false,
+            ));
+        }
+        (Text, Timestamp) => {
+            suggestions.push(suggestion(
+                format!("to_timestamp({}, 'YYYY-MM-DD HH24:MI:SS')::{}", c, to),
+                "Parse with an explicit format instead of the session DateStyle",
+                false,
+            ));
+        }
+        (Integer, Boolean) => {
+            suggestions.push(suggestion(format!("{} <> 0", c), "Zero becomes false, anything else true", false));
+        }
+        (Boolean, Integer) => {
+            suggestions.push(suggestion(
+                format!("CASE WHEN {} THEN 1 ELSE 0 END", c),
+                "true becomes 1, false becomes 0",
+                false,
+            ));
+        }
+        (Integer, Timestamp) => {
+            suggestions.push(suggestion(
+                format!("to_timestamp({})::{}", c, to),
+                "Interpret values as Unix epoch seconds",
+                false,
+            ));
+            suggestions.push(suggestion(
+                format!("to_timestamp({} / 1000.0)::{}", c, to),
+                "Interpret values as Unix epoch milliseconds",
+                false,
+            ));
+        }
+        (Decimal, Integer) => {
+            suggestions.push(suggestion(format!("round({})::{}", c, to), "Round to the nearest whole number", true));
+            suggestions.push(suggestion(format!("trunc({})::{}", c, to), "Drop the fractional part", true));
+        }
+        (Json, Text) => {
+            suggestions.push(suggestion(
+                format!("{} #>> '{{}}'", c),
+                "Unwrap JSON strings instead of keeping their quotes",
+                false,
+            ));
+        }
+        _ => {}
+    }
+
+    suggestions
+}
+
+fn suggestion(expression: String, description: &str, lossy: bool) -> UsingSuggestion {
+    UsingSuggestion {
+        expression,
+        description: description.to_string(),
+        lossy,
+    }
+}
+
+/// Quote an optionally schema-qualified table name
+pub(crate) fn quote_table(table: &str) -> String {
+    match split_table_name(table) {
+        (Some(schema), name) => format!("{}.{}", SqlBuilder::quote_ident(schema), SqlBuilder::quote_ident(name)),
+        (None, name) => SqlBuilder::quote_ident(name),
+    }
+}
+
+/// Current type of a column, as written in DDL (e.g. "character varying(255)")
+pub async fn column_type(pool: &Pool, table: &str, column: &str) -> Result<String, AppError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_opt(
+            "SELECT format_type(a.atttypid, a.atttypmod)
+             FROM pg_attribute a
+             WHERE a.attrelid = to_regclass($1) AND a.attname = $2 AND a.attnum > 0 AND NOT a.attisdropped",
+            &[&quote_table(table), &column],
+        )
+        .await?;
+    row.map(|r| r.get(0))
+        .ok_or_else(|| AppError::NotFound(format!("Column {}.{} not found", table, column)))
+}
+
+/// Run `expression` over up to `sample_size` non-null values of the column.
+/// Everything happens in a read-only transaction that is rolled back.
+pub async fn validate(
+    pool: &Pool,
+    table: &str,
+    column: &str,
+    from_type: &str,
+    to_type: &str,
+    expression: &str,
+    sample_size: usize,
+) -> Result<UsingValidation, AppError> {
+    let mut client = pool.get().await?;
+    let mut tx = client.build_transaction().read_only(true).start().await?;
+    tx.execute("SET LOCAL statement_timeout = '5s'", &[]).await?;
+
+    let c = SqlBuilder::quote_ident(column);
+    let values: Vec<String> = tx
+        .query(
+            &format!(
+                "SELECT {c}::text FROM {} WHERE {c} IS NOT NULL LIMIT $1",
+                quote_table(table),
+                c = c
+            ),
+            &[&(sample_size as i64)],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    // Each value is cast back to the column's type so the expression sees what ALTER would
+    let convert = format!(
+        "SELECT (({}))::{}::text FROM (SELECT $1::text::{} AS {}) AS sample",
+        expression, to_type, from_type, c
+    );
+
+    let mut validation = UsingValidation {
+        sampled: values.len(),
+        failed: 0,
+        failures: Vec::new(),
+    };
+    for value in values {
+        let savepoint = tx.savepoint("using_sample").await?;
+        let result = savepoint.query_one(convert.as_str(), &[&value]).await;
+        savepoint.rollback().await?;
+
+        if let Err(e) = result {
+            validation.failed += 1;
+            if validation.failures.len() < MAX_REPORTED_FAILURES {
+                let error = e.as_db_error().map(|db| db.message().to_string()).unwrap_or_else(|| e.to_string());
+                validation.failures.push(ConversionFailure { value, error });
+            }
+        }
+    }
+
+    tx.rollback().await?;
+    Ok(validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_text_to_jsonb() {
+        let suggestions = suggest("payload", "character varying(255)", "jsonb");
+
+        assert_eq!(suggestions[0].expression, "\"payload\"::jsonb");
+        assert!(suggestions.iter().any(|s| s.expression == "to_jsonb(\"payload\")"));
+        assert!(suggestions.iter().all(|s| !s.lossy));
+
+        // Unrelated types only get the plain cast
+        assert_eq!(suggest("id", "uuid", "inet").len(), 1);
+    }
+}
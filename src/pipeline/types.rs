@@ -1,5 +1,6 @@
 //! Schema types for the governance pipeline
 
+use crate::introspection::{CompositeAttribute, DomainCheck, PiiLevel};
 #[allow(unused_imports)]
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -10,9 +11,26 @@ use uuid::Uuid;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SchemaChange {
+    CreateSchema {
+        schema_name: String,
+        #[serde(default)]
+        owner: Option<String>,
+    },
+    DropSchema {
+        schema_name: String,
+        #[serde(default)]
+        cascade: bool,
+    },
+    RenameSchema {
+        old_name: String,
+        new_name: String,
+    },
     CreateTable {
         table_name: String,
         columns: Vec<ColumnDef>,
+        /// Create a partitioned table; rows live in its partitions
+        #[serde(default)]
+        partition_by: Option<PartitionKey>,
     },
     DropTable {
         table_name: String,
@@ -31,6 +49,9 @@ pub enum SchemaChange {
         new_type: Option<String>,
         new_nullable: Option<bool>,
         new_default: Option<String>,
+        /// USING expression for a type change PostgreSQL cannot cast implicitly
+        #[serde(default)]
+        using: Option<String>,
     },
     RenameTable {
         old_name: String,
@@ -46,6 +67,9 @@ pub enum SchemaChange {
         index_name: String,
         columns: Vec<String>,
         unique: bool,
+        /// Build with CREATE INDEX CONCURRENTLY to avoid blocking writes
+        #[serde(default)]
+        concurrently: bool,
     },
     DropIndex {
         index_name: String,
@@ -65,12 +89,339 @@ pub enum SchemaChange {
         table_name: String,
         constraint_name: String,
         expression: String,
+        /// Skip checking existing rows; validate later with `ValidateConstraint`
+        #[serde(default)]
+        not_valid: bool,
+    },
+    /// Check existing rows against a constraint added NOT VALID
+    ValidateConstraint {
+        table_name: String,
+        constraint_name: String,
+    },
+    DropConstraint {
+        table_name: String,
+        constraint_name: String,
+    },
+    /// Fill a column's NULLs, `batch_size` rows per statement
+    Backfill {
+        table_name: String,
+        column_name: String,
+        /// SQL expression written into the NULL rows
+        value: String,
+        batch_size: u32,
+        /// Statements to generate, from the NULL estimate at planning time
+        batches: u32,
     },
     AddUnique {
         table_name: String,
         constraint_name: String,
         columns: Vec<String>,
     },
+    /// Rebuild an index, or every index on a table
+    Reindex {
+        target: ReindexTarget,
+        name: String,
+        /// REINDEX CONCURRENTLY avoids blocking writes (PostgreSQL 12+)
+        #[serde(default)]
+        concurrently: bool,
+    },
+    Vacuum {
+        table_name: String,
+        /// VACUUM FULL rewrites the table under an exclusive lock
+        #[serde(default)]
+        full: bool,
+        /// Refresh planner statistics in the same pass
+        #[serde(default)]
+        analyze: bool,
+    },
+    Analyze {
+        table_name: String,
+    },
+    CreateExtension {
+        name: String,
+        /// Schema for the extension's objects (the first on the search path if omitted)
+        #[serde(default)]
+        schema: Option<String>,
+        /// Version to install (the extension's default if omitted)
+        #[serde(default)]
+        version: Option<String>,
+    },
+    DropExtension {
+        name: String,
+        /// Also drop the columns and indexes that use the extension
+        #[serde(default)]
+        cascade: bool,
+    },
+    /// Run the extension's update scripts up to `version`
+    AlterExtensionVersion {
+        name: String,
+        version: String,
+    },
+    /// Create a partition of a partitioned table
+    CreatePartition {
+        table_name: String,
+        parent_table: String,
+        bound: PartitionBound,
+    },
+    /// Make an existing table a partition. Its rows are scanned against the
+    /// bound unless a valid CHECK constraint already implies it.
+    AttachPartition {
+        parent_table: String,
+        partition_name: String,
+        bound: PartitionBound,
+    },
+    /// Turn a partition back into a standalone table, keeping its rows
+    DetachPartition {
+        parent_table: String,
+        partition_name: String,
+        /// DETACH ... CONCURRENTLY avoids blocking queries on the parent (PostgreSQL 14+)
+        #[serde(default)]
+        concurrently: bool,
+    },
+    /// Emulate a column reorder by copying into a new table and swapping it
+    /// in. `plan` is filled in from the latest snapshot and the table's size
+    /// when the change is added to a proposal.
+    ReorderColumns {
+        table_name: String,
+        /// Every column of the table, in the order wanted
+        column_order: Vec<String>,
+        #[serde(default)]
+        plan: Option<ReorderPlan>,
+    },
+    /// Add a row-level security policy
+    CreatePolicy {
+        table_name: String,
+        policy_name: String,
+        /// Restrictive policies narrow what the permissive ones allow
+        #[serde(default)]
+        restrictive: bool,
+        /// `ALL` (the default), `SELECT`, `INSERT`, `UPDATE` or `DELETE`
+        #[serde(default)]
+        command: Option<String>,
+        /// Roles the policy applies to (everyone if empty)
+        #[serde(default)]
+        roles: Vec<String>,
+        #[serde(default)]
+        using: Option<String>,
+        #[serde(default)]
+        with_check: Option<String>,
+    },
+    /// Change a policy's roles or expressions; unset fields are kept
+    AlterPolicy {
+        table_name: String,
+        policy_name: String,
+        #[serde(default)]
+        roles: Option<Vec<String>>,
+        #[serde(default)]
+        using: Option<String>,
+        #[serde(default)]
+        with_check: Option<String>,
+    },
+    DropPolicy {
+        table_name: String,
+        policy_name: String,
+    },
+    /// Turn row-level security on or off for a table
+    SetRowSecurity {
+        table_name: String,
+        enabled: bool,
+        /// Also apply policies to the table owner (unchanged if omitted)
+        #[serde(default)]
+        forced: Option<bool>,
+    },
+    /// Create a domain: a base type with constraints that columns can use
+    CreateDomain {
+        domain_name: String,
+        base_type: String,
+        #[serde(default)]
+        not_null: bool,
+        #[serde(default)]
+        default_value: Option<String>,
+        #[serde(default)]
+        checks: Vec<DomainCheck>,
+    },
+    /// Change a domain's default, NOT NULL or checks. New checks and NOT NULL
+    /// are validated against every column using the domain.
+    AlterDomain {
+        domain_name: String,
+        #[serde(default)]
+        set_default: Option<String>,
+        #[serde(default)]
+        drop_default: bool,
+        /// Add (true) or drop (false) NOT NULL; unchanged if omitted
+        #[serde(default)]
+        not_null: Option<bool>,
+        #[serde(default)]
+        add_checks: Vec<DomainCheck>,
+        /// Names of checks to drop
+        #[serde(default)]
+        drop_checks: Vec<String>,
+    },
+    DropDomain {
+        domain_name: String,
+        /// Also drop the columns that use the domain
+        #[serde(default)]
+        cascade: bool,
+    },
+    /// Create a composite type (`CREATE TYPE ... AS (...)`)
+    CreateCompositeType {
+        type_name: String,
+        attributes: Vec<CompositeAttribute>,
+    },
+    /// Add, drop or retype attributes of a composite type
+    AlterCompositeType {
+        type_name: String,
+        #[serde(default)]
+        add_attributes: Vec<CompositeAttribute>,
+        #[serde(default)]
+        drop_attributes: Vec<String>,
+        /// Attributes to change to the given type
+        #[serde(default)]
+        alter_attributes: Vec<CompositeAttribute>,
+    },
+    DropCompositeType {
+        type_name: String,
+        /// Also drop the columns that use the type
+        #[serde(default)]
+        cascade: bool,
+    },
+    CreateView {
+        view_name: String,
+        /// The SELECT the view returns
+        query: String,
+    },
+    /// Redefine a view in place. PostgreSQL only allows new columns at the
+    /// end; renaming, dropping or retyping columns needs a drop and create.
+    AlterView {
+        view_name: String,
+        query: String,
+    },
+    DropView {
+        view_name: String,
+        /// Also drop the views that read it
+        #[serde(default)]
+        cascade: bool,
+    },
+    /// Create a materialized view, populated by running its query now
+    /// unless `with_no_data`
+    CreateMaterializedView {
+        view_name: String,
+        query: String,
+        #[serde(default)]
+        with_no_data: bool,
+    },
+    DropMaterializedView {
+        view_name: String,
+        /// Also drop the views that read it
+        #[serde(default)]
+        cascade: bool,
+    },
+    /// Re-run a materialized view's query. CONCURRENTLY keeps it readable
+    /// but needs a unique index on it.
+    RefreshMaterializedView {
+        view_name: String,
+        #[serde(default)]
+        concurrently: bool,
+    },
+    /// Governance-only: set (or clear, without a value) a data dictionary
+    /// custom field on a table, or on one of its columns. Runs no SQL.
+    SetCustomField {
+        table_name: String,
+        #[serde(default)]
+        column_name: Option<String>,
+        field: String,
+        #[serde(default)]
+        value: Option<String>,
+    },
+    /// Governance-only: attach a tag from the project's taxonomy to a table,
+    /// or to one of its columns. Runs no SQL.
+    AddTag {
+        table_name: String,
+        #[serde(default)]
+        column_name: Option<String>,
+        tag: String,
+    },
+    /// Governance-only: detach a tag attached with `add_tag`
+    RemoveTag {
+        table_name: String,
+        #[serde(default)]
+        column_name: Option<String>,
+        tag: String,
+    },
+    /// Governance-only: classify a column (of a table or a view) in the
+    /// data dictionary, or clear that classification without a level. Where
+    /// its comment carries a `[pii:<level>]` tag too, the stronger level
+    /// applies. Runs no SQL.
+    SetClassification {
+        table_name: String,
+        column_name: String,
+        #[serde(default)]
+        level: Option<PiiLevel>,
+    },
+}
+
+/// What a REINDEX rebuilds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReindexTarget {
+    Index,
+    Table,
+}
+
+/// How a partitioned table splits its rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionStrategy {
+    Range,
+    List,
+    Hash,
+}
+
+/// PARTITION BY clause of a partitioned table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionKey {
+    pub strategy: PartitionStrategy,
+    pub columns: Vec<String>,
+}
+
+impl PartitionKey {
+    pub fn to_sql(&self) -> String {
+        let strategy = match self.strategy {
+            PartitionStrategy::Range => "RANGE",
+            PartitionStrategy::List => "LIST",
+            PartitionStrategy::Hash => "HASH",
+        };
+        format!("PARTITION BY {} ({})", strategy, self.columns.join(", "))
+    }
+}
+
+/// Rows a partition holds. Values are SQL expressions, e.g. `'2024-01-01'` or `MINVALUE`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PartitionBound {
+    /// From `from` (inclusive) up to `to` (exclusive)
+    Range { from: Vec<String>, to: Vec<String> },
+    List { values: Vec<String> },
+    Hash { modulus: u32, remainder: u32 },
+    /// Rows no other partition accepts
+    Default,
+}
+
+impl PartitionBound {
+    /// Bound clause following the partition name
+    pub fn to_sql(&self) -> String {
+        match self {
+            PartitionBound::Range { from, to } => {
+                format!("FOR VALUES FROM ({}) TO ({})", from.join(", "), to.join(", "))
+            }
+            PartitionBound::List { values } => format!("FOR VALUES IN ({})", values.join(", ")),
+            PartitionBound::Hash { modulus, remainder } => {
+                format!("FOR VALUES WITH (MODULUS {}, REMAINDER {})", modulus, remainder)
+            }
+            PartitionBound::Default => "DEFAULT".to_string(),
+        }
+    }
 }
 
 /// Column definition
@@ -82,6 +433,51 @@ pub struct ColumnDef {
     pub nullable: bool,
     pub default_value: Option<String>,
     pub is_primary_key: bool,
+    /// Explicit collation (defaults to the database collation when omitted)
+    #[serde(default)]
+    pub collation: Option<String>,
+}
+
+/// Everything a column reorder recreates on the new table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderPlan {
+    /// Column definitions in the new order
+    pub columns: Vec<ColumnDef>,
+    pub primary_key_name: String,
+    pub key_columns: Vec<String>,
+    /// Indexes other than the primary key
+    #[serde(default)]
+    pub indexes: Vec<IndexCopy>,
+    /// Foreign keys from and to the table
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKeyCopy>,
+    pub batch_size: u32,
+    pub batches: u32,
+    pub estimated_rows: i64,
+    pub total_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexCopy {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// Access method, e.g. btree or gin
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyCopy {
+    pub constraint_name: String,
+    pub table_name: String,
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    pub on_update: String,
+    pub on_delete: String,
 }
 
 /// Comment target for proposal comments
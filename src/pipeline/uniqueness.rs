@@ -0,0 +1,287 @@
+//! Duplicate pre-check for unique constraints and indexes
+//!
+//! Adding a unique constraint or building a unique index fails if the table
+//! already holds duplicate keys, often after a long scan under a lock. Before
+//! a proposal is analyzed, approved or executed, each key that would have to
+//! be unique is grouped on the live table (rows with a NULL in the key never
+//! conflict and are left out) and the number of duplicated keys is attached
+//! to the risk analysis together with a few sample keys.
+//!
+//! The query runs in a read-only transaction with a statement timeout; a
+//! table too large to group in time is reported as unchecked rather than
+//! holding up the request.
+
+use crate::db::queries::SqlBuilder;
+use crate::error::AppError;
+use crate::i18n::Message;
+use crate::pipeline::proposal::{RiskAnalysis, SchemaProposal};
+use crate::pipeline::risk::level_for;
+use crate::pipeline::scratch::db_message;
+use crate::pipeline::type_migration::quote_table;
+use crate::pipeline::types::SchemaChange;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+
+/// Longest a duplicate check may run
+const CHECK_TIMEOUT_MS: u32 = 5_000;
+
+/// Duplicated keys kept as samples
+pub const SAMPLE_KEYS: i64 = 5;
+
+/// Score added for a constraint that cannot succeed as things stand
+const DUPLICATES_SCORE: u32 = 50;
+
+/// Score added for a constraint that could not be checked in time
+const UNCHECKED_SCORE: u32 = 10;
+
+/// What the duplicate check found for one unique constraint or index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCheck {
+    pub table: String,
+    /// Constraint or index name
+    pub name: String,
+    pub columns: Vec<String>,
+    /// Key values that occur more than once
+    pub duplicate_keys: i64,
+    /// Rows holding one of those keys
+    pub duplicate_rows: i64,
+    /// The most repeated keys, column values as text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sample_keys: Vec<Vec<Option<String>>>,
+    /// Set when the check gave up at the timeout
+    #[serde(default)]
+    pub timed_out: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl DuplicateCheck {
+    fn key(&self) -> String {
+        format!("{} on {}({})", self.name, self.table, self.columns.join(", "))
+    }
+}
+
+/// Unique keys the changes add to tables that already exist: table,
+/// constraint or index name, columns
+pub fn unique_targets(changes: &[SchemaChange]) -> Vec<(&str, &str, &[String])> {
+    // New tables and columns hold no rows or only the default yet
+    let created = |table: &str, columns: &[String]| {
+        changes.iter().any(|change| match change {
+            SchemaChange::CreateTable { table_name, .. } => table_name == table,
+            SchemaChange::AddColumn { table_name, column } => table_name == table && columns.contains(&column.name),
+            _ => false,
+        })
+    };
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            SchemaChange::AddUnique { table_name, constraint_name, columns } => {
+                Some((table_name.as_str(), constraint_name.as_str(), columns.as_slice()))
+            }
+            SchemaChange::AddIndex { table_name, index_name, columns, unique: true, .. } => {
+                Some((table_name.as_str(), index_name.as_str(), columns.as_slice()))
+            }
+            _ => None,
+        })
+        .filter(|(table, _, columns)| !columns.is_empty() && !created(table, columns))
+        .collect()
+}
+
+/// Look for duplicate keys behind each unique constraint or index the
+/// proposal adds
+pub async fn check(pool: &Pool, proposal: &SchemaProposal) -> Result<Vec<DuplicateCheck>, AppError> {
+    let targets = unique_targets(&proposal.changes);
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut client = pool.get().await?;
+    let mut checks = Vec::with_capacity(targets.len());
+    for (table, name, columns) in targets {
+        let quoted: Vec<String> = columns.iter().map(|c| SqlBuilder::quote_ident(c)).collect();
+        let keys = quoted.iter().map(|c| format!("{}::text", c)).collect::<Vec<_>>().join(", ");
+        let not_null = quoted.iter().map(|c| format!("{} IS NOT NULL", c)).collect::<Vec<_>>().join(" AND ");
+        // The window totals cover every duplicated key, not just the samples
+        let query = format!(
+            "SELECT ARRAY[{keys}], count(*) OVER (), sum(count(*)) OVER ()::bigint
+             FROM {table} WHERE {not_null}
+             GROUP BY {group}
+             HAVING count(*) > 1
+             ORDER BY count(*) DESC
+             LIMIT {limit}",
+            keys = keys,
+            table = quote_table(table),
+            not_null = not_null,
+            group = quoted.join(", "),
+            limit = SAMPLE_KEYS
+        );
+
+        let mut result = DuplicateCheck {
+            table: table.to_string(),
+            name: name.to_string(),
+            columns: columns.to_vec(),
+            duplicate_keys: 0,
+            duplicate_rows: 0,
+            sample_keys: Vec::new(),
+            timed_out: false,
+            checked_at: Utc::now(),
+        };
+
+        // Settings are local to the transaction, so the pooled connection comes back unchanged
+        let tx = client.build_transaction().read_only(true).start().await?;
+        tx.batch_execute(&format!("SET LOCAL statement_timeout = {}", CHECK_TIMEOUT_MS)).await?;
+        match tx.query(&query, &[]).await {
+            Ok(rows) => {
+                if let Some(first) = rows.first() {
+                    result.duplicate_keys = first.get(1);
+                    result.duplicate_rows = first.get(2);
+                }
+                result.sample_keys = rows.iter().map(|row| row.get(0)).collect();
+            }
+            Err(e) if e.code() == Some(&tokio_postgres::error::SqlState::QUERY_CANCELED) => result.timed_out = true,
+            Err(e) => {
+                return Err(AppError::BadRequest(format!(
+                    "Could not check {} for duplicates: {}",
+                    result.key(),
+                    db_message(&e)
+                )))
+            }
+        }
+        let _ = tx.rollback().await;
+        checks.push(result);
+    }
+    Ok(checks)
+}
+
+/// Attach checks to an analysis, raising its score for constraints that
+/// cannot succeed or could not be checked. Replaces earlier checks.
+pub fn attach(analysis: &mut RiskAnalysis, checks: Vec<DuplicateCheck>) {
+    // Drop what earlier checks contributed before adding the new findings
+    let previous = std::mem::take(&mut analysis.duplicate_checks);
+    for check in &previous {
+        analysis.score = analysis.score.saturating_sub(check_score(check));
+    }
+    // Analyses stored before warnings had codes cannot be told apart; keep them whole
+    if analysis.warnings.len() == analysis.warning_codes.len() {
+        let (warnings, codes) = analysis
+            .warnings
+            .drain(..)
+            .zip(analysis.warning_codes.drain(..))
+            .filter(|(_, code)| !code.code.starts_with("risk.unique_"))
+            .unzip();
+        analysis.warnings = warnings;
+        analysis.warning_codes = codes;
+    }
+
+    for check in &checks {
+        analysis.score += check_score(check);
+        if check.timed_out {
+            analysis.warning_codes.push(Message::new("risk.unique_unchecked").with("constraint", check.key()));
+            analysis.warnings.push(format!(
+                "Could not check {} for duplicates within {} ms; the constraint may fail",
+                check.key(),
+                CHECK_TIMEOUT_MS
+            ));
+        } else if check.duplicate_keys > 0 {
+            let samples: Vec<String> = check
+                .sample_keys
+                .iter()
+                .map(|key| {
+                    let values: Vec<&str> = key.iter().map(|v| v.as_deref().unwrap_or("NULL")).collect();
+                    format!("({})", values.join(", "))
+                })
+                .collect();
+            analysis.warning_codes.push(
+                Message::new("risk.unique_duplicates")
+                    .with("constraint", check.key())
+                    .with("keys", check.duplicate_keys)
+                    .with("samples", samples.join(", ")),
+            );
+            analysis.warnings.push(format!(
+                "{} cannot be created: {} key(s) occur more than once across {} rows, e.g. {}",
+                check.key(),
+                check.duplicate_keys,
+                check.duplicate_rows,
+                samples.join(", ")
+            ));
+        }
+    }
+    analysis.overall_risk = level_for(analysis.score);
+    analysis.duplicate_checks = checks;
+}
+
+fn check_score(check: &DuplicateCheck) -> u32 {
+    match (check.timed_out, check.duplicate_keys > 0) {
+        (true, _) => UNCHECKED_SCORE,
+        (false, true) => DUPLICATES_SCORE,
+        (false, false) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::proposal::RiskLevel;
+    use crate::pipeline::types::ColumnDef;
+
+    #[test]
+    fn test_targets_and_attached_findings() {
+        let unique_email = SchemaChange::AddUnique {
+            table_name: "users".to_string(),
+            constraint_name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+        };
+        let unique_on_new_column = SchemaChange::AddIndex {
+            table_name: "users".to_string(),
+            index_name: "users_handle_idx".to_string(),
+            columns: vec!["handle".to_string()],
+            unique: true,
+            concurrently: true,
+        };
+        let new_column = ColumnDef {
+            name: "handle".to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            collation: None,
+        };
+        let changes = vec![
+            unique_email,
+            SchemaChange::AddColumn { table_name: "users".to_string(), column: new_column },
+            unique_on_new_column,
+        ];
+        let targets = unique_targets(&changes);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].1, "users_email_key");
+
+        let mut proposal = SchemaProposal::new(uuid::Uuid::new_v4(), "t".to_string(), String::new(), "1".to_string());
+        proposal.changes = changes;
+        let mut analysis = crate::pipeline::risk::RiskEngine::new().analyze(&proposal, None).unwrap();
+        let base = analysis.score;
+
+        let duplicates = DuplicateCheck {
+            table: "users".to_string(),
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+            duplicate_keys: 2,
+            duplicate_rows: 5,
+            sample_keys: vec![vec![Some("a@example.com".to_string())], vec![Some("b@example.com".to_string())]],
+            timed_out: false,
+            checked_at: Utc::now(),
+        };
+        attach(&mut analysis, vec![duplicates.clone()]);
+        assert_eq!(analysis.score, base + DUPLICATES_SCORE);
+        assert_ne!(analysis.overall_risk, RiskLevel::Low);
+        assert!(analysis.warnings.last().unwrap().contains("(a@example.com), (b@example.com)"));
+        assert_eq!(analysis.warnings.len(), analysis.warning_codes.len());
+
+        // A later check replaces the earlier findings
+        let clean = DuplicateCheck { duplicate_keys: 0, duplicate_rows: 0, sample_keys: Vec::new(), ..duplicates };
+        attach(&mut analysis, vec![clean]);
+        assert_eq!(analysis.score, base);
+        assert!(analysis.warnings.iter().all(|w| !w.contains("cannot be created")));
+        assert_eq!(analysis.duplicate_checks[0].duplicate_keys, 0);
+    }
+}
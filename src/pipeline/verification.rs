@@ -0,0 +1,589 @@
+//! Post-execution verification
+//!
+//! Compares the schema introspected after an execution with what the
+//! proposal's changes should have produced. The expected state is built by
+//! replaying the changes in order, so a column added and then renamed is
+//! expected under its new name. A mismatch means something besides the
+//! migration shaped the result (a trigger, a concurrent change), and the
+//! proposal is flagged for an admin.
+
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::risk::split_table_name;
+use crate::pipeline::types::{ColumnDef, SchemaChange};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Outcome of comparing the applied schema with the expected one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    pub passed: bool,
+    /// Snapshot the proposal was verified against
+    pub snapshot_id: Uuid,
+    pub mismatches: Vec<String>,
+    pub verified_at: DateTime<Utc>,
+}
+
+/// What a column should look like; `None` fields are not checked
+#[derive(Debug, Clone, Default)]
+struct ColumnExpectation {
+    data_type: Option<String>,
+    nullable: Option<bool>,
+}
+
+impl From<&ColumnDef> for ColumnExpectation {
+    fn from(column: &ColumnDef) -> Self {
+        Self {
+            data_type: Some(column.data_type.clone()),
+            nullable: Some(column.nullable && !column.is_primary_key),
+        }
+    }
+}
+
+type TableKey = (String, String);
+
+/// Expected post-change state; `false`/`None` means the object must be absent
+#[derive(Debug, Default)]
+struct Expectations {
+    schemas: BTreeMap<String, bool>,
+    tables: BTreeMap<TableKey, bool>,
+    columns: BTreeMap<(String, String, String), Option<ColumnExpectation>>,
+    /// (schema, index name) -> must be unique
+    indexes: BTreeMap<TableKey, Option<bool>>,
+    /// (schema, table, constraint)
+    foreign_keys: BTreeMap<(String, String, String), bool>,
+    /// Extension name -> installed version, if one was named
+    extensions: BTreeMap<String, Option<Option<String>>>,
+    /// (schema, table, policy)
+    policies: BTreeMap<(String, String, String), bool>,
+    /// Table -> row-level security enabled
+    row_security: BTreeMap<TableKey, bool>,
+    /// (schema, domain)
+    domains: BTreeMap<TableKey, bool>,
+    /// (schema, type)
+    composites: BTreeMap<TableKey, bool>,
+    /// (schema, view) -> whether it is materialized, if it must exist
+    views: BTreeMap<TableKey, Option<bool>>,
+}
+
+impl Expectations {
+    fn from_changes(changes: &[SchemaChange]) -> Self {
+        let mut expected = Self::default();
+        for change in changes {
+            expected.apply(change);
+        }
+        expected
+    }
+
+    fn apply(&mut self, change: &SchemaChange) {
+        match change {
+            SchemaChange::CreateSchema { schema_name, .. } => {
+                self.schemas.insert(ident(schema_name), true);
+            }
+            SchemaChange::DropSchema { schema_name, .. } => {
+                self.schemas.insert(ident(schema_name), false);
+            }
+            SchemaChange::RenameSchema { old_name, new_name } => {
+                self.schemas.insert(ident(old_name), false);
+                self.schemas.insert(ident(new_name), true);
+            }
+            SchemaChange::CreateTable { table_name, columns, .. } => {
+                let (schema, table) = table_key(table_name);
+                for column in columns {
+                    self.columns.insert(
+                        (schema.clone(), table.clone(), ident(&column.name)),
+                        Some(ColumnExpectation::from(column)),
+                    );
+                }
+                self.tables.insert((schema, table), true);
+            }
+            SchemaChange::DropTable { table_name } => {
+                let (schema, table) = table_key(table_name);
+                self.columns.retain(|(s, t, _), _| !(*s == schema && *t == table));
+                self.tables.insert((schema, table), false);
+            }
+            SchemaChange::AddColumn { table_name, column } => {
+                let (schema, table) = table_key(table_name);
+                self.columns
+                    .insert((schema, table, ident(&column.name)), Some(ColumnExpectation::from(column)));
+            }
+            SchemaChange::DropColumn { table_name, column_name } => {
+                let (schema, table) = table_key(table_name);
+                self.columns.insert((schema, table, ident(column_name)), None);
+            }
+            SchemaChange::AlterColumn { table_name, column_name, new_type, new_nullable, .. } => {
+                let (schema, table) = table_key(table_name);
+                let expectation = self
+                    .columns
+                    .entry((schema, table, ident(column_name)))
+                    .or_insert(None)
+                    .get_or_insert_with(ColumnExpectation::default);
+                if new_type.is_some() {
+                    expectation.data_type = new_type.clone();
+                }
+                if new_nullable.is_some() {
+                    expectation.nullable = *new_nullable;
+                }
+            }
+            SchemaChange::RenameTable { old_name, new_name } => {
+                // RENAME TO keeps the table in its schema
+                let (schema, old_table) = table_key(old_name);
+                let new_table = ident(split_table_name(new_name).1);
+
+                let moved: Vec<_> = self
+                    .columns
+                    .iter()
+                    .filter(|((s, t, _), _)| *s == schema && *t == old_table)
+                    .map(|((_, _, c), e)| (c.clone(), e.clone()))
+                    .collect();
+                self.columns.retain(|(s, t, _), _| !(*s == schema && *t == old_table));
+                for (column, expectation) in moved {
+                    self.columns.insert((schema.clone(), new_table.clone(), column), expectation);
+                }
+
+                self.tables.insert((schema.clone(), old_table), false);
+                self.tables.insert((schema, new_table), true);
+            }
+            SchemaChange::RenameColumn { table_name, old_name, new_name } => {
+                let (schema, table) = table_key(table_name);
+                let previous = self
+                    .columns
+                    .insert((schema.clone(), table.clone(), ident(old_name)), None)
+                    .flatten()
+                    .unwrap_or_default();
+                self.columns.insert((schema, table, ident(new_name)), Some(previous));
+            }
+            SchemaChange::AddIndex { table_name, index_name, unique, .. } => {
+                let (schema, _) = table_key(table_name);
+                self.indexes.insert((schema, ident(index_name)), Some(*unique));
+            }
+            SchemaChange::DropIndex { index_name } => {
+                self.indexes.insert(table_key(index_name), None);
+            }
+            SchemaChange::AddUnique { table_name, constraint_name, .. } => {
+                let (schema, _) = table_key(table_name);
+                self.indexes.insert((schema, ident(constraint_name)), Some(true));
+            }
+            SchemaChange::AddForeignKey { table_name, constraint_name, .. } => {
+                let (schema, table) = table_key(table_name);
+                self.foreign_keys.insert((schema, table, ident(constraint_name)), true);
+            }
+            SchemaChange::DropForeignKey { table_name, constraint_name } => {
+                let (schema, table) = table_key(table_name);
+                self.foreign_keys.insert((schema, table, ident(constraint_name)), false);
+            }
+            SchemaChange::CreateExtension { name, version, .. } => {
+                self.extensions.insert(name.clone(), Some(version.clone()));
+            }
+            SchemaChange::DropExtension { name, .. } => {
+                self.extensions.insert(name.clone(), None);
+            }
+            SchemaChange::AlterExtensionVersion { name, version } => {
+                self.extensions.insert(name.clone(), Some(Some(version.clone())));
+            }
+            SchemaChange::CreatePartition { table_name, .. } => {
+                self.tables.insert(table_key(table_name), true);
+            }
+            SchemaChange::CreatePolicy { table_name, policy_name, .. } => {
+                let (schema, table) = table_key(table_name);
+                self.policies.insert((schema, table, ident(policy_name)), true);
+            }
+            SchemaChange::DropPolicy { table_name, policy_name } => {
+                let (schema, table) = table_key(table_name);
+                self.policies.insert((schema, table, ident(policy_name)), false);
+            }
+            SchemaChange::SetRowSecurity { table_name, enabled, .. } => {
+                self.row_security.insert(table_key(table_name), *enabled);
+            }
+            SchemaChange::CreateDomain { domain_name, .. } => {
+                self.domains.insert(table_key(domain_name), true);
+            }
+            SchemaChange::DropDomain { domain_name, .. } => {
+                self.domains.insert(table_key(domain_name), false);
+            }
+            SchemaChange::CreateCompositeType { type_name, .. } => {
+                self.composites.insert(table_key(type_name), true);
+            }
+            SchemaChange::DropCompositeType { type_name, .. } => {
+                self.composites.insert(table_key(type_name), false);
+            }
+            SchemaChange::CreateView { view_name, .. } => {
+                self.views.insert(table_key(view_name), Some(false));
+            }
+            SchemaChange::CreateMaterializedView { view_name, .. } => {
+                self.views.insert(table_key(view_name), Some(true));
+            }
+            SchemaChange::DropView { view_name, .. } | SchemaChange::DropMaterializedView { view_name, .. } => {
+                self.views.insert(table_key(view_name), None);
+            }
+            // Check constraints and partition bounds are not introspected and
+            // column order is not compared; data, maintenance and governance
+            // leave the schema alone; policy expressions are compared as
+            // PostgreSQL deparses them, so altered policies and domains are not
+            // checked
+            SchemaChange::AttachPartition { .. }
+            | SchemaChange::AlterPolicy { .. }
+            | SchemaChange::AlterDomain { .. }
+            | SchemaChange::AlterCompositeType { .. }
+            | SchemaChange::AlterView { .. }
+            | SchemaChange::RefreshMaterializedView { .. }
+            | SchemaChange::DetachPartition { .. }
+            | SchemaChange::ReorderColumns { .. }
+            | SchemaChange::AddCheck { .. }
+            | SchemaChange::ValidateConstraint { .. }
+            | SchemaChange::DropConstraint { .. }
+            | SchemaChange::Backfill { .. }
+            | SchemaChange::Reindex { .. }
+            | SchemaChange::Vacuum { .. }
+            | SchemaChange::Analyze { .. }
+            | SchemaChange::SetCustomField { .. }
+            | SchemaChange::AddTag { .. }
+            | SchemaChange::RemoveTag { .. }
+            | SchemaChange::SetClassification { .. } => {}
+        }
+    }
+}
+
+/// Check the executed proposal's changes against the introspected schema.
+/// Objects outside the connection's schema scope are not checked.
+pub fn verify(proposal: &SchemaProposal, after: &SchemaSnapshot, scope: &[String]) -> VerificationReport {
+    let expected = Expectations::from_changes(&proposal.changes);
+    let in_scope = |schema: &str| scope.is_empty() || scope.iter().any(|s| s == schema);
+    let mut mismatches = Vec::new();
+
+    for (schema, present) in &expected.schemas {
+        if !in_scope(schema) {
+            continue;
+        }
+        let found = after.schemas.iter().any(|n| &n.name == schema);
+        if found != *present {
+            mismatches.push(presence_mismatch("Schema", schema, *present));
+        }
+    }
+
+    for ((schema, table), present) in &expected.tables {
+        if !in_scope(schema) {
+            continue;
+        }
+        let found = after.tables.iter().any(|t| &t.schema == schema && &t.name == table);
+        if found != *present {
+            mismatches.push(presence_mismatch("Table", &format!("{}.{}", schema, table), *present));
+        }
+    }
+
+    for ((schema, table, column), expectation) in &expected.columns {
+        if !in_scope(schema) || expected.tables.get(&(schema.clone(), table.clone())) == Some(&false) {
+            continue;
+        }
+        let path = format!("{}.{}.{}", schema, table, column);
+        let found = after
+            .tables
+            .iter()
+            .find(|t| &t.schema == schema && &t.name == table)
+            .and_then(|t| t.columns.iter().find(|c| &c.name == column));
+
+        match (expectation, found) {
+            (None, Some(_)) => mismatches.push(presence_mismatch("Column", &path, false)),
+            (Some(_), None) => mismatches.push(presence_mismatch("Column", &path, true)),
+            (Some(expectation), Some(actual)) => {
+                if let Some(nullable) = expectation.nullable.filter(|n| *n != actual.nullable) {
+                    mismatches.push(format!(
+                        "Column {} should be {} but is {}",
+                        path,
+                        nullability(nullable),
+                        nullability(actual.nullable)
+                    ));
+                }
+                if let Some(data_type) = &expectation.data_type {
+                    let actual_type = normalize_type(&actual.data_type);
+                    let expected_type = normalize_type(data_type);
+                    // Domains, enums and composites are reported schema-qualified (as
+                    // USER-DEFINED in older snapshots); an unqualified type may be any schema's
+                    let same_user_type = !expected_type.contains('.')
+                        && actual_type.rsplit_once('.').is_some_and(|(_, name)| ident(name) == ident(&expected_type));
+                    if actual_type != "user-defined" && expected_type != actual_type && !same_user_type {
+                        mismatches.push(format!(
+                            "Column {} has type {}, expected {}",
+                            path, actual.data_type, data_type
+                        ));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    for ((schema, index), expectation) in &expected.indexes {
+        if !in_scope(schema) {
+            continue;
+        }
+        let path = format!("{}.{}", schema, index);
+        let found = after.indexes.iter().find(|i| &i.schema == schema && &i.name == index);
+
+        match (expectation, found) {
+            (None, Some(_)) => mismatches.push(presence_mismatch("Index", &path, false)),
+            (Some(_), None) => mismatches.push(presence_mismatch("Index", &path, true)),
+            (Some(true), Some(actual)) if !actual.is_unique => {
+                mismatches.push(format!("Index {} should be unique but is not", path));
+            }
+            _ => {}
+        }
+    }
+
+    for ((schema, table, constraint), present) in &expected.foreign_keys {
+        if !in_scope(schema) {
+            continue;
+        }
+        let found = after.foreign_keys.iter().any(|fk| {
+            &fk.source_schema == schema && &fk.source_table == table && &fk.constraint_name == constraint
+        });
+        if found != *present {
+            mismatches.push(presence_mismatch("Foreign key", &format!("{}.{}", table, constraint), *present));
+        }
+    }
+
+    // Snapshots taken before extensions were recorded have none at all
+    if !after.extensions.is_empty() {
+        for (name, expectation) in &expected.extensions {
+            let found = after.extensions.iter().find(|e| &e.name == name);
+            match (expectation, found) {
+                (None, Some(_)) => mismatches.push(presence_mismatch("Extension", name, false)),
+                (Some(_), None) => mismatches.push(presence_mismatch("Extension", name, true)),
+                (Some(Some(version)), Some(actual)) if &actual.version != version => {
+                    mismatches.push(format!(
+                        "Extension {} is at version {}, expected {}",
+                        name, actual.version, version
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Snapshots taken before types were recorded have no domains or composites to check
+    if let Some(types) = &after.types {
+        let found = |kind: &str, schema: &str, name: &str| match kind {
+            "Domain" => types.domains.iter().any(|d| d.schema == schema && d.name == name),
+            _ => types.composites.iter().any(|c| c.schema == schema && c.name == name),
+        };
+        let expected_types = expected
+            .domains
+            .iter()
+            .map(|(key, present)| ("Domain", key, present))
+            .chain(expected.composites.iter().map(|(key, present)| ("Type", key, present)));
+        for (kind, (schema, name), present) in expected_types {
+            if in_scope(schema) && found(kind, schema, name) != *present {
+                mismatches.push(presence_mismatch(kind, &format!("{}.{}", schema, name), *present));
+            }
+        }
+    }
+
+    // Snapshots taken before views were recorded have none to check
+    if let Some(views) = &after.views {
+        for ((schema, name), materialized) in &expected.views {
+            if !in_scope(schema) {
+                continue;
+            }
+            let found = views.iter().find(|v| &v.schema == schema && &v.name == name);
+            let path = format!("{}.{}", schema, name);
+            match (found, materialized) {
+                (None, Some(materialized)) => {
+                    let kind = if *materialized { "Materialized view" } else { "View" };
+                    mismatches.push(presence_mismatch(kind, &path, true));
+                }
+                (Some(view), None) => mismatches.push(presence_mismatch(view.kind(), &path, false)),
+                (Some(view), Some(materialized)) if view.materialized != *materialized => {
+                    mismatches.push(format!("{} {} exists, expected {}", view.kind(), path, if *materialized { "a materialized view" } else { "a view" }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Tables from snapshots taken before row-level security was recorded are skipped
+    let row_security = |schema: &str, table: &str| {
+        after
+            .tables
+            .iter()
+            .find(|t| t.schema == schema && t.name == table)
+            .and_then(|t| t.governance.row_security.as_ref())
+    };
+    for ((schema, table, policy), present) in &expected.policies {
+        if !in_scope(schema) {
+            continue;
+        }
+        if let Some(rls) = row_security(schema, table) {
+            let found = rls.policies.iter().any(|p| &p.name == policy);
+            if found != *present {
+                mismatches.push(presence_mismatch("Policy", &format!("{}.{}.{}", schema, table, policy), *present));
+            }
+        }
+    }
+    for ((schema, table), enabled) in &expected.row_security {
+        if !in_scope(schema) {
+            continue;
+        }
+        if let Some(rls) = row_security(schema, table).filter(|rls| rls.enabled != *enabled) {
+            mismatches.push(format!(
+                "Row-level security on {}.{} should be {} but is {}",
+                schema,
+                table,
+                if *enabled { "enabled" } else { "disabled" },
+                if rls.enabled { "enabled" } else { "disabled" }
+            ));
+        }
+    }
+
+    VerificationReport {
+        passed: mismatches.is_empty(),
+        snapshot_id: after.id,
+        mismatches,
+        verified_at: Utc::now(),
+    }
+}
+
+fn presence_mismatch(kind: &str, path: &str, expected_present: bool) -> String {
+    if expected_present {
+        format!("{} {} should exist but was not found", kind, path)
+    } else {
+        format!("{} {} should have been removed but still exists", kind, path)
+    }
+}
+
+fn nullability(nullable: bool) -> &'static str {
+    if nullable { "nullable" } else { "NOT NULL" }
+}
+
+/// Unquoted identifiers are folded to lower case, as PostgreSQL does
+fn ident(name: &str) -> String {
+    let name = name.trim();
+    match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        Some(quoted) => quoted.to_string(),
+        None => name.to_lowercase(),
+    }
+}
+
+/// Schema and table of a possibly qualified name; unqualified names are in `public`
+fn table_key(name: &str) -> TableKey {
+    let (schema, table) = split_table_name(name);
+    (ident(schema.unwrap_or("public")), ident(table))
+}
+
+/// Reduce a type to the name information_schema reports, dropping modifiers
+pub(crate) fn normalize_type(data_type: &str) -> String {
+    let lower = data_type.trim().to_lowercase();
+    if lower.ends_with("[]") {
+        return "array".to_string();
+    }
+
+    let mut base = String::new();
+    let mut depth = 0;
+    for ch in lower.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 => base.push(ch),
+            _ => {}
+        }
+    }
+    let base = base.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match base.as_str() {
+        "int" | "int4" | "serial" | "serial4" => "integer",
+        "int8" | "bigserial" | "serial8" => "bigint",
+        "int2" | "smallserial" | "serial2" => "smallint",
+        "varchar" => "character varying",
+        "char" | "bpchar" => "character",
+        "bool" => "boolean",
+        "float8" | "float" => "double precision",
+        "float4" => "real",
+        "decimal" => "numeric",
+        "timestamptz" | "timestamp with time zone" => "timestamp with time zone",
+        "timestamp" | "timestamp without time zone" => "timestamp without time zone",
+        "timetz" | "time with time zone" => "time with time zone",
+        "time" | "time without time zone" => "time without time zone",
+        other => other,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, Table, TableGovernance};
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default_value: None,
+            ordinal_position: 1,
+            is_primary_key: false,
+            is_unique: false,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+        }
+    }
+
+    fn column_def(name: &str, data_type: &str, nullable: bool) -> ColumnDef {
+        ColumnDef {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default_value: None,
+            is_primary_key: false,
+            collation: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_replays_changes_and_reports_mismatches() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".to_string(), String::new(), "dev".to_string());
+        proposal.changes = vec![
+            SchemaChange::AddColumn { table_name: "users".to_string(), column: column_def("Nick", "VARCHAR(40)", true) },
+            SchemaChange::RenameColumn { table_name: "users".to_string(), old_name: "nick".to_string(), new_name: "handle".to_string() },
+            SchemaChange::AddColumn { table_name: "users".to_string(), column: column_def("age", "int", false) },
+            SchemaChange::AddColumn { table_name: "billing.invoices".to_string(), column: column_def("total", "numeric", false) },
+        ];
+
+        let after = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: proposal.connection_id,
+            version: 2,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: vec![Table {
+                name: "users".to_string(),
+                schema: "public".to_string(),
+                // A trigger made `age` nullable and bigint
+                columns: vec![column("handle", "character varying", true), column("age", "bigint", true)],
+                primary_key: None,
+                position: None,
+                color: None,
+                collapsed: false,
+                governance: TableGovernance::default(),
+            }],
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        };
+
+        // billing is outside the scope, so the missing invoices table is not reported
+        let report = verify(&proposal, &after, &["public".to_string()]);
+        assert!(!report.passed);
+        assert_eq!(report.mismatches.len(), 2, "{:?}", report.mismatches);
+        assert!(report.mismatches.iter().any(|m| m.contains("public.users.age should be NOT NULL")));
+        assert!(report.mismatches.iter().any(|m| m.contains("has type bigint, expected int")));
+    }
+}
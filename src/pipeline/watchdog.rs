@@ -0,0 +1,232 @@
+//! Long-running statement watchdog
+//!
+//! Estimates can be wildly wrong: a backfill the planner thought would take
+//! seconds can hold locks on production for an hour. With a watchdog in the
+//! project's execution policy, every statement of a real run gets a time
+//! limit of its estimated duration times `factor`, never below
+//! `min_statement_secs` and never above `max_statement_secs`. The limit is
+//! set as the statement's `statement_timeout`, so the server itself cancels a
+//! statement that overruns; its chunk rolls back like any other failure and
+//! the result names the statement and the limit it broke.
+//!
+//! A statement's estimate is its share of the risk analysis's duration
+//! estimate, weighted by planner cost where the latest dry run explained it.
+//! A `statement_timeout` in the policy's settings still applies when it is
+//! the lower of the two.
+
+use crate::error::AppError;
+use crate::pipeline::explain::CostSummary;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest `statement_timeout` the server accepts, in milliseconds
+const MAX_TIMEOUT_MS: u64 = i32::MAX as u64;
+
+/// Kill policy for statements that run far longer than estimated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchdogPolicy {
+    /// Multiple of the estimate a statement may take
+    pub factor: f64,
+    /// Floor for the limit, so quick statements are not killed over noise
+    pub min_statement_secs: u64,
+    /// Absolute cap, whatever the estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_statement_secs: Option<u64>,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self { factor: 5.0, min_statement_secs: 60, max_statement_secs: None }
+    }
+}
+
+impl WatchdogPolicy {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !self.factor.is_finite() || self.factor < 1.0 {
+            return Err(AppError::Validation("The watchdog factor must be at least 1".to_string()));
+        }
+        if self.min_statement_secs == 0 {
+            return Err(AppError::Validation("The watchdog's minimum limit must be at least 1 second".to_string()));
+        }
+        let ceiling = MAX_TIMEOUT_MS / 1000;
+        if self.min_statement_secs > ceiling || self.max_statement_secs.is_some_and(|max| max > ceiling) {
+            return Err(AppError::Validation(format!(
+                "The watchdog's limits must not exceed {} seconds",
+                ceiling
+            )));
+        }
+        if self.max_statement_secs.is_some_and(|max| max < self.min_statement_secs) {
+            return Err(AppError::Validation(
+                "The watchdog's cap must not be below its minimum limit".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Limit of each statement, from the whole migration's estimate
+    pub fn limits(
+        &self,
+        statement_count: usize,
+        estimated_total_secs: u64,
+        costs: Option<&CostSummary>,
+        settings: &BTreeMap<String, String>,
+    ) -> Vec<StatementLimit> {
+        let policy_timeout = settings.get("statement_timeout").and_then(|v| parse_duration_ms(v)).filter(|ms| *ms > 0);
+        let total_ms = estimated_total_secs.saturating_mul(1000) as f64;
+        estimate_shares(statement_count, costs)
+            .into_iter()
+            .map(|share| {
+                let estimated_ms = (total_ms * share).round() as u64;
+                let scaled = ((estimated_ms as f64) * self.factor).round() as u64;
+                let mut limit = StatementLimit {
+                    estimated_ms,
+                    limit_ms: scaled.max(self.min_statement_secs.saturating_mul(1000)).min(MAX_TIMEOUT_MS),
+                    reason: LimitReason::Factor,
+                };
+                if let Some(cap) = self.max_statement_secs.map(|s| s.saturating_mul(1000)).filter(|cap| *cap < limit.limit_ms) {
+                    limit.limit_ms = cap;
+                    limit.reason = LimitReason::Cap;
+                }
+                if let Some(timeout) = policy_timeout.filter(|t| *t < limit.limit_ms) {
+                    limit.limit_ms = timeout;
+                    limit.reason = LimitReason::PolicyTimeout;
+                }
+                limit
+            })
+            .collect()
+    }
+}
+
+/// What set a statement's limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitReason {
+    /// The estimate times the factor, or the minimum
+    Factor,
+    Cap,
+    /// The policy's own `statement_timeout` was lower
+    PolicyTimeout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementLimit {
+    pub estimated_ms: u64,
+    pub limit_ms: u64,
+    pub reason: LimitReason,
+}
+
+/// The statement the watchdog cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchdogKill {
+    /// Zero-based position in the migration
+    pub statement_index: usize,
+    pub statement: String,
+    #[serde(flatten)]
+    pub limit: StatementLimit,
+}
+
+impl WatchdogKill {
+    pub fn describe(&self) -> String {
+        let why = match self.limit.reason {
+            LimitReason::Factor => format!("{} ms estimated", self.limit.estimated_ms),
+            LimitReason::Cap => "the watchdog's cap".to_string(),
+            LimitReason::PolicyTimeout => "the policy's statement_timeout".to_string(),
+        };
+        format!(
+            "Statement {} ran past its watchdog limit of {} ms ({}) and was cancelled",
+            self.statement_index + 1,
+            self.limit.limit_ms,
+            why
+        )
+    }
+}
+
+/// Each statement's share of the migration's duration: by planner cost where
+/// explained, with unexplained statements counted at the average explained
+/// cost, and evenly without any costs
+fn estimate_shares(statement_count: usize, costs: Option<&CostSummary>) -> Vec<f64> {
+    let explained: BTreeMap<usize, f64> = costs
+        .into_iter()
+        .flat_map(|c| c.statements.iter())
+        .filter_map(|s| s.total_cost.filter(|cost| *cost > 0.0).map(|cost| (s.index, cost)))
+        .collect();
+    let average = match explained.is_empty() {
+        true => 1.0,
+        false => explained.values().sum::<f64>() / explained.len() as f64,
+    };
+    let weights: Vec<f64> = (0..statement_count).map(|i| explained.get(&i).copied().unwrap_or(average)).collect();
+    let total: f64 = weights.iter().sum();
+    weights.into_iter().map(|w| if total > 0.0 { w / total } else { 0.0 }).collect()
+}
+
+/// `SET LOCAL` (or session `SET`) of a statement's limit
+pub fn timeout_statement(limit_ms: u64, local: bool) -> String {
+    format!("{} statement_timeout = {};", if local { "SET LOCAL" } else { "SET" }, limit_ms)
+}
+
+/// A Postgres duration setting in milliseconds; bare numbers are milliseconds
+fn parse_duration_ms(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let scale = match unit.trim() {
+        "" | "ms" => 1,
+        "s" => 1_000,
+        "min" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return None,
+    };
+    number.checked_mul(scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::explain::StatementEstimate;
+
+    #[test]
+    fn test_limits_scale_estimates_and_respect_caps() {
+        let estimate = |index: usize, cost: Option<f64>| StatementEstimate {
+            index,
+            node_type: None,
+            startup_cost: None,
+            total_cost: cost,
+            plan_rows: None,
+            skipped: cost.is_none().then(|| "DDL".to_string()),
+        };
+        let costs = CostSummary::from_estimates(vec![estimate(0, None), estimate(1, Some(300.0)), estimate(2, Some(100.0))]);
+        let policy = WatchdogPolicy { factor: 5.0, min_statement_secs: 10, max_statement_secs: Some(600) };
+        assert!(policy.validate().is_ok());
+
+        // 400 s over weights 200 (the average), 300 and 100
+        let limits = policy.limits(3, 400, Some(&costs), &BTreeMap::new());
+        assert_eq!(limits.iter().map(|l| l.estimated_ms).collect::<Vec<_>>(), vec![133_333, 200_000, 66_667]);
+        assert_eq!(limits[0].limit_ms, 600_000);
+        assert_eq!(limits[0].reason, LimitReason::Cap);
+        assert_eq!(limits[2].limit_ms, 333_335);
+        assert_eq!(limits[2].reason, LimitReason::Factor);
+
+        // The floor keeps quick statements from being killed, the policy's own timeout still wins
+        let settings = BTreeMap::from([("statement_timeout".to_string(), "2min".to_string())]);
+        let limits = policy.limits(2, 1, None, &settings);
+        assert_eq!(limits[0].limit_ms, 10_000);
+        let strict = WatchdogPolicy { min_statement_secs: 300, max_statement_secs: None, ..policy.clone() };
+        let limits = strict.limits(1, 1, None, &settings);
+        assert_eq!((limits[0].limit_ms, limits[0].reason), (120_000, LimitReason::PolicyTimeout));
+
+        assert!(WatchdogPolicy { factor: 0.5, ..policy.clone() }.validate().is_err());
+        assert!(WatchdogPolicy { max_statement_secs: Some(5), ..policy }.validate().is_err());
+        // Limits past what statement_timeout holds are refused, and huge estimates clamp to it
+        assert!(WatchdogPolicy { min_statement_secs: u64::MAX, max_statement_secs: None, ..strict.clone() }.validate().is_err());
+        assert!(WatchdogPolicy { max_statement_secs: Some(u64::MAX), ..strict.clone() }.validate().is_err());
+        let unchecked = WatchdogPolicy { min_statement_secs: u64::MAX, max_statement_secs: Some(u64::MAX), ..strict };
+        let limits = unchecked.limits(1, u64::MAX, None, &BTreeMap::new());
+        assert_eq!(limits[0].limit_ms, MAX_TIMEOUT_MS);
+        assert_eq!(timeout_statement(1500, true), "SET LOCAL statement_timeout = 1500;");
+    }
+}
@@ -0,0 +1,236 @@
+//! Object watch subscriptions
+//!
+//! A user can watch a schema, or a single table in it, on a connection. Any
+//! proposal submitted for review that touches a watched object, drift found
+//! on it and any executed change to it notify its watchers, whichever project
+//! the change belongs to. Users are not notified of their own changes.
+//! Watches are stored per user in `object_watches`.
+
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::notifications::{Audience, Notification};
+use crate::pipeline::access::touched_tables;
+use crate::pipeline::changelog::drift_table;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::diff::ObjectType;
+use crate::snapshot::SchemaDiff;
+use crate::state::SharedState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+use uuid::Uuid;
+
+/// A user's watch on a schema, or one table when `table_name` is set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectWatch {
+    pub id: i32,
+    pub user_id: i32,
+    pub connection_id: Uuid,
+    pub schema_name: String,
+    pub table_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ObjectWatch {
+    /// Whether a change to `object` ("schema" or "schema.table") concerns this watch
+    pub fn covers(&self, object: &str) -> bool {
+        let (schema, table) = match object.split_once('.') {
+            Some((schema, table)) => (schema, Some(table)),
+            None => (object, None),
+        };
+        // A change to a whole schema concerns every table in it
+        schema == self.schema_name
+            && match (&self.table_name, table) {
+                (Some(watched), Some(table)) => watched == table,
+                _ => true,
+            }
+    }
+}
+
+/// What happened to a watched object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchEvent {
+    /// A proposal touching it was submitted for review
+    Proposed,
+    /// Drift was detected on it
+    Drifted,
+    /// A proposal changing it was executed
+    Executed,
+}
+
+/// Schema and optional table of a new watch, trimmed and checked
+pub fn normalize(schema: &str, table: Option<&str>) -> Result<(String, Option<String>), AppError> {
+    let schema = schema.trim();
+    if schema.is_empty() || schema.contains('.') {
+        return Err(AppError::Validation("A schema name without dots is required".to_string()));
+    }
+    let table = table.map(str::trim).filter(|t| !t.is_empty());
+    if table.is_some_and(|t| t.contains('.')) {
+        return Err(AppError::Validation("Give the table name without its schema".to_string()));
+    }
+    Ok((schema.to_string(), table.map(str::to_string)))
+}
+
+/// Objects a proposal's changes touch: the tables, plus schemas created, dropped or renamed
+pub fn proposal_objects(changes: &[SchemaChange], snapshot: &SchemaSnapshot) -> BTreeSet<String> {
+    let mut objects = touched_tables(changes, snapshot);
+    for change in changes {
+        match change {
+            SchemaChange::CreateSchema { schema_name, .. } | SchemaChange::DropSchema { schema_name, .. } => {
+                objects.insert(schema_name.clone());
+            }
+            SchemaChange::RenameSchema { old_name, new_name } => {
+                objects.insert(old_name.clone());
+                objects.insert(new_name.clone());
+            }
+            _ => {}
+        }
+    }
+    objects
+}
+
+/// Objects drift was found on: the tables, plus schemas created or dropped
+pub fn drift_objects(diff: &SchemaDiff) -> BTreeSet<String> {
+    diff.changes
+        .iter()
+        .filter_map(|item| match item.object_type {
+            ObjectType::Schema => Some(item.object_path.clone()),
+            _ => drift_table(item),
+        })
+        .collect()
+}
+
+/// Watchers to notify about changes to `objects`, with the objects each watches; skips `actor`
+pub fn recipients(watches: &[ObjectWatch], objects: &BTreeSet<String>, actor: Option<&str>) -> BTreeMap<i32, Vec<String>> {
+    let mut recipients: BTreeMap<i32, BTreeSet<String>> = BTreeMap::new();
+    for watch in watches {
+        if actor == Some(watch.user_id.to_string().as_str()) {
+            continue;
+        }
+        for object in objects.iter().filter(|o| watch.covers(o)) {
+            recipients.entry(watch.user_id).or_default().insert(object.clone());
+        }
+    }
+    recipients
+        .into_iter()
+        .map(|(user_id, objects)| (user_id, objects.into_iter().collect()))
+        .collect()
+}
+
+/// Notify the watchers of `objects` on a connection. Failures are logged, never
+/// returned, so a watch lookup cannot fail the change that triggered it.
+pub async fn notify(
+    state: &SharedState,
+    connection_id: Uuid,
+    objects: &BTreeSet<String>,
+    event: WatchEvent,
+    proposal: Option<&SchemaProposal>,
+    actor: Option<&str>,
+) {
+    if objects.is_empty() {
+        return;
+    }
+    let watches = match state.user_service.watches_for_connection(connection_id).await {
+        Ok(watches) => watches,
+        Err(e) => {
+            warn!("Could not load watches for connection {}: {}", connection_id, e);
+            return;
+        }
+    };
+    let recipients = recipients(&watches, objects, actor);
+    if recipients.is_empty() {
+        return;
+    }
+
+    let connection_name = state
+        .connections
+        .get_connection(connection_id)
+        .await
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| connection_id.to_string());
+    for (user_id, objects) in recipients {
+        state.notifier.notify(
+            Notification::WatchedObjectChanged {
+                connection_id,
+                connection_name: connection_name.clone(),
+                event,
+                objects,
+                proposal: proposal.cloned(),
+            },
+            Audience::Users(vec![user_id.to_string()]),
+        );
+    }
+}
+
+/// Notify the watchers of the objects a proposal touches
+pub async fn notify_proposal(state: &SharedState, proposal: &SchemaProposal, event: WatchEvent, actor: &str) {
+    let snapshot = match state.latest_scoped_snapshot(proposal.connection_id).await {
+        Ok(snapshot) => snapshot.unwrap_or_else(|| SchemaSnapshot::empty(proposal.connection_id)),
+        Err(e) => {
+            warn!("Could not load the snapshot of connection {}: {}", proposal.connection_id, e);
+            SchemaSnapshot::empty(proposal.connection_id)
+        }
+    };
+    let objects = proposal_objects(&proposal.changes, &snapshot);
+    notify(state, proposal.connection_id, &objects, event, Some(proposal), Some(actor)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::diff::{ChangeType, RiskLevel};
+    use crate::snapshot::SchemaDiffItem;
+
+    fn watch(user_id: i32, schema: &str, table: Option<&str>) -> ObjectWatch {
+        ObjectWatch {
+            id: user_id,
+            user_id,
+            connection_id: Uuid::nil(),
+            schema_name: schema.to_string(),
+            table_name: table.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_watchers_of_proposal_and_drift() {
+        let watches = vec![
+            watch(1, "sales", None),
+            watch(2, "sales", Some("orders")),
+            watch(3, "public", Some("users")),
+        ];
+
+        let changes = vec![SchemaChange::DropTable { table_name: "sales.refunds".to_string() }];
+        let objects = proposal_objects(&changes, &SchemaSnapshot::empty(Uuid::nil()));
+        let notified = recipients(&watches, &objects, None);
+        assert_eq!(notified.keys().copied().collect::<Vec<_>>(), vec![1]);
+        // The author of the change is not notified
+        assert!(recipients(&watches, &objects, Some("1")).is_empty());
+
+        let drop_schema = vec![SchemaChange::DropSchema { schema_name: "sales".to_string(), cascade: true }];
+        let objects = proposal_objects(&drop_schema, &SchemaSnapshot::empty(Uuid::nil()));
+        assert_eq!(recipients(&watches, &objects, None).len(), 2);
+
+        let removed = SchemaDiffItem {
+            change_type: ChangeType::Removed,
+            object_type: ObjectType::Column,
+            object_path: "public.users.email".to_string(),
+            description: "Column public.users.email removed".to_string(),
+            before: None,
+            after: None,
+            risk_level: RiskLevel::High,
+            is_breaking: true,
+        };
+        let diff = SchemaDiff::from_changes(1, 2, String::new(), String::new(), vec![removed]);
+        let notified = recipients(&watches, &drift_objects(&diff), None);
+        assert_eq!(notified.get(&3), Some(&vec!["public.users".to_string()]));
+        assert_eq!(notified.len(), 1);
+
+        assert!(normalize("sales.orders", None).is_err());
+        assert_eq!(normalize(" sales ", Some(" ")).unwrap(), ("sales".to_string(), None));
+    }
+}
@@ -2,13 +2,12 @@
 //!
 //! Handles schema change proposals, reviews, and approvals.
 
+#[allow(dead_code)]
 mod models;
-mod store;
 mod changes;
 mod migration;
 
 pub use models::*;
-pub use store::ProposalStore;
 #[allow(unused_imports)]
 pub use changes::*;
 pub use migration::MigrationGenerator;
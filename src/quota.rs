@@ -0,0 +1,294 @@
+//! Usage quotas
+//!
+//! Hosted deployments cap what each project may consume: live connections,
+//! snapshots per UTC day, and executions running at once. Limits come from
+//! the `QUOTA_*` environment defaults, overridden per project by an admin.
+//! Connections opened without a project share one bucket under the defaults.
+//!
+//! Hitting the connection cap is a 403 (it will not clear by itself); the
+//! snapshot and execution limits are 429s. Admins may bypass a limit by
+//! sending `X-Quota-Override` with a reason, which is audited.
+
+use crate::auth::Claims;
+use crate::error::AppError;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::state::AppState;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Admins send this (with a reason) to bypass a quota
+pub const QUOTA_OVERRIDE_HEADER: &str = "x-quota-override";
+
+/// Limits for one project; `None` means unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectQuota {
+    pub max_connections: Option<u32>,
+    pub max_snapshots_per_day: Option<u32>,
+    pub max_concurrent_executions: Option<u32>,
+}
+
+impl ProjectQuota {
+    /// Fill limits this quota leaves unset from `defaults`
+    pub fn or(&self, defaults: &ProjectQuota) -> ProjectQuota {
+        ProjectQuota {
+            max_connections: self.max_connections.or(defaults.max_connections),
+            max_snapshots_per_day: self.max_snapshots_per_day.or(defaults.max_snapshots_per_day),
+            max_concurrent_executions: self.max_concurrent_executions.or(defaults.max_concurrent_executions),
+        }
+    }
+}
+
+/// Current consumption against a project's quota
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+    pub connections: usize,
+    pub snapshots_today: usize,
+    pub running_executions: usize,
+}
+
+/// Tracks executions in flight; limits are looked up per check
+pub struct QuotaService {
+    defaults: ProjectQuota,
+    running: Arc<Mutex<HashMap<Option<i32>, usize>>>,
+}
+
+/// Held for the duration of an execution; releases its slot when dropped
+pub struct ExecutionPermit {
+    running: Arc<Mutex<HashMap<Option<i32>, usize>>>,
+    project_id: Option<i32>,
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        let mut running = self.running.lock().expect("quota lock poisoned");
+        if let Some(count) = running.get_mut(&self.project_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                running.remove(&self.project_id);
+            }
+        }
+    }
+}
+
+impl QuotaService {
+    pub fn new(defaults: ProjectQuota) -> Self {
+        Self {
+            defaults,
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn defaults(&self) -> &ProjectQuota {
+        &self.defaults
+    }
+
+    pub fn running_executions(&self, project_id: Option<i32>) -> usize {
+        self.running.lock().expect("quota lock poisoned").get(&project_id).copied().unwrap_or(0)
+    }
+
+    /// Take an execution slot, failing once `limit` are already running
+    pub fn try_begin_execution(&self, project_id: Option<i32>, limit: Option<u32>) -> Result<ExecutionPermit, AppError> {
+        let mut running = self.running.lock().expect("quota lock poisoned");
+        let count = running.entry(project_id).or_insert(0);
+        if let Some(limit) = limit.filter(|&limit| *count >= limit as usize) {
+            return Err(AppError::QuotaExceeded(format!(
+                "{} already has {} execution(s) running (limit {}); retry when one finishes",
+                describe(project_id),
+                count,
+                limit
+            )));
+        }
+        *count += 1;
+
+        Ok(ExecutionPermit {
+            running: self.running.clone(),
+            project_id,
+        })
+    }
+}
+
+/// Effective limits for a project (or the shared bucket)
+pub async fn limits(state: &AppState, project_id: Option<i32>) -> Result<ProjectQuota, AppError> {
+    let stored = match project_id {
+        Some(id) => state.project_service.get_quota(id).await?.unwrap_or_default(),
+        None => ProjectQuota::default(),
+    };
+    Ok(stored.or(state.quotas.defaults()))
+}
+
+/// What a project currently uses
+pub async fn usage(state: &AppState, project_id: Option<i32>) -> Result<QuotaUsage, AppError> {
+    let connections = project_connections(state, project_id).await;
+    Ok(QuotaUsage {
+        connections: connections.len(),
+        snapshots_today: snapshots_since(state, &connections, start_of_day(Utc::now())).await?,
+        running_executions: state.quotas.running_executions(project_id),
+    })
+}
+
+/// Whether the caller asked to bypass quotas; only admins may
+pub fn override_requested(headers: &HeaderMap, claims: &Claims) -> Result<Option<String>, AppError> {
+    let Some(value) = headers.get(QUOTA_OVERRIDE_HEADER) else {
+        return Ok(None);
+    };
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can override quotas".to_string()));
+    }
+    let reason = value.to_str().unwrap_or_default().trim();
+    if reason.is_empty() {
+        return Err(AppError::BadRequest(format!("{} needs a reason", QUOTA_OVERRIDE_HEADER)));
+    }
+    Ok(Some(reason.to_string()))
+}
+
+/// Refuse a new connection once the project is at its limit
+pub async fn check_connection(
+    state: &AppState,
+    project_id: Option<i32>,
+    claims: &Claims,
+    headers: &HeaderMap,
+) -> Result<(), AppError> {
+    let Some(limit) = limits(state, project_id).await?.max_connections else {
+        return Ok(());
+    };
+    let open = project_connections(state, project_id).await.len();
+    if open < limit as usize {
+        return Ok(());
+    }
+
+    let message = format!(
+        "{} has {} open connection(s) (limit {}); disconnect one or ask an admin to raise the quota",
+        describe(project_id),
+        open,
+        limit
+    );
+    bypass_or(state, project_id, claims, headers, AppError::Forbidden(message)).await
+}
+
+/// Refuse a snapshot once the project has taken its daily allowance
+pub async fn check_snapshot(
+    state: &AppState,
+    connection_id: Uuid,
+    claims: &Claims,
+    headers: &HeaderMap,
+) -> Result<(), AppError> {
+    let project_id = state.connections.project_id(connection_id).await;
+    let Some(limit) = limits(state, project_id).await?.max_snapshots_per_day else {
+        return Ok(());
+    };
+    let connections = project_connections(state, project_id).await;
+    let taken = snapshots_since(state, &connections, start_of_day(Utc::now())).await?;
+    if taken < limit as usize {
+        return Ok(());
+    }
+
+    let message = format!(
+        "{} has taken {} snapshot(s) today (limit {}); the allowance resets at midnight UTC",
+        describe(project_id),
+        taken,
+        limit
+    );
+    bypass_or(state, project_id, claims, headers, AppError::QuotaExceeded(message)).await
+}
+
+/// Take an execution slot for a proposal's project; overrides run without one
+pub async fn begin_execution(
+    state: &AppState,
+    project_id: Option<i32>,
+    claims: &Claims,
+    headers: &HeaderMap,
+) -> Result<Option<ExecutionPermit>, AppError> {
+    let limit = limits(state, project_id).await?.max_concurrent_executions;
+    match state.quotas.try_begin_execution(project_id, limit) {
+        Ok(permit) => Ok(Some(permit)),
+        Err(e) => bypass_or(state, project_id, claims, headers, e).await.map(|_| None),
+    }
+}
+
+/// Let an admin override through (and audit it), otherwise fail with `error`
+async fn bypass_or(
+    state: &AppState,
+    project_id: Option<i32>,
+    claims: &Claims,
+    headers: &HeaderMap,
+    error: AppError,
+) -> Result<(), AppError> {
+    let Some(reason) = override_requested(headers, claims)? else {
+        return Err(error);
+    };
+
+    tracing::warn!("Admin {} overrode a quota for {}: {}", claims.sub, describe(project_id), reason);
+    let target = project_id.map(|id| id.to_string()).unwrap_or_else(|| "unassigned".to_string());
+    let entry = AuditEntry::new(AuditAction::QuotaOverridden, &claims.sub, "project", &target)
+        .with_details(&format!("{} Reason: {}", error, reason));
+    state.metadata.add_audit_entry(entry).await?;
+    Ok(())
+}
+
+async fn project_connections(state: &AppState, project_id: Option<i32>) -> Vec<Uuid> {
+    state
+        .connections
+        .list_connections()
+        .await
+        .into_iter()
+        .filter(|c| c.project_id == project_id)
+        .map(|c| c.id)
+        .collect()
+}
+
+async fn snapshots_since(state: &AppState, connections: &[Uuid], since: DateTime<Utc>) -> Result<usize, AppError> {
+    let mut count = 0;
+    for connection_id in connections {
+        count += state
+            .snapshots
+            .list(*connection_id)
+            .await?
+            .iter()
+            .filter(|s| s.captured_at >= since)
+            .count();
+    }
+    Ok(count)
+}
+
+fn start_of_day(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.date_naive().and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc()
+}
+
+fn describe(project_id: Option<i32>) -> String {
+    match project_id {
+        Some(id) => format!("Project {}", id),
+        None => "Connections without a project".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_slots_are_released_on_drop() {
+        let quotas = QuotaService::new(ProjectQuota::default());
+
+        let first = quotas.try_begin_execution(Some(1), Some(1)).unwrap();
+        assert!(matches!(quotas.try_begin_execution(Some(1), Some(1)), Err(AppError::QuotaExceeded(_))));
+        // Other projects have their own slots
+        let _other = quotas.try_begin_execution(Some(2), Some(1)).unwrap();
+
+        drop(first);
+        assert_eq!(quotas.running_executions(Some(1)), 0);
+        assert!(quotas.try_begin_execution(Some(1), Some(1)).is_ok());
+
+        let project = ProjectQuota { max_connections: Some(3), ..Default::default() };
+        let defaults = ProjectQuota { max_connections: Some(1), max_snapshots_per_day: Some(50), ..Default::default() };
+        assert_eq!(
+            project.or(&defaults),
+            ProjectQuota { max_connections: Some(3), max_snapshots_per_day: Some(50), max_concurrent_executions: None }
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-client request rate limiting
+//!
+//! When `rate_limits.requests_per_minute` is set, each client may send that
+//! many requests per fixed one-minute window; further requests get a 429
+//! until the window rolls over. Clients are told apart by the first
+//! `X-Forwarded-For` address, then `X-Real-IP`, then the peer address.
+
+use crate::error::AppError;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Length of a counting window
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Tracked clients above which expired windows are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Request counts per client for the current window
+pub struct RateLimiter {
+    limit: u32,
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self {
+            limit: requests_per_minute,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request from `client`; the error holds the seconds until it may retry
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), u64> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() > PRUNE_THRESHOLD {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        }
+
+        let (start, count) = windows.entry(client.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            let elapsed = now.duration_since(*start);
+            return Err(WINDOW.saturating_sub(elapsed).as_secs().max(1));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// Identify the client behind a request
+fn client_key(headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    header("x-forwarded-for")
+        .or_else(|| header("x-real-ip"))
+        .or_else(|| peer.map(|addr| addr.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Reject requests over the client's per-minute allowance
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let client = client_key(request.headers(), peer);
+
+    match limiter.check(&client, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let mut response = AppError::RateLimited(format!(
+                "Too many requests; limit is {} per minute, retry in {}s",
+                limiter.limit, retry_after
+            ))
+            .into_response();
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, retry_after.into());
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_resets_after_window() {
+        let limiter = RateLimiter::new(2);
+        let now = Instant::now();
+        assert!(limiter.check("a", now).is_ok());
+        assert!(limiter.check("a", now).is_ok());
+        assert!(limiter.check("a", now).is_err());
+        assert!(limiter.check("b", now).is_ok());
+        assert!(limiter.check("a", now + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_client_key_prefers_forwarded_for() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", "10.0.0.2".parse().unwrap());
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        assert_eq!(client_key(&headers, None), "203.0.113.7");
+
+        let peer: SocketAddr = "192.0.2.1:4000".parse().unwrap();
+        assert_eq!(client_key(&HeaderMap::new(), Some(peer)), "192.0.2.1");
+    }
+}
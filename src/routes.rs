@@ -2,25 +2,32 @@
 //!
 //! Configures all API routes and middleware.
 
+pub mod activity;
 pub mod auth;
 pub mod connection;
 pub mod project;
 mod database;
 mod foreign_key;
 pub mod pipeline;
+pub mod presence;
+pub mod sandbox;
 pub mod snapshot;
 mod table;
 
 use crate::auth::middleware::auth_middleware;
+use crate::idempotency::{idempotency_middleware, IDEMPOTENCY_KEY_HEADER};
+use crate::quota::QUOTA_OVERRIDE_HEADER;
+use crate::rate_limit::{rate_limit_middleware, RateLimiter};
 use crate::config::Settings;
 use crate::state::SharedState;
 use axum::{
     http::{header, Method},
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use std::sync::Arc;
 use std::time::Duration;
-use tower::ServiceBuilder;
+use tower::{limit::ConcurrencyLimitLayer, ServiceBuilder};
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
@@ -49,14 +56,25 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .layer(cors)
         .propagate_x_request_id();
 
+    // Replays the stored response for a repeated Idempotency-Key
+    let idempotent = || axum::middleware::from_fn_with_state(state.clone(), idempotency_middleware);
+
     // Protected routes that require authentication
     let protected_routes = Router::new()
         // ============================================
         // AUTHENTICATION API (Protected)
         // ============================================
         .route("/api/auth/me", get(auth::me))
+        .route("/api/auth/me/notifications", get(auth::get_notification_preferences))
+        .route("/api/auth/me/notifications", put(auth::update_notification_preferences))
+        .route("/api/auth/me/watches", get(auth::list_watches).post(auth::add_watch))
+        .route("/api/auth/me/watches/{id}", delete(auth::remove_watch))
         .route("/api/auth/role/{user_id}", put(auth::update_role))
+        .route("/api/auth/impersonate/{user_id}", post(auth::start_impersonation))
+        .route("/api/auth/impersonations", get(auth::list_impersonations))
+        .route("/api/auth/impersonations/{session_id}", delete(auth::end_impersonation))
         .route("/api/users", get(auth::list_users))
+        .route("/api/users/{user_id}/activity", get(auth::get_user_activity))
         
         // ============================================
         // PROJECT MANAGEMENT API
@@ -67,10 +85,33 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .route("/api/projects/{id}", get(project::get_project))
         .route("/api/projects/{id}", put(project::update_project))
         .route("/api/projects/{id}", delete(project::delete_project))
+        .route("/api/projects/{id}/lint-config", get(project::get_lint_config))
+        .route("/api/projects/{id}/lint-config", put(project::update_lint_config))
+        .route("/api/projects/{id}/proposal-template", get(project::get_proposal_template))
+        .route("/api/projects/{id}/proposal-template", put(project::update_proposal_template))
+        .route("/api/projects/{id}/access-policy", get(project::get_access_policy))
+        .route("/api/projects/{id}/access-policy", put(project::update_access_policy))
+        .route("/api/projects/{id}/review-sla", get(project::get_review_sla))
+        .route("/api/projects/{id}/review-sla", put(project::update_review_sla))
+        .route("/api/projects/{id}/execution-policy", get(project::get_execution_policy))
+        .route("/api/projects/{id}/execution-policy", put(project::update_execution_policy))
+        .route("/api/projects/{id}/teams", get(project::get_teams))
+        .route("/api/projects/{id}/teams", put(project::update_teams))
+        .route("/api/projects/{id}/quotas", get(project::get_quotas))
+        .route("/api/projects/{id}/quotas", put(project::update_quotas))
+        .route("/api/projects/{id}/features", put(project::update_features))
+        .route("/api/features", get(project::list_features))
+        .route("/api/projects/{id}/custom-fields", get(project::get_custom_fields))
+        .route("/api/projects/{id}/custom-fields", put(project::update_custom_fields))
+        .route("/api/projects/{id}/tags", get(project::get_tag_taxonomy))
+        .route("/api/projects/{id}/tags", put(project::update_tag_taxonomy))
+        .route("/api/projects/{id}/analytics/violations", get(project::get_violation_analytics))
+        .route("/api/projects/{id}/proposals/board", get(project::get_proposal_board))
         .route("/api/projects/{project_id}/connections", post(project::save_connection))
         .route("/api/projects/{project_id}/connections", get(project::list_connections))
         .route("/api/projects/{project_id}/connections/{connection_id}", delete(project::remove_connection))
         .route("/api/projects/{project_id}/connections/{connection_id}/activate", post(project::activate_connection))
+        .route("/api/projects/{project_id}/connections/{connection_id}/reveal", post(project::reveal_connection))
         
         // ============================================
         // CONNECTION MANAGEMENT API
@@ -84,7 +125,26 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .route("/api/connections/disconnect-all", post(connection::disconnect_all))
         .route("/api/connections/{id}", get(connection::get_connection))
         .route("/api/connections/{id}", delete(connection::disconnect))
+        .route("/api/connections/{id}/clone", post(connection::clone_connection))
+        .route("/api/connections/{id}/sandboxes", post(sandbox::create_sandbox))
+        .route("/api/connections/{id}/sandboxes", get(sandbox::list_sandboxes))
+        .route("/api/sandboxes/{id}", get(sandbox::get_sandbox))
+        .route("/api/sandboxes/{id}", delete(sandbox::delete_sandbox))
+        .route("/api/sandboxes/{id}/extend", post(sandbox::extend_sandbox))
         .route("/api/connections/{id}/introspect", post(connection::introspect))
+        .route("/api/connections/{id}/schema-scope", put(connection::set_schema_scope))
+        .route("/api/connections/{id}/diff-ignore", get(connection::get_diff_ignore))
+        .route("/api/connections/{id}/diff-ignore", put(connection::set_diff_ignore))
+        .route("/api/connections/{id}/diff-mode", put(connection::set_diff_mode))
+        .route("/api/connections/{id}/blast-radius-limits", put(connection::set_blast_radius_limits))
+        .route("/api/connections/{id}/backups", post(connection::record_backup))
+        .route("/api/connections/{id}/backups", get(connection::list_backups))
+        .route("/api/connections/{id}/type-migrations", post(connection::suggest_type_migration))
+        .route("/api/connections/{id}/activity", get(connection::get_activity))
+        .route("/api/connections/{id}/pool", get(connection::get_pool_metrics))
+        .route("/api/connections/{id}/partitioning/candidates", get(connection::get_partition_candidates))
+        .route("/api/connections/{id}/partitioning/scaffold", post(pipeline::scaffold_partitioning))
+        .route("/api/connections/{id}/tags/bulk", post(pipeline::bulk_tag))
         
         // Schema API (for active connection)
         .route("/api/schema", get(connection::get_active_schema))
@@ -94,31 +154,69 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         // Stage 1: Mirror (Introspection & Semantic Map)
         // ============================================
         .route("/api/connections/{id}/semantic-map", post(pipeline::build_semantic_map))
+        .route("/api/connections/{id}/semantic-map", get(pipeline::get_semantic_map))
+        .route("/api/connections/{id}/semantic-map/layers/{layer}", post(pipeline::refresh_semantic_layer))
         .route("/api/connections/{id}/drift", get(pipeline::check_drift))
+        .route("/api/connections/{id}/changelog", get(pipeline::get_changelog))
         
         // ============================================
         // Stage 2: Proposals (Schema PRs)
         // ============================================
-        .route("/api/proposals", post(pipeline::create_proposal))
+        .route("/api/proposals", post(pipeline::create_proposal).layer(idempotent()))
         .route("/api/proposals", get(pipeline::list_proposals))
         .route("/api/proposals/{id}", get(pipeline::get_proposal))
+        .route("/api/proposals/{id}", patch(pipeline::patch_proposal))
         .route("/api/proposals/{id}/changes", post(pipeline::add_change_to_proposal))
         .route("/api/proposals/{id}/migration", post(pipeline::generate_migration))
+        .route("/api/proposals/{id}/migrations", get(pipeline::list_migration_versions))
+        .route("/api/proposals/{id}/parameters/{environment}", put(pipeline::set_parameter_values))
         .route("/api/proposals/{id}/submit", post(pipeline::submit_for_review))
-        .route("/api/proposals/{id}/approve", post(pipeline::approve_proposal))
+        .route("/api/proposals/{id}/blast-radius", get(pipeline::get_blast_radius))
+        .route("/api/proposals/{id}/approve", post(pipeline::approve_proposal).layer(idempotent()))
+        .route("/api/proposals/{id}/team-reviews", get(pipeline::get_team_reviews))
         .route("/api/proposals/{id}/reject", post(pipeline::reject_proposal))
         .route("/api/proposals/{id}/comments", post(pipeline::add_comment))
+        .route("/api/proposals/{id}/comments", get(pipeline::list_comments))
+        .route("/api/proposals/{id}/comments/{comment_id}/resolve", post(pipeline::resolve_comment_thread))
+        .route("/api/proposals/{id}/statements", get(pipeline::list_statement_reviews))
+        .route("/api/proposals/{id}/statements/{index}/approve", post(pipeline::approve_statement))
+        .route("/api/proposals/{id}/checklist", get(pipeline::get_checklist))
+        .route("/api/proposals/{id}/checklist/{item_id}", put(pipeline::check_checklist_item))
+        .route("/api/proposals/{id}/share-links", post(pipeline::create_share_link))
+        .route("/api/proposals/{id}/share-links", get(pipeline::list_share_links))
+        .route("/api/proposals/{id}/share-links/{link_id}", delete(pipeline::revoke_share_link))
+        .route("/api/proposals/{id}/presence", get(presence::get_presence))
+        .route("/api/proposals/{id}/presence/ws", get(presence::presence_socket))
+        .route("/api/proposals/{id}/events", get(activity::proposal_events))
+        .route("/api/proposals/{id}/lock", post(presence::acquire_edit_lock).delete(presence::release_edit_lock))
         
         // ============================================
         // Stage 3: Risk Analysis
         // ============================================
         .route("/api/proposals/{id}/analyze", post(pipeline::analyze_risk))
+        .route("/api/proposals/{id}/risk-history", get(pipeline::get_risk_history))
         
         // ============================================
         // Stage 4: Execution & Rollback
         // ============================================
-        .route("/api/proposals/{id}/execute", post(pipeline::execute_proposal))
+        .route("/api/proposals/{id}/execute", post(pipeline::execute_proposal).layer(idempotent()))
+        .route("/api/proposals/{id}/plan", post(pipeline::create_execution_plan))
+        .route("/api/proposals/{id}/plans", get(pipeline::list_execution_plans))
+        .route("/api/plans/{id}", get(pipeline::get_execution_plan))
+        .route("/api/plans/{id}/stages/{stage}/execute", post(pipeline::execute_plan_stage).layer(idempotent()))
+        .route("/api/plans/{id}/pause", post(pipeline::pause_execution_plan))
+        .route("/api/plans/{id}/resume", post(pipeline::resume_execution_plan))
+        .route("/api/plans/{id}/cancel", post(pipeline::cancel_execution_plan))
+        .route("/api/proposals/{id}/execution", get(pipeline::get_execution_status))
+        .route("/api/proposals/{id}/preflight", get(pipeline::get_permission_preflight))
+        .route("/api/executions/interrupted", get(pipeline::list_interrupted_executions))
+        .route("/api/executions/{id}/verify", post(pipeline::verify_interrupted_execution))
         .route("/api/proposals/{id}/rollback", post(pipeline::rollback_proposal))
+        .route("/api/proposals/{id}/clone", post(pipeline::clone_proposal))
+        .route("/api/proposals/{id}/revert", post(pipeline::revert_proposal))
+        .route("/api/proposals/{id}/retrospective", post(pipeline::review_retrospective))
+        .route("/api/connections/{id}/break-glass", post(pipeline::break_glass).layer(idempotent()))
+        .route("/api/break-glass", get(pipeline::list_break_glass))
         
         // ============================================
         // SCHEMA SNAPSHOTS & IMPACT ANALYSIS
@@ -129,21 +227,35 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .route("/api/connections/{id}/snapshots/latest", get(snapshot::get_latest_snapshot))
         .route("/api/connections/{id}/snapshots/{version}", get(snapshot::get_snapshot_version))
         .route("/api/connections/{id}/snapshots/diff", get(snapshot::diff_snapshots))
+        .route("/api/connections/{id}/snapshots/archive", post(snapshot::archive_snapshots))
+        .route("/api/connections/{id}/snapshots/archived", get(snapshot::list_archived_snapshots))
+        .route("/api/connections/{id}/snapshots/restore", post(snapshot::restore_snapshot))
         .route("/api/connections/{id}/snapshots/{snapshot_id}/baseline", post(snapshot::set_baseline))
         .route("/api/connections/{id}/blast-radius", post(snapshot::analyze_blast_radius))
+        .route("/api/connections/{id}/benchmark", post(snapshot::run_benchmark))
         .route("/api/connections/{id}/schema-drift", get(snapshot::check_drift))
+        .route("/api/connections/{id}/encryption-report", get(snapshot::encryption_report))
+        .route("/api/connections/{id}/docs", get(snapshot::generate_docs))
+        .route("/api/connections/{id}/dictionary", get(snapshot::search_dictionary))
+        .route("/api/connections/{id}/governance/coverage", get(snapshot::governance_coverage))
+        .route("/api/connections/{id}/governance/classification-propagation", get(snapshot::classification_propagation))
+        .route("/api/connections/{id}/governance/classification-propagation", post(pipeline::propose_classification_propagation))
         .route("/api/rules", get(snapshot::list_rules))
         
         // ============================================
         // Audit Log
         // ============================================
         .route("/api/audit-log", get(pipeline::get_audit_log))
+        .route("/api/audit-log/export", get(pipeline::export_audit_log))
+        .route("/api/audit-log/verify", get(pipeline::verify_audit_log))
+        .route("/api/legal-holds", get(pipeline::list_legal_holds).post(pipeline::place_legal_hold))
+        .route("/api/legal-holds/{id}/release", post(pipeline::release_legal_hold))
         
         // Apply auth middleware to all protected routes
-        .layer(axum::middleware::from_fn(auth_middleware));
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auth_middleware));
     
     // Build the main router
-    Router::new()
+    let router = Router::new()
         // Health check
         .route("/health", get(health_check))
         
@@ -154,6 +266,9 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .route("/api/auth/register", post(auth::register))
         .route("/api/auth/refresh", post(auth::refresh))
         
+        // Read-only proposal view for share link holders
+        .route("/api/share/{token}", get(pipeline::view_shared_proposal))
+        
         // Merge protected routes
         .merge(protected_routes)
         
@@ -181,9 +296,23 @@ pub fn create_router(state: SharedState, settings: &Settings) -> Router {
         .route("/foreignKey/primaryKeys", get(foreign_key::get_primary_keys))
         .route("/foreignKey/validateReference", post(foreign_key::validate_reference))
         
-        // Apply middleware and state
-        .layer(middleware)
-        .with_state(state)
+        .with_state(state);
+
+    // Optional traffic limits from [rate_limits]
+    let router = match settings.rate_limits.requests_per_minute {
+        Some(limit) => router.layer(axum::middleware::from_fn_with_state(
+            Arc::new(RateLimiter::new(limit)),
+            rate_limit_middleware,
+        )),
+        None => router,
+    };
+    let router = match settings.rate_limits.max_concurrent_requests {
+        Some(limit) => router.layer(ConcurrencyLimitLayer::new(limit)),
+        None => router,
+    };
+
+    // Apply middleware
+    router.layer(middleware)
 }
 
 /// Build CORS layer from settings
@@ -199,13 +328,25 @@ fn build_cors_layer(settings: &Settings) -> CorsLayer {
         CorsLayer::new()
             .allow_origin(Any)
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::ACCEPT])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                header::HeaderName::from_static(QUOTA_OVERRIDE_HEADER),
+            ])
             .max_age(Duration::from_secs(3600))
     } else {
         CorsLayer::new()
             .allow_origin(origins)
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::ACCEPT])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                header::HeaderName::from_static(QUOTA_OVERRIDE_HEADER),
+            ])
             .max_age(Duration::from_secs(3600))
     }
 }
@@ -0,0 +1,58 @@
+//! Proposal activity stream
+//!
+//! The WebSocket that pushes a proposal's comments, approvals, status
+//! changes, risk analyses and execution progress as they happen. See
+//! `pipeline::activity`.
+
+use crate::auth::Claims;
+use crate::error::ApiResult;
+use crate::pipeline::membership;
+use crate::state::SharedState;
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Extension, Path, State},
+    response::Response,
+};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// GET /api/proposals/{id}/events
+/// WebSocket stream of the proposal's activity, one JSON event per message,
+/// told apart by `type`. A `{"type": "lagged"}` message means events were
+/// missed and the proposal should be fetched again.
+pub async fn proposal_events(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    membership::require_proposal(&state, &claims, id).await?;
+    Ok(ws.on_upgrade(move |socket| stream_events(state, id, socket)))
+}
+
+async fn stream_events(state: SharedState, proposal_id: Uuid, mut socket: WebSocket) {
+    let mut events = state.activity.subscribe();
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // The stream is one-way; pings are answered by axum
+                Some(Ok(_)) => {}
+            },
+            event = events.recv() => {
+                let text = match event {
+                    Ok(event) if event.proposal_id == proposal_id => serde_json::to_string(&event),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => serde_json::to_string(&json!({ "type": "lagged", "missed": missed })),
+                    Err(RecvError::Closed) => break,
+                };
+                let Ok(text) = text else { continue };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            },
+        }
+    }
+}
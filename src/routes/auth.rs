@@ -3,18 +3,26 @@
 //! Provides login, register, refresh, and user management endpoints.
 
 use crate::auth::{
-    create_tokens, decode_token, refresh_tokens, TokenPair,
-    Role,
+    create_impersonation_token, create_tokens, decode_token, refresh_tokens, Claims,
+    ImpersonationSession, Impersonator, TokenPair, Role,
 };
 use crate::error::AppError;
+use crate::notifications::NotificationPreferences;
+use crate::pipeline::contributions::{self, UserActivity};
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::analytics;
+use crate::pipeline::membership;
+use crate::pipeline::watches::{self, ObjectWatch};
 use crate::state::SharedState;
 use crate::users::User;
 use axum::{
-    extract::State,
+    extract::{Extension, Query, State},
     http::{header, StatusCode},
     Json,
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 // ============================================
 // Request/Response Types
@@ -327,3 +335,304 @@ pub async fn list_users(
         users: user_list,
     }))
 }
+
+/// GET/PUT /api/auth/me/notifications
+/// 
+/// Email notification opt-outs for the current user.
+#[derive(Debug, Serialize)]
+pub struct NotificationPreferencesResponse {
+    pub success: bool,
+    pub preferences: NotificationPreferences,
+}
+
+pub async fn get_notification_preferences(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let user_id = claims.sub.parse::<i32>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+    
+    let preferences = state.user_service
+        .get_notification_preferences(user_id)
+        .await?
+        .unwrap_or_default();
+    
+    Ok(Json(NotificationPreferencesResponse {
+        success: true,
+        preferences,
+    }))
+}
+
+pub async fn update_notification_preferences(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Json(preferences): Json<NotificationPreferences>,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let user_id = claims.sub.parse::<i32>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+    
+    state.user_service
+        .set_notification_preferences(user_id, &preferences)
+        .await?;
+    
+    Ok(Json(NotificationPreferencesResponse {
+        success: true,
+        preferences,
+    }))
+}
+
+/// GET/POST /api/auth/me/watches, DELETE /api/auth/me/watches/{id}
+/// 
+/// Schemas and tables the current user watches. Proposals touching them,
+/// drift on them and executed changes to them notify the user.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchRequest {
+    pub connection_id: Uuid,
+    pub schema: String,
+    /// Watch one table of the schema instead of all of it
+    #[serde(default)]
+    pub table: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchesResponse {
+    pub success: bool,
+    pub watches: Vec<ObjectWatch>,
+}
+
+pub async fn list_watches(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<WatchesResponse>, AppError> {
+    let user_id = claims.sub.parse::<i32>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+    
+    Ok(Json(WatchesResponse {
+        success: true,
+        watches: state.user_service.list_watches(user_id).await?,
+    }))
+}
+
+pub async fn add_watch(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<WatchRequest>,
+) -> Result<(StatusCode, Json<WatchesResponse>), AppError> {
+    let user_id = claims.sub.parse::<i32>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+    membership::require_connection(&state, &claims, req.connection_id).await?;
+    let (schema, table) = watches::normalize(&req.schema, req.table.as_deref())?;
+    
+    state.user_service
+        .add_watch(user_id, req.connection_id, &schema, table.as_deref())
+        .await?;
+    
+    Ok((StatusCode::CREATED, Json(WatchesResponse {
+        success: true,
+        watches: state.user_service.list_watches(user_id).await?,
+    })))
+}
+
+pub async fn remove_watch(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::Path(watch_id): axum::extract::Path<i32>,
+) -> Result<Json<WatchesResponse>, AppError> {
+    let user_id = claims.sub.parse::<i32>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token".to_string()))?;
+    
+    if !state.user_service.remove_watch(user_id, watch_id).await? {
+        return Err(AppError::NotFound(format!("Watch {} not found", watch_id)));
+    }
+    
+    Ok(Json(WatchesResponse {
+        success: true,
+        watches: state.user_service.list_watches(user_id).await?,
+    }))
+}
+
+/// POST /api/auth/impersonate/{user_id}
+/// 
+/// Start a time-boxed, read-only impersonation session (Admin only).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonateRequest {
+    /// Why the admin needs to see the user's view; recorded in the audit log
+    pub reason: String,
+    #[serde(default = "default_impersonation_minutes")]
+    pub duration_minutes: i64,
+}
+
+fn default_impersonation_minutes() -> i64 {
+    15
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonateResponse {
+    pub success: bool,
+    pub access_token: String,
+    pub session: ImpersonationSession,
+}
+
+pub async fn start_impersonation(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::Path(user_id): axum::extract::Path<i32>,
+    Json(req): Json<ImpersonateRequest>,
+) -> Result<Json<ImpersonateResponse>, AppError> {
+    if claims.role != Role::Admin || claims.impersonator.is_some() {
+        return Err(AppError::Forbidden("Only admins can impersonate users".to_string()));
+    }
+    if req.reason.trim().is_empty() {
+        return Err(AppError::BadRequest("A reason is required to impersonate a user".to_string()));
+    }
+    if claims.sub == user_id.to_string() {
+        return Err(AppError::BadRequest("Cannot impersonate yourself".to_string()));
+    }
+    
+    let target = state.user_service
+        .find_by_id(user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    
+    let session = state.impersonations
+        .start(
+            &claims.sub,
+            &claims.email,
+            &target.id.to_string(),
+            &target.email,
+            req.reason.trim(),
+            req.duration_minutes,
+        )
+        .await;
+    
+    let access_token = create_impersonation_token(
+        &session.target_user_id,
+        &session.target_email,
+        Impersonator {
+            sub: claims.sub.clone(),
+            email: claims.email.clone(),
+            session_id: session.id,
+        },
+        session.expires_at,
+    )?;
+    
+    let entry = AuditEntry::new(AuditAction::ImpersonationStarted, &claims.email, "user", &session.target_user_id)
+        .with_details(&format!(
+            "{} as {} until {} (session {}): {}",
+            claims.email,
+            session.target_email,
+            session.expires_at.to_rfc3339(),
+            session.id,
+            session.reason
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+    
+    Ok(Json(ImpersonateResponse {
+        success: true,
+        access_token,
+        session,
+    }))
+}
+
+/// GET /api/auth/impersonations
+/// 
+/// List active impersonation sessions (Admin only).
+#[derive(Debug, Serialize)]
+pub struct ImpersonationsResponse {
+    pub success: bool,
+    pub sessions: Vec<ImpersonationSession>,
+}
+
+pub async fn list_impersonations(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ImpersonationsResponse>, AppError> {
+    if claims.role != Role::Admin {
+        return Err(AppError::Forbidden("Only admins can list impersonation sessions".to_string()));
+    }
+    
+    Ok(Json(ImpersonationsResponse {
+        success: true,
+        sessions: state.impersonations.list_active().await,
+    }))
+}
+
+/// DELETE /api/auth/impersonations/{session_id}
+/// 
+/// End an impersonation session before it expires (Admin only).
+pub async fn end_impersonation(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::Path(session_id): axum::extract::Path<Uuid>,
+) -> Result<Json<ImpersonationsResponse>, AppError> {
+    if claims.role != Role::Admin {
+        return Err(AppError::Forbidden("Only admins can end impersonation sessions".to_string()));
+    }
+    
+    let session = state.impersonations
+        .end(session_id)
+        .await
+        .ok_or_else(|| AppError::NotFound("No active impersonation session with that ID".to_string()))?;
+    
+    let entry = AuditEntry::new(AuditAction::ImpersonationEnded, &claims.email, "user", &session.target_user_id)
+        .with_details(&format!(
+            "Session {} ({} as {}) ended by {}",
+            session.id, session.admin_email, session.target_email, claims.email
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+    
+    Ok(Json(ImpersonationsResponse {
+        success: true,
+        sessions: state.impersonations.list_active().await,
+    }))
+}
+
+/// Query for a user's activity summary
+#[derive(Debug, Deserialize)]
+pub struct UserActivityQuery {
+    /// Reporting window such as "90d", "12w" or "48h"
+    #[serde(default = "default_activity_period")]
+    pub period: String,
+}
+
+fn default_activity_period() -> String {
+    "90d".to_string()
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserActivityResponse {
+    pub success: bool,
+    pub activity: UserActivity,
+}
+
+/// GET /api/users/{user_id}/activity
+///
+/// Proposals authored, reviews given, executions performed and comments over
+/// a period (the user themselves, or an admin).
+pub async fn get_user_activity(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::Path(user_id): axum::extract::Path<i32>,
+    Query(query): Query<UserActivityQuery>,
+) -> Result<Json<UserActivityResponse>, AppError> {
+    if claims.sub != user_id.to_string() && !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can view other users' activity".to_string()));
+    }
+
+    state.user_service
+        .find_by_id(user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let since = Utc::now() - analytics::parse_period(&query.period)?;
+    let actor = user_id.to_string();
+    let entries = state.metadata.get_user_audit_log(&actor, since).await?;
+
+    Ok(Json(UserActivityResponse {
+        success: true,
+        activity: contributions::summarize(&actor, entries, since),
+    }))
+}
@@ -2,21 +2,70 @@
 //!
 //! API endpoints for the Governance Pipeline.
 
+use crate::auth::{create_share_token, decode_share_token, Claims};
+use crate::config::AuditExportFormat;
+use crate::connection::DatabaseType;
 use crate::error::AppError;
-use crate::models::SuccessResponse;
-use crate::pipeline::metadata::{AuditAction, AuditEntry, ProposalSummary};
-use crate::pipeline::mirror::{MirrorService, SemanticMap};
-use crate::pipeline::orchestrator::Orchestrator;
-use crate::pipeline::proposal::{MigrationArtifacts, SchemaProposal};
+use crate::features::{self, Feature};
+use crate::models::{fields, FieldSelection, SuccessResponse};
+use crate::notifications::{Audience, Notification};
+use crate::pipeline::access::{self, AccessAction};
+use crate::pipeline::advisory_lock;
+use crate::pipeline::analytics::RuleEvaluationRecord;
+use crate::pipeline::audit_export;
+use crate::pipeline::backup::{self, BackupRequest};
+use crate::pipeline::break_glass::{self, BreakGlass, BreakGlassEntry};
+use crate::pipeline::changelog::{self, Changelog, ChangelogQuery};
+use crate::pipeline::checklist::{self, ChecklistItem};
+use crate::pipeline::impact::{self, BlastRadiusReport};
+use crate::pipeline::journal::ExecutionRecord;
+use crate::pipeline::load_test::{self, LoadTest, LoadTestResult};
+use crate::pipeline::membership;
+use crate::pipeline::metadata::{AuditAction, AuditEntry, ChainVerification, ProposalSummary};
+use crate::pipeline::mirror::{MirrorService, SemanticLayer, SemanticMap};
+use crate::pipeline::mysql;
+use crate::pipeline::not_null;
+use crate::pipeline::orchestrator::{
+    irreversible_steps, split_statements, ExecutionOptions, ExecutionResult, Orchestrator,
+};
+use crate::pipeline::partitioning::{self, ScaffoldRequest};
+use crate::pipeline::patch::{apply_patch, PatchOperation};
+use crate::pipeline::plan::{ExecutionPlan, PlanStatus};
+use crate::pipeline::preflight::{self, PermissionReport};
+use crate::pipeline::progress::ExecutionProgress;
+use crate::pipeline::proposal::{
+    Comment, CommentTarget, MigrationArtifacts, ProposalStatus, RiskLevel, SchemaProposal, StatementApproval,
+};
+use crate::pipeline::reanalysis::invalidate_risk;
+use crate::pipeline::references::{AnnotatedComment, Resolver};
+use crate::pipeline::refresh;
+use crate::pipeline::reorder;
+use crate::pipeline::retention::{HoldScope, LegalHold};
+use crate::pipeline::revert::build_revert;
 use crate::pipeline::risk::RiskEngine;
+use crate::pipeline::risk_history::{self, RiskHistoryEntry};
+use crate::pipeline::scratch::{self, ScratchRunResult};
+use crate::pipeline::share::{ShareAccess, ShareLink};
+use crate::pipeline::summary;
+use crate::pipeline::teams::{self, TeamReview};
+use crate::pipeline::templating::{self, ParameterValues};
 use crate::pipeline::types::*;
+use crate::pipeline::uniqueness::{self, DuplicateCheck};
+use crate::pipeline::watches::{self, WatchEvent};
+use crate::quota;
+use crate::snapshot::dictionary::{self, BulkTagRequest};
+use crate::snapshot::propagation;
+use crate::snapshot::rules::RulesResult;
+use crate::snapshot::LintConfig;
 use crate::state::SharedState;
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap},
     Json,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use uuid::Uuid;
 
 // =============================================================================
@@ -27,7 +76,11 @@ use uuid::Uuid;
 #[serde(rename_all = "camelCase")]
 pub struct CreateProposalRequest {
     pub connection_id: Uuid,
+    #[serde(default)]
+    pub project_id: Option<i32>,
     pub title: String,
+    /// Defaults to the project's proposal template when empty
+    #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub changes: Vec<SchemaChange>,
@@ -37,12 +90,87 @@ pub struct CreateProposalRequest {
 #[serde(rename_all = "camelCase")]
 pub struct AddChangeRequest {
     pub change: SchemaChange,
+    /// Also add a CONCURRENTLY index when a foreign key's columns are unindexed
+    #[serde(default)]
+    pub auto_index_foreign_keys: bool,
+    /// Value for existing NULLs when the change sets NOT NULL (defaults to the new default)
+    #[serde(default)]
+    pub backfill_value: Option<String>,
+    #[serde(default)]
+    pub backfill_batch_size: Option<u32>,
+    /// Add a NOT NULL change as-is instead of expanding it into a backfill plan
+    #[serde(default)]
+    pub skip_not_null_plan: bool,
+    /// Rows per copy statement of a column reorder
+    #[serde(default)]
+    pub copy_batch_size: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommentRequest {
     pub content: String,
+    /// Comment on one change, by index
+    #[serde(default)]
+    pub change_index: Option<usize>,
+    /// Comment on one statement of the generated migration, by index
+    #[serde(default)]
+    pub statement_index: Option<usize>,
+    /// Reply to an existing thread; the reply takes the thread's target
+    #[serde(default)]
+    pub reply_to: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveThreadRequest {
+    /// False reopens the thread
+    #[serde(default = "default_resolved")]
+    pub resolved: bool,
+}
+
+fn default_resolved() -> bool {
+    true
+}
+
+/// One migration statement with its review state
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementReview {
+    pub index: usize,
+    pub sql: String,
+    pub approvals: Vec<StatementApproval>,
+    pub open_threads: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementReviewResponse {
+    pub statements: Vec<StatementReview>,
+    /// What still stands in the way of approving the proposal
+    pub approval_blockers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistResponse {
+    pub items: Vec<ChecklistItem>,
+    /// What still stands in the way of approving the proposal
+    pub approval_blockers: Vec<String>,
+}
+
+fn default_checked() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistTickRequest {
+    /// False unticks the item
+    #[serde(default = "default_checked")]
+    pub checked: bool,
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -63,6 +191,70 @@ pub struct RejectionRequest {
 pub struct ExecuteRequest {
     #[serde(default)]
     pub dry_run: bool,
+    /// Also execute the dry run against the real data inside a transaction
+    /// that is rolled back, reporting row counts and constraint violations.
+    /// Admins only
+    #[serde(default)]
+    pub deep: bool,
+    /// Statements per transaction; overrides the server default
+    pub chunk_size: Option<usize>,
+    /// Continue a failed execution from its last committed chunk
+    #[serde(default)]
+    pub resume: bool,
+    /// Also run the dry run against scratch clones of the affected tables
+    /// (`temp_schema_dry_run` feature)
+    #[serde(default)]
+    pub temp_schema: bool,
+    /// Replay these reads against sample-filled clones while the dry run
+    /// applies the migration there (`temp_schema_dry_run` feature)
+    #[serde(default)]
+    pub load_test: Option<LoadTest>,
+    /// Execute on this connection first and stop if it fails
+    /// (`canary_execution` feature)
+    pub canary_connection_id: Option<Uuid>,
+    /// Label of the confirmation gate a paused execution resumes past
+    #[serde(default)]
+    pub confirm_gate: Option<String>,
+    /// How long to wait while another SchemaFlow instance migrates the same
+    /// database (default 30, at most 600)
+    #[serde(default)]
+    pub lock_wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakGlassRequest {
+    pub title: String,
+    pub changes: Vec<SchemaChange>,
+    /// Why the change cannot wait for review; shown to every admin
+    pub justification: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrospectiveRequest {
+    pub notes: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BreakGlassQuery {
+    /// Only changes still waiting for their retrospective
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateShareLinkRequest {
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Defaults to 72 hours, capped at 30 days
+    #[serde(default = "default_share_link_hours")]
+    pub expires_in_hours: i64,
+}
+
+fn default_share_link_hours() -> i64 {
+    72
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,7 +272,8 @@ pub struct ProposalResponse {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProposalListResponse {
-    pub proposals: Vec<ProposalSummary>,
+    /// Summaries, trimmed and extended as `?fields=` and `?include=` ask
+    pub proposals: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -89,16 +282,26 @@ pub struct MigrationResponse {
     pub migration: MigrationArtifacts,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationHistoryResponse {
+    pub versions: Vec<MigrationArtifacts>,
+    pub current_version: Option<u32>,
+    pub approved_version: Option<u32>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RiskAnalysisResponse {
     pub analysis: crate::pipeline::proposal::RiskAnalysis,
+    /// Lint findings for objects the proposal creates or renames
+    pub rules_result: RulesResult,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SemanticMapResponse {
-    pub semantic_map: SemanticMap,
+pub struct SemanticMapResponse<T = SemanticMap> {
+    pub semantic_map: T,
 }
 
 #[derive(Debug, Serialize)]
@@ -112,7 +315,68 @@ pub struct DriftResponse {
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionResponse {
     pub success: bool,
-    pub result: crate::pipeline::orchestrator::ExecutionResult,
+    pub result: ExecutionResult,
+    /// Run against scratch clones, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_schema: Option<ScratchRunResult>,
+    /// Read latencies before and during the migration, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_test: Option<LoadTestResult>,
+    /// Run on the canary connection before the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary: Option<ExecutionResult>,
+}
+
+/// Database an interrupted execution is checked against
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyExecutionRequest {
+    /// Connection to the proposal's database, made after the restart
+    pub connection_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStatusResponse {
+    pub running: bool,
+    /// Live progress while an execution runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<ExecutionProgress>,
+    /// Outcome of the most recent finished execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_execution: Option<ExecutionResult>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkResponse {
+    pub link: ShareLink,
+    /// Signed token; only returned when the link is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkListResponse {
+    pub links: Vec<ShareLink>,
+}
+
+/// What a share link shows: the plan, risk and SQL, but no people or discussion
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedProposalResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub status: ProposalStatus,
+    pub changes: Vec<SchemaChange>,
+    pub risk_analysis: Option<crate::pipeline::proposal::RiskAnalysis>,
+    pub migration: MigrationArtifacts,
+    pub updated_at: chrono::DateTime<Utc>,
+    pub link_expires_at: chrono::DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize)]
@@ -129,15 +393,24 @@ pub struct AuditLogResponse {
 /// Build a semantic map of the database schema
 pub async fn build_semantic_map(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
 ) -> Result<Json<SuccessResponse<SemanticMapResponse>>, AppError> {
-    // Build semantic map
+    let project_id = membership::require_connection(&state, &claims, connection_id).await?;
+    let pool = state.connections.get_connection_pool(connection_id).await?;
+    let scope = state.connections.schema_scope(connection_id).await;
+
+    // Build every layer; the background refresher keeps them current from here
     let mirror = MirrorService::new();
-    let semantic_map = mirror.build_semantic_map(connection_id).await?;
+    let semantic_map = mirror.build_semantic_map(&pool, connection_id, &scope).await?;
+    state.semantic_maps.put(semantic_map.clone()).await;
 
     // Log audit
-    let entry = AuditEntry::new(AuditAction::SchemaChanged, "system", "semantic_map", &connection_id.to_string());
-    state.metadata.add_audit_entry(entry).await;
+    let entry = AuditEntry::new(AuditAction::SchemaChanged, "system", "semantic_map", &connection_id.to_string())
+        .with_project(project_id);
+    state.metadata.add_audit_entry(entry).await?;
+
+    invalidate_risk(&state, connection_id, "semantic map rebuild").await;
 
     Ok(Json(SuccessResponse::with_data(
         "Semantic map built",
@@ -145,24 +418,89 @@ pub async fn build_semantic_map(
     )))
 }
 
+/// GET /api/connections/{id}/semantic-map
+/// Stored semantic map, with when each layer was last refreshed
+pub async fn get_semantic_map(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(selection): Query<FieldSelection>,
+) -> Result<Json<SuccessResponse<SemanticMapResponse<serde_json::Value>>>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    let semantic_map = state.semantic_maps.get(connection_id).await.ok_or_else(|| {
+        AppError::NotFound(format!("No semantic map for connection {}; build one first", connection_id))
+    })?;
+    // Every field of a map is always serialized, so its own keys are the known ones
+    selection.check(&fields::keys(&semantic_map), &[] as &[&str])?;
+    let semantic_map = selection.apply(serde_json::to_value(&semantic_map).unwrap_or_default(), Default::default());
+
+    Ok(Json(SuccessResponse::with_data(
+        "Semantic map",
+        SemanticMapResponse { semantic_map },
+    )))
+}
+
+/// POST /api/connections/{id}/semantic-map/layers/{layer}
+/// Refresh one layer of the stored semantic map, leaving the others as they are
+pub async fn refresh_semantic_layer(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((connection_id, layer)): Path<(Uuid, String)>,
+) -> Result<Json<SuccessResponse<SemanticMapResponse>>, AppError> {
+    let project_id = membership::require_connection(&state, &claims, connection_id).await?;
+    let layer = SemanticLayer::parse(&layer).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Unknown layer '{}'; expected schema, statistics, dependencies or hot-spots",
+            layer
+        ))
+    })?;
+    if state.semantic_maps.get(connection_id).await.is_none() {
+        return Err(AppError::NotFound(format!(
+            "No semantic map for connection {}; build one first",
+            connection_id
+        )));
+    }
+
+    let pool = state.connections.get_connection_pool(connection_id).await?;
+    let scope = state.connections.schema_scope(connection_id).await;
+    let data = MirrorService::new().refresh_layer(&pool, &scope, layer).await?;
+    let semantic_map = state
+        .semantic_maps
+        .apply(connection_id, data)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("No semantic map for connection {}", connection_id)))?;
+
+    // Statistics and hot spots do not change what risk analyses see
+    if layer.affects_risk() {
+        let entry = AuditEntry::new(AuditAction::SchemaChanged, "system", "semantic_map", &connection_id.to_string())
+            .with_project(project_id);
+        state.metadata.add_audit_entry(entry).await?;
+        invalidate_risk(&state, connection_id, "semantic map rebuild").await;
+    }
+
+    Ok(Json(SuccessResponse::with_data(
+        "Semantic map layer refreshed",
+        SemanticMapResponse { semantic_map },
+    )))
+}
+
 /// GET /api/connections/{id}/drift
 /// Check for schema drift
 pub async fn check_drift(
-    State(_state): State<SharedState>,
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
 ) -> Result<Json<SuccessResponse<DriftResponse>>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
     let mirror = MirrorService::new();
     
     // Create an empty semantic map for comparison (simplified)
-    let empty_map = SemanticMap {
-        id: Uuid::new_v4(),
-        connection_id,
-        tables: std::collections::HashMap::new(),
-        relationships: Vec::new(),
-        created_at: Utc::now(),
-    };
+    let empty_map = SemanticMap::empty(connection_id);
 
     let result = mirror.check_drift(connection_id, &empty_map).await?;
+    if result.has_drift {
+        invalidate_risk(&state, connection_id, "schema drift").await;
+    }
 
     Ok(Json(SuccessResponse::with_data(
         "Drift check complete",
@@ -173,6 +511,21 @@ pub async fn check_drift(
     )))
 }
 
+/// GET /api/connections/{id}/changelog
+/// Markdown changelog of executed proposals and drift, grouped by table
+pub async fn get_changelog(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(query): Query<ChangelogQuery>,
+) -> Result<Json<SuccessResponse<Changelog>>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    let range = query.range(Utc::now())?;
+    let changelog = changelog::generate(&state, connection_id, range).await?;
+
+    Ok(Json(SuccessResponse::with_data("Changelog generated", changelog)))
+}
+
 // =============================================================================
 // ROUTE HANDLERS - Proposals (Stage 2)
 // =============================================================================
@@ -181,58 +534,268 @@ pub async fn check_drift(
 /// Create a new proposal
 pub async fn create_proposal(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<CreateProposalRequest>,
 ) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    // Proposals live in their connection's project
+    let connection_project = state.connections.project_id(req.connection_id).await;
+    let project_id = membership::resolve_project(req.project_id, connection_project)?;
+    membership::require_project(&state, &claims, project_id).await?;
+
     // Create proposal
     let mut proposal = SchemaProposal::new(
         req.connection_id,
         req.title,
         req.description,
-        "anonymous".to_string(), // TODO: Get from auth
+        claims.sub.clone(),
     );
+    proposal.project_id = project_id;
+
+    // Start from the project's template when no description was written
+    if let Some(project_id) = proposal.project_id {
+        if proposal.description.trim().is_empty() {
+            let template = state.project_service.get_proposal_template(project_id).await?.unwrap_or_default();
+            proposal.description = template.render_description();
+        }
+    }
 
     // Add initial changes if provided
-    for change in req.changes {
+    dictionary::check_changes(&state, proposal.project_id, &req.changes).await?;
+    let mut changes = req.changes;
+    plan_reorders(&state, proposal.connection_id, &mut changes, reorder::DEFAULT_BATCH_SIZE).await?;
+    for change in changes {
         proposal.changes.push(change);
     }
+    access::authorize(&state, &proposal, &claims.sub, AccessAction::Propose).await?;
 
-    // Create summary for metadata store
-    let summary = ProposalSummary {
-        id: proposal.id,
-        connection_id: proposal.connection_id,
-        title: proposal.title.clone(),
-        description: proposal.description.clone(),
-        status: "draft".to_string(),
-        created_by: proposal.created_by.clone(),
-        created_at: proposal.created_at,
-        updated_at: proposal.updated_at,
-        change_count: proposal.changes.len(),
+    // Store the full proposal and a summary for listing
+    let proposal = state.pipeline_proposals.create(proposal).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    // Log audit
+    let entry = AuditEntry::new(
+        AuditAction::ProposalCreated,
+        &proposal.created_by,
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id);
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Proposal created",
+        ProposalResponse { proposal },
+    )))
+}
+
+/// POST /api/connections/:id/partitioning/scaffold
+/// Draft a proposal that swaps a table for a range-partitioned one
+pub async fn scaffold_partitioning(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<ScaffoldRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    let connection_project = state.connections.project_id(connection_id).await;
+    let project_id = membership::resolve_project(req.project_id, connection_project)?;
+    membership::require_project(&state, &claims, project_id).await?;
+
+    let snapshot = state.snapshots.get_latest(connection_id).await?
+        .ok_or_else(|| AppError::NotFound("No snapshots found for this connection".to_string()))?;
+    let scaffold = partitioning::scaffold(&snapshot, &req, Utc::now())?;
+
+    let description = format!(
+        "Partition {} by range without copying rows.\n\n{}",
+        req.table,
+        scaffold.plan.iter().map(|step| format!("- {}", step)).collect::<Vec<_>>().join("\n")
+    );
+    let mut proposal = SchemaProposal::new(
+        connection_id,
+        format!("Partition {}", req.table),
+        description,
+        claims.sub.clone(),
+    );
+    proposal.project_id = project_id;
+
+    dictionary::check_changes(&state, proposal.project_id, &scaffold.changes).await?;
+    proposal.changes = scaffold.changes;
+    access::authorize(&state, &proposal, &claims.sub, AccessAction::Propose).await?;
+
+    let proposal = state.pipeline_proposals.create(proposal).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    let entry = AuditEntry::new(
+        AuditAction::ProposalCreated,
+        &proposal.created_by,
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!("Partitioning scaffold for {}", req.table));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Partitioning proposal drafted",
+        ProposalResponse { proposal },
+    )))
+}
+
+/// POST /api/connections/:id/tags/bulk
+/// Draft one governance proposal tagging every matching table or column
+pub async fn bulk_tag(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<BulkTagRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    let connection_project = state.connections.project_id(connection_id).await;
+    let project_id = membership::resolve_project(req.project_id, connection_project)?;
+    membership::require_project(&state, &claims, project_id).await?;
+
+    let mut snapshot = state.latest_scoped_snapshot(connection_id).await?
+        .ok_or_else(|| AppError::NotFound("No snapshots found for this connection".to_string()))?;
+    dictionary::annotate(&state, &mut snapshot).await?;
+    let changes = dictionary::bulk_tag_changes(&snapshot, &req)?;
+
+    let target = match &req.column_pattern {
+        Some(columns) => format!("columns {} of tables {}", columns, req.table_pattern),
+        None => format!("tables {}", req.table_pattern),
     };
+    let mut proposal = SchemaProposal::new(
+        connection_id,
+        req.title.clone().unwrap_or_else(|| format!("Tag {} objects with {}", changes.len(), req.tag)),
+        format!("Tag {} matching {} with `{}`. Governance only; no SQL runs.", changes.len(), target, req.tag),
+        claims.sub.clone(),
+    );
+    proposal.project_id = project_id;
 
-    state.metadata.add_proposal(summary).await;
+    dictionary::check_changes(&state, proposal.project_id, &changes).await?;
+    proposal.changes = changes;
+    access::authorize(&state, &proposal, &claims.sub, AccessAction::Propose).await?;
+
+    let proposal = state.pipeline_proposals.create(proposal).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
 
-    // Log audit
     let entry = AuditEntry::new(
         AuditAction::ProposalCreated,
         &proposal.created_by,
         "proposal",
         &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!("Bulk tag {} on {}", req.tag, target));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Tagging proposal drafted",
+        ProposalResponse { proposal },
+    )))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropagationProposalRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<i32>,
+}
+
+/// POST /api/connections/{id}/governance/classification-propagation
+/// Draft a governance proposal applying every suggested classification
+pub async fn propose_classification_propagation(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<PropagationProposalRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    let connection_project = state.connections.project_id(connection_id).await;
+    let project_id = membership::resolve_project(req.project_id, connection_project)?;
+    membership::require_project(&state, &claims, project_id).await?;
+
+    let report = propagation::for_connection(&state, connection_id).await?;
+    let changes = report.changes();
+    if changes.is_empty() {
+        return Err(AppError::Validation("Every column fed by a classified column is classified already".to_string()));
+    }
+    let origins: BTreeSet<&str> = report.suggestions.iter().map(|s| s.origin.as_str()).collect();
+    let mut proposal = SchemaProposal::new(
+        connection_id,
+        req.title.clone().unwrap_or_else(|| format!("Propagate PII classification to {} columns", changes.len())),
+        format!(
+            "Classify {} columns that reference or project {}. Governance only; no SQL runs.",
+            changes.len(),
+            origins.into_iter().collect::<Vec<_>>().join(", ")
+        ),
+        claims.sub.clone(),
     );
-    state.metadata.add_audit_entry(entry).await;
+    proposal.project_id = project_id;
+
+    dictionary::check_changes(&state, proposal.project_id, &changes).await?;
+    proposal.changes = changes;
+    access::authorize(&state, &proposal, &claims.sub, AccessAction::Propose).await?;
+
+    let proposal = state.pipeline_proposals.create(proposal).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    let entry = AuditEntry::new(
+        AuditAction::ProposalCreated,
+        &proposal.created_by,
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!("Classification propagation to {} columns", proposal.changes.len()));
+    state.metadata.add_audit_entry(entry).await?;
 
     Ok(Json(SuccessResponse::with_data(
-        "Proposal created",
+        "Classification proposal drafted",
         ProposalResponse { proposal },
     )))
 }
 
+/// Parts of the full proposal `?include=` can add to a summary
+fn includable_proposal_parts() -> Vec<String> {
+    let blank = SchemaProposal::new(Uuid::nil(), String::new(), String::new(), String::new());
+    fields::keys(&blank)
+        .into_iter()
+        .filter(|key| !ProposalSummary::FIELDS.contains(&key.as_str()))
+        .collect()
+}
+
+/// A summary with `selection` applied, including parts of the full proposal
+async fn select_summary(state: &SharedState, summary: &ProposalSummary, selection: &FieldSelection) -> serde_json::Value {
+    let mut included = serde_json::Map::new();
+    if !selection.include().is_empty() {
+        if let Ok(Some(full)) = state.pipeline_proposals.get(summary.id).await {
+            included = selection.included_from(&serde_json::to_value(&full).unwrap_or_default());
+        }
+    }
+    selection.apply(serde_json::to_value(summary).unwrap_or_default(), included)
+}
+
 /// GET /api/proposals
-/// List all proposals
+/// List all proposals; `?fields=` and `?include=` shape each summary
 pub async fn list_proposals(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Query(_query): Query<ProposalListQuery>,
+    Query(selection): Query<FieldSelection>,
 ) -> Result<Json<SuccessResponse<ProposalListResponse>>, AppError> {
-    let proposals = state.metadata.list_proposals().await;
+    selection.check(ProposalSummary::FIELDS, &includable_proposal_parts())?;
+    let now = Utc::now();
+    let visibility = membership::visible_projects(&state, &claims).await?;
+    let mut proposals = Vec::new();
+    for proposal in state.metadata.list_proposals().await? {
+        // Summaries saved before proposals were bound carry no project
+        let project_id = match proposal.project_id {
+            Some(id) => Some(id),
+            None => state.connections.project_id(proposal.connection_id).await,
+        };
+        if membership::can_see(&visibility, project_id) {
+            proposals.push(select_summary(&state, &proposal.with_sla_countdown(now), &selection).await);
+        }
+    }
 
     Ok(Json(SuccessResponse::with_data(
         "Proposals retrieved",
@@ -241,199 +804,2188 @@ pub async fn list_proposals(
 }
 
 /// GET /api/proposals/{id}
-/// Get a specific proposal
+/// Get a specific proposal; `?fields=` and `?include=` shape the summary
 pub async fn get_proposal(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<ProposalSummary>>, AppError> {
+    Query(selection): Query<FieldSelection>,
+) -> Result<Json<SuccessResponse<serde_json::Value>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    selection.check(ProposalSummary::FIELDS, &includable_proposal_parts())?;
     let proposal = state
         .metadata
         .get_proposal(id)
-        .await
+        .await?
         .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
 
+    let now = Utc::now();
+    let mut proposal = proposal.with_sla_countdown(now).with_rollback_countdown(now);
+    if let Some(full) = state.pipeline_proposals.get(id).await? {
+        let snapshot = state.latest_scoped_snapshot(full.connection_id).await?;
+        proposal.references = Resolver::new(&full, snapshot.as_ref()).resolve(&full.description);
+        proposal.executive_summary = Some(summary::current(&full));
+    }
+    let proposal = select_summary(&state, &proposal, &selection).await;
     Ok(Json(SuccessResponse::with_data("Proposal retrieved", proposal)))
 }
 
-/// POST /api/proposals/{id}/changes
-/// Add a change to a proposal
-pub async fn add_change_to_proposal(
-    State(_state): State<SharedState>,
-    Path(_id): Path<Uuid>,
-    Json(_req): Json<AddChangeRequest>,
-) -> Result<Json<SuccessResponse<()>>, AppError> {
-    // TODO: Implement with proper proposal store
-    Err(AppError::Internal("Not implemented yet".to_string()))
-}
-
-/// POST /api/proposals/{id}/migration
-/// Generate migration SQL for a proposal
-pub async fn generate_migration(
-    State(_state): State<SharedState>,
-    Path(_id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<MigrationResponse>>, AppError> {
-    // TODO: Implement with proper proposal store
-    Err(AppError::Internal("Not implemented yet".to_string()))
-}
-
-/// POST /api/proposals/{id}/submit
-/// Submit a proposal for review
-pub async fn submit_for_review(
+/// PATCH /api/proposals/{id}
+/// Partially update a draft proposal using JSON Patch operations
+pub async fn patch_proposal(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<()>>, AppError> {
+    Json(operations): Json<Vec<PatchOperation>>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let warning = state.presence.check_edit(id, &claims.sub).await?;
+    let operation_count = operations.len();
+    let previous = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    let project_teams = teams::for_project(&state, previous.project_id).await?;
+
+    // Assigned teams must exist in the project; try the patch on a copy first
+    if operations.iter().any(|op| op.path.starts_with("/reviewerTeams")) {
+        let mut preview = previous.clone();
+        apply_patch(&mut preview, &operations)?;
+        project_teams.check_slugs(&preview.reviewer_teams)?;
+    }
+
+    let proposal = state
+        .pipeline_proposals
+        .patch(id, operations, &claims.sub)
+        .await?;
+
+    let added_reviewers: Vec<String> = proposal
+        .reviewers
+        .iter()
+        .filter(|r| !previous.reviewers.contains(r))
+        .cloned()
+        .collect();
+    let added_teams: Vec<_> = project_teams
+        .assigned_to(&proposal)
+        .into_iter()
+        .filter(|t| !previous.reviewer_teams.contains(&t.slug))
+        .collect();
+    if !added_reviewers.is_empty() || !added_teams.is_empty() {
+        state.notifier.notify(
+            Notification::ReviewerAssigned {
+                proposal: proposal.clone(),
+                assigned_by: claims.email.clone(),
+            },
+            teams::review_audience(&added_reviewers, &added_teams),
+        );
+    }
+
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
     let entry = AuditEntry::new(
-        AuditAction::ProposalSubmitted,
-        "system",
+        AuditAction::ProposalUpdated,
+        &claims.sub,
         "proposal",
         &id.to_string(),
-    );
-    state.metadata.add_audit_entry(entry).await;
+    )
+    .with_project(project_id)
+    .with_details(&format!(
+        "Applied {} patch operation(s) (revision {})",
+        operation_count,
+        proposal.revisions.len()
+    ));
+    state.metadata.add_audit_entry(entry).await?;
 
-    Ok(Json(SuccessResponse::<()>::message_only("Proposal submitted for review")))
+    Ok(Json(SuccessResponse::with_data(
+        with_edit_warning("Proposal updated", warning),
+        ProposalResponse { proposal },
+    )))
 }
 
-/// POST /api/proposals/{id}/approve
-/// Approve a proposal (Admin only)
-pub async fn approve_proposal(
-    State(state): State<SharedState>,
-    Path(id): Path<Uuid>,
-    Json(_req): Json<ApprovalRequest>,
-) -> Result<Json<SuccessResponse<()>>, AppError> {
-    let entry = AuditEntry::new(
-        AuditAction::ProposalApproved,
-        "admin",
-        "proposal",
-        &id.to_string(),
-    );
-    state.metadata.add_audit_entry(entry).await;
+/// Response message of an edit, noting anyone else editing the proposal
+fn with_edit_warning(message: &str, warning: Option<String>) -> String {
+    match warning {
+        Some(warning) => format!("{}; note: {}", message, warning),
+        None => message.to_string(),
+    }
+}
 
-    Ok(Json(SuccessResponse::<()>::message_only("Proposal approved")))
+/// Plan column reorders against the latest snapshot and the tables' sizes,
+/// refusing tables above the policy's size limit
+async fn plan_reorders(
+    state: &SharedState,
+    connection_id: Uuid,
+    changes: &mut [SchemaChange],
+    batch_size: u32,
+) -> Result<(), AppError> {
+    let max_bytes = state.pipeline_proposals.policy().reorder_max_table_mb.map(|mb| mb * 1024 * 1024);
+    for change in changes.iter_mut() {
+        let SchemaChange::ReorderColumns { table_name, plan: None, .. } = &*change else {
+            continue;
+        };
+        let snapshot = state
+            .latest_scoped_snapshot(connection_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Take a snapshot before reordering columns".to_string()))?;
+        let pool = state.connections.get_pool(connection_id).await?;
+        let size = reorder::table_size(&pool, table_name).await?;
+        *change = reorder::plan(change, &snapshot, size, max_bytes, batch_size)?;
+    }
+    Ok(())
 }
 
-/// POST /api/proposals/{id}/reject
-/// Reject a proposal
-pub async fn reject_proposal(
-    State(state): State<SharedState>,
+/// POST /api/proposals/{id}/changes
+/// Add a change to a proposal
+pub async fn add_change_to_proposal(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AddChangeRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let warning = state.presence.check_edit(id, &claims.sub).await?;
+    let mut proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    dictionary::check_changes(&state, proposal.project_id, std::slice::from_ref(&req.change)).await?;
+    let mut changes = vec![req.change];
+    plan_reorders(
+        &state,
+        proposal.connection_id,
+        &mut changes,
+        req.copy_batch_size.unwrap_or(reorder::DEFAULT_BATCH_SIZE),
+    )
+    .await?;
+
+    // Tightening a nullable column becomes backfill + NOT VALID check + validate
+    if !req.skip_not_null_plan {
+        let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+        let tightened = snapshot
+            .as_ref()
+            .and_then(|s| not_null::tightened_column(&changes[0], s))
+            .map(|(table, column)| (table.to_string(), column.to_string()));
+        if let Some((table, column)) = tightened {
+            let pool = state.connections.get_pool(proposal.connection_id).await?;
+            let estimate = not_null::estimate_nulls(&pool, &table, &column).await?;
+            changes = not_null::plan(
+                &changes[0],
+                &estimate,
+                req.backfill_value.as_deref(),
+                req.backfill_batch_size.unwrap_or(not_null::DEFAULT_BATCH_SIZE),
+            )?;
+        }
+    }
+
+    if req.auto_index_foreign_keys && matches!(changes[0], SchemaChange::AddForeignKey { .. }) {
+        // Only consider the new FK so earlier advisories aren't re-added
+        let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+        proposal.changes.retain(|c| !matches!(c, SchemaChange::AddForeignKey { .. }));
+        proposal.changes.extend(changes.iter().cloned());
+        changes.extend(RiskEngine::missing_fk_indexes(&proposal, snapshot.as_ref()));
+    }
+
+    let proposal = state.pipeline_proposals.add_changes(id, changes).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    Ok(Json(SuccessResponse::with_data(
+        with_edit_warning("Change added", warning),
+        ProposalResponse { proposal },
+    )))
+}
+
+/// POST /api/proposals/{id}/migration
+/// Generate migration SQL for a proposal
+pub async fn generate_migration(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<MigrationResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let migration = match state.connections.database_type(proposal.connection_id).await {
+        DatabaseType::Postgres => Orchestrator::new().generate_migration(&proposal),
+        DatabaseType::MySql => {
+            let current = state.connections.introspect(proposal.connection_id).await?;
+            mysql::generate_migration(&proposal, &current)?
+        }
+    };
+    let updated = state.pipeline_proposals.set_migration(id, migration).await?;
+    let migration = updated
+        .migration
+        .ok_or_else(|| AppError::Internal("Generated migration was not stored".to_string()))?;
+
+    let message = match updated.approved_migration_version {
+        Some(approved) if approved != migration.version => format!(
+            "Migration v{} generated; v{} was approved and is the version that will execute",
+            migration.version, approved
+        ),
+        _ => format!("Migration v{} generated", migration.version),
+    };
+    Ok(Json(SuccessResponse::with_data(message, MigrationResponse { migration })))
+}
+
+/// GET /api/proposals/{id}/migrations
+/// Every migration version generated for a proposal, oldest first
+pub async fn list_migration_versions(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<MigrationHistoryResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Migration versions retrieved",
+        MigrationHistoryResponse {
+            current_version: proposal.migration.as_ref().map(|m| m.version),
+            approved_version: proposal.approved_migration_version,
+            versions: proposal.migration_history,
+        },
+    )))
+}
+
+/// PUT /api/proposals/{id}/parameters/{environment}
+/// Store the placeholder values to execute with in one environment
+pub async fn set_parameter_values(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, environment)): Path<(Uuid, String)>,
+    Json(values): Json<ParameterValues>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot set parameter values".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let environment = environment.trim().to_lowercase();
+    if environment.is_empty() {
+        return Err(AppError::Validation("Environment name cannot be empty".to_string()));
+    }
+
+    let count = values.len();
+    let proposal = state.pipeline_proposals.set_parameter_values(id, &environment, values).await?;
+
+    let entry = AuditEntry::new(AuditAction::ProposalUpdated, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&format!("Set {} parameter value(s) for {}", count, environment));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Parameter values saved",
+        ProposalResponse { proposal },
+    )))
+}
+
+/// POST /api/proposals/{id}/submit
+/// Submit a proposal for review
+pub async fn submit_for_review(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let draft = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    // Only a template the project set is enforced; the default is just a starting point
+    if let Some(project_id) = draft.project_id {
+        if let Some(template) = state.project_service.get_proposal_template(project_id).await? {
+            let problems = template.problems(&draft);
+            if !problems.is_empty() {
+                return Err(AppError::BadRequest(format!(
+                    "Proposal does not follow the project template: {}",
+                    problems.join("; ")
+                )));
+            }
+        }
+    }
+    // Changes may have been added since creation
+    access::authorize(&state, &draft, &claims.sub, AccessAction::Propose).await?;
+    dictionary::check_changes(&state, draft.project_id, &draft.changes).await?;
+
+    let sla = match draft.project_id {
+        Some(project_id) => state.project_service.get_review_sla(project_id).await?,
+        None => None,
+    };
+    let mut proposal = state.pipeline_proposals.submit(id, sla.as_ref()).await?;
+    if let Some(snapshot) = state.latest_scoped_snapshot(proposal.connection_id).await? {
+        let limits = state.connections.blast_radius_limits(proposal.connection_id).await;
+        let report = impact::compute(&proposal, &snapshot, limits);
+        proposal = state.pipeline_proposals.set_blast_radius(id, report).await?;
+    }
+
+    let entry = AuditEntry::new(
+        AuditAction::ProposalSubmitted,
+        &claims.sub,
+        "proposal",
+        &id.to_string(),
+    )
+    .with_project(proposal.project_id);
+    state.metadata.add_audit_entry(entry).await?;
+
+    if features::is_enabled(&state, proposal.project_id, Feature::AutoApproval).await? {
+        proposal = auto_approve(&state, proposal).await?;
+    }
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+    summary::schedule(&state, &proposal);
+
+    let message = if proposal.status == ProposalStatus::Approved {
+        "Proposal submitted and approved automatically"
+    } else {
+        let assigned = teams::for_project(&state, proposal.project_id).await?.assigned_to(&proposal);
+        state.notifier.notify(
+            Notification::ApprovalRequested { proposal: proposal.clone() },
+            teams::review_audience(&proposal.reviewers, &assigned),
+        );
+        "Proposal submitted for review"
+    };
+
+    watches::notify_proposal(&state, &proposal, WatchEvent::Proposed, &claims.sub).await;
+
+    Ok(Json(SuccessResponse::with_data(
+        message,
+        ProposalResponse { proposal },
+    )))
+}
+
+/// Approver recorded on automatic approvals
+const AUTO_APPROVER: &str = "schemaflow:auto-approval";
+
+/// Analyze a freshly submitted proposal and approve it if the risk is low,
+/// it needs no downtime and no rule blocks or errors
+async fn auto_approve(state: &SharedState, proposal: SchemaProposal) -> Result<SchemaProposal, AppError> {
+    let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+    let analysis = RiskEngine::new().analyze(&proposal, snapshot.as_ref())?;
+    let lint = match proposal.project_id {
+        Some(project_id) => state.project_service.get_lint_config(project_id).await?.unwrap_or_default(),
+        None => LintConfig::default(),
+    };
+    let environment = state.connections.environment(proposal.connection_id).await;
+    let rules = state.rules.evaluate_proposal(&proposal, snapshot.as_ref(), &lint, &environment);
+
+    let details = format!("Auto-approved: low risk (score {})", analysis.score);
+    let items = checklist::build(&proposal, &analysis, &rules.violations);
+    let low_risk = analysis.overall_risk == RiskLevel::Low
+        && !analysis.requires_downtime
+        && !rules.has_blockers
+        && !rules.has_errors;
+    let proposal = state
+        .pipeline_proposals
+        .record_analysis(proposal.id, items, analysis, AUTO_APPROVER)
+        .await?;
+    // Nobody has had the chance to tick required checklist items yet
+    let eligible = low_risk && proposal.unchecked_required_items().is_empty();
+    if !eligible {
+        return Ok(proposal);
+    }
+
+    let environment = state.connections.environment(proposal.connection_id).await;
+    let proposal = state
+        .pipeline_proposals
+        .approve(proposal.id, AUTO_APPROVER, environment.key())
+        .await?;
+    let entry = AuditEntry::new(
+        AuditAction::ProposalApproved,
+        AUTO_APPROVER,
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&details);
+    state.metadata.add_audit_entry(entry).await?;
+    Ok(proposal)
+}
+
+/// GET /api/proposals/{id}/blast-radius
+/// Blast radius report saved when the proposal was submitted
+pub async fn get_blast_radius(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<BlastRadiusReport>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    let report = proposal.blast_radius.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Proposal {} has no blast radius report; it is computed on submission from the latest snapshot",
+            id
+        ))
+    })?;
+
+    Ok(Json(SuccessResponse::with_data("Blast radius retrieved", report)))
+}
+
+/// POST /api/proposals/{id}/approve
+/// Approve a proposal (Admin only)
+pub async fn approve_proposal(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ApprovalRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let pending = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    // With reviewer teams assigned, their members approve instead of admins
+    let assigned = teams::for_project(&state, pending.project_id).await?.assigned_to(&pending);
+    if assigned.is_empty() && !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can approve proposals".to_string()));
+    }
+    access::authorize(&state, &pending, &claims.sub, AccessAction::Approve).await?;
+
+    // Reviewers approve knowing whether the unique constraints can be created
+    match duplicate_checks(&state, &pending).await {
+        Ok(checks) if !checks.is_empty() => {
+            state.pipeline_proposals.set_duplicate_checks(id, checks).await?;
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Skipped duplicate checks for proposal {}: {}", id, e),
+    }
+
+    let environment = state.connections.environment(pending.connection_id).await;
+    let proposal = if assigned.is_empty() {
+        state.pipeline_proposals.approve(id, &claims.sub, environment.key()).await?
+    } else {
+        state
+            .pipeline_proposals
+            .approve_for_teams(id, &claims.sub, &assigned, environment.key())
+            .await?
+    };
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    let approved = proposal.status == ProposalStatus::Approved;
+    let mut details = Vec::new();
+    if !assigned.is_empty() {
+        let own: Vec<&str> = assigned.iter().filter(|t| t.is_member(&claims.sub)).map(|t| t.slug.as_str()).collect();
+        details.push(format!("On behalf of team(s) {}", own.join(", ")));
+    }
+    if let Some(comment) = &req.comment {
+        details.push(comment.clone());
+    }
+    let action = if approved { AuditAction::ProposalApproved } else { AuditAction::TeamApprovalRecorded };
+    let mut entry = AuditEntry::new(
+        action,
+        &claims.sub,
+        "proposal",
+        &id.to_string(),
+    )
+    .with_project(proposal.project_id);
+    if !details.is_empty() {
+        entry = entry.with_details(&details.join(": "));
+    }
+    state.metadata.add_audit_entry(entry).await?;
+
+    let message = if approved { "Proposal approved" } else { "Team approval recorded; waiting for other reviewers" };
+    Ok(Json(SuccessResponse::with_data(
+        message,
+        ProposalResponse { proposal },
+    )))
+}
+
+/// GET /api/proposals/{id}/team-reviews
+/// Approval progress of each reviewer team assigned to a proposal
+pub async fn get_team_reviews(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<Vec<TeamReview>>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let assigned = teams::for_project(&state, proposal.project_id).await?.assigned_to(&proposal);
+    let reviews = teams::reviews(&proposal, &assigned);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Team reviews retrieved",
+        reviews,
+    )))
+}
+
+/// POST /api/proposals/{id}/reject
+/// Reject a proposal
+pub async fn reject_proposal(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-    Json(_req): Json<RejectionRequest>,
-) -> Result<Json<SuccessResponse<()>>, AppError> {
+    Json(req): Json<RejectionRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can reject proposals".to_string()));
+    }
+    membership::require_proposal(&state, &claims, id).await?;
+
+    let proposal = state.pipeline_proposals.reject(id).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
     let entry = AuditEntry::new(
         AuditAction::ProposalRejected,
-        "admin",
+        &claims.sub,
         "proposal",
         &id.to_string(),
-    );
-    state.metadata.add_audit_entry(entry).await;
+    )
+    .with_project(proposal.project_id)
+    .with_details(&req.reason);
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Proposal rejected",
+        ProposalResponse { proposal },
+    )))
+}
+
+/// POST /api/proposals/{id}/comments
+/// Add a comment to a proposal
+pub async fn add_comment(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CommentRequest>,
+) -> Result<Json<SuccessResponse<AnnotatedComment>>, AppError> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    if req.content.trim().is_empty() {
+        return Err(AppError::Validation("Comment must not be empty".to_string()));
+    }
+
+    let target = match (req.change_index, req.statement_index) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::BadRequest(
+                "A comment targets a change or a statement, not both".to_string(),
+            ));
+        }
+        (Some(index), None) => CommentTarget::Change { index },
+        (None, Some(index)) => CommentTarget::Statement { index },
+        (None, None) => CommentTarget::Proposal,
+    };
+    let comment = state
+        .pipeline_proposals
+        .add_comment(
+            id,
+            Comment {
+                id: Uuid::new_v4(),
+                author: claims.sub.clone(),
+                content: req.content,
+                target,
+                reply_to: req.reply_to,
+                resolved_by: None,
+                resolved_at: None,
+                created_at: Utc::now(),
+            },
+        )
+        .await?;
+
+    let entry = AuditEntry::new(AuditAction::CommentAdded, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&comment.content);
+    state.metadata.add_audit_entry(entry).await?;
+
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+    let comment = Resolver::new(&proposal, snapshot.as_ref()).annotate(comment);
+
+    Ok(Json(SuccessResponse::with_data("Comment added", comment)))
+}
+
+/// GET /api/proposals/{id}/comments
+/// Comments with their schema object references resolved
+pub async fn list_comments(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<Vec<AnnotatedComment>>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+    let resolver = Resolver::new(&proposal, snapshot.as_ref());
+    let comments: Vec<AnnotatedComment> = proposal.comments.iter().cloned().map(|c| resolver.annotate(c)).collect();
+
+    Ok(Json(SuccessResponse::with_data(
+        format!("{} comment(s)", comments.len()),
+        comments,
+    )))
+}
+
+/// POST /api/proposals/{id}/comments/{comment_id}/resolve
+/// Resolve or reopen a comment thread (its author or an admin)
+pub async fn resolve_comment_thread(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<ResolveThreadRequest>,
+) -> Result<Json<SuccessResponse<Comment>>, AppError> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let comment = state
+        .pipeline_proposals
+        .resolve_thread(id, comment_id, &claims.sub, claims.role.can_approve(), req.resolved)
+        .await?;
+
+    let entry = AuditEntry::new(AuditAction::CommentThreadResolved, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&format!(
+            "Thread {} {}",
+            comment_id,
+            if req.resolved { "resolved" } else { "reopened" }
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        if req.resolved { "Thread resolved" } else { "Thread reopened" },
+        comment,
+    )))
+}
+
+/// GET /api/proposals/{id}/statements
+/// Migration statements with their sign-offs and open threads
+pub async fn list_statement_reviews(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<StatementReviewResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let open_threads = proposal.open_statement_threads();
+    let statements = proposal
+        .statements()
+        .into_iter()
+        .enumerate()
+        .map(|(index, sql)| StatementReview {
+            index,
+            approvals: proposal.statement_approvals_for(index, &sql).into_iter().cloned().collect(),
+            open_threads: open_threads
+                .iter()
+                .filter(|c| matches!(c.target, CommentTarget::Statement { index: i } if i == index))
+                .count(),
+            sql,
+        })
+        .collect();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Statements retrieved",
+        StatementReviewResponse {
+            statements,
+            approval_blockers: proposal.approval_blockers(state.pipeline_proposals.policy()),
+        },
+    )))
+}
+
+/// POST /api/proposals/{id}/statements/{index}/approve
+/// Sign off one migration statement (Admin only)
+pub async fn approve_statement(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, index)): Path<(Uuid, usize)>,
+    Json(req): Json<ApprovalRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can sign off statements".to_string()));
+    }
+    membership::require_proposal(&state, &claims, id).await?;
+    let pending = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    access::authorize(&state, &pending, &claims.sub, AccessAction::Approve).await?;
+
+    let proposal = state
+        .pipeline_proposals
+        .approve_statement(id, index, &claims.sub, req.comment.clone())
+        .await?;
+
+    let mut details = format!("Signed off statement {}", index + 1);
+    if let Some(comment) = &req.comment {
+        details.push_str(&format!(": {}", comment));
+    }
+    let entry = AuditEntry::new(AuditAction::StatementApproved, &claims.sub, "proposal", &id.to_string())
+        .with_project(proposal.project_id)
+        .with_details(&details);
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Statement signed off",
+        ProposalResponse { proposal },
+    )))
+}
+
+/// GET /api/proposals/{id}/checklist
+/// Review checklist built by the latest risk analysis
+pub async fn get_checklist(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ChecklistResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Checklist retrieved",
+        ChecklistResponse {
+            approval_blockers: proposal.approval_blockers(state.pipeline_proposals.policy()),
+            items: proposal.checklist,
+        },
+    )))
+}
+
+/// PUT /api/proposals/{id}/checklist/{item_id}
+/// Tick or untick a checklist item (Admin only)
+pub async fn check_checklist_item(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, item_id)): Path<(Uuid, String)>,
+    Json(req): Json<ChecklistTickRequest>,
+) -> Result<Json<SuccessResponse<ChecklistResponse>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can tick checklist items".to_string()));
+    }
+    membership::require_proposal(&state, &claims, id).await?;
+    let pending = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    access::authorize(&state, &pending, &claims.sub, AccessAction::Approve).await?;
+
+    let (proposal, item) = state
+        .pipeline_proposals
+        .check_item(id, &item_id, &claims.sub, req.checked, req.note.clone())
+        .await?;
+
+    let action = match req.checked {
+        true => AuditAction::ChecklistItemChecked,
+        false => AuditAction::ChecklistItemUnchecked,
+    };
+    let mut details = format!("{}: {}", item.id, item.text);
+    if let Some(note) = &req.note {
+        details.push_str(&format!(" ({})", note));
+    }
+    let entry = AuditEntry::new(action, &claims.sub, "proposal", &id.to_string())
+        .with_project(proposal.project_id)
+        .with_details(&details);
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        if req.checked { "Checklist item ticked" } else { "Checklist item unticked" },
+        ChecklistResponse {
+            approval_blockers: proposal.approval_blockers(state.pipeline_proposals.policy()),
+            items: proposal.checklist,
+        },
+    )))
+}
+
+// =============================================================================
+// ROUTE HANDLERS - Risk Analysis (Stage 3)
+// =============================================================================
+
+/// POST /api/proposals/{id}/analyze
+/// Analyze the risk of a proposal
+pub async fn analyze_risk(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<SuccessResponse<RiskAnalysisResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    // Compare against the latest known schema, if a snapshot exists
+    let snapshot = state.latest_scoped_snapshot(proposal.connection_id).await?;
+
+    let engine = RiskEngine::new();
+    let mut analysis = engine.analyze(&proposal, snapshot.as_ref())?;
+    match duplicate_checks(&state, &proposal).await {
+        Ok(checks) => uniqueness::attach(&mut analysis, checks),
+        Err(e) => tracing::warn!("Skipped duplicate checks for proposal {}: {}", proposal.id, e),
+    }
+
+    let lint = match proposal.project_id {
+        Some(project_id) => state.project_service.get_lint_config(project_id).await?.unwrap_or_default(),
+        None => LintConfig::default(),
+    };
+    let environment = state.connections.environment(proposal.connection_id).await;
+    let mut rules_result = state.rules.evaluate_proposal(&proposal, snapshot.as_ref(), &lint, &environment);
+
+    // Keep the evaluation for project analytics; losing one is not worth failing the analysis
+    if let Some(project_id) = proposal.project_id {
+        let record = RuleEvaluationRecord::new(&proposal, project_id, analysis.score, &rules_result);
+        if let Err(e) = state.project_service.record_rule_evaluation(&record).await {
+            tracing::warn!("Failed to record rules evaluation for proposal {}: {}", proposal.id, e);
+        }
+    }
+
+    let items = checklist::build(&proposal, &analysis, &rules_result.violations);
+    let proposal = state
+        .pipeline_proposals
+        .record_analysis(proposal.id, items, analysis.clone(), &claims.sub)
+        .await?;
+    summary::schedule(&state, &proposal);
+
+    // Stored in English; only the response is translated
+    let language = state.translations.language_for(&headers);
+    analysis.localize(&state.translations, &language);
+    rules_result.localize(&state.translations, &language);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Risk analysis complete",
+        RiskAnalysisResponse { analysis, rules_result },
+    )))
+}
+
+/// Duplicate checks for the unique constraints and indexes a proposal adds
+async fn duplicate_checks(state: &SharedState, proposal: &SchemaProposal) -> Result<Vec<DuplicateCheck>, AppError> {
+    if uniqueness::unique_targets(&proposal.changes).is_empty() {
+        return Ok(Vec::new());
+    }
+    let pool = state.connections.get_pool(proposal.connection_id).await?;
+    uniqueness::check(&pool, proposal).await
+}
+
+/// GET /api/proposals/{id}/risk-history
+/// Every risk analysis of a proposal, with what changed between revisions
+pub async fn get_risk_history(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<Vec<RiskHistoryEntry>>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Risk history retrieved",
+        risk_history::compare(&proposal.risk_history),
+    )))
+}
+
+// =============================================================================
+// ROUTE HANDLERS - Execution (Stage 4)
+// =============================================================================
+
+/// POST /api/proposals/{id}/execute
+/// Execute a proposal's migration
+pub async fn execute_proposal(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<ExecuteRequest>,
+) -> Result<Json<SuccessResponse<ExecutionResponse>>, AppError> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let start_at = if req.resume {
+        match (&proposal.status, &proposal.last_execution) {
+            (ProposalStatus::Failed, Some(last)) => last.checkpoint,
+            (ProposalStatus::Executing, Some(last)) if last.paused_at_gate.is_some() => last.checkpoint,
+            _ => return Err(AppError::BadRequest("Only a failed or paused execution can be resumed".to_string())),
+        }
+    } else {
+        0
+    };
+
+    // Dry runs are allowed at any stage; real executions need a current approval
+    if !req.dry_run {
+        if !req.resume && proposal.status != ProposalStatus::Approved {
+            return Err(AppError::BadRequest("Proposal must be approved before execution".to_string()));
+        }
+        if proposal.approval_expired(Utc::now()) {
+            return Err(AppError::BadRequest(
+                "Approval has expired; the proposal must be re-approved".to_string()
+            ));
+        }
+    }
+
+    let orchestrator = Orchestrator::new()
+        .with_monitor(state.executions.clone())
+        .with_journal(state.journal.clone());
+
+    // Keep the SQL that actually ran so the proposal can be reverted later.
+    // Placeholders are filled in with the values stored for the target's
+    // environment, which are fixed once approved; a resumed run keeps the
+    // values and SQL it started with.
+    let template = templating::is_templated(&proposal).then(|| proposal.clone());
+    let (proposal, template_values) = match &template {
+        Some(source) => {
+            let provided = match (req.resume, &source.last_execution) {
+                (true, Some(last)) => last.template_values.clone(),
+                _ => ParameterValues::new(),
+            };
+            let environment = state.connections.environment(source.connection_id).await;
+            let (mut rendered, values) = templating::render_for(source, environment.key(), &provided)?;
+            // Approval pinned the rendered migration; only dry runs before
+            // approval render a fresh one
+            let pinned = req.resume || source.approved_migration_version.is_some();
+            rendered.migration = match source.migration_for_run(req.resume).filter(|_| pinned) {
+                Some(migration) => Some(migration.clone()),
+                None => {
+                    let migration = orchestrator.generate_migration(&rendered);
+                    state.pipeline_proposals.set_migration(id, migration).await?.migration
+                }
+            };
+            (rendered, values)
+        }
+        // Run the version that was approved (or started), not whatever was
+        // generated last
+        None => match proposal.migration_for_run(req.resume).cloned() {
+            Some(migration) => {
+                let mut proposal = proposal;
+                proposal.migration = Some(migration);
+                (proposal, ParameterValues::new())
+            }
+            None => {
+                let migration = orchestrator.generate_migration(&proposal);
+                (state.pipeline_proposals.set_migration(id, migration).await?, ParameterValues::new())
+            }
+        },
+    };
+
+    // Passing a confirmation gate takes its label, so nobody steps past one by accident
+    let gate = proposal
+        .migration
+        .as_ref()
+        .and_then(|m| split_statements(&m.up_sql).get(start_at).and_then(|s| reorder::gate_label(s).map(str::to_string)));
+    if let (Some(label), false) = (&gate, req.dry_run) {
+        if req.confirm_gate.as_deref() != Some(label.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Execution is paused at confirmation gate '{}'; resume with confirmGate set to it",
+                label
+            )));
+        }
+    }
+
+    if req.deep {
+        if !req.dry_run {
+            return Err(AppError::BadRequest("deep only applies to dry runs".to_string()));
+        }
+        // It runs the migration against the real data and holds real locks while it lasts
+        if !claims.role.can_execute() {
+            return Err(AppError::Forbidden("Only admins can run a deep dry run".to_string()));
+        }
+    }
+    if req.temp_schema {
+        if !req.dry_run {
+            return Err(AppError::BadRequest("tempSchema only applies to dry runs".to_string()));
+        }
+        features::require(&state, project_id, Feature::TempSchemaDryRun).await?;
+    }
+    if let Some(load) = &req.load_test {
+        if !req.dry_run {
+            return Err(AppError::BadRequest("loadTest only applies to dry runs".to_string()));
+        }
+        load.validate()?;
+        features::require(&state, project_id, Feature::TempSchemaDryRun).await?;
+    }
+    let canary_pool = match req.canary_connection_id {
+        Some(canary_id) => {
+            if req.dry_run || req.resume {
+                return Err(AppError::BadRequest(
+                    "A canary run only applies to new, real executions".to_string()
+                ));
+            }
+            if canary_id == proposal.connection_id {
+                return Err(AppError::BadRequest("The canary must be a different connection".to_string()));
+            }
+            features::require(&state, project_id, Feature::CanaryExecution).await?;
+            membership::require_connection(&state, &claims, canary_id).await?;
+            Some(state.connections.get_pool(canary_id).await?)
+        }
+        None => None,
+    };
+
+    let pool = state.connections.get_pool(proposal.connection_id).await?;
+    let _permit = quota::begin_execution(&state, project_id, &claims, &headers).await?;
+
+    // A unique constraint over duplicate keys would fail halfway through the run
+    if !req.dry_run {
+        let checks = uniqueness::check(&pool, &proposal).await?;
+        if let Some(check) = checks.iter().find(|c| c.duplicate_keys > 0) {
+            let message = format!(
+                "{} cannot be created: {} key(s) in {} occur more than once",
+                check.name, check.duplicate_keys, check.table
+            );
+            state.pipeline_proposals.set_duplicate_checks(id, checks).await?;
+            return Err(AppError::Conflict(message));
+        }
+        if !checks.is_empty() {
+            state.pipeline_proposals.set_duplicate_checks(id, checks).await?;
+        }
+    }
+
+    let execution_policy = match project_id {
+        Some(project_id) => state.project_service.get_execution_policy(project_id).await?.unwrap_or_default(),
+        None => Default::default(),
+    };
+    // The project may want a recent whole-database backup before destroying anything
+    let verified_backup = match req.dry_run || req.resume {
+        true => None,
+        false => backup::require_recent(&state, &execution_policy, &proposal).await?,
+    };
+    let options = ExecutionOptions {
+        dry_run: req.dry_run,
+        deep: req.deep,
+        chunk_size: req.chunk_size.or(state.pipeline_proposals.policy().execution_chunk_size),
+        start_at,
+        stop_at: None,
+        lock_wait: Some(advisory_lock::wait_for(req.lock_wait_seconds)),
+        settings: execution_policy.settings,
+        watchdog: execution_policy.watchdog,
+    };
+
+    // Destructive changes are backed up first; without a backup nothing runs
+    let backup = match (&state.backup, req.dry_run) {
+        (Some(hook), false) => {
+            let connection = state
+                .connections
+                .get_connection(proposal.connection_id)
+                .await
+                .ok_or_else(|| AppError::NotFound(format!("Connection {} not found", proposal.connection_id)))?;
+            match BackupRequest::for_changes(id, connection.params.clone(), &proposal.changes) {
+                Some(request) => Some(hook.run(&request).await.map_err(|e| {
+                    AppError::Internal(format!("Backup failed, so the proposal was not executed: {}", e))
+                })?),
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    // A full backup taken here counts towards the next run's requirement
+    if let Some(reference) = backup.as_ref().filter(|b| b.is_full()) {
+        if let Err(e) = state.project_service.record_backup(proposal.connection_id, reference, &claims.sub).await {
+            tracing::warn!("Failed to record the backup taken for proposal {}: {}", id, e);
+        }
+    }
+
+    // The target is only touched once the canary took the migration cleanly
+    let canary = match (&canary_pool, req.canary_connection_id) {
+        (Some(canary_pool), Some(canary_id)) => {
+            // The canary gets the values of its own environment
+            let canary_proposal = match &template {
+                Some(source) => {
+                    let environment = state.connections.environment(canary_id).await;
+                    let (mut rendered, _) = templating::render_for(source, environment.key(), &ParameterValues::new())?;
+                    rendered.migration = Some(orchestrator.generate_migration(&rendered));
+                    rendered
+                }
+                None => proposal.clone(),
+            };
+            let canary = Orchestrator::new().execute(canary_pool, &canary_proposal, options.clone()).await?;
+            if !canary.success {
+                let entry = AuditEntry::new(
+                    AuditAction::ProposalExecuted,
+                    &claims.sub,
+                    "proposal",
+                    &id.to_string(),
+                )
+                .with_project(project_id)
+                .with_details(&format!(
+                    "Canary run failed; target not executed: {}",
+                    canary.error.as_deref().unwrap_or("unknown error")
+                ));
+                state.metadata.add_audit_entry(entry).await?;
+                return Err(AppError::Conflict(format!(
+                    "Canary execution failed, so the target was not touched: {}",
+                    canary.error.unwrap_or_default()
+                )));
+            }
+            Some(canary)
+        }
+        _ => None,
+    };
+
+    let mut result = orchestrator.execute(&pool, &proposal, options).await?;
+    result.backup = backup;
+    result.template_values = template_values;
+
+    let (temp_schema, load_test) = if req.temp_schema || req.load_test.is_some() {
+        let statements = proposal.migration.as_ref().map(|m| split_statements(&m.up_sql)).unwrap_or_default();
+        let tables = proposal
+            .risk_analysis
+            .as_ref()
+            .map(|a| a.affected_tables.clone())
+            .or_else(|| RiskEngine::new().analyze(&proposal, None).ok().map(|a| a.affected_tables))
+            .unwrap_or_default();
+        let temp_schema = match req.temp_schema {
+            true => Some(scratch::dry_run(&pool, &statements[start_at..], &tables).await?),
+            false => None,
+        };
+        let load_test = match &req.load_test {
+            Some(load) => Some(load_test::run(&pool, &statements[start_at..], &tables, load).await?),
+            None => None,
+        };
+        (temp_schema, load_test)
+    } else {
+        (None, None)
+    };
+
+    // Keep the latest estimates on the proposal for reviewers
+    if let Some(summary) = &result.cost_summary {
+        state.pipeline_proposals.set_cost_summary(id, summary.clone()).await?;
+    }
+
+    let mut custom_fields = 0;
+    if !req.dry_run {
+        let updated = state.pipeline_proposals.mark_executed(id, &result).await?;
+        state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+
+        state.notifier.notify(
+            Notification::ExecutionFinished {
+                proposal: updated.clone(),
+                result: Box::new(result.clone()),
+            },
+            Audience::Users(vec![updated.created_by.clone()]),
+        );
+
+        if let Some(row_counts) = result.row_counts.as_ref().filter(|v| !v.passed()) {
+            state.notifier.notify(
+                Notification::RowCountAnomaly { proposal: updated.clone(), anomalies: row_counts.anomalies.clone() },
+                Audience::Admins,
+            );
+        }
+
+        if result.success && result.paused_at_gate.is_none() {
+            custom_fields = dictionary::record_changes(&state, &updated).await?;
+            refresh::schedule_refresh(&state, &updated);
+            watches::notify_proposal(&state, &updated, WatchEvent::Executed, &claims.sub).await;
+        }
+    }
+
+    let mut entry = AuditEntry::new(
+        AuditAction::ProposalExecuted,
+        &claims.sub,
+        "proposal",
+        &id.to_string(),
+    )
+    .with_project(project_id);
+    let mut details = Vec::new();
+    if start_at > 0 {
+        details.push(format!(
+            "Resumed at statement {}; checkpoint {}/{}",
+            start_at + 1,
+            result.checkpoint,
+            result.total_statements
+        ));
+    }
+    if let (Some(label), false) = (&gate, req.dry_run) {
+        details.push(format!("Confirmed gate '{}'", label));
+    }
+    if let Some(label) = &result.paused_at_gate {
+        details.push(format!("Paused at confirmation gate '{}'", label));
+    }
+    if let Some(verified) = &verified_backup {
+        details.push(format!(
+            "Verified {} backup {} completed {}",
+            verified.backup.provider, verified.backup.location, verified.backup.completed_at
+        ));
+    }
+    if let Some(backup) = &result.backup {
+        details.push(format!("Backed up via {} to {}", backup.provider, backup.location));
+    }
+    if let Some(canary_id) = req.canary_connection_id {
+        details.push(format!("Canary run succeeded on connection {}", canary_id));
+    }
+    if !result.template_values.is_empty() {
+        let values: Vec<String> = result.template_values.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        details.push(format!("Rendered with {}", values.join(", ")));
+    }
+    if custom_fields > 0 {
+        details.push(format!("Recorded {} custom field value(s)", custom_fields));
+    }
+    if let Some(row_counts) = result.row_counts.as_ref().filter(|v| !v.passed()) {
+        details.push(format!("Unexpected row count changes: {}", row_counts.anomalies.join(", ")));
+    }
+    if !details.is_empty() {
+        entry = entry.with_details(&details.join("; "));
+    }
+    state.metadata.add_audit_entry(entry).await?;
+
+    // Archiving is best-effort; the execution itself already happened
+    if !req.dry_run {
+        if let Some(archive) = &state.archive {
+            if let Err(e) = archive.archive_execution(&proposal, &result).await {
+                tracing::warn!("Failed to archive execution of proposal {}: {}", id, e);
+            }
+        }
+    }
+
+    Ok(Json(SuccessResponse::with_data(
+        match (req.dry_run, &result.paused_at_gate) {
+            (true, _) => "Dry run complete",
+            (false, Some(_)) => "Execution paused at a confirmation gate",
+            (false, None) => "Proposal executed",
+        },
+        ExecutionResponse {
+            success: result.success
+                && temp_schema.as_ref().is_none_or(|run| run.success)
+                && load_test.as_ref().is_none_or(|run| run.migration.success),
+            result,
+            temp_schema,
+            load_test,
+            canary,
+        },
+    )))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakGlassResponse {
+    pub proposal: SchemaProposal,
+    pub result: ExecutionResult,
+}
+
+/// POST /api/connections/{id}/break-glass
+/// Run changes at once, without review, during an incident. Admin only; the
+/// changes become a retroactive proposal that owes a retrospective review,
+/// and every admin is notified.
+pub async fn break_glass(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<BreakGlassRequest>,
+) -> Result<Json<SuccessResponse<BreakGlassResponse>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can make break-glass changes".to_string()));
+    }
+    let project_id = membership::require_connection(&state, &claims, connection_id).await?;
+    if req.title.trim().is_empty() || req.changes.is_empty() {
+        return Err(AppError::BadRequest("A break-glass change needs a title and at least one change".to_string()));
+    }
+    templating::validate_declaration(&[], &req.changes)?;
+
+    let policy = state.pipeline_proposals.policy();
+    let record = BreakGlass::new(&claims.sub, &req.justification, Utc::now(), policy.break_glass_review_days)?;
+    let mut proposal = break_glass::proposal(connection_id, project_id, &req.title, req.changes, record);
+
+    // The retrospective reviewer sees the risk that was taken
+    let snapshot = state.latest_scoped_snapshot(connection_id).await?;
+    let analysis = RiskEngine::new().analyze(&proposal, snapshot.as_ref())?;
+    proposal.set_risk_analysis(analysis, &claims.sub);
+
+    let orchestrator = Orchestrator::new()
+        .with_monitor(state.executions.clone())
+        .with_journal(state.journal.clone());
+    proposal.record_migration(orchestrator.generate_migration(&proposal));
+
+    let pool = state.connections.get_pool(connection_id).await?;
+    let _permit = quota::begin_execution(&state, project_id, &claims, &headers).await?;
+    let execution_policy = match project_id {
+        Some(project_id) => state.project_service.get_execution_policy(project_id).await?.unwrap_or_default(),
+        None => Default::default(),
+    };
+    let options = ExecutionOptions {
+        dry_run: false,
+        deep: false,
+        chunk_size: policy.execution_chunk_size,
+        start_at: 0,
+        stop_at: None,
+        lock_wait: Some(advisory_lock::wait_for(None)),
+        settings: execution_policy.settings,
+        watchdog: execution_policy.watchdog,
+    };
+
+    // Recorded before anything runs, so the change is on file whatever happens
+    let proposal = state.pipeline_proposals.create(proposal).await?;
+    let id = proposal.id;
+    let justification = proposal.break_glass.as_ref().map(|b| b.justification.clone()).unwrap_or_default();
+    let audit = |details: String| {
+        AuditEntry::new(AuditAction::BreakGlassExecuted, &claims.sub, "proposal", &id.to_string())
+            .with_project(project_id)
+            .with_details(&format!("{}; justification: {}", details, justification))
+    };
+
+    let result = match orchestrator.execute(&pool, &proposal, options).await {
+        Ok(result) => result,
+        Err(e) => {
+            let failed = state
+                .pipeline_proposals
+                .update(id, |failed| failed.set_status(ProposalStatus::Failed, Utc::now()))
+                .await?;
+            state.metadata.add_proposal(ProposalSummary::from(&failed)).await;
+            state.metadata.add_audit_entry(audit(format!("Execution could not start: {}", e))).await?;
+            state.notifier.notify(Notification::BreakGlassUsed { proposal: failed }, Audience::Admins);
+            return Err(e);
+        }
+    };
+
+    let updated = state.pipeline_proposals.mark_executed(id, &result).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+    let outcome = match (result.success, &result.paused_at_gate) {
+        (true, Some(label)) => format!("Paused at confirmation gate '{}'", label),
+        (true, None) => format!("Executed {} statement(s)", result.executed_statements.len()),
+        (false, _) => format!("Failed: {}", result.error.as_deref().unwrap_or("unknown error")),
+    };
+    state.metadata.add_audit_entry(audit(outcome)).await?;
+    state.notifier.notify(Notification::BreakGlassUsed { proposal: updated.clone() }, Audience::Admins);
+
+    if result.success && result.paused_at_gate.is_none() {
+        dictionary::record_changes(&state, &updated).await?;
+        refresh::schedule_refresh(&state, &updated);
+        watches::notify_proposal(&state, &updated, WatchEvent::Executed, &claims.sub).await;
+    }
+
+    Ok(Json(SuccessResponse::with_data(
+        if result.success { "Break-glass change executed; a retrospective review is due" } else { "Break-glass change failed" },
+        BreakGlassResponse { proposal: updated, result },
+    )))
+}
+
+/// POST /api/proposals/{id}/retrospective
+/// Record the retrospective review of a break-glass change. Admin only, and
+/// not the admin who made the change.
+pub async fn review_retrospective(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RetrospectiveRequest>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can review break-glass changes".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state.pipeline_proposals.record_retrospective(id, &claims.sub, &req.notes).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+
+    let entry = AuditEntry::new(AuditAction::RetrospectiveReviewed, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(req.notes.trim());
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Retrospective recorded", ProposalResponse { proposal })))
+}
+
+/// GET /api/break-glass
+/// Break-glass changes the caller can see, unreviewed first (`?pending=true`
+/// for those still waiting)
+pub async fn list_break_glass(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BreakGlassQuery>,
+) -> Result<Json<SuccessResponse<Vec<BreakGlassEntry>>>, AppError> {
+    let visibility = membership::visible_projects(&state, &claims).await?;
+    let proposals: Vec<SchemaProposal> = state
+        .pipeline_proposals
+        .list()
+        .await?
+        .into_iter()
+        .filter(|p| membership::can_see(&visibility, p.project_id))
+        .collect();
+    let entries = break_glass::retrospectives(&proposals, query.pending, Utc::now());
+
+    Ok(Json(SuccessResponse::with_data(
+        format!("{} break-glass change(s)", entries.len()),
+        entries,
+    )))
+}
+
+/// GET /api/proposals/{id}/preflight
+/// Whether the connection's role holds the privileges each migration statement needs
+pub async fn get_permission_preflight(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<PermissionReport>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    let migration = proposal
+        .migration
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Generate the migration before checking permissions".to_string()))?;
+
+    let pool = state.connections.get_pool(proposal.connection_id).await?;
+    let client = pool.get().await?;
+    let report = preflight::check(&client, &split_statements(&migration.up_sql), 0).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        if report.passed() { "The role can run every statement" } else { "The role is missing privileges" },
+        report,
+    )))
+}
+
+/// GET /api/proposals/{id}/execution
+/// Progress of a running execution, or the last one's outcome
+pub async fn get_execution_status(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ExecutionStatusResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    let progress = state.executions.get(id).await;
+
+    Ok(Json(SuccessResponse::with_data(
+        if progress.is_some() { "Execution in progress" } else { "No execution running" },
+        ExecutionStatusResponse {
+            running: progress.is_some(),
+            progress,
+            last_execution: proposal.last_execution,
+        },
+    )))
+}
+
+/// GET /api/executions/interrupted
+/// Executions the server stopped in the middle of, awaiting verification or a resume
+pub async fn list_interrupted_executions(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<SuccessResponse<Vec<ExecutionRecord>>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can list interrupted executions".to_string()));
+    }
+    let records = state.journal.interrupted().await?;
 
-    Ok(Json(SuccessResponse::<()>::message_only("Proposal rejected")))
+    Ok(Json(SuccessResponse::with_data(
+        format!("{} interrupted execution(s)", records.len()),
+        records,
+    )))
 }
 
-/// POST /api/proposals/{id}/comments
-/// Add a comment to a proposal
-pub async fn add_comment(
-    State(_state): State<SharedState>,
-    Path(_id): Path<Uuid>,
-    Json(_req): Json<CommentRequest>,
-) -> Result<Json<SuccessResponse<()>>, AppError> {
-    Ok(Json(SuccessResponse::<()>::message_only("Comment added")))
+/// POST /api/executions/{id}/verify
+/// Compare the database with the journal of an interrupted execution and say
+/// whether it is safe to resume
+pub async fn verify_interrupted_execution(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<VerifyExecutionRequest>,
+) -> Result<Json<SuccessResponse<ExecutionRecord>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can verify interrupted executions".to_string()));
+    }
+
+    let pool = state.connections.get_pool(req.connection_id).await?;
+    let record = state
+        .journal
+        .verify(&pool, id, req.connection_id, &state.pipeline_proposals)
+        .await?;
+    if let Some(proposal) = state.pipeline_proposals.get(record.proposal_id).await? {
+        state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+    }
+
+    let guidance = record
+        .recovery
+        .as_ref()
+        .map(|r| r.guidance.clone())
+        .unwrap_or_default();
+    let entry = AuditEntry::new(AuditAction::ExecutionVerified, &claims.sub, "proposal", &record.proposal_id.to_string())
+        .with_project(record.proposal.project_id)
+        .with_details(&guidance);
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(guidance, record)))
 }
 
-// =============================================================================
-// ROUTE HANDLERS - Risk Analysis (Stage 3)
-// =============================================================================
+/// POST /api/proposals/{id}/clone
+/// Copy a proposal's changes into a new draft
+pub async fn clone_proposal(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let original = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
 
-/// POST /api/proposals/{id}/analyze
-/// Analyze the risk of a proposal
-pub async fn analyze_risk(
-    State(_state): State<SharedState>,
-    Path(_id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<RiskAnalysisResponse>>, AppError> {
-    // Create a dummy proposal for analysis
-    let proposal = SchemaProposal::new(
-        Uuid::new_v4(),
-        "Test".to_string(),
-        "Test".to_string(),
-        "system".to_string(),
-    );
+    let proposal = state.pipeline_proposals.create(original.duplicate(&claims.sub)).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
 
-    let engine = RiskEngine::new();
-    let analysis = engine.analyze(&proposal)?;
+    let entry = AuditEntry::new(
+        AuditAction::ProposalCreated,
+        &claims.sub,
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!("Cloned from {}", id));
+    state.metadata.add_audit_entry(entry).await?;
 
     Ok(Json(SuccessResponse::with_data(
-        "Risk analysis complete",
-        RiskAnalysisResponse { analysis },
+        "Proposal cloned",
+        ProposalResponse { proposal },
     )))
 }
 
-// =============================================================================
-// ROUTE HANDLERS - Execution (Stage 4)
-// =============================================================================
-
-/// POST /api/proposals/{id}/execute
-/// Execute a proposal's migration
-pub async fn execute_proposal(
+/// POST /api/proposals/{id}/revert
+/// Create a draft proposal that undoes an executed one
+pub async fn revert_proposal(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-    Json(req): Json<ExecuteRequest>,
-) -> Result<Json<SuccessResponse<ExecutionResponse>>, AppError> {
-    // Create a dummy proposal for execution
-    let proposal = SchemaProposal::new(
-        Uuid::new_v4(),
-        "Test".to_string(),
-        "Test".to_string(),
-        "system".to_string(),
-    );
+) -> Result<Json<SuccessResponse<ProposalResponse>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let original = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    if original.status != ProposalStatus::Executed {
+        return Err(AppError::BadRequest("Only executed proposals can be reverted".to_string()));
+    }
+
+    // The schema as it was before execution lets drops be reconstructed
+    let before = match original.executed_at {
+        Some(executed_at) => state.snapshots.latest_before(original.connection_id, executed_at).await?,
+        None => None,
+    };
 
-    let orchestrator = Orchestrator::new();
-    let result = orchestrator.execute(&proposal, req.dry_run).await?;
+    let (revert, manual_steps) = build_revert(&original, before.as_ref(), &claims.sub);
+    let proposal = state.pipeline_proposals.create(revert).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&proposal)).await;
 
     let entry = AuditEntry::new(
-        AuditAction::ProposalExecuted,
-        "system",
+        AuditAction::ProposalCreated,
+        &claims.sub,
         "proposal",
-        &id.to_string(),
-    );
-    state.metadata.add_audit_entry(entry).await;
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!("Revert of {}", id));
+    state.metadata.add_audit_entry(entry).await?;
+
+    let message = if manual_steps.is_empty() {
+        "Revert proposal created".to_string()
+    } else {
+        format!("Revert proposal created; {} steps need manual handling", manual_steps.len())
+    };
 
     Ok(Json(SuccessResponse::with_data(
-        if req.dry_run { "Dry run complete" } else { "Proposal executed" },
-        ExecutionResponse {
-            success: result.success,
-            result,
-        },
+        message,
+        ProposalResponse { proposal },
     )))
 }
 
 /// POST /api/proposals/{id}/rollback
-/// Rollback a proposal's migration
+/// Run an executed proposal's down migration while its rollback window is
+/// open; afterwards, undoing it takes a revert proposal
 pub async fn rollback_proposal(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<SuccessResponse<ExecutionResponse>>, AppError> {
-    let proposal = SchemaProposal::new(
-        Uuid::new_v4(),
-        "Test".to_string(),
-        "Test".to_string(),
-        "system".to_string(),
-    );
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can roll back executed proposals".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    access::authorize(&state, &proposal, &claims.sub, AccessAction::Approve).await?;
 
-    let orchestrator = Orchestrator::new();
-    let result = orchestrator.rollback(&proposal).await?;
+    let now = Utc::now();
+    if !proposal.rollback_window_open(now) {
+        let reason = match (proposal.status, proposal.rollback_window_ends_at) {
+            (ProposalStatus::Executed | ProposalStatus::VerificationFailed, Some(ends)) => {
+                format!("The rollback window closed at {}", ends.to_rfc3339())
+            }
+            (ProposalStatus::Executed | ProposalStatus::VerificationFailed, None) => {
+                "Direct rollbacks are disabled".to_string()
+            }
+            (status, _) => format!("Only executed proposals can be rolled back (status is {})", status.as_str()),
+        };
+        return Err(AppError::Conflict(format!(
+            "{}; create a revert proposal with POST /api/proposals/{}/revert",
+            reason, id
+        )));
+    }
+    let irreversible = proposal.migration.as_ref().map(|m| irreversible_steps(&m.down_sql)).unwrap_or_default();
+    if !irreversible.is_empty() {
+        return Err(AppError::Conflict(format!(
+            "The migration cannot be rolled back automatically ({}); create a revert proposal instead",
+            irreversible.join("; ")
+        )));
+    }
 
-    let entry = AuditEntry::new(
+    let pool = state.connections.get_pool(proposal.connection_id).await?;
+    let _permit = quota::begin_execution(&state, project_id, &claims, &headers).await?;
+    let result = Orchestrator::new()
+        .with_monitor(state.executions.clone())
+        .rollback(&pool, &proposal)
+        .await?;
+
+    let updated = state.pipeline_proposals.mark_rolled_back(id, &result).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+    // Open proposals on the connection were analyzed against the rolled-back schema
+    if result.success {
+        invalidate_risk(&state, proposal.connection_id, "proposal rollback").await;
+    }
+
+    let mut entry = AuditEntry::new(
         AuditAction::ProposalRolledBack,
-        "system",
+        &claims.sub,
         "proposal",
         &id.to_string(),
-    );
-    state.metadata.add_audit_entry(entry).await;
+    )
+    .with_project(project_id);
+    if let Some(error) = &result.error {
+        entry = entry.with_details(&format!("Rollback failed: {}", error));
+    }
+    state.metadata.add_audit_entry(entry).await?;
 
     Ok(Json(SuccessResponse::with_data(
-        "Rollback complete",
+        if result.success { "Rollback complete" } else { "Rollback failed" },
         ExecutionResponse {
             success: result.success,
             result,
+            temp_schema: None,
+            load_test: None,
+            canary: None,
+        },
+    )))
+}
+
+// =============================================================================
+// ROUTE HANDLERS - Execution Plans
+// =============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePlanRequest {
+    /// Statements per transaction within a stage; overrides the server default
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStageRequest {
+    /// Label of the confirmation gate the stage opens
+    #[serde(default)]
+    pub confirm_gate: Option<String>,
+    /// How long to wait while another SchemaFlow instance migrates the same
+    /// database (default 30, at most 600)
+    #[serde(default)]
+    pub lock_wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageRunResponse {
+    pub plan: ExecutionPlan,
+    pub result: ExecutionResult,
+}
+
+/// A plan with the project of its proposal, once the caller may see it
+async fn load_plan(state: &SharedState, claims: &Claims, plan_id: Uuid) -> Result<(ExecutionPlan, Option<i32>), AppError> {
+    let plan = state.plans.get(plan_id).await?;
+    let project_id = membership::require_proposal(state, claims, plan.proposal_id).await?;
+    Ok((plan, project_id))
+}
+
+/// POST /api/proposals/{id}/plan
+/// Split an approved proposal's migration into stages run one at a time
+pub async fn create_execution_plan(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreatePlanRequest>,
+) -> Result<Json<SuccessResponse<ExecutionPlan>>, AppError> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    if proposal.status != ProposalStatus::Approved {
+        return Err(AppError::BadRequest("Proposal must be approved before it is planned".to_string()));
+    }
+    if proposal.approval_expired(Utc::now()) {
+        return Err(AppError::BadRequest(
+            "Approval has expired; the proposal must be re-approved".to_string()
+        ));
+    }
+    // Rendered SQL depends on the target's values, so stages could not be fixed ahead
+    if templating::is_templated(&proposal) {
+        return Err(AppError::BadRequest("Templated proposals are executed directly, not planned".to_string()));
+    }
+    if state.plans.for_proposal(id).await?.iter().any(|p| p.is_active()) {
+        return Err(AppError::Conflict("The proposal already has an execution plan in progress".to_string()));
+    }
+
+    let migration = match proposal.migration_for_run(false).cloned() {
+        Some(migration) => migration,
+        None => {
+            let migration = Orchestrator::new().generate_migration(&proposal);
+            state
+                .pipeline_proposals
+                .set_migration(id, migration)
+                .await?
+                .migration
+                .ok_or_else(|| AppError::Internal("Generated migration was not stored".to_string()))?
+        }
+    };
+    let chunk_size = req.chunk_size.or(state.pipeline_proposals.policy().execution_chunk_size);
+    let plan = ExecutionPlan::build(&proposal, &migration, chunk_size, &claims.sub)?;
+    state.plans.put(&plan).await?;
+
+    let entry = AuditEntry::new(AuditAction::ProposalUpdated, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&format!(
+            "Planned execution {} in {} stage(s) of {} statement(s)",
+            plan.id,
+            plan.stages.len(),
+            plan.total_statements
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Execution plan created", plan)))
+}
+
+/// GET /api/proposals/{id}/plans
+/// A proposal's execution plans, oldest first
+pub async fn list_execution_plans(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<Vec<ExecutionPlan>>>, AppError> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let plans = state.plans.for_proposal(id).await?;
+    Ok(Json(SuccessResponse::with_data("Execution plans retrieved", plans)))
+}
+
+/// GET /api/plans/{id}
+pub async fn get_execution_plan(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ExecutionPlan>>, AppError> {
+    let (plan, _) = load_plan(&state, &claims, plan_id).await?;
+    Ok(Json(SuccessResponse::with_data("Execution plan retrieved", plan)))
+}
+
+/// POST /api/plans/{id}/stages/{stage}/execute
+/// Run the plan's next stage, or run a failed stage again from its checkpoint
+pub async fn execute_plan_stage(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((plan_id, index)): Path<(Uuid, usize)>,
+    headers: HeaderMap,
+    Json(req): Json<RunStageRequest>,
+) -> Result<Json<SuccessResponse<StageRunResponse>>, AppError> {
+    let (mut plan, project_id) = load_plan(&state, &claims, plan_id).await?;
+    let id = plan.proposal_id;
+    let mut proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+
+    let started = plan.has_started();
+    match (started, proposal.status) {
+        (false, ProposalStatus::Approved) | (true, ProposalStatus::Executing) | (true, ProposalStatus::Failed) => {}
+        (_, status) => {
+            return Err(AppError::BadRequest(format!(
+                "The proposal is {:?}; the plan's stages cannot run",
+                status
+            )));
+        }
+    }
+    if proposal.approval_expired(Utc::now()) {
+        return Err(AppError::BadRequest(
+            "Approval has expired; the proposal must be re-approved".to_string()
+        ));
+    }
+    // The plan runs the migration it was built from, or nothing
+    let migration = proposal
+        .migration_for_run(started)
+        .filter(|m| m.checksum == plan.migration_checksum)
+        .cloned()
+        .ok_or_else(|| {
+            AppError::Conflict("The proposal's migration changed since the plan was made; plan it again".to_string())
+        })?;
+    proposal.migration = Some(migration);
+
+    let stage = plan
+        .stages
+        .get(index)
+        .ok_or_else(|| AppError::NotFound(format!("Stage {} not found in plan {}", index, plan_id)))?;
+    if let Some(label) = &stage.gate {
+        if req.confirm_gate.as_deref() != Some(label.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Stage {} opens confirmation gate '{}'; run it with confirmGate set to it",
+                index, label
+            )));
+        }
+    }
+
+    let pool = state.connections.get_pool(proposal.connection_id).await?;
+    let _permit = quota::begin_execution(&state, project_id, &claims, &headers).await?;
+    let execution_policy = match project_id {
+        Some(project_id) => state.project_service.get_execution_policy(project_id).await?.unwrap_or_default(),
+        None => Default::default(),
+    };
+
+    // Checked when the plan starts; later stages continue what was allowed
+    let verified_backup = match started {
+        true => None,
+        false => backup::require_recent(&state, &execution_policy, &proposal).await?,
+    };
+
+    let now = Utc::now();
+    let stage = plan.start_stage(index, now)?;
+    state.plans.put(&plan).await?;
+
+    // A run interrupted by a restart committed past the stage's stored checkpoint
+    let resumed = match (&proposal.status, &proposal.last_execution) {
+        (ProposalStatus::Failed, Some(last)) => last.checkpoint,
+        _ => 0,
+    };
+    let options = ExecutionOptions {
+        dry_run: false,
+        deep: false,
+        chunk_size: plan.chunk_size,
+        start_at: stage.checkpoint.max(resumed).min(stage.end()),
+        stop_at: Some(stage.end()),
+        lock_wait: Some(advisory_lock::wait_for(req.lock_wait_seconds)),
+        settings: execution_policy.settings,
+        watchdog: execution_policy.watchdog,
+    };
+    let result = Orchestrator::new()
+        .with_monitor(state.executions.clone())
+        .with_journal(state.journal.clone())
+        .execute(&pool, &proposal, options)
+        .await;
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            plan.abort_stage(index, &e.to_string(), Utc::now());
+            state.plans.put(&plan).await?;
+            return Err(e);
+        }
+    };
+
+    plan.finish_stage(index, &result, Utc::now());
+    state.plans.put(&plan).await?;
+    let updated = state.pipeline_proposals.mark_executed(id, &result).await?;
+    state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+
+    let finished = plan.status == PlanStatus::Completed;
+    if finished || !result.success {
+        state.notifier.notify(
+            Notification::ExecutionFinished {
+                proposal: updated.clone(),
+                result: Box::new(result.clone()),
+            },
+            Audience::Users(vec![updated.created_by.clone()]),
+        );
+    }
+    if finished {
+        dictionary::record_changes(&state, &updated).await?;
+        refresh::schedule_refresh(&state, &updated);
+        watches::notify_proposal(&state, &updated, WatchEvent::Executed, &claims.sub).await;
+    }
+
+    let mut details = format!("Stage {} of {} of plan {}", index + 1, plan.stages.len(), plan.id);
+    if let Some(label) = &stage.gate {
+        details.push_str(&format!("; confirmed gate '{}'", label));
+    }
+    if let Some(verified) = &verified_backup {
+        details.push_str(&format!(
+            "; verified {} backup {} completed {}",
+            verified.backup.provider, verified.backup.location, verified.backup.completed_at
+        ));
+    }
+    if !result.success {
+        details.push_str(&format!(
+            "; failed at checkpoint {}/{}",
+            result.checkpoint, result.total_statements
+        ));
+    }
+    let entry = AuditEntry::new(AuditAction::ProposalExecuted, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&details);
+    state.metadata.add_audit_entry(entry).await?;
+
+    if let Some(archive) = &state.archive {
+        if let Err(e) = archive.archive_execution(&proposal, &result).await {
+            tracing::warn!("Failed to archive execution of proposal {}: {}", id, e);
+        }
+    }
+
+    Ok(Json(SuccessResponse::with_data(
+        match (result.success, finished) {
+            (false, _) => "Stage failed; run it again to resume from its checkpoint",
+            (true, false) => "Stage executed",
+            (true, true) => "Plan completed",
+        },
+        StageRunResponse { plan, result },
+    )))
+}
+
+/// POST /api/plans/{id}/pause
+/// Hold the plan after the stage that is running, if any
+pub async fn pause_execution_plan(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ExecutionPlan>>, AppError> {
+    let (mut plan, _) = load_plan(&state, &claims, plan_id).await?;
+    plan.pause(Utc::now())?;
+    state.plans.put(&plan).await?;
+    Ok(Json(SuccessResponse::with_data("Execution plan paused", plan)))
+}
+
+/// POST /api/plans/{id}/resume
+pub async fn resume_execution_plan(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ExecutionPlan>>, AppError> {
+    let (mut plan, _) = load_plan(&state, &claims, plan_id).await?;
+    plan.resume(Utc::now())?;
+    state.plans.put(&plan).await?;
+    Ok(Json(SuccessResponse::with_data("Execution plan resumed", plan)))
+}
+
+/// POST /api/plans/{id}/cancel
+/// Stop the plan between stages. Once stages have run, the proposal is left
+/// failed at its checkpoint, to be resumed by a plain execution or rolled back.
+pub async fn cancel_execution_plan(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ExecutionPlan>>, AppError> {
+    let (mut plan, project_id) = load_plan(&state, &claims, plan_id).await?;
+    plan.cancel(Utc::now())?;
+    state.plans.put(&plan).await?;
+
+    let proposal = state.pipeline_proposals.get(plan.proposal_id).await?;
+    if let Some(mut last) = proposal
+        .filter(|p| plan.has_started() && p.status == ProposalStatus::Executing)
+        .and_then(|p| p.last_execution)
+    {
+        last.success = false;
+        last.error = Some(format!("Execution plan {} was cancelled", plan.id));
+        let updated = state.pipeline_proposals.mark_executed(plan.proposal_id, &last).await?;
+        state.metadata.add_proposal(ProposalSummary::from(&updated)).await;
+    }
+
+    let entry = AuditEntry::new(AuditAction::ProposalUpdated, &claims.sub, "proposal", &plan.proposal_id.to_string())
+        .with_project(project_id)
+        .with_details(&format!("Cancelled execution plan {}", plan.id));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Execution plan cancelled", plan)))
+}
+
+// =============================================================================
+// ROUTE HANDLERS - Share Links
+// =============================================================================
+
+/// POST /api/proposals/{id}/share-links
+/// Create an expiring read-only link for people without an account
+pub async fn create_share_link(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateShareLinkRequest>,
+) -> Result<Json<SuccessResponse<ShareLinkResponse>>, AppError> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot share proposals".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    if state.pipeline_proposals.get(id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Proposal {} not found", id)));
+    }
+
+    let label = req.label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    let link = state.share_links.create(id, label, &claims.sub, req.expires_in_hours).await;
+    let token = create_share_token(link.id, id, link.expires_at)?;
+
+    let entry = AuditEntry::new(AuditAction::ShareLinkCreated, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&format!("Link {} expires {}", link.id, link.expires_at.to_rfc3339()));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Share link created",
+        ShareLinkResponse {
+            path: Some(format!("/api/share/{}", token)),
+            token: Some(token),
+            link,
+        },
+    )))
+}
+
+/// GET /api/proposals/{id}/share-links
+/// List a proposal's share links with their access logs
+pub async fn list_share_links(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ShareLinkListResponse>>, AppError> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot manage share links".to_string()));
+    }
+    membership::require_proposal(&state, &claims, id).await?;
+
+    let links = state.share_links.list_for_proposal(id).await;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Share links retrieved",
+        ShareLinkListResponse { links },
+    )))
+}
+
+/// DELETE /api/proposals/{id}/share-links/{link_id}
+/// Revoke a share link before it expires
+pub async fn revoke_share_link(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, link_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SuccessResponse<ShareLinkResponse>>, AppError> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot manage share links".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+
+    let link = state
+        .share_links
+        .revoke(id, link_id, &claims.sub)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("No active share link {} on proposal {}", link_id, id)))?;
+
+    let entry = AuditEntry::new(AuditAction::ShareLinkRevoked, &claims.sub, "proposal", &id.to_string())
+        .with_project(project_id)
+        .with_details(&format!("Link {}", link_id));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Share link revoked",
+        ShareLinkResponse { link, token: None, path: None },
+    )))
+}
+
+/// GET /api/share/{token}
+/// Public read-only view of a shared proposal
+pub async fn view_shared_proposal(
+    State(state): State<SharedState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<SuccessResponse<SharedProposalResponse>>, AppError> {
+    let share = decode_share_token(&token)?;
+
+    let header_value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let access = ShareAccess {
+        accessed_at: Utc::now(),
+        client_ip: header_value("x-forwarded-for")
+            .and_then(|v| v.split(',').next().map(|ip| ip.trim().to_string()))
+            .or_else(|| header_value("x-real-ip")),
+        user_agent: header_value(header::USER_AGENT.as_str()),
+    };
+
+    // A valid signature is not enough: the link may have been revoked
+    let link = state
+        .share_links
+        .record_access(share.sid, access.clone())
+        .await
+        .filter(|l| l.proposal_id == share.pid)
+        .ok_or_else(|| AppError::Unauthorized("Share link has been revoked".to_string()))?;
+
+    let proposal = state
+        .pipeline_proposals
+        .get(link.proposal_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Shared proposal no longer exists".to_string()))?;
+
+    let entry = AuditEntry::new(
+        AuditAction::ShareLinkAccessed,
+        &format!("share:{}", link.id),
+        "proposal",
+        &proposal.id.to_string(),
+    )
+    .with_project(proposal.project_id)
+    .with_details(&format!(
+        "Opened from {} ({})",
+        access.client_ip.as_deref().unwrap_or("unknown address"),
+        access.user_agent.as_deref().unwrap_or("unknown client")
+    ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    let migration = proposal
+        .migration
+        .clone()
+        .unwrap_or_else(|| Orchestrator::new().generate_migration(&proposal));
+
+    Ok(Json(SuccessResponse::with_data(
+        "Shared proposal retrieved",
+        SharedProposalResponse {
+            id: proposal.id,
+            title: proposal.title,
+            description: proposal.description,
+            status: proposal.status,
+            changes: proposal.changes,
+            risk_analysis: proposal.risk_analysis,
+            migration,
+            updated_at: proposal.updated_at,
+            link_expires_at: link.expires_at,
         },
     )))
 }
@@ -442,12 +2994,160 @@ pub async fn rollback_proposal(
 // ROUTE HANDLERS - Audit Log
 // =============================================================================
 
+/// GET /api/audit-log/verify
+/// Re-validate the audit log's hash chain (Admin only)
+pub async fn verify_audit_log(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<SuccessResponse<ChainVerification>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can verify the audit log".to_string()));
+    }
+    let report = state.metadata.verify_audit_chain().await?;
+    if let Some(tampered) = &report.first_tampered {
+        tracing::warn!(
+            "Audit chain broken at entry {} (position {}): {}",
+            tampered.id, tampered.position, tampered.reason
+        );
+    }
+
+    let message = if report.valid { "Audit log chain is intact" } else { "Audit log chain is broken" };
+    Ok(Json(SuccessResponse::with_data(message, report)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditExportQuery {
+    /// cef or json; the configured format by default
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Only entries after this time
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// GET /api/audit-log/export
+/// Export the whole audit log for a SIEM (Admin only)
+pub async fn export_audit_log(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AuditExportQuery>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can export the audit log".to_string()));
+    }
+    let format = match query.format.as_deref() {
+        None => state.audit_export.format,
+        Some(format) => AuditExportFormat::parse(format)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown export format '{}'; use cef or json", format)))?,
+    };
+
+    let entries = audit_export::select(state.metadata.get_audit_log().await?, query.since);
+    let holds = state.metadata.list_legal_holds().await?;
+    let count = entries.len();
+    let body = audit_export::render(entries, &holds, format, state.audit_export.signing_key.as_deref());
+
+    let entry = AuditEntry::new(AuditAction::AuditLogExported, &claims.sub, "audit_log", "export")
+        .with_details(&format!("Exported {} entries as {}", count, format.extension()));
+    state.metadata.add_audit_entry(entry).await?;
+
+    let content_type = match format {
+        AuditExportFormat::Cef => "text/plain; charset=utf-8",
+        AuditExportFormat::Json => "application/json",
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], body))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceLegalHoldRequest {
+    pub scope: HoldScope,
+    pub reason: String,
+}
+
+/// GET /api/legal-holds
+/// List legal holds, released ones included (Admin only)
+pub async fn list_legal_holds(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<SuccessResponse<Vec<LegalHold>>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can view legal holds".to_string()));
+    }
+    let holds = state.metadata.list_legal_holds().await?;
+    Ok(Json(SuccessResponse::with_data("Legal holds retrieved", holds)))
+}
+
+/// POST /api/legal-holds
+/// Keep a proposal's records or a range of the audit log from retention (Admin only)
+pub async fn place_legal_hold(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PlaceLegalHoldRequest>,
+) -> Result<Json<SuccessResponse<LegalHold>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can place legal holds".to_string()));
+    }
+    if let HoldScope::Proposal { proposal_id } = &payload.scope {
+        if state.pipeline_proposals.get(*proposal_id).await?.is_none() {
+            return Err(AppError::NotFound(format!("Proposal {} not found", proposal_id)));
+        }
+    }
+    let hold = LegalHold::new(payload.scope, payload.reason, &claims.sub)?;
+    state.metadata.put_legal_hold(&hold).await?;
+
+    let entry = AuditEntry::new(AuditAction::LegalHoldPlaced, &claims.sub, "legal_hold", &hold.id.to_string())
+        .with_details(&format!("Placed a legal hold on {}: {}", hold.describe(), hold.reason));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Legal hold placed", hold)))
+}
+
+/// POST /api/legal-holds/{id}/release
+/// Release a legal hold; retention catches up on the next sweep (Admin only)
+pub async fn release_legal_hold(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<LegalHold>>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can release legal holds".to_string()));
+    }
+    let mut hold = state
+        .metadata
+        .list_legal_holds()
+        .await?
+        .into_iter()
+        .find(|h| h.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Legal hold {} not found", id)))?;
+    if !hold.is_active() {
+        return Err(AppError::Conflict(format!("Legal hold {} is already released", id)));
+    }
+    hold.released_by = Some(claims.sub.clone());
+    hold.released_at = Some(Utc::now());
+    state.metadata.put_legal_hold(&hold).await?;
+
+    let entry = AuditEntry::new(AuditAction::LegalHoldReleased, &claims.sub, "legal_hold", &id.to_string())
+        .with_details(&format!("Released the legal hold on {}", hold.describe()));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Legal hold released", hold)))
+}
+
 /// GET /api/audit-log
-/// Get the audit log
+/// Get the audit log, limited to the caller's projects
 pub async fn get_audit_log(
     State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<Json<SuccessResponse<AuditLogResponse>>, AppError> {
-    let entries = state.metadata.get_audit_log().await;
+    let visibility = membership::visible_projects(&state, &claims).await?;
+    let entries = state
+        .metadata
+        .get_audit_log()
+        .await?
+        .into_iter()
+        .filter(|e| membership::can_see(&visibility, e.project_id))
+        .collect();
 
     Ok(Json(SuccessResponse::with_data(
         "Audit log retrieved",
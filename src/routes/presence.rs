@@ -0,0 +1,187 @@
+//! Presence route handlers
+//!
+//! Who has a proposal open, the WebSocket channel that keeps it current and
+//! the edit lock on drafts. See `pipeline::presence`.
+
+use crate::auth::Claims;
+use crate::error::{ApiResult, AppError};
+use crate::models::SuccessResponse;
+use crate::pipeline::membership;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::presence::{EditLock, PresenceActivity, PresenceSnapshot};
+use crate::pipeline::proposal::ProposalStatus;
+use crate::state::SharedState;
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Extension, Path, State},
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquireLockRequest {
+    /// Reject other users' edits instead of only warning them
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Replace another user's exclusive lock
+    #[serde(default)]
+    pub takeover: bool,
+}
+
+/// Messages a client sends over the presence channel
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Viewing,
+    Editing,
+    Ping,
+}
+
+/// GET /api/proposals/{id}/presence
+/// Users with the proposal open and its edit lock
+pub async fn get_presence(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SuccessResponse<PresenceSnapshot>>> {
+    membership::require_proposal(&state, &claims, id).await?;
+    let snapshot = state.presence.snapshot(id).await;
+    Ok(Json(SuccessResponse::with_data("Presence retrieved", snapshot)))
+}
+
+/// POST /api/proposals/{id}/lock
+/// Take (or renew) the edit lock on a draft
+pub async fn acquire_edit_lock(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AcquireLockRequest>,
+) -> ApiResult<Json<SuccessResponse<EditLock>>> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot edit proposals".to_string()));
+    }
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let proposal = state
+        .pipeline_proposals
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+    if proposal.status != ProposalStatus::Draft {
+        return Err(AppError::Conflict(format!(
+            "Only draft proposals can be locked for editing; this one is {:?}",
+            proposal.status
+        )));
+    }
+
+    let previous = state.presence.snapshot(id).await.lock;
+    let lock = state
+        .presence
+        .acquire_lock(id, &claims.sub, &claims.email, req.exclusive, req.takeover)
+        .await?;
+
+    let message = match previous.filter(|l| l.holder != claims.sub) {
+        Some(previous) => {
+            let entry = AuditEntry::new(AuditAction::EditLockTakenOver, &claims.sub, "proposal", &id.to_string())
+                .with_project(project_id)
+                .with_details(&format!(
+                    "Took over the {} edit lock of {}",
+                    if previous.exclusive { "exclusive" } else { "soft" },
+                    previous.holder_email
+                ));
+            state.metadata.add_audit_entry(entry).await?;
+            format!("Edit lock taken over from {}", previous.holder_email)
+        }
+        None => "Edit lock acquired".to_string(),
+    };
+
+    Ok(Json(SuccessResponse::with_data(message, lock)))
+}
+
+/// DELETE /api/proposals/{id}/lock
+/// Release the edit lock; admins may release anyone's
+pub async fn release_edit_lock(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SuccessResponse<Option<EditLock>>>> {
+    let project_id = membership::require_proposal(&state, &claims, id).await?;
+    let released = state
+        .presence
+        .release_lock(id, &claims.sub, claims.role.can_approve())
+        .await?;
+
+    if let Some(lock) = released.as_ref().filter(|l| l.holder != claims.sub) {
+        let entry = AuditEntry::new(AuditAction::EditLockReleased, &claims.sub, "proposal", &id.to_string())
+            .with_project(project_id)
+            .with_details(&format!("Released the edit lock of {}", lock.holder_email));
+        state.metadata.add_audit_entry(entry).await?;
+    }
+
+    let message = match released {
+        Some(_) => "Edit lock released",
+        None => "No edit lock was held",
+    };
+    Ok(Json(SuccessResponse::with_data(message, released)))
+}
+
+/// GET /api/proposals/{id}/presence/ws
+/// WebSocket presence channel. The client is a viewer until it sends
+/// `{"type": "editing"}`, keeps its session (and lock) alive with
+/// `{"type": "ping"}` and receives the proposal's presence on every change.
+pub async fn presence_socket(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    membership::require_proposal(&state, &claims, id).await?;
+    Ok(ws.on_upgrade(move |socket| run_presence_session(state, claims, id, socket)))
+}
+
+async fn run_presence_session(state: SharedState, claims: Claims, proposal_id: Uuid, mut socket: WebSocket) {
+    let mut changes = state.presence.subscribe();
+    let session_id = state.presence.join(proposal_id, &claims.sub, &claims.email).await;
+    let mut pending = true;
+
+    loop {
+        if pending {
+            let snapshot = state.presence.snapshot(proposal_id).await;
+            let Ok(text) = serde_json::to_string(&snapshot) else { break };
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+            pending = false;
+        }
+
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let activity = match serde_json::from_str::<ClientMessage>(text.as_str()) {
+                        Ok(ClientMessage::Viewing) => Some(PresenceActivity::Viewing),
+                        Ok(ClientMessage::Editing) => Some(PresenceActivity::Editing),
+                        Ok(ClientMessage::Ping) => None,
+                        Err(e) => {
+                            debug!("Ignoring presence message on proposal {}: {}", proposal_id, e);
+                            None
+                        }
+                    };
+                    state.presence.touch(proposal_id, session_id, activity).await;
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => state.presence.touch(proposal_id, session_id, None).await,
+            },
+            changed = changes.recv() => match changed {
+                Ok(changed) => pending = changed == proposal_id,
+                Err(RecvError::Lagged(_)) => pending = true,
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    state.presence.leave(proposal_id, session_id).await;
+}
@@ -4,18 +4,33 @@
 
 use crate::auth::Claims;
 use crate::error::{ApiResult, AppError};
+use crate::connection::ConnectionTarget;
+use crate::crypto::{ConnectionCipher, SealedConnection};
+use crate::features::{self, FeatureOverrides, FeatureStatus};
 use crate::models::{
-    CreateProjectRequest, Project, SaveConnectionRequest, SavedConnection,
-    ConnectionDetails, SuccessResponse, MessageResponse, UpdateProjectRequest,
+    CreateProjectRequest, Project, SaveConnectionRequest, RevealConnectionRequest,
+    RevealedConnection, ConnectionDetails, SuccessResponse, MessageResponse, UpdateProjectRequest,
 };
+use crate::pipeline::access::AccessPolicy;
+use crate::pipeline::execution_policy::ExecutionPolicy;
+use crate::pipeline::membership;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::analytics::{self, ViolationAnalytics};
+use crate::pipeline::board::{self, ProposalBoard};
+use crate::pipeline::sla::{self, ReviewSla};
+use crate::pipeline::teams::ProjectTeams;
+use crate::quota::{self, ProjectQuota, QuotaUsage};
+use crate::pipeline::template::ProposalTemplate;
+use crate::snapshot::dictionary::{CustomFieldDefinition, TagDefinition};
+use crate::snapshot::LintConfig;
 use crate::state::SharedState;
 use axum::{
-    extract::{Path, State, Extension},
+    extract::{Path, Query, State, Extension},
     Json,
 };
 use chrono::Utc;
-use serde::Serialize;
-use tracing::{debug, info, error};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, error, warn};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -242,6 +257,603 @@ pub async fn update_project(
     )))
 }
 
+/// Get the lint settings for a project (defaults if none were saved)
+pub async fn get_lint_config(
+    State(state): State<SharedState>,
+    Extension(_claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<LintConfig>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let config = state.project_service.get_lint_config(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Lint configuration retrieved.",
+        config,
+    )))
+}
+
+/// Replace the lint settings for a project
+pub async fn update_lint_config(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<LintConfig>,
+) -> ApiResult<Json<SuccessResponse<LintConfig>>> {
+    debug!("Updating lint config for project: {}", id);
+
+    // Parse user_id from claims
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    // Only the owner may change project conventions
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    state.project_service.set_lint_config(id, &payload).await?;
+
+    info!("Lint config updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Lint configuration updated.",
+        payload,
+    )))
+}
+
+/// Get the proposal template for a project (defaults if none were saved)
+pub async fn get_proposal_template(
+    State(state): State<SharedState>,
+    Extension(_claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<ProposalTemplate>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let template = state.project_service.get_proposal_template(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Proposal template retrieved.",
+        template,
+    )))
+}
+
+/// Replace the proposal template for a project
+pub async fn update_proposal_template(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ProposalTemplate>,
+) -> ApiResult<Json<SuccessResponse<ProposalTemplate>>> {
+    debug!("Updating proposal template for project: {}", id);
+
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    payload.validate()?;
+    state.project_service.set_proposal_template(id, &payload).await?;
+
+    info!("Proposal template updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Proposal template updated.",
+        payload,
+    )))
+}
+
+/// Get the tag access policy for a project (empty if none was saved)
+pub async fn get_access_policy(
+    State(state): State<SharedState>,
+    Extension(_claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<AccessPolicy>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let policy = state.project_service.get_access_policy(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Access policy retrieved.",
+        policy,
+    )))
+}
+
+/// Replace the tag access policy for a project
+pub async fn update_access_policy(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<AccessPolicy>,
+) -> ApiResult<Json<SuccessResponse<AccessPolicy>>> {
+    debug!("Updating access policy for project: {}", id);
+
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    payload.validate()?;
+
+    // Teams may only contain people who can see the project
+    let members: Vec<String> = state.project_service.member_ids(id).await?
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    if let Some(outsider) = payload.members().into_iter().find(|m| !members.iter().any(|id| id == m)) {
+        return Err(AppError::Validation(format!(
+            "User {} is not a member of project {}",
+            outsider, id
+        )));
+    }
+
+    state.project_service.set_access_policy(id, &payload).await?;
+
+    info!("Access policy updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Access policy updated.",
+        payload,
+    )))
+}
+
+/// Get the review SLA for a project (none if not configured)
+pub async fn get_review_sla(
+    State(state): State<SharedState>,
+    Extension(_claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<ReviewSla>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let sla = state.project_service.get_review_sla(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Review SLA retrieved.",
+        sla,
+    )))
+}
+
+/// Replace the review SLA for a project; applies to proposals submitted afterwards
+pub async fn update_review_sla(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ReviewSla>,
+) -> ApiResult<Json<SuccessResponse<ReviewSla>>> {
+    debug!("Updating review SLA for project: {}", id);
+
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    payload.validate()?;
+    state.project_service.set_review_sla(id, &payload).await?;
+
+    info!("Review SLA updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Review SLA updated.",
+        payload,
+    )))
+}
+
+/// Get the execution policy for a project (no overrides if not configured)
+pub async fn get_execution_policy(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<ExecutionPolicy>>> {
+    membership::require_project(&state, &claims, Some(id)).await?;
+    let policy = state.project_service.get_execution_policy(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Execution policy retrieved.",
+        policy,
+    )))
+}
+
+/// Replace the execution policy for a project; applies to executions started afterwards
+pub async fn update_execution_policy(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ExecutionPolicy>,
+) -> ApiResult<Json<SuccessResponse<ExecutionPolicy>>> {
+    debug!("Updating execution policy for project: {}", id);
+
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    payload.validate()?;
+    state.project_service.set_execution_policy(id, &payload).await?;
+
+    let settings: Vec<String> = payload.settings.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let watchdog = match &payload.watchdog {
+        Some(w) => format!(
+            "{}x estimate, at least {}s, at most {}",
+            w.factor,
+            w.min_statement_secs,
+            w.max_statement_secs.map(|s| format!("{}s", s)).unwrap_or_else(|| "unbounded".to_string())
+        ),
+        None => "off".to_string(),
+    };
+    let entry = AuditEntry::new(AuditAction::ExecutionPolicyChanged, &claims.sub, "project", &id.to_string())
+        .with_project(Some(id))
+        .with_details(&format!(
+            "Settings: {}; watchdog: {}",
+            if settings.is_empty() { "none".to_string() } else { settings.join(", ") },
+            watchdog
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    info!("Execution policy updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Execution policy updated.",
+        payload,
+    )))
+}
+
+/// List the reviewer teams defined for a project
+pub async fn get_teams(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<ProjectTeams>>> {
+    membership::require_project(&state, &claims, Some(id)).await?;
+    let teams = state.project_service.get_teams(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Teams retrieved.",
+        teams,
+    )))
+}
+
+/// Replace the reviewer teams for a project; proposals keep their assignments
+/// but a removed team no longer takes part in their review
+pub async fn update_teams(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ProjectTeams>,
+) -> ApiResult<Json<SuccessResponse<ProjectTeams>>> {
+    debug!("Updating teams for project: {}", id);
+
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    let project = state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    if project.owner_id != owner_id {
+        return Err(AppError::NotFound(format!("Project {} not found", id)));
+    }
+
+    payload.validate()?;
+
+    // Teams may only contain people who can see the project
+    let members: Vec<String> = state.project_service.member_ids(id).await?
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    if let Some(outsider) = payload.members().into_iter().find(|m| !members.iter().any(|id| id == m)) {
+        return Err(AppError::Validation(format!(
+            "User {} is not a member of project {}",
+            outsider, id
+        )));
+    }
+
+    state.project_service.set_teams(id, &payload).await?;
+
+    let slugs: Vec<&str> = payload.teams.iter().map(|t| t.slug.as_str()).collect();
+    let entry = AuditEntry::new(AuditAction::TeamsChanged, &claims.sub, "project", &id.to_string())
+        .with_project(Some(id))
+        .with_details(&format!("Teams: {}", if slugs.is_empty() { "none".to_string() } else { slugs.join(", ") }));
+    state.metadata.add_audit_entry(entry).await?;
+
+    info!("Teams updated for project {}", id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Teams updated.",
+        payload,
+    )))
+}
+
+/// Effective quota limits for a project and what it currently uses
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaResponse {
+    pub limits: ProjectQuota,
+    pub usage: QuotaUsage,
+}
+
+/// Get a project's quota limits (defaults merged with overrides) and usage
+pub async fn get_quotas(
+    State(state): State<SharedState>,
+    Extension(_claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<QuotaResponse>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let response = QuotaResponse {
+        limits: quota::limits(&state, Some(id)).await?,
+        usage: quota::usage(&state, Some(id)).await?,
+    };
+
+    Ok(Json(SuccessResponse::with_data(
+        "Quotas retrieved.",
+        response,
+    )))
+}
+
+/// Replace a project's quota overrides (admin only); unset limits fall back to the defaults
+pub async fn update_quotas(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ProjectQuota>,
+) -> ApiResult<Json<SuccessResponse<QuotaResponse>>> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can change quotas".to_string()));
+    }
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    state.project_service.set_quota(id, &payload).await?;
+
+    info!("Quotas updated for project {} by {}", id, claims.sub);
+
+    let response = QuotaResponse {
+        limits: quota::limits(&state, Some(id)).await?,
+        usage: quota::usage(&state, Some(id)).await?,
+    };
+
+    Ok(Json(SuccessResponse::with_data(
+        "Quotas updated.",
+        response,
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureQuery {
+    /// Apply this project's overrides; config values only if omitted
+    pub project_id: Option<i32>,
+}
+
+/// List feature flags in effect, for the frontend to show or hide capabilities
+pub async fn list_features(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<FeatureQuery>,
+) -> ApiResult<Json<SuccessResponse<Vec<FeatureStatus>>>> {
+    membership::require_project(&state, &claims, query.project_id).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Feature flags retrieved.",
+        features::resolve(&state, query.project_id).await?,
+    )))
+}
+
+/// Replace a project's feature flag overrides (admin only); flags left out follow the config
+pub async fn update_features(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<FeatureOverrides>,
+) -> ApiResult<Json<SuccessResponse<Vec<FeatureStatus>>>> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can change feature flags".to_string()));
+    }
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    payload.validate()?;
+    state.project_service.set_feature_overrides(id, &payload).await?;
+
+    info!("Feature flags updated for project {} by {}: {:?}", id, claims.sub, payload.flags);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Feature flags updated.",
+        features::resolve(&state, Some(id)).await?,
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFieldsPayload {
+    pub fields: Vec<CustomFieldDefinition>,
+}
+
+/// List the data dictionary custom fields defined for a project
+pub async fn get_custom_fields(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<CustomFieldsPayload>>> {
+    membership::require_project(&state, &claims, Some(id)).await?;
+    let dictionary = state.project_service.get_data_dictionary(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Custom fields retrieved.",
+        CustomFieldsPayload { fields: dictionary.fields },
+    )))
+}
+
+/// Replace a project's custom field definitions (admin only); values of removed fields are dropped
+pub async fn update_custom_fields(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<CustomFieldsPayload>,
+) -> ApiResult<Json<SuccessResponse<CustomFieldsPayload>>> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can define custom fields".to_string()));
+    }
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let mut dictionary = state.project_service.get_data_dictionary(id).await?.unwrap_or_default();
+    dictionary.set_fields(payload.fields)?;
+    state.project_service.set_data_dictionary(id, &dictionary).await?;
+
+    let keys: Vec<&str> = dictionary.fields.iter().map(|f| f.key.as_str()).collect();
+    let entry = AuditEntry::new(AuditAction::CustomFieldsChanged, &claims.sub, "project", &id.to_string())
+        .with_project(Some(id))
+        .with_details(&format!("Custom fields: {}", if keys.is_empty() { "none".to_string() } else { keys.join(", ") }));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Custom fields updated.",
+        CustomFieldsPayload { fields: dictionary.fields },
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagTaxonomyPayload {
+    pub tags: Vec<TagDefinition>,
+}
+
+/// List the tags a project's taxonomy allows
+pub async fn get_tag_taxonomy(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<TagTaxonomyPayload>>> {
+    membership::require_project(&state, &claims, Some(id)).await?;
+    let dictionary = state.project_service.get_data_dictionary(id).await?.unwrap_or_default();
+
+    Ok(Json(SuccessResponse::with_data(
+        "Tag taxonomy retrieved.",
+        TagTaxonomyPayload { tags: dictionary.tags },
+    )))
+}
+
+/// Replace a project's tag taxonomy (admin only); removed tags are detached everywhere
+pub async fn update_tag_taxonomy(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<TagTaxonomyPayload>,
+) -> ApiResult<Json<SuccessResponse<TagTaxonomyPayload>>> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can manage the tag taxonomy".to_string()));
+    }
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+
+    let mut dictionary = state.project_service.get_data_dictionary(id).await?.unwrap_or_default();
+    dictionary.set_tags(payload.tags)?;
+    state.project_service.set_data_dictionary(id, &dictionary).await?;
+
+    let names: Vec<&str> = dictionary.tags.iter().map(|t| t.name.as_str()).collect();
+    let entry = AuditEntry::new(AuditAction::TagTaxonomyChanged, &claims.sub, "project", &id.to_string())
+        .with_project(Some(id))
+        .with_details(&format!("Tags: {}", if names.is_empty() { "none".to_string() } else { names.join(", ") }));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        "Tag taxonomy updated.",
+        TagTaxonomyPayload { tags: dictionary.tags },
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    /// Reporting window such as "90d", "12w" or "48h"
+    #[serde(default = "default_analytics_period")]
+    pub period: String,
+}
+
+fn default_analytics_period() -> String {
+    "90d".to_string()
+}
+
+/// Summarize rule violations, safety scores, and trends for a project's proposals
+pub async fn get_violation_analytics(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Query(query): Query<AnalyticsQuery>,
+) -> ApiResult<Json<SuccessResponse<ViolationAnalytics>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    membership::require_project(&state, &claims, Some(id)).await?;
+
+    let until = Utc::now();
+    let since = until - analytics::parse_period(&query.period)?;
+    let records = state.project_service.list_rule_evaluations(id, since).await?;
+    let mut report = analytics::summarize(id, &records, since, until);
+
+    let proposals: Vec<_> = state.pipeline_proposals.list().await?
+        .into_iter()
+        .filter(|p| p.project_id == Some(id))
+        .collect();
+    report.review_sla = Some(sla::stats(&proposals, since, until, until));
+
+    Ok(Json(SuccessResponse::with_data(
+        format!(
+            "{} violation(s) across {} proposal(s) in the last {}.",
+            report.total_violations, report.proposals, query.period
+        ),
+        report,
+    )))
+}
+
+/// Proposals grouped by status for a board view, one call per board
+pub async fn get_proposal_board(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<SuccessResponse<ProposalBoard>>> {
+    state.project_service.get_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    membership::require_project(&state, &claims, Some(id)).await?;
+
+    let mut proposals = Vec::new();
+    for proposal in state.pipeline_proposals.list().await? {
+        // Proposals created before they were bound fall back to their connection's project
+        let project_id = match proposal.project_id {
+            Some(project_id) => Some(project_id),
+            None => state.connections.project_id(proposal.connection_id).await,
+        };
+        if project_id == Some(id) {
+            proposals.push(proposal);
+        }
+    }
+    let board = board::build(id, &proposals, Utc::now());
+
+    Ok(Json(SuccessResponse::with_data(
+        format!("{} proposal(s) on the board", board.total),
+        board,
+    )))
+}
+
 /// Delete a project
 pub async fn delete_project(
     State(state): State<SharedState>,
@@ -286,7 +898,7 @@ pub async fn save_connection(
     Extension(claims): Extension<Claims>,
     Path(project_id): Path<i32>,
     Json(payload): Json<SaveConnectionRequest>,
-) -> ApiResult<Json<SuccessResponse<SavedConnection>>> {
+) -> ApiResult<Json<SuccessResponse<ConnectionDetails>>> {
     debug!("Saving connection to project: {}", project_id);
 
     // Parse user_id from claims
@@ -297,14 +909,16 @@ pub async fn save_connection(
     let client = state.db_pool.get().await
         .map_err(|e| AppError::Internal(format!("Failed to get database connection: {}", e)))?;
 
-    // Insert saved connection into database
+    // Only the sealed string is stored
+    let sealed = state.connection_cipher.encrypt(project_id, &payload.connection_string)?;
     let row = client.query_one(
-        "INSERT INTO saved_connections (project_id, connection_string, database_type, connection_name, created_at, updated_at)
-         VALUES ($1, $2, $3, $4, $5, $6)
-         RETURNING id, project_id, connection_string, database_type, connection_name, created_at, updated_at",
+        "INSERT INTO saved_connections (project_id, connection_string_encrypted, encryption_key_id, database_type, connection_name, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id, project_id, connection_string, connection_string_encrypted, encryption_key_id, database_type, connection_name, created_at, updated_at",
         &[
             &project_id,
-            &payload.connection_string,
+            &sealed.blob,
+            &sealed.key_id,
             &payload.connection_type,
             &payload.name,
             &Utc::now(),
@@ -316,27 +930,54 @@ pub async fn save_connection(
         AppError::Internal(format!("Failed to save connection: {}", e))
     })?;
 
-    let connection = SavedConnection {
+    let connection = connection_details(&row, &state.connection_cipher, false);
+
+    info!("Connection saved: {}", connection.id);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Connection saved successfully.",
+        connection,
+    )))
+}
+
+/// Connection string of a saved connection row, decrypted. Rows saved before
+/// encryption keep their plaintext until startup seals them.
+fn stored_connection_string(row: &tokio_postgres::Row, cipher: &ConnectionCipher) -> ApiResult<String> {
+    let blob: Option<Vec<u8>> = row.get("connection_string_encrypted");
+    let key_id: Option<String> = row.get("encryption_key_id");
+    match (blob, key_id) {
+        (Some(blob), Some(key_id)) => cipher.decrypt(row.get("project_id"), &SealedConnection { key_id, blob }),
+        _ => row
+            .get::<_, Option<String>>("connection_string")
+            .ok_or_else(|| AppError::Internal("Saved connection has no connection string".to_string())),
+    }
+}
+
+/// Build the client-facing view of a saved connection row; the stored string
+/// is reduced to host, database and user. A row that can't be decrypted is
+/// still listed, flagged with the reason, so it can be removed or re-saved.
+fn connection_details(row: &tokio_postgres::Row, cipher: &ConnectionCipher, is_active: bool) -> ConnectionDetails {
+    let (target, decryption_error) = match stored_connection_string(row, cipher) {
+        Ok(connection_string) => (ConnectionTarget::from_connection_string(&connection_string), None),
+        Err(e) => {
+            warn!("Saved connection {} cannot be read: {}", row.get::<_, i32>("id"), e);
+            (None, Some(e.to_string()))
+        }
+    };
+    ConnectionDetails {
         id: row.get("id"),
         project_id: row.get("project_id"),
         name: row.get("connection_name"),
-        connection_string_encrypted: vec![],
         connection_type: row.get("database_type"),
         environment: "production".to_string(),
-        is_active: false,
+        target,
+        decryption_error,
+        is_active,
         last_tested: None,
         test_status: None,
-        created_by: _user_id,
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
-    };
-
-    info!("Connection saved: {}", connection.id);
-
-    Ok(Json(SuccessResponse::with_data(
-        "Connection saved successfully.",
-        connection,
-    )))
+    }
 }
 
 /// List all connections for a project
@@ -365,7 +1006,7 @@ pub async fn list_connections(
 
     // Fetch all connections for the project
     let rows = client.query(
-        "SELECT id, project_id, connection_name, database_type, created_at, updated_at
+        "SELECT id, project_id, connection_string, connection_string_encrypted, encryption_key_id, connection_name, database_type, created_at, updated_at
          FROM saved_connections
          WHERE project_id = $1
          ORDER BY created_at DESC",
@@ -376,20 +1017,9 @@ pub async fn list_connections(
         AppError::Internal(format!("Failed to list connections: {}", e))
     })?;
 
-    let connections: Vec<ConnectionDetails> = rows.iter().map(|row| {
-        ConnectionDetails {
-            id: row.get("id"),
-            project_id: row.get("project_id"),
-            name: row.get("connection_name"),
-            connection_type: row.get("database_type"),
-            environment: "production".to_string(),
-            is_active: false,
-            last_tested: None,
-            test_status: None,
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        }
-    }).collect();
+    let connections: Vec<ConnectionDetails> = rows.iter()
+        .map(|row| connection_details(row, &state.connection_cipher, false))
+        .collect();
 
     Ok(Json(SuccessResponse::with_data(
         format!("{} connections found.", connections.len()),
@@ -471,7 +1101,7 @@ pub async fn activate_connection(
 
     // Fetch the connection
     let row = client.query_opt(
-        "SELECT id, project_id, connection_name, database_type, created_at, updated_at
+        "SELECT id, project_id, connection_string, connection_string_encrypted, encryption_key_id, connection_name, database_type, created_at, updated_at
          FROM saved_connections
          WHERE id = $1 AND project_id = $2",
         &[&connection_id, &project_id],
@@ -479,18 +1109,7 @@ pub async fn activate_connection(
     .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?
     .ok_or_else(|| AppError::NotFound(format!("Connection {} not found", connection_id)))?;
 
-    let connection = ConnectionDetails {
-        id: row.get("id"),
-        project_id: row.get("project_id"),
-        name: row.get("connection_name"),
-        connection_type: row.get("database_type"),
-        environment: "production".to_string(),
-        is_active: true,
-        last_tested: None,
-        test_status: None,
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-    };
+    let connection = connection_details(&row, &state.connection_cipher, true);
 
     info!("Connection activated: {}", connection.id);
 
@@ -498,4 +1117,74 @@ pub async fn activate_connection(
         "Connection activated successfully.",
         connection,
     )))
-}
\ No newline at end of file
+}
+
+/// Shortest justification accepted for revealing credentials
+const MIN_REVEAL_JUSTIFICATION: usize = 10;
+
+/// Reveal the full connection string of a saved connection. Admin only; the
+/// justification is written to the audit log.
+pub async fn reveal_connection(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path((project_id, connection_id)): Path<(i32, i32)>,
+    Json(payload): Json<RevealConnectionRequest>,
+) -> ApiResult<Json<SuccessResponse<RevealedConnection>>> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can reveal connection strings".to_string()));
+    }
+
+    let justification = payload.justification.trim();
+    if justification.len() < MIN_REVEAL_JUSTIFICATION {
+        return Err(AppError::BadRequest(format!(
+            "A justification of at least {} characters is required",
+            MIN_REVEAL_JUSTIFICATION
+        )));
+    }
+
+    // Parse user_id from claims
+    let owner_id: i32 = claims.sub.parse()
+        .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+    // Get database client (required - no fallback)
+    let client = state.db_pool.get().await
+        .map_err(|e| AppError::Internal(format!("Failed to get database connection: {}", e)))?;
+
+    // Verify project ownership
+    let _project_exists = client.query_opt(
+        "SELECT id FROM projects WHERE id = $1 AND owner_id = $2",
+        &[&project_id, &owner_id],
+    ).await
+    .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::NotFound("Project not found".to_string()))?;
+
+    let row = client.query_opt(
+        "SELECT project_id, connection_string, connection_string_encrypted, encryption_key_id
+         FROM saved_connections WHERE id = $1 AND project_id = $2",
+        &[&connection_id, &project_id],
+    ).await
+    .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::NotFound(format!("Connection {} not found", connection_id)))?;
+    let connection_string = stored_connection_string(&row, &state.connection_cipher)?;
+
+    let entry = AuditEntry::new(
+        AuditAction::ConnectionRevealed,
+        &claims.sub,
+        "saved_connection",
+        &connection_id.to_string(),
+    )
+    .with_project(Some(project_id))
+    .with_details(justification);
+    state.metadata.add_audit_entry(entry).await?;
+
+    info!("Connection string of {} revealed to user {}", connection_id, claims.sub);
+
+    Ok(Json(SuccessResponse::with_data(
+        "Connection string revealed. This access has been audited.",
+        RevealedConnection {
+            id: connection_id,
+            project_id,
+            connection_string,
+        },
+    )))
+}
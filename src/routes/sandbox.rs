@@ -0,0 +1,181 @@
+//! Sandbox route handlers
+//!
+//! Disposable databases built from a connection's snapshots, for proposal
+//! authors to experiment against real structure. See `pipeline::sandbox`.
+
+use crate::auth::Claims;
+use crate::error::{ApiResult, AppError};
+use crate::models::SuccessResponse;
+use crate::pipeline::membership;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::sandbox::{self, Sandbox, DEFAULT_SANDBOX_HOURS};
+use crate::quota;
+use crate::state::SharedState;
+use axum::{
+    extract::{Extension, Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Request to build a sandbox
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSandboxRequest {
+    /// Snapshot to build from (the latest if omitted)
+    pub snapshot_version: Option<u64>,
+    /// Hours until the sandbox is dropped (DEFAULT_SANDBOX_HOURS if omitted)
+    pub ttl_hours: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendSandboxRequest {
+    /// Hours from now until the sandbox is dropped
+    pub ttl_hours: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxListResponse {
+    pub sandboxes: Vec<Sandbox>,
+}
+
+/// A sandbox the caller's project may see
+async fn find_sandbox(state: &SharedState, claims: &Claims, id: Uuid) -> Result<Sandbox, AppError> {
+    let sandbox = state
+        .sandboxes
+        .get(id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Sandbox {} not found", id)))?;
+    membership::require_project(state, claims, sandbox.project_id).await?;
+    Ok(sandbox)
+}
+
+/// Only the sandbox's creator or an approver may change it
+fn require_owner(claims: &Claims, sandbox: &Sandbox) -> Result<(), AppError> {
+    if sandbox.created_by != claims.sub && !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only the sandbox's creator or an admin can change it".to_string()));
+    }
+    if !sandbox.is_active() {
+        return Err(AppError::Conflict(format!("Sandbox {} has already been dropped", sandbox.id)));
+    }
+    Ok(())
+}
+
+/// POST /api/connections/{id}/sandboxes
+/// Build a sandbox database from one of the connection's snapshots
+pub async fn create_sandbox(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<CreateSandboxRequest>,
+) -> ApiResult<Json<SuccessResponse<Sandbox>>> {
+    if !claims.role.can_propose() {
+        return Err(AppError::Forbidden("Viewers cannot create sandboxes".to_string()));
+    }
+    let project_id = membership::require_connection(&state, &claims, connection_id).await?;
+    quota::check_connection(&state, project_id, &claims, &headers).await?;
+
+    let snapshot = match req.snapshot_version {
+        Some(version) => state.snapshots.get_version(connection_id, version).await?.ok_or_else(|| {
+            AppError::NotFound(format!("Snapshot v{} not found for connection {}", version, connection_id))
+        })?,
+        None => state
+            .snapshots
+            .get_latest(connection_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?,
+    };
+
+    let sandbox = sandbox::provision(&state, &snapshot, &claims.sub, req.ttl_hours.unwrap_or(DEFAULT_SANDBOX_HOURS)).await?;
+
+    let entry = AuditEntry::new(AuditAction::SandboxCreated, &claims.sub, "connection", &connection_id.to_string())
+        .with_project(project_id)
+        .with_details(&format!(
+            "Sandbox {} from snapshot v{}, expires {}",
+            sandbox.database,
+            sandbox.snapshot_version,
+            sandbox.expires_at.to_rfc3339()
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        format!("Sandbox '{}' is ready as connection {}.", sandbox.database, sandbox.connection_id),
+        sandbox,
+    )))
+}
+
+/// GET /api/connections/{id}/sandboxes
+/// Sandboxes built from a connection, including dropped ones
+pub async fn list_sandboxes(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> ApiResult<Json<SuccessResponse<SandboxListResponse>>> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    let sandboxes = state.sandboxes.list_for_connection(connection_id).await;
+
+    Ok(Json(SuccessResponse::with_data("Sandboxes retrieved", SandboxListResponse { sandboxes })))
+}
+
+/// GET /api/sandboxes/{id}
+pub async fn get_sandbox(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SuccessResponse<Sandbox>>> {
+    let sandbox = find_sandbox(&state, &claims, id).await?;
+    Ok(Json(SuccessResponse::with_data("Sandbox retrieved", sandbox)))
+}
+
+/// POST /api/sandboxes/{id}/extend
+/// Keep a sandbox for longer
+pub async fn extend_sandbox(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ExtendSandboxRequest>,
+) -> ApiResult<Json<SuccessResponse<Sandbox>>> {
+    let sandbox = find_sandbox(&state, &claims, id).await?;
+    require_owner(&claims, &sandbox)?;
+
+    let sandbox = state
+        .sandboxes
+        .extend(id, req.ttl_hours)
+        .await
+        .ok_or_else(|| AppError::Conflict(format!("Sandbox {} has already been dropped", id)))?;
+    sandbox::stamp_expiry(&state, &sandbox).await;
+
+    let entry = AuditEntry::new(AuditAction::SandboxExtended, &claims.sub, "connection", &sandbox.source_connection_id.to_string())
+        .with_project(sandbox.project_id)
+        .with_details(&format!("Sandbox {} now expires {}", sandbox.database, sandbox.expires_at.to_rfc3339()));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data("Sandbox extended", sandbox)))
+}
+
+/// DELETE /api/sandboxes/{id}
+/// Disconnect a sandbox and drop its database before it expires
+pub async fn delete_sandbox(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<SuccessResponse<Sandbox>>> {
+    let sandbox = find_sandbox(&state, &claims, id).await?;
+    require_owner(&claims, &sandbox)?;
+
+    let sandbox = sandbox::tear_down(&state, &sandbox, &claims.sub).await?;
+
+    let entry = AuditEntry::new(AuditAction::SandboxDropped, &claims.sub, "connection", &sandbox.source_connection_id.to_string())
+        .with_project(sandbox.project_id)
+        .with_details(&format!("Sandbox {} dropped", sandbox.database));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(SuccessResponse::with_data(
+        format!("Sandbox '{}' dropped.", sandbox.database),
+        sandbox,
+    )))
+}
@@ -4,11 +4,26 @@
 
 use crate::auth::Claims;
 use crate::error::AppError;
-use crate::introspection::PostgresIntrospector;
-use crate::snapshot::{BlastRadiusAnalyzer, DiffEngine, SchemaDiff};
+use crate::notifications::{Audience, Notification};
+use crate::pipeline::membership;
+use crate::pipeline::metadata::{AuditAction, AuditEntry};
+use crate::pipeline::reanalysis::invalidate_risk;
+use crate::pipeline::watches::{self, WatchEvent};
+use crate::quota;
+use crate::snapshot::benchmark::{self, BenchmarkReport, BenchmarkRequest};
+use crate::snapshot::coverage::{self, CoverageQuery, CoverageReport};
+use crate::snapshot::propagation::{self, PropagationReport};
+use crate::snapshot::dictionary::{self, DictionaryEntry, DictionaryQuery};
+use crate::snapshot::docs::{self, DocsBundle, DocsFormat};
+use crate::snapshot::encryption::{self, EncryptionReport};
+use crate::snapshot::migration::{self, DiffFormat, DiffMigration};
+use crate::snapshot::stream::{self, ObjectFilter};
+use crate::snapshot::{BlastRadiusAnalyzer, DiffEngine, SchemaDiff, SnapshotArchive, TraversalLimits};
 use crate::state::SharedState;
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -45,6 +60,9 @@ pub struct DiffQuery {
     pub from_version: Option<u64>,
     /// To version (defaults to latest)
     pub to_version: Option<u64>,
+    /// `sql` adds a migration and reverse script for the diff
+    #[serde(default)]
+    pub format: DiffFormat,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,6 +71,9 @@ pub struct DiffResponse {
     pub success: bool,
     pub diff: SchemaDiff,
     pub rules_result: crate::snapshot::rules::RulesResult,
+    /// Present when the SQL format was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migration: Option<DiffMigration>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,6 +82,9 @@ pub struct BlastRadiusRequest {
     pub schema: String,
     pub table: String,
     pub column: Option<String>,
+    /// Overrides the connection's traversal limits for this request
+    #[serde(flatten)]
+    pub limits: TraversalLimits,
 }
 
 #[derive(Debug, Serialize)]
@@ -70,6 +94,47 @@ pub struct BlastRadiusResponse {
     pub blast_radius: crate::snapshot::BlastRadius,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResponse {
+    pub success: bool,
+    pub report: BenchmarkReport,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSnapshotsRequest {
+    /// Number of recent versions to keep in the store
+    #[serde(default = "default_keep_versions")]
+    pub keep_versions: usize,
+}
+
+fn default_keep_versions() -> usize {
+    5
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSnapshotsResponse {
+    pub success: bool,
+    pub message: String,
+    pub archived: Vec<crate::snapshot::archive::ArchivedObject>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedListResponse {
+    pub success: bool,
+    pub objects: Vec<crate::snapshot::archive::ArchivedObject>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSnapshotRequest {
+    /// Archive key returned by the archived listing
+    pub key: String,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RulesListResponse {
@@ -77,6 +142,13 @@ pub struct RulesListResponse {
     pub rules: Vec<crate::snapshot::Rule>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionReportResponse {
+    pub success: bool,
+    pub report: EncryptionReport,
+}
+
 // ==================== Handlers ====================
 
 /// Create a new schema snapshot for a connection
@@ -85,13 +157,25 @@ pub async fn create_snapshot(
     State(state): State<SharedState>,
     Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(req): Json<CreateSnapshotRequest>,
 ) -> Result<Json<SnapshotResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
     // Get the connection
-    let pool = state.connections.get_pool(connection_id).await?;
+    let conn = state
+        .connections
+        .get_connection(connection_id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Connection {} not found", connection_id)))?;
+    quota::check_snapshot(&state, connection_id, &claims, &headers).await?;
     
     // Introspect current schema
-    let snapshot = PostgresIntrospector::introspect(&pool, connection_id).await?;
+    let mut snapshot = conn
+        .introspect()
+        .await?
+        .with_column_order(state.connections.column_order(connection_id).await);
+    dictionary::annotate(&state, &mut snapshot).await?;
     
     // Save the snapshot (auto-increments version)
     let snapshot = state.snapshots.save(snapshot).await?;
@@ -113,10 +197,12 @@ pub async fn create_snapshot(
 /// List all snapshots for a connection
 pub async fn list_snapshots(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
 ) -> Result<Json<SnapshotListResponse>, AppError> {
-    let snapshots = state.snapshots.list(connection_id).await;
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshots = state.snapshots.list(connection_id).await?;
     
     Ok(Json(SnapshotListResponse {
         success: true,
@@ -124,47 +210,69 @@ pub async fn list_snapshots(
     }))
 }
 
-/// Get the latest snapshot for a connection
+/// Get the latest snapshot for a connection, filtered by `schema` and `table`
+/// and streamed as NDJSON when asked for
 pub async fn get_latest_snapshot(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
-) -> Result<Json<SnapshotResponse>, AppError> {
-    let snapshot = state.snapshots.get_latest(connection_id).await
+    headers: HeaderMap,
+    Query(filter): Query<ObjectFilter>,
+) -> Result<Response, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshot = state.snapshots.get_latest(connection_id).await?
         .ok_or_else(|| AppError::NotFound("No snapshots found for this connection".to_string()))?;
+    let snapshot = filter.apply_to_snapshot(snapshot);
+    if stream::wants_ndjson(&headers) {
+        return Ok(stream::snapshot_response(snapshot));
+    }
     
     Ok(Json(SnapshotResponse {
         success: true,
         message: format!("Latest snapshot v{}", snapshot.version),
         snapshot,
-    }))
+    }).into_response())
 }
 
-/// Get a specific snapshot version
+/// Get a specific snapshot version, filtered and streamed like the latest one
 pub async fn get_snapshot_version(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path((connection_id, version)): Path<(Uuid, u64)>,
-) -> Result<Json<SnapshotResponse>, AppError> {
-    let snapshot = state.snapshots.get_version(connection_id, version).await
+    headers: HeaderMap,
+    Query(filter): Query<ObjectFilter>,
+) -> Result<Response, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshot = state.snapshots.get_version(connection_id, version).await?
         .ok_or_else(|| AppError::NotFound(format!("Snapshot v{} not found", version)))?;
+    let snapshot = filter.apply_to_snapshot(snapshot);
+    if stream::wants_ndjson(&headers) {
+        return Ok(stream::snapshot_response(snapshot));
+    }
     
     Ok(Json(SnapshotResponse {
         success: true,
         message: format!("Snapshot v{}", version),
         snapshot,
-    }))
+    }).into_response())
 }
 
-/// Compare two schema snapshots and show diff + rules violations
+/// Compare two schema snapshots and show diff + rules violations.
+/// Rules see the whole diff; filters only narrow the changes returned.
 pub async fn diff_snapshots(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
+    headers: HeaderMap,
     Query(query): Query<DiffQuery>,
-) -> Result<Json<DiffResponse>, AppError> {
+    Query(filter): Query<ObjectFilter>,
+) -> Result<Response, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
     // Get latest version
-    let latest = state.snapshots.get_latest(connection_id).await
+    let latest = state.snapshots.get_latest(connection_id).await?
         .ok_or_else(|| AppError::NotFound("No snapshots found".to_string()))?;
     
     let to_version = query.to_version.unwrap_or(latest.version);
@@ -179,35 +287,59 @@ pub async fn diff_snapshots(
         .compare_versions(connection_id, from_version, to_version)
         .await?;
     
-    // Compute diff
-    let diff = DiffEngine::diff(&from_snapshot, &to_snapshot);
+    // Older snapshots may predate the connection's schema scope
+    let scope = state.connections.schema_scope(connection_id).await;
+    let (from_snapshot, to_snapshot) = (from_snapshot.scoped(&scope), to_snapshot.scoped(&scope));
+    
+    // Compute diff, minus what the connection ignores
+    let ignore = state.connections.diff_ignore(connection_id).await?;
+    let diff = DiffEngine::diff_ignoring(&from_snapshot, &to_snapshot, &ignore);
+    
+    // Evaluate rules against the diff, at the connection environment's severities
+    let environment = state.connections.environment(connection_id).await;
+    let mut rules_result = state.rules.evaluate(&diff, &to_snapshot, &environment);
+    rules_result.localize(&state.translations, &state.translations.language_for(&headers));
     
-    // Evaluate rules against the diff
-    let rules_result = state.rules.evaluate(&diff, &to_snapshot);
+    let diff = filter.apply_to_diff(diff);
+    let migration = (query.format == DiffFormat::Sql).then(|| migration::from_diff(&diff, &from_snapshot));
+    
+    if stream::wants_ndjson(&headers) {
+        let mut trailers = vec![("rules", serde_json::to_value(&rules_result).unwrap_or_default())];
+        if let Some(migration) = &migration {
+            trailers.push(("migration", serde_json::to_value(migration).unwrap_or_default()));
+        }
+        return Ok(stream::diff_response(diff, trailers));
+    }
     
     Ok(Json(DiffResponse {
         success: true,
         diff,
         rules_result,
-    }))
+        migration,
+    }).into_response())
 }
 
 /// Analyze blast radius for a table or column
 pub async fn analyze_blast_radius(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
     Json(req): Json<BlastRadiusRequest>,
 ) -> Result<Json<BlastRadiusResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
     // Get the latest snapshot
-    let snapshot = state.snapshots.get_latest(connection_id).await
+    let snapshot = state.snapshots.get_latest(connection_id).await?
         .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?;
     
+    req.limits.validate()?;
+    let limits = req.limits.or(state.connections.blast_radius_limits(connection_id).await);
+
     // Analyze blast radius
     let blast_radius = if let Some(column) = req.column {
-        BlastRadiusAnalyzer::analyze_column(&snapshot, &req.schema, &req.table, &column)
+        BlastRadiusAnalyzer::analyze_column_within(&snapshot, &req.schema, &req.table, &column, limits)
     } else {
-        BlastRadiusAnalyzer::analyze_table(&snapshot, &req.schema, &req.table)
+        BlastRadiusAnalyzer::analyze_table_within(&snapshot, &req.schema, &req.table, limits)
     };
     
     Ok(Json(BlastRadiusResponse {
@@ -248,27 +380,341 @@ pub async fn list_rules(
     }))
 }
 
+/// List Restricted/Secret columns and whether they are encrypted, for security review
+pub async fn encryption_report(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<Json<EncryptionReportResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    // Prefer the live schema so freshly tagged columns show up
+    let snapshot = match state.connections.get_connection(connection_id).await {
+        Some(conn) => conn.introspect().await?,
+        None => state.snapshots.get_latest(connection_id).await?
+            .ok_or_else(|| AppError::NotFound("Connection is not active and has no snapshots".to_string()))?,
+    };
+    
+    Ok(Json(EncryptionReportResponse {
+        success: true,
+        report: encryption::report(&snapshot),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocsQuery {
+    #[serde(default)]
+    pub format: DocsFormat,
+    /// Snapshot version to document (latest if omitted)
+    pub version: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocsResponse {
+    pub success: bool,
+    pub docs: DocsBundle,
+}
+
+/// Generate a documentation bundle (a page per table plus ER diagram data) from a snapshot
+pub async fn generate_docs(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(query): Query<DocsQuery>,
+) -> Result<Json<DocsResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshot = match query.version {
+        Some(version) => state.snapshots.get_version(connection_id, version).await?
+            .ok_or_else(|| AppError::NotFound(format!("Snapshot v{} not found", version)))?,
+        None => state.snapshots.get_latest(connection_id).await?
+            .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?,
+    };
+    let scope = state.connections.schema_scope(connection_id).await;
+
+    Ok(Json(DocsResponse {
+        success: true,
+        docs: docs::generate(&snapshot.scoped(&scope), query.format),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionarySearchResponse {
+    pub success: bool,
+    pub entries: Vec<DictionaryEntry>,
+}
+
+/// Search the data dictionary of the latest snapshot by text, custom field and value
+pub async fn search_dictionary(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(query): Query<DictionaryQuery>,
+) -> Result<Json<DictionarySearchResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    // Current values, even if they changed after the snapshot was taken
+    let mut snapshot = state.latest_scoped_snapshot(connection_id).await?
+        .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?;
+    dictionary::annotate(&state, &mut snapshot).await?;
+    
+    Ok(Json(DictionarySearchResponse {
+        success: true,
+        entries: dictionary::search(&snapshot, &query),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropagationResponse {
+    pub success: bool,
+    pub report: PropagationReport,
+}
+
+/// Columns that should inherit a PII classification from the columns they
+/// reference or project
+pub async fn classification_propagation(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<Json<PropagationResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+
+    Ok(Json(PropagationResponse {
+        success: true,
+        report: propagation::for_connection(&state, connection_id).await?,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageResponse {
+    pub success: bool,
+    pub report: CoverageReport,
+}
+
+/// Share of tables and columns with descriptions, owners, tags and PII classification
+pub async fn governance_coverage(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(query): Query<CoverageQuery>,
+) -> Result<Json<CoverageResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshot = state.latest_scoped_snapshot(connection_id).await?
+        .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?;
+    
+    Ok(Json(CoverageResponse {
+        success: true,
+        report: coverage::report(&snapshot, &query),
+    }))
+}
+
 /// Compare current live schema against baseline
 pub async fn check_drift(
     State(state): State<SharedState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<DiffResponse>, AppError> {
-    // Get baseline
-    let baseline = state.snapshots.get_baseline(connection_id).await
-        .ok_or_else(|| AppError::NotFound("No baseline set. Set a baseline first.".to_string()))?;
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    // Get baseline, limited to the current scope in case it was captured before it
+    let scope = state.connections.schema_scope(connection_id).await;
+    let baseline = state.snapshots.get_baseline(connection_id).await?
+        .ok_or_else(|| AppError::NotFound("No baseline set. Set a baseline first.".to_string()))?
+        .scoped(&scope);
     
     // Get current live schema
-    let pool = state.connections.get_pool(connection_id).await?;
-    let current = PostgresIntrospector::introspect(&pool, connection_id).await?;
+    let ignore = state.connections.diff_ignore(connection_id).await?;
+    let mut current = state
+        .connections
+        .introspect(connection_id)
+        .await?
+        .with_column_order(ignore.column_order());
+    // The baseline carries dictionary metadata; without it every tag and
+    // custom field would show as removed
+    dictionary::annotate(&state, &mut current).await?;
     
     // Compute drift
-    let diff = DiffEngine::diff(&baseline, &current);
-    let rules_result = state.rules.evaluate(&diff, &current);
+    let diff = DiffEngine::diff_ignoring(&baseline, &current, &ignore);
+    let environment = state.connections.environment(connection_id).await;
+    let mut rules_result = state.rules.evaluate(&diff, &current, &environment);
+    rules_result.localize(&state.translations, &state.translations.language_for(&headers));
+    
+    // Polled regularly; only drift that differs from the last one found is announced
+    let fingerprint = (!diff.changes.is_empty()).then(|| diff.fingerprint());
+    if state.connections.record_drift(connection_id, fingerprint).await && !diff.changes.is_empty() {
+        let connection_name = state
+            .connections
+            .get_connection(connection_id)
+            .await
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| connection_id.to_string());
+        // The project's owner hears about it; watchers are notified below
+        let audience = match state.connections.project_id(connection_id).await {
+            Some(project_id) => match state.project_service.get_by_id(project_id).await? {
+                Some(project) => Audience::Users(vec![project.owner_id.to_string()]),
+                None => Audience::Admins,
+            },
+            None => Audience::Admins,
+        };
+        state.notifier.notify(
+            Notification::DriftDetected {
+                connection_id,
+                connection_name,
+                changes: diff.changes.len(),
+                breaking: diff.changes.iter().filter(|c| c.is_breaking).count(),
+                ownership: diff.summary.ownership_changes,
+                permissions: diff.summary.permission_changes,
+                extensions: diff.summary.extension_changes,
+                policies: diff.summary.policy_changes,
+                types: diff.summary.type_changes,
+            },
+            audience,
+        );
+        invalidate_risk(&state, connection_id, "schema drift").await;
+        watches::notify(&state, connection_id, &watches::drift_objects(&diff), WatchEvent::Drifted, None, None).await;
+    }
     
     Ok(Json(DiffResponse {
         success: true,
         diff,
         rules_result,
+        migration: None,
+    }))
+}
+
+fn archive(state: &SharedState) -> Result<&SnapshotArchive, AppError> {
+    state
+        .archive
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Snapshot archive is not configured".to_string()))
+}
+
+/// Move all but the newest snapshots of a connection to the archive
+pub async fn archive_snapshots(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<ArchiveSnapshotsRequest>,
+) -> Result<Json<ArchiveSnapshotsResponse>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can archive snapshots".to_string()));
+    }
+    let archive = archive(&state)?;
+    
+    // list() is newest first, so everything past keep_versions goes
+    let old_versions: Vec<u64> = state
+        .snapshots
+        .list(connection_id)
+        .await?
+        .into_iter()
+        .skip(req.keep_versions)
+        .map(|m| m.version)
+        .collect();
+    
+    let mut archived = Vec::with_capacity(old_versions.len());
+    let mut archived_versions = Vec::with_capacity(old_versions.len());
+    for version in old_versions {
+        if let Some(snapshot) = state.snapshots.get_version(connection_id, version).await? {
+            archived.push(archive.archive_snapshot(&snapshot).await?);
+            archived_versions.push(version);
+        }
+    }
+    
+    // Only remove once every upload succeeded, and only what was uploaded;
+    // a snapshot saved meanwhile shifts which versions count as old
+    state.snapshots.remove_versions(connection_id, &archived_versions).await?;
+    
+    tracing::info!(
+        "User {} archived {} snapshots for connection {}",
+        claims.sub,
+        archived.len(),
+        connection_id
+    );
+    
+    Ok(Json(ArchiveSnapshotsResponse {
+        success: true,
+        message: format!("Archived {} snapshots", archived.len()),
+        archived,
+    }))
+}
+
+/// List archived snapshots for a connection
+pub async fn list_archived_snapshots(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<Json<ArchivedListResponse>, AppError> {
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let objects = archive(&state)?.list_snapshots(connection_id).await?;
+    
+    Ok(Json(ArchivedListResponse {
+        success: true,
+        objects,
+    }))
+}
+
+/// Restore an archived snapshot back into the store
+pub async fn restore_snapshot(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<RestoreSnapshotRequest>,
+) -> Result<Json<SnapshotResponse>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can restore snapshots".to_string()));
+    }
+    membership::require_connection(&state, &claims, connection_id).await?;
+    
+    let snapshot = archive(&state)?.fetch_snapshot(connection_id, &req.key).await?;
+    let snapshot = state.snapshots.restore(snapshot).await?;
+    
+    tracing::info!(
+        "User {} restored snapshot v{} for connection {}",
+        claims.sub,
+        snapshot.version,
+        connection_id
+    );
+    
+    Ok(Json(SnapshotResponse {
+        success: true,
+        message: format!("Snapshot v{} restored from archive", snapshot.version),
+        snapshot,
+    }))
+}
+
+/// Time introspection, diff and blast radius on a synthetic schema built in
+/// a scratch schema of the connection's database (admin only)
+pub async fn run_benchmark(
+    State(state): State<SharedState>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(req): Json<BenchmarkRequest>,
+) -> Result<Json<BenchmarkResponse>, AppError> {
+    if !claims.role.can_approve() {
+        return Err(AppError::Forbidden("Only admins can run benchmarks".to_string()));
+    }
+    let project_id = membership::require_connection(&state, &claims, connection_id).await?;
+    req.validate()?;
+
+    let pool = state.connections.get_pool(connection_id).await?;
+    let report = benchmark::run(&pool, connection_id, &req).await?;
+
+    let entry = AuditEntry::new(AuditAction::BenchmarkRun, &claims.sub, "connection", &connection_id.to_string())
+        .with_project(project_id)
+        .with_details(&format!(
+            "{} tables, {} foreign keys; introspection median {:.1} ms",
+            report.tables, report.foreign_keys, report.introspection.median_ms
+        ));
+    state.metadata.add_audit_entry(entry).await?;
+
+    Ok(Json(BenchmarkResponse {
+        success: true,
+        report,
     }))
 }
@@ -1,12 +1,31 @@
 //! Dry run executor
 //!
 //! Validates migrations in a transaction that gets rolled back.
+//!
+//! The deep dry run executes a migration's statements against the real data
+//! inside one transaction, each under its own savepoint, so that failures
+//! only the data reveals (SET NOT NULL over existing NULLs, a unique
+//! constraint over duplicates, a foreign key with orphans) show up before the
+//! real run. A failed statement is rolled back to its savepoint and the run
+//! carries on, so every such problem is reported at once. The transaction is
+//! always rolled back. Locks are real while it lasts, so lock waits and
+//! statements are kept short.
 
 use crate::error::AppError;
+use crate::pipeline::execution_policy;
+use crate::pipeline::orchestrator::requires_autocommit;
+use crate::pipeline::scratch::LOCK_TIMEOUT_MS;
 use crate::proposal::MigrationGenerator;
 use crate::proposal::SchemaChange;
 use deadpool_postgres::Pool;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::SimpleQueryMessage;
+
+/// Longest a statement may run in a deep dry run before it is left unchecked
+const DEEP_STATEMENT_TIMEOUT_MS: u32 = 30_000;
 
 pub struct DryRunner;
 
@@ -19,7 +38,193 @@ pub struct DryRunResult {
     pub warnings: Vec<String>,
 }
 
+/// Findings of a deep dry run; nothing it did was kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepDryRun {
+    /// No statement failed
+    pub success: bool,
+    pub statements: Vec<StatementFinding>,
+    /// Rows the statements changed, together
+    pub rows_affected: u64,
+    pub duration_ms: u64,
+}
+
+impl DeepDryRun {
+    /// The first statement that would fail
+    pub fn first_failure(&self) -> Option<&StatementFinding> {
+        self.statements.iter().find(|s| s.outcome == StatementOutcome::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementOutcome {
+    Succeeded,
+    Failed,
+    /// Ran past the time limit and was cancelled; its effect is unknown
+    TimedOut,
+    /// PostgreSQL cannot run it inside a transaction
+    Skipped,
+}
+
+/// What one statement did in a deep dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementFinding {
+    /// Zero-based position of the statement in the migration
+    pub statement_index: usize,
+    pub outcome: StatementOutcome,
+    /// Rows inserted, updated or deleted; DDL reports none
+    pub rows_affected: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set when existing data breaks a constraint the statement adds or relies on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violation: Option<ConstraintViolation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    NotNull,
+    Unique,
+    ForeignKey,
+    Check,
+    Exclusion,
+}
+
+impl ViolationKind {
+    /// The kind of integrity constraint violation a SQLSTATE stands for
+    pub fn from_sqlstate(code: &SqlState) -> Option<Self> {
+        match *code {
+            SqlState::NOT_NULL_VIOLATION => Some(Self::NotNull),
+            SqlState::UNIQUE_VIOLATION => Some(Self::Unique),
+            SqlState::FOREIGN_KEY_VIOLATION => Some(Self::ForeignKey),
+            SqlState::CHECK_VIOLATION => Some(Self::Check),
+            SqlState::EXCLUSION_VIOLATION => Some(Self::Exclusion),
+            _ => None,
+        }
+    }
+}
+
+/// Existing data breaking a constraint, as PostgreSQL reported it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintViolation {
+    pub kind: ViolationKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<String>,
+    /// Usually names an offending row, e.g. the duplicated key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ConstraintViolation {
+    fn from_error(e: &tokio_postgres::Error) -> Option<Self> {
+        let db = e.as_db_error()?;
+        let kind = ViolationKind::from_sqlstate(db.code())?;
+        let table = db.table().map(|table| match db.schema() {
+            Some(schema) => format!("{}.{}", schema, table),
+            None => table.to_string(),
+        });
+        Some(Self {
+            kind,
+            table,
+            column: db.column().map(str::to_string),
+            constraint: db.constraint().map(str::to_string),
+            detail: db.detail().map(str::to_string),
+        })
+    }
+}
+
 impl DryRunner {
+    /// Execute `statements` (starting at migration position `first_index`)
+    /// against the real data under savepoints, then roll everything back.
+    /// `settings` are the execution policy's session settings.
+    pub async fn deep(
+        pool: &Pool,
+        statements: &[String],
+        first_index: usize,
+        settings: &BTreeMap<String, String>,
+    ) -> Result<DeepDryRun, AppError> {
+        let started = Instant::now();
+        let mut client = pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        if !settings.is_empty() {
+            transaction.batch_execute(&execution_policy::set_statements(settings, true)).await?;
+        }
+        // After the policy's settings: a dry run never waits or holds locks for long
+        transaction
+            .batch_execute(&format!(
+                "SET LOCAL lock_timeout = {}; SET LOCAL statement_timeout = {}",
+                LOCK_TIMEOUT_MS, DEEP_STATEMENT_TIMEOUT_MS
+            ))
+            .await?;
+
+        let mut findings = Vec::with_capacity(statements.len());
+        for (offset, statement) in statements.iter().enumerate() {
+            let mut finding = StatementFinding {
+                statement_index: first_index + offset,
+                outcome: StatementOutcome::Succeeded,
+                rows_affected: 0,
+                duration_ms: 0,
+                error: None,
+                violation: None,
+            };
+            // The real run commits these on their own, outside any transaction
+            if requires_autocommit(statement) {
+                finding.outcome = StatementOutcome::Skipped;
+                finding.error = Some("Runs outside a transaction; not checked".to_string());
+                findings.push(finding);
+                continue;
+            }
+
+            let statement_started = Instant::now();
+            transaction.batch_execute("SAVEPOINT deep_dry_run").await?;
+            match transaction.simple_query(statement).await {
+                Ok(messages) => {
+                    finding.rows_affected = messages
+                        .iter()
+                        .map(|m| match m {
+                            SimpleQueryMessage::CommandComplete(rows) => *rows,
+                            _ => 0,
+                        })
+                        .sum();
+                    transaction.batch_execute("RELEASE SAVEPOINT deep_dry_run").await?;
+                }
+                Err(e) => {
+                    finding.outcome = match e.code() {
+                        Some(&SqlState::QUERY_CANCELED) => StatementOutcome::TimedOut,
+                        _ => StatementOutcome::Failed,
+                    };
+                    finding.error = Some(e.as_db_error().map(|db| db.message().to_string()).unwrap_or_else(|| e.to_string()));
+                    finding.violation = ConstraintViolation::from_error(&e);
+                    // Later statements still run against the state before this one
+                    transaction.batch_execute("ROLLBACK TO SAVEPOINT deep_dry_run").await?;
+                }
+            }
+            finding.duration_ms = statement_started.elapsed().as_millis() as u64;
+            findings.push(finding);
+        }
+
+        // Always roll back - this is a dry run
+        transaction.rollback().await?;
+
+        Ok(DeepDryRun {
+            success: findings.iter().all(|f| f.outcome != StatementOutcome::Failed),
+            rows_affected: findings.iter().map(|f| f.rows_affected).sum(),
+            statements: findings,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
     /// Execute a dry run of the migration
     pub async fn execute(pool: &Pool, changes: &[SchemaChange]) -> Result<DryRunResult, AppError> {
         let mut client = pool.get().await?;
@@ -86,3 +291,47 @@ impl DryRunner {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violations_by_sqlstate_and_skipped_statements() {
+        assert_eq!(ViolationKind::from_sqlstate(&SqlState::NOT_NULL_VIOLATION), Some(ViolationKind::NotNull));
+        assert_eq!(ViolationKind::from_sqlstate(&SqlState::UNIQUE_VIOLATION), Some(ViolationKind::Unique));
+        assert_eq!(ViolationKind::from_sqlstate(&SqlState::LOCK_NOT_AVAILABLE), None);
+
+        // Skipped exactly where the real run leaves the transaction
+        assert!(requires_autocommit("CREATE INDEX CONCURRENTLY idx_orders_user ON orders (user_id);"));
+        assert!(requires_autocommit("vacuum analyze orders;"));
+        assert!(requires_autocommit("-- backfill batch 1/4\nUPDATE orders SET status = 'new' WHERE status IS NULL;"));
+        assert!(requires_autocommit("-- reorder copy batch sweep\nDO $$ BEGIN COMMIT; END $$;"));
+        assert!(!requires_autocommit("ALTER TABLE orders ALTER COLUMN user_id SET NOT NULL;"));
+
+        let run = DeepDryRun {
+            success: false,
+            statements: vec![
+                StatementFinding {
+                    statement_index: 3,
+                    outcome: StatementOutcome::Skipped,
+                    rows_affected: 0,
+                    duration_ms: 0,
+                    error: None,
+                    violation: None,
+                },
+                StatementFinding {
+                    statement_index: 4,
+                    outcome: StatementOutcome::Failed,
+                    rows_affected: 0,
+                    duration_ms: 2,
+                    error: Some("column \"user_id\" contains null values".to_string()),
+                    violation: None,
+                },
+            ],
+            rows_affected: 0,
+            duration_ms: 2,
+        };
+        assert_eq!(run.first_failure().map(|f| f.statement_index), Some(4));
+    }
+}
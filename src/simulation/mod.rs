@@ -8,4 +8,4 @@ mod dry_run;
 #[allow(unused_imports)]
 pub use analyzer::RiskAnalyzer;
 #[allow(unused_imports)]
-pub use dry_run::DryRunner;
+pub use dry_run::{DeepDryRun, DryRunner};
@@ -0,0 +1,196 @@
+//! Snapshot Archive
+//!
+//! Moves old snapshots and execution artifacts to S3-compatible storage
+//! (AWS S3, MinIO, ...) so the in-memory store stays small while history
+//! remains restorable.
+
+use crate::config::ArchiveConfig;
+use crate::error::AppError;
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::orchestrator::ExecutionResult;
+use crate::pipeline::proposal::SchemaProposal;
+use s3::creds::Credentials;
+use s3::serde_types::{
+    AbortIncompleteMultipartUpload, BucketLifecycleConfiguration, Expiration, LifecycleFilter,
+    LifecycleRule, Transition,
+};
+use s3::{Bucket, Region};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An object stored in the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedObject {
+    pub key: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// Archived record of a proposal execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionArtifact {
+    pub proposal: SchemaProposal,
+    pub result: ExecutionResult,
+}
+
+/// Client for the snapshot archive bucket
+pub struct SnapshotArchive {
+    bucket: Box<Bucket>,
+    config: ArchiveConfig,
+}
+
+impl SnapshotArchive {
+    pub fn new(config: ArchiveConfig) -> Result<Self, AppError> {
+        let region = match &config.endpoint {
+            Some(endpoint) => Region::Custom {
+                region: config.region.clone(),
+                endpoint: endpoint.clone(),
+            },
+            None => config
+                .region
+                .parse()
+                .map_err(|_| AppError::Config(format!("Invalid archive region '{}'", config.region)))?,
+        };
+
+        let credentials = Credentials::new(
+            Some(&config.access_key),
+            Some(&config.secret_key),
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| AppError::Config(format!("Invalid archive credentials: {}", e)))?;
+
+        let mut bucket = Bucket::new(&config.bucket, region, credentials)?;
+        if config.path_style {
+            bucket = bucket.with_path_style();
+        }
+
+        Ok(Self { bucket, config })
+    }
+
+    /// Apply the configured lifecycle rules to the archive prefix
+    pub async fn apply_lifecycle(&self) -> Result<(), AppError> {
+        if self.config.expire_after_days.is_none() && self.config.transition_after_days.is_none() {
+            return Ok(());
+        }
+
+        let mut rule = LifecycleRule::builder("Enabled")
+            .id("schemaflow-archive")
+            .filter(LifecycleFilter::new(None, None, None, Some(format!("{}/", self.config.prefix)), None))
+            .abort_incomplete_multipart_upload(AbortIncompleteMultipartUpload::new(Some(7)));
+
+        if let Some(days) = self.config.expire_after_days {
+            rule = rule.expiration(Expiration::new(None, Some(days), None));
+        }
+        if let Some(days) = self.config.transition_after_days {
+            let storage_class = self
+                .config
+                .transition_storage_class
+                .clone()
+                .unwrap_or_else(|| "GLACIER".to_string());
+            rule = rule.transition(vec![Transition::new(None, Some(days), Some(storage_class))]);
+        }
+
+        self.bucket
+            .put_bucket_lifecycle(BucketLifecycleConfiguration::new(vec![rule.build()]))
+            .await?;
+
+        tracing::info!("Applied lifecycle rules to archive bucket '{}'", self.config.bucket);
+        Ok(())
+    }
+
+    /// Upload a snapshot to the archive
+    pub async fn archive_snapshot(&self, snapshot: &SchemaSnapshot) -> Result<ArchivedObject, AppError> {
+        let key = format!(
+            "{}v{:010}-{}.json",
+            self.snapshot_prefix(snapshot.connection_id),
+            snapshot.version,
+            snapshot.id
+        );
+        self.put_json(key, snapshot).await
+    }
+
+    /// Upload the artifacts of a proposal execution
+    pub async fn archive_execution(
+        &self,
+        proposal: &SchemaProposal,
+        result: &ExecutionResult,
+    ) -> Result<ArchivedObject, AppError> {
+        let key = format!("{}/executions/{}/{}.json", self.config.prefix, proposal.id, result.id);
+        let artifact = ExecutionArtifact {
+            proposal: proposal.clone(),
+            result: result.clone(),
+        };
+        self.put_json(key, &artifact).await
+    }
+
+    /// Upload a pre-execution database backup
+    pub async fn store_backup(&self, proposal_id: Uuid, file_name: &str, body: &[u8]) -> Result<ArchivedObject, AppError> {
+        let key = format!("{}/backups/{}/{}", self.config.prefix, proposal_id, file_name);
+        self.bucket
+            .put_object_with_content_type(&key, body, "application/octet-stream")
+            .await?;
+
+        Ok(ArchivedObject {
+            key,
+            size: body.len() as u64,
+            last_modified: None,
+        })
+    }
+
+    /// List archived snapshots for a connection, newest first
+    pub async fn list_snapshots(&self, connection_id: Uuid) -> Result<Vec<ArchivedObject>, AppError> {
+        let pages = self.bucket.list(self.snapshot_prefix(connection_id), None).await?;
+
+        let mut objects: Vec<ArchivedObject> = pages
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .map(|object| ArchivedObject {
+                key: object.key,
+                size: object.size,
+                last_modified: Some(object.last_modified),
+            })
+            .collect();
+
+        // Keys embed a zero-padded version, so lexical order is version order
+        objects.sort_by(|a, b| b.key.cmp(&a.key));
+        Ok(objects)
+    }
+
+    /// Download an archived snapshot belonging to the given connection
+    pub async fn fetch_snapshot(&self, connection_id: Uuid, key: &str) -> Result<SchemaSnapshot, AppError> {
+        if !key.starts_with(&self.snapshot_prefix(connection_id)) {
+            return Err(AppError::BadRequest(format!(
+                "Archive key '{}' does not belong to connection {}",
+                key, connection_id
+            )));
+        }
+
+        let response = self.bucket.get_object(key).await?;
+        serde_json::from_slice(response.bytes())
+            .map_err(|e| AppError::Internal(format!("Archived snapshot '{}' is corrupt: {}", key, e)))
+    }
+
+    fn snapshot_prefix(&self, connection_id: Uuid) -> String {
+        format!("{}/snapshots/{}/", self.config.prefix, connection_id)
+    }
+
+    async fn put_json<T: Serialize>(&self, key: String, value: &T) -> Result<ArchivedObject, AppError> {
+        let body = serde_json::to_vec(value)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize archive object: {}", e)))?;
+
+        self.bucket
+            .put_object_with_content_type(&key, &body, "application/json")
+            .await?;
+
+        Ok(ArchivedObject {
+            key,
+            size: body.len() as u64,
+            last_modified: None,
+        })
+    }
+}
@@ -0,0 +1,283 @@
+//! Introspection benchmark
+//!
+//! Builds a synthetic schema of N tables, each with a primary key, filler
+//! columns and foreign keys to earlier tables, in a scratch schema on the
+//! target database. Introspection, diffing and blast radius analysis are then
+//! timed against it, and the scratch schema is dropped whatever the outcome.
+//! Run it on the same hardware before and after an upgrade to spot
+//! regressions.
+
+use crate::error::AppError;
+use crate::introspection::PostgresIntrospector;
+use crate::snapshot::{BlastRadiusAnalyzer, DiffEngine};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::warn;
+use uuid::Uuid;
+
+pub const MAX_TABLES: usize = 2000;
+pub const MAX_COLUMNS_PER_TABLE: usize = 100;
+pub const MAX_FOREIGN_KEYS_PER_TABLE: usize = 10;
+pub const MAX_ITERATIONS: usize = 10;
+
+/// Tables created per round trip while building the schema
+const DDL_BATCH: usize = 50;
+
+/// Filler column types, cycled through
+const COLUMN_TYPES: [&str; 5] = ["text", "integer", "timestamptz", "boolean", "numeric(12,2)"];
+
+/// Size of the synthetic schema and how often each step is timed
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BenchmarkRequest {
+    pub tables: usize,
+    pub columns_per_table: usize,
+    pub foreign_keys_per_table: usize,
+    pub iterations: usize,
+}
+
+impl Default for BenchmarkRequest {
+    fn default() -> Self {
+        Self {
+            tables: 100,
+            columns_per_table: 10,
+            foreign_keys_per_table: 2,
+            iterations: 3,
+        }
+    }
+}
+
+impl BenchmarkRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let check = |name: &str, value: usize, min: usize, max: usize| {
+            if value < min || value > max {
+                return Err(AppError::Validation(format!("{} must be between {} and {}", name, min, max)));
+            }
+            Ok(())
+        };
+        check("tables", self.tables, 1, MAX_TABLES)?;
+        check("columnsPerTable", self.columns_per_table, 1, MAX_COLUMNS_PER_TABLE)?;
+        check("foreignKeysPerTable", self.foreign_keys_per_table, 0, MAX_FOREIGN_KEYS_PER_TABLE)?;
+        check("iterations", self.iterations, 1, MAX_ITERATIONS)
+    }
+}
+
+/// Timings of one step over all iterations, in milliseconds
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepTiming {
+    pub runs_ms: Vec<f64>,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub max_ms: f64,
+}
+
+impl StepTiming {
+    fn from_runs(runs_ms: Vec<f64>) -> Self {
+        let mut sorted = runs_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        let median_ms = match sorted.len() {
+            0 => 0.0,
+            n if n % 2 == 1 => sorted[n / 2],
+            n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+        };
+        Self {
+            min_ms: sorted.first().copied().unwrap_or_default(),
+            max_ms: sorted.last().copied().unwrap_or_default(),
+            median_ms,
+            runs_ms,
+        }
+    }
+}
+
+/// What was built and how long each step took
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    /// Scratch schema used; dropped before the report is returned
+    pub schema: String,
+    pub tables: usize,
+    pub columns: usize,
+    pub foreign_keys: usize,
+    pub setup_ms: f64,
+    pub introspection: StepTiming,
+    pub diff: StepTiming,
+    pub blast_radius: StepTiming,
+    pub teardown_ms: f64,
+    /// Changes the diff found after the schema was altered
+    pub changes_detected: usize,
+    /// Objects reached from the root table
+    pub impacted_objects: usize,
+    pub server_version: String,
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Earlier tables that table `index` references; none for the first table
+fn fk_targets(index: usize, foreign_keys: usize) -> Vec<usize> {
+    if index == 0 {
+        return Vec::new();
+    }
+    let mut targets: Vec<usize> = (0..foreign_keys).map(|k| (index * 7 + k * 13) % index).collect();
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+}
+
+/// CREATE TABLE statement for table `index` of the synthetic schema
+fn table_ddl(schema: &str, index: usize, req: &BenchmarkRequest) -> String {
+    let mut columns = vec!["id bigint PRIMARY KEY".to_string()];
+    columns.extend(
+        (0..req.columns_per_table).map(|c| format!("c{} {}", c, COLUMN_TYPES[c % COLUMN_TYPES.len()])),
+    );
+    columns.extend(fk_targets(index, req.foreign_keys_per_table).into_iter().map(|target| {
+        format!("ref_t{} bigint REFERENCES {}.t{}(id)", target, schema, target)
+    }));
+    format!("CREATE TABLE {}.t{} (\n  {}\n);", schema, index, columns.join(",\n  "))
+}
+
+/// Build the synthetic schema, time each step and drop the schema again
+pub async fn run(pool: &Pool, connection_id: Uuid, req: &BenchmarkRequest) -> Result<BenchmarkReport, AppError> {
+    req.validate()?;
+    let schema = format!("schemaflow_bench_{}", Uuid::new_v4().simple());
+
+    let result = measure(pool, connection_id, req, &schema).await;
+
+    let started = Instant::now();
+    let teardown = match pool.get().await {
+        Ok(client) => client
+            .batch_execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE", schema))
+            .await
+            .map_err(AppError::from),
+        Err(e) => Err(AppError::from(e)),
+    };
+    if let Err(e) = &teardown {
+        warn!("Failed to drop benchmark schema {}: {}", schema, e);
+    }
+
+    let mut report = result?;
+    teardown?;
+    report.teardown_ms = elapsed_ms(started);
+    Ok(report)
+}
+
+async fn measure(
+    pool: &Pool,
+    connection_id: Uuid,
+    req: &BenchmarkRequest,
+    schema: &str,
+) -> Result<BenchmarkReport, AppError> {
+    let client = pool.get().await?;
+    let server_version: String = client.query_one("SHOW server_version", &[]).await?.get(0);
+
+    let started = Instant::now();
+    client.batch_execute(&format!("CREATE SCHEMA {}", schema)).await?;
+    let indexes: Vec<usize> = (0..req.tables).collect();
+    for batch in indexes.chunks(DDL_BATCH) {
+        let ddl: Vec<String> = batch.iter().map(|&i| table_ddl(schema, i, req)).collect();
+        client.batch_execute(&ddl.join("\n")).await?;
+    }
+    let setup_ms = elapsed_ms(started);
+    drop(client);
+
+    let scope = vec![schema.to_string()];
+    let mut runs = Vec::with_capacity(req.iterations);
+    let mut before = None;
+    for _ in 0..req.iterations {
+        let started = Instant::now();
+        let snapshot = PostgresIntrospector::introspect(pool, connection_id, &scope).await?;
+        runs.push(elapsed_ms(started));
+        before = Some(snapshot);
+    }
+    let before = before.ok_or_else(|| AppError::Internal("Benchmark ran no iterations".to_string()))?;
+    let introspection = StepTiming::from_runs(runs);
+
+    // Alter roughly a tenth of the tables so the diff has work to do
+    let client = pool.get().await?;
+    let alterations: Vec<String> = (0..req.tables)
+        .step_by(10)
+        .map(|i| format!("ALTER TABLE {}.t{} ADD COLUMN bench_added text;", schema, i))
+        .collect();
+    client.batch_execute(&alterations.join("\n")).await?;
+    drop(client);
+    let after = PostgresIntrospector::introspect(pool, connection_id, &scope).await?;
+
+    let mut runs = Vec::with_capacity(req.iterations);
+    let mut changes_detected = 0;
+    for _ in 0..req.iterations {
+        let started = Instant::now();
+        let diff = DiffEngine::diff(&before, &after);
+        runs.push(elapsed_ms(started));
+        changes_detected = diff.summary.total_changes;
+    }
+    let diff = StepTiming::from_runs(runs);
+
+    // Every other table reaches the first one through its foreign keys
+    let mut runs = Vec::with_capacity(req.iterations);
+    let mut impacted_objects = 0;
+    for _ in 0..req.iterations {
+        let started = Instant::now();
+        let radius = BlastRadiusAnalyzer::analyze_table(&before, schema, "t0");
+        runs.push(elapsed_ms(started));
+        impacted_objects = radius.impacted.len();
+    }
+    let blast_radius = StepTiming::from_runs(runs);
+
+    Ok(BenchmarkReport {
+        schema: schema.to_string(),
+        tables: req.tables,
+        columns: req.tables * (1 + req.columns_per_table),
+        foreign_keys: (0..req.tables).map(|i| fk_targets(i, req.foreign_keys_per_table).len()).sum(),
+        setup_ms,
+        introspection,
+        diff,
+        blast_radius,
+        teardown_ms: 0.0,
+        changes_detected,
+        impacted_objects,
+        server_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fk_targets_point_backwards() {
+        assert!(fk_targets(0, 3).is_empty());
+        for index in 1..50 {
+            let targets = fk_targets(index, 3);
+            assert!(!targets.is_empty());
+            assert!(targets.iter().all(|&t| t < index));
+        }
+    }
+
+    #[test]
+    fn test_table_ddl() {
+        let req = BenchmarkRequest { columns_per_table: 2, foreign_keys_per_table: 1, ..Default::default() };
+        let ddl = table_ddl("bench", 3, &req);
+        assert!(ddl.starts_with("CREATE TABLE bench.t3 ("));
+        assert!(ddl.contains("c0 text"));
+        assert!(ddl.contains("c1 integer"));
+        assert!(ddl.contains("REFERENCES bench.t"));
+    }
+
+    #[test]
+    fn test_request_limits() {
+        assert!(BenchmarkRequest::default().validate().is_ok());
+        let too_big = BenchmarkRequest { tables: MAX_TABLES + 1, ..Default::default() };
+        assert!(too_big.validate().is_err());
+    }
+
+    #[test]
+    fn test_step_timing_median() {
+        let timing = StepTiming::from_runs(vec![3.0, 1.0, 2.0, 10.0]);
+        assert_eq!(timing.min_ms, 1.0);
+        assert_eq!(timing.max_ms, 10.0);
+        assert_eq!(timing.median_ms, 2.5);
+    }
+}
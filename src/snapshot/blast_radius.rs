@@ -2,11 +2,33 @@
 //!
 //! "What breaks if I change this column?"
 //! This module walks the dependency graph to find all downstream impacts.
+//!
+//! Highly connected schemas can make the walk explode, so it stops at a
+//! maximum depth and number of impacted objects and marks the result as
+//! truncated. The dependency graph of a snapshot (foreign keys, and views
+//! on the relations they read) is built once and reused by every analysis
+//! of that snapshot.
 
+use crate::error::AppError;
 #[allow(unused_imports)]
 use crate::introspection::{ForeignKey, SchemaSnapshot, Table};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use uuid::Uuid;
+
+/// Hops walked when neither the request nor the connection sets a limit
+pub const DEFAULT_MAX_DEPTH: u32 = 8;
+
+/// Impacted objects reported when neither the request nor the connection sets a limit
+pub const DEFAULT_MAX_NODES: usize = 1000;
+
+/// Highest limits a request or connection may ask for
+pub const DEPTH_CEILING: u32 = 64;
+pub const NODES_CEILING: usize = 20_000;
+
+/// Snapshots whose dependency graphs are kept between analyses
+const GRAPH_CACHE_SIZE: usize = 32;
 
 /// An object impacted by a change
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +92,138 @@ pub struct BlastRadius {
     pub risk_level: BlastRiskLevel,
     /// Human-readable explanation
     pub explanation: String,
+    /// True when a traversal limit cut the walk short
+    #[serde(default)]
+    pub truncated: bool,
+    /// Limits the walk ran with
+    #[serde(default)]
+    pub limits: TraversalLimits,
+}
+
+/// How far a blast radius walk may go; unset limits fall back to the
+/// connection's and then to the defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraversalLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_nodes: Option<usize>,
+}
+
+impl TraversalLimits {
+    /// Limits must be positive and within the ceilings
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(depth) = self.max_depth.filter(|&d| d == 0 || d > DEPTH_CEILING) {
+            return Err(AppError::Validation(format!(
+                "maxDepth must be between 1 and {} (got {})",
+                DEPTH_CEILING, depth
+            )));
+        }
+        if let Some(nodes) = self.max_nodes.filter(|&n| n == 0 || n > NODES_CEILING) {
+            return Err(AppError::Validation(format!(
+                "maxNodes must be between 1 and {} (got {})",
+                NODES_CEILING, nodes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fill limits left unset here from `fallback`
+    pub fn or(self, fallback: TraversalLimits) -> Self {
+        Self {
+            max_depth: self.max_depth.or(fallback.max_depth),
+            max_nodes: self.max_nodes.or(fallback.max_nodes),
+        }
+    }
+
+    /// Both limits set, defaults filling the gaps
+    pub fn resolved(self) -> Self {
+        Self {
+            max_depth: Some(self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH).max(1)),
+            max_nodes: Some(self.max_nodes.unwrap_or(DEFAULT_MAX_NODES).max(1)),
+        }
+    }
+}
+
+/// Foreign key and view adjacency of a snapshot, by `schema.name` path
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// Tables referencing each table, and views reading each table or view
+    dependents: HashMap<String, Vec<String>>,
+    /// (referencing, referenced) table pairs
+    references: HashSet<(String, String)>,
+    /// Paths that are views or materialized views
+    views: HashSet<String>,
+}
+
+/// Snapshot a cached graph was built from: id, checksum and foreign key and
+/// view counts, so a filtered copy of a snapshot does not pick up the full
+/// one's graph
+type GraphKey = (Uuid, String, usize, usize);
+
+/// Recently built graphs, oldest first
+type GraphCache = VecDeque<(GraphKey, Arc<DependencyGraph>)>;
+
+static GRAPHS: OnceLock<Mutex<GraphCache>> = OnceLock::new();
+
+impl DependencyGraph {
+    pub fn build(snapshot: &SchemaSnapshot) -> Self {
+        let mut graph = Self::default();
+        for fk in &snapshot.foreign_keys {
+            let source = format!("{}.{}", fk.source_schema, fk.source_table);
+            let target = format!("{}.{}", fk.referenced_schema, fk.referenced_table);
+            if graph.references.insert((source.clone(), target.clone())) {
+                graph.dependents.entry(target).or_default().push(source);
+            }
+        }
+        for view in snapshot.views.iter().flatten() {
+            let path = view.qualified_name();
+            for relation in &view.depends_on {
+                graph.dependents.entry(relation.clone()).or_default().push(path.clone());
+            }
+            graph.views.insert(path);
+        }
+        graph
+    }
+
+    /// Graph of `snapshot`, built on first use and shared afterwards
+    pub fn cached(snapshot: &SchemaSnapshot) -> Arc<Self> {
+        let views = snapshot.views.as_ref().map_or(0, Vec::len);
+        let key: GraphKey = (snapshot.id, snapshot.checksum.clone(), snapshot.foreign_keys.len(), views);
+        let cache = GRAPHS.get_or_init(Mutex::default);
+        if let Some((_, graph)) = cache.lock().expect("graph cache poisoned").iter().find(|(k, _)| *k == key) {
+            return graph.clone();
+        }
+
+        let graph = Arc::new(Self::build(snapshot));
+        let mut cache = cache.lock().expect("graph cache poisoned");
+        if cache.len() >= GRAPH_CACHE_SIZE {
+            cache.pop_front();
+        }
+        cache.push_back((key, graph.clone()));
+        graph
+    }
+
+    fn dependents(&self, path: &str) -> &[String] {
+        self.dependents.get(path).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn is_view(&self, path: &str) -> bool {
+        self.views.contains(path)
+    }
+
+    fn relationship(&self, source: &str, target: &str) -> RelationshipType {
+        if self.is_view(target) {
+            RelationshipType::ViewDependency
+        } else if self.references.contains(&(target.to_string(), source.to_string())) {
+            RelationshipType::ForeignKeyTo
+        } else if self.references.contains(&(source.to_string(), target.to_string())) {
+            RelationshipType::ForeignKeyFrom
+        } else {
+            RelationshipType::ForeignKeyTo // Default
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +234,9 @@ pub struct BlastRadiusSummary {
     pub total_tables: usize,
     pub total_columns: usize,
     pub total_indexes: usize,
+    /// Views and materialized views reading the source, directly or through other views
+    #[serde(default)]
+    pub total_views: usize,
     pub max_depth: u32,
 }
 
@@ -96,73 +253,101 @@ pub enum BlastRiskLevel {
 pub struct BlastRadiusAnalyzer;
 
 impl BlastRadiusAnalyzer {
-    /// Analyze blast radius for a table change
+    /// Analyze blast radius for a table change within the default limits
     pub fn analyze_table(snapshot: &SchemaSnapshot, schema: &str, table_name: &str) -> BlastRadius {
+        Self::analyze_table_within(snapshot, schema, table_name, TraversalLimits::default())
+    }
+
+    /// Analyze blast radius for a table change, walking at most
+    /// `max_depth` hops and reporting at most `max_nodes` tables
+    pub fn analyze_table_within(
+        snapshot: &SchemaSnapshot,
+        schema: &str,
+        table_name: &str,
+        limits: TraversalLimits,
+    ) -> BlastRadius {
+        let limits = limits.resolved();
+        let (max_depth, max_nodes) = (limits.max_depth.unwrap_or(DEFAULT_MAX_DEPTH), limits.max_nodes.unwrap_or(DEFAULT_MAX_NODES));
         let source_path = format!("{}.{}", schema, table_name);
+        let graph = DependencyGraph::cached(snapshot);
         let mut impacted = Vec::new();
-        let mut visited: HashSet<String> = HashSet::new();
-        
-        // Build dependency graph
-        let deps = Self::build_dependency_graph(snapshot);
-        
-        // BFS to find all downstream dependencies
-        let mut queue: VecDeque<(String, u32, bool)> = VecDeque::new();
-        
-        // Add direct dependencies
-        if let Some(direct_deps) = deps.get(&source_path) {
-            for dep in direct_deps {
-                queue.push_back((dep.clone(), 1, true));
+        let mut truncated = false;
+
+        // Paths are marked when queued, so each is queued once and a cycle
+        // never leads back to the source
+        let mut visited: HashSet<&str> = HashSet::from([source_path.as_str()]);
+        let mut queue: VecDeque<(&str, u32)> = VecDeque::new();
+        for dep in graph.dependents(&source_path) {
+            if visited.insert(dep) {
+                queue.push_back((dep, 1));
             }
         }
-        
-        while let Some((path, distance, _is_direct)) = queue.pop_front() {
-            if visited.contains(&path) {
-                continue;
+
+        while let Some((path, distance)) = queue.pop_front() {
+            if impacted.len() >= max_nodes {
+                truncated = true;
+                break;
             }
-            visited.insert(path.clone());
-            
-            // Determine relationship type
-            let relationship = Self::determine_relationship(snapshot, &source_path, &path);
-            
+
+            let relationship = graph.relationship(&source_path, path);
             impacted.push(ImpactedObject {
-                object_type: ImpactType::Table,
-                path: path.clone(),
+                object_type: if graph.is_view(path) { ImpactType::View } else { ImpactType::Table },
+                path: path.to_string(),
                 relationship,
                 distance,
-                impact: Self::describe_impact(&relationship, &source_path, &path),
+                impact: Self::describe_impact(&relationship, &source_path, path),
                 is_direct: distance == 1,
             });
-            
+
             // Add transitive dependencies
-            if let Some(transitive_deps) = deps.get(&path) {
-                for dep in transitive_deps {
-                    if !visited.contains(dep) {
-                        queue.push_back((dep.clone(), distance + 1, false));
-                    }
+            for dep in graph.dependents(path) {
+                if visited.contains(dep.as_str()) {
+                    continue;
+                }
+                if distance >= max_depth {
+                    truncated = true;
+                    break;
                 }
+                visited.insert(dep);
+                queue.push_back((dep, distance + 1));
             }
         }
-        
+
         let summary = Self::calculate_summary(&impacted);
         let risk_level = Self::assess_risk(&summary, snapshot.tables.len());
-        let explanation = Self::generate_explanation(&source_path, &summary, &risk_level);
-        
+        let explanation = Self::generate_explanation(&source_path, &summary, &risk_level, truncated);
+
         BlastRadius {
             source_path,
             impacted,
             summary,
             risk_level,
             explanation,
+            truncated,
+            limits,
         }
     }
 
-    /// Analyze blast radius for a specific column
+    /// Analyze blast radius for a specific column within the default limits
     pub fn analyze_column(
         snapshot: &SchemaSnapshot,
         schema: &str,
         table_name: &str,
         column_name: &str,
     ) -> BlastRadius {
+        Self::analyze_column_within(snapshot, schema, table_name, column_name, TraversalLimits::default())
+    }
+
+    /// Analyze blast radius for a specific column. Only direct dependents are
+    /// walked, so of the limits only `max_nodes` can cut the result short.
+    pub fn analyze_column_within(
+        snapshot: &SchemaSnapshot,
+        schema: &str,
+        table_name: &str,
+        column_name: &str,
+        limits: TraversalLimits,
+    ) -> BlastRadius {
+        let limits = limits.resolved();
         let source_path = format!("{}.{}.{}", schema, table_name, column_name);
         let table_path = format!("{}.{}", schema, table_name);
         let mut impacted = Vec::new();
@@ -206,6 +391,18 @@ impl BlastRadiusAnalyzer {
             }
         }
         
+        // Views are not tracked per column, so every view reading the table may use it
+        for view in snapshot.views.iter().flatten().filter(|v| v.depends_on.contains(&table_path)) {
+            impacted.push(ImpactedObject {
+                object_type: ImpactType::View,
+                path: view.qualified_name(),
+                relationship: RelationshipType::ViewDependency,
+                distance: 1,
+                impact: format!("{} {} reads {} and may use this column", view.kind(), view.name, table_name),
+                is_direct: true,
+            });
+        }
+        
         // Find indexes on this column
         for idx in &snapshot.indexes {
             if idx.schema == schema && idx.table == table_name && idx.columns.contains(&column_name.to_string()) {
@@ -223,10 +420,14 @@ impl BlastRadiusAnalyzer {
                 });
             }
         }
+
+        let max_nodes = limits.max_nodes.unwrap_or(DEFAULT_MAX_NODES);
+        let truncated = impacted.len() > max_nodes;
+        impacted.truncate(max_nodes);
         
         let summary = Self::calculate_summary(&impacted);
         let risk_level = Self::assess_risk(&summary, snapshot.tables.len());
-        let explanation = Self::generate_explanation(&source_path, &summary, &risk_level);
+        let explanation = Self::generate_explanation(&source_path, &summary, &risk_level, truncated);
         
         BlastRadius {
             source_path,
@@ -234,45 +435,11 @@ impl BlastRadiusAnalyzer {
             summary,
             risk_level,
             explanation,
+            truncated,
+            limits,
         }
     }
 
-    /// Build a dependency graph from foreign keys
-    fn build_dependency_graph(snapshot: &SchemaSnapshot) -> HashMap<String, Vec<String>> {
-        let mut deps: HashMap<String, Vec<String>> = HashMap::new();
-        
-        for fk in &snapshot.foreign_keys {
-            let source = format!("{}.{}", fk.source_schema, fk.source_table);
-            let target = format!("{}.{}", fk.referenced_schema, fk.referenced_table);
-            
-            // Target table has dependents
-            deps.entry(target.clone())
-                .or_default()
-                .push(source.clone());
-        }
-        
-        deps
-    }
-
-    fn determine_relationship(
-        snapshot: &SchemaSnapshot,
-        source: &str,
-        target: &str,
-    ) -> RelationshipType {
-        for fk in &snapshot.foreign_keys {
-            let fk_target = format!("{}.{}", fk.referenced_schema, fk.referenced_table);
-            let fk_source = format!("{}.{}", fk.source_schema, fk.source_table);
-            
-            if fk_target == *source && fk_source == *target {
-                return RelationshipType::ForeignKeyTo;
-            }
-            if fk_source == *source && fk_target == *target {
-                return RelationshipType::ForeignKeyFrom;
-            }
-        }
-        RelationshipType::ForeignKeyTo // Default
-    }
-
     fn describe_impact(relationship: &RelationshipType, source: &str, target: &str) -> String {
         let target_name = target.split('.').last().unwrap_or(target);
         let source_name = source.split('.').last().unwrap_or(source);
@@ -313,6 +480,9 @@ impl BlastRadiusAnalyzer {
         let total_indexes = impacted.iter()
             .filter(|i| i.object_type == ImpactType::Index)
             .count();
+        let total_views = impacted.iter()
+            .filter(|i| i.object_type == ImpactType::View)
+            .count();
         let max_depth = impacted.iter()
             .map(|i| i.distance)
             .max()
@@ -324,13 +494,17 @@ impl BlastRadiusAnalyzer {
             total_tables,
             total_columns,
             total_indexes,
+            total_views,
             max_depth,
         }
     }
 
     fn assess_risk(summary: &BlastRadiusSummary, total_tables: usize) -> BlastRiskLevel {
         if summary.total_tables == 0 {
-            return BlastRiskLevel::None;
+            return match summary.total_views {
+                0 => BlastRiskLevel::None,
+                _ => BlastRiskLevel::Contained,
+            };
         }
         
         let impact_ratio = summary.total_tables as f64 / total_tables as f64;
@@ -348,10 +522,15 @@ impl BlastRadiusAnalyzer {
         source: &str,
         summary: &BlastRadiusSummary,
         risk: &BlastRiskLevel,
+        truncated: bool,
     ) -> String {
         let source_name = source.split('.').last().unwrap_or(source);
         
-        match risk {
+        let views = match summary.total_views {
+            0 => String::new(),
+            n => format!(" {} view(s) read it.", n),
+        };
+        let explanation = match risk {
             BlastRiskLevel::None => {
                 format!("No dependencies found for {}. Safe to modify.", source_name)
             }
@@ -373,6 +552,13 @@ impl BlastRadiusAnalyzer {
                     source_name, summary.total_tables
                 )
             }
+        };
+
+        let explanation = format!("{}{}", explanation, views);
+        if truncated {
+            format!("{} Traversal limits were reached, so this is a partial result.", explanation)
+        } else {
+            explanation
         }
     }
 }
@@ -380,7 +566,7 @@ impl BlastRadiusAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::introspection::{Column, Table, Index, ForeignKey};
+    use crate::introspection::{Column, Table, Index, ForeignKey, View};
     use uuid::Uuid;
     use chrono::Utc;
 
@@ -390,6 +576,11 @@ mod tests {
             connection_id: Uuid::new_v4(),
             version: 1,
             captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: vec![],
+            extensions: vec![],
+            types: None,
+            views: None,
             tables: vec![
                 Table {
                     name: "users".to_string(),
@@ -403,9 +594,11 @@ mod tests {
                             is_primary_key: true,
                             is_unique: true,
                             ordinal_position: 1,
+                            collation: None,
                             pii_classification: None,
                             description: None,
                             tags: vec![],
+                            custom_fields: Default::default(),
                         }
                     ],
                     primary_key: None,
@@ -451,5 +644,80 @@ mod tests {
         assert_eq!(result.impacted.len(), 1);
         assert_eq!(result.impacted[0].path, "public.orders");
         assert_eq!(result.summary.direct_tables, 1);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_traversal_limits_truncate_the_walk() {
+        // t1 -> t0, t2 -> t1, ... and t0 -> t5 closing the cycle
+        let mut snapshot = create_test_snapshot();
+        snapshot.foreign_keys = (0..6)
+            .map(|i| ForeignKey {
+                constraint_name: format!("t{}_fk", i),
+                source_schema: "public".to_string(),
+                source_table: format!("t{}", (i + 1) % 6),
+                source_columns: vec!["parent_id".to_string()],
+                referenced_schema: "public".to_string(),
+                referenced_table: format!("t{}", i),
+                referenced_columns: vec!["id".to_string()],
+                on_update: "NO ACTION".to_string(),
+                on_delete: "NO ACTION".to_string(),
+            })
+            .collect();
+
+        let full = BlastRadiusAnalyzer::analyze_table(&snapshot, "public", "t0");
+        let paths: Vec<_> = full.impacted.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["public.t1", "public.t2", "public.t3", "public.t4", "public.t5"]);
+        assert!(!full.truncated);
+        assert_eq!(full.limits.max_depth, Some(DEFAULT_MAX_DEPTH));
+
+        let shallow = TraversalLimits { max_depth: Some(2), max_nodes: None };
+        let result = BlastRadiusAnalyzer::analyze_table_within(&snapshot, "public", "t0", shallow);
+        assert_eq!(result.impacted.len(), 2);
+        assert!(result.truncated);
+        assert!(result.explanation.contains("partial result"));
+
+        let narrow = TraversalLimits { max_depth: None, max_nodes: Some(3) };
+        let result = BlastRadiusAnalyzer::analyze_table_within(&snapshot, "public", "t0", narrow.or(shallow));
+        assert_eq!(result.impacted.len(), 2);
+        assert_eq!(result.limits, TraversalLimits { max_depth: Some(2), max_nodes: Some(3) });
+
+        // A filtered copy of the snapshot gets a graph of its own
+        let mut filtered = snapshot.clone();
+        filtered.foreign_keys.truncate(1);
+        assert_eq!(BlastRadiusAnalyzer::analyze_table(&filtered, "public", "t0").impacted.len(), 1);
+
+        assert!(TraversalLimits { max_depth: Some(0), max_nodes: None }.validate().is_err());
+        assert!(TraversalLimits { max_depth: None, max_nodes: Some(NODES_CEILING + 1) }.validate().is_err());
+        assert!(narrow.validate().is_ok());
+    }
+    #[test]
+    fn test_views_are_dependents() {
+        let view = |name: &str, materialized: bool, depends_on: &str| View {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            materialized,
+            definition: "SELECT 1".to_string(),
+            depends_on: vec![depends_on.to_string()],
+        };
+        let mut snapshot = create_test_snapshot();
+        snapshot.views = Some(vec![
+            view("active_users", false, "public.users"),
+            view("user_stats", true, "public.active_users"),
+        ]);
+
+        let result = BlastRadiusAnalyzer::analyze_table(&snapshot, "public", "users");
+        let views: Vec<_> = result.impacted.iter().filter(|i| i.object_type == ImpactType::View).collect();
+        assert_eq!(views.len(), 2);
+        assert!(views.iter().all(|v| v.relationship == RelationshipType::ViewDependency));
+        assert_eq!(result.summary.total_views, 2);
+
+        let result = BlastRadiusAnalyzer::analyze_table(&snapshot, "public", "active_users");
+        assert_eq!(result.impacted.len(), 1);
+        assert_eq!(result.impacted[0].path, "public.user_stats");
+        assert_eq!(result.risk_level, BlastRiskLevel::Contained);
+
+        let result = BlastRadiusAnalyzer::analyze_column(&snapshot, "public", "users", "id");
+        assert!(result.impacted.iter().any(|i| i.path == "public.active_users" && i.is_direct));
     }
 }
@@ -0,0 +1,272 @@
+//! Governance Coverage
+//!
+//! How much of a schema is documented: the share of tables with a
+//! description, owner and tags, and of columns with a description, tags and
+//! a PII classification, overall and per schema. Tables are ranked by how
+//! many of these are missing so governance leads know where to start.
+//!
+//! `pii:` tags count as a classification rather than as tags, and an
+//! explicit `[pii:none]` counts as classified. Snapshots captured before
+//! owners were recorded report no table as owned.
+
+use crate::introspection::{SchemaSnapshot, Table};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Gaps listed when the request doesn't say
+pub const DEFAULT_GAP_LIMIT: usize = 20;
+
+/// How many objects have one attribute
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Measure {
+    pub covered: usize,
+    pub total: usize,
+    /// `covered / total`, or 1.0 when there is nothing to cover
+    pub fraction: f64,
+}
+
+impl Default for Measure {
+    fn default() -> Self {
+        Self { covered: 0, total: 0, fraction: 1.0 }
+    }
+}
+
+impl Measure {
+    fn add(&mut self, covered: bool) {
+        self.total += 1;
+        if covered {
+            self.covered += 1;
+        }
+        self.fraction = self.covered as f64 / self.total as f64;
+    }
+}
+
+/// Coverage of one set of tables
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Coverage {
+    pub tables: usize,
+    pub columns: usize,
+    pub table_descriptions: Measure,
+    pub table_owners: Measure,
+    pub table_tags: Measure,
+    pub column_descriptions: Measure,
+    pub column_tags: Measure,
+    pub pii_classification: Measure,
+}
+
+impl Coverage {
+    fn add(&mut self, table: &Table) {
+        self.tables += 1;
+        self.table_descriptions.add(has_text(&table.governance.description));
+        self.table_owners.add(table.governance.owner.is_some());
+        self.table_tags.add(!table.governance.tags.is_empty());
+        for column in &table.columns {
+            self.columns += 1;
+            self.column_descriptions.add(has_text(&column.description));
+            self.column_tags.add(column.tags.iter().any(|t| !t.starts_with("pii:")));
+            self.pii_classification.add(column.pii_classification.is_some());
+        }
+    }
+}
+
+/// Coverage of one schema
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaCoverage {
+    pub schema: String,
+    #[serde(flatten)]
+    pub coverage: Coverage,
+}
+
+/// A table and what it is missing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageGap {
+    pub table: String,
+    /// Table-level attributes missing: `description`, `owner`, `tags`
+    pub missing: Vec<&'static str>,
+    pub undescribed_columns: Vec<String>,
+    pub unclassified_columns: Vec<String>,
+    /// Table-level attributes plus columns missing a description or classification
+    pub missing_count: usize,
+}
+
+/// Coverage report for a connection
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageReport {
+    pub connection_id: Uuid,
+    pub snapshot_version: u64,
+    pub generated_at: DateTime<Utc>,
+    pub overall: Coverage,
+    pub schemas: Vec<SchemaCoverage>,
+    /// Tables missing the most, worst first
+    pub gaps: Vec<CoverageGap>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CoverageQuery {
+    /// Only report on this schema
+    pub schema: Option<String>,
+    /// Number of gaps to list
+    pub limit: Option<usize>,
+}
+
+fn has_text(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|v| !v.trim().is_empty())
+}
+
+fn gap(table: &Table) -> CoverageGap {
+    let governance = &table.governance;
+    let mut missing = Vec::new();
+    if !has_text(&governance.description) {
+        missing.push("description");
+    }
+    if governance.owner.is_none() {
+        missing.push("owner");
+    }
+    if governance.tags.is_empty() {
+        missing.push("tags");
+    }
+    let undescribed_columns =
+        table.columns.iter().filter(|c| !has_text(&c.description)).map(|c| c.name.clone()).collect::<Vec<_>>();
+    let unclassified_columns =
+        table.columns.iter().filter(|c| c.pii_classification.is_none()).map(|c| c.name.clone()).collect::<Vec<_>>();
+
+    CoverageGap {
+        table: format!("{}.{}", table.schema, table.name),
+        missing_count: missing.len() + undescribed_columns.len() + unclassified_columns.len(),
+        missing,
+        undescribed_columns,
+        unclassified_columns,
+    }
+}
+
+/// Coverage of a snapshot, optionally narrowed to one schema
+pub fn report(snapshot: &SchemaSnapshot, query: &CoverageQuery) -> CoverageReport {
+    let tables: Vec<&Table> = snapshot
+        .tables
+        .iter()
+        .filter(|t| query.schema.as_deref().is_none_or(|s| s == t.schema))
+        .collect();
+
+    let mut overall = Coverage::default();
+    let mut by_schema: BTreeMap<&str, Coverage> = BTreeMap::new();
+    for table in &tables {
+        overall.add(table);
+        by_schema.entry(table.schema.as_str()).or_default().add(table);
+    }
+
+    let mut gaps: Vec<CoverageGap> = tables.iter().map(|t| gap(t)).filter(|g| g.missing_count > 0).collect();
+    gaps.sort_by(|a, b| b.missing_count.cmp(&a.missing_count).then_with(|| a.table.cmp(&b.table)));
+    gaps.truncate(query.limit.unwrap_or(DEFAULT_GAP_LIMIT));
+
+    CoverageReport {
+        connection_id: snapshot.connection_id,
+        snapshot_version: snapshot.version,
+        generated_at: Utc::now(),
+        overall,
+        schemas: by_schema
+            .into_iter()
+            .map(|(schema, coverage)| SchemaCoverage { schema: schema.to_string(), coverage })
+            .collect(),
+        gaps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, PiiLevel, TableGovernance};
+
+    fn column(name: &str, description: Option<&str>, pii: Option<PiiLevel>) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: pii,
+            description: description.map(str::to_string),
+            tags: vec![],
+            custom_fields: Default::default(),
+        }
+    }
+
+    fn table(schema: &str, name: &str, governance: TableGovernance, columns: Vec<Column>) -> Table {
+        Table {
+            name: name.to_string(),
+            schema: schema.to_string(),
+            columns,
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance,
+        }
+    }
+
+    #[test]
+    fn test_coverage_by_schema_and_gaps() {
+        let documented = TableGovernance {
+            description: Some("Customers".to_string()),
+            owner: Some("app".to_string()),
+            tags: vec!["crm".to_string()],
+            ..Default::default()
+        };
+        let snapshot = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 2,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: vec![
+                table(
+                    "public",
+                    "users",
+                    documented,
+                    vec![
+                        column("email", Some("Login"), Some(PiiLevel::Confidential)),
+                        column("plan", Some("  "), Some(PiiLevel::None)),
+                    ],
+                ),
+                table("audit", "events", TableGovernance::default(), vec![column("payload", None, None)]),
+            ],
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        };
+
+        let all = report(&snapshot, &CoverageQuery::default());
+        assert_eq!(all.overall.tables, 2);
+        assert_eq!(all.overall.columns, 3);
+        assert_eq!(all.overall.table_descriptions.covered, 1);
+        assert_eq!(all.overall.column_descriptions.covered, 1);
+        assert_eq!(all.overall.pii_classification.covered, 2);
+        assert!((all.overall.pii_classification.fraction - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(all.schemas.iter().map(|s| s.schema.as_str()).collect::<Vec<_>>(), vec!["audit", "public"]);
+        assert_eq!(all.schemas[1].coverage.table_owners.fraction, 1.0);
+
+        assert_eq!(all.gaps[0].table, "audit.events");
+        assert_eq!(all.gaps[0].missing, vec!["description", "owner", "tags"]);
+        assert_eq!(all.gaps[0].missing_count, 5);
+        assert_eq!(all.gaps[1].undescribed_columns, vec!["plan"]);
+
+        let public = report(&snapshot, &CoverageQuery { schema: Some("public".to_string()), limit: Some(0) });
+        assert_eq!(public.overall.tables, 1);
+        assert!(public.gaps.is_empty());
+        assert_eq!(public.overall.column_tags.fraction, 0.0);
+    }
+}
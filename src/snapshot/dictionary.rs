@@ -0,0 +1,739 @@
+//! Data dictionary custom fields and tag taxonomy
+//!
+//! Admins define project-wide fields such as "Data Steward", "Source System"
+//! or "Refresh Frequency" that can be attached to tables or columns. Values
+//! live in SchemaFlow rather than in the database: they change through
+//! governance-only `set_custom_field` proposal changes, which run no SQL and
+//! are recorded here once the proposal executes. Snapshots carry the values
+//! of their connection, so they appear in documentation bundles and can be
+//! searched.
+//!
+//! Tags work the same way. The taxonomy lists the tags a project allows,
+//! with a color and description; `add_tag` and `remove_tag` changes attach
+//! them to tables and columns, next to the tags written in database comments.
+//! Tags from comments can only be removed by editing the comment.
+//!
+//! `set_classification` changes record a column's PII classification the
+//! same way, for columns whose comments nobody wants to edit; see
+//! `propagation` for where they usually come from.
+
+use crate::error::AppError;
+use crate::introspection::{PiiLevel, SchemaSnapshot};
+use crate::snapshot::ignore::glob_to_regex;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use uuid::Uuid;
+
+/// Longest value a custom field accepts
+pub const MAX_VALUE_LENGTH: usize = 500;
+
+/// Most tables and columns one bulk tagging proposal may touch
+pub const MAX_BULK_TAG_OBJECTS: usize = 500;
+
+/// Objects a field can be attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldTarget {
+    Table,
+    Column,
+}
+
+/// Values a field accepts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FieldType {
+    #[default]
+    Text,
+    Number,
+    /// One of a fixed list of options
+    Choice { options: Vec<String> },
+}
+
+/// A custom field admins have defined for a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFieldDefinition {
+    /// Stable identifier used in changes and snapshots, e.g. `data_steward`
+    pub key: String,
+    /// Display name, e.g. "Data Steward"
+    pub label: String,
+    /// Objects the field may be set on; empty means tables and columns
+    #[serde(default)]
+    pub applies_to: Vec<FieldTarget>,
+    #[serde(default)]
+    pub field_type: FieldType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl CustomFieldDefinition {
+    fn applies_to(&self, target: FieldTarget) -> bool {
+        self.applies_to.is_empty() || self.applies_to.contains(&target)
+    }
+}
+
+/// A tag the project's taxonomy allows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagDefinition {
+    /// Lowercase, as tags in comments are read, e.g. `billing` or `source:crm`
+    pub name: String,
+    /// `#rrggbb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A project's field definitions and the values set on each connection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DataDictionary {
+    pub fields: Vec<CustomFieldDefinition>,
+    /// Connection → object (`schema.table` or `schema.table.column`) → field → value
+    pub values: BTreeMap<Uuid, BTreeMap<String, BTreeMap<String, String>>>,
+    /// Tag taxonomy
+    pub tags: Vec<TagDefinition>,
+    /// Connection → object → tags attached through proposals
+    pub tagged: BTreeMap<Uuid, BTreeMap<String, BTreeSet<String>>>,
+    /// Connection → column → classification set through proposals
+    pub classified: BTreeMap<Uuid, BTreeMap<String, PiiLevel>>,
+}
+
+/// Changes recorded in the data dictionary rather than run as SQL
+pub fn is_dictionary_change(change: &SchemaChange) -> bool {
+    matches!(
+        change,
+        SchemaChange::SetCustomField { .. }
+            | SchemaChange::AddTag { .. }
+            | SchemaChange::RemoveTag { .. }
+            | SchemaChange::SetClassification { .. }
+    )
+}
+
+/// Key of a table or column in the value map; unqualified tables are in `public`
+fn object_key(table_name: &str, column_name: Option<&str>) -> String {
+    let table = if table_name.contains('.') {
+        table_name.to_string()
+    } else {
+        format!("public.{}", table_name)
+    };
+    match column_name {
+        Some(column) => format!("{}.{}", table, column),
+        None => table,
+    }
+}
+
+impl DataDictionary {
+    pub fn field(&self, key: &str) -> Option<&CustomFieldDefinition> {
+        self.fields.iter().find(|f| f.key == key)
+    }
+
+    /// Replace the field definitions, dropping values of fields that no longer exist
+    pub fn set_fields(&mut self, fields: Vec<CustomFieldDefinition>) -> Result<(), AppError> {
+        let mut keys = HashSet::new();
+        for field in &fields {
+            let valid_key = !field.key.is_empty()
+                && field.key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !valid_key {
+                return Err(AppError::Validation(format!(
+                    "Custom field key '{}' must be lowercase letters, digits and underscores",
+                    field.key
+                )));
+            }
+            if !keys.insert(field.key.as_str()) {
+                return Err(AppError::Validation(format!("Custom field '{}' is defined twice", field.key)));
+            }
+            if field.label.trim().is_empty() {
+                return Err(AppError::Validation(format!("Custom field '{}' needs a label", field.key)));
+            }
+            if let FieldType::Choice { options } = &field.field_type {
+                if options.is_empty() {
+                    return Err(AppError::Validation(format!("Choice field '{}' needs at least one option", field.key)));
+                }
+            }
+        }
+
+        for objects in self.values.values_mut() {
+            for values in objects.values_mut() {
+                values.retain(|field, _| keys.contains(field.as_str()));
+            }
+            objects.retain(|_, values| !values.is_empty());
+        }
+        self.values.retain(|_, objects| !objects.is_empty());
+        self.fields = fields;
+        Ok(())
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&TagDefinition> {
+        self.tags.iter().find(|t| t.name == name)
+    }
+
+    /// Replace the tag taxonomy, detaching tags that are no longer in it
+    pub fn set_tags(&mut self, tags: Vec<TagDefinition>) -> Result<(), AppError> {
+        let mut names = HashSet::new();
+        for tag in &tags {
+            let valid_name = !tag.name.is_empty()
+                && tag.name.len() <= 64
+                && tag.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-:".contains(c));
+            if !valid_name {
+                return Err(AppError::Validation(format!(
+                    "Tag '{}' must be up to 64 lowercase letters, digits, '_', '-' and ':'",
+                    tag.name
+                )));
+            }
+            // PII classification is read from comments and drives masking rules
+            if tag.name.starts_with("pii:") {
+                return Err(AppError::Validation(format!(
+                    "Tag '{}' is reserved; classify PII in column comments",
+                    tag.name
+                )));
+            }
+            if !names.insert(tag.name.as_str()) {
+                return Err(AppError::Validation(format!("Tag '{}' is defined twice", tag.name)));
+            }
+            if let Some(color) = &tag.color {
+                let valid_color = color.len() == 7
+                    && color.starts_with('#')
+                    && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+                if !valid_color {
+                    return Err(AppError::Validation(format!(
+                        "Color of tag '{}' must look like #1f6feb",
+                        tag.name
+                    )));
+                }
+            }
+        }
+
+        for objects in self.tagged.values_mut() {
+            for tags in objects.values_mut() {
+                tags.retain(|tag| names.contains(tag.as_str()));
+            }
+            objects.retain(|_, tags| !tags.is_empty());
+        }
+        self.tagged.retain(|_, objects| !objects.is_empty());
+        self.tags = tags;
+        Ok(())
+    }
+
+    /// Check a governance change against the custom field definitions and tag taxonomy
+    pub fn check(&self, change: &SchemaChange) -> Result<(), AppError> {
+        if let SchemaChange::AddTag { table_name, column_name, tag } = change {
+            if self.tag(tag).is_none() {
+                return Err(AppError::Validation(format!(
+                    "Tag '{}' on {} is not in this project's taxonomy",
+                    tag,
+                    object_key(table_name, column_name.as_deref())
+                )));
+            }
+            return Ok(());
+        }
+        let SchemaChange::SetCustomField { table_name, column_name, field, value } = change else {
+            return Ok(());
+        };
+        let definition = self
+            .field(field)
+            .ok_or_else(|| AppError::Validation(format!("Custom field '{}' is not defined for this project", field)))?;
+        let target = if column_name.is_some() { FieldTarget::Column } else { FieldTarget::Table };
+        if !definition.applies_to(target) {
+            return Err(AppError::Validation(format!(
+                "Custom field '{}' cannot be set on a {}",
+                field,
+                if column_name.is_some() { "column" } else { "table" }
+            )));
+        }
+
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let object = object_key(table_name, column_name.as_deref());
+        if value.trim().is_empty() || value.len() > MAX_VALUE_LENGTH || value.chars().any(char::is_control) {
+            return Err(AppError::Validation(format!(
+                "Value of '{}' on {} must be 1 to {} characters on one line",
+                field, object, MAX_VALUE_LENGTH
+            )));
+        }
+        match &definition.field_type {
+            FieldType::Text => Ok(()),
+            FieldType::Number if value.trim().parse::<f64>().is_ok() => Ok(()),
+            FieldType::Number => Err(AppError::Validation(format!("'{}' on {} must be a number", field, object))),
+            FieldType::Choice { options } if options.contains(value) => Ok(()),
+            FieldType::Choice { options } => Err(AppError::Validation(format!(
+                "'{}' on {} must be one of: {}",
+                field,
+                object,
+                options.join(", ")
+            ))),
+        }
+    }
+
+    /// Record a governance change for a connection
+    pub fn apply(&mut self, connection_id: Uuid, change: &SchemaChange) {
+        match change {
+            SchemaChange::AddTag { table_name, column_name, tag } => {
+                self.tagged
+                    .entry(connection_id)
+                    .or_default()
+                    .entry(object_key(table_name, column_name.as_deref()))
+                    .or_default()
+                    .insert(tag.clone());
+                return;
+            }
+            SchemaChange::RemoveTag { table_name, column_name, tag } => {
+                if let Some(objects) = self.tagged.get_mut(&connection_id) {
+                    let key = object_key(table_name, column_name.as_deref());
+                    if let Some(tags) = objects.get_mut(&key) {
+                        tags.remove(tag);
+                        if tags.is_empty() {
+                            objects.remove(&key);
+                        }
+                    }
+                }
+                return;
+            }
+            SchemaChange::SetClassification { table_name, column_name, level } => {
+                let objects = self.classified.entry(connection_id).or_default();
+                let key = object_key(table_name, Some(column_name));
+                match level {
+                    Some(level) => {
+                        objects.insert(key, level.clone());
+                    }
+                    None => {
+                        objects.remove(&key);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+        let SchemaChange::SetCustomField { table_name, column_name, field, value } = change else {
+            return;
+        };
+        let objects = self.values.entry(connection_id).or_default();
+        let key = object_key(table_name, column_name.as_deref());
+        match value {
+            Some(value) => {
+                objects.entry(key).or_default().insert(field.clone(), value.clone());
+            }
+            None => {
+                if let Some(values) = objects.get_mut(&key) {
+                    values.remove(field);
+                    if values.is_empty() {
+                        objects.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Classifications set through proposals on a connection's columns
+    pub fn classifications(&self, connection_id: Uuid) -> BTreeMap<String, PiiLevel> {
+        self.classified.get(&connection_id).cloned().unwrap_or_default()
+    }
+
+    /// Copy the snapshot's connection values, tags and classifications onto
+    /// its tables and columns
+    pub fn annotate(&self, snapshot: &mut SchemaSnapshot) {
+        let classified = self.classified.get(&snapshot.connection_id);
+        let objects = self.values.get(&snapshot.connection_id);
+        let lookup = |key: &str| objects.and_then(|o| o.get(key)).cloned().unwrap_or_default();
+        let tagged = self.tagged.get(&snapshot.connection_id);
+        let add_tags = |key: &str, tags: &mut Vec<String>| {
+            for tag in tagged.and_then(|t| t.get(key)).into_iter().flatten() {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        };
+        for table in &mut snapshot.tables {
+            let qualified = format!("{}.{}", table.schema, table.name);
+            table.governance.custom_fields = lookup(&qualified);
+            add_tags(&qualified, &mut table.governance.tags);
+            for column in &mut table.columns {
+                let key = format!("{}.{}", qualified, column.name);
+                column.custom_fields = lookup(&key);
+                add_tags(&key, &mut column.tags);
+                // The stronger of the comment's and the dictionary's classification applies
+                if let Some(level) = classified.and_then(|c| c.get(&key)) {
+                    if column.pii_classification.as_ref().is_none_or(|current| current < level) {
+                        column.pii_classification = Some(level.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tag every table (or column of a table) matching a pattern
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkTagRequest {
+    pub tag: String,
+    /// Glob (`*`, `?`) on `schema.table`; without a dot, on table names in any schema
+    pub table_pattern: String,
+    /// Tag the columns matching this glob instead of the tables
+    #[serde(default)]
+    pub column_pattern: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<i32>,
+}
+
+fn glob(pattern: &str) -> Result<Regex, AppError> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(AppError::Validation("Tag patterns cannot be empty".to_string()));
+    }
+    Regex::new(&glob_to_regex(pattern))
+        .map_err(|e| AppError::Validation(format!("Invalid pattern '{}': {}", pattern, e)))
+}
+
+/// `add_tag` changes for the objects of an annotated snapshot that match the
+/// request and do not carry the tag yet
+pub fn bulk_tag_changes(snapshot: &SchemaSnapshot, request: &BulkTagRequest) -> Result<Vec<SchemaChange>, AppError> {
+    let tables = glob(&request.table_pattern)?;
+    let qualified_pattern = request.table_pattern.contains('.');
+    let columns = request.column_pattern.as_deref().map(glob).transpose()?;
+
+    let mut changes = Vec::new();
+    for table in &snapshot.tables {
+        let qualified = format!("{}.{}", table.schema, table.name);
+        if !tables.is_match(if qualified_pattern { &qualified } else { &table.name }) {
+            continue;
+        }
+        let add = |column_name: Option<&str>| SchemaChange::AddTag {
+            table_name: qualified.clone(),
+            column_name: column_name.map(str::to_string),
+            tag: request.tag.clone(),
+        };
+        match &columns {
+            Some(columns) => changes.extend(
+                table
+                    .columns
+                    .iter()
+                    .filter(|c| columns.is_match(&c.name) && !c.tags.contains(&request.tag))
+                    .map(|c| add(Some(&c.name))),
+            ),
+            None if !table.governance.tags.contains(&request.tag) => changes.push(add(None)),
+            None => {}
+        }
+    }
+
+    if changes.is_empty() {
+        return Err(AppError::Validation(format!(
+            "No untagged {} match the pattern",
+            if columns.is_some() { "columns" } else { "tables" }
+        )));
+    }
+    if changes.len() > MAX_BULK_TAG_OBJECTS {
+        return Err(AppError::Validation(format!(
+            "The pattern matches {} objects; narrow it to at most {}",
+            changes.len(),
+            MAX_BULK_TAG_OBJECTS
+        )));
+    }
+    Ok(changes)
+}
+
+/// Filters for a data dictionary search; all given filters must match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DictionaryQuery {
+    /// Case-insensitive text in the object's name, description or field values
+    pub q: Option<String>,
+    /// Only objects with this field set
+    pub field: Option<String>,
+    /// Only objects with this value (in `field`, if given), ignoring case
+    pub value: Option<String>,
+    pub target: Option<FieldTarget>,
+}
+
+/// A table or column matching a search
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryEntry {
+    /// `schema.table` or `schema.table.column`
+    pub object: String,
+    pub target: FieldTarget,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+impl DictionaryQuery {
+    fn matches(&self, entry: &DictionaryEntry) -> bool {
+        if self.target.is_some_and(|t| t != entry.target) {
+            return false;
+        }
+        if let Some(field) = &self.field {
+            if !entry.custom_fields.contains_key(field) {
+                return false;
+            }
+        }
+        if let Some(value) = &self.value {
+            let found = match &self.field {
+                Some(field) => entry.custom_fields.get(field).is_some_and(|v| v.eq_ignore_ascii_case(value)),
+                None => entry.custom_fields.values().any(|v| v.eq_ignore_ascii_case(value)),
+            };
+            if !found {
+                return false;
+            }
+        }
+        if let Some(q) = self.q.as_deref().map(str::to_lowercase).filter(|q| !q.is_empty()) {
+            let found = entry.object.to_lowercase().contains(&q)
+                || entry.description.as_deref().is_some_and(|d| d.to_lowercase().contains(&q))
+                || entry.custom_fields.values().any(|v| v.to_lowercase().contains(&q));
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Tables and columns of an annotated snapshot that match the query
+pub fn search(snapshot: &SchemaSnapshot, query: &DictionaryQuery) -> Vec<DictionaryEntry> {
+    let mut entries = Vec::new();
+    for table in &snapshot.tables {
+        let qualified = format!("{}.{}", table.schema, table.name);
+        entries.push(DictionaryEntry {
+            object: qualified.clone(),
+            target: FieldTarget::Table,
+            description: table.governance.description.clone(),
+            tags: table.governance.tags.clone(),
+            custom_fields: table.governance.custom_fields.clone(),
+        });
+        entries.extend(table.columns.iter().map(|column| DictionaryEntry {
+            object: format!("{}.{}", qualified, column.name),
+            target: FieldTarget::Column,
+            description: column.description.clone(),
+            tags: column.tags.clone(),
+            custom_fields: column.custom_fields.clone(),
+        }));
+    }
+    entries.retain(|entry| query.matches(entry));
+    entries
+}
+
+/// Data dictionary of a project; connections outside a project have none
+pub async fn load(state: &AppState, project_id: Option<i32>) -> Result<DataDictionary, AppError> {
+    match project_id {
+        Some(id) => Ok(state.project_service.get_data_dictionary(id).await?.unwrap_or_default()),
+        None => Ok(DataDictionary::default()),
+    }
+}
+
+/// Attach the current custom field values to a snapshot of a connection
+pub async fn annotate(state: &AppState, snapshot: &mut SchemaSnapshot) -> Result<(), AppError> {
+    let project_id = state.connections.project_id(snapshot.connection_id).await;
+    load(state, project_id).await?.annotate(snapshot);
+    Ok(())
+}
+
+/// Refuse governance changes the project's definitions and taxonomy do not allow
+pub async fn check_changes(state: &AppState, project_id: Option<i32>, changes: &[SchemaChange]) -> Result<(), AppError> {
+    if !changes.iter().any(is_dictionary_change) {
+        return Ok(());
+    }
+    if project_id.is_none() {
+        return Err(AppError::Validation(
+            "Custom fields and tags can only be set on proposals in a project".to_string(),
+        ));
+    }
+    let dictionary = load(state, project_id).await?;
+    changes.iter().try_for_each(|change| dictionary.check(change))
+}
+
+/// Record an executed proposal's custom field and tag changes; returns how many were applied
+pub async fn record_changes(state: &AppState, proposal: &SchemaProposal) -> Result<usize, AppError> {
+    let Some(project_id) = proposal.project_id else {
+        return Ok(0);
+    };
+    let changes: Vec<&SchemaChange> = proposal.changes.iter().filter(|c| is_dictionary_change(c)).collect();
+    if changes.is_empty() {
+        return Ok(0);
+    }
+
+    let mut dictionary = load(state, Some(project_id)).await?;
+    for change in &changes {
+        // Definitions may have changed since review
+        dictionary.check(change)?;
+        dictionary.apply(proposal.connection_id, change);
+    }
+    state.project_service.set_data_dictionary(project_id, &dictionary).await?;
+    Ok(changes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, Table, TableGovernance};
+    use chrono::Utc;
+
+    fn dictionary() -> DataDictionary {
+        let mut dictionary = DataDictionary::default();
+        dictionary
+            .set_fields(vec![
+                CustomFieldDefinition {
+                    key: "data_steward".to_string(),
+                    label: "Data Steward".to_string(),
+                    applies_to: vec![],
+                    field_type: FieldType::Text,
+                    description: None,
+                },
+                CustomFieldDefinition {
+                    key: "refresh_frequency".to_string(),
+                    label: "Refresh Frequency".to_string(),
+                    applies_to: vec![FieldTarget::Table],
+                    field_type: FieldType::Choice {
+                        options: vec!["hourly".to_string(), "daily".to_string()],
+                    },
+                    description: None,
+                },
+            ])
+            .unwrap();
+        dictionary
+    }
+
+    fn set(table: &str, column: Option<&str>, field: &str, value: Option<&str>) -> SchemaChange {
+        SchemaChange::SetCustomField {
+            table_name: table.to_string(),
+            column_name: column.map(str::to_string),
+            field: field.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    fn snapshot(connection_id: Uuid) -> SchemaSnapshot {
+        let column = |name: &str| Column {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: vec![],
+            custom_fields: BTreeMap::new(),
+        };
+        SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id,
+            version: 1,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: vec![],
+            extensions: vec![],
+            types: None,
+            views: None,
+            tables: vec![Table {
+                name: "orders".to_string(),
+                schema: "public".to_string(),
+                columns: vec![column("id"), column("email")],
+                primary_key: None,
+                position: None,
+                color: None,
+                collapsed: false,
+                governance: TableGovernance::default(),
+            }],
+            foreign_keys: vec![],
+            indexes: vec![],
+            checksum: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_enforces_definitions() {
+        let dictionary = dictionary();
+        assert!(dictionary.check(&set("orders", Some("email"), "data_steward", Some("alice"))).is_ok());
+        assert!(dictionary.check(&set("orders", None, "refresh_frequency", Some("daily"))).is_ok());
+        // Unknown field, wrong target, option outside the list, multi-line value
+        assert!(dictionary.check(&set("orders", None, "source_system", Some("crm"))).is_err());
+        assert!(dictionary.check(&set("orders", Some("email"), "refresh_frequency", Some("daily"))).is_err());
+        assert!(dictionary.check(&set("orders", None, "refresh_frequency", Some("weekly"))).is_err());
+        assert!(dictionary.check(&set("orders", None, "data_steward", Some("a\nDROP TABLE x"))).is_err());
+    }
+
+    #[test]
+    fn test_applied_values_annotate_and_search() {
+        let connection_id = Uuid::new_v4();
+        let mut dictionary = dictionary();
+        dictionary.apply(connection_id, &set("orders", None, "refresh_frequency", Some("daily")));
+        dictionary.apply(connection_id, &set("public.orders", Some("email"), "data_steward", Some("Alice")));
+
+        let mut snapshot = snapshot(connection_id);
+        dictionary.annotate(&mut snapshot);
+        assert_eq!(snapshot.tables[0].governance.custom_fields.get("refresh_frequency").unwrap(), "daily");
+        assert_eq!(snapshot.tables[0].columns[1].custom_fields.get("data_steward").unwrap(), "Alice");
+
+        let query = DictionaryQuery {
+            field: Some("data_steward".to_string()),
+            value: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let found = search(&snapshot, &query);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].object, "public.orders.email");
+
+        // Clearing a value and dropping its definition both remove it
+        dictionary.apply(connection_id, &set("orders", Some("email"), "data_steward", None));
+        dictionary.set_fields(vec![]).unwrap();
+        assert!(dictionary.values.is_empty());
+    }
+
+    #[test]
+    fn test_tag_taxonomy_and_bulk_tagging() {
+        let connection_id = Uuid::new_v4();
+        let mut dictionary = DataDictionary::default();
+        let tag = |name: &str, color: Option<&str>| TagDefinition {
+            name: name.to_string(),
+            color: color.map(str::to_string),
+            description: None,
+        };
+        assert!(dictionary.set_tags(vec![tag("Billing", None)]).is_err());
+        assert!(dictionary.set_tags(vec![tag("pii:secret", None)]).is_err());
+        assert!(dictionary.set_tags(vec![tag("billing", Some("blue"))]).is_err());
+        dictionary.set_tags(vec![tag("billing", Some("#1f6feb")), tag("contact", None)]).unwrap();
+
+        let add = |column: Option<&str>, tag: &str| SchemaChange::AddTag {
+            table_name: "orders".to_string(),
+            column_name: column.map(str::to_string),
+            tag: tag.to_string(),
+        };
+        assert!(dictionary.check(&add(None, "billing")).is_ok());
+        assert!(dictionary.check(&add(None, "finance")).is_err());
+
+        let mut snapshot = snapshot(connection_id);
+        let request = |table_pattern: &str, column_pattern: Option<&str>| BulkTagRequest {
+            tag: "contact".to_string(),
+            table_pattern: table_pattern.to_string(),
+            column_pattern: column_pattern.map(str::to_string),
+            title: None,
+            project_id: None,
+        };
+        let changes = bulk_tag_changes(&snapshot, &request("ord*", Some("e*"))).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(bulk_tag_changes(&snapshot, &request("audit.*", None)).is_err());
+
+        dictionary.apply(connection_id, &changes[0]);
+        dictionary.apply(connection_id, &add(None, "billing"));
+        dictionary.annotate(&mut snapshot);
+        assert_eq!(snapshot.tables[0].governance.tags, vec!["billing"]);
+        assert_eq!(snapshot.tables[0].columns[1].tags, vec!["contact"]);
+        // Already tagged columns are not proposed again
+        assert!(bulk_tag_changes(&snapshot, &request("public.orders", Some("e*"))).is_err());
+
+        // Dropping a tag from the taxonomy detaches it
+        dictionary.set_tags(vec![tag("billing", None)]).unwrap();
+        assert_eq!(dictionary.tagged[&connection_id].len(), 1);
+    }
+}
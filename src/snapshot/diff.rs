@@ -3,9 +3,15 @@
 //! The core comparison engine that detects changes between schema snapshots.
 //! This is the "git diff" for your database schema.
 
-use crate::introspection::{Column, ForeignKey, Index, SchemaSnapshot, Table};
+use crate::introspection::{
+    AclEntry, Column, ColumnOrder, CompositeType, DatabaseMetadata, Domain, Extension, ForeignKey, Index, Namespace, PrimaryKey,
+    RlsPolicy, SchemaSnapshot, Table, UserTypes, View,
+};
+use crate::snapshot::ignore::IgnoreRules;
+use crate::snapshot::metadata_diff::{self, MetadataChange};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Type of schema change detected
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -25,12 +31,27 @@ pub enum ChangeType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ObjectType {
+    Database,
+    Schema,
     Table,
     Column,
     Index,
     ForeignKey,
     PrimaryKey,
     Constraint,
+    /// Ownership of a table
+    Owner,
+    /// Grants on a table
+    Privilege,
+    Extension,
+    /// Row-level security being enabled, disabled or forced on a table
+    RowSecurity,
+    /// Row-level security policy on a table
+    Policy,
+    Domain,
+    CompositeType,
+    View,
+    MaterializedView,
 }
 
 /// A single item in the schema diff
@@ -86,6 +107,60 @@ pub struct SchemaDiff {
     pub overall_risk: RiskLevel,
     /// Has any breaking changes
     pub has_breaking_changes: bool,
+    /// Changes dropped by the connection's ignore rules
+    #[serde(default)]
+    pub ignored_changes: usize,
+    /// Governance metadata changes, kept apart from the physical changes
+    #[serde(default)]
+    pub metadata_changes: Vec<MetadataChange>,
+    /// Highest risk among the metadata changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_risk: Option<RiskLevel>,
+}
+
+impl SchemaDiff {
+    /// Identifies the changes against a baseline; the same drift seen again
+    /// has the same fingerprint
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.from_checksum.as_bytes());
+        hasher.update(serde_json::to_vec(&self.changes).unwrap_or_default());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Assemble a diff from its changes, computing the summary and risk
+    pub fn from_changes(
+        from_version: u64,
+        to_version: u64,
+        from_checksum: String,
+        to_checksum: String,
+        changes: Vec<SchemaDiffItem>,
+    ) -> Self {
+        let summary = DiffEngine::calculate_summary(&changes);
+        let overall_risk = DiffEngine::calculate_overall_risk(&changes);
+        let has_breaking_changes = changes.iter().any(|c| c.is_breaking);
+        
+        Self {
+            from_version,
+            to_version,
+            from_checksum,
+            to_checksum,
+            changes,
+            summary,
+            overall_risk,
+            has_breaking_changes,
+            ignored_changes: 0,
+            metadata_changes: Vec::new(),
+            metadata_risk: None,
+        }
+    }
+
+    /// Attach governance metadata changes and their risk
+    pub fn with_metadata(mut self, changes: Vec<MetadataChange>) -> Self {
+        self.metadata_risk = metadata_diff::overall_risk(&changes);
+        self.metadata_changes = changes;
+        self
+    }
 }
 
 /// Summary statistics for the diff
@@ -102,46 +177,504 @@ pub struct DiffSummary {
     pub indexes_removed: usize,
     pub fks_added: usize,
     pub fks_removed: usize,
+    /// Schemas and tables that changed owner
+    #[serde(default)]
+    pub ownership_changes: usize,
+    /// Tables whose grants changed
+    #[serde(default)]
+    pub permission_changes: usize,
+    /// Extensions installed, dropped or updated
+    #[serde(default)]
+    pub extension_changes: usize,
+    /// Row-level security settings and policies changed
+    #[serde(default)]
+    pub policy_changes: usize,
+    /// Domains and composite types created, dropped or redefined
+    #[serde(default)]
+    pub type_changes: usize,
+    /// Views and materialized views created, dropped or redefined
+    #[serde(default)]
+    pub view_changes: usize,
     pub total_changes: usize,
 }
 
+/// Column renames detected per table: table path → (old name → new name)
+type ColumnRenames = HashMap<String, HashMap<String, String>>;
+
 /// The diff engine that compares schema snapshots
 pub struct DiffEngine;
 
 impl DiffEngine {
-    /// Compare two schema snapshots and return all differences
+    /// Compare two schema snapshots and return all differences, ignoring column order
     pub fn diff(from: &SchemaSnapshot, to: &SchemaSnapshot) -> SchemaDiff {
+        Self::diff_in(from, to, ColumnOrder::Semantic)
+    }
+
+    /// Compare two schema snapshots, reporting reordered columns when strict
+    pub fn diff_in(from: &SchemaSnapshot, to: &SchemaSnapshot, column_order: ColumnOrder) -> SchemaDiff {
         let mut changes = Vec::new();
         
-        // Diff tables
-        Self::diff_tables(&from.tables, &to.tables, &mut changes);
+        // Snapshots captured before types were recorded name column types differently
+        let adopted;
+        let (from, to) = match (&from.types, &to.types) {
+            (None, Some(types)) => {
+                adopted = Self::adopt_type_names(from, to, types);
+                (&adopted, to)
+            }
+            (Some(types), None) => {
+                adopted = Self::adopt_type_names(to, from, types);
+                (from, &adopted)
+            }
+            _ => (from, to),
+        };
+        
+        // Diff database-level settings (encoding, collation)
+        Self::diff_database(&from.database, &to.database, &mut changes);
+        
+        // Diff schemas (namespaces)
+        Self::diff_schemas(&from.schemas, &to.schemas, &from.tables, &mut changes);
+        
+        // Diff installed extensions
+        Self::diff_extensions(&from.extensions, &to.extensions, &mut changes);
+        
+        // Diff domains and composite types
+        if let (Some(from_types), Some(to_types)) = (&from.types, &to.types) {
+            Self::diff_types(from_types, to_types, &from.tables, &mut changes);
+        }
+        
+        // Diff views and materialized views
+        if let (Some(from_views), Some(to_views)) = (&from.views, &to.views) {
+            Self::diff_views(from_views, to_views, &mut changes);
+        }
+        
+        // Diff tables (also detects column renames, needed to compare keys)
+        let renames = Self::diff_tables(&from.tables, &to.tables, &mut changes);
+        if column_order == ColumnOrder::Strict {
+            Self::diff_column_order(&from.tables, &to.tables, &renames, &mut changes);
+        }
         
         // Diff foreign keys
-        Self::diff_foreign_keys(&from.foreign_keys, &to.foreign_keys, &mut changes);
+        Self::diff_foreign_keys(&from.foreign_keys, &to.foreign_keys, &renames, &mut changes);
         
         // Diff indexes
         Self::diff_indexes(&from.indexes, &to.indexes, &mut changes);
         
-        // Calculate summary
-        let summary = Self::calculate_summary(&changes);
+        SchemaDiff::from_changes(from.version, to.version, from.checksum.clone(), to.checksum.clone(), changes)
+            .with_metadata(metadata_diff::diff(from, to))
+    }
+
+    /// Compare two snapshots in the rules' column order mode, leaving out
+    /// changes the ignore rules match
+    pub fn diff_ignoring(from: &SchemaSnapshot, to: &SchemaSnapshot, ignore: &IgnoreRules) -> SchemaDiff {
+        ignore.apply(Self::diff_in(from, to, ignore.column_order()))
+    }
+
+    fn diff_database(from: &DatabaseMetadata, to: &DatabaseMetadata, changes: &mut Vec<SchemaDiffItem>) {
+        // Snapshots captured before database metadata was recorded have nothing to compare
+        if from.encoding.is_empty() || to.encoding.is_empty() {
+            return;
+        }
         
-        // Calculate overall risk
-        let overall_risk = Self::calculate_overall_risk(&changes);
-        let has_breaking_changes = changes.iter().any(|c| c.is_breaking);
+        let mut modifications = Vec::new();
+        if from.encoding != to.encoding {
+            modifications.push(format!("encoding: {} → {}", from.encoding, to.encoding));
+        }
+        if from.collation != to.collation {
+            modifications.push(format!("collation: {} → {}", from.collation, to.collation));
+        }
+        if from.ctype != to.ctype {
+            modifications.push(format!("ctype: {} → {}", from.ctype, to.ctype));
+        }
         
-        SchemaDiff {
-            from_version: from.version,
-            to_version: to.version,
-            from_checksum: from.checksum.clone(),
-            to_checksum: to.checksum.clone(),
-            changes,
-            summary,
-            overall_risk,
-            has_breaking_changes,
+        if modifications.is_empty() {
+            return;
+        }
+        
+        changes.push(SchemaDiffItem {
+            change_type: ChangeType::Modified,
+            object_type: ObjectType::Database,
+            object_path: to.name.clone(),
+            description: format!("Database settings differ: {}", modifications.join(", ")),
+            before: Some(serde_json::to_value(from).unwrap_or_default()),
+            after: Some(serde_json::to_value(to).unwrap_or_default()),
+            risk_level: RiskLevel::Medium,
+            is_breaking: false,
+        });
+    }
+
+    fn diff_schemas(
+        from_schemas: &[Namespace],
+        to_schemas: &[Namespace],
+        from_tables: &[Table],
+        changes: &mut Vec<SchemaDiffItem>,
+    ) {
+        let from_map: HashMap<&str, &Namespace> = from_schemas
+            .iter()
+            .map(|s| (s.name.as_str(), s))
+            .collect();
+        
+        let to_map: HashMap<&str, &Namespace> = to_schemas
+            .iter()
+            .map(|s| (s.name.as_str(), s))
+            .collect();
+        
+        let from_keys: HashSet<_> = from_map.keys().copied().collect();
+        let to_keys: HashSet<_> = to_map.keys().copied().collect();
+        
+        // Added schemas
+        for name in to_keys.difference(&from_keys) {
+            let schema = to_map.get(name).unwrap();
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Added,
+                object_type: ObjectType::Schema,
+                object_path: name.to_string(),
+                description: format!("Schema {} created (owner: {})", name, schema.owner),
+                before: None,
+                after: Some(serde_json::to_value(schema).unwrap_or_default()),
+                risk_level: RiskLevel::Safe,
+                is_breaking: false,
+            });
+        }
+        
+        // Removed schemas - record how many tables lived in them
+        for name in from_keys.difference(&to_keys) {
+            let schema = from_map.get(name).unwrap();
+            let table_count = from_tables.iter().filter(|t| t.schema == *name).count();
+            let mut before = serde_json::to_value(schema).unwrap_or_default();
+            before["tableCount"] = serde_json::json!(table_count);
+            
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Removed,
+                object_type: ObjectType::Schema,
+                object_path: name.to_string(),
+                description: format!("Schema {} dropped ({} tables)", name, table_count),
+                before: Some(before),
+                after: None,
+                risk_level: if table_count > 0 { RiskLevel::Critical } else { RiskLevel::Low },
+                is_breaking: table_count > 0,
+            });
+        }
+        
+        // Owner changes
+        for name in from_keys.intersection(&to_keys) {
+            let from_schema = from_map.get(name).unwrap();
+            let to_schema = to_map.get(name).unwrap();
+            if from_schema.owner != to_schema.owner {
+                changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Modified,
+                    object_type: ObjectType::Schema,
+                    object_path: name.to_string(),
+                    description: format!(
+                        "Schema {} owner: {} → {}",
+                        name, from_schema.owner, to_schema.owner
+                    ),
+                    before: Some(serde_json::to_value(from_schema).unwrap_or_default()),
+                    after: Some(serde_json::to_value(to_schema).unwrap_or_default()),
+                    risk_level: RiskLevel::Low,
+                    is_breaking: false,
+                });
+            }
+        }
+    }
+
+    fn diff_extensions(from_extensions: &[Extension], to_extensions: &[Extension], changes: &mut Vec<SchemaDiffItem>) {
+        // Every database has plpgsql, so no extensions means the snapshot predates them
+        if from_extensions.is_empty() || to_extensions.is_empty() {
+            return;
+        }
+        
+        let from_map: HashMap<&str, &Extension> = from_extensions.iter().map(|e| (e.name.as_str(), e)).collect();
+        let to_map: HashMap<&str, &Extension> = to_extensions.iter().map(|e| (e.name.as_str(), e)).collect();
+        
+        for (name, extension) in &to_map {
+            match from_map.get(name) {
+                None => changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Added,
+                    object_type: ObjectType::Extension,
+                    object_path: name.to_string(),
+                    description: format!("Extension {} {} installed in {}", name, extension.version, extension.schema),
+                    before: None,
+                    after: Some(serde_json::to_value(extension).unwrap_or_default()),
+                    risk_level: RiskLevel::Low,
+                    is_breaking: false,
+                }),
+                Some(previous) if previous.version != extension.version => changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Modified,
+                    object_type: ObjectType::Extension,
+                    object_path: name.to_string(),
+                    description: format!("Extension {} updated: {} → {}", name, previous.version, extension.version),
+                    before: Some(serde_json::to_value(previous).unwrap_or_default()),
+                    after: Some(serde_json::to_value(extension).unwrap_or_default()),
+                    risk_level: RiskLevel::Medium,
+                    is_breaking: false,
+                }),
+                Some(_) => {}
+            }
+        }
+        
+        // Columns and indexes using a dropped extension went with it (CASCADE)
+        for (name, extension) in &from_map {
+            if to_map.contains_key(name) {
+                continue;
+            }
+            let dependents = extension.dependent_objects;
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Removed,
+                object_type: ObjectType::Extension,
+                object_path: name.to_string(),
+                description: format!("Extension {} dropped ({} dependent objects)", name, dependents),
+                before: Some(serde_json::to_value(extension).unwrap_or_default()),
+                after: None,
+                risk_level: if dependents > 0 { RiskLevel::High } else { RiskLevel::Medium },
+                is_breaking: dependents > 0,
+            });
+        }
+    }
+
+    /// `old` with column types named as in `new`, where `old` showed a domain
+    /// column as its base type or another user-defined type as USER-DEFINED
+    fn adopt_type_names(old: &SchemaSnapshot, new: &SchemaSnapshot, types: &UserTypes) -> SchemaSnapshot {
+        let mut adopted = old.clone();
+        for table in &mut adopted.tables {
+            let Some(new_table) = new.tables.iter().find(|t| t.schema == table.schema && t.name == table.name) else {
+                continue;
+            };
+            for column in &mut table.columns {
+                let Some(new_column) = new_table.columns.iter().find(|c| c.name == column.name) else {
+                    continue;
+                };
+                let named_type = column.data_type == "USER-DEFINED"
+                    || types.domain(&new_column.data_type).is_some()
+                    || types.composite(&new_column.data_type).is_some();
+                if named_type {
+                    column.data_type = new_column.data_type.clone();
+                }
+            }
         }
+        adopted
     }
 
-    fn diff_tables(from_tables: &[Table], to_tables: &[Table], changes: &mut Vec<SchemaDiffItem>) {
+    fn diff_types(from: &UserTypes, to: &UserTypes, from_tables: &[Table], changes: &mut Vec<SchemaDiffItem>) {
+        let from_domains: HashMap<String, &Domain> = from.domains.iter().map(|d| (d.qualified_name(), d)).collect();
+        let to_domains: HashMap<String, &Domain> = to.domains.iter().map(|d| (d.qualified_name(), d)).collect();
+        
+        for (path, domain) in &to_domains {
+            let Some(previous) = from_domains.get(path) else {
+                changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Added,
+                    object_type: ObjectType::Domain,
+                    object_path: path.clone(),
+                    description: format!("Domain {} created as {}", path, domain.base_type),
+                    before: None,
+                    after: Some(serde_json::to_value(domain).unwrap_or_default()),
+                    risk_level: RiskLevel::Low,
+                    is_breaking: false,
+                });
+                continue;
+            };
+            if previous == domain {
+                continue;
+            }
+            
+            // Tightening makes existing values fail validation; loosening is safe
+            let mut modifications = Vec::new();
+            let mut tightened = false;
+            let retyped = previous.base_type != domain.base_type;
+            if retyped {
+                modifications.push(format!("base type {} → {}", previous.base_type, domain.base_type));
+            }
+            if previous.not_null != domain.not_null {
+                modifications.push(if domain.not_null { "NOT NULL added" } else { "NOT NULL dropped" }.to_string());
+                tightened |= domain.not_null;
+            }
+            if previous.default_value != domain.default_value {
+                modifications.push(format!(
+                    "default {} → {}",
+                    previous.default_value.as_deref().unwrap_or("none"),
+                    domain.default_value.as_deref().unwrap_or("none")
+                ));
+            }
+            if previous.collation != domain.collation {
+                modifications.push(format!(
+                    "collation {} → {}",
+                    previous.collation.as_deref().unwrap_or("default"),
+                    domain.collation.as_deref().unwrap_or("default")
+                ));
+                tightened = true;
+            }
+            for check in &domain.checks {
+                match previous.checks.iter().find(|c| c.name == check.name) {
+                    None => {
+                        modifications.push(format!("check {} added: {}", check.name, check.expression));
+                        tightened = true;
+                    }
+                    Some(old) if old.expression != check.expression => {
+                        modifications.push(format!("check {}: {} → {}", check.name, old.expression, check.expression));
+                        tightened = true;
+                    }
+                    Some(_) => {}
+                }
+            }
+            for check in previous.checks.iter().filter(|c| !domain.checks.iter().any(|n| n.name == c.name)) {
+                modifications.push(format!("check {} dropped", check.name));
+            }
+            
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::Domain,
+                object_path: path.clone(),
+                description: format!("Domain {} changed: {}", path, modifications.join(", ")),
+                before: Some(serde_json::to_value(previous).unwrap_or_default()),
+                after: Some(serde_json::to_value(domain).unwrap_or_default()),
+                risk_level: if retyped {
+                    RiskLevel::High
+                } else if tightened {
+                    RiskLevel::Medium
+                } else {
+                    RiskLevel::Low
+                },
+                is_breaking: retyped,
+            });
+        }
+        for (path, domain) in from_domains.iter().filter(|(path, _)| !to_domains.contains_key(*path)) {
+            changes.push(Self::type_removed(ObjectType::Domain, "Domain", path, domain, from_tables));
+        }
+        
+        let from_composites: HashMap<String, &CompositeType> =
+            from.composites.iter().map(|c| (c.qualified_name(), c)).collect();
+        let to_composites: HashMap<String, &CompositeType> =
+            to.composites.iter().map(|c| (c.qualified_name(), c)).collect();
+        
+        for (path, composite) in &to_composites {
+            let Some(previous) = from_composites.get(path) else {
+                let attributes: Vec<String> =
+                    composite.attributes.iter().map(|a| format!("{} {}", a.name, a.data_type)).collect();
+                changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Added,
+                    object_type: ObjectType::CompositeType,
+                    object_path: path.clone(),
+                    description: format!("Composite type {} created ({})", path, attributes.join(", ")),
+                    before: None,
+                    after: Some(serde_json::to_value(composite).unwrap_or_default()),
+                    risk_level: RiskLevel::Low,
+                    is_breaking: false,
+                });
+                continue;
+            };
+            if previous == composite {
+                continue;
+            }
+            
+            // Stored values and code reading the type break when attributes go or change type
+            let mut modifications = Vec::new();
+            let mut breaking = false;
+            for attribute in &composite.attributes {
+                match previous.attributes.iter().find(|a| a.name == attribute.name) {
+                    None => modifications.push(format!("{} {} added", attribute.name, attribute.data_type)),
+                    Some(old) if old.data_type != attribute.data_type => {
+                        modifications.push(format!("{}: {} → {}", attribute.name, old.data_type, attribute.data_type));
+                        breaking = true;
+                    }
+                    Some(_) => {}
+                }
+            }
+            for attribute in previous.attributes.iter().filter(|a| !composite.attributes.iter().any(|n| n.name == a.name)) {
+                modifications.push(format!("{} dropped", attribute.name));
+                breaking = true;
+            }
+            if modifications.is_empty() {
+                modifications.push("attributes reordered".to_string());
+            }
+            
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::CompositeType,
+                object_path: path.clone(),
+                description: format!("Composite type {} changed: {}", path, modifications.join(", ")),
+                before: Some(serde_json::to_value(previous).unwrap_or_default()),
+                after: Some(serde_json::to_value(composite).unwrap_or_default()),
+                risk_level: if breaking { RiskLevel::High } else { RiskLevel::Low },
+                is_breaking: breaking,
+            });
+        }
+        for (path, composite) in from_composites.iter().filter(|(path, _)| !to_composites.contains_key(*path)) {
+            changes.push(Self::type_removed(ObjectType::CompositeType, "Composite type", path, composite, from_tables));
+        }
+    }
+
+    /// A dropped type; columns using it went with it (CASCADE)
+    fn type_removed(
+        object_type: ObjectType,
+        label: &str,
+        path: &str,
+        before: &impl Serialize,
+        from_tables: &[Table],
+    ) -> SchemaDiffItem {
+        let columns = from_tables.iter().flat_map(|t| &t.columns).filter(|c| c.data_type == path).count();
+        SchemaDiffItem {
+            change_type: ChangeType::Removed,
+            object_type,
+            object_path: path.to_string(),
+            description: format!("{} {} dropped ({} columns used it)", label, path, columns),
+            before: Some(serde_json::to_value(before).unwrap_or_default()),
+            after: None,
+            risk_level: if columns > 0 { RiskLevel::High } else { RiskLevel::Medium },
+            is_breaking: columns > 0,
+        }
+    }
+
+    /// A view that changes kind is reported as dropped and recreated
+    fn diff_views(from: &[View], to: &[View], changes: &mut Vec<SchemaDiffItem>) {
+        let key = |v: &View| (v.qualified_name(), v.materialized);
+        let from_views: BTreeMap<(String, bool), &View> = from.iter().map(|v| (key(v), v)).collect();
+        let to_views: BTreeMap<(String, bool), &View> = to.iter().map(|v| (key(v), v)).collect();
+        let object_type = |v: &View| if v.materialized { ObjectType::MaterializedView } else { ObjectType::View };
+        
+        for (key, view) in &to_views {
+            let path = &key.0;
+            match from_views.get(key) {
+                None => changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Added,
+                    object_type: object_type(view),
+                    object_path: path.clone(),
+                    description: format!("{} {} created", view.kind(), path),
+                    before: None,
+                    after: Some(serde_json::to_value(view).unwrap_or_default()),
+                    risk_level: RiskLevel::Safe,
+                    is_breaking: false,
+                }),
+                // Readers may depend on the columns or rows the old query returned
+                Some(previous) if previous.definition != view.definition => changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Modified,
+                    object_type: object_type(view),
+                    object_path: path.clone(),
+                    description: format!("{} {} redefined", view.kind(), path),
+                    before: Some(serde_json::to_value(previous).unwrap_or_default()),
+                    after: Some(serde_json::to_value(view).unwrap_or_default()),
+                    risk_level: if view.materialized { RiskLevel::Medium } else { RiskLevel::Low },
+                    is_breaking: false,
+                }),
+                Some(_) => {}
+            }
+        }
+        
+        for (key, view) in from_views.iter().filter(|(key, _)| !to_views.contains_key(*key)) {
+            let path = &key.0;
+            let dependents = from.iter().filter(|v| v.depends_on.contains(path)).count();
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Removed,
+                object_type: object_type(view),
+                object_path: path.clone(),
+                description: format!("{} {} dropped ({} views read it)", view.kind(), path, dependents),
+                before: Some(serde_json::to_value(view).unwrap_or_default()),
+                after: None,
+                risk_level: if dependents > 0 { RiskLevel::High } else { RiskLevel::Medium },
+                is_breaking: true,
+            });
+        }
+    }
+
+    fn diff_tables(from_tables: &[Table], to_tables: &[Table], changes: &mut Vec<SchemaDiffItem>) -> ColumnRenames {
         // Build lookup maps
         let from_map: HashMap<String, &Table> = from_tables
             .iter()
@@ -186,15 +719,79 @@ impl DiffEngine {
             });
         }
         
-        // Detect modified tables (compare columns)
+        // Detect modified tables (compare columns, then the primary key as a unit)
+        let mut renames = ColumnRenames::new();
         for key in from_keys.intersection(&to_keys) {
             let from_table = from_map.get(*key).unwrap();
             let to_table = to_map.get(*key).unwrap();
-            Self::diff_columns(from_table, to_table, changes);
+            let table_renames = Self::diff_columns(from_table, to_table, changes);
+            Self::diff_primary_key(key, from_table, to_table, &table_renames, changes);
+            Self::diff_governance(key, from_table, to_table, changes);
+            Self::diff_row_security(key, from_table, to_table, changes);
+            if !table_renames.is_empty() {
+                renames.insert(key.to_string(), table_renames);
+            }
+        }
+        
+        renames
+    }
+
+    /// Report tables whose surviving columns changed relative order. Added and
+    /// dropped columns do not count, and renamed ones keep their place.
+    fn diff_column_order(
+        from_tables: &[Table],
+        to_tables: &[Table],
+        renames: &ColumnRenames,
+        changes: &mut Vec<SchemaDiffItem>,
+    ) {
+        let ordered = |table: &Table| -> Vec<String> {
+            let mut columns: Vec<&Column> = table.columns.iter().collect();
+            columns.sort_by_key(|c| c.ordinal_position);
+            columns.into_iter().map(|c| c.name.clone()).collect()
+        };
+
+        for to_table in to_tables {
+            let path = format!("{}.{}", to_table.schema, to_table.name);
+            let Some(from_table) = from_tables.iter().find(|t| t.schema == to_table.schema && t.name == to_table.name)
+            else {
+                continue;
+            };
+            let table_renames = renames.get(&path);
+            let after = ordered(to_table);
+            let before: Vec<String> = ordered(from_table)
+                .into_iter()
+                .map(|name| table_renames.and_then(|r| r.get(&name).cloned()).unwrap_or(name))
+                .filter(|name| after.contains(name))
+                .collect();
+            let after: Vec<String> = after.into_iter().filter(|name| before.contains(name)).collect();
+            if before == after {
+                continue;
+            }
+
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::Table,
+                object_path: path.clone(),
+                description: format!(
+                    "Column order of {} changed: ({}) → ({})",
+                    path,
+                    before.join(", "),
+                    after.join(", ")
+                ),
+                before: Some(serde_json::json!({ "columnOrder": before })),
+                after: Some(serde_json::json!({ "columnOrder": after })),
+                // Only SELECT * and INSERT without a column list notice
+                risk_level: RiskLevel::Low,
+                is_breaking: false,
+            });
         }
     }
 
-    fn diff_columns(from_table: &Table, to_table: &Table, changes: &mut Vec<SchemaDiffItem>) {
+    fn diff_columns(
+        from_table: &Table,
+        to_table: &Table,
+        changes: &mut Vec<SchemaDiffItem>,
+    ) -> HashMap<String, String> {
         let table_path = format!("{}.{}", from_table.schema, from_table.name);
         
         let from_cols: HashMap<&str, &Column> = from_table
@@ -212,8 +809,50 @@ impl DiffEngine {
         let from_keys: HashSet<_> = from_cols.keys().copied().collect();
         let to_keys: HashSet<_> = to_cols.keys().copied().collect();
         
+        // A dropped and an added column at the same position with the same type
+        // is a rename: PostgreSQL never reuses a dropped column's position
+        let renames: HashMap<String, String> = from_keys
+            .difference(&to_keys)
+            .filter_map(|old| {
+                let from_col = from_cols.get(old).unwrap();
+                to_keys
+                    .difference(&from_keys)
+                    .find(|new| {
+                        let to_col = to_cols.get(*new).unwrap();
+                        to_col.ordinal_position == from_col.ordinal_position
+                            && to_col.data_type == from_col.data_type
+                    })
+                    .map(|new| (old.to_string(), new.to_string()))
+            })
+            .collect();
+        let renamed_to: HashSet<&str> = renames.values().map(String::as_str).collect();
+        
+        // Detect renamed columns
+        for (old_name, new_name) in &renames {
+            let from_col = from_cols.get(old_name.as_str()).unwrap();
+            let to_col = to_cols.get(new_name.as_str()).unwrap();
+            
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Renamed,
+                object_type: ObjectType::Column,
+                object_path: format!("{}.{}", table_path, old_name),
+                description: format!("Column {} renamed to {}", old_name, new_name),
+                before: Some(serde_json::to_value(from_col).unwrap_or_default()),
+                after: Some(serde_json::to_value(to_col).unwrap_or_default()),
+                risk_level: RiskLevel::Medium,
+                is_breaking: true, // Queries using the old name fail
+            });
+            
+            if let Some(change) = Self::compare_columns(&table_path, from_col, to_col) {
+                changes.push(change);
+            }
+        }
+        
         // Detect added columns
         for col_name in to_keys.difference(&from_keys) {
+            if renamed_to.contains(col_name) {
+                continue;
+            }
             let col = to_cols.get(col_name).unwrap();
             let (risk, is_breaking) = Self::assess_add_column_risk(col);
             
@@ -234,6 +873,9 @@ impl DiffEngine {
         
         // Detect removed columns
         for col_name in from_keys.difference(&to_keys) {
+            if renames.contains_key(*col_name) {
+                continue;
+            }
             let col = from_cols.get(col_name).unwrap();
             
             changes.push(SchemaDiffItem {
@@ -260,6 +902,8 @@ impl DiffEngine {
                 changes.push(change);
             }
         }
+        
+        renames
     }
 
     fn compare_columns(table_path: &str, from: &Column, to: &Column) -> Option<SchemaDiffItem> {
@@ -293,18 +937,24 @@ impl DiffEngine {
             ));
         }
         
-        // Primary key change
-        if from.is_primary_key != to.is_primary_key {
-            if to.is_primary_key {
-                modifications.push("added to PRIMARY KEY".to_string());
-                risk = RiskLevel::High;
-            } else {
-                modifications.push("removed from PRIMARY KEY".to_string());
-                risk = RiskLevel::Critical;
-                is_breaking = true;
+        // Collation change (affects sort order and uniqueness of text values)
+        if from.collation != to.collation {
+            modifications.push(format!(
+                "collation: {:?} → {:?}",
+                from.collation, to.collation
+            ));
+            if risk == RiskLevel::Low {
+                risk = RiskLevel::Medium;
             }
         }
         
+        // Comments are documentation only
+        if from.description != to.description {
+            modifications.push("comment changed".to_string());
+        }
+        
+        // Primary key membership is compared per constraint in diff_primary_key
+        
         if modifications.is_empty() {
             return None;
         }
@@ -312,8 +962,8 @@ impl DiffEngine {
         Some(SchemaDiffItem {
             change_type: ChangeType::Modified,
             object_type: ObjectType::Column,
-            object_path: format!("{}.{}", table_path, from.name),
-            description: format!("Column {} modified: {}", from.name, modifications.join(", ")),
+            object_path: format!("{}.{}", table_path, to.name),
+            description: format!("Column {} modified: {}", to.name, modifications.join(", ")),
             before: Some(serde_json::to_value(from).unwrap_or_default()),
             after: Some(serde_json::to_value(to).unwrap_or_default()),
             risk_level: risk,
@@ -321,7 +971,286 @@ impl DiffEngine {
         })
     }
 
-    fn diff_foreign_keys(from_fks: &[ForeignKey], to_fks: &[ForeignKey], changes: &mut Vec<SchemaDiffItem>) {
+    /// Compare a table's comment, owner and grants
+    fn diff_governance(table_path: &str, from: &Table, to: &Table, changes: &mut Vec<SchemaDiffItem>) {
+        let (from_gov, to_gov) = (&from.governance, &to.governance);
+        
+        if from_gov.description != to_gov.description {
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::Table,
+                object_path: table_path.to_string(),
+                description: format!("Table {} comment changed", table_path),
+                before: Some(serde_json::json!({ "description": from_gov.description })),
+                after: Some(serde_json::json!({ "description": to_gov.description })),
+                risk_level: RiskLevel::Safe,
+                is_breaking: false,
+            });
+        }
+        
+        // Snapshots captured before owners were recorded have no owner or grants to compare
+        let (Some(from_owner), Some(to_owner)) = (&from_gov.owner, &to_gov.owner) else {
+            return;
+        };
+        
+        if from_owner != to_owner {
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::Owner,
+                object_path: table_path.to_string(),
+                description: format!("Table {} owner: {} → {}", table_path, from_owner, to_owner),
+                before: Some(serde_json::json!({ "owner": from_owner })),
+                after: Some(serde_json::json!({ "owner": to_owner })),
+                risk_level: RiskLevel::Medium,
+                is_breaking: false,
+            });
+        }
+        
+        if from_gov.acl == to_gov.acl {
+            return;
+        }
+        
+        let privileges = |acl: &[AclEntry]| -> HashMap<String, BTreeSet<String>> {
+            acl.iter()
+                .map(|e| (e.grantee.clone(), e.privileges.iter().cloned().collect()))
+                .collect()
+        };
+        let before = privileges(&from_gov.acl);
+        let after = privileges(&to_gov.acl);
+        let mut grantees: Vec<&String> = before.keys().chain(after.keys()).collect();
+        grantees.sort();
+        grantees.dedup();
+        
+        let empty = BTreeSet::new();
+        let mut details = Vec::new();
+        let mut risk = RiskLevel::Low;
+        for grantee in grantees {
+            let had = before.get(grantee).unwrap_or(&empty);
+            let has = after.get(grantee).unwrap_or(&empty);
+            let granted: Vec<&str> = has.difference(had).map(String::as_str).collect();
+            let revoked: Vec<&str> = had.difference(has).map(String::as_str).collect();
+            if !granted.is_empty() {
+                details.push(format!("{} +{}", grantee, granted.join(",")));
+                let writes = granted.iter().any(|p| matches!(*p, "INSERT" | "UPDATE" | "DELETE" | "TRUNCATE"));
+                if grantee == "PUBLIC" {
+                    risk = RiskLevel::High;
+                } else if writes && risk != RiskLevel::High {
+                    risk = RiskLevel::Medium;
+                }
+            }
+            if !revoked.is_empty() {
+                details.push(format!("{} -{}", grantee, revoked.join(",")));
+                // Applications relying on the grant start failing
+                if risk == RiskLevel::Low {
+                    risk = RiskLevel::Medium;
+                }
+            }
+        }
+        
+        changes.push(SchemaDiffItem {
+            change_type: ChangeType::Modified,
+            object_type: ObjectType::Privilege,
+            object_path: table_path.to_string(),
+            description: format!("Privileges on {} changed: {}", table_path, details.join("; ")),
+            before: Some(serde_json::to_value(&from_gov.acl).unwrap_or_default()),
+            after: Some(serde_json::to_value(&to_gov.acl).unwrap_or_default()),
+            risk_level: risk,
+            is_breaking: false,
+        });
+    }
+
+    /// Compare a table's row-level security settings and policies
+    fn diff_row_security(table_path: &str, from: &Table, to: &Table, changes: &mut Vec<SchemaDiffItem>) {
+        // Snapshots captured before row-level security was recorded have nothing to compare
+        let (Some(from_rls), Some(to_rls)) = (&from.governance.row_security, &to.governance.row_security) else {
+            return;
+        };
+        
+        if from_rls.enabled != to_rls.enabled || from_rls.forced != to_rls.forced {
+            let state = |enabled: bool, forced: bool| match (enabled, forced) {
+                (false, _) => "disabled",
+                (true, false) => "enabled",
+                (true, true) => "enabled and forced",
+            };
+            // Disabling exposes every row; enabling without policies hides them all from non-owners
+            let (risk_level, is_breaking) = if from_rls.enabled && !to_rls.enabled {
+                (RiskLevel::High, false)
+            } else if to_rls.enabled && to_rls.policies.is_empty() {
+                (RiskLevel::High, true)
+            } else {
+                (RiskLevel::Medium, false)
+            };
+            changes.push(SchemaDiffItem {
+                change_type: ChangeType::Modified,
+                object_type: ObjectType::RowSecurity,
+                object_path: table_path.to_string(),
+                description: format!(
+                    "Row-level security on {}: {} → {}",
+                    table_path,
+                    state(from_rls.enabled, from_rls.forced),
+                    state(to_rls.enabled, to_rls.forced)
+                ),
+                before: Some(serde_json::json!({ "enabled": from_rls.enabled, "forced": from_rls.forced })),
+                after: Some(serde_json::json!({ "enabled": to_rls.enabled, "forced": to_rls.forced })),
+                risk_level,
+                is_breaking,
+            });
+        }
+        
+        let from_map: HashMap<&str, &RlsPolicy> = from_rls.policies.iter().map(|p| (p.name.as_str(), p)).collect();
+        let to_map: HashMap<&str, &RlsPolicy> = to_rls.policies.iter().map(|p| (p.name.as_str(), p)).collect();
+        let item = |change_type, name: &str, description: String, before: Option<&RlsPolicy>, after: Option<&RlsPolicy>, risk_level| {
+            SchemaDiffItem {
+                change_type,
+                object_type: ObjectType::Policy,
+                object_path: format!("{}.{}", table_path, name),
+                description,
+                before: before.map(|p| serde_json::to_value(p).unwrap_or_default()),
+                after: after.map(|p| serde_json::to_value(p).unwrap_or_default()),
+                risk_level,
+                is_breaking: false,
+            }
+        };
+        
+        for policy in &to_rls.policies {
+            match from_map.get(policy.name.as_str()) {
+                None => changes.push(item(
+                    ChangeType::Added,
+                    &policy.name,
+                    format!("Policy {} created on {} for {}", policy.name, table_path, policy.command),
+                    None,
+                    Some(policy),
+                    RiskLevel::Low,
+                )),
+                Some(previous) if *previous != policy => changes.push(item(
+                    ChangeType::Modified,
+                    &policy.name,
+                    format!("Policy {} on {} changed", policy.name, table_path),
+                    Some(previous),
+                    Some(policy),
+                    RiskLevel::Medium,
+                )),
+                Some(_) => {}
+            }
+        }
+        for policy in &from_rls.policies {
+            if to_map.contains_key(policy.name.as_str()) {
+                continue;
+            }
+            // Dropping a permissive policy hides rows; dropping a restrictive one exposes them
+            let risk_level = if policy.permissive { RiskLevel::Medium } else { RiskLevel::High };
+            changes.push(item(
+                ChangeType::Removed,
+                &policy.name,
+                format!("Policy {} dropped from {}", policy.name, table_path),
+                Some(policy),
+                None,
+                risk_level,
+            ));
+        }
+    }
+
+    /// Compare a table's primary key as one unit, so renaming or reordering
+    /// part of a composite key is not reported as a column leaving the key
+    fn diff_primary_key(
+        table_path: &str,
+        from_table: &Table,
+        to_table: &Table,
+        renames: &HashMap<String, String>,
+        changes: &mut Vec<SchemaDiffItem>,
+    ) {
+        let from_pk = Self::primary_key(from_table);
+        let to_pk = Self::primary_key(to_table);
+        
+        let (from_pk, to_pk) = match (from_pk, to_pk) {
+            (None, None) => return,
+            (None, Some(pk)) => {
+                changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Added,
+                    object_type: ObjectType::PrimaryKey,
+                    object_path: format!("{}.{}", table_path, pk.constraint_name),
+                    description: format!("Primary key added on {} ({})", table_path, pk.columns.join(", ")),
+                    before: None,
+                    after: Some(serde_json::to_value(&pk).unwrap_or_default()),
+                    risk_level: RiskLevel::High, // Fails on duplicate or NULL values
+                    is_breaking: false,
+                });
+                return;
+            }
+            (Some(pk), None) => {
+                changes.push(SchemaDiffItem {
+                    change_type: ChangeType::Removed,
+                    object_type: ObjectType::PrimaryKey,
+                    object_path: format!("{}.{}", table_path, pk.constraint_name),
+                    description: format!("Primary key dropped from {} ({})", table_path, pk.columns.join(", ")),
+                    before: Some(serde_json::json!({
+                        "constraintName": pk.constraint_name,
+                        "columns": pk.columns,
+                        "removedColumns": pk.columns,
+                    })),
+                    after: None,
+                    risk_level: RiskLevel::Critical,
+                    is_breaking: true,
+                });
+                return;
+            }
+            (Some(from_pk), Some(to_pk)) => (from_pk, to_pk),
+        };
+        
+        let delta = KeyDelta::compare(&from_pk.columns, &to_pk.columns, |c| {
+            renames.get(c).cloned().unwrap_or_else(|| c.to_string())
+        });
+        if delta.is_empty() {
+            return;
+        }
+        
+        let (risk_level, is_breaking) = if !delta.removed.is_empty() {
+            // Fewer key columns means a stricter uniqueness guarantee
+            (RiskLevel::Critical, true)
+        } else if !delta.added.is_empty() {
+            (RiskLevel::High, false)
+        } else if delta.reordered {
+            (RiskLevel::Medium, false)
+        } else {
+            (RiskLevel::Low, false)
+        };
+        
+        changes.push(SchemaDiffItem {
+            change_type: ChangeType::Modified,
+            object_type: ObjectType::PrimaryKey,
+            object_path: format!("{}.{}", table_path, to_pk.constraint_name),
+            description: format!("Primary key on {} modified: {}", table_path, delta.describe()),
+            before: Some(serde_json::to_value(&from_pk).unwrap_or_default()),
+            after: Some(delta.to_json(&to_pk.constraint_name, &to_pk.columns)),
+            risk_level,
+            is_breaking,
+        });
+    }
+
+    /// Primary key of a table, falling back to column flags for older snapshots
+    fn primary_key(table: &Table) -> Option<PrimaryKey> {
+        if let Some(pk) = &table.primary_key {
+            return Some(pk.clone());
+        }
+        
+        let mut columns: Vec<&Column> = table.columns.iter().filter(|c| c.is_primary_key).collect();
+        if columns.is_empty() {
+            return None;
+        }
+        columns.sort_by_key(|c| c.ordinal_position);
+        
+        Some(PrimaryKey {
+            constraint_name: format!("{}_pkey", table.name),
+            columns: columns.into_iter().map(|c| c.name.clone()).collect(),
+        })
+    }
+
+    fn diff_foreign_keys(
+        from_fks: &[ForeignKey],
+        to_fks: &[ForeignKey],
+        renames: &ColumnRenames,
+        changes: &mut Vec<SchemaDiffItem>,
+    ) {
         let from_map: HashMap<&str, &ForeignKey> = from_fks
             .iter()
             .map(|fk| (fk.constraint_name.as_str(), fk))
@@ -371,6 +1300,98 @@ impl DiffEngine {
                 is_breaking: false,
             });
         }
+        
+        // Modified FKs - column pairs are compared as a unit
+        for name in from_keys.intersection(&to_keys) {
+            let from_fk = from_map.get(name).unwrap();
+            let to_fk = to_map.get(name).unwrap();
+            if let Some(change) = Self::compare_foreign_keys(from_fk, to_fk, renames) {
+                changes.push(change);
+            }
+        }
+    }
+
+    fn compare_foreign_keys(
+        from: &ForeignKey,
+        to: &ForeignKey,
+        renames: &ColumnRenames,
+    ) -> Option<SchemaDiffItem> {
+        let rename = |table: String, column: &str| {
+            renames
+                .get(&table)
+                .and_then(|r| r.get(column))
+                .cloned()
+                .unwrap_or_else(|| column.to_string())
+        };
+        let source_table = format!("{}.{}", from.source_schema, from.source_table);
+        let referenced_table = format!("{}.{}", from.referenced_schema, from.referenced_table);
+        
+        // Each source column only makes sense with the column it references
+        let pair = |source: &String, referenced: &String| format!("{} → {}", source, referenced);
+        let from_pairs: Vec<String> = from.source_columns.iter().zip(&from.referenced_columns)
+            .map(|(s, r)| pair(s, r))
+            .collect();
+        let to_pairs: Vec<String> = to.source_columns.iter().zip(&to.referenced_columns)
+            .map(|(s, r)| pair(s, r))
+            .collect();
+        let renamed_pairs: HashMap<String, String> = from.source_columns.iter().zip(&from.referenced_columns)
+            .map(|(s, r)| (
+                pair(s, r),
+                pair(&rename(source_table.clone(), s), &rename(referenced_table.clone(), r)),
+            ))
+            .collect();
+        
+        let delta = KeyDelta::compare(&from_pairs, &to_pairs, |p| renamed_pairs[p].clone());
+        
+        let mut modifications = Vec::new();
+        if !delta.is_empty() {
+            modifications.push(delta.describe());
+        }
+        if from.on_delete != to.on_delete {
+            modifications.push(format!("ON DELETE {} → {}", from.on_delete, to.on_delete));
+        }
+        if from.on_update != to.on_update {
+            modifications.push(format!("ON UPDATE {} → {}", from.on_update, to.on_update));
+        }
+        if from.referenced_table != to.referenced_table || from.referenced_schema != to.referenced_schema {
+            modifications.push(format!(
+                "references {}.{} → {}.{}",
+                from.referenced_schema, from.referenced_table, to.referenced_schema, to.referenced_table
+            ));
+        }
+        
+        if modifications.is_empty() {
+            return None;
+        }
+        
+        let pairs_changed = !delta.added.is_empty()
+            || !delta.removed.is_empty()
+            || from.referenced_table != to.referenced_table
+            || from.referenced_schema != to.referenced_schema;
+        let (risk_level, is_breaking) = if pairs_changed {
+            // Existing rows may not satisfy the new constraint
+            (RiskLevel::High, true)
+        } else if from.on_delete != to.on_delete || from.on_update != to.on_update {
+            (RiskLevel::Medium, false)
+        } else {
+            (RiskLevel::Low, false)
+        };
+        
+        let mut after = delta.to_json(&to.constraint_name, &to_pairs);
+        if let (Some(obj), Ok(serde_json::Value::Object(fk))) = (after.as_object_mut(), serde_json::to_value(to)) {
+            obj.extend(fk);
+        }
+        
+        Some(SchemaDiffItem {
+            change_type: ChangeType::Modified,
+            object_type: ObjectType::ForeignKey,
+            object_path: format!("{}.{}.{}", to.source_schema, to.source_table, to.constraint_name),
+            description: format!("FK {} modified: {}", to.constraint_name, modifications.join(", ")),
+            before: Some(serde_json::to_value(from).unwrap_or_default()),
+            after: Some(after),
+            risk_level,
+            is_breaking,
+        })
     }
 
     fn diff_indexes(from_idxs: &[Index], to_idxs: &[Index], changes: &mut Vec<SchemaDiffItem>) {
@@ -468,6 +1489,12 @@ impl DiffEngine {
             indexes_removed: 0,
             fks_added: 0,
             fks_removed: 0,
+            ownership_changes: 0,
+            permission_changes: 0,
+            extension_changes: 0,
+            policy_changes: 0,
+            type_changes: 0,
+            view_changes: 0,
             total_changes: changes.len(),
         };
         
@@ -490,7 +1517,7 @@ impl DiffEngine {
                         modified_tables.insert(table.to_string());
                     }
                 }
-                (ObjectType::Column, ChangeType::Modified) => {
+                (ObjectType::Column, ChangeType::Modified | ChangeType::Renamed) => {
                     summary.columns_modified += 1;
                     if let Some(table) = change.object_path.rsplit('.').nth(1) {
                         modified_tables.insert(table.to_string());
@@ -503,6 +1530,14 @@ impl DiffEngine {
                 (ObjectType::ForeignKey, ChangeType::Added) => summary.fks_added += 1,
                 (ObjectType::ForeignKey, ChangeType::Removed) => summary.fks_removed += 1,
                 
+                // Schemas are only ever modified by an owner change
+                (ObjectType::Owner, _) | (ObjectType::Schema, ChangeType::Modified) => summary.ownership_changes += 1,
+                (ObjectType::Privilege, _) => summary.permission_changes += 1,
+                (ObjectType::Extension, _) => summary.extension_changes += 1,
+                (ObjectType::RowSecurity | ObjectType::Policy, _) => summary.policy_changes += 1,
+                (ObjectType::Domain | ObjectType::CompositeType, _) => summary.type_changes += 1,
+                (ObjectType::View | ObjectType::MaterializedView, _) => summary.view_changes += 1,
+                
                 _ => {}
             }
         }
@@ -529,3 +1564,487 @@ impl DiffEngine {
         max_risk.unwrap_or(RiskLevel::Safe)
     }
 }
+
+/// Column-level differences within one multi-column key
+#[derive(Debug, Default)]
+struct KeyDelta {
+    added: Vec<String>,
+    removed: Vec<String>,
+    /// old → new
+    renamed: Vec<(String, String)>,
+    reordered: bool,
+}
+
+impl KeyDelta {
+    /// Compare key members, mapping old members through `rename` first
+    fn compare(from: &[String], to: &[String], rename: impl Fn(&String) -> String) -> Self {
+        let mapped: Vec<String> = from.iter().map(&rename).collect();
+        
+        let renamed = from.iter().zip(&mapped)
+            .filter(|(old, new)| old != new && to.contains(new))
+            .map(|(old, new)| (old.clone(), new.clone()))
+            .collect();
+        let removed: Vec<String> = from.iter().zip(&mapped)
+            .filter(|(_, new)| !to.contains(new))
+            .map(|(old, _)| old.clone())
+            .collect();
+        let added: Vec<String> = to.iter()
+            .filter(|c| !mapped.contains(c))
+            .cloned()
+            .collect();
+        let reordered = added.is_empty() && removed.is_empty() && mapped != to;
+        
+        Self { added, removed, renamed, reordered }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty() && !self.reordered
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        if !self.renamed.is_empty() {
+            let renamed: Vec<String> = self.renamed.iter().map(|(o, n)| format!("{} → {}", o, n)).collect();
+            parts.push(format!("renamed {}", renamed.join(", ")));
+        }
+        if self.reordered {
+            parts.push("columns reordered".to_string());
+        }
+        parts.join("; ")
+    }
+
+    fn to_json(&self, constraint_name: &str, columns: &[String]) -> serde_json::Value {
+        serde_json::json!({
+            "constraintName": constraint_name,
+            "columns": columns,
+            "addedColumns": self.added,
+            "removedColumns": self.removed,
+            "renamedColumns": self.renamed.iter()
+                .map(|(o, n)| serde_json::json!({ "from": o, "to": n }))
+                .collect::<Vec<_>>(),
+            "reordered": self.reordered,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::Environment;
+    use crate::snapshot::Severity;
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn column(name: &str, ordinal_position: i32) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            nullable: false,
+            default_value: None,
+            is_primary_key: true,
+            is_unique: false,
+            ordinal_position,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+        }
+    }
+
+    fn snapshot(key: &[&str], fk_columns: &[&str]) -> SchemaSnapshot {
+        SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 1,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: vec![],
+            extensions: vec![],
+            types: None,
+            views: None,
+            tables: vec![Table {
+                name: "order_lines".to_string(),
+                schema: "public".to_string(),
+                columns: key.iter().enumerate().map(|(i, c)| column(c, i as i32 + 1)).collect(),
+                primary_key: Some(PrimaryKey {
+                    constraint_name: "order_lines_pkey".to_string(),
+                    columns: key.iter().map(|c| c.to_string()).collect(),
+                }),
+                position: None,
+                color: None,
+                collapsed: false,
+                governance: Default::default(),
+            }],
+            foreign_keys: vec![ForeignKey {
+                constraint_name: "shipments_line_fk".to_string(),
+                source_schema: "public".to_string(),
+                source_table: "shipments".to_string(),
+                source_columns: vec!["order_id".to_string(), "line_no".to_string()],
+                referenced_schema: "public".to_string(),
+                referenced_table: "order_lines".to_string(),
+                referenced_columns: fk_columns.iter().map(|c| c.to_string()).collect(),
+                on_update: "NO ACTION".to_string(),
+                on_delete: "NO ACTION".to_string(),
+            }],
+            indexes: vec![],
+            checksum: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_composite_key_rename_is_not_a_drop() {
+        let from = snapshot(&["order_id", "line_no"], &["order_id", "line_no"]);
+        let to = snapshot(&["order_id", "line_number"], &["order_id", "line_number"]);
+
+        let diff = DiffEngine::diff(&from, &to);
+
+        assert!(diff.changes.iter().all(|c| c.change_type != ChangeType::Removed));
+        let rename = diff.changes.iter().find(|c| c.change_type == ChangeType::Renamed).unwrap();
+        assert_eq!(rename.object_path, "public.order_lines.line_no");
+
+        let pk = diff.changes.iter().find(|c| c.object_type == ObjectType::PrimaryKey).unwrap();
+        assert_eq!(pk.risk_level, RiskLevel::Low);
+        assert!(!pk.is_breaking);
+
+        let fk = diff.changes.iter().find(|c| c.object_type == ObjectType::ForeignKey).unwrap();
+        assert_eq!(fk.risk_level, RiskLevel::Low);
+        assert_eq!(fk.after.as_ref().unwrap()["addedColumns"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_fingerprint_follows_the_changes() {
+        let from = snapshot(&["order_id", "line_no"], &["order_id", "line_no"]);
+        let renamed = snapshot(&["order_id", "line_number"], &["order_id", "line_number"]);
+        let narrowed = snapshot(&["order_id"], &["order_id"]);
+
+        let first = DiffEngine::diff(&from, &renamed).fingerprint();
+        assert_eq!(DiffEngine::diff(&from, &renamed).fingerprint(), first);
+        assert_ne!(DiffEngine::diff(&from, &narrowed).fingerprint(), first);
+    }
+
+    #[test]
+    fn test_composite_key_reorder_and_removal() {
+        let from = snapshot(&["order_id", "line_no"], &["order_id", "line_no"]);
+
+        let mut reordered = snapshot(&["order_id", "line_no"], &["order_id", "line_no"]);
+        reordered.tables[0].primary_key.as_mut().unwrap().columns.reverse();
+        let pk = DiffEngine::diff(&from, &reordered).changes.into_iter()
+            .find(|c| c.object_type == ObjectType::PrimaryKey)
+            .unwrap();
+        assert_eq!(pk.risk_level, RiskLevel::Medium);
+        assert_eq!(pk.after.unwrap()["reordered"], serde_json::json!(true));
+
+        let mut narrowed = snapshot(&["order_id", "line_no"], &["order_id", "line_no"]);
+        narrowed.tables[0].primary_key.as_mut().unwrap().columns.pop();
+        let pk = DiffEngine::diff(&from, &narrowed).changes.into_iter()
+            .find(|c| c.object_type == ObjectType::PrimaryKey)
+            .unwrap();
+        assert_eq!(pk.risk_level, RiskLevel::Critical);
+        assert_eq!(pk.after.unwrap()["removedColumns"], serde_json::json!(["line_no"]));
+    }
+
+    #[test]
+    fn test_owner_and_grant_drift() {
+        let grant = |grantee: &str, privileges: &[&str]| AclEntry {
+            grantee: grantee.to_string(),
+            privileges: privileges.iter().map(|p| p.to_string()).collect(),
+        };
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.tables[0].governance.owner = Some("app_owner".to_string());
+        from.tables[0].governance.acl = vec![grant("reporting", &["SELECT"])];
+
+        let mut to = from.clone();
+        to.tables[0].governance.owner = Some("dba".to_string());
+        to.tables[0].governance.acl = vec![grant("PUBLIC", &["SELECT"]), grant("reporting", &["INSERT", "SELECT"])];
+        to.tables[0].governance.description = Some("Order lines".to_string());
+
+        let diff = DiffEngine::diff(&from, &to);
+        assert_eq!(diff.summary.ownership_changes, 1);
+        assert_eq!(diff.summary.permission_changes, 1);
+
+        let grants = diff.changes.iter().find(|c| c.object_type == ObjectType::Privilege).unwrap();
+        assert_eq!(grants.description, "Privileges on public.order_lines changed: PUBLIC +SELECT; reporting +INSERT");
+        assert_eq!(grants.risk_level, RiskLevel::High);
+        assert!(diff.changes.iter().any(|c| c.object_type == ObjectType::Table && c.risk_level == RiskLevel::Safe));
+
+        // Snapshots from before owners were recorded do not report every grant as new
+        from.tables[0].governance.owner = None;
+        from.tables[0].governance.acl.clear();
+        to.tables[0].governance.description = None;
+        assert!(DiffEngine::diff(&from, &to).changes.is_empty());
+    }
+
+    #[test]
+    fn test_extension_drift() {
+        let extension = |name: &str, version: &str, dependent_objects: i64| Extension {
+            name: name.to_string(),
+            version: version.to_string(),
+            schema: "public".to_string(),
+            dependent_objects,
+        };
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.extensions = vec![extension("plpgsql", "1.0", 0), extension("postgis", "3.3.2", 4)];
+
+        let mut to = from.clone();
+        to.extensions = vec![extension("pgcrypto", "1.3", 0), extension("plpgsql", "1.1", 0)];
+
+        let diff = DiffEngine::diff(&from, &to);
+        assert_eq!(diff.summary.extension_changes, 3);
+
+        let dropped = diff.changes.iter().find(|c| c.change_type == ChangeType::Removed).unwrap();
+        assert_eq!(dropped.object_path, "postgis");
+        assert_eq!(dropped.risk_level, RiskLevel::High);
+        assert!(dropped.is_breaking);
+
+        let engine = crate::snapshot::RulesEngine::new();
+        let rules = engine.evaluate(&diff, &to, &Environment::Development);
+        let dropped = rules.violations.iter().find(|v| v.rule_id == "R021" && v.affected_object == "postgis").unwrap();
+        assert_eq!(dropped.severity, Severity::Warning);
+        assert!(!rules.has_errors);
+
+        // The same drop is escalated on a production connection
+        let rules = engine.evaluate(&diff, &to, &Environment::Production);
+        assert!(rules.violations.iter().any(|v| v.rule_id == "R021" && v.severity == Severity::Error));
+        assert!(rules.has_errors);
+
+        // Config overrides apply to custom environments too
+        let overrides = BTreeMap::from([("qa".to_string(), BTreeMap::from([("R021".to_string(), Severity::Info)]))]);
+        let rules = crate::snapshot::RulesEngine::new()
+            .with_environment_severity(&overrides)
+            .evaluate(&diff, &to, &Environment::Custom("qa".to_string()));
+        assert!(rules.violations.iter().any(|v| v.rule_id == "R021" && v.severity == Severity::Info));
+
+        // Snapshots without extensions predate them and report nothing
+        from.extensions.clear();
+        assert!(DiffEngine::diff(&from, &to).changes.is_empty());
+    }
+
+    #[test]
+    fn test_row_security_drift() {
+        use crate::introspection::{PiiLevel, RowSecurity};
+        let policy = |name: &str, using: &str| RlsPolicy {
+            name: name.to_string(),
+            permissive: true,
+            command: "SELECT".to_string(),
+            roles: vec!["app".to_string()],
+            using: Some(using.to_string()),
+            with_check: None,
+        };
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.tables[0].columns[0].pii_classification = Some(PiiLevel::Confidential);
+        from.tables[0].governance.row_security = Some(RowSecurity {
+            enabled: true,
+            forced: false,
+            policies: vec![policy("own_rows", "owner = current_user"), policy("support", "true")],
+        });
+
+        let mut to = from.clone();
+        to.tables[0].governance.row_security = Some(RowSecurity {
+            enabled: false,
+            forced: false,
+            policies: vec![policy("own_rows", "tenant = current_setting('app.tenant')")],
+        });
+
+        let diff = DiffEngine::diff(&from, &to);
+        assert_eq!(diff.summary.policy_changes, 3);
+        let toggled = diff.changes.iter().find(|c| c.object_type == ObjectType::RowSecurity).unwrap();
+        assert_eq!(toggled.description, "Row-level security on public.order_lines: enabled → disabled");
+        assert_eq!(toggled.risk_level, RiskLevel::High);
+        let dropped = diff.changes.iter().find(|c| c.change_type == ChangeType::Removed).unwrap();
+        assert_eq!(dropped.object_path, "public.order_lines.support");
+
+        let rules = crate::snapshot::RulesEngine::new().evaluate(&diff, &to, &Environment::Production);
+        let violation = rules.violations.iter().find(|v| v.rule_id == "R022").unwrap();
+        assert_eq!(violation.severity, Severity::Block);
+        assert_eq!(violation.affected_object, "public.order_lines");
+
+        // Snapshots without row-level security predate it and report nothing
+        from.tables[0].governance.row_security = None;
+        assert!(DiffEngine::diff(&from, &to).changes.is_empty());
+    }
+
+    #[test]
+    fn test_domain_and_composite_drift() {
+        use crate::introspection::{CompositeAttribute, DomainCheck};
+
+        let domain = |name: &str, base_type: &str, checks: &[(&str, &str)]| Domain {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            base_type: base_type.to_string(),
+            not_null: false,
+            default_value: None,
+            collation: None,
+            checks: checks
+                .iter()
+                .map(|(name, expression)| DomainCheck { name: name.to_string(), expression: expression.to_string() })
+                .collect(),
+        };
+        let address = |attributes: &[(&str, &str)]| CompositeType {
+            schema: "public".to_string(),
+            name: "address".to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(name, data_type)| CompositeAttribute { name: name.to_string(), data_type: data_type.to_string() })
+                .collect(),
+        };
+
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.tables[0].columns[0].data_type = "public.quantity".to_string();
+        from.types = Some(UserTypes {
+            domains: vec![domain("quantity", "integer", &[]), domain("sku", "text", &[])],
+            composites: vec![address(&[("street", "text"), ("zip", "text")])],
+        });
+
+        let mut to = from.clone();
+        to.types = Some(UserTypes {
+            domains: vec![domain("quantity", "integer", &[("quantity_check", "(VALUE > 0)")])],
+            composites: vec![address(&[("street", "text"), ("zip", "integer"), ("country", "text")])],
+        });
+
+        let diff = DiffEngine::diff(&from, &to);
+        assert_eq!(diff.summary.type_changes, 3);
+        assert_eq!(diff.changes.len(), 3);
+
+        let quantity = diff.changes.iter().find(|c| c.object_path == "public.quantity").unwrap();
+        assert_eq!(quantity.description, "Domain public.quantity changed: check quantity_check added: (VALUE > 0)");
+        assert_eq!(quantity.risk_level, RiskLevel::Medium);
+        assert!(!quantity.is_breaking);
+
+        let sku = diff.changes.iter().find(|c| c.object_path == "public.sku").unwrap();
+        assert_eq!(sku.change_type, ChangeType::Removed);
+        assert_eq!(sku.risk_level, RiskLevel::Medium);
+
+        let address = diff.changes.iter().find(|c| c.object_type == ObjectType::CompositeType).unwrap();
+        assert_eq!(address.description, "Composite type public.address changed: zip: text → integer, country text added");
+        assert!(address.is_breaking);
+
+        // A snapshot without types showed the domain column as its base type
+        from.types = None;
+        from.tables[0].columns[0].data_type = "integer".to_string();
+        assert!(DiffEngine::diff(&from, &to).changes.is_empty());
+    }
+
+    #[test]
+    fn test_view_drift() {
+        let view = |name: &str, materialized: bool, definition: &str, depends_on: &[&str]| View {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            materialized,
+            definition: definition.to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+        };
+
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.views = Some(vec![
+            view("open_orders", false, "SELECT * FROM orders", &["public.orders"]),
+            view("order_totals", true, "SELECT sum(total) FROM orders", &["public.orders"]),
+            view("open_order_count", false, "SELECT count(*) FROM open_orders", &["public.open_orders"]),
+        ]);
+        let mut to = from.clone();
+        to.views = Some(vec![
+            view("order_totals", true, "SELECT sum(total), count(*) FROM orders", &["public.orders"]),
+            view("open_order_count", false, "SELECT count(*) FROM orders", &["public.orders"]),
+        ]);
+
+        let diff = DiffEngine::diff(&from, &to);
+        assert_eq!(diff.summary.view_changes, 3);
+        let change = |path: &str| diff.changes.iter().find(|c| c.object_path == path).unwrap();
+        let dropped = change("public.open_orders");
+        assert_eq!((dropped.change_type, dropped.risk_level), (ChangeType::Removed, RiskLevel::High));
+        assert_eq!(dropped.description, "View public.open_orders dropped (1 views read it)");
+        let totals = change("public.order_totals");
+        assert_eq!((totals.object_type, totals.risk_level), (ObjectType::MaterializedView, RiskLevel::Medium));
+
+        // Snapshots captured before views were recorded report none
+        from.views = None;
+        assert!(DiffEngine::diff(&from, &to).changes.is_empty());
+    }
+
+    #[test]
+    fn test_column_order_only_counts_when_strict() {
+        let from = snapshot(&["order_id", "line_no", "sku"], &["order_id", "line_no"]);
+        let mut to = from.clone();
+        to.tables[0].columns = vec![column("sku", 1), column("order_id", 2), column("line_no", 3)];
+
+        let is_reorder = |c: &SchemaDiffItem| c.object_type == ObjectType::Table && c.description.starts_with("Column order");
+        assert!(!DiffEngine::diff(&from, &to).changes.iter().any(is_reorder));
+
+        let strict = IgnoreRules::default().with_column_order(ColumnOrder::Strict);
+        let diff = DiffEngine::diff_ignoring(&from, &to, &strict);
+        let reorder = diff.changes.iter().find(|c| is_reorder(c)).unwrap();
+        assert_eq!(
+            reorder.description,
+            "Column order of public.order_lines changed: (order_id, line_no, sku) → (sku, order_id, line_no)"
+        );
+
+        // Checksums follow the same mode
+        let checksum = |s: &SchemaSnapshot, order| s.clone().with_column_order(order).checksum;
+        assert_eq!(checksum(&from, ColumnOrder::Semantic), checksum(&to, ColumnOrder::Semantic));
+        assert_ne!(checksum(&from, ColumnOrder::Strict), checksum(&to, ColumnOrder::Strict));
+
+        // The gap a dropped column leaves is not a reorder
+        let mut gapped = from.clone();
+        gapped.tables[0].columns.remove(1);
+        let mut compact = gapped.clone();
+        compact.tables[0].columns[1].ordinal_position = 2;
+        assert!(!DiffEngine::diff_in(&gapped, &compact, ColumnOrder::Strict).changes.iter().any(is_reorder));
+        assert_eq!(checksum(&gapped, ColumnOrder::Strict), checksum(&compact, ColumnOrder::Strict));
+        assert_eq!(
+            gapped.clone().with_column_order(ColumnOrder::Strict).scoped(&["public".to_string()]).checksum,
+            checksum(&gapped, ColumnOrder::Strict)
+        );
+    }
+
+    #[test]
+    fn test_encoding_and_collation_changes_are_detected_and_flagged() {
+        let database = |encoding: &str, collation: &str| DatabaseMetadata {
+            name: "shop".to_string(),
+            encoding: encoding.to_string(),
+            collation: collation.to_string(),
+            ctype: "en_US.UTF-8".to_string(),
+        };
+        let mut from = snapshot(&["order_id"], &["order_id"]);
+        from.database = database("UTF8", "en_US.UTF-8");
+        let engine = crate::snapshot::RulesEngine::new();
+
+        // Database encoding and default collation
+        let mut to = from.clone();
+        to.database = database("LATIN1", "C");
+        let diff = DiffEngine::diff(&from, &to);
+        let settings = diff.changes.iter().find(|c| c.object_type == ObjectType::Database).unwrap();
+        assert_eq!(settings.change_type, ChangeType::Modified);
+        assert!(settings.description.contains("encoding: UTF8 → LATIN1"));
+        assert!(settings.description.contains("collation: en_US.UTF-8 → C"));
+        let rules = engine.evaluate(&diff, &to, &Environment::Production);
+        let encoding = rules.violations.iter().find(|v| v.rule_id == "R011").unwrap();
+        assert_eq!(encoding.severity, Severity::Warning);
+        assert!(encoding.message.contains("UTF8 vs LATIN1"));
+
+        // A column moved off the database's default collation
+        let mut to = from.clone();
+        to.tables[0].columns[0].collation = Some("C".to_string());
+        let diff = DiffEngine::diff(&from, &to);
+        let column = diff.changes.iter().find(|c| c.object_type == ObjectType::Column).unwrap();
+        assert_eq!(column.change_type, ChangeType::Modified);
+        assert_eq!(column.object_path, "public.order_lines.order_id");
+        assert!(column.description.contains("collation"));
+        assert_eq!(column.risk_level, RiskLevel::Medium);
+        let rules = engine.evaluate(&diff, &to, &Environment::Production);
+        let collation = rules.violations.iter().find(|v| v.rule_id == "R010").unwrap();
+        assert_eq!(collation.affected_object, "public.order_lines.order_id");
+
+        // Moving back to the default is detected but not flagged
+        let diff = DiffEngine::diff(&to, &from);
+        assert!(diff.changes.iter().any(|c| c.object_type == ObjectType::Column));
+        let rules = engine.evaluate(&diff, &from, &Environment::Production);
+        assert!(rules.violations.iter().all(|v| v.rule_id != "R010"));
+    }
+}
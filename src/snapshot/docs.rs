@@ -0,0 +1,483 @@
+//! Schema documentation bundles
+//!
+//! Renders a snapshot into one page per table plus an index, in Markdown or
+//! HTML, ready to drop into a static site. Descriptions and tags come from the
+//! governance metadata (table and column comments), alongside any data
+//! dictionary custom fields recorded on the snapshot. The ER diagram data is
+//! returned alongside the pages so a site can draw it with its own tooling,
+//! and the index embeds it as Mermaid for renderers that support it.
+
+use crate::introspection::{ForeignKey, SchemaSnapshot, Table};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Output format of the pages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl DocsFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DocsFormat::Markdown => "md",
+            DocsFormat::Html => "html",
+        }
+    }
+}
+
+/// One generated page
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocFile {
+    /// Relative path within the bundle, e.g. "tables/public.users.md"
+    pub path: String,
+    pub content: String,
+}
+
+/// A table in the ER diagram
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErNode {
+    /// Schema-qualified table name
+    pub id: String,
+    pub columns: Vec<ErColumn>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErColumn {
+    pub name: String,
+    pub data_type: String,
+    pub primary_key: bool,
+}
+
+/// A foreign key in the ER diagram, pointing from the referencing table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErEdge {
+    pub from: String,
+    pub to: String,
+    pub constraint_name: String,
+    pub columns: Vec<String>,
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErDiagram {
+    pub nodes: Vec<ErNode>,
+    pub edges: Vec<ErEdge>,
+    /// The same diagram as a Mermaid `erDiagram`
+    pub mermaid: String,
+}
+
+/// Documentation generated from one snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocsBundle {
+    pub connection_id: Uuid,
+    pub snapshot_id: Uuid,
+    pub version: u64,
+    pub format: DocsFormat,
+    pub generated_at: DateTime<Utc>,
+    pub files: Vec<DocFile>,
+    pub er_diagram: ErDiagram,
+}
+
+/// Build the documentation bundle for a snapshot
+pub fn generate(snapshot: &SchemaSnapshot, format: DocsFormat) -> DocsBundle {
+    let mut tables: Vec<&Table> = snapshot.tables.iter().collect();
+    tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+
+    let er_diagram = er_diagram(&tables, &snapshot.foreign_keys);
+
+    let mut files = vec![DocFile {
+        path: format!("index.{}", format.extension()),
+        content: render_index(snapshot, &tables, &er_diagram, format),
+    }];
+    files.extend(tables.iter().map(|table| DocFile {
+        path: table_path(table, format),
+        content: render_table(snapshot, table, format),
+    }));
+
+    DocsBundle {
+        connection_id: snapshot.connection_id,
+        snapshot_id: snapshot.id,
+        version: snapshot.version,
+        format,
+        generated_at: Utc::now(),
+        files,
+        er_diagram,
+    }
+}
+
+fn qualified(table: &Table) -> String {
+    format!("{}.{}", table.schema, table.name)
+}
+
+fn table_path(table: &Table, format: DocsFormat) -> String {
+    format!("tables/{}.{}", qualified(table), format.extension())
+}
+
+fn er_diagram(tables: &[&Table], foreign_keys: &[ForeignKey]) -> ErDiagram {
+    let nodes: Vec<ErNode> = tables
+        .iter()
+        .map(|table| ErNode {
+            id: qualified(table),
+            columns: table
+                .columns
+                .iter()
+                .map(|c| ErColumn {
+                    name: c.name.clone(),
+                    data_type: c.data_type.clone(),
+                    primary_key: c.is_primary_key,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let edges: Vec<ErEdge> = foreign_keys
+        .iter()
+        .map(|fk| ErEdge {
+            from: format!("{}.{}", fk.source_schema, fk.source_table),
+            to: format!("{}.{}", fk.referenced_schema, fk.referenced_table),
+            constraint_name: fk.constraint_name.clone(),
+            columns: fk.source_columns.clone(),
+            referenced_columns: fk.referenced_columns.clone(),
+        })
+        .collect();
+
+    // Mermaid entity names cannot contain dots or spaces
+    let entity = |id: &str| id.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_");
+    let mut mermaid = String::from("erDiagram\n");
+    for node in &nodes {
+        mermaid.push_str(&format!("    {} {{\n", entity(&node.id)));
+        for column in &node.columns {
+            let data_type = column.data_type.replace(' ', "_");
+            let key = if column.primary_key { " PK" } else { "" };
+            mermaid.push_str(&format!("        {} {}{}\n", data_type, column.name, key));
+        }
+        mermaid.push_str("    }\n");
+    }
+    for edge in &edges {
+        mermaid.push_str(&format!(
+            "    {} }}o--|| {} : \"{}\"\n",
+            entity(&edge.from),
+            entity(&edge.to),
+            edge.constraint_name
+        ));
+    }
+
+    ErDiagram { nodes, edges, mermaid }
+}
+
+fn render_index(snapshot: &SchemaSnapshot, tables: &[&Table], er: &ErDiagram, format: DocsFormat) -> String {
+    let title = if snapshot.database.name.is_empty() {
+        "Database schema".to_string()
+    } else {
+        format!("{} schema", snapshot.database.name)
+    };
+    let captured = format!(
+        "Snapshot v{} captured {}",
+        snapshot.version,
+        snapshot.captured_at.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    match format {
+        DocsFormat::Markdown => {
+            let mut out = format!("# {}\n\n{}\n\n## Tables\n\n", title, captured);
+            out.push_str("| Table | Description | Tags |\n|---|---|---|\n");
+            for table in tables {
+                out.push_str(&format!(
+                    "| [{}]({}) | {} | {} |\n",
+                    qualified(table),
+                    table_path(table, format),
+                    md_cell(table.governance.description.as_deref().unwrap_or("")),
+                    md_cell(&table.governance.tags.join(", "))
+                ));
+            }
+            out.push_str(&format!("\n## Relationships\n\n```mermaid\n{}```\n", er.mermaid));
+            out
+        }
+        DocsFormat::Html => {
+            let rows: String = tables
+                .iter()
+                .map(|table| {
+                    format!(
+                        "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                        escape(&table_path(table, format)),
+                        escape(&qualified(table)),
+                        escape(table.governance.description.as_deref().unwrap_or("")),
+                        escape(&table.governance.tags.join(", "))
+                    )
+                })
+                .collect();
+            let body = format!(
+                "<h1>{}</h1>\n<p>{}</p>\n<h2>Tables</h2>\n<table>\n<tr><th>Table</th><th>Description</th><th>Tags</th></tr>\n{}</table>\n<h2>Relationships</h2>\n<pre class=\"mermaid\">{}</pre>\n",
+                escape(&title),
+                escape(&captured),
+                rows,
+                escape(&er.mermaid)
+            );
+            html_page(&title, &body)
+        }
+    }
+}
+
+fn render_table(snapshot: &SchemaSnapshot, table: &Table, format: DocsFormat) -> String {
+    let name = qualified(table);
+    let outgoing: Vec<&ForeignKey> = snapshot
+        .foreign_keys
+        .iter()
+        .filter(|fk| fk.source_schema == table.schema && fk.source_table == table.name)
+        .collect();
+    let incoming: Vec<&ForeignKey> = snapshot
+        .foreign_keys
+        .iter()
+        .filter(|fk| fk.referenced_schema == table.schema && fk.referenced_table == table.name)
+        .collect();
+    let indexes: Vec<_> = snapshot
+        .indexes
+        .iter()
+        .filter(|i| i.schema == table.schema && i.table == table.name)
+        .collect();
+
+    let references = |fk: &ForeignKey| {
+        format!(
+            "{} ({}) → {}.{} ({})",
+            fk.constraint_name,
+            fk.source_columns.join(", "),
+            fk.referenced_schema,
+            fk.referenced_table,
+            fk.referenced_columns.join(", ")
+        )
+    };
+    let referenced_by = |fk: &ForeignKey| {
+        format!(
+            "{}.{} ({}) via {}",
+            fk.source_schema,
+            fk.source_table,
+            fk.source_columns.join(", "),
+            fk.constraint_name
+        )
+    };
+    let index_line = |i: &&crate::introspection::Index| {
+        let kind = if i.is_primary { "primary key" } else if i.is_unique { "unique" } else { "index" };
+        format!("{} ({}) {}, {}", i.name, i.columns.join(", "), kind, i.index_type)
+    };
+
+    match format {
+        DocsFormat::Markdown => {
+            let mut out = format!("# {}\n\n", name);
+            if let Some(description) = &table.governance.description {
+                out.push_str(&format!("{}\n\n", description));
+            }
+            if !table.governance.tags.is_empty() {
+                out.push_str(&format!("Tags: {}\n\n", table.governance.tags.join(", ")));
+            }
+            if let Some(owner) = &table.governance.owner {
+                out.push_str(&format!("Owner: {}\n\n", owner));
+            }
+            for (field, value) in &table.governance.custom_fields {
+                out.push_str(&format!("{}: {}\n\n", field, value));
+            }
+
+            out.push_str("## Columns\n\n| Column | Type | Nullable | Default | Description | Tags | Custom fields |\n|---|---|---|---|---|---|---|\n");
+            for c in &table.columns {
+                let key = if c.is_primary_key { " (PK)" } else { "" };
+                out.push_str(&format!(
+                    "| {}{} | {} | {} | {} | {} | {} | {} |\n",
+                    md_cell(&c.name),
+                    key,
+                    md_cell(&c.data_type),
+                    if c.nullable { "yes" } else { "no" },
+                    md_cell(c.default_value.as_deref().unwrap_or("")),
+                    md_cell(c.description.as_deref().unwrap_or("")),
+                    md_cell(&c.tags.join(", ")),
+                    md_cell(&custom_fields(&c.custom_fields))
+                ));
+            }
+
+            let mut section = |heading: &str, lines: Vec<String>| {
+                if !lines.is_empty() {
+                    out.push_str(&format!("\n## {}\n\n", heading));
+                    for line in lines {
+                        out.push_str(&format!("- {}\n", line));
+                    }
+                }
+            };
+            section("References", outgoing.iter().map(|fk| references(fk)).collect());
+            section("Referenced by", incoming.iter().map(|fk| referenced_by(fk)).collect());
+            section("Indexes", indexes.iter().map(index_line).collect());
+            out
+        }
+        DocsFormat::Html => {
+            let mut body = format!("<h1>{}</h1>\n", escape(&name));
+            if let Some(description) = &table.governance.description {
+                body.push_str(&format!("<p>{}</p>\n", escape(description)));
+            }
+            if !table.governance.tags.is_empty() {
+                body.push_str(&format!("<p>Tags: {}</p>\n", escape(&table.governance.tags.join(", "))));
+            }
+            if let Some(owner) = &table.governance.owner {
+                body.push_str(&format!("<p>Owner: {}</p>\n", escape(owner)));
+            }
+            for (field, value) in &table.governance.custom_fields {
+                body.push_str(&format!("<p>{}: {}</p>\n", escape(field), escape(value)));
+            }
+
+            body.push_str("<h2>Columns</h2>\n<table>\n<tr><th>Column</th><th>Type</th><th>Nullable</th><th>Default</th><th>Description</th><th>Tags</th><th>Custom fields</th></tr>\n");
+            for c in &table.columns {
+                let key = if c.is_primary_key { " (PK)" } else { "" };
+                body.push_str(&format!(
+                    "<tr><td>{}{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&c.name),
+                    key,
+                    escape(&c.data_type),
+                    if c.nullable { "yes" } else { "no" },
+                    escape(c.default_value.as_deref().unwrap_or("")),
+                    escape(c.description.as_deref().unwrap_or("")),
+                    escape(&c.tags.join(", ")),
+                    escape(&custom_fields(&c.custom_fields))
+                ));
+            }
+            body.push_str("</table>\n");
+
+            let mut section = |heading: &str, lines: Vec<String>| {
+                if !lines.is_empty() {
+                    body.push_str(&format!("<h2>{}</h2>\n<ul>\n", heading));
+                    for line in lines {
+                        body.push_str(&format!("<li>{}</li>\n", escape(&line)));
+                    }
+                    body.push_str("</ul>\n");
+                }
+            };
+            section("References", outgoing.iter().map(|fk| references(fk)).collect());
+            section("Referenced by", incoming.iter().map(|fk| referenced_by(fk)).collect());
+            section("Indexes", indexes.iter().map(index_line).collect());
+            html_page(&name, &body)
+        }
+    }
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        body
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Custom field values as "field: value" pairs
+fn custom_fields(fields: &BTreeMap<String, String>) -> String {
+    fields
+        .iter()
+        .map(|(field, value)| format!("{}: {}", field, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Keep a value from breaking a Markdown table row
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, TableGovernance};
+
+    fn column(name: &str, primary: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            nullable: !primary,
+            default_value: None,
+            is_primary_key: primary,
+            is_unique: primary,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: None,
+            description: Some("Owner <id> | key".to_string()),
+            tags: vec![],
+            custom_fields: Default::default(),
+        }
+    }
+
+    fn table(name: &str, columns: Vec<Column>) -> Table {
+        Table {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            columns,
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: TableGovernance {
+                tags: vec!["payments".to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_bundle_has_page_per_table_and_er_edges() {
+        let mut snapshot = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 3,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: vec![
+                table("orders", vec![column("id", true), column("user_id", false)]),
+                table("users", vec![column("id", true)]),
+            ],
+            foreign_keys: vec![ForeignKey {
+                constraint_name: "orders_user_fk".to_string(),
+                source_schema: "public".to_string(),
+                source_table: "orders".to_string(),
+                source_columns: vec!["user_id".to_string()],
+                referenced_schema: "public".to_string(),
+                referenced_table: "users".to_string(),
+                referenced_columns: vec!["id".to_string()],
+                on_update: "NO ACTION".to_string(),
+                on_delete: "CASCADE".to_string(),
+            }],
+            indexes: Vec::new(),
+            checksum: String::new(),
+        };
+
+        snapshot.tables[0].governance.custom_fields.insert("data_steward".to_string(), "Alice".to_string());
+        snapshot.tables[0].columns[1].custom_fields.insert("source_system".to_string(), "CRM".to_string());
+
+        let markdown = generate(&snapshot, DocsFormat::Markdown);
+        let paths: Vec<_> = markdown.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["index.md", "tables/public.orders.md", "tables/public.users.md"]);
+        assert!(markdown.files[2].content.contains("public.orders (user_id) via orders_user_fk"));
+        assert!(markdown.files[1].content.contains("Owner <id> \\| key"));
+        assert!(markdown.files[1].content.contains("data_steward: Alice"));
+        assert!(markdown.files[1].content.contains("| source_system: CRM |"));
+        assert_eq!(markdown.er_diagram.edges[0].to, "public.users");
+        assert!(markdown.er_diagram.mermaid.contains("public_orders }o--|| public_users"));
+
+        let html = generate(&snapshot, DocsFormat::Html);
+        assert!(html.files[1].content.contains("Owner &lt;id&gt; | key"));
+    }
+}
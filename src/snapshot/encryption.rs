@@ -0,0 +1,207 @@
+//! Column Encryption Checks
+//!
+//! Flags Restricted and Secret columns that are stored as readable values.
+//! Classification comes from `[pii:<level>]` tags in column comments; columns
+//! without a tag are classified from well-known names (ssn, card_number,
+//! password, ...). A column counts as encrypted when it is `bytea` (pgcrypto
+//! output) or its comment carries an `[encrypted]` tag for application-level
+//! ciphertext kept in a text column.
+
+use crate::i18n::params;
+use crate::introspection::{PiiLevel, SchemaSnapshot};
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::rules::{RuleViolation, Severity};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Where a column's classification came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassificationSource {
+    Tagged,
+    Inferred,
+}
+
+/// A Restricted or Secret column
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitiveColumn {
+    pub table: String,
+    pub column: String,
+    pub data_type: String,
+    pub classification: PiiLevel,
+    pub source: ClassificationSource,
+    pub encrypted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Security review listing for a connection
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionReport {
+    pub connection_id: Uuid,
+    pub snapshot_version: u64,
+    pub generated_at: DateTime<Utc>,
+    pub columns: Vec<SensitiveColumn>,
+    pub plaintext_count: usize,
+}
+
+/// Name fragments that identify sensitive data when no tag is present
+const SECRET_NAMES: &[&str] = &["password", "passwd", "secret", "api_key", "apikey", "private_key", "access_token", "refresh_token"];
+const RESTRICTED_NAMES: &[&str] = &[
+    "ssn", "social_security", "tax_id", "national_id", "passport", "card_number", "credit_card",
+    "cvv", "iban", "account_number", "routing_number", "date_of_birth", "dob", "medical", "diagnosis",
+];
+
+/// Classification from a name alone, for untagged columns
+pub fn infer_level(column_name: &str) -> Option<PiiLevel> {
+    let name = column_name.to_lowercase();
+    // Hashes are already one-way
+    if name.ends_with("_hash") || name.ends_with("_digest") {
+        return None;
+    }
+
+    let words: Vec<&str> = name.split('_').collect();
+    let matches = |fragments: &[&str]| {
+        fragments.iter().any(|f| {
+            let parts: Vec<&str> = f.split('_').collect();
+            words.windows(parts.len()).any(|w| w == parts.as_slice())
+        })
+    };
+    if matches(SECRET_NAMES) {
+        Some(PiiLevel::Secret)
+    } else if matches(RESTRICTED_NAMES) {
+        Some(PiiLevel::Restricted)
+    } else {
+        None
+    }
+}
+
+/// Effective classification if the column is Restricted or Secret
+pub fn sensitive_level(column_name: &str, tagged: Option<&PiiLevel>) -> Option<(PiiLevel, ClassificationSource)> {
+    let (level, source) = match tagged {
+        Some(level) => (level.clone(), ClassificationSource::Tagged),
+        None => (infer_level(column_name)?, ClassificationSource::Inferred),
+    };
+    matches!(level, PiiLevel::Restricted | PiiLevel::Secret).then_some((level, source))
+}
+
+pub fn is_encrypted(data_type: &str, tags: &[String]) -> bool {
+    data_type.eq_ignore_ascii_case("bytea") || tags.iter().any(|t| t == "encrypted")
+}
+
+fn suggestion(column_name: &str, level: &PiiLevel) -> String {
+    let name = column_name.to_lowercase();
+    if name.contains("password") || name.contains("passwd") {
+        return "Store a salted hash (bcrypt/argon2) instead of the password itself".to_string();
+    }
+    match level {
+        PiiLevel::Secret => "Encrypt in the application with a KMS-managed key so plaintext and key never reach the database; tag the column [encrypted]".to_string(),
+        _ => "Store as bytea using pgcrypto (pgp_sym_encrypt/pgp_sym_decrypt), or encrypt in the application and tag the column [encrypted]".to_string(),
+    }
+}
+
+/// R020 for one column, if it is sensitive and readable
+pub fn check_column(
+    path: &str,
+    column_name: &str,
+    data_type: &str,
+    tagged: Option<&PiiLevel>,
+    tags: &[String],
+) -> Option<RuleViolation> {
+    let (level, source) = sensitive_level(column_name, tagged)?;
+    if is_encrypted(data_type, tags) {
+        return None;
+    }
+
+    let how = match source {
+        ClassificationSource::Tagged => "classified",
+        ClassificationSource::Inferred => "looks",
+    };
+    Some(RuleViolation {
+        rule_id: "R020".to_string(),
+        rule_name: "Unencrypted Sensitive Column".to_string(),
+        severity: if level == PiiLevel::Secret { Severity::Error } else { Severity::Warning },
+        message: format!("Column {} {} {:?} but is stored as plaintext {}", path, how, level, data_type),
+        affected_object: path.to_string(),
+        suggestion: Some(suggestion(column_name, &level)),
+        params: params(&[("object", path), ("level", &format!("{:?}", level)), ("dataType", data_type), ("source", how)]),
+    })
+}
+
+/// R020 for columns a proposal creates
+pub fn check_proposal(proposal: &SchemaProposal) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+
+    for change in &proposal.changes {
+        let (table_name, columns) = match change {
+            SchemaChange::CreateTable { table_name, columns, .. } => (table_name, columns.iter().collect::<Vec<_>>()),
+            SchemaChange::AddColumn { table_name, column } => (table_name, vec![column]),
+            _ => continue,
+        };
+        for column in columns {
+            let path = format!("{}.{}", table_name, column.name);
+            violations.extend(check_column(&path, &column.name, &column.data_type, None, &[]));
+        }
+    }
+
+    violations
+}
+
+/// Every Restricted or Secret column in a snapshot, plaintext ones first
+pub fn report(snapshot: &SchemaSnapshot) -> EncryptionReport {
+    let mut columns: Vec<SensitiveColumn> = snapshot
+        .tables
+        .iter()
+        .flat_map(|table| {
+            table.columns.iter().filter_map(move |column| {
+                let (classification, source) = sensitive_level(&column.name, column.pii_classification.as_ref())?;
+                let encrypted = is_encrypted(&column.data_type, &column.tags);
+                Some(SensitiveColumn {
+                    table: format!("{}.{}", table.schema, table.name),
+                    column: column.name.clone(),
+                    data_type: column.data_type.clone(),
+                    suggestion: (!encrypted).then(|| suggestion(&column.name, &classification)),
+                    classification,
+                    source,
+                    encrypted,
+                })
+            })
+        })
+        .collect();
+    columns.sort_by(|a, b| a.encrypted.cmp(&b.encrypted).then_with(|| (&a.table, &a.column).cmp(&(&b.table, &b.column))));
+
+    EncryptionReport {
+        connection_id: snapshot.connection_id,
+        snapshot_version: snapshot.version,
+        generated_at: Utc::now(),
+        plaintext_count: columns.iter().filter(|c| !c.encrypted).count(),
+        columns,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_plaintext_columns_are_flagged() {
+        assert_eq!(infer_level("customer_ssn"), Some(PiiLevel::Restricted));
+        assert_eq!(infer_level("smtp_password"), Some(PiiLevel::Secret));
+        assert_eq!(infer_level("password_hash"), None);
+        assert_eq!(infer_level("dobby"), None);
+
+        let v = check_column("public.users.ssn", "ssn", "text", None, &[]).unwrap();
+        assert_eq!(v.severity, Severity::Warning);
+        assert!(check_column("public.users.ssn", "ssn", "bytea", None, &[]).is_none());
+        assert!(check_column("public.users.ssn", "ssn", "text", None, &["encrypted".to_string()]).is_none());
+
+        // An explicit tag wins over the name
+        assert!(check_column("public.users.ssn", "ssn", "text", Some(&PiiLevel::Internal), &[]).is_none());
+        let v = check_column("public.users.notes", "notes", "text", Some(&PiiLevel::Secret), &[]).unwrap();
+        assert_eq!(v.severity, Severity::Error);
+    }
+}
@@ -0,0 +1,145 @@
+//! Diff ignore rules
+//!
+//! Some differences are noise: partition children created by a scheduler,
+//! `pg_repack` scratch tables, a schema owned by an extension. A connection
+//! can list patterns for object paths (`schema.table.column`) whose changes
+//! are dropped from diffs and drift checks. Patterns are globs (`*` and `?`)
+//! unless marked as regular expressions. Ignoring a table also ignores its
+//! columns, since a path matches when any of its leading segments do.
+//!
+//! The rules also carry the connection's column order mode: in strict mode
+//! diffs report tables whose columns were reordered, in semantic mode (the
+//! default) they do not.
+
+use crate::error::AppError;
+use crate::introspection::ColumnOrder;
+use crate::snapshot::diff::SchemaDiff;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// One ignore pattern on object paths
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnorePattern {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression instead of a glob
+    #[serde(default)]
+    pub regex: bool,
+    /// Why the difference is noise, for whoever reads the config next
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Compiled ignore patterns
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    patterns: Vec<Regex>,
+    column_order: ColumnOrder,
+}
+
+impl IgnoreRules {
+    /// Compile patterns, rejecting empty or invalid ones
+    pub fn compile(patterns: &[IgnorePattern]) -> Result<Self, AppError> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                let pattern = p.pattern.trim();
+                if pattern.is_empty() {
+                    return Err(AppError::Validation("Ignore patterns cannot be empty".to_string()));
+                }
+                let source = if p.regex { format!("^(?:{})$", pattern) } else { glob_to_regex(pattern) };
+                Regex::new(&source)
+                    .map_err(|e| AppError::Validation(format!("Invalid ignore pattern '{}': {}", pattern, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns, column_order: ColumnOrder::default() })
+    }
+
+    pub fn with_column_order(mut self, column_order: ColumnOrder) -> Self {
+        self.column_order = column_order;
+        self
+    }
+
+    pub fn column_order(&self) -> ColumnOrder {
+        self.column_order
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether `path` or one of its parents matches a pattern
+    pub fn ignores(&self, path: &str) -> bool {
+        let mut prefix_end = 0;
+        for segment in path.split('.') {
+            prefix_end += segment.len();
+            let prefix = &path[..prefix_end];
+            if self.patterns.iter().any(|p| p.is_match(prefix)) {
+                return true;
+            }
+            prefix_end += 1;
+        }
+        false
+    }
+
+    /// Drop ignored changes from a diff and recompute its totals
+    pub fn apply(&self, diff: SchemaDiff) -> SchemaDiff {
+        if self.is_empty() {
+            return diff;
+        }
+        let before = diff.changes.len();
+        let changes: Vec<_> = diff.changes.into_iter().filter(|c| !self.ignores(&c.object_path)).collect();
+        let ignored = before - changes.len();
+        let metadata = diff.metadata_changes.into_iter().filter(|c| !self.ignores(&c.object_path)).collect();
+        SchemaDiff {
+            ignored_changes: diff.ignored_changes + ignored,
+            ..SchemaDiff::from_changes(diff.from_version, diff.to_version, diff.from_checksum, diff.to_checksum, changes)
+                .with_metadata(metadata)
+        }
+    }
+}
+
+/// Translate a glob into an anchored regular expression
+pub(crate) fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str, regex: bool) -> IgnorePattern {
+        IgnorePattern { pattern: pattern.to_string(), regex, reason: None }
+    }
+
+    #[test]
+    fn test_globs_and_regexes_match_paths_and_children() {
+        let rules = IgnoreRules::compile(&[
+            pattern("public.events_p2024*", false),
+            pattern("repack", false),
+            pattern(r"public\.tmp_\d+", true),
+        ])
+        .unwrap();
+
+        assert!(rules.ignores("public.events_p2024_01"));
+        assert!(rules.ignores("public.events_p2024_01.payload"));
+        assert!(rules.ignores("repack.log_16384"));
+        assert!(rules.ignores("public.tmp_42"));
+        assert!(!rules.ignores("public.events"));
+        assert!(!rules.ignores("public.tmp_x"));
+        // Globs are anchored, so other partitions stay visible
+        assert!(!rules.ignores("public.events_p2023_12"));
+
+        assert!(matches!(IgnoreRules::compile(&[pattern("(", true)]), Err(AppError::Validation(_))));
+        assert!(matches!(IgnoreRules::compile(&[pattern(" ", false)]), Err(AppError::Validation(_))));
+    }
+}
@@ -0,0 +1,281 @@
+//! Schema Linting
+//!
+//! Project-level conventions checked against proposals that create or rename
+//! objects: naming style, reserved words, table plurality, required audit
+//! columns, and primary keys.
+
+use crate::i18n::params;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::rules::{RuleViolation, Severity};
+use serde::{Deserialize, Serialize};
+
+/// Identifier style enforced for new names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingConvention {
+    SnakeCase,
+    Any,
+}
+
+/// Whether table names should be singular or plural
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Plurality {
+    Any,
+    Singular,
+    Plural,
+}
+
+/// Lint settings for a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintConfig {
+    pub naming: NamingConvention,
+    pub forbid_reserved_words: bool,
+    pub table_plurality: Plurality,
+    /// Columns every new table must define
+    pub required_columns: Vec<String>,
+    pub require_primary_key: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            naming: NamingConvention::SnakeCase,
+            forbid_reserved_words: true,
+            table_plurality: Plurality::Any,
+            required_columns: vec!["created_at".to_string(), "updated_at".to_string()],
+            require_primary_key: true,
+        }
+    }
+}
+
+/// PostgreSQL reserved keywords that cannot be used unquoted as identifiers
+const RESERVED_WORDS: &[&str] = &[
+    "all", "analyse", "analyze", "and", "any", "array", "as", "asc", "asymmetric",
+    "authorization", "binary", "both", "case", "cast", "check", "collate", "collation",
+    "column", "concurrently", "constraint", "create", "cross", "current_catalog",
+    "current_date", "current_role", "current_schema", "current_time", "current_timestamp",
+    "current_user", "default", "deferrable", "desc", "distinct", "do", "else", "end",
+    "except", "false", "fetch", "for", "foreign", "freeze", "from", "full", "grant",
+    "group", "having", "ilike", "in", "initially", "inner", "intersect", "into", "is",
+    "isnull", "join", "lateral", "leading", "left", "like", "limit", "localtime",
+    "localtimestamp", "natural", "not", "notnull", "null", "offset", "on", "only", "or",
+    "order", "outer", "overlaps", "placing", "primary", "references", "returning", "right",
+    "select", "session_user", "similar", "some", "symmetric", "table", "tablesample",
+    "then", "to", "trailing", "true", "union", "unique", "user", "using", "variadic",
+    "verbose", "when", "where", "window", "with",
+];
+
+/// Kind of object a new name belongs to
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NameKind {
+    Schema,
+    Table,
+    Column,
+}
+
+impl NameKind {
+    fn label(self) -> &'static str {
+        match self {
+            NameKind::Schema => "Schema",
+            NameKind::Table => "Table",
+            NameKind::Column => "Column",
+        }
+    }
+}
+
+/// Check every change in a proposal against the project's lint settings
+pub fn lint_proposal(proposal: &SchemaProposal, config: &LintConfig) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+
+    for change in &proposal.changes {
+        match change {
+            SchemaChange::CreateSchema { schema_name, .. } => {
+                check_name(config, NameKind::Schema, schema_name, schema_name, &mut violations);
+            }
+            SchemaChange::RenameSchema { new_name, .. } => {
+                check_name(config, NameKind::Schema, new_name, new_name, &mut violations);
+            }
+            SchemaChange::CreateTable { table_name, columns, .. } => {
+                check_name(config, NameKind::Table, table_name, table_name, &mut violations);
+                for column in columns {
+                    let path = format!("{}.{}", table_name, column.name);
+                    check_name(config, NameKind::Column, &column.name, &path, &mut violations);
+                }
+
+                let missing: Vec<&str> = config
+                    .required_columns
+                    .iter()
+                    .filter(|required| !columns.iter().any(|c| c.name.eq_ignore_ascii_case(required)))
+                    .map(String::as_str)
+                    .collect();
+                if !missing.is_empty() {
+                    violations.push(RuleViolation {
+                        rule_id: "R016".to_string(),
+                        rule_name: "Missing Audit Columns".to_string(),
+                        severity: Severity::Warning,
+                        message: format!("Table {} is missing required columns: {}", table_name, missing.join(", ")),
+                        affected_object: table_name.clone(),
+                        suggestion: Some(format!("Add {} TIMESTAMPTZ NOT NULL DEFAULT now()", missing.join(", "))),
+                        params: params(&[("object", table_name), ("columns", &missing.join(", "))]),
+                    });
+                }
+
+                if config.require_primary_key && !columns.iter().any(|c| c.is_primary_key) {
+                    violations.push(RuleViolation {
+                        rule_id: "R017".to_string(),
+                        rule_name: "Missing Primary Key".to_string(),
+                        severity: Severity::Error,
+                        message: format!("Table {} has no primary key", table_name),
+                        affected_object: table_name.clone(),
+                        suggestion: Some("Add an id column marked as the primary key".to_string()),
+                        params: params(&[("object", table_name)]),
+                    });
+                }
+            }
+            SchemaChange::RenameTable { new_name, .. } => {
+                check_name(config, NameKind::Table, new_name, new_name, &mut violations);
+            }
+            SchemaChange::AddColumn { table_name, column } => {
+                let path = format!("{}.{}", table_name, column.name);
+                check_name(config, NameKind::Column, &column.name, &path, &mut violations);
+            }
+            SchemaChange::RenameColumn { table_name, new_name, .. } => {
+                let path = format!("{}.{}", table_name, new_name);
+                check_name(config, NameKind::Column, new_name, &path, &mut violations);
+            }
+            _ => {}
+        }
+    }
+
+    violations
+}
+
+fn check_name(
+    config: &LintConfig,
+    kind: NameKind,
+    name: &str,
+    path: &str,
+    violations: &mut Vec<RuleViolation>,
+) {
+    // Table names may be schema-qualified; only the last part is new
+    let name = name.rsplit('.').next().unwrap_or(name);
+
+    if config.naming == NamingConvention::SnakeCase && !is_snake_case(name) {
+        violations.push(RuleViolation {
+            rule_id: "R013".to_string(),
+            rule_name: "Naming Convention".to_string(),
+            severity: Severity::Warning,
+            message: format!("{} name '{}' is not snake_case", kind.label(), name),
+            affected_object: path.to_string(),
+            suggestion: Some(format!("Rename to '{}'", to_snake_case(name))),
+            params: params(&[("object", path), ("kind", kind.label()), ("name", name), ("suggested", &to_snake_case(name))]),
+        });
+    }
+
+    if config.forbid_reserved_words && RESERVED_WORDS.contains(&name.to_lowercase().as_str()) {
+        violations.push(RuleViolation {
+            rule_id: "R014".to_string(),
+            rule_name: "Reserved Word Identifier".to_string(),
+            severity: Severity::Error,
+            message: format!("{} name '{}' is a reserved SQL keyword", kind.label(), name),
+            affected_object: path.to_string(),
+            suggestion: Some("Choose a name that does not require quoting".to_string()),
+            params: params(&[("object", path), ("kind", kind.label()), ("name", name)]),
+        });
+    }
+
+    if kind == NameKind::Table {
+        let wrong = match config.table_plurality {
+            Plurality::Singular if is_plural(name) => Some("singular"),
+            Plurality::Plural if !is_plural(name) => Some("plural"),
+            _ => None,
+        };
+        if let Some(expected) = wrong {
+            violations.push(RuleViolation {
+                rule_id: "R015".to_string(),
+                rule_name: "Table Name Plurality".to_string(),
+                severity: Severity::Warning,
+                message: format!("Table name '{}' should be {}", name, expected),
+                affected_object: path.to_string(),
+                suggestion: None,
+                params: params(&[("object", path), ("name", name), ("expected", expected)]),
+            });
+        }
+    }
+}
+
+fn is_snake_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !name.ends_with('_')
+        && !name.contains("__")
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else if !out.ends_with('_') {
+            out.push('_');
+            prev_lower = false;
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+/// English plural heuristic on the last word of a name
+fn is_plural(name: &str) -> bool {
+    let word = name.rsplit('_').next().unwrap_or(name).to_lowercase();
+    word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us") && !word.ends_with("is")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::types::ColumnDef;
+    use uuid::Uuid;
+
+    fn column(name: &str, is_primary_key: bool) -> ColumnDef {
+        ColumnDef {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            nullable: false,
+            default_value: None,
+            is_primary_key,
+            collation: None,
+        }
+    }
+
+    #[test]
+    fn test_lint_create_table() {
+        let mut proposal = SchemaProposal::new(Uuid::new_v4(), "t".into(), "d".into(), "me".into());
+        proposal.changes.push(SchemaChange::CreateTable {
+            table_name: "public.UserAccounts".to_string(),
+            columns: vec![column("order", false), column("created_at", false)],
+            partition_by: None,
+        });
+
+        let config = LintConfig {
+            table_plurality: Plurality::Singular,
+            ..LintConfig::default()
+        };
+        let violations = lint_proposal(&proposal, &config);
+        let ids: Vec<&str> = violations.iter().map(|v| v.rule_id.as_str()).collect();
+
+        assert_eq!(ids, vec!["R013", "R015", "R014", "R016", "R017"]);
+        assert_eq!(violations[0].suggestion.as_deref(), Some("Rename to 'user_accounts'"));
+        assert!(violations[3].message.ends_with("updated_at"));
+    }
+}
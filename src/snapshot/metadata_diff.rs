@@ -0,0 +1,328 @@
+//! Governance metadata diff
+//!
+//! The physical diff compares what the database enforces. This compares what
+//! SchemaFlow knows about tables and columns (descriptions, tags, PII
+//! classification, retention and data dictionary custom fields) for objects
+//! present in both snapshots, and reports it as a separate section of the
+//! diff. Its risk is about governance rather than breakage: loosening a PII
+//! classification or shortening retention is what reviewers need to see.
+//! Table owners are physical and stay in the main diff.
+//!
+//! Live introspections carry only comment-based metadata, so compare
+//! snapshots annotated with `dictionary::annotate`.
+
+use crate::introspection::{Column, PiiLevel, SchemaSnapshot, Table};
+use crate::snapshot::diff::{ChangeType, ObjectType, RiskLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Governance attribute that changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    Description,
+    Tags,
+    PiiClassification,
+    Retention,
+    CustomField,
+}
+
+/// One governance metadata change on a table or column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataChange {
+    pub change_type: ChangeType,
+    /// `Table` or `Column`
+    pub object_type: ObjectType,
+    /// `schema.table` or `schema.table.column`
+    pub object_path: String,
+    pub field: MetadataField,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<serde_json::Value>,
+    pub risk_level: RiskLevel,
+}
+
+/// Governance metadata changes between two snapshots, tables first, in path order
+pub fn diff(from: &SchemaSnapshot, to: &SchemaSnapshot) -> Vec<MetadataChange> {
+    let (before, after) = (tables_by_path(from), tables_by_path(to));
+
+    let mut changes = Vec::new();
+    for (path, to_table) in &after {
+        let Some(from_table) = before.get(path) else { continue };
+        let (from_gov, to_gov) = (&from_table.governance, &to_table.governance);
+        let target = Target { object_type: ObjectType::Table, path };
+
+        target.description(from_gov.description.as_deref(), to_gov.description.as_deref(), &mut changes);
+        target.tags(&from_gov.tags, &to_gov.tags, &mut changes);
+        target.retention(from_gov.retention_days, to_gov.retention_days, &mut changes);
+        target.custom_fields(&from_gov.custom_fields, &to_gov.custom_fields, &mut changes);
+
+        let columns: HashMap<&str, &Column> = from_table.columns.iter().map(|c| (c.name.as_str(), c)).collect();
+        for to_col in &to_table.columns {
+            let Some(from_col) = columns.get(to_col.name.as_str()) else { continue };
+            let path = format!("{}.{}", path, to_col.name);
+            let target = Target { object_type: ObjectType::Column, path: &path };
+
+            target.classification(from_col.pii_classification.as_ref(), to_col.pii_classification.as_ref(), &mut changes);
+            target.description(from_col.description.as_deref(), to_col.description.as_deref(), &mut changes);
+            target.tags(&from_col.tags, &to_col.tags, &mut changes);
+            target.custom_fields(&from_col.custom_fields, &to_col.custom_fields, &mut changes);
+        }
+    }
+    changes
+}
+
+fn tables_by_path(snapshot: &SchemaSnapshot) -> BTreeMap<String, &Table> {
+    snapshot.tables.iter().map(|t| (format!("{}.{}", t.schema, t.name), t)).collect()
+}
+
+fn present(text: Option<&str>) -> Option<&str> {
+    text.map(str::trim).filter(|t| !t.is_empty())
+}
+
+/// Highest risk among the changes, if there are any
+pub fn overall_risk(changes: &[MetadataChange]) -> Option<RiskLevel> {
+    changes.iter().map(|c| c.risk_level).max_by_key(|r| rank(*r))
+}
+
+fn rank(risk: RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Safe => 0,
+        RiskLevel::Low => 1,
+        RiskLevel::Medium => 2,
+        RiskLevel::High => 3,
+        RiskLevel::Critical => 4,
+    }
+}
+
+fn change_type<T>(from: Option<T>, to: Option<T>) -> ChangeType {
+    match (from, to) {
+        (None, Some(_)) => ChangeType::Added,
+        (Some(_), None) => ChangeType::Removed,
+        _ => ChangeType::Modified,
+    }
+}
+
+struct Target<'a> {
+    object_type: ObjectType,
+    path: &'a str,
+}
+
+impl Target<'_> {
+    fn label(&self) -> &'static str {
+        match self.object_type {
+            ObjectType::Column => "Column",
+            _ => "Table",
+        }
+    }
+
+    fn change(
+        &self,
+        change_type: ChangeType,
+        field: MetadataField,
+        description: String,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+        risk_level: RiskLevel,
+    ) -> MetadataChange {
+        MetadataChange {
+            change_type,
+            object_type: self.object_type,
+            object_path: self.path.to_string(),
+            field,
+            description,
+            before,
+            after,
+            risk_level,
+        }
+    }
+
+    /// Any loss of classification is high risk, and dropping a restricted or
+    /// secret column to unclassified is critical
+    fn classification(&self, from: Option<&PiiLevel>, to: Option<&PiiLevel>, changes: &mut Vec<MetadataChange>) {
+        let level = |l: Option<&PiiLevel>| l.cloned().unwrap_or(PiiLevel::None);
+        let (before, after) = (level(from), level(to));
+        if before == after {
+            return;
+        }
+        let risk = if after > before {
+            RiskLevel::Low
+        } else if before >= PiiLevel::Restricted && after == PiiLevel::None {
+            RiskLevel::Critical
+        } else {
+            RiskLevel::High
+        };
+        let verb = if after > before { "raised" } else { "downgraded" };
+        changes.push(self.change(
+            change_type(from.filter(|l| **l != PiiLevel::None), to.filter(|l| **l != PiiLevel::None)),
+            MetadataField::PiiClassification,
+            format!("{} {} PII classification {}: {:?} → {:?}", self.label(), self.path, verb, before, after),
+            Some(json!(before)),
+            Some(json!(after)),
+            risk,
+        ));
+    }
+
+    fn description(&self, from: Option<&str>, to: Option<&str>, changes: &mut Vec<MetadataChange>) {
+        let (before, after) = (present(from), present(to));
+        if before == after {
+            return;
+        }
+        let (verb, risk) = match after {
+            Some(_) => ("updated", RiskLevel::Safe),
+            None => ("removed", RiskLevel::Low),
+        };
+        changes.push(self.change(
+            change_type(before, after),
+            MetadataField::Description,
+            format!("{} {} description {}", self.label(), self.path, verb),
+            before.map(|d| json!(d)),
+            after.map(|d| json!(d)),
+            risk,
+        ));
+    }
+
+    /// `pii:` tags feed the classification and are reported there
+    fn tags(&self, from: &[String], to: &[String], changes: &mut Vec<MetadataChange>) {
+        let tags = |t: &[String]| -> BTreeSet<String> {
+            t.iter().filter(|t| PiiLevel::from_tag(t).is_none()).cloned().collect()
+        };
+        let (before, after) = (tags(from), tags(to));
+        let removed: Vec<&String> = before.difference(&after).collect();
+        let added: Vec<&String> = after.difference(&before).collect();
+        if removed.is_empty() && added.is_empty() {
+            return;
+        }
+        let mut parts = Vec::new();
+        if !added.is_empty() {
+            parts.push(format!("added {}", added.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", ")));
+        }
+        if !removed.is_empty() {
+            parts.push(format!("removed {}", removed.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", ")));
+        }
+        changes.push(self.change(
+            ChangeType::Modified,
+            MetadataField::Tags,
+            format!("{} {} tags: {}", self.label(), self.path, parts.join("; ")),
+            Some(json!(before)),
+            Some(json!(after)),
+            if removed.is_empty() { RiskLevel::Safe } else { RiskLevel::Low },
+        ));
+    }
+
+    /// Shorter or removed retention means data is deleted sooner or no
+    /// longer governed
+    fn retention(&self, from: Option<i32>, to: Option<i32>, changes: &mut Vec<MetadataChange>) {
+        if from == to {
+            return;
+        }
+        let risk = match (from, to) {
+            (Some(before), Some(after)) if after < before => RiskLevel::Medium,
+            (Some(_), None) => RiskLevel::Medium,
+            _ => RiskLevel::Safe,
+        };
+        let days = |d: Option<i32>| d.map_or_else(|| "none".to_string(), |d| format!("{} days", d));
+        changes.push(self.change(
+            change_type(from, to),
+            MetadataField::Retention,
+            format!("{} {} retention: {} → {}", self.label(), self.path, days(from), days(to)),
+            from.map(|d| json!(d)),
+            to.map(|d| json!(d)),
+            risk,
+        ));
+    }
+
+    fn custom_fields(
+        &self,
+        from: &BTreeMap<String, String>,
+        to: &BTreeMap<String, String>,
+        changes: &mut Vec<MetadataChange>,
+    ) {
+        let keys: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+        for key in keys {
+            let (before, after) = (from.get(key), to.get(key));
+            if before == after {
+                continue;
+            }
+            changes.push(self.change(
+                change_type(before, after),
+                MetadataField::CustomField,
+                format!("{} {} field {}: {:?} → {:?}", self.label(), self.path, key, before, after),
+                before.map(|v| json!({ key.as_str(): v })),
+                after.map(|v| json!({ key.as_str(): v })),
+                if after.is_none() { RiskLevel::Low } else { RiskLevel::Safe },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::TableGovernance;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn snapshot(level: Option<PiiLevel>, retention_days: Option<i32>, tags: &[&str]) -> SchemaSnapshot {
+        SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::nil(),
+            version: 1,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: vec![Table {
+                name: "users".to_string(),
+                schema: "public".to_string(),
+                columns: vec![Column {
+                    name: "ssn".to_string(),
+                    data_type: "text".to_string(),
+                    nullable: true,
+                    default_value: None,
+                    is_primary_key: false,
+                    is_unique: false,
+                    ordinal_position: 1,
+                    collation: None,
+                    pii_classification: level,
+                    description: None,
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    custom_fields: BTreeMap::new(),
+                }],
+                primary_key: None,
+                position: None,
+                color: None,
+                collapsed: false,
+                governance: TableGovernance { retention_days, ..Default::default() },
+            }],
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_pii_downgrade_and_shorter_retention_are_flagged() {
+        let from = snapshot(Some(PiiLevel::Restricted), Some(365), &["pii:restricted", "finance"]);
+        let to = snapshot(None, Some(30), &[]);
+        let changes = diff(&from, &to);
+
+        let field = |f: MetadataField| changes.iter().find(|c| c.field == f).unwrap();
+        let pii = field(MetadataField::PiiClassification);
+        assert_eq!((pii.object_path.as_str(), pii.risk_level), ("public.users.ssn", RiskLevel::Critical));
+        assert_eq!(pii.change_type, ChangeType::Removed);
+        assert_eq!(field(MetadataField::Retention).risk_level, RiskLevel::Medium);
+        // The pii: tag is reported as the classification, not as a tag
+        assert_eq!(field(MetadataField::Tags).description, "Column public.users.ssn tags: removed finance");
+        assert_eq!(overall_risk(&changes), Some(RiskLevel::Critical));
+
+        assert_eq!(diff(&to, &from).iter().find(|c| c.field == MetadataField::PiiClassification).unwrap().risk_level, RiskLevel::Low);
+        assert!(diff(&from, &from).is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+//! Diffs as migration SQL
+//!
+//! Turns a snapshot diff into the proposal changes that would reproduce it and
+//! runs them through the proposal SQL generator, so a drift diff can become a
+//! corrective migration file without retyping it. The reverse script inverts
+//! those changes against the `from` snapshot, the same way reverts are built.
+//! Differences with no proposal change (owners, grants, primary keys, check
+//! constraints, database settings) are listed as manual steps.
+
+use crate::introspection::{
+    Column, CompositeType, Domain, Extension, ForeignKey, Index, Namespace, RlsPolicy, SchemaSnapshot, Table,
+    View,
+};
+use crate::pipeline::orchestrator::Orchestrator;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::revert::{create_policy, create_table, column_def, invert_changes};
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::diff::{ChangeType, ObjectType, SchemaDiff, SchemaDiffItem};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Output form of a snapshot diff
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFormat {
+    #[default]
+    Json,
+    /// Also emit migration and reverse SQL
+    Sql,
+}
+
+/// Migration reproducing a diff, and its reverse
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffMigration {
+    /// Takes the `from` schema to the `to` schema
+    pub up_sql: String,
+    /// Takes the `to` schema back to the `from` schema
+    pub down_sql: String,
+    pub changes: Vec<SchemaChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub manual_steps: Vec<String>,
+}
+
+/// Build migration SQL for a diff between `from` and a later snapshot
+pub fn from_diff(diff: &SchemaDiff, from: &SchemaSnapshot) -> DiffMigration {
+    let mut changes = Vec::new();
+    let mut manual_steps = Vec::new();
+    for item in &diff.changes {
+        match to_changes(item) {
+            Ok(converted) => changes.extend(converted),
+            Err(step) => manual_steps.push(step),
+        }
+    }
+    // The diff groups changes by object type; PostgreSQL needs dependencies first
+    changes.sort_by_key(phase);
+
+    let inversion = invert_changes(&changes, Some(from));
+    manual_steps.extend(inversion.manual_steps.iter().map(|step| format!("Reverse: {}", step)));
+
+    let orchestrator = Orchestrator::new();
+    let title = format!("Snapshot v{} to v{}", diff.from_version, diff.to_version);
+    let mut forward = SchemaProposal::new(from.connection_id, title.clone(), String::new(), String::new());
+    forward.changes = changes;
+    let mut reverse = SchemaProposal::new(from.connection_id, title, String::new(), String::new());
+    reverse.changes = inversion.changes;
+
+    let with_manual_steps = |sql: String| {
+        if manual_steps.is_empty() {
+            return sql;
+        }
+        let notes: Vec<String> = manual_steps.iter().map(|step| format!("-- Manual step: {}", step)).collect();
+        format!("{}\n\n{}", sql, notes.join("\n"))
+    };
+
+    DiffMigration {
+        up_sql: with_manual_steps(orchestrator.generate_migration(&forward).up_sql),
+        down_sql: orchestrator.generate_migration(&reverse).up_sql,
+        changes: forward.changes,
+        manual_steps,
+    }
+}
+
+/// Proposal changes for one diff item, or the manual step it needs
+fn to_changes(item: &SchemaDiffItem) -> Result<Vec<SchemaChange>, String> {
+    let manual = || Err(item.description.clone());
+    let change = match (item.object_type, item.change_type) {
+        (ObjectType::Schema, ChangeType::Added) => {
+            let schema: Namespace = state(&item.after, item)?;
+            SchemaChange::CreateSchema { schema_name: schema.name, owner: Some(schema.owner) }
+        }
+        (ObjectType::Schema, ChangeType::Removed) => SchemaChange::DropSchema {
+            schema_name: item.object_path.clone(),
+            cascade: false,
+        },
+        (ObjectType::Extension, ChangeType::Added) => {
+            let extension: Extension = state(&item.after, item)?;
+            SchemaChange::CreateExtension {
+                name: extension.name,
+                schema: Some(extension.schema),
+                version: Some(extension.version),
+            }
+        }
+        (ObjectType::Extension, ChangeType::Removed) => SchemaChange::DropExtension {
+            name: item.object_path.clone(),
+            cascade: false,
+        },
+        (ObjectType::Extension, ChangeType::Modified) => {
+            let extension: Extension = state(&item.after, item)?;
+            SchemaChange::AlterExtensionVersion { name: extension.name, version: extension.version }
+        }
+        (ObjectType::Domain, ChangeType::Added) => {
+            let domain: Domain = state(&item.after, item)?;
+            SchemaChange::CreateDomain {
+                domain_name: item.object_path.clone(),
+                base_type: domain.base_type,
+                not_null: domain.not_null,
+                default_value: domain.default_value,
+                checks: domain.checks,
+            }
+        }
+        (ObjectType::Domain, ChangeType::Removed) => SchemaChange::DropDomain {
+            domain_name: item.object_path.clone(),
+            cascade: false,
+        },
+        (ObjectType::Domain, ChangeType::Modified) => {
+            let (before, after): (Domain, Domain) = (state(&item.before, item)?, state(&item.after, item)?);
+            return alter_domain(&item.object_path, &before, &after);
+        }
+        (ObjectType::CompositeType, ChangeType::Added) => SchemaChange::CreateCompositeType {
+            type_name: item.object_path.clone(),
+            attributes: state::<CompositeType>(&item.after, item)?.attributes,
+        },
+        (ObjectType::CompositeType, ChangeType::Removed) => SchemaChange::DropCompositeType {
+            type_name: item.object_path.clone(),
+            cascade: false,
+        },
+        (ObjectType::CompositeType, ChangeType::Modified) => {
+            let (before, after): (CompositeType, CompositeType) = (state(&item.before, item)?, state(&item.after, item)?);
+            let previous = |name: &str| before.attributes.iter().find(|a| a.name == name);
+            SchemaChange::AlterCompositeType {
+                type_name: item.object_path.clone(),
+                add_attributes: after.attributes.iter().filter(|a| previous(&a.name).is_none()).cloned().collect(),
+                drop_attributes: before
+                    .attributes
+                    .iter()
+                    .filter(|a| !after.attributes.iter().any(|n| n.name == a.name))
+                    .map(|a| a.name.clone())
+                    .collect(),
+                alter_attributes: after
+                    .attributes
+                    .iter()
+                    .filter(|a| previous(&a.name).is_some_and(|p| p.data_type != a.data_type))
+                    .cloned()
+                    .collect(),
+            }
+        }
+        (ObjectType::View, ChangeType::Added) => SchemaChange::CreateView {
+            view_name: item.object_path.clone(),
+            query: state::<View>(&item.after, item)?.definition,
+        },
+        (ObjectType::View, ChangeType::Removed) => SchemaChange::DropView {
+            view_name: item.object_path.clone(),
+            cascade: false,
+        },
+        (ObjectType::View, ChangeType::Modified) => SchemaChange::AlterView {
+            view_name: item.object_path.clone(),
+            query: state::<View>(&item.after, item)?.definition,
+        },
+        (ObjectType::MaterializedView, ChangeType::Added) => SchemaChange::CreateMaterializedView {
+            view_name: item.object_path.clone(),
+            query: state::<View>(&item.after, item)?.definition,
+            with_no_data: false,
+        },
+        (ObjectType::MaterializedView, ChangeType::Removed) => SchemaChange::DropMaterializedView {
+            view_name: item.object_path.clone(),
+            cascade: false,
+        },
+        // Materialized views cannot be redefined in place
+        (ObjectType::MaterializedView, ChangeType::Modified) => {
+            let after: View = state(&item.after, item)?;
+            return Ok(vec![
+                SchemaChange::DropMaterializedView { view_name: item.object_path.clone(), cascade: false },
+                SchemaChange::CreateMaterializedView {
+                    view_name: item.object_path.clone(),
+                    query: after.definition,
+                    with_no_data: false,
+                },
+            ]);
+        }
+        (ObjectType::Table, ChangeType::Added) => create_table(&item.object_path, &state::<Table>(&item.after, item)?),
+        (ObjectType::Table, ChangeType::Removed) => SchemaChange::DropTable { table_name: item.object_path.clone() },
+        // Only the comment differs, which no migration needs
+        (ObjectType::Table, ChangeType::Modified) => return Ok(Vec::new()),
+        (ObjectType::Column, ChangeType::Added) => SchemaChange::AddColumn {
+            table_name: parent(&item.object_path),
+            column: column_def(&state::<Column>(&item.after, item)?),
+        },
+        (ObjectType::Column, ChangeType::Removed) => SchemaChange::DropColumn {
+            table_name: parent(&item.object_path),
+            column_name: state::<Column>(&item.before, item)?.name,
+        },
+        (ObjectType::Column, ChangeType::Renamed) => {
+            let (before, after): (Column, Column) = (state(&item.before, item)?, state(&item.after, item)?);
+            SchemaChange::RenameColumn {
+                table_name: parent(&item.object_path),
+                old_name: before.name,
+                new_name: after.name,
+            }
+        }
+        (ObjectType::Column, ChangeType::Modified) => {
+            let (before, after): (Column, Column) = (state(&item.before, item)?, state(&item.after, item)?);
+            return alter_column(&parent(&item.object_path), &before, &after);
+        }
+        (ObjectType::Index, ChangeType::Added) => {
+            let index: Index = state(&item.after, item)?;
+            // Primary key indexes come with the table or the constraint
+            if index.is_primary {
+                return Ok(Vec::new());
+            }
+            SchemaChange::AddIndex {
+                table_name: format!("{}.{}", index.schema, index.table),
+                index_name: index.name,
+                columns: index.columns,
+                unique: index.is_unique,
+                concurrently: false,
+            }
+        }
+        (ObjectType::Index, ChangeType::Removed) => {
+            let index: Index = state(&item.before, item)?;
+            if index.is_primary {
+                return Ok(Vec::new());
+            }
+            SchemaChange::DropIndex { index_name: item.object_path.clone() }
+        }
+        (ObjectType::ForeignKey, ChangeType::Added) => add_foreign_key(state(&item.after, item)?),
+        (ObjectType::ForeignKey, ChangeType::Removed) => drop_foreign_key(&state(&item.before, item)?),
+        // Constraints cannot be altered in place
+        (ObjectType::ForeignKey, ChangeType::Modified) => {
+            let before: ForeignKey = state(&item.before, item)?;
+            return Ok(vec![drop_foreign_key(&before), add_foreign_key(state(&item.after, item)?)]);
+        }
+        (ObjectType::Policy, ChangeType::Added) => create_policy(&parent(&item.object_path), &state(&item.after, item)?),
+        (ObjectType::Policy, ChangeType::Removed) => SchemaChange::DropPolicy {
+            table_name: parent(&item.object_path),
+            policy_name: state::<RlsPolicy>(&item.before, item)?.name,
+        },
+        // The command and kind of a policy cannot be altered in place
+        (ObjectType::Policy, ChangeType::Modified) => {
+            let table_name = parent(&item.object_path);
+            let after: RlsPolicy = state(&item.after, item)?;
+            return Ok(vec![
+                SchemaChange::DropPolicy { table_name: table_name.clone(), policy_name: after.name.clone() },
+                create_policy(&table_name, &after),
+            ]);
+        }
+        (ObjectType::RowSecurity, ChangeType::Modified) => {
+            let after = item.after.as_ref().ok_or_else(|| item.description.clone())?;
+            let flag = |key: &str| after.get(key).and_then(|v| v.as_bool()).ok_or_else(|| item.description.clone());
+            SchemaChange::SetRowSecurity {
+                table_name: item.object_path.clone(),
+                enabled: flag("enabled")?,
+                forced: Some(flag("forced")?),
+            }
+        }
+        _ => return manual(),
+    };
+    Ok(vec![change])
+}
+
+fn alter_column(table_name: &str, before: &Column, after: &Column) -> Result<Vec<SchemaChange>, String> {
+    let column = format!("{}.{}", table_name, after.name);
+    if before.collation != after.collation {
+        return Err(format!("Change the collation of {} to {:?}", column, after.collation));
+    }
+    if before.default_value.is_some() && after.default_value.is_none() {
+        return Err(format!("Drop the default on {}", column));
+    }
+
+    let new_type = (before.data_type != after.data_type).then(|| after.data_type.clone());
+    let new_nullable = (before.nullable != after.nullable).then_some(after.nullable);
+    let new_default = after.default_value.clone().filter(|_| before.default_value != after.default_value);
+    // Comment-only changes need no SQL
+    if new_type.is_none() && new_nullable.is_none() && new_default.is_none() {
+        return Ok(Vec::new());
+    }
+
+    Ok(vec![SchemaChange::AlterColumn {
+        table_name: table_name.to_string(),
+        column_name: after.name.clone(),
+        new_type,
+        new_nullable,
+        new_default,
+        using: None,
+    }])
+}
+
+fn alter_domain(domain_name: &str, before: &Domain, after: &Domain) -> Result<Vec<SchemaChange>, String> {
+    // ALTER DOMAIN cannot change the base type or collation
+    if before.base_type != after.base_type || before.collation != after.collation {
+        return Err(format!("Recreate domain {} as {}", domain_name, after.base_type));
+    }
+    let mut drop_checks = Vec::new();
+    let mut add_checks = Vec::new();
+    for check in &before.checks {
+        match after.checks.iter().find(|c| c.name == check.name) {
+            Some(new) if new.expression == check.expression => {}
+            _ => drop_checks.push(check.name.clone()),
+        }
+    }
+    for check in &after.checks {
+        if !before.checks.contains(check) {
+            add_checks.push(check.clone());
+        }
+    }
+
+    Ok(vec![SchemaChange::AlterDomain {
+        domain_name: domain_name.to_string(),
+        set_default: after.default_value.clone().filter(|_| before.default_value != after.default_value),
+        drop_default: before.default_value.is_some() && after.default_value.is_none(),
+        not_null: (before.not_null != after.not_null).then_some(after.not_null),
+        add_checks,
+        drop_checks,
+    }])
+}
+
+fn add_foreign_key(fk: ForeignKey) -> SchemaChange {
+    SchemaChange::AddForeignKey {
+        table_name: format!("{}.{}", fk.source_schema, fk.source_table),
+        constraint_name: fk.constraint_name,
+        columns: fk.source_columns,
+        ref_table: format!("{}.{}", fk.referenced_schema, fk.referenced_table),
+        ref_columns: fk.referenced_columns,
+    }
+}
+
+fn drop_foreign_key(fk: &ForeignKey) -> SchemaChange {
+    SchemaChange::DropForeignKey {
+        table_name: format!("{}.{}", fk.source_schema, fk.source_table),
+        constraint_name: fk.constraint_name.clone(),
+    }
+}
+
+/// Statement order: new schemas, extensions and types first, then anything
+/// referencing what gets dropped, tables, and finally what references new tables
+/// and views
+fn phase(change: &SchemaChange) -> u8 {
+    match change {
+        SchemaChange::CreateSchema { .. }
+        | SchemaChange::CreateExtension { .. }
+        | SchemaChange::AlterExtensionVersion { .. }
+        | SchemaChange::CreateDomain { .. }
+        | SchemaChange::CreateCompositeType { .. } => 0,
+        SchemaChange::DropForeignKey { .. }
+        | SchemaChange::DropView { .. }
+        | SchemaChange::DropMaterializedView { .. } => 1,
+        SchemaChange::DropIndex { .. } => 2,
+        SchemaChange::DropTable { .. } | SchemaChange::DropDomain { .. } | SchemaChange::DropCompositeType { .. } => 4,
+        SchemaChange::DropSchema { .. } => 5,
+        SchemaChange::DropExtension { .. } => 6,
+        SchemaChange::AddIndex { .. } => 7,
+        SchemaChange::AddForeignKey { .. } => 8,
+        SchemaChange::CreateView { .. }
+        | SchemaChange::AlterView { .. }
+        | SchemaChange::CreateMaterializedView { .. }
+        | SchemaChange::RefreshMaterializedView { .. } => 9,
+        _ => 3,
+    }
+}
+
+/// The table path of a column path
+fn parent(path: &str) -> String {
+    path.rsplit_once('.').map(|(table, _)| table).unwrap_or(path).to_string()
+}
+
+/// Read an item's before or after state
+fn state<T: DeserializeOwned>(value: &Option<serde_json::Value>, item: &SchemaDiffItem) -> Result<T, String> {
+    value
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .ok_or_else(|| item.description.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::DiffEngine;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn column(name: &str, data_type: &str, ordinal_position: i32) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position,
+            collation: None,
+            pii_classification: None,
+            description: None,
+            tags: vec![],
+            custom_fields: Default::default(),
+        }
+    }
+
+    fn snapshot(tables: Vec<(&str, Vec<Column>)>) -> SchemaSnapshot {
+        SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 1,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: vec![],
+            extensions: vec![],
+            types: None,
+            views: None,
+            tables: tables
+                .into_iter()
+                .map(|(name, columns)| Table {
+                    name: name.to_string(),
+                    schema: "public".to_string(),
+                    columns,
+                    primary_key: None,
+                    position: None,
+                    color: None,
+                    collapsed: false,
+                    governance: Default::default(),
+                })
+                .collect(),
+            foreign_keys: vec![],
+            indexes: vec![],
+            checksum: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_drift_diff_becomes_migration_and_reverse() {
+        let from = snapshot(vec![
+            ("users", vec![column("id", "integer", 1), column("age", "integer", 2)]),
+            ("legacy", vec![column("id", "integer", 1)]),
+        ]);
+        let to = snapshot(vec![(
+            "users",
+            vec![column("id", "integer", 1), column("age", "bigint", 2), column("email", "text", 3)],
+        )]);
+
+        let migration = from_diff(&DiffEngine::diff(&from, &to), &from);
+
+        assert!(migration.up_sql.contains("ALTER TABLE public.users ADD COLUMN email text;"));
+        assert!(migration.up_sql.contains("ALTER TABLE public.users ALTER COLUMN age TYPE bigint;"));
+        assert!(migration.up_sql.contains("DROP TABLE public.legacy;"));
+        // The dropped table is recreated from the earlier snapshot
+        assert!(migration.down_sql.contains("CREATE TABLE public.legacy"));
+        assert!(migration.down_sql.contains("ALTER TABLE public.users DROP COLUMN email;"));
+        assert!(migration.down_sql.contains("ALTER TABLE public.users ALTER COLUMN age TYPE integer;"));
+        assert!(migration.manual_steps.is_empty());
+    }
+}
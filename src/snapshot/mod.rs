@@ -6,16 +6,42 @@
 //! - Schema diff engine (comparing snapshots)
 //! - Change detection (what breaks if I change this?)
 //! - Blast radius analysis (downstream impact)
+//! - Archival to S3-compatible storage
+//! - Project lint conventions
+//! - Encryption checks for sensitive columns
+//! - Documentation bundles for publishing
+//! - Ignore rules for noisy differences
+//! - Diffs rendered as migration SQL
+//! - Benchmarks of introspection, diff and blast radius
+//! - Data dictionary custom fields
+//! - Filtered, streamed (NDJSON) snapshot and diff responses
+//! - Governance documentation coverage
+//! - PII classification propagation through foreign keys and views
+//! - Governance metadata diffs (tags, PII, descriptions, retention)
 
+pub mod archive;
 pub mod store;
 pub mod diff;
 pub mod blast_radius;
 pub mod rules;
+pub mod lint;
+pub mod encryption;
+pub mod docs;
+pub mod ignore;
+pub mod migration;
+pub mod benchmark;
+pub mod dictionary;
+pub mod stream;
+pub mod coverage;
+pub mod propagation;
+pub mod metadata_diff;
 
+pub use archive::SnapshotArchive;
 pub use store::SnapshotStore;
 #[allow(unused_imports)]
 pub use diff::{SchemaDiff, DiffEngine, ChangeType, SchemaDiffItem};
 #[allow(unused_imports)]
-pub use blast_radius::{BlastRadiusAnalyzer, BlastRadius, ImpactedObject};
+pub use blast_radius::{BlastRadiusAnalyzer, BlastRadius, ImpactedObject, TraversalLimits};
 #[allow(unused_imports)]
 pub use rules::{RulesEngine, Rule, RuleViolation, Severity};
+pub use lint::LintConfig;
@@ -0,0 +1,324 @@
+//! Classification propagation
+//!
+//! A column classified as PII (confidential or above) leaks its data into
+//! every column that references it through a foreign key and every view
+//! column that projects it, yet those usually stay unclassified. Starting
+//! from the classified columns of an annotated snapshot, this walks foreign
+//! keys from referenced to referencing columns and views from base to view
+//! columns, transitively, and suggests the source's level wherever a column
+//! is classified lower or not at all. The suggestions can be drafted into a
+//! governance proposal of `set_classification` changes.
+//!
+//! Views are not part of snapshots; their projections are read from the live
+//! database and matched by column name, so a view column renamed with `AS`
+//! is not followed.
+
+use crate::error::AppError;
+use crate::introspection::{PiiLevel, SchemaSnapshot};
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::dictionary;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use uuid::Uuid;
+
+/// A view column passing a base table column through under the same name
+#[derive(Debug, Clone)]
+pub struct ViewProjection {
+    /// `schema.view`
+    pub view: String,
+    pub column: String,
+    /// `schema.table` the column comes from
+    pub source_table: String,
+}
+
+/// How a classification reached a column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropagationPath {
+    ForeignKey { constraint: String },
+    View { view: String },
+}
+
+/// A column that should carry a stronger classification
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassificationSuggestion {
+    /// `schema.table` or `schema.view`
+    pub table: String,
+    pub column: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<PiiLevel>,
+    pub suggested: PiiLevel,
+    /// Column the level was inherited from directly
+    pub inherited_from: String,
+    pub via: PropagationPath,
+    /// Classified column the chain starts at
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropagationReport {
+    pub connection_id: Uuid,
+    pub snapshot_version: u64,
+    pub generated_at: DateTime<Utc>,
+    /// Columns classified confidential or above the walk started from
+    pub classified_columns: usize,
+    pub suggestions: Vec<ClassificationSuggestion>,
+}
+
+impl PropagationReport {
+    /// `set_classification` changes applying every suggestion
+    pub fn changes(&self) -> Vec<SchemaChange> {
+        self.suggestions
+            .iter()
+            .map(|s| SchemaChange::SetClassification {
+                table_name: s.table.clone(),
+                column_name: s.column.clone(),
+                level: Some(s.suggested.clone()),
+            })
+            .collect()
+    }
+}
+
+/// View columns that pass base table columns through, as far as the role can see
+pub async fn view_projections(pool: &Pool) -> Result<Vec<ViewProjection>, AppError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT u.view_schema, u.view_name, u.column_name, u.table_schema, u.table_name
+             FROM information_schema.view_column_usage u
+             JOIN information_schema.columns c
+               ON c.table_schema = u.view_schema AND c.table_name = u.view_name AND c.column_name = u.column_name
+             WHERE u.view_schema NOT IN ('pg_catalog', 'information_schema')",
+            &[],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| ViewProjection {
+            view: format!("{}.{}", row.get::<_, String>(0), row.get::<_, String>(1)),
+            column: row.get(2),
+            source_table: format!("{}.{}", row.get::<_, String>(3), row.get::<_, String>(4)),
+        })
+        .collect())
+}
+
+/// Propagation over a connection's latest snapshot, with the current
+/// dictionary classifications and, while connected, its views
+pub async fn for_connection(state: &AppState, connection_id: Uuid) -> Result<PropagationReport, AppError> {
+    let mut snapshot = state
+        .latest_scoped_snapshot(connection_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No snapshots found. Create a snapshot first.".to_string()))?;
+    let project_id = state.connections.project_id(connection_id).await;
+    let dictionary = dictionary::load(state, project_id).await?;
+    dictionary.annotate(&mut snapshot);
+    let views = match state.connections.get_pool(connection_id).await {
+        Ok(pool) => view_projections(&pool).await?,
+        Err(_) => Vec::new(),
+    };
+    Ok(propagate(&snapshot, &views, &dictionary.classifications(connection_id)))
+}
+
+/// Suggest classifications for columns fed by classified ones. `classified`
+/// holds dictionary classifications of view columns, which the snapshot
+/// does not carry.
+pub fn propagate(
+    snapshot: &SchemaSnapshot,
+    views: &[ViewProjection],
+    classified: &BTreeMap<String, PiiLevel>,
+) -> PropagationReport {
+    let mut levels: HashMap<String, Option<PiiLevel>> = HashMap::new();
+    for table in &snapshot.tables {
+        for column in &table.columns {
+            let key = format!("{}.{}.{}", table.schema, table.name, column.name);
+            levels.insert(key, column.pii_classification.clone());
+        }
+    }
+    for view in views {
+        let key = format!("{}.{}", view.view, view.column);
+        let level = classified.get(&key).cloned();
+        levels.entry(key).or_insert(level);
+    }
+
+    // Column → (column it feeds, path)
+    let mut edges: HashMap<String, Vec<(String, PropagationPath)>> = HashMap::new();
+    for fk in &snapshot.foreign_keys {
+        for (source, referenced) in fk.source_columns.iter().zip(&fk.referenced_columns) {
+            edges
+                .entry(format!("{}.{}.{}", fk.referenced_schema, fk.referenced_table, referenced))
+                .or_default()
+                .push((
+                    format!("{}.{}.{}", fk.source_schema, fk.source_table, source),
+                    PropagationPath::ForeignKey { constraint: fk.constraint_name.clone() },
+                ));
+        }
+    }
+    for view in views {
+        edges
+            .entry(format!("{}.{}", view.source_table, view.column))
+            .or_default()
+            .push((format!("{}.{}", view.view, view.column), PropagationPath::View { view: view.view.clone() }));
+    }
+
+    let original = levels.clone();
+    let mut origins: HashMap<String, String> = HashMap::new();
+    let mut queue: VecDeque<String> = levels
+        .iter()
+        .filter(|(_, level)| level.as_ref().is_some_and(|l| *l >= PiiLevel::Confidential))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let classified_columns = queue.len();
+    let mut suggestions: BTreeMap<String, ClassificationSuggestion> = BTreeMap::new();
+
+    // Levels only rise, so every column is queued a bounded number of times
+    while let Some(key) = queue.pop_front() {
+        let Some(level) = levels.get(&key).cloned().flatten() else {
+            continue;
+        };
+        let origin = origins.get(&key).cloned().unwrap_or_else(|| key.clone());
+        for (target, path) in edges.get(&key).into_iter().flatten() {
+            let current = levels.get(target).cloned().flatten();
+            if current.as_ref().is_some_and(|c| *c >= level) {
+                continue;
+            }
+            let Some((table, column)) = target.rsplit_once('.') else {
+                continue;
+            };
+            levels.insert(target.clone(), Some(level.clone()));
+            origins.insert(target.clone(), origin.clone());
+            suggestions.insert(
+                target.clone(),
+                ClassificationSuggestion {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                    current: original.get(target).cloned().flatten(),
+                    suggested: level.clone(),
+                    inherited_from: key.clone(),
+                    via: path.clone(),
+                    origin: origin.clone(),
+                },
+            );
+            queue.push_back(target.clone());
+        }
+    }
+
+    PropagationReport {
+        connection_id: snapshot.connection_id,
+        snapshot_version: snapshot.version,
+        generated_at: Utc::now(),
+        classified_columns,
+        suggestions: suggestions.into_values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Column, ForeignKey, Table, TableGovernance};
+
+    fn column(name: &str, pii: Option<PiiLevel>) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default_value: None,
+            is_primary_key: false,
+            is_unique: false,
+            ordinal_position: 1,
+            collation: None,
+            pii_classification: pii,
+            description: None,
+            tags: vec![],
+            custom_fields: BTreeMap::new(),
+        }
+    }
+
+    fn table(name: &str, columns: Vec<Column>) -> Table {
+        Table {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            columns,
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: TableGovernance::default(),
+        }
+    }
+
+    fn fk(name: &str, source: (&str, &str), referenced: (&str, &str)) -> ForeignKey {
+        ForeignKey {
+            constraint_name: name.to_string(),
+            source_schema: "public".to_string(),
+            source_table: source.0.to_string(),
+            source_columns: vec![source.1.to_string()],
+            referenced_schema: "public".to_string(),
+            referenced_table: referenced.0.to_string(),
+            referenced_columns: vec![referenced.1.to_string()],
+            on_update: "NO ACTION".to_string(),
+            on_delete: "NO ACTION".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classification_follows_foreign_keys_and_views() {
+        let snapshot = SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::new_v4(),
+            version: 3,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: vec![],
+            extensions: vec![],
+            types: None,
+            views: None,
+            tables: vec![
+                table("users", vec![column("email", Some(PiiLevel::Restricted))]),
+                table("invites", vec![column("email", Some(PiiLevel::Internal))]),
+                table("invite_log", vec![column("invite_email", None)]),
+                table("admins", vec![column("email", Some(PiiLevel::Secret))]),
+            ],
+            foreign_keys: vec![
+                fk("invites_email_fkey", ("invites", "email"), ("users", "email")),
+                fk("invite_log_email_fkey", ("invite_log", "invite_email"), ("invites", "email")),
+                fk("admins_email_fkey", ("admins", "email"), ("users", "email")),
+            ],
+            indexes: vec![],
+            checksum: String::new(),
+        };
+        let views = vec![ViewProjection {
+            view: "reporting.active_users".to_string(),
+            column: "email".to_string(),
+            source_table: "public.users".to_string(),
+        }];
+
+        let report = propagate(&snapshot, &views, &BTreeMap::new());
+        assert_eq!(report.classified_columns, 2);
+        let found: Vec<(&str, &str, Option<&PiiLevel>)> = report
+            .suggestions
+            .iter()
+            .map(|s| (s.table.as_str(), s.column.as_str(), s.current.as_ref()))
+            .collect();
+        // Admins are already classified higher; the log inherits through the invite
+        assert_eq!(
+            found,
+            vec![
+                ("public.invite_log", "invite_email", None),
+                ("public.invites", "email", Some(&PiiLevel::Internal)),
+                ("reporting.active_users", "email", None),
+            ]
+        );
+        assert_eq!(report.suggestions[0].origin, "public.users.email");
+        assert_eq!(report.suggestions[0].inherited_from, "public.invites.email");
+        assert_eq!(report.changes().len(), 3);
+
+        // A view column already classified in the dictionary needs nothing
+        let classified = BTreeMap::from([("reporting.active_users.email".to_string(), PiiLevel::Secret)]);
+        assert_eq!(propagate(&snapshot, &views, &classified).suggestions.len(), 2);
+    }
+}
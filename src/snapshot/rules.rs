@@ -3,11 +3,19 @@
 //! "Junior-proof" guardrails for database changes.
 //! This is what managers pay for - automated enforcement.
 
-use crate::introspection::SchemaSnapshot;
+use crate::connection::Environment;
+use crate::i18n::{params, Params, Translations};
+use crate::introspection::{PiiLevel, SchemaSnapshot};
+use crate::pipeline::proposal::SchemaProposal;
+use crate::pipeline::types::SchemaChange;
+use crate::snapshot::encryption;
+use crate::snapshot::lint::{lint_proposal, LintConfig};
 use crate::snapshot::diff::{ChangeType, ObjectType, SchemaDiff, SchemaDiffItem};
 #[allow(unused_imports)]
 use crate::snapshot::blast_radius::{BlastRadius, BlastRadiusAnalyzer};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::warn;
 
 /// Rule severity levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
@@ -29,6 +37,26 @@ pub struct RuleViolation {
     pub message: String,
     pub affected_object: String,
     pub suggestion: Option<String>,
+    /// Values interpolated into the message and suggestion, for translations
+    /// keyed by `rule_id` and `<rule_id>.suggestion`
+    #[serde(default, skip_serializing_if = "Params::is_empty")]
+    pub params: Params,
+}
+
+impl RuleViolation {
+    /// Translate the message and suggestion into `language` where possible
+    pub fn localize(&mut self, translations: &Translations, language: &str) {
+        if let Some(message) = translations.render(language, &self.rule_id, &self.params) {
+            self.message = message;
+        }
+        let suggestion_code = format!("{}.suggestion", self.rule_id);
+        if let (Some(suggestion), Some(translated)) = (
+            self.suggestion.as_mut(),
+            translations.render(language, &suggestion_code, &self.params),
+        ) {
+            *suggestion = translated;
+        }
+    }
 }
 
 /// A governance rule definition
@@ -42,6 +70,10 @@ pub struct Rule {
     pub enabled: bool,
     /// Category for grouping
     pub category: RuleCategory,
+    /// Severity used instead on connections in these environments, keyed by
+    /// `development`, `staging`, `production` or a custom environment name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment_severity: BTreeMap<String, Severity>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -65,6 +97,14 @@ pub struct RulesResult {
     pub summary: RulesSummary,
 }
 
+impl RulesResult {
+    pub fn localize(&mut self, translations: &Translations, language: &str) {
+        for violation in &mut self.violations {
+            violation.localize(translations, language);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RulesSummary {
@@ -87,13 +127,39 @@ impl RulesEngine {
         }
     }
 
+    /// Apply per-environment severities from config (environment → rule id → severity)
+    /// on top of the built-in ones
+    pub fn with_environment_severity(mut self, overrides: &BTreeMap<String, BTreeMap<String, Severity>>) -> Self {
+        for (environment, severities) in overrides {
+            for (rule_id, severity) in severities {
+                match self.rules.iter_mut().find(|r| &r.id == rule_id) {
+                    Some(rule) => {
+                        rule.environment_severity.insert(environment.clone(), *severity);
+                    }
+                    None => warn!("⚠️  Unknown rule `rule_severity.{}.{}` in config is ignored", environment, rule_id),
+                }
+            }
+        }
+        self
+    }
+
     /// Get all configured rules
     pub fn list_rules(&self) -> &[Rule] {
         &self.rules
     }
 
-    /// Evaluate a schema diff against all rules
-    pub fn evaluate(&self, diff: &SchemaDiff, snapshot: &SchemaSnapshot) -> RulesResult {
+    /// Severity a rule's violations get on a connection in `environment`
+    fn severity_for(&self, rule_id: &str, found: Severity, environment: &Environment) -> Severity {
+        self.rules
+            .iter()
+            .find(|r| r.id == rule_id)
+            .and_then(|r| r.environment_severity.get(environment.key()))
+            .copied()
+            .unwrap_or(found)
+    }
+
+    /// Evaluate a schema diff against all rules for a connection in `environment`
+    pub fn evaluate(&self, diff: &SchemaDiff, snapshot: &SchemaSnapshot, environment: &Environment) -> RulesResult {
         let mut violations = Vec::new();
         
         for change in &diff.changes {
@@ -105,9 +171,41 @@ impl RulesEngine {
             violations.extend(self.check_not_null_without_default(change));
             violations.extend(self.check_rename_without_alias(change));
             violations.extend(self.check_pk_modification(change));
+            violations.extend(self.check_key_column_change(change));
             violations.extend(self.check_cascade_delete(change, snapshot));
+            violations.extend(self.check_collation_mismatch(change, snapshot));
+            violations.extend(self.check_encoding_mismatch(change));
+            violations.extend(self.check_drop_schema_rule(change));
+            violations.extend(self.check_drop_extension_rule(change));
+            violations.extend(self.check_unencrypted_pii(change));
+            violations.extend(self.check_rls_disabled_on_pii(change, snapshot));
         }
         
+        self.summarize(violations, environment)
+    }
+
+    /// Evaluate a proposal's new and renamed objects against project lint
+    /// settings, and its drops against the latest snapshot of the target
+    pub fn evaluate_proposal(
+        &self,
+        proposal: &SchemaProposal,
+        snapshot: Option<&SchemaSnapshot>,
+        lint: &LintConfig,
+        environment: &Environment,
+    ) -> RulesResult {
+        let mut violations = lint_proposal(proposal, lint);
+        violations.extend(encryption::check_proposal(proposal));
+        if let Some(snapshot) = snapshot {
+            violations.extend(self.check_proposed_schema_drops(proposal, snapshot));
+        }
+        self.summarize(violations, environment)
+    }
+
+    fn summarize(&self, mut violations: Vec<RuleViolation>, environment: &Environment) -> RulesResult {
+        for v in &mut violations {
+            v.severity = self.severity_for(&v.rule_id, v.severity, environment);
+        }
+
         let has_blockers = violations.iter().any(|v| v.severity == Severity::Block);
         let has_errors = violations.iter().any(|v| v.severity == Severity::Error);
         let has_warnings = violations.iter().any(|v| v.severity == Severity::Warning);
@@ -161,6 +259,11 @@ impl RulesEngine {
         let blast = BlastRadiusAnalyzer::analyze_column(snapshot, schema, table, column);
         
         if blast.impacted.len() > 0 {
+            let dependencies = blast.impacted.iter()
+                .take(3)
+                .map(|i| i.path.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
             violations.push(RuleViolation {
                 rule_id: "R001".to_string(),
                 rule_name: "Column Drop with Dependencies".to_string(),
@@ -171,14 +274,8 @@ impl RulesEngine {
                     blast.impacted.len()
                 ),
                 affected_object: change.object_path.clone(),
-                suggestion: Some(format!(
-                    "First remove or update these dependencies: {}",
-                    blast.impacted.iter()
-                        .take(3)
-                        .map(|i| i.path.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )),
+                suggestion: Some(format!("First remove or update these dependencies: {}", dependencies)),
+                params: params(&[("object", &change.object_path), ("count", &blast.impacted.len().to_string()), ("dependencies", &dependencies)]),
             });
         }
         
@@ -219,6 +316,7 @@ impl RulesEngine {
                 ),
                 affected_object: change.object_path.clone(),
                 suggestion: Some("Drop dependent tables first, or update their foreign keys".to_string()),
+                params: params(&[("object", &change.object_path), ("tables", &blast.summary.total_tables.to_string())]),
             });
         }
         
@@ -250,6 +348,7 @@ impl RulesEngine {
                     ),
                     affected_object: change.object_path.clone(),
                     suggestion: Some("Consider adding a unique constraint if uniqueness is required".to_string()),
+                    params: params(&[("object", &change.object_path)]),
                 });
             } else {
                 violations.push(RuleViolation {
@@ -262,6 +361,7 @@ impl RulesEngine {
                     ),
                     affected_object: change.object_path.clone(),
                     suggestion: Some("Review query plans before removing indexes".to_string()),
+                    params: params(&[("object", &change.object_path)]),
                 });
             }
         }
@@ -303,6 +403,7 @@ impl RulesEngine {
                             "Consider: 1) Add new column with {}, 2) Migrate data, 3) Drop old column",
                             after
                         )),
+                        params: params(&[("object", &change.object_path), ("from", before), ("to", after)]),
                     });
                 }
             }
@@ -340,6 +441,7 @@ impl RulesEngine {
                 ),
                 affected_object: change.object_path.clone(),
                 suggestion: Some("Either: 1) Set a default value, 2) Backfill NULLs first, 3) Make it nullable".to_string()),
+                params: params(&[("object", &change.object_path)]),
             });
         }
         
@@ -364,45 +466,116 @@ impl RulesEngine {
             ),
             affected_object: change.object_path.clone(),
             suggestion: Some("Consider creating a view alias for backward compatibility".to_string()),
+            params: params(&[("object", &change.object_path)]),
         });
         
         violations
     }
 
-    /// Rule: Block primary key modifications
+    /// Rule: Block dropping a primary key or removing columns from it
     fn check_pk_modification(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
         let mut violations = Vec::new();
         
-        if change.object_type != ObjectType::Column || change.change_type != ChangeType::Modified {
+        if change.object_type != ObjectType::PrimaryKey {
             return violations;
         }
         
-        let before_pk = change.before.as_ref()
-            .and_then(|b| b.get("isPrimaryKey"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-        let after_pk = change.after.as_ref()
-            .and_then(|a| a.get("isPrimaryKey"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+        // Removed keys record all their columns as removed
+        let state = match change.change_type {
+            ChangeType::Removed => change.before.as_ref(),
+            ChangeType::Modified => change.after.as_ref(),
+            _ => None,
+        };
+        let removed = Self::string_list(state, "removedColumns");
         
-        if before_pk && !after_pk {
+        if !removed.is_empty() {
             violations.push(RuleViolation {
                 rule_id: "R008".to_string(),
                 rule_name: "Primary Key Removal".to_string(),
                 severity: Severity::Block,
                 message: format!(
-                    "Removing {} from primary key requires careful migration",
+                    "Removing {} from primary key {} requires careful migration",
+                    removed.join(", "),
                     change.object_path
                 ),
                 affected_object: change.object_path.clone(),
                 suggestion: Some("Create a new table with correct PK and migrate data".to_string()),
+                params: params(&[("object", &change.object_path), ("columns", &removed.join(", "))]),
             });
         }
         
         violations
     }
 
+    /// Rule: Error when the column set of a composite key changes, and note reorders
+    fn check_key_column_change(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        if !matches!(change.object_type, ObjectType::PrimaryKey | ObjectType::ForeignKey)
+            || change.change_type != ChangeType::Modified
+        {
+            return violations;
+        }
+        
+        let added = Self::string_list(change.after.as_ref(), "addedColumns");
+        let removed = Self::string_list(change.after.as_ref(), "removedColumns");
+        let reordered = change.after.as_ref()
+            .and_then(|a| a.get("reordered"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        
+        // Columns leaving a primary key are already blocked by R008
+        let changed = match change.object_type {
+            ObjectType::PrimaryKey => !added.is_empty() && removed.is_empty(),
+            _ => !added.is_empty() || !removed.is_empty(),
+        };
+        
+        if changed {
+            let mut detail = Vec::new();
+            if !added.is_empty() {
+                detail.push(format!("adds {}", added.join(", ")));
+            }
+            if !removed.is_empty() {
+                detail.push(format!("removes {}", removed.join(", ")));
+            }
+            violations.push(RuleViolation {
+                rule_id: "R018".to_string(),
+                rule_name: "Key Column Set Change".to_string(),
+                severity: Severity::Error,
+                message: format!("Key {} {}", change.object_path, detail.join(" and ")),
+                affected_object: change.object_path.clone(),
+                suggestion: Some(
+                    "Check that foreign keys referencing this key and existing rows still satisfy the new column set".to_string()
+                ),
+                params: params(&[("object", &change.object_path), ("added", &added.join(", ")), ("removed", &removed.join(", "))]),
+            });
+        }
+        
+        if reordered {
+            violations.push(RuleViolation {
+                rule_id: "R019".to_string(),
+                rule_name: "Key Column Reorder".to_string(),
+                severity: Severity::Info,
+                message: format!("Columns of key {} were reordered", change.object_path),
+                affected_object: change.object_path.clone(),
+                suggestion: Some(
+                    "Reordering rebuilds the key index and changes which prefix lookups it can serve".to_string()
+                ),
+                params: params(&[("object", &change.object_path)]),
+            });
+        }
+        
+        violations
+    }
+
+    fn string_list(value: Option<&serde_json::Value>, key: &str) -> Vec<String> {
+        value
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|i| i.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
     /// Rule: Warn on CASCADE DELETE additions
     fn check_cascade_delete(
         &self,
@@ -411,16 +584,25 @@ impl RulesEngine {
     ) -> Vec<RuleViolation> {
         let mut violations = Vec::new();
         
-        if change.object_type != ObjectType::ForeignKey || change.change_type != ChangeType::Added {
+        if change.object_type != ObjectType::ForeignKey {
             return violations;
         }
         
-        let on_delete = change.after.as_ref()
-            .and_then(|a| a.get("onDelete"))
+        let on_delete = |state: Option<&serde_json::Value>| state
+            .and_then(|s| s.get("onDelete"))
             .and_then(|v| v.as_str())
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_uppercase();
+        
+        // New FKs, or existing FKs switched to CASCADE
+        let adds_cascade = match change.change_type {
+            ChangeType::Added => on_delete(change.after.as_ref()) == "CASCADE",
+            ChangeType::Modified => on_delete(change.after.as_ref()) == "CASCADE"
+                && on_delete(change.before.as_ref()) != "CASCADE",
+            _ => false,
+        };
         
-        if on_delete.to_uppercase() == "CASCADE" {
+        if adds_cascade {
             violations.push(RuleViolation {
                 rule_id: "R009".to_string(),
                 rule_name: "CASCADE DELETE Addition".to_string(),
@@ -431,6 +613,261 @@ impl RulesEngine {
                 ),
                 affected_object: change.object_path.clone(),
                 suggestion: Some("Use RESTRICT or SET NULL if data preservation is important".to_string()),
+                params: params(&[("object", &change.object_path)]),
+            });
+        }
+        
+        violations
+    }
+
+    /// Rule: Warn on text columns with a collation different from the database default
+    fn check_collation_mismatch(
+        &self,
+        change: &SchemaDiffItem,
+        snapshot: &SchemaSnapshot,
+    ) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        let default_collation = snapshot.database.collation.as_str();
+        if default_collation.is_empty() {
+            return violations;
+        }
+        
+        // Collect (path, collation) pairs for new or modified columns
+        let columns: Vec<(String, &str)> = match (change.object_type, change.change_type) {
+            (ObjectType::Column, ChangeType::Added | ChangeType::Modified) => change.after.as_ref()
+                .and_then(|a| a.get("collation"))
+                .and_then(|v| v.as_str())
+                .map(|c| vec![(change.object_path.clone(), c)])
+                .unwrap_or_default(),
+            (ObjectType::Table, ChangeType::Added) => change.after.as_ref()
+                .and_then(|a| a.get("columns"))
+                .and_then(|v| v.as_array())
+                .map(|cols| cols.iter()
+                    .filter_map(|col| {
+                        let name = col.get("name")?.as_str()?;
+                        let collation = col.get("collation")?.as_str()?;
+                        Some((format!("{}.{}", change.object_path, name), collation))
+                    })
+                    .collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        
+        for (path, collation) in columns {
+            if collation == "default" || collation == default_collation {
+                continue;
+            }
+            
+            violations.push(RuleViolation {
+                rule_id: "R010".to_string(),
+                rule_name: "Collation Mismatch".to_string(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Column {} uses collation '{}' but the database default is '{}'",
+                    path, collation, default_collation
+                ),
+                affected_object: path.clone(),
+                suggestion: Some("Mixed collations change sort order and can make comparisons and joins fail; use the database default unless required".to_string()),
+                params: params(&[("object", &path), ("collation", collation), ("default", default_collation)]),
+            });
+        }
+        
+        violations
+    }
+
+    /// Rule: Flag new or changed Restricted/Secret columns stored as plaintext
+    fn check_unencrypted_pii(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
+        let columns: Vec<(String, &serde_json::Value)> = match (change.object_type, change.change_type) {
+            (ObjectType::Column, ChangeType::Added | ChangeType::Modified) => change.after.iter()
+                .map(|a| (change.object_path.clone(), a))
+                .collect(),
+            (ObjectType::Table, ChangeType::Added) => change.after.as_ref()
+                .and_then(|a| a.get("columns"))
+                .and_then(|v| v.as_array())
+                .map(|cols| cols.iter()
+                    .filter_map(|col| {
+                        let name = col.get("name")?.as_str()?;
+                        Some((format!("{}.{}", change.object_path, name), col))
+                    })
+                    .collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        
+        columns.into_iter()
+            .filter_map(|(path, col)| {
+                let name = col.get("name")?.as_str()?;
+                let data_type = col.get("dataType")?.as_str()?;
+                let tagged: Option<PiiLevel> = col.get("piiClassification")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok());
+                let tags = Self::string_list(Some(col), "tags");
+                encryption::check_column(&path, name, data_type, tagged.as_ref(), &tags)
+            })
+            .collect()
+    }
+
+    /// Rule: Warn when compared databases use different encodings
+    fn check_encoding_mismatch(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        if change.object_type != ObjectType::Database || change.change_type != ChangeType::Modified {
+            return violations;
+        }
+        
+        let before_encoding = change.before.as_ref()
+            .and_then(|b| b.get("encoding"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let after_encoding = change.after.as_ref()
+            .and_then(|a| a.get("encoding"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        
+        if before_encoding != after_encoding {
+            violations.push(RuleViolation {
+                rule_id: "R011".to_string(),
+                rule_name: "Encoding Mismatch".to_string(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Databases use different encodings ({} vs {}) - text data may not round-trip between them",
+                    before_encoding, after_encoding
+                ),
+                affected_object: change.object_path.clone(),
+                suggestion: Some("Align server encodings across environments before migrating data".to_string()),
+                params: params(&[("object", &change.object_path), ("before", before_encoding), ("after", after_encoding)]),
+            });
+        }
+        
+        violations
+    }
+
+    /// Rule: Block dropping schemas that still contain tables
+    fn check_drop_schema_rule(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        if change.object_type != ObjectType::Schema || change.change_type != ChangeType::Removed {
+            return violations;
+        }
+        
+        let table_count = change.before.as_ref()
+            .and_then(|b| b.get("tableCount"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        
+        if table_count > 0 {
+            violations.push(RuleViolation {
+                rule_id: "R012".to_string(),
+                rule_name: "Schema Drop with Tables".to_string(),
+                severity: Severity::Block,
+                message: format!(
+                    "Cannot drop schema {} - it still contains {} tables",
+                    change.object_path, table_count
+                ),
+                affected_object: change.object_path.clone(),
+                suggestion: Some("Move or drop the tables explicitly before dropping the schema".to_string()),
+                params: params(&[("object", &change.object_path), ("tables", &table_count.to_string())]),
+            });
+        }
+        
+        violations
+    }
+
+    /// Rule R012 for proposals: block dropping schemas the snapshot still has tables in
+    fn check_proposed_schema_drops(&self, proposal: &SchemaProposal, snapshot: &SchemaSnapshot) -> Vec<RuleViolation> {
+        proposal
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                SchemaChange::DropSchema { schema_name, .. } => Some(schema_name),
+                _ => None,
+            })
+            .filter_map(|schema_name| {
+                let table_count = snapshot.tables.iter().filter(|t| &t.schema == schema_name).count();
+                (table_count > 0).then(|| RuleViolation {
+                    rule_id: "R012".to_string(),
+                    rule_name: "Schema Drop with Tables".to_string(),
+                    severity: Severity::Block,
+                    message: format!("Cannot drop schema {} - it still contains {} tables", schema_name, table_count),
+                    affected_object: schema_name.clone(),
+                    suggestion: Some("Move or drop the tables explicitly before dropping the schema".to_string()),
+                    params: params(&[("object", schema_name), ("tables", &table_count.to_string())]),
+                })
+            })
+            .collect()
+    }
+
+    /// Rule: Warn when a dropped extension was still in use
+    fn check_drop_extension_rule(&self, change: &SchemaDiffItem) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        if change.object_type != ObjectType::Extension || change.change_type != ChangeType::Removed {
+            return violations;
+        }
+        
+        let dependents = change.before.as_ref()
+            .and_then(|b| b.get("dependentObjects"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        
+        if dependents > 0 {
+            violations.push(RuleViolation {
+                rule_id: "R021".to_string(),
+                rule_name: "Extension Drop with Dependents".to_string(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Extension {} was dropped while {} columns or indexes used it",
+                    change.object_path, dependents
+                ),
+                affected_object: change.object_path.clone(),
+                suggestion: Some(
+                    "Migrate columns and indexes off the extension's types and operator classes before dropping it".to_string()
+                ),
+                params: params(&[("object", &change.object_path), ("dependents", &dependents.to_string())]),
+            });
+        }
+        
+        violations
+    }
+
+    /// Rule: Flag turning off row-level security on a table holding PII
+    fn check_rls_disabled_on_pii(&self, change: &SchemaDiffItem, snapshot: &SchemaSnapshot) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        
+        if change.object_type != ObjectType::RowSecurity {
+            return violations;
+        }
+        let enabled = |state: &Option<serde_json::Value>| state.as_ref()
+            .and_then(|s| s.get("enabled"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if !enabled(&change.before) || enabled(&change.after) {
+            return violations;
+        }
+        
+        let Some((schema, table)) = change.object_path.split_once('.') else {
+            return violations;
+        };
+        let pii = snapshot.tables.iter()
+            .find(|t| t.schema == schema && t.name == table)
+            .map(|t| t.pii_columns())
+            .unwrap_or_default();
+        
+        if !pii.is_empty() {
+            violations.push(RuleViolation {
+                rule_id: "R022".to_string(),
+                rule_name: "Row-Level Security Disabled on PII".to_string(),
+                severity: Severity::Error,
+                message: format!(
+                    "Row-level security was disabled on {}, which holds PII in {}",
+                    change.object_path,
+                    pii.join(", ")
+                ),
+                affected_object: change.object_path.clone(),
+                suggestion: Some(
+                    "Keep row-level security on and adjust the policies instead, or move the PII columns out of the table first".to_string()
+                ),
+                params: params(&[("object", &change.object_path), ("columns", &pii.join(", "))]),
             });
         }
         
@@ -470,6 +907,7 @@ impl RulesEngine {
                 severity: Severity::Block,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R002".to_string(),
@@ -478,6 +916,7 @@ impl RulesEngine {
                 severity: Severity::Block,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R003".to_string(),
@@ -486,14 +925,16 @@ impl RulesEngine {
                 severity: Severity::Block,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R004".to_string(),
                 name: "Index Removal Performance".to_string(),
-                description: "Warn when removing indexes that may impact performance".to_string(),
+                description: "Warn when removing indexes that may impact performance (Error in production)".to_string(),
                 severity: Severity::Warning,
                 enabled: true,
                 category: RuleCategory::Performance,
+                environment_severity: in_production(Severity::Error),
             },
             Rule {
                 id: "R005".to_string(),
@@ -502,6 +943,7 @@ impl RulesEngine {
                 severity: Severity::Error,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R006".to_string(),
@@ -510,37 +952,202 @@ impl RulesEngine {
                 severity: Severity::Block,
                 enabled: true,
                 category: RuleCategory::Compatibility,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R007".to_string(),
                 name: "Rename Without Alias".to_string(),
-                description: "Warn when renaming objects without backward compatibility".to_string(),
+                description: "Warn when renaming objects without backward compatibility (Error in production)".to_string(),
                 severity: Severity::Warning,
                 enabled: true,
                 category: RuleCategory::Compatibility,
+                environment_severity: in_production(Severity::Error),
             },
             Rule {
                 id: "R008".to_string(),
                 name: "Primary Key Removal".to_string(),
-                description: "Block removing columns from primary keys".to_string(),
+                description: "Block dropping primary keys or removing columns from them".to_string(),
                 severity: Severity::Block,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
             },
             Rule {
                 id: "R009".to_string(),
                 name: "CASCADE DELETE Addition".to_string(),
-                description: "Warn when adding CASCADE DELETE foreign keys".to_string(),
+                description: "Warn when adding CASCADE DELETE foreign keys (Error in production)".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::DataLoss,
+                environment_severity: in_production(Severity::Error),
+            },
+            Rule {
+                id: "R010".to_string(),
+                name: "Collation Mismatch".to_string(),
+                description: "Warn when text columns use a collation other than the database default".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::Compatibility,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R011".to_string(),
+                name: "Encoding Mismatch".to_string(),
+                description: "Warn when comparing databases with different server encodings".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::Compatibility,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R012".to_string(),
+                name: "Schema Drop with Tables".to_string(),
+                description: "Block dropping schemas that still contain tables".to_string(),
+                severity: Severity::Block,
+                enabled: true,
+                category: RuleCategory::DataLoss,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R013".to_string(),
+                name: "Naming Convention".to_string(),
+                description: "Warn when new schema, table, or column names break the project naming style".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::BestPractice,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R014".to_string(),
+                name: "Reserved Word Identifier".to_string(),
+                description: "Error when a new name is a reserved SQL keyword".to_string(),
+                severity: Severity::Error,
+                enabled: true,
+                category: RuleCategory::BestPractice,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R015".to_string(),
+                name: "Table Name Plurality".to_string(),
+                description: "Warn when table names break the project singular/plural policy".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::BestPractice,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R016".to_string(),
+                name: "Missing Audit Columns".to_string(),
+                description: "Warn when new tables lack the project's required audit columns".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::BestPractice,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R017".to_string(),
+                name: "Missing Primary Key".to_string(),
+                description: "Error when new tables are created without a primary key".to_string(),
+                severity: Severity::Error,
+                enabled: true,
+                category: RuleCategory::BestPractice,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R018".to_string(),
+                name: "Key Column Set Change".to_string(),
+                description: "Error when columns are added to a primary key or a foreign key's column pairs change".to_string(),
+                severity: Severity::Error,
+                enabled: true,
+                category: RuleCategory::Compatibility,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R019".to_string(),
+                name: "Key Column Reorder".to_string(),
+                description: "Note when the columns of a composite key are reordered".to_string(),
+                severity: Severity::Info,
+                enabled: true,
+                category: RuleCategory::Performance,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R020".to_string(),
+                name: "Unencrypted Sensitive Column".to_string(),
+                description: "Flag Restricted or Secret columns stored as plaintext (Error for Secret, Warning for Restricted)".to_string(),
+                severity: Severity::Warning,
+                enabled: true,
+                category: RuleCategory::Security,
+                environment_severity: BTreeMap::new(),
+            },
+            Rule {
+                id: "R021".to_string(),
+                name: "Extension Drop with Dependents".to_string(),
+                description: "Warn when an extension is dropped while columns or indexes use its types or operator classes (Error in production)".to_string(),
                 severity: Severity::Warning,
                 enabled: true,
                 category: RuleCategory::DataLoss,
+                environment_severity: in_production(Severity::Error),
+            },
+            Rule {
+                id: "R022".to_string(),
+                name: "Row-Level Security Disabled on PII".to_string(),
+                description: "Flag disabling row-level security on a table with PII columns (Block in production)".to_string(),
+                severity: Severity::Error,
+                enabled: true,
+                category: RuleCategory::Security,
+                environment_severity: in_production(Severity::Block),
             },
         ]
     }
 }
 
+/// Built-in escalation for production connections
+fn in_production(severity: Severity) -> BTreeMap<String, Severity> {
+    BTreeMap::from([(Environment::Production.key().to_string(), severity)])
+}
+
 impl Default for RulesEngine {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::{Table, TableGovernance};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_proposed_schema_drop_blocks_only_when_tables_remain() {
+        let mut snapshot = SchemaSnapshot::empty(Uuid::new_v4());
+        snapshot.tables = vec![Table {
+            name: "invoices".to_string(),
+            schema: "billing".to_string(),
+            columns: vec![],
+            primary_key: None,
+            position: None,
+            color: None,
+            collapsed: false,
+            governance: TableGovernance::default(),
+        }];
+        let drop = |schema: &str| {
+            let mut proposal = SchemaProposal::new(snapshot.connection_id, "t".into(), "d".into(), "me".into());
+            proposal.changes.push(SchemaChange::DropSchema { schema_name: schema.to_string(), cascade: true });
+            proposal
+        };
+        let engine = RulesEngine::new();
+        let environment = Environment::Production;
+
+        let non_empty = engine.evaluate_proposal(&drop("billing"), Some(&snapshot), &LintConfig::default(), &environment);
+        assert!(non_empty.has_blockers);
+        let violation = non_empty.violations.iter().find(|v| v.rule_id == "R012").unwrap();
+        assert_eq!(violation.affected_object, "billing");
+        assert!(violation.message.contains("1 tables"));
+
+        let empty = engine.evaluate_proposal(&drop("archive"), Some(&snapshot), &LintConfig::default(), &environment);
+        assert!(!empty.has_blockers);
+        assert!(empty.violations.iter().all(|v| v.rule_id != "R012"));
+    }
+}
@@ -5,11 +5,11 @@
 
 use crate::error::AppError;
 use crate::introspection::SchemaSnapshot;
+use crate::storage::{MemorySnapshotBackend, SnapshotBackend};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
 /// Metadata about a snapshot (lightweight, used for listing)
@@ -49,46 +49,35 @@ impl From<&SchemaSnapshot> for SnapshotMetadata {
 
 /// Store for managing schema snapshots
 pub struct SnapshotStore {
-    /// Connection ID -> (Version -> Snapshot)
-    snapshots: Arc<RwLock<HashMap<Uuid, HashMap<u64, SchemaSnapshot>>>>,
-    /// Connection ID -> Latest version number
-    versions: Arc<RwLock<HashMap<Uuid, u64>>>,
-    /// Connection ID -> Baseline snapshot ID (the "production" state)
-    baselines: Arc<RwLock<HashMap<Uuid, Uuid>>>,
+    backend: Arc<dyn SnapshotBackend>,
+    /// Latest snapshot per connection for schema reads. Held behind a plain
+    /// lock that is only taken to swap an `Arc`, so readers never wait on
+    /// introspection or the backend.
+    latest: RwLock<HashMap<Uuid, Arc<SchemaSnapshot>>>,
 }
 
 impl SnapshotStore {
+    /// In-memory store
     pub fn new() -> Self {
+        Self::with_backend(Arc::new(MemorySnapshotBackend::default()))
+    }
+
+    pub fn with_backend(backend: Arc<dyn SnapshotBackend>) -> Self {
         Self {
-            snapshots: Arc::new(RwLock::new(HashMap::new())),
-            versions: Arc::new(RwLock::new(HashMap::new())),
-            baselines: Arc::new(RwLock::new(HashMap::new())),
+            backend,
+            latest: RwLock::new(HashMap::new()),
         }
     }
 
     /// Store a new snapshot, auto-incrementing version
-    pub async fn save(&self, mut snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
-        let connection_id = snapshot.connection_id;
-        
-        // Get next version number
-        let mut versions = self.versions.write().await;
-        let current_version = versions.get(&connection_id).copied().unwrap_or(0);
-        let new_version = current_version + 1;
-        
-        snapshot.version = new_version;
-        versions.insert(connection_id, new_version);
-        
-        // Store the snapshot
-        let mut snapshots = self.snapshots.write().await;
-        let connection_snapshots = snapshots
-            .entry(connection_id)
-            .or_insert_with(HashMap::new);
-        connection_snapshots.insert(new_version, snapshot.clone());
+    pub async fn save(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let snapshot = self.backend.insert_next(snapshot).await?;
+        self.cache_latest(Arc::new(snapshot.clone()));
         
         tracing::info!(
             "Saved snapshot v{} for connection {}: {} tables, {} FKs",
-            new_version,
-            connection_id,
+            snapshot.version,
+            snapshot.connection_id,
             snapshot.tables.len(),
             snapshot.foreign_keys.len()
         );
@@ -96,103 +85,94 @@ impl SnapshotStore {
         Ok(snapshot)
     }
 
-    /// Get the latest snapshot for a connection
-    pub async fn get_latest(&self, connection_id: Uuid) -> Option<SchemaSnapshot> {
-        let versions = self.versions.read().await;
-        let version = versions.get(&connection_id)?;
+    /// Put a previously archived snapshot back into the store, keeping its version
+    pub async fn restore(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let snapshot = self.backend.insert_existing(snapshot).await?;
+        self.latest.write().unwrap_or_else(|e| e.into_inner()).remove(&snapshot.connection_id);
         
-        let snapshots = self.snapshots.read().await;
-        snapshots
-            .get(&connection_id)?
-            .get(version)
-            .cloned()
+        tracing::info!("Restored snapshot v{} for connection {}", snapshot.version, snapshot.connection_id);
+        Ok(snapshot)
+    }
+
+    /// Get the latest snapshot for a connection
+    pub async fn get_latest(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.backend.latest(connection_id).await
+    }
+
+    /// Latest snapshot from the read cache, loading it from the backend on a
+    /// miss. Snapshots saved by other instances show up after a restart or a
+    /// save through this one.
+    pub async fn latest_cached(&self, connection_id: Uuid) -> Result<Option<Arc<SchemaSnapshot>>, AppError> {
+        let cached = self.latest.read().unwrap_or_else(|e| e.into_inner()).get(&connection_id).cloned();
+        if cached.is_some() {
+            return Ok(cached);
+        }
+        let Some(snapshot) = self.backend.latest(connection_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.cache_latest(Arc::new(snapshot))))
+    }
+
+    /// Cache `snapshot` unless a newer version is already cached; returns the
+    /// cached entry
+    fn cache_latest(&self, snapshot: Arc<SchemaSnapshot>) -> Arc<SchemaSnapshot> {
+        let mut latest = self.latest.write().unwrap_or_else(|e| e.into_inner());
+        let entry = latest.entry(snapshot.connection_id).or_insert_with(|| snapshot.clone());
+        if entry.version < snapshot.version {
+            *entry = snapshot;
+        }
+        entry.clone()
     }
 
     /// Get a specific version
-    pub async fn get_version(&self, connection_id: Uuid, version: u64) -> Option<SchemaSnapshot> {
-        let snapshots = self.snapshots.read().await;
-        snapshots
-            .get(&connection_id)?
-            .get(&version)
-            .cloned()
+    pub async fn get_version(&self, connection_id: Uuid, version: u64) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.backend.get_version(connection_id, version).await
+    }
+
+    /// Get the newest snapshot captured at or before a point in time
+    pub async fn latest_before(&self, connection_id: Uuid, at: DateTime<Utc>) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.backend.latest_before(connection_id, at).await
     }
 
     /// Get snapshot by ID
-    pub async fn get_by_id(&self, snapshot_id: Uuid) -> Option<SchemaSnapshot> {
-        let snapshots = self.snapshots.read().await;
-        for connection_snapshots in snapshots.values() {
-            for snapshot in connection_snapshots.values() {
-                if snapshot.id == snapshot_id {
-                    return Some(snapshot.clone());
-                }
-            }
-        }
-        None
+    pub async fn get_by_id(&self, snapshot_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.backend.get_by_id(snapshot_id).await
     }
 
     /// List all snapshots for a connection (metadata only)
-    pub async fn list(&self, connection_id: Uuid) -> Vec<SnapshotMetadata> {
-        let snapshots = self.snapshots.read().await;
-        
-        snapshots
-            .get(&connection_id)
-            .map(|m| {
-                let mut list: Vec<_> = m.values()
-                    .map(SnapshotMetadata::from)
-                    .collect();
-                list.sort_by(|a, b| b.version.cmp(&a.version));
-                list
-            })
-            .unwrap_or_default()
+    pub async fn list(&self, connection_id: Uuid) -> Result<Vec<SnapshotMetadata>, AppError> {
+        self.backend.list(connection_id).await
     }
 
     /// Set baseline snapshot (the "production" reference)
     pub async fn set_baseline(&self, connection_id: Uuid, snapshot_id: Uuid) -> Result<(), AppError> {
         // Verify snapshot exists
-        if self.get_by_id(snapshot_id).await.is_none() {
+        if self.get_by_id(snapshot_id).await?.is_none() {
             return Err(AppError::NotFound("Snapshot not found".to_string()));
         }
         
-        let mut baselines = self.baselines.write().await;
-        baselines.insert(connection_id, snapshot_id);
+        self.backend.set_baseline(connection_id, snapshot_id).await?;
         
         tracing::info!("Set baseline for connection {} to snapshot {}", connection_id, snapshot_id);
         Ok(())
     }
 
     /// Get baseline snapshot for a connection
-    pub async fn get_baseline(&self, connection_id: Uuid) -> Option<SchemaSnapshot> {
-        let baselines = self.baselines.read().await;
-        let baseline_id = baselines.get(&connection_id)?;
-        self.get_by_id(*baseline_id).await
+    pub async fn get_baseline(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.backend.baseline(connection_id).await
     }
 
-    /// Delete old snapshots, keeping the last N versions
-    pub async fn prune(&self, connection_id: Uuid, keep_versions: usize) -> Result<usize, AppError> {
-        let mut snapshots = self.snapshots.write().await;
+    /// Delete exactly the given versions, e.g. the ones just archived
+    pub async fn remove_versions(&self, connection_id: Uuid, versions: &[u64]) -> Result<usize, AppError> {
+        let removed_count = self.backend.remove_versions(connection_id, versions).await?;
+        // The cached latest may be among them; reload it on the next read
+        // rather than serve a snapshot that no longer exists
+        self.latest.write().unwrap_or_else(|e| e.into_inner()).remove(&connection_id);
         
-        if let Some(connection_snapshots) = snapshots.get_mut(&connection_id) {
-            if connection_snapshots.len() <= keep_versions {
-                return Ok(0);
-            }
-            
-            // Get versions sorted descending
-            let mut versions: Vec<_> = connection_snapshots.keys().copied().collect();
-            versions.sort_by(|a, b| b.cmp(a));
-            
-            // Remove old versions
-            let to_remove: Vec<_> = versions.into_iter().skip(keep_versions).collect();
-            let removed_count = to_remove.len();
-            
-            for v in to_remove {
-                connection_snapshots.remove(&v);
-            }
-            
+        if removed_count > 0 {
             tracing::info!("Pruned {} old snapshots for connection {}", removed_count, connection_id);
-            Ok(removed_count)
-        } else {
-            Ok(0)
         }
+        Ok(removed_count)
     }
 
     /// Compare two snapshots by version number
@@ -204,12 +184,12 @@ impl SnapshotStore {
     ) -> Result<(SchemaSnapshot, SchemaSnapshot), AppError> {
         let from = self
             .get_version(connection_id, from_version)
-            .await
+            .await?
             .ok_or_else(|| AppError::NotFound(format!("Snapshot v{} not found", from_version)))?;
         
         let to = self
             .get_version(connection_id, to_version)
-            .await
+            .await?
             .ok_or_else(|| AppError::NotFound(format!("Snapshot v{} not found", to_version)))?;
         
         Ok((from, to))
@@ -0,0 +1,265 @@
+//! Filtered and streamed snapshot and diff responses
+//!
+//! Schemas with tens of thousands of columns make a single JSON document too
+//! big to build and parse comfortably. Diff and snapshot endpoints therefore
+//! take filters that are applied before anything is serialized, and clients
+//! sending `Accept: application/x-ndjson` get a chunked response with one
+//! JSON object per line, each serialized only as it is sent.
+//!
+//! Every line carries a `type`: a header first, then one line per object,
+//! then an `end` line with the number of objects sent. A stream without an
+//! `end` line was cut short.
+
+use crate::introspection::SchemaSnapshot;
+use crate::snapshot::diff::{ObjectType, RiskLevel};
+use crate::snapshot::metadata_diff::MetadataChange;
+use crate::snapshot::{ChangeType, SchemaDiff, SchemaDiffItem};
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+
+pub const NDJSON: &str = "application/x-ndjson";
+
+/// Whether the client asked for newline-delimited JSON
+pub fn wants_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.split(',').any(|t| t.trim().starts_with(NDJSON)))
+}
+
+/// Filters on the objects a response includes; unset filters match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ObjectFilter {
+    pub schema: Option<String>,
+    /// Exact table name, or a prefix when it ends in `*`
+    pub table: Option<String>,
+    pub object_type: Option<ObjectType>,
+    pub change_type: Option<ChangeType>,
+    /// Diff changes at or above this risk
+    pub min_risk: Option<RiskLevel>,
+    pub breaking_only: bool,
+}
+
+fn risk_rank(risk: RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Safe => 0,
+        RiskLevel::Low => 1,
+        RiskLevel::Medium => 2,
+        RiskLevel::High => 3,
+        RiskLevel::Critical => 4,
+    }
+}
+
+impl ObjectFilter {
+    pub fn is_empty(&self) -> bool {
+        self.schema.is_none()
+            && self.table.is_none()
+            && self.object_type.is_none()
+            && self.change_type.is_none()
+            && self.min_risk.is_none()
+            && !self.breaking_only
+    }
+
+    fn matches_table(&self, schema: &str, table: &str) -> bool {
+        let schema_ok = self.schema.as_deref().is_none_or(|s| s == schema);
+        let table_ok = match self.table.as_deref() {
+            None => true,
+            Some(pattern) => match pattern.strip_suffix('*') {
+                Some(prefix) => table.starts_with(prefix),
+                None => table == pattern,
+            },
+        };
+        schema_ok && table_ok
+    }
+
+    /// Diff changes are matched on `schema.table[.column]` object paths
+    pub fn matches_change(&self, item: &SchemaDiffItem) -> bool {
+        if self.object_type.is_some_and(|t| t != item.object_type)
+            || self.change_type.is_some_and(|t| t != item.change_type)
+            || self.min_risk.is_some_and(|r| risk_rank(item.risk_level) < risk_rank(r))
+            || (self.breaking_only && !item.is_breaking)
+        {
+            return false;
+        }
+        self.matches_path(&item.object_path)
+    }
+
+    /// Governance metadata changes are never breaking
+    pub fn matches_metadata(&self, item: &MetadataChange) -> bool {
+        if self.object_type.is_some_and(|t| t != item.object_type)
+            || self.change_type.is_some_and(|t| t != item.change_type)
+            || self.min_risk.is_some_and(|r| risk_rank(item.risk_level) < risk_rank(r))
+            || self.breaking_only
+        {
+            return false;
+        }
+        self.matches_path(&item.object_path)
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        if self.schema.is_none() && self.table.is_none() {
+            return true;
+        }
+        let mut parts = path.splitn(3, '.');
+        match (parts.next(), parts.next()) {
+            (Some(schema), Some(table)) => self.matches_table(schema, table),
+            (Some(schema), None) => self.table.is_none() && self.matches_table(schema, ""),
+            _ => false,
+        }
+    }
+
+    /// Narrow a diff to the matching changes, recomputing its summary
+    pub fn apply_to_diff(&self, diff: SchemaDiff) -> SchemaDiff {
+        if self.is_empty() {
+            return diff;
+        }
+        let ignored_changes = diff.ignored_changes;
+        let changes = diff.changes.into_iter().filter(|c| self.matches_change(c)).collect();
+        let metadata = diff.metadata_changes.into_iter().filter(|c| self.matches_metadata(c)).collect();
+        let mut filtered =
+            SchemaDiff::from_changes(diff.from_version, diff.to_version, diff.from_checksum, diff.to_checksum, changes)
+                .with_metadata(metadata);
+        filtered.ignored_changes = ignored_changes;
+        filtered
+    }
+
+    /// Narrow a snapshot to the matching schemas and tables
+    pub fn apply_to_snapshot(&self, mut snapshot: SchemaSnapshot) -> SchemaSnapshot {
+        if self.schema.is_none() && self.table.is_none() {
+            return snapshot;
+        }
+        if let Some(schema) = &self.schema {
+            snapshot.schemas.retain(|n| &n.name == schema);
+        }
+        snapshot.tables.retain(|t| self.matches_table(&t.schema, &t.name));
+        snapshot.foreign_keys.retain(|fk| self.matches_table(&fk.source_schema, &fk.source_table));
+        snapshot.indexes.retain(|i| self.matches_table(&i.schema, &i.table));
+        snapshot
+    }
+}
+
+/// One NDJSON line: the value's fields plus its `type`
+fn line(kind: &str, value: impl Serialize) -> Vec<u8> {
+    let mut value = serde_json::to_value(value).unwrap_or(Value::Null);
+    match value.as_object_mut() {
+        Some(object) => {
+            object.insert("type".to_string(), Value::String(kind.to_string()));
+        }
+        None => value = json!({ "type": kind, "value": value }),
+    }
+    let mut bytes = serde_json::to_vec(&value).unwrap_or_default();
+    bytes.push(b'\n');
+    bytes
+}
+
+/// Chunked response serializing `lines` one at a time
+fn ndjson_response(lines: impl Iterator<Item = Vec<u8>> + Send + 'static) -> Response {
+    let stream = futures_util::stream::iter(lines.map(Ok::<_, Infallible>));
+    let mut response = Body::from_stream(stream).into_response();
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(NDJSON));
+    response
+}
+
+/// Header, one line per change and per metadata change, trailing sections
+/// (e.g. rule results) and an end line
+pub fn diff_response(diff: SchemaDiff, trailers: Vec<(&'static str, Value)>) -> Response {
+    let header = json!({
+        "fromVersion": diff.from_version,
+        "toVersion": diff.to_version,
+        "fromChecksum": diff.from_checksum,
+        "toChecksum": diff.to_checksum,
+        "summary": diff.summary,
+        "overallRisk": diff.overall_risk,
+        "hasBreakingChanges": diff.has_breaking_changes,
+        "ignoredChanges": diff.ignored_changes,
+        "metadataRisk": diff.metadata_risk,
+    });
+    let count = diff.changes.len();
+    let metadata_count = diff.metadata_changes.len();
+
+    let lines = std::iter::once(line("header", header))
+        .chain(diff.changes.into_iter().map(|change| line("change", change)))
+        .chain(diff.metadata_changes.into_iter().map(|change| line("metadataChange", change)))
+        .chain(trailers.into_iter().map(|(kind, value)| line(kind, value)))
+        .chain(std::iter::once_with(move || {
+            line("end", json!({ "changes": count, "metadataChanges": metadata_count }))
+        }));
+    ndjson_response(lines)
+}
+
+/// Header, then schemas, extensions, tables, foreign keys and indexes, then an end line
+pub fn snapshot_response(snapshot: SchemaSnapshot) -> Response {
+    let header = json!({
+        "id": snapshot.id,
+        "connectionId": snapshot.connection_id,
+        "version": snapshot.version,
+        "capturedAt": snapshot.captured_at,
+        "database": snapshot.database,
+        "checksum": snapshot.checksum,
+        "tables": snapshot.tables.len(),
+        "foreignKeys": snapshot.foreign_keys.len(),
+        "indexes": snapshot.indexes.len(),
+    });
+    let count = snapshot.schemas.len()
+        + snapshot.extensions.len()
+        + snapshot.tables.len()
+        + snapshot.foreign_keys.len()
+        + snapshot.indexes.len();
+
+    let lines = std::iter::once(line("header", header))
+        .chain(snapshot.schemas.into_iter().map(|s| line("schema", s)))
+        .chain(snapshot.extensions.into_iter().map(|e| line("extension", e)))
+        .chain(snapshot.tables.into_iter().map(|t| line("table", t)))
+        .chain(snapshot.foreign_keys.into_iter().map(|fk| line("foreignKey", fk)))
+        .chain(snapshot.indexes.into_iter().map(|i| line("index", i)))
+        .chain(std::iter::once_with(move || line("end", json!({ "objects": count }))));
+    ndjson_response(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, object_type: ObjectType, risk_level: RiskLevel) -> SchemaDiffItem {
+        SchemaDiffItem {
+            change_type: ChangeType::Added,
+            object_type,
+            object_path: path.to_string(),
+            description: String::new(),
+            before: None,
+            after: None,
+            risk_level,
+            is_breaking: false,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_table_prefix_and_risk() {
+        let filter = ObjectFilter {
+            schema: Some("public".to_string()),
+            table: Some("order*".to_string()),
+            min_risk: Some(RiskLevel::Medium),
+            ..Default::default()
+        };
+        assert!(filter.matches_change(&item("public.orders.total", ObjectType::Column, RiskLevel::High)));
+        assert!(!filter.matches_change(&item("public.orders.total", ObjectType::Column, RiskLevel::Low)));
+        assert!(!filter.matches_change(&item("public.users.email", ObjectType::Column, RiskLevel::High)));
+        assert!(!filter.matches_change(&item("audit.orders", ObjectType::Table, RiskLevel::High)));
+    }
+
+    #[test]
+    fn test_line_tags_type() {
+        let bytes = line("change", item("public.users", ObjectType::Table, RiskLevel::Safe));
+        assert_eq!(bytes.last(), Some(&b'\n'));
+        let value: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value["type"], "change");
+        assert_eq!(value["objectPath"], "public.users");
+    }
+}
@@ -1,15 +1,39 @@
 //! Application state management
 //!
 //! Contains shared state accessible across all handlers.
-//! DATABASE-ONLY: All storage is backed by PostgreSQL, no in-memory fallbacks.
+//! Users and projects always live in PostgreSQL; governance stores follow
+//! `STORAGE_BACKEND` (PostgreSQL unless set to `memory`).
 
+use crate::auth::ImpersonationRegistry;
+use crate::config::{AuditExportConfig, PoolConfig, ProposalPolicyConfig, RetentionConfig, StorageBackend};
 use crate::connection::ConnectionManager;
+use crate::crypto::ConnectionCipher;
 use crate::db::{UserService, ProjectService};
-use crate::pipeline::MetadataStore;
-use crate::proposal::ProposalStore;
-use crate::snapshot::{SnapshotStore, RulesEngine};
+use crate::error::AppError;
+use crate::features::FeatureFlags;
+use crate::i18n::Translations;
+use crate::idempotency::IdempotencyStore;
+use crate::introspection::SchemaSnapshot;
+use crate::notifications::Notifier;
+use crate::pipeline::backup::BackupHook;
+use crate::pipeline::sandbox::SandboxRegistry;
+use crate::pipeline::summary::LlmSummarizer;
+use crate::pipeline::journal::ExecutionJournal;
+use crate::pipeline::plan::PlanStore;
+use crate::pipeline::mirror::SemanticMapStore;
+use crate::pipeline::activity::ActivityHub;
+use crate::pipeline::presence::PresenceHub;
+use crate::pipeline::progress::ExecutionMonitor;
+use crate::pipeline::{MetadataStore, ProposalService, ShareLinkRegistry};
+use crate::quota::{ProjectQuota, QuotaService};
+use crate::snapshot::{SnapshotArchive, SnapshotStore, RulesEngine};
+use crate::storage::{
+    PostgresExecutionJournalBackend, PostgresExecutionPlanBackend, PostgresIdempotencyBackend, PostgresMetadataBackend, PostgresProposalBackend,
+    PostgresSnapshotBackend,
+};
 use deadpool_postgres::Pool;
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// Application state shared across all handlers
 /// All operations require a valid database connection
@@ -29,8 +53,8 @@ pub struct AppState {
     /// Governance Pipeline: Metadata store for proposals, snapshots, and audit logs
     pub metadata: MetadataStore,
     
-    /// Proposal management store (has internal locking)
-    pub proposals: ProposalStore,
+    /// Governance Pipeline: Full proposals backing the /api/proposals routes
+    pub pipeline_proposals: ProposalService,
     
     /// Schema snapshot store for versioned schema tracking
     pub snapshots: SnapshotStore,
@@ -38,28 +62,198 @@ pub struct AppState {
     /// Rules engine for governance guardrails
     pub rules: RulesEngine,
     
+    /// Optional S3-compatible archive for old snapshots and execution artifacts
+    pub archive: Option<SnapshotArchive>,
+    
+    /// Email notifications (no-op when SMTP is not configured)
+    pub notifier: Notifier,
+    
+    /// Active admin impersonation sessions
+    pub impersonations: ImpersonationRegistry,
+    
+    /// Read-only proposal links for people without accounts
+    pub share_links: ShareLinkRegistry,
+
+    /// Disposable databases built from snapshots
+    pub sandboxes: SandboxRegistry,
+
+    /// Who has each proposal open, and edit locks on drafts
+    pub presence: PresenceHub,
+
+    /// Comments, approvals, status changes, risk analyses and execution progress as they happen
+    pub activity: ActivityHub,
+    
+    /// Stored results of requests sent with an Idempotency-Key
+    pub idempotency: IdempotencyStore,
+    
+    /// Per-project usage limits and executions in flight
+    pub quotas: QuotaService,
+    
+    /// Translations of risk and rule messages
+    pub translations: Translations,
+
+    /// Optional backup taken before destructive executions
+    pub backup: Option<BackupHook>,
+
+    /// Live progress of running executions
+    pub executions: ExecutionMonitor,
+
+    /// Durable state transitions of executions, for recovery after a crash
+    pub journal: ExecutionJournal,
+
+    /// Staged executions of approved proposals
+    pub plans: PlanStore,
+
+    /// Deployment-wide feature flags; projects override them
+    pub features: FeatureFlags,
+
+    /// Language model that rewrites proposal summaries, if configured
+    pub summarizer: Option<Arc<LlmSummarizer>>,
+
+    /// Format and signing key of audit log exports
+    pub audit_export: AuditExportConfig,
+
+    /// Layered semantic maps, by connection
+    pub semantic_maps: SemanticMapStore,
+
+    /// How long audit entries and execution records are kept
+    pub retention: RetentionConfig,
+
+    /// Seals connection strings saved to projects
+    pub connection_cipher: ConnectionCipher,
+    
     /// JWT secret key for token signing
     pub jwt_secret: String,
 }
 
 impl AppState {
     /// Create new application state with database pool (the only way)
-    pub fn new(pool: Pool, jwt_secret: String) -> Self {
+    pub fn new(
+        pool: Pool,
+        jwt_secret: String,
+        proposal_policy: ProposalPolicyConfig,
+        storage: StorageBackend,
+        archive: Option<SnapshotArchive>,
+        notifier: Notifier,
+        quotas: ProjectQuota,
+    ) -> Self {
         let user_service = UserService::new(pool.clone());
         let project_service = ProjectService::new(pool.clone());
         
+        let proposal_service = ProposalService::with_policy(proposal_policy);
+        let (metadata, pipeline_proposals, snapshots, idempotency, journal, plans) = match storage {
+            StorageBackend::Memory => (
+                MetadataStore::new(),
+                proposal_service,
+                SnapshotStore::new(),
+                IdempotencyStore::new(),
+                ExecutionJournal::new(),
+                PlanStore::new(),
+            ),
+            StorageBackend::Postgres => (
+                MetadataStore::with_backend(Arc::new(PostgresMetadataBackend::new(pool.clone()))),
+                proposal_service.with_backend(Arc::new(PostgresProposalBackend::new(pool.clone()))),
+                SnapshotStore::with_backend(Arc::new(PostgresSnapshotBackend::new(pool.clone()))),
+                IdempotencyStore::with_backend(Arc::new(PostgresIdempotencyBackend::new(pool.clone()))),
+                ExecutionJournal::with_backend(Arc::new(PostgresExecutionJournalBackend::new(pool.clone()))),
+                PlanStore::with_backend(Arc::new(PostgresExecutionPlanBackend::new(pool.clone()))),
+            ),
+        };
+        
+        let activity = ActivityHub::new();
+
         Self {
             db_pool: pool,
             user_service,
             project_service,
             connections: ConnectionManager::new(),
-            metadata: MetadataStore::new(),
-            proposals: ProposalStore::new(),
-            snapshots: SnapshotStore::new(),
+            metadata,
+            pipeline_proposals: pipeline_proposals.with_activity(activity.clone()),
+            snapshots,
             rules: RulesEngine::new(),
+            archive,
+            notifier,
+            impersonations: ImpersonationRegistry::new(),
+            share_links: ShareLinkRegistry::new(),
+            sandboxes: SandboxRegistry::new(),
+            presence: PresenceHub::new(),
+            activity: activity.clone(),
+            idempotency,
+            quotas: QuotaService::new(quotas),
+            translations: Translations::new(),
+            backup: None,
+            executions: ExecutionMonitor::new().with_activity(activity),
+            journal,
+            plans,
+            features: FeatureFlags::default(),
+            summarizer: None,
+            audit_export: AuditExportConfig::default(),
+            semantic_maps: SemanticMapStore::new(),
+            retention: RetentionConfig::default(),
+            connection_cipher: ConnectionCipher::development(),
             jwt_secret,
         }
     }
+    
+    /// Use translations loaded from the locales directory
+    pub fn with_translations(mut self, translations: Translations) -> Self {
+        self.translations = translations;
+        self
+    }
+
+    /// Back up before destructive executions
+    pub fn with_backup(mut self, backup: Option<BackupHook>) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// Use the feature flags from config
+    pub fn with_features(mut self, features: FeatureFlags) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Have proposal summaries rewritten by a language model
+    pub fn with_summarizer(mut self, summarizer: Option<LlmSummarizer>) -> Self {
+        self.summarizer = summarizer.map(Arc::new);
+        self
+    }
+
+    /// Use rules with the per-environment severities from config
+    pub fn with_rules(mut self, rules: RulesEngine) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Export the audit log as configured
+    pub fn with_audit_export(mut self, audit_export: AuditExportConfig) -> Self {
+        self.audit_export = audit_export;
+        self
+    }
+
+    /// Prune audit entries and execution records as configured
+    pub fn with_retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Seal saved connection strings with the configured keys
+    pub fn with_connection_cipher(mut self, cipher: ConnectionCipher) -> Self {
+        self.connection_cipher = cipher;
+        self
+    }
+
+    /// Size, warm-up and idle teardown of the pools opened for user databases
+    pub fn with_target_pools(mut self, policy: PoolConfig) -> Self {
+        self.connections = ConnectionManager::with_pool_config(policy);
+        self
+    }
+    
+    /// Latest snapshot of a connection, limited to the connection's schema scope
+    pub async fn latest_scoped_snapshot(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        let scope = self.connections.schema_scope(connection_id).await;
+        Ok(self.snapshots.get_latest(connection_id).await?.map(|s| s.scoped(&scope)))
+    }
 }
 
 /// Type alias for shared state
@@ -0,0 +1,540 @@
+//! In-memory storage backends
+//!
+//! Everything is lost on restart; used by tests and `STORAGE_BACKEND=memory`.
+
+use super::{
+    ExecutionJournalBackend, ExecutionPlanBackend, IdempotencyBackend, MetadataBackend, ProposalBackend,
+    ProposalChange, SnapshotBackend,
+};
+use crate::error::AppError;
+use crate::idempotency::{IdempotencyRecord, StoredResponse};
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::journal::{ExecutionRecord, ExecutionState};
+use crate::pipeline::metadata::{chain_head, AuditEntry, ProposalSummary};
+use crate::pipeline::plan::{ExecutionPlan, PlanStatus};
+use crate::pipeline::retention::LegalHold;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::snapshot::store::SnapshotMetadata;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+
+/// Independently locked shards per map
+const SHARDS: usize = 16;
+
+/// A map split into independently locked shards. Writers to keys in
+/// different shards never wait on each other, so one busy connection or
+/// proposal does not stall the rest; listing visits the shards in turn.
+pub(crate) struct ShardedMap<K, V> {
+    shards: Box<[RwLock<HashMap<K, V>>]>,
+}
+
+impl<K: Hash + Eq, V> ShardedMap<K, V> {
+    fn shard(&self, key: &K) -> &RwLock<HashMap<K, V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    /// Shared access to the shard holding `key`
+    pub(crate) async fn read(&self, key: &K) -> RwLockReadGuard<'_, HashMap<K, V>> {
+        self.shard(key).read().await
+    }
+
+    /// Exclusive access to the shard holding `key`
+    pub(crate) async fn write(&self, key: &K) -> RwLockWriteGuard<'_, HashMap<K, V>> {
+        self.shard(key).write().await
+    }
+
+    pub(crate) async fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.read(key).await.get(key).cloned()
+    }
+
+    pub(crate) async fn insert(&self, key: K, value: V) {
+        self.write(&key).await.insert(key, value);
+    }
+
+    /// Clones of the values matching `filter`, one shard at a time
+    pub(crate) async fn collect(&self, filter: impl Fn(&V) -> bool) -> Vec<V>
+    where
+        V: Clone,
+    {
+        let mut values = Vec::new();
+        for shard in self.shards.iter() {
+            values.extend(shard.read().await.values().filter(|v| filter(v)).cloned());
+        }
+        values
+    }
+}
+
+impl<K, V> Default for ShardedMap<K, V> {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| RwLock::new(HashMap::new())).collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryMetadataBackend {
+    proposals: ShardedMap<Uuid, ProposalSummary>,
+    audit_log: RwLock<Vec<AuditEntry>>,
+    legal_holds: RwLock<Vec<LegalHold>>,
+}
+
+#[async_trait]
+impl MetadataBackend for MemoryMetadataBackend {
+    async fn put_proposal(&self, proposal: ProposalSummary) -> Result<(), AppError> {
+        self.proposals.insert(proposal.id, proposal).await;
+        Ok(())
+    }
+
+    async fn get_proposal(&self, id: Uuid) -> Result<Option<ProposalSummary>, AppError> {
+        Ok(self.proposals.get(&id).await)
+    }
+
+    async fn list_proposals(&self) -> Result<Vec<ProposalSummary>, AppError> {
+        Ok(self.proposals.collect(|_| true).await)
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<(), AppError> {
+        let mut audit_log = self.audit_log.write().await;
+        let entry = entry.chained(chain_head(&audit_log));
+        audit_log.push(entry);
+        Ok(())
+    }
+
+    async fn list_audit_entries(&self) -> Result<Vec<AuditEntry>, AppError> {
+        Ok(self.audit_log.read().await.clone())
+    }
+
+    async fn list_audit_entries_by_actor(&self, actor: &str, since: DateTime<Utc>) -> Result<Vec<AuditEntry>, AppError> {
+        let audit_log = self.audit_log.read().await;
+        Ok(audit_log
+            .iter()
+            .filter(|e| e.actor == actor && e.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+
+    async fn prune_audit_entries(&self, ids: &[Uuid], at: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut audit_log = self.audit_log.write().await;
+        let mut pruned = 0;
+        for entry in audit_log.iter_mut().filter(|e| e.pruned_at.is_none() && e.payload_digest.is_some() && ids.contains(&e.id)) {
+            entry.prune(at);
+            pruned += 1;
+        }
+        Ok(pruned)
+    }
+
+    async fn put_legal_hold(&self, hold: &LegalHold) -> Result<(), AppError> {
+        let mut holds = self.legal_holds.write().await;
+        match holds.iter_mut().find(|h| h.id == hold.id) {
+            Some(existing) => *existing = hold.clone(),
+            None => holds.push(hold.clone()),
+        }
+        Ok(())
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHold>, AppError> {
+        Ok(self.legal_holds.read().await.clone())
+    }
+}
+
+/// Snapshots of one connection
+#[derive(Default)]
+struct ConnectionSnapshots {
+    /// Version -> Snapshot
+    versions: HashMap<u64, SchemaSnapshot>,
+    /// Latest version number; keeps counting after a prune
+    latest: u64,
+    /// Baseline snapshot ID (the "production" state)
+    baseline: Option<Uuid>,
+}
+
+/// Snapshots are sharded by connection, so saving one connection's snapshot
+/// does not block reads of another's
+#[derive(Default)]
+pub struct MemorySnapshotBackend {
+    connections: ShardedMap<Uuid, ConnectionSnapshots>,
+    /// Snapshot ID -> (Connection ID, Version)
+    locations: ShardedMap<Uuid, (Uuid, u64)>,
+}
+
+impl MemorySnapshotBackend {
+    async fn find(&self, snapshot_id: Uuid) -> Option<SchemaSnapshot> {
+        let (connection_id, version) = self.locations.get(&snapshot_id).await?;
+        let shard = self.connections.read(&connection_id).await;
+        shard.get(&connection_id)?.versions.get(&version).cloned()
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for MemorySnapshotBackend {
+    async fn insert_next(&self, mut snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let connection_id = snapshot.connection_id;
+        {
+            let mut shard = self.connections.write(&connection_id).await;
+            let connection = shard.entry(connection_id).or_default();
+            connection.latest += 1;
+            snapshot.version = connection.latest;
+            connection.versions.insert(snapshot.version, snapshot.clone());
+        }
+        self.locations.insert(snapshot.id, (connection_id, snapshot.version)).await;
+        Ok(snapshot)
+    }
+
+    async fn insert_existing(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let connection_id = snapshot.connection_id;
+        {
+            let mut shard = self.connections.write(&connection_id).await;
+            let connection = shard.entry(connection_id).or_default();
+
+            if connection.versions.contains_key(&snapshot.version) {
+                return Err(AppError::Conflict(format!(
+                    "Snapshot v{} is already present for connection {}",
+                    snapshot.version, snapshot.connection_id
+                )));
+            }
+            connection.versions.insert(snapshot.version, snapshot.clone());
+            connection.latest = connection.latest.max(snapshot.version);
+        }
+        self.locations.insert(snapshot.id, (connection_id, snapshot.version)).await;
+        Ok(snapshot)
+    }
+
+    async fn latest(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        let shard = self.connections.read(&connection_id).await;
+        Ok(shard
+            .get(&connection_id)
+            .and_then(|c| c.versions.get(&c.latest))
+            .cloned())
+    }
+
+    async fn get_version(&self, connection_id: Uuid, version: u64) -> Result<Option<SchemaSnapshot>, AppError> {
+        let shard = self.connections.read(&connection_id).await;
+        Ok(shard.get(&connection_id).and_then(|c| c.versions.get(&version)).cloned())
+    }
+
+    async fn latest_before(&self, connection_id: Uuid, at: DateTime<Utc>) -> Result<Option<SchemaSnapshot>, AppError> {
+        let shard = self.connections.read(&connection_id).await;
+        Ok(shard.get(&connection_id).and_then(|c| {
+            c.versions
+                .values()
+                .filter(|s| s.captured_at <= at)
+                .max_by_key(|s| s.version)
+                .cloned()
+        }))
+    }
+
+    async fn get_by_id(&self, snapshot_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        Ok(self.find(snapshot_id).await)
+    }
+
+    async fn list(&self, connection_id: Uuid) -> Result<Vec<SnapshotMetadata>, AppError> {
+        let shard = self.connections.read(&connection_id).await;
+        let mut list: Vec<_> = shard
+            .get(&connection_id)
+            .map(|c| c.versions.values().map(SnapshotMetadata::from).collect())
+            .unwrap_or_default();
+        list.sort_by(|a, b| b.version.cmp(&a.version));
+        Ok(list)
+    }
+
+    async fn set_baseline(&self, connection_id: Uuid, snapshot_id: Uuid) -> Result<(), AppError> {
+        let mut shard = self.connections.write(&connection_id).await;
+        shard.entry(connection_id).or_default().baseline = Some(snapshot_id);
+        Ok(())
+    }
+
+    async fn baseline(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        let baseline = {
+            let shard = self.connections.read(&connection_id).await;
+            shard.get(&connection_id).and_then(|c| c.baseline)
+        };
+        Ok(match baseline {
+            Some(id) => self.find(id).await,
+            None => None,
+        })
+    }
+
+    async fn remove_versions(&self, connection_id: Uuid, versions: &[u64]) -> Result<usize, AppError> {
+        let removed: Vec<Uuid> = {
+            let mut shard = self.connections.write(&connection_id).await;
+            let Some(connection) = shard.get_mut(&connection_id) else {
+                return Ok(0);
+            };
+            versions
+                .iter()
+                .filter_map(|v| connection.versions.remove(v))
+                .map(|s| s.id)
+                .collect()
+        };
+        for id in &removed {
+            self.locations.write(id).await.remove(id);
+        }
+        Ok(removed.len())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryProposalBackend {
+    proposals: RwLock<HashMap<Uuid, SchemaProposal>>,
+}
+
+#[async_trait]
+impl ProposalBackend for MemoryProposalBackend {
+    async fn insert(&self, proposal: &SchemaProposal) -> Result<(), AppError> {
+        self.proposals.write().await.insert(proposal.id, proposal.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<SchemaProposal>, AppError> {
+        Ok(self.proposals.read().await.get(&id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<SchemaProposal>, AppError> {
+        Ok(self.proposals.read().await.values().cloned().collect())
+    }
+
+    async fn update<'a>(&self, id: Uuid, change: ProposalChange<'a>) -> Result<SchemaProposal, AppError> {
+        let mut proposals = self.proposals.write().await;
+        let stored = proposals
+            .get_mut(&id)
+            .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+        // Changed on a copy, so a failed change leaves nothing half done
+        let mut proposal = stored.clone();
+        change(&mut proposal)?;
+        *stored = proposal.clone();
+        Ok(proposal)
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryIdempotencyBackend {
+    records: RwLock<HashMap<String, IdempotencyRecord>>,
+}
+
+#[async_trait]
+impl IdempotencyBackend for MemoryIdempotencyBackend {
+    async fn reserve(&self, record: IdempotencyRecord, now: DateTime<Utc>) -> Result<Option<IdempotencyRecord>, AppError> {
+        let mut records = self.records.write().await;
+        if let Some(existing) = records.get(&record.key).filter(|r| r.expires_at > now) {
+            return Ok(Some(existing.clone()));
+        }
+        records.insert(record.key.clone(), record);
+        Ok(None)
+    }
+
+    async fn complete(&self, key: &str, response: StoredResponse, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        if let Some(record) = self.records.write().await.get_mut(key) {
+            record.response = Some(response);
+            record.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), AppError> {
+        self.records.write().await.remove(key);
+        Ok(())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut records = self.records.write().await;
+        let before = records.len();
+        records.retain(|_, r| r.expires_at > now);
+        Ok(before - records.len())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryExecutionJournalBackend {
+    records: RwLock<HashMap<Uuid, ExecutionRecord>>,
+}
+
+#[async_trait]
+impl ExecutionJournalBackend for MemoryExecutionJournalBackend {
+    async fn put(&self, record: &ExecutionRecord) -> Result<(), AppError> {
+        self.records.write().await.insert(record.execution_id, record.clone());
+        Ok(())
+    }
+
+    async fn get(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, AppError> {
+        Ok(self.records.read().await.get(&execution_id).cloned())
+    }
+
+    async fn list_by_state(&self, state: ExecutionState) -> Result<Vec<ExecutionRecord>, AppError> {
+        let records = self.records.read().await;
+        Ok(records.values().filter(|r| r.state == state).cloned().collect())
+    }
+
+    async fn list_updated_before(&self, before: DateTime<Utc>) -> Result<Vec<ExecutionRecord>, AppError> {
+        let records = self.records.read().await;
+        Ok(records.values().filter(|r| r.updated_at < before).cloned().collect())
+    }
+
+    async fn delete(&self, execution_ids: &[Uuid]) -> Result<usize, AppError> {
+        let mut records = self.records.write().await;
+        Ok(execution_ids.iter().filter(|id| records.remove(id).is_some()).count())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryExecutionPlanBackend {
+    plans: RwLock<HashMap<Uuid, ExecutionPlan>>,
+}
+
+#[async_trait]
+impl ExecutionPlanBackend for MemoryExecutionPlanBackend {
+    async fn put(&self, plan: &ExecutionPlan) -> Result<(), AppError> {
+        self.plans.write().await.insert(plan.id, plan.clone());
+        Ok(())
+    }
+
+    async fn get(&self, plan_id: Uuid) -> Result<Option<ExecutionPlan>, AppError> {
+        Ok(self.plans.read().await.get(&plan_id).cloned())
+    }
+
+    async fn list_for_proposal(&self, proposal_id: Uuid) -> Result<Vec<ExecutionPlan>, AppError> {
+        let plans = self.plans.read().await;
+        let mut listed: Vec<ExecutionPlan> = plans.values().filter(|p| p.proposal_id == proposal_id).cloned().collect();
+        listed.sort_by_key(|p| p.created_at);
+        Ok(listed)
+    }
+
+    async fn list_by_status(&self, status: PlanStatus) -> Result<Vec<ExecutionPlan>, AppError> {
+        let plans = self.plans.read().await;
+        Ok(plans.values().filter(|p| p.status == status).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardedMap;
+    use crate::introspection::SchemaSnapshot;
+    use crate::pipeline::metadata::ProposalSummary;
+    use crate::pipeline::proposal::SchemaProposal;
+    use crate::pipeline::MetadataStore;
+    use crate::snapshot::SnapshotStore;
+    use chrono::Utc;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn snapshot(connection_id: Uuid, version: u64) -> SchemaSnapshot {
+        SchemaSnapshot {
+            id: Uuid::new_v4(),
+            connection_id,
+            version,
+            captured_at: Utc::now(),
+            database: Default::default(),
+            schemas: Vec::new(),
+            extensions: Vec::new(),
+            types: None,
+            views: None,
+            tables: Vec::new(),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            checksum: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_versions_survive_removal_and_restore() {
+        let store = SnapshotStore::new();
+        let connection_id = Uuid::new_v4();
+
+        for _ in 0..3 {
+            store.save(snapshot(connection_id, 0)).await.unwrap();
+        }
+        assert_eq!(store.remove_versions(connection_id, &[1, 2]).await.unwrap(), 2);
+        assert_eq!(store.list(connection_id).await.unwrap().len(), 1);
+
+        // Versions keep counting after a prune, and a restore cannot overwrite
+        assert_eq!(store.save(snapshot(connection_id, 0)).await.unwrap().version, 4);
+        assert!(store.restore(snapshot(connection_id, 4)).await.is_err());
+        store.restore(snapshot(connection_id, 1)).await.unwrap();
+        assert_eq!(store.get_latest(connection_id).await.unwrap().unwrap().version, 4);
+        assert_eq!(store.latest_cached(connection_id).await.unwrap().unwrap().version, 4);
+        store.save(snapshot(connection_id, 0)).await.unwrap();
+        assert_eq!(store.latest_cached(connection_id).await.unwrap().unwrap().version, 5);
+
+        let first = store.get_version(connection_id, 1).await.unwrap().unwrap();
+        store.set_baseline(connection_id, first.id).await.unwrap();
+        assert_eq!(store.get_baseline(connection_id).await.unwrap().unwrap().id, first.id);
+        assert!(store.set_baseline(connection_id, Uuid::new_v4()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_removing_versions_spares_snapshots_saved_since() {
+        let store = SnapshotStore::new();
+        let connection_id = Uuid::new_v4();
+        for _ in 0..3 {
+            store.save(snapshot(connection_id, 0)).await.unwrap();
+        }
+
+        // Archiving picks the old versions, then a new snapshot lands before they go
+        let old: Vec<u64> = store.list(connection_id).await.unwrap().iter().skip(1).map(|m| m.version).collect();
+        store.save(snapshot(connection_id, 0)).await.unwrap();
+        assert_eq!(store.remove_versions(connection_id, &old).await.unwrap(), 2);
+        let kept: Vec<u64> = store.list(connection_id).await.unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(kept, vec![4, 3]);
+
+        // Removing the cached latest version drops it from the cache too
+        assert_eq!(store.latest_cached(connection_id).await.unwrap().unwrap().version, 4);
+        store.remove_versions(connection_id, &[4]).await.unwrap();
+        assert!(store.latest_cached(connection_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_writer_only_blocks_its_own_shard() {
+        let map: ShardedMap<Uuid, u32> = ShardedMap::default();
+        let busy = Uuid::new_v4();
+        let ptr = |key: &Uuid| map.shard(key) as *const _;
+        let neighbour = std::iter::repeat_with(Uuid::new_v4).find(|k| ptr(k) == ptr(&busy)).unwrap();
+        let elsewhere = std::iter::repeat_with(Uuid::new_v4).find(|k| ptr(k) != ptr(&busy)).unwrap();
+
+        let _guard = map.write(&busy).await;
+        let wait = Duration::from_millis(50);
+        assert!(tokio::time::timeout(wait, map.get(&elsewhere)).await.is_ok());
+        assert!(tokio::time::timeout(wait, map.get(&neighbour)).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_load_keeps_versions_consistent() {
+        const CONNECTIONS: usize = 32;
+        const SNAPSHOTS: usize = 40;
+
+        let snapshots = Arc::new(SnapshotStore::new());
+        let metadata = Arc::new(MetadataStore::new());
+        let mut tasks = Vec::new();
+        for _ in 0..CONNECTIONS {
+            let snapshots = snapshots.clone();
+            let metadata = metadata.clone();
+            tasks.push(tokio::spawn(async move {
+                let connection_id = Uuid::new_v4();
+                for i in 0..SNAPSHOTS {
+                    snapshots.save(snapshot(connection_id, 0)).await.unwrap();
+                    let proposal = SchemaProposal::new(connection_id, format!("p{}", i), String::new(), "load".to_string());
+                    metadata.add_proposal(ProposalSummary::from(&proposal)).await;
+                }
+                connection_id
+            }));
+        }
+
+        for task in tasks {
+            let connection_id = task.await.unwrap();
+            let mut versions: Vec<u64> = snapshots.list(connection_id).await.unwrap().iter().map(|m| m.version).collect();
+            versions.sort_unstable();
+            assert_eq!(versions, (1..=SNAPSHOTS as u64).collect::<Vec<_>>());
+        }
+        assert_eq!(metadata.list_proposals().await.unwrap().len(), CONNECTIONS * SNAPSHOTS);
+    }
+}
@@ -0,0 +1,121 @@
+//! Storage backends
+//!
+//! `MetadataStore`, `SnapshotStore`, `ProposalService`, `IdempotencyStore`,
+//! `ExecutionJournal` and `PlanStore` keep their APIs and the business rules around them; the traits below are
+//! only about where the data lives. The in-memory backends need nothing to
+//! run and back the unit tests, the Postgres backends survive restarts and are
+//! the server default.
+//! The backend is picked with `STORAGE_BACKEND` (`postgres` or `memory`).
+
+mod memory;
+mod postgres;
+
+use crate::error::AppError;
+use crate::idempotency::{IdempotencyRecord, StoredResponse};
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::journal::{ExecutionRecord, ExecutionState};
+use crate::pipeline::metadata::{AuditEntry, ProposalSummary};
+use crate::pipeline::plan::{ExecutionPlan, PlanStatus};
+use crate::pipeline::retention::LegalHold;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::snapshot::store::SnapshotMetadata;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub use memory::{
+    MemoryExecutionJournalBackend, MemoryExecutionPlanBackend, MemoryIdempotencyBackend, MemoryMetadataBackend,
+    MemoryProposalBackend, MemorySnapshotBackend,
+};
+pub use postgres::{
+    PostgresExecutionJournalBackend, PostgresExecutionPlanBackend, PostgresIdempotencyBackend,
+    PostgresMetadataBackend, PostgresProposalBackend, PostgresSnapshotBackend,
+};
+
+/// Persistence for proposal summaries and the audit log
+#[async_trait]
+pub trait MetadataBackend: Send + Sync {
+    /// Insert or replace a proposal summary
+    async fn put_proposal(&self, proposal: ProposalSummary) -> Result<(), AppError>;
+    async fn get_proposal(&self, id: Uuid) -> Result<Option<ProposalSummary>, AppError>;
+    async fn list_proposals(&self) -> Result<Vec<ProposalSummary>, AppError>;
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<(), AppError>;
+    /// All audit entries, oldest first
+    async fn list_audit_entries(&self) -> Result<Vec<AuditEntry>, AppError>;
+    /// One actor's audit entries at or after `since`, oldest first
+    async fn list_audit_entries_by_actor(&self, actor: &str, since: DateTime<Utc>) -> Result<Vec<AuditEntry>, AppError>;
+    /// Prune the content of the given entries; returns how many were not pruned already
+    async fn prune_audit_entries(&self, ids: &[Uuid], at: DateTime<Utc>) -> Result<usize, AppError>;
+    /// Insert or replace a legal hold
+    async fn put_legal_hold(&self, hold: &LegalHold) -> Result<(), AppError>;
+    /// All legal holds, released ones included, oldest first
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHold>, AppError>;
+}
+
+/// Persistence for versioned schema snapshots
+#[async_trait]
+pub trait SnapshotBackend: Send + Sync {
+    /// Store a snapshot as the connection's next version and return it with that version set
+    async fn insert_next(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError>;
+    /// Store a snapshot under its own version; `Conflict` if the version is taken
+    async fn insert_existing(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError>;
+    async fn latest(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError>;
+    async fn get_version(&self, connection_id: Uuid, version: u64) -> Result<Option<SchemaSnapshot>, AppError>;
+    /// Newest snapshot captured at or before `at`
+    async fn latest_before(&self, connection_id: Uuid, at: DateTime<Utc>) -> Result<Option<SchemaSnapshot>, AppError>;
+    async fn get_by_id(&self, snapshot_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError>;
+    /// Metadata for a connection's snapshots, newest version first
+    async fn list(&self, connection_id: Uuid) -> Result<Vec<SnapshotMetadata>, AppError>;
+    async fn set_baseline(&self, connection_id: Uuid, snapshot_id: Uuid) -> Result<(), AppError>;
+    async fn baseline(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError>;
+    /// Delete the given versions of a connection's snapshots; returns how many were removed
+    async fn remove_versions(&self, connection_id: Uuid, versions: &[u64]) -> Result<usize, AppError>;
+}
+
+/// A change applied to one stored proposal; an error leaves it untouched
+pub type ProposalChange<'a> = Box<dyn FnOnce(&mut SchemaProposal) -> Result<(), AppError> + Send + 'a>;
+
+/// Persistence for change proposals
+#[async_trait]
+pub trait ProposalBackend: Send + Sync {
+    async fn insert(&self, proposal: &SchemaProposal) -> Result<(), AppError>;
+    async fn get(&self, id: Uuid) -> Result<Option<SchemaProposal>, AppError>;
+    async fn list(&self) -> Result<Vec<SchemaProposal>, AppError>;
+    /// Apply `change` to the stored proposal with no other write in between;
+    /// `NotFound` if it does not exist
+    async fn update<'a>(&self, id: Uuid, change: ProposalChange<'a>) -> Result<SchemaProposal, AppError>;
+}
+
+/// Persistence for idempotency keys
+#[async_trait]
+pub trait IdempotencyBackend: Send + Sync {
+    /// Store the record unless its key is held by an unexpired record, which is returned instead
+    async fn reserve(&self, record: IdempotencyRecord, now: DateTime<Utc>) -> Result<Option<IdempotencyRecord>, AppError>;
+    async fn complete(&self, key: &str, response: StoredResponse, expires_at: DateTime<Utc>) -> Result<(), AppError>;
+    async fn release(&self, key: &str) -> Result<(), AppError>;
+    /// Delete records expired as of `now`; returns how many were removed
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, AppError>;
+}
+
+/// Persistence for execution state transitions
+#[async_trait]
+pub trait ExecutionJournalBackend: Send + Sync {
+    /// Insert or replace a record
+    async fn put(&self, record: &ExecutionRecord) -> Result<(), AppError>;
+    async fn get(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, AppError>;
+    async fn list_by_state(&self, state: ExecutionState) -> Result<Vec<ExecutionRecord>, AppError>;
+    /// Records last updated before `before`
+    async fn list_updated_before(&self, before: DateTime<Utc>) -> Result<Vec<ExecutionRecord>, AppError>;
+    /// Returns how many records were deleted
+    async fn delete(&self, execution_ids: &[Uuid]) -> Result<usize, AppError>;
+}
+
+/// Persistence for staged execution plans
+#[async_trait]
+pub trait ExecutionPlanBackend: Send + Sync {
+    /// Insert or replace a plan
+    async fn put(&self, plan: &ExecutionPlan) -> Result<(), AppError>;
+    async fn get(&self, plan_id: Uuid) -> Result<Option<ExecutionPlan>, AppError>;
+    async fn list_for_proposal(&self, proposal_id: Uuid) -> Result<Vec<ExecutionPlan>, AppError>;
+    async fn list_by_status(&self, status: PlanStatus) -> Result<Vec<ExecutionPlan>, AppError>;
+}
@@ -0,0 +1,714 @@
+//! PostgreSQL storage backends
+//!
+//! Documents are stored as JSONB next to the columns that are filtered or
+//! sorted on. Tables are created at startup with the rest of the schema.
+
+use super::{
+    ExecutionJournalBackend, ExecutionPlanBackend, IdempotencyBackend, MetadataBackend, ProposalBackend,
+    ProposalChange, SnapshotBackend,
+};
+use crate::error::AppError;
+use crate::idempotency::{IdempotencyRecord, StoredResponse};
+use crate::introspection::SchemaSnapshot;
+use crate::pipeline::journal::{ExecutionRecord, ExecutionState};
+use crate::pipeline::metadata::{AuditAction, AuditEntry, ProposalSummary, GENESIS_HASH};
+use crate::pipeline::plan::{ExecutionPlan, PlanStatus};
+use crate::pipeline::retention::LegalHold;
+use crate::pipeline::proposal::SchemaProposal;
+use crate::snapshot::store::SnapshotMetadata;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+fn db_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(format!("Database error: {}", e))
+}
+
+fn to_json(value: &impl Serialize) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(value).map_err(|e| AppError::Internal(format!("Failed to serialize record: {}", e)))
+}
+
+fn from_json<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, AppError> {
+    serde_json::from_value(value).map_err(|e| AppError::Internal(format!("Invalid stored record: {}", e)))
+}
+
+/// Serialized name of a unit enum variant, e.g. `ProposalStatus::PendingReview` -> "pending_review"
+fn variant_name(value: &impl Serialize) -> Result<String, AppError> {
+    match to_json(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Err(AppError::Internal(format!("Expected a string variant, got {}", other))),
+    }
+}
+
+async fn client(pool: &Pool) -> Result<deadpool_postgres::Object, AppError> {
+    pool.get()
+        .await
+        .map_err(|e| AppError::Internal(format!("Database pool error: {}", e)))
+}
+
+pub struct PostgresMetadataBackend {
+    pool: Pool,
+}
+
+impl PostgresMetadataBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+fn audit_entry_from_row(row: &Row) -> Result<AuditEntry, AppError> {
+    let action: AuditAction = from_json(serde_json::Value::String(row.get(1)))?;
+    Ok(AuditEntry {
+        id: row.get(0),
+        action,
+        actor: row.get(2),
+        target_type: row.get(3),
+        target_id: row.get(4),
+        details: row.get(5),
+        timestamp: row.get(6),
+        project_id: row.get(7),
+        previous_hash: row.get(8),
+        hash: row.get(9),
+        pruned_at: row.get(10),
+        payload_digest: row.get(11),
+    })
+}
+
+/// Advisory lock key serializing appends to the audit chain
+const AUDIT_CHAIN_LOCK: i64 = 0x5346_4155_4449_5401;
+
+const AUDIT_COLUMNS: &str =
+    "id, action, actor, target_type, target_id, details, timestamp, project_id, previous_hash, hash, pruned_at, payload_digest";
+
+#[async_trait]
+impl MetadataBackend for PostgresMetadataBackend {
+    async fn put_proposal(&self, proposal: ProposalSummary) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO governance_proposal_summaries (id, data, updated_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data, updated_at = EXCLUDED.updated_at",
+                &[&proposal.id, &to_json(&proposal)?, &proposal.updated_at],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get_proposal(&self, id: Uuid) -> Result<Option<ProposalSummary>, AppError> {
+        let client = client(&self.pool).await?;
+        let row = client
+            .query_opt("SELECT data FROM governance_proposal_summaries WHERE id = $1", &[&id])
+            .await
+            .map_err(db_error)?;
+        row.map(|r| from_json(r.get(0))).transpose()
+    }
+
+    async fn list_proposals(&self) -> Result<Vec<ProposalSummary>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM governance_proposal_summaries", &[])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+
+    async fn append_audit_entry(&self, entry: AuditEntry) -> Result<(), AppError> {
+        let mut client = client(&self.pool).await?;
+        let tx = client.transaction().await.map_err(db_error)?;
+
+        // One append at a time, so each entry links to the one stored before it
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&AUDIT_CHAIN_LOCK])
+            .await
+            .map_err(db_error)?;
+        let previous: Option<String> = tx
+            .query_opt(
+                "SELECT hash FROM governance_audit_log WHERE hash IS NOT NULL ORDER BY seq DESC LIMIT 1",
+                &[],
+            )
+            .await
+            .map_err(db_error)?
+            .map(|r| r.get(0));
+        let entry = entry.chained(previous.as_deref().unwrap_or(GENESIS_HASH));
+
+        tx.execute(
+            "INSERT INTO governance_audit_log
+             (id, action, actor, target_type, target_id, details, timestamp, project_id, previous_hash, hash,
+              payload_digest)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            &[
+                &entry.id,
+                &variant_name(&entry.action)?,
+                &entry.actor,
+                &entry.target_type,
+                &entry.target_id,
+                &entry.details,
+                &entry.timestamp,
+                &entry.project_id,
+                &entry.previous_hash,
+                &entry.hash,
+                &entry.payload_digest,
+            ],
+        )
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn list_audit_entries(&self) -> Result<Vec<AuditEntry>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query(
+                &format!("SELECT {} FROM governance_audit_log ORDER BY seq", AUDIT_COLUMNS),
+                &[],
+            )
+            .await
+            .map_err(db_error)?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn list_audit_entries_by_actor(&self, actor: &str, since: DateTime<Utc>) -> Result<Vec<AuditEntry>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM governance_audit_log WHERE actor = $1 AND timestamp >= $2 ORDER BY seq",
+                    AUDIT_COLUMNS
+                ),
+                &[&actor, &since],
+            )
+            .await
+            .map_err(db_error)?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn prune_audit_entries(&self, ids: &[Uuid], at: DateTime<Utc>) -> Result<usize, AppError> {
+        let client = client(&self.pool).await?;
+        let pruned = client
+            .execute(
+                "UPDATE governance_audit_log SET actor = '', target_id = '', details = NULL, pruned_at = $2
+                 WHERE id = ANY($1) AND pruned_at IS NULL AND payload_digest IS NOT NULL",
+                &[&ids, &at],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(pruned as usize)
+    }
+
+    async fn put_legal_hold(&self, hold: &LegalHold) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO governance_legal_holds (id, data, placed_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data",
+                &[&hold.id, &to_json(hold)?, &hold.placed_at],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn list_legal_holds(&self) -> Result<Vec<LegalHold>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM governance_legal_holds ORDER BY placed_at", &[])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+}
+
+pub struct PostgresSnapshotBackend {
+    pool: Pool,
+}
+
+impl PostgresSnapshotBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    async fn query_snapshot(&self, sql: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<Option<SchemaSnapshot>, AppError> {
+        let client = client(&self.pool).await?;
+        let row = client.query_opt(sql, params).await.map_err(db_error)?;
+        row.map(|r| from_json(r.get(0))).transpose()
+    }
+}
+
+const INSERT_SNAPSHOT: &str = "INSERT INTO schema_snapshots
+     (id, connection_id, version, captured_at, checksum, table_count, fk_count, index_count, data)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+     ON CONFLICT (connection_id, version) DO NOTHING";
+
+async fn insert_snapshot(tx: &deadpool_postgres::Transaction<'_>, snapshot: &SchemaSnapshot) -> Result<u64, AppError> {
+    tx.execute(
+        INSERT_SNAPSHOT,
+        &[
+            &snapshot.id,
+            &snapshot.connection_id,
+            &(snapshot.version as i64),
+            &snapshot.captured_at,
+            &snapshot.checksum,
+            &(snapshot.tables.len() as i32),
+            &(snapshot.foreign_keys.len() as i32),
+            &(snapshot.indexes.len() as i32),
+            &to_json(snapshot)?,
+        ],
+    )
+    .await
+    .map_err(db_error)
+}
+
+#[async_trait]
+impl SnapshotBackend for PostgresSnapshotBackend {
+    async fn insert_next(&self, mut snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let mut client = client(&self.pool).await?;
+        let tx = client.transaction().await.map_err(db_error)?;
+
+        // The head row serializes concurrent saves for the same connection
+        let row = tx
+            .query_one(
+                "INSERT INTO schema_snapshot_heads (connection_id, latest_version) VALUES ($1, 1)
+                 ON CONFLICT (connection_id) DO UPDATE SET latest_version = schema_snapshot_heads.latest_version + 1
+                 RETURNING latest_version",
+                &[&snapshot.connection_id],
+            )
+            .await
+            .map_err(db_error)?;
+        snapshot.version = row.get::<_, i64>(0) as u64;
+
+        insert_snapshot(&tx, &snapshot).await?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(snapshot)
+    }
+
+    async fn insert_existing(&self, snapshot: SchemaSnapshot) -> Result<SchemaSnapshot, AppError> {
+        let mut client = client(&self.pool).await?;
+        let tx = client.transaction().await.map_err(db_error)?;
+
+        if insert_snapshot(&tx, &snapshot).await? == 0 {
+            return Err(AppError::Conflict(format!(
+                "Snapshot v{} is already present for connection {}",
+                snapshot.version, snapshot.connection_id
+            )));
+        }
+        tx.execute(
+            "INSERT INTO schema_snapshot_heads (connection_id, latest_version) VALUES ($1, $2)
+             ON CONFLICT (connection_id) DO UPDATE
+             SET latest_version = GREATEST(schema_snapshot_heads.latest_version, EXCLUDED.latest_version)",
+            &[&snapshot.connection_id, &(snapshot.version as i64)],
+        )
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(snapshot)
+    }
+
+    async fn latest(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.query_snapshot(
+            "SELECT s.data FROM schema_snapshots s
+             JOIN schema_snapshot_heads h ON h.connection_id = s.connection_id AND h.latest_version = s.version
+             WHERE s.connection_id = $1",
+            &[&connection_id],
+        )
+        .await
+    }
+
+    async fn get_version(&self, connection_id: Uuid, version: u64) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.query_snapshot(
+            "SELECT data FROM schema_snapshots WHERE connection_id = $1 AND version = $2",
+            &[&connection_id, &(version as i64)],
+        )
+        .await
+    }
+
+    async fn latest_before(&self, connection_id: Uuid, at: DateTime<Utc>) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.query_snapshot(
+            "SELECT data FROM schema_snapshots WHERE connection_id = $1 AND captured_at <= $2
+             ORDER BY version DESC LIMIT 1",
+            &[&connection_id, &at],
+        )
+        .await
+    }
+
+    async fn get_by_id(&self, snapshot_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.query_snapshot("SELECT data FROM schema_snapshots WHERE id = $1", &[&snapshot_id])
+            .await
+    }
+
+    async fn list(&self, connection_id: Uuid) -> Result<Vec<SnapshotMetadata>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT id, connection_id, version, captured_at, checksum, table_count, fk_count, index_count
+                 FROM schema_snapshots WHERE connection_id = $1 ORDER BY version DESC",
+                &[&connection_id],
+            )
+            .await
+            .map_err(db_error)?;
+
+        Ok(rows
+            .iter()
+            .map(|r| SnapshotMetadata {
+                id: r.get(0),
+                connection_id: r.get(1),
+                version: r.get::<_, i64>(2) as u64,
+                captured_at: r.get(3),
+                checksum: r.get(4),
+                table_count: r.get::<_, i32>(5) as usize,
+                fk_count: r.get::<_, i32>(6) as usize,
+                index_count: r.get::<_, i32>(7) as usize,
+                label: None,
+                captured_by: None,
+            })
+            .collect())
+    }
+
+    async fn set_baseline(&self, connection_id: Uuid, snapshot_id: Uuid) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO schema_snapshot_heads (connection_id, latest_version, baseline_id) VALUES ($1, 0, $2)
+                 ON CONFLICT (connection_id) DO UPDATE SET baseline_id = EXCLUDED.baseline_id",
+                &[&connection_id, &snapshot_id],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn baseline(&self, connection_id: Uuid) -> Result<Option<SchemaSnapshot>, AppError> {
+        self.query_snapshot(
+            "SELECT s.data FROM schema_snapshots s
+             JOIN schema_snapshot_heads h ON h.baseline_id = s.id
+             WHERE h.connection_id = $1",
+            &[&connection_id],
+        )
+        .await
+    }
+
+    async fn remove_versions(&self, connection_id: Uuid, versions: &[u64]) -> Result<usize, AppError> {
+        let client = client(&self.pool).await?;
+        let versions: Vec<i64> = versions.iter().map(|v| *v as i64).collect();
+        let removed = client
+            .execute(
+                "DELETE FROM schema_snapshots WHERE connection_id = $1 AND version = ANY($2)",
+                &[&connection_id, &versions],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(removed as usize)
+    }
+}
+
+pub struct PostgresProposalBackend {
+    pool: Pool,
+}
+
+impl PostgresProposalBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ProposalBackend for PostgresProposalBackend {
+    async fn insert(&self, proposal: &SchemaProposal) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO change_proposals (id, connection_id, status, data, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &proposal.id,
+                    &proposal.connection_id,
+                    &variant_name(&proposal.status)?,
+                    &to_json(proposal)?,
+                    &proposal.created_at,
+                    &proposal.updated_at,
+                ],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<SchemaProposal>, AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .query_opt("SELECT data FROM change_proposals WHERE id = $1", &[&id])
+            .await
+            .map_err(db_error)?
+            .map(|r| from_json(r.get(0)))
+            .transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<SchemaProposal>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM change_proposals", &[])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+
+    async fn update<'a>(&self, id: Uuid, change: ProposalChange<'a>) -> Result<SchemaProposal, AppError> {
+        let mut client = client(&self.pool).await?;
+        let tx = client.transaction().await.map_err(db_error)?;
+
+        // The row lock holds off other writers until the change is committed
+        let row = tx
+            .query_opt("SELECT data FROM change_proposals WHERE id = $1 FOR UPDATE", &[&id])
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| AppError::NotFound(format!("Proposal {} not found", id)))?;
+        let mut proposal: SchemaProposal = from_json(row.get(0))?;
+        change(&mut proposal)?;
+
+        tx.execute(
+            "UPDATE change_proposals SET connection_id = $2, status = $3, data = $4, updated_at = $5
+             WHERE id = $1",
+            &[
+                &proposal.id,
+                &proposal.connection_id,
+                &variant_name(&proposal.status)?,
+                &to_json(&proposal)?,
+                &proposal.updated_at,
+            ],
+        )
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(proposal)
+    }
+}
+
+pub struct PostgresIdempotencyBackend {
+    pool: Pool,
+}
+
+impl PostgresIdempotencyBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyBackend for PostgresIdempotencyBackend {
+    async fn reserve(&self, record: IdempotencyRecord, now: DateTime<Utc>) -> Result<Option<IdempotencyRecord>, AppError> {
+        let client = client(&self.pool).await?;
+
+        // Inserts a new key or takes over an expired one; returns no row if the key is held
+        let claimed = client
+            .query_opt(
+                "INSERT INTO idempotency_keys (key, method, path, fingerprint, created_at, expires_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (key) DO UPDATE SET
+                     method = EXCLUDED.method, path = EXCLUDED.path, fingerprint = EXCLUDED.fingerprint,
+                     response_status = NULL, response_body = NULL,
+                     created_at = EXCLUDED.created_at, expires_at = EXCLUDED.expires_at
+                 WHERE idempotency_keys.expires_at <= $7
+                 RETURNING key",
+                &[
+                    &record.key,
+                    &record.method,
+                    &record.path,
+                    &record.fingerprint,
+                    &record.created_at,
+                    &record.expires_at,
+                    &now,
+                ],
+            )
+            .await
+            .map_err(db_error)?;
+        if claimed.is_some() {
+            return Ok(None);
+        }
+
+        let row = client
+            .query_opt(
+                "SELECT key, method, path, fingerprint, response_status, response_body, created_at, expires_at
+                 FROM idempotency_keys WHERE key = $1",
+                &[&record.key],
+            )
+            .await
+            .map_err(db_error)?;
+
+        Ok(row.map(|r| {
+            let status: Option<i32> = r.get(4);
+            let body: Option<String> = r.get(5);
+            IdempotencyRecord {
+                key: r.get(0),
+                method: r.get(1),
+                path: r.get(2),
+                fingerprint: r.get(3),
+                response: status.zip(body).map(|(status, body)| StoredResponse { status: status as u16, body }),
+                created_at: r.get(6),
+                expires_at: r.get(7),
+            }
+        }))
+    }
+
+    async fn complete(&self, key: &str, response: StoredResponse, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "UPDATE idempotency_keys SET response_status = $2, response_body = $3, expires_at = $4 WHERE key = $1",
+                &[&key, &(response.status as i32), &response.body, &expires_at],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute("DELETE FROM idempotency_keys WHERE key = $1", &[&key])
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let client = client(&self.pool).await?;
+        let removed = client
+            .execute("DELETE FROM idempotency_keys WHERE expires_at <= $1", &[&now])
+            .await
+            .map_err(db_error)?;
+        Ok(removed as usize)
+    }
+}
+
+pub struct PostgresExecutionJournalBackend {
+    pool: Pool,
+}
+
+impl PostgresExecutionJournalBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ExecutionJournalBackend for PostgresExecutionJournalBackend {
+    async fn put(&self, record: &ExecutionRecord) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO execution_journal (execution_id, proposal_id, state, data, updated_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (execution_id) DO UPDATE SET
+                     state = EXCLUDED.state, data = EXCLUDED.data, updated_at = EXCLUDED.updated_at",
+                &[
+                    &record.execution_id,
+                    &record.proposal_id,
+                    &variant_name(&record.state)?,
+                    &to_json(record)?,
+                    &record.updated_at,
+                ],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .query_opt("SELECT data FROM execution_journal WHERE execution_id = $1", &[&execution_id])
+            .await
+            .map_err(db_error)?
+            .map(|row| from_json(row.get(0)))
+            .transpose()
+    }
+
+    async fn list_by_state(&self, state: ExecutionState) -> Result<Vec<ExecutionRecord>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM execution_journal WHERE state = $1", &[&variant_name(&state)?])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+
+    async fn list_updated_before(&self, before: DateTime<Utc>) -> Result<Vec<ExecutionRecord>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM execution_journal WHERE updated_at < $1", &[&before])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+
+    async fn delete(&self, execution_ids: &[Uuid]) -> Result<usize, AppError> {
+        let client = client(&self.pool).await?;
+        let deleted = client
+            .execute("DELETE FROM execution_journal WHERE execution_id = ANY($1)", &[&execution_ids])
+            .await
+            .map_err(db_error)?;
+        Ok(deleted as usize)
+    }
+}
+
+pub struct PostgresExecutionPlanBackend {
+    pool: Pool,
+}
+
+impl PostgresExecutionPlanBackend {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlanBackend for PostgresExecutionPlanBackend {
+    async fn put(&self, plan: &ExecutionPlan) -> Result<(), AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO execution_plans (plan_id, proposal_id, status, data, updated_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (plan_id) DO UPDATE SET
+                     status = EXCLUDED.status, data = EXCLUDED.data, updated_at = EXCLUDED.updated_at",
+                &[&plan.id, &plan.proposal_id, &variant_name(&plan.status)?, &to_json(plan)?, &plan.updated_at],
+            )
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, plan_id: Uuid) -> Result<Option<ExecutionPlan>, AppError> {
+        let client = client(&self.pool).await?;
+        client
+            .query_opt("SELECT data FROM execution_plans WHERE plan_id = $1", &[&plan_id])
+            .await
+            .map_err(db_error)?
+            .map(|row| from_json(row.get(0)))
+            .transpose()
+    }
+
+    async fn list_for_proposal(&self, proposal_id: Uuid) -> Result<Vec<ExecutionPlan>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM execution_plans WHERE proposal_id = $1", &[&proposal_id])
+            .await
+            .map_err(db_error)?;
+        let mut plans: Vec<ExecutionPlan> = rows.into_iter().map(|r| from_json(r.get(0))).collect::<Result<_, _>>()?;
+        plans.sort_by_key(|p| p.created_at);
+        Ok(plans)
+    }
+
+    async fn list_by_status(&self, status: PlanStatus) -> Result<Vec<ExecutionPlan>, AppError> {
+        let client = client(&self.pool).await?;
+        let rows = client
+            .query("SELECT data FROM execution_plans WHERE status = $1", &[&variant_name(&status)?])
+            .await
+            .map_err(db_error)?;
+        rows.into_iter().map(|r| from_json(r.get(0))).collect()
+    }
+}
@@ -0,0 +1,70 @@
+//! Registering and cloning connections through the API
+
+use super::fixtures::SchemaFixture;
+use super::{Actor, TestApp};
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Connect and give the connection settings a clone should carry over
+async fn configured_connection(app: &TestApp) -> Uuid {
+    let id = app.connect().await;
+    let path = |setting: &str| format!("/api/connections/{}/{}", id, setting);
+    app.call(Actor::Admin, Method::PUT, &path("schema-scope"), Some(json!({ "schemas": ["public"] }))).await;
+    let ignore = json!({ "patterns": [{ "pattern": "public.audit_*", "reason": "Managed by the audit extension" }] });
+    app.call(Actor::Admin, Method::PUT, &path("diff-ignore"), Some(ignore)).await;
+    app.call(Actor::Admin, Method::PUT, &path("blast-radius-limits"), Some(json!({ "maxDepth": 2 }))).await;
+    id
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_clone_uses_only_the_credentials_entered() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    app.query_target("CREATE ROLE reviewer LOGIN PASSWORD 'reviewer-secret'").await;
+    let source = configured_connection(&app).await;
+    let path = format!("/api/connections/{}/clone", source);
+
+    // The source's password is not a fallback for a wrong one
+    let wrong = json!({ "user": "reviewer", "password": "postgres" });
+    let (status, response) = app.request(Actor::Admin, Method::POST, &path, Some(wrong)).await;
+    assert!(!status.is_success(), "response: {}", response);
+
+    let (status, _) = app.request(Actor::Admin, Method::POST, &path, Some(json!({ "password": "" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = json!({ "user": "reviewer", "password": "reviewer-secret" });
+    let cloned = app.call(Actor::Admin, Method::POST, &path, Some(body)).await;
+    assert_eq!(cloned["user"], "reviewer");
+    assert!(!cloned.to_string().contains("reviewer-secret"));
+
+    let cloned: Uuid = serde_json::from_value(cloned["id"].clone()).unwrap();
+    let cloned = app.state.connections.get_connection(cloned).await.unwrap();
+    let source = app.state.connections.get_connection(source).await.unwrap();
+    assert_eq!(cloned.params.password, "reviewer-secret");
+    assert_eq!(source.params.user, "postgres");
+    assert_eq!(source.params.password, "postgres");
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_clone_keeps_scope_ignore_rules_and_limits() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    let source = configured_connection(&app).await;
+
+    let body = json!({ "password": "postgres", "name": "target (staging)", "environment": "staging" });
+    let cloned = app.call(Actor::Admin, Method::POST, &format!("/api/connections/{}/clone", source), Some(body)).await;
+
+    assert_ne!(cloned["id"], json!(source));
+    assert_eq!(cloned["name"], "target (staging)");
+    assert_eq!(cloned["environment"], "staging");
+    assert_eq!(cloned["user"], "postgres");
+    assert_eq!(cloned["schemaScope"], json!(["public"]));
+    assert_eq!(cloned["diffIgnore"][0]["pattern"], "public.audit_*");
+    assert_eq!(cloned["blastRadiusLimits"], json!({ "maxDepth": 2 }));
+    assert!(cloned["lastIntrospectedAt"].is_null());
+
+    // Cloning leaves the active connection on the source
+    let active = app.state.connections.get_active_connection().await.unwrap();
+    assert_eq!(active.id, source);
+}
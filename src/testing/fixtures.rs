@@ -0,0 +1,268 @@
+//! Declarative fixtures
+//!
+//! `SchemaFixture` describes the tables a test database starts with and
+//! renders them as SQL; `ProposalFixture` describes a proposal and its
+//! changes. Both build on the API's own types, so a new `SchemaChange`
+//! variant can be exercised by passing it to `ProposalFixture::change`.
+
+use crate::pipeline::types::{ColumnDef, SchemaChange};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// A column of a fixture table or of an `add_column` change
+#[derive(Debug, Clone)]
+pub struct ColumnFixture {
+    name: String,
+    data_type: String,
+    nullable: bool,
+    default_value: Option<String>,
+    primary_key: bool,
+    references: Option<String>,
+}
+
+/// Nullable column `name` of type `data_type`
+pub fn column(name: &str, data_type: &str) -> ColumnFixture {
+    ColumnFixture {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        default_value: None,
+        primary_key: false,
+        references: None,
+    }
+}
+
+impl ColumnFixture {
+    pub fn not_null(mut self) -> Self {
+        self.nullable = false;
+        self
+    }
+
+    pub fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self.nullable = false;
+        self
+    }
+
+    pub fn default_value(mut self, expression: &str) -> Self {
+        self.default_value = Some(expression.to_string());
+        self
+    }
+
+    /// Foreign key to `table`'s `id`
+    pub fn references(mut self, table: &str) -> Self {
+        self.references = Some(table.to_string());
+        self
+    }
+
+    pub fn to_def(&self) -> ColumnDef {
+        ColumnDef {
+            name: self.name.clone(),
+            data_type: self.data_type.clone(),
+            nullable: self.nullable,
+            default_value: self.default_value.clone(),
+            is_primary_key: self.primary_key,
+            collation: None,
+        }
+    }
+
+    fn sql(&self) -> String {
+        let mut sql = format!("{} {}", self.name, self.data_type);
+        if self.primary_key {
+            sql.push_str(" PRIMARY KEY");
+        } else if !self.nullable {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default_value {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        if let Some(table) = &self.references {
+            sql.push_str(&format!(" REFERENCES {}(id)", table));
+        }
+        sql
+    }
+}
+
+/// A table with its columns and generated rows
+#[derive(Debug, Clone)]
+pub struct TableFixture {
+    name: String,
+    columns: Vec<ColumnFixture>,
+    rows: usize,
+}
+
+/// Table `name` in `public` with a `bigserial` primary key named `id`
+pub fn table(name: &str) -> TableFixture {
+    TableFixture {
+        name: name.to_string(),
+        columns: vec![column("id", "bigserial").primary_key()],
+        rows: 0,
+    }
+}
+
+impl TableFixture {
+    pub fn column(mut self, column: ColumnFixture) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Fill the table with `rows` rows of generated values
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    fn create_sql(&self) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|c| format!("    {}", c.sql())).collect();
+        format!("CREATE TABLE public.{} (\n{}\n);", self.name, columns.join(",\n"))
+    }
+
+    /// Rows reference the first `rows` ids of referenced tables, so seed
+    /// referenced tables with at least as many rows
+    fn insert_sql(&self) -> Option<String> {
+        if self.rows == 0 {
+            return None;
+        }
+        let (names, values): (Vec<&str>, Vec<String>) = self
+            .columns
+            .iter()
+            .filter(|c| !c.primary_key && c.default_value.is_none())
+            .map(|c| (c.name.as_str(), generated_value(c)))
+            .unzip();
+        Some(match names.is_empty() {
+            true => format!("INSERT INTO public.{} SELECT FROM generate_series(1, {});", self.name, self.rows),
+            false => format!(
+                "INSERT INTO public.{} ({}) SELECT {} FROM generate_series(1, {}) AS g;",
+                self.name,
+                names.join(", "),
+                values.join(", "),
+                self.rows
+            ),
+        })
+    }
+}
+
+fn generated_value(column: &ColumnFixture) -> String {
+    if column.references.is_some() {
+        return "g".to_string();
+    }
+    match column.data_type.to_lowercase().as_str() {
+        "int" | "integer" | "bigint" | "smallint" | "numeric" => "g".to_string(),
+        "boolean" | "bool" => "g % 2 = 0".to_string(),
+        "timestamptz" | "timestamp" | "date" => "now() - g * interval '1 day'".to_string(),
+        _ => format!("'{}-' || g", column.name),
+    }
+}
+
+/// The user schema a test database is seeded with
+#[derive(Debug, Clone, Default)]
+pub struct SchemaFixture {
+    tables: Vec<TableFixture>,
+}
+
+impl SchemaFixture {
+    /// Customers and their orders, with a few rows each
+    pub fn synthetic() -> Self {
+        Self::default()
+            .table(
+                table("customers")
+                    .column(column("email", "text").not_null())
+                    .column(column("created_at", "timestamptz").not_null().default_value("now()"))
+                    .rows(20),
+            )
+            .table(
+                table("orders")
+                    .column(column("customer_id", "bigint").not_null().references("customers"))
+                    .column(column("total_cents", "integer").not_null())
+                    .rows(20),
+            )
+    }
+
+    /// Tables are created in the order they are added
+    pub fn table(mut self, table: TableFixture) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let create = self.tables.iter().map(TableFixture::create_sql);
+        let insert = self.tables.iter().filter_map(TableFixture::insert_sql);
+        create.chain(insert).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+/// A proposal to create through the API
+#[derive(Debug, Clone)]
+pub struct ProposalFixture {
+    title: String,
+    description: String,
+    changes: Vec<SchemaChange>,
+}
+
+impl ProposalFixture {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            description: String::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Any change, as the API accepts it
+    pub fn change(mut self, change: SchemaChange) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn add_column(self, table_name: &str, column: ColumnFixture) -> Self {
+        self.change(SchemaChange::AddColumn {
+            table_name: table_name.to_string(),
+            column: column.to_def(),
+        })
+    }
+
+    pub fn create_table(self, table: &TableFixture) -> Self {
+        self.change(SchemaChange::CreateTable {
+            table_name: table.name.clone(),
+            columns: table.columns.iter().map(ColumnFixture::to_def).collect(),
+            partition_by: None,
+        })
+    }
+
+    /// Body of `POST /api/proposals`
+    pub fn request_body(&self, connection_id: Uuid) -> Value {
+        json!({
+            "connectionId": connection_id,
+            "title": self.title,
+            "description": self.description,
+            "changes": self.changes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_fixture_renders_tables_before_rows() {
+        let sql = SchemaFixture::synthetic().sql();
+        let orders = sql.find("CREATE TABLE public.orders").unwrap();
+        assert!(sql.find("CREATE TABLE public.customers").unwrap() < orders);
+        assert!(sql.contains("    customer_id bigint NOT NULL REFERENCES customers(id)"));
+        assert!(sql.find("INSERT INTO public.customers (email) SELECT 'email-' || g").unwrap() > orders);
+
+        let body = ProposalFixture::new("Add phone")
+            .add_column("customers", column("phone", "text"))
+            .create_table(&table("notes").column(column("body", "text").not_null()))
+            .request_body(Uuid::nil());
+        assert_eq!(body["changes"][0]["type"], "add_column");
+        assert_eq!(body["changes"][0]["column"]["nullable"], true);
+        assert_eq!(body["changes"][1]["columns"][0]["isPrimaryKey"], true);
+    }
+}
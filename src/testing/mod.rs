@@ -0,0 +1,182 @@
+//! Integration test harness
+//!
+//! `TestApp::start` runs a throwaway PostgreSQL in Docker, migrates
+//! SchemaFlow's own tables into its `postgres` database, seeds a
+//! `SchemaFixture` into a separate `target` database and serves the full
+//! router in-process. Requests go through the real middleware stack,
+//! authenticated as an admin or a developer, so tests see exactly what a
+//! client would.
+//!
+//! Tests that start a `TestApp` need Docker and are marked
+//! `#[ignore = "needs Docker"]`; run them with `cargo test -- --ignored`.
+
+pub mod fixtures;
+mod connection_flow;
+mod pipeline_flow;
+mod snapshot_flow;
+
+use crate::auth::{create_tokens, Role, DEV_JWT_SECRET};
+use crate::config::{Settings, StorageBackend};
+use crate::db;
+use crate::notifications::Notifier;
+use crate::routes::create_router;
+use crate::state::{AppState, SharedState};
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use deadpool_postgres::{Config, Pool, Runtime};
+use fixtures::{ProposalFixture, SchemaFixture};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::ContainerAsync;
+use tokio_postgres::NoTls;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Who a request is made as
+#[derive(Debug, Clone, Copy)]
+pub enum Actor {
+    Admin,
+    Developer,
+}
+
+impl Actor {
+    fn token(self) -> String {
+        let (id, email, role) = match self {
+            Actor::Admin => ("1", "admin@schemaflow.test", Role::Admin),
+            Actor::Developer => ("2", "dev@schemaflow.test", Role::Developer),
+        };
+        create_tokens(id, email, role).unwrap().access_token
+    }
+}
+
+/// The API running against an ephemeral database
+pub struct TestApp {
+    router: Router,
+    /// State behind the router, for setting up what no route exposes
+    pub state: SharedState,
+    /// Connection string of the seeded user database
+    pub target_url: String,
+    _container: ContainerAsync<Postgres>,
+}
+
+impl TestApp {
+    pub async fn start(schema: &SchemaFixture) -> Self {
+        let container = Postgres::default().start().await.expect("Docker is required for integration tests");
+        let host = container.get_host().await.unwrap();
+        let port = container.get_host_port_ipv4(5432).await.unwrap();
+        let url = |database: &str| format!("postgres://postgres:postgres@{}:{}/{}", host, port, database);
+
+        let pool = pool(&url("postgres"));
+        db::migrations::run(&pool).await.unwrap();
+
+        let client = pool.get().await.unwrap();
+        client.batch_execute("CREATE DATABASE target").await.unwrap();
+        let (target, connection) = tokio_postgres::connect(&url("target"), NoTls).await.unwrap();
+        tokio::spawn(connection);
+        target.batch_execute(&schema.sql()).await.unwrap();
+
+        let settings = Settings::for_tests();
+        let notifier = Notifier::new(None, pool.clone(), None);
+        let state = Arc::new(AppState::new(
+            pool,
+            DEV_JWT_SECRET.to_string(),
+            settings.proposal_policy.clone(),
+            StorageBackend::Memory,
+            None,
+            notifier,
+            settings.quotas.clone(),
+        ));
+        let router = create_router(state.clone(), &settings);
+
+        Self {
+            router,
+            state,
+            target_url: url("target"),
+            _container: container,
+        }
+    }
+
+    /// Send a request and return its status and JSON body (`Null` if empty)
+    pub async fn request(&self, actor: Actor, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", actor.token()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    /// Conditional GET; returns the status and the response's ETag
+    pub async fn get_conditional(&self, actor: Actor, path: &str, if_none_match: Option<&str>) -> (StatusCode, Option<String>) {
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", actor.token()));
+        if let Some(etag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        let response = self.router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let etag = response.headers().get(header::ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+        (response.status(), etag)
+    }
+
+    /// Like `request`, failing the test unless the response is a success;
+    /// returns the body's `data`
+    pub async fn call(&self, actor: Actor, method: Method, path: &str, body: Option<Value>) -> Value {
+        let (status, body) = self.request(actor, method.clone(), path, body).await;
+        assert!(status.is_success(), "{} {} returned {}: {}", method, path, status, body);
+        body["data"].clone()
+    }
+
+    /// Connect to the seeded database; returns the connection id
+    pub async fn connect(&self) -> Uuid {
+        let body = serde_json::json!({ "connectionString": self.target_url, "name": "target" });
+        let data = self.call(Actor::Admin, Method::POST, "/api/connections", Some(body)).await;
+        serde_json::from_value(data["connection"]["id"].clone()).unwrap()
+    }
+
+    /// Create the proposal as a developer; returns its id
+    pub async fn propose(&self, connection_id: Uuid, proposal: &ProposalFixture) -> Uuid {
+        let body = proposal.request_body(connection_id);
+        let data = self.call(Actor::Developer, Method::POST, "/api/proposals", Some(body)).await;
+        serde_json::from_value(data["proposal"]["id"].clone()).unwrap()
+    }
+
+    /// Poll the proposal, with its verification, until `done` accepts it,
+    /// for up to ten seconds
+    pub async fn wait_for_proposal(&self, id: Uuid, done: impl Fn(&Value) -> bool) -> Value {
+        let path = format!("/api/proposals/{}?include=verification", id);
+        for _ in 0..50 {
+            let proposal = self.call(Actor::Admin, Method::GET, &path, None).await;
+            if done(&proposal) {
+                return proposal;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        panic!("Proposal {} did not reach the expected state", id);
+    }
+
+    /// Run a query against the seeded database
+    pub async fn query_target(&self, sql: &str) -> Vec<tokio_postgres::Row> {
+        let (client, connection) = tokio_postgres::connect(&self.target_url, NoTls).await.unwrap();
+        tokio::spawn(connection);
+        client.query(sql, &[]).await.unwrap()
+    }
+}
+
+fn pool(url: &str) -> Pool {
+    let cfg = Config {
+        url: Some(url.to_string()),
+        ..Default::default()
+    };
+    cfg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap()
+}
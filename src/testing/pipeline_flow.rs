@@ -0,0 +1,80 @@
+//! The governed change pipeline, end to end against a real database
+
+use super::fixtures::{column, ProposalFixture, SchemaFixture};
+use super::{Actor, TestApp};
+use axum::http::Method;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_add_column_is_proposed_approved_executed_and_verified() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    let connection_id = app.connect().await;
+
+    let proposal = ProposalFixture::new("Add customer phone")
+        .description("Store a contact number for support")
+        .add_column("customers", column("phone", "text"));
+    let id = app.propose(connection_id, &proposal).await;
+    let path = |action: &str| format!("/api/proposals/{}/{}", id, action);
+
+    let analysis = app.call(Actor::Developer, Method::POST, &path("analyze"), None).await;
+    assert!(analysis.to_string().contains("customers"), "analysis: {}", analysis);
+    app.call(Actor::Developer, Method::POST, &path("submit"), None).await;
+
+    // A developer cannot approve their own change
+    let (status, _) = app.request(Actor::Developer, Method::POST, &path("approve"), Some(json!({}))).await;
+    assert!(status.is_client_error());
+    app.call(Actor::Admin, Method::POST, &path("approve"), Some(json!({}))).await;
+    app.call(Actor::Admin, Method::POST, &path("execute"), Some(json!({}))).await;
+
+    let proposal = app.wait_for_proposal(id, |p| !p["verification"].is_null()).await;
+    assert_eq!(proposal["verification"]["passed"], true, "proposal: {}", proposal);
+
+    let rows = app
+        .query_target("SELECT count(*) FROM public.customers WHERE phone IS NULL")
+        .await;
+    assert_eq!(rows[0].get::<_, i64>(0), 20);
+}
+
+/// Analyze, submit, approve and execute a proposal; returns it once the run ended
+async fn execute(app: &TestApp, connection_id: Uuid, proposal: &ProposalFixture) -> Value {
+    let id = app.propose(connection_id, proposal).await;
+    let path = |action: &str| format!("/api/proposals/{}/{}", id, action);
+    app.call(Actor::Developer, Method::POST, &path("analyze"), None).await;
+    app.call(Actor::Developer, Method::POST, &path("submit"), None).await;
+    app.call(Actor::Admin, Method::POST, &path("approve"), Some(json!({}))).await;
+    app.request(Actor::Admin, Method::POST, &path("execute"), Some(json!({}))).await;
+    app.wait_for_proposal(id, |p| !p["lastExecution"].is_null()).await
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_only_successful_executions_move_the_baseline() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    let connection_id = app.connect().await;
+    let baseline = || async { app.state.snapshots.get_baseline(connection_id).await.unwrap().map(|s| s.id) };
+    let before = baseline().await;
+
+    // Existing rows have no value for a NOT NULL column without a default
+    let failing = ProposalFixture::new("Require a tier").add_column("customers", column("tier", "text").not_null());
+    let failed = execute(&app, connection_id, &failing).await;
+    assert_eq!(failed["status"], "failed", "proposal: {}", failed);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(baseline().await, before);
+    let failed = app.wait_for_proposal(serde_json::from_value(failed["id"].clone()).unwrap(), |_| true).await;
+    assert!(failed["resultSnapshotId"].is_null());
+
+    let passing = ProposalFixture::new("Add a nickname").add_column("customers", column("nickname", "text"));
+    let executed = execute(&app, connection_id, &passing).await;
+    let executed = app.wait_for_proposal(
+        serde_json::from_value(executed["id"].clone()).unwrap(),
+        |p| !p["resultSnapshotId"].is_null(),
+    ).await;
+    let result_snapshot: Uuid = serde_json::from_value(executed["resultSnapshotId"].clone()).unwrap();
+    assert_eq!(baseline().await, Some(result_snapshot));
+    let snapshot = app.state.snapshots.get_by_id(result_snapshot).await.unwrap().unwrap();
+    let customers = snapshot.tables.iter().find(|t| t.name == "customers").unwrap();
+    assert!(customers.columns.iter().any(|c| c.name == "nickname"));
+}
@@ -0,0 +1,50 @@
+//! Snapshot archiving, restore and cached schema reads through the API
+
+use super::fixtures::SchemaFixture;
+use super::{Actor, TestApp};
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_only_admins_restore_archived_snapshots() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    let connection_id = app.connect().await;
+    let path = format!("/api/connections/{}/snapshots/restore", connection_id);
+    let body = json!({ "key": format!("snapshots/{}/v0000000001.json", connection_id) });
+
+    let (status, _) = app.request(Actor::Developer, Method::POST, &path, Some(body.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Admins get past the check; this app has no archive to restore from
+    let (status, response) = app.request(Actor::Admin, Method::POST, &path, Some(body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "response: {}", response);
+}
+
+#[tokio::test]
+#[ignore = "needs Docker"]
+async fn test_schema_etag_changes_with_scope_and_archiving() {
+    let app = TestApp::start(&SchemaFixture::synthetic()).await;
+    let connection_id = app.connect().await;
+
+    let (status, etag) = app.get_conditional(Actor::Developer, "/api/schema", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let etag = etag.unwrap();
+    let (status, _) = app.get_conditional(Actor::Developer, "/api/schema", Some(&etag)).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    // Same snapshot, narrower body
+    let scope_path = format!("/api/connections/{}/schema-scope", connection_id);
+    app.call(Actor::Admin, Method::PUT, &scope_path, Some(json!({ "schemas": ["public"] }))).await;
+    let (status, scoped_etag) = app.get_conditional(Actor::Developer, "/api/schema", Some(&etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    let scoped_etag = scoped_etag.unwrap();
+    assert_ne!(scoped_etag, etag);
+
+    // Removing every version drops the cached snapshot with them
+    let removed: Vec<u64> = app.state.snapshots.list(connection_id).await.unwrap().iter().map(|s| s.version).collect();
+    app.state.snapshots.remove_versions(connection_id, &removed).await.unwrap();
+    let (status, fresh_etag) = app.get_conditional(Actor::Developer, "/api/schema", Some(&scoped_etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(fresh_etag.unwrap(), scoped_etag);
+}